@@ -90,6 +90,50 @@ const DEFAULTS = {
     taxYearStartMonth: 4,
     taxYearStartDay: 6,
   },
+  cgt: {
+    annualExemptAmount: 3000,
+  },
+  pensionAllowance: {
+    annualAllowance: 60000,
+    moneyPurchaseAnnualAllowance: 10000,
+    basicRateRelief: 20,
+    lumpSumAllowance: 268275,
+  },
+  incomeTax: {
+    personalAllowance: 12570,
+    basicRate: 20,
+    basicRateBand: 37700,
+    higherRate: 40,
+    additionalRateThreshold: 125140,
+    additionalRate: 45,
+  },
+  cashBuffer: {
+    forecastMonths: 12,
+    method: "priority",
+  },
+  retirementProjection: {
+    years: 40,
+    simulations: 1000,
+    historyPeriod: "3y",
+    defaultReturn: 5,
+    defaultVolatility: 12,
+  },
+  rebalancing: {
+    tolerancePercent: 5,
+    minimumTrade: 250,
+  },
+  riskMetrics: {
+    riskFreeRate: 4,
+    riskFreeSeries: "",
+  },
+  inheritanceTax: {
+    nilRateBand: 325000,
+    residenceNilRateBand: 175000,
+    taperThreshold: 2000000,
+    rate: 40,
+    annualExemption: 3000,
+    smallGiftLimit: 250,
+  },
   fetchBatch: {
     batchSize: 8,
     cooldownSeconds: 120,
@@ -229,6 +273,91 @@ export function loadConfig() {
     taxYearStartDay: typeof rawIsaAllowance.taxYearStartDay === "number" && Number.isInteger(rawIsaAllowance.taxYearStartDay) && rawIsaAllowance.taxYearStartDay >= 1 && rawIsaAllowance.taxYearStartDay <= 28 ? rawIsaAllowance.taxYearStartDay : DEFAULTS.isaAllowance.taxYearStartDay,
   };
 
+  // cgt — annual exempt amount applied to tax years without a published historic figure
+  const rawCgt = rawConfig.cgt || {};
+  config.cgt = {
+    annualExemptAmount: typeof rawCgt.annualExemptAmount === "number" && rawCgt.annualExemptAmount >= 0 ? rawCgt.annualExemptAmount : DEFAULTS.cgt.annualExemptAmount,
+  };
+
+  // pensionAllowance — annual allowance, money purchase annual allowance, basic rate relief (percent) and lump sum allowance
+  const rawPension = rawConfig.pensionAllowance || {};
+  config.pensionAllowance = {
+    annualAllowance: typeof rawPension.annualAllowance === "number" && rawPension.annualAllowance > 0 ? rawPension.annualAllowance : DEFAULTS.pensionAllowance.annualAllowance,
+
+    moneyPurchaseAnnualAllowance: typeof rawPension.moneyPurchaseAnnualAllowance === "number" && rawPension.moneyPurchaseAnnualAllowance > 0 ? rawPension.moneyPurchaseAnnualAllowance : DEFAULTS.pensionAllowance.moneyPurchaseAnnualAllowance,
+
+    basicRateRelief: typeof rawPension.basicRateRelief === "number" && rawPension.basicRateRelief >= 0 && rawPension.basicRateRelief < 100 ? rawPension.basicRateRelief : DEFAULTS.pensionAllowance.basicRateRelief,
+
+    lumpSumAllowance: typeof rawPension.lumpSumAllowance === "number" && rawPension.lumpSumAllowance > 0 ? rawPension.lumpSumAllowance : DEFAULTS.pensionAllowance.lumpSumAllowance,
+  };
+
+  // incomeTax — personal allowance, bands (taxable income) and rates (percent) used for PAYE on drawdowns
+  const rawIncomeTax = rawConfig.incomeTax || {};
+  const isRate = function (value) {
+    return typeof value === "number" && value >= 0 && value < 100;
+  };
+  config.incomeTax = {
+    personalAllowance: typeof rawIncomeTax.personalAllowance === "number" && rawIncomeTax.personalAllowance >= 0 ? rawIncomeTax.personalAllowance : DEFAULTS.incomeTax.personalAllowance,
+
+    basicRate: isRate(rawIncomeTax.basicRate) ? rawIncomeTax.basicRate : DEFAULTS.incomeTax.basicRate,
+
+    basicRateBand: typeof rawIncomeTax.basicRateBand === "number" && rawIncomeTax.basicRateBand > 0 ? rawIncomeTax.basicRateBand : DEFAULTS.incomeTax.basicRateBand,
+
+    higherRate: isRate(rawIncomeTax.higherRate) ? rawIncomeTax.higherRate : DEFAULTS.incomeTax.higherRate,
+
+    additionalRateThreshold: typeof rawIncomeTax.additionalRateThreshold === "number" && rawIncomeTax.additionalRateThreshold > 0 ? rawIncomeTax.additionalRateThreshold : DEFAULTS.incomeTax.additionalRateThreshold,
+
+    additionalRate: isRate(rawIncomeTax.additionalRate) ? rawIncomeTax.additionalRate : DEFAULTS.incomeTax.additionalRate,
+  };
+  if (config.incomeTax.additionalRateThreshold <= config.incomeTax.basicRateBand) {
+    config.incomeTax.basicRateBand = DEFAULTS.incomeTax.basicRateBand;
+    config.incomeTax.additionalRateThreshold = DEFAULTS.incomeTax.additionalRateThreshold;
+  }
+
+  // cashBuffer — default horizon (months) and how sales are spread when planning cash for drawdowns
+  const rawCashBuffer = rawConfig.cashBuffer || {};
+  config.cashBuffer = {
+    forecastMonths: typeof rawCashBuffer.forecastMonths === "number" && Number.isInteger(rawCashBuffer.forecastMonths) && rawCashBuffer.forecastMonths >= 1 && rawCashBuffer.forecastMonths <= 60 ? rawCashBuffer.forecastMonths : DEFAULTS.cashBuffer.forecastMonths,
+    method: ["priority", "pro_rata"].includes(rawCashBuffer.method) ? rawCashBuffer.method : DEFAULTS.cashBuffer.method,
+  };
+
+  // retirementProjection — horizon, simulation count, price history used for return and volatility,
+  // and the annual return/volatility (percent) assumed where holdings lack history
+  const rawProjection = rawConfig.retirementProjection || {};
+  config.retirementProjection = {
+    years: Number.isInteger(rawProjection.years) && rawProjection.years >= 1 && rawProjection.years <= 60 ? rawProjection.years : DEFAULTS.retirementProjection.years,
+    simulations: Number.isInteger(rawProjection.simulations) && rawProjection.simulations >= 100 && rawProjection.simulations <= 10000 ? rawProjection.simulations : DEFAULTS.retirementProjection.simulations,
+    historyPeriod: ["1y", "2y", "3y"].includes(rawProjection.historyPeriod) ? rawProjection.historyPeriod : DEFAULTS.retirementProjection.historyPeriod,
+    defaultReturn: typeof rawProjection.defaultReturn === "number" && rawProjection.defaultReturn >= -10 && rawProjection.defaultReturn <= 20 ? rawProjection.defaultReturn : DEFAULTS.retirementProjection.defaultReturn,
+    defaultVolatility: typeof rawProjection.defaultVolatility === "number" && rawProjection.defaultVolatility >= 0 && rawProjection.defaultVolatility <= 50 ? rawProjection.defaultVolatility : DEFAULTS.retirementProjection.defaultVolatility,
+  };
+
+  // rebalancing — tolerance band (percentage points either side of a target) and smallest trade proposed
+  const rawRebalancing = rawConfig.rebalancing || {};
+  config.rebalancing = {
+    tolerancePercent: typeof rawRebalancing.tolerancePercent === "number" && rawRebalancing.tolerancePercent >= 0 && rawRebalancing.tolerancePercent <= 50 ? rawRebalancing.tolerancePercent : DEFAULTS.rebalancing.tolerancePercent,
+    minimumTrade: typeof rawRebalancing.minimumTrade === "number" && rawRebalancing.minimumTrade >= 0 ? rawRebalancing.minimumTrade : DEFAULTS.rebalancing.minimumTrade,
+  };
+
+  // riskMetrics — annual risk-free rate (percent) for Sharpe and Sortino, optionally from a named index series
+  const rawRisk = rawConfig.riskMetrics || {};
+  config.riskMetrics = {
+    riskFreeRate: typeof rawRisk.riskFreeRate === "number" && rawRisk.riskFreeRate >= 0 && rawRisk.riskFreeRate <= 20 ? rawRisk.riskFreeRate : DEFAULTS.riskMetrics.riskFreeRate,
+    riskFreeSeries: typeof rawRisk.riskFreeSeries === "string" ? rawRisk.riskFreeSeries.trim() : DEFAULTS.riskMetrics.riskFreeSeries,
+  };
+
+  // inheritanceTax — nil-rate band, residence nil-rate band and its taper threshold (pounds), the rate (percent),
+  // and the gift exemptions: the annual exemption and the small gift limit per recipient (pounds)
+  const rawIht = rawConfig.inheritanceTax || {};
+  config.inheritanceTax = {
+    nilRateBand: typeof rawIht.nilRateBand === "number" && rawIht.nilRateBand >= 0 ? rawIht.nilRateBand : DEFAULTS.inheritanceTax.nilRateBand,
+    residenceNilRateBand: typeof rawIht.residenceNilRateBand === "number" && rawIht.residenceNilRateBand >= 0 ? rawIht.residenceNilRateBand : DEFAULTS.inheritanceTax.residenceNilRateBand,
+    taperThreshold: typeof rawIht.taperThreshold === "number" && rawIht.taperThreshold > 0 ? rawIht.taperThreshold : DEFAULTS.inheritanceTax.taperThreshold,
+    rate: isRate(rawIht.rate) ? rawIht.rate : DEFAULTS.inheritanceTax.rate,
+    annualExemption: typeof rawIht.annualExemption === "number" && rawIht.annualExemption >= 0 ? rawIht.annualExemption : DEFAULTS.inheritanceTax.annualExemption,
+    smallGiftLimit: typeof rawIht.smallGiftLimit === "number" && rawIht.smallGiftLimit >= 0 ? rawIht.smallGiftLimit : DEFAULTS.inheritanceTax.smallGiftLimit,
+  };
+
   // fetchDelayProfile — must be "interactive" or "cron"
   // Also accepts legacy key name "scrapeDelayProfile" for backwards compatibility
   const validProfiles = ["interactive", "cron"];
@@ -335,6 +464,79 @@ export function getIsaAllowanceConfig() {
   return config.isaAllowance;
 }
 
+/**
+ * @description Get the capital gains tax configuration with defaults applied.
+ * @returns {{ annualExemptAmount: number }}
+ */
+export function getCgtConfig() {
+  const config = loadConfig();
+  return config.cgt;
+}
+
+/**
+ * @description Get the pension allowance configuration with defaults applied.
+ * @returns {{ annualAllowance: number, moneyPurchaseAnnualAllowance: number, basicRateRelief: number, lumpSumAllowance: number }}
+ */
+export function getPensionAllowanceConfig() {
+  const config = loadConfig();
+  return config.pensionAllowance;
+}
+
+/**
+ * @description Get the income tax configuration used for PAYE on pension drawdowns, with defaults applied.
+ * @returns {{ personalAllowance: number, basicRate: number, basicRateBand: number, higherRate: number, additionalRateThreshold: number, additionalRate: number }}
+ */
+export function getIncomeTaxConfig() {
+  const config = loadConfig();
+  return config.incomeTax;
+}
+
+/**
+ * @description Get the cash buffer planner defaults with defaults applied.
+ * @returns {{ forecastMonths: number, method: string }}
+ */
+export function getCashBufferConfig() {
+  const config = loadConfig();
+  return config.cashBuffer;
+}
+
+/**
+ * @description Get the retirement projection settings with defaults applied.
+ * @returns {{ years: number, simulations: number, historyPeriod: string, defaultReturn: number, defaultVolatility: number }}
+ */
+export function getRetirementProjectionConfig() {
+  const config = loadConfig();
+  return config.retirementProjection;
+}
+
+/**
+ * @description Get the rebalancing planner settings with defaults applied.
+ * @returns {{ tolerancePercent: number, minimumTrade: number }}
+ */
+export function getRebalancingConfig() {
+  const config = loadConfig();
+  return config.rebalancing;
+}
+
+/**
+ * @description Get the risk metrics settings with defaults applied.
+ * @returns {{ riskFreeRate: number, riskFreeSeries: string }}
+ */
+export function getRiskMetricsConfig() {
+  const config = loadConfig();
+  return config.riskMetrics;
+}
+
+/**
+ * @description Get the inheritance tax settings with defaults applied.
+ * @returns {{ nilRateBand: number, residenceNilRateBand: number, taperThreshold: number, rate: number,
+ *   annualExemption: number, smallGiftLimit: number }}
+ */
+export function getInheritanceTaxConfig() {
+  const config = loadConfig();
+  return config.inheritanceTax;
+}
+
 /**
  * @description Get whether cron-initiated fetches should also update the test database.
  * @returns {boolean} True if the test database should be updated after live fetch
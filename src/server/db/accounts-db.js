@@ -1,8 +1,11 @@
 import { getDatabase } from "./connection.js";
 import { CURRENCY_SCALE_FACTOR } from "../../shared/server-constants.js";
+import { deleteAccountValuations, invalidateAccountValuations } from "./portfolio-valuations-db.js";
+import { recalculateBalanceAfter } from "./cash-transactions-db.js";
 
 /**
- * @description Get all accounts for a user, ordered by account type.
+ * @description Get all accounts for a user, ordered by account type. A user
+ * may have several accounts of the same type; these are ordered by ID.
  * @param {number} userId - The user ID
  * @returns {Object[]} Array of account objects with unscaled cash values
  */
@@ -10,11 +13,11 @@ export function getAccountsByUserId(userId) {
   const db = getDatabase();
   const rows = db
     .query(
-      `SELECT a.id, a.user_id, a.account_type, a.account_ref, a.cash_balance, a.warn_cash,
+      `SELECT a.id, a.user_id, a.account_type, a.account_ref, a.provider, a.cash_balance, a.warn_cash,
               (SELECT COUNT(*) FROM holdings h WHERE h.account_id = a.id) AS holdings_count
        FROM accounts a
        WHERE a.user_id = ?
-       ORDER BY a.account_type`,
+       ORDER BY a.account_type, a.id`,
     )
     .all(userId);
 
@@ -48,7 +51,7 @@ export function getAccountById(id) {
   const db = getDatabase();
   const row = db
     .query(
-      `SELECT id, user_id, account_type, account_ref, cash_balance, warn_cash
+      `SELECT id, user_id, account_type, account_ref, provider, cash_balance, warn_cash
        FROM accounts
        WHERE id = ?`,
     )
@@ -59,11 +62,13 @@ export function getAccountById(id) {
 }
 
 /**
- * @description Create a new account.
+ * @description Create a new account. When no provider is given, the user's
+ * default provider is used.
  * @param {Object} data - The account data
  * @param {number} data.user_id - FK to users table
  * @param {string} data.account_type - One of 'trading', 'isa', 'sipp'
  * @param {string} data.account_ref - Account reference (max 15 chars)
+ * @param {string} [data.provider] - Provider code (e.g. 'ii', 'hl')
  * @param {number} data.cash_balance - Cash balance as a decimal (e.g. 23765.50)
  * @param {number} data.warn_cash - Warning threshold as a decimal (e.g. 25000.00)
  * @returns {Object} The created account with its new ID
@@ -75,9 +80,9 @@ export function createAccount(data) {
   db.exec("BEGIN");
   try {
     const result = db.run(
-      `INSERT INTO accounts (user_id, account_type, account_ref, cash_balance, warn_cash)
-       VALUES (?, ?, ?, ?, ?)`,
-      [data.user_id, data.account_type, data.account_ref, scaledCash, scaleCash(data.warn_cash || 0)],
+      `INSERT INTO accounts (user_id, account_type, account_ref, provider, cash_balance, warn_cash)
+       VALUES (?, ?, ?, COALESCE(?, (SELECT NULLIF(provider, '-') FROM users WHERE id = ?)), ?, ?)`,
+      [data.user_id, data.account_type, data.account_ref, data.provider || null, data.user_id, scaledCash, scaleCash(data.warn_cash || 0)],
     );
 
     // Auto-create opening balance deposit if cash balance is non-zero
@@ -99,43 +104,74 @@ export function createAccount(data) {
 }
 
 /**
- * @description Update an existing account.
+ * @description Update an existing account. The provider is left unchanged
+ * when not supplied. When the cash balance changes, the running balances of
+ * its cash transactions are recalculated to end at the new balance, and its
+ * valuation snapshots from the first cash transaction are invalidated.
  * @param {number} id - The account ID to update
  * @param {Object} data - The updated account data
  * @param {string} data.account_ref - Account reference (max 15 chars)
+ * @param {string} [data.provider] - Provider code (e.g. 'ii', 'hl')
  * @param {number} data.cash_balance - Cash balance as a decimal
  * @param {number} data.warn_cash - Warning threshold as a decimal
  * @returns {Object|null} The updated account, or null if not found
  */
 export function updateAccount(id, data) {
   const db = getDatabase();
-  const result = db.run(
-    `UPDATE accounts SET account_ref = ?, cash_balance = ?, warn_cash = ?
-     WHERE id = ?`,
-    [data.account_ref, scaleCash(data.cash_balance || 0), scaleCash(data.warn_cash || 0), id],
-  );
-
-  if (result.changes === 0) {
+  const current = db.query("SELECT cash_balance FROM accounts WHERE id = ?").get(id);
+  if (!current) {
     return null;
   }
 
+  const scaledCash = scaleCash(data.cash_balance || 0);
+
+  db.exec("BEGIN");
+  try {
+    db.run(
+      `UPDATE accounts SET account_ref = ?, provider = COALESCE(?, provider), cash_balance = ?, warn_cash = ?
+       WHERE id = ?`,
+      [data.account_ref, data.provider || null, scaledCash, scaleCash(data.warn_cash || 0), id],
+    );
+
+    if (scaledCash !== current.cash_balance) {
+      recalculateBalanceAfter(id);
+      const first = db.query("SELECT MIN(transaction_date) AS first_date FROM cash_transactions WHERE account_id = ?").get(id);
+      if (first.first_date) {
+        invalidateAccountValuations(id, first.first_date);
+      }
+    }
+
+    db.exec("COMMIT");
+  } catch (err) {
+    try { db.exec("ROLLBACK"); } catch (_) { /* already rolled back */ }
+    throw err;
+  }
+
   return getAccountById(id);
 }
 
 /**
  * @description Delete an account by ID. Also deletes all associated child records
- * (holding movements, holdings, cash transactions, drawdown schedules).
+ * (holding movements, holdings, cash transactions, drawdown schedules, crystallisations).
  * @param {number} id - The account ID to delete
  * @returns {boolean} True if the account was deleted, false if not found
  */
 export function deleteAccount(id) {
   const db = getDatabase();
   // Delete in dependency order (no ON DELETE CASCADE in schema)
-  // cash_transactions references holding_movements via holding_movement_id FK, so must go first
+  // sipp_crystallisations references cash_transactions, and cash_transactions references
+  // holding_movements via holding_movement_id FK, so these must go first
+  db.run("DELETE FROM sipp_crystallisations WHERE account_id = ?", [id]);
+  // A gift paid by one of the account's withdrawals stays in the gifts register, just no longer linked
+  db.run("UPDATE gifts SET cash_transaction_id = NULL WHERE cash_transaction_id IN (SELECT id FROM cash_transactions WHERE account_id = ?)", [id]);
   db.run("DELETE FROM cash_transactions WHERE account_id = ?", [id]);
+  // The other side of an ISA transfer now came from (or went to) an ISA not held here
+  db.run("UPDATE cash_transactions SET transfer_account_id = NULL WHERE transfer_account_id = ?", [id]);
   db.run("DELETE FROM holding_movements WHERE holding_id IN (SELECT id FROM holdings WHERE account_id = ?)", [id]);
   db.run("DELETE FROM holdings WHERE account_id = ?", [id]);
   db.run("DELETE FROM drawdown_schedules WHERE account_id = ?", [id]);
+  db.run("DELETE FROM allocation_targets WHERE account_id = ?", [id]);
+  deleteAccountValuations(id);
   const result = db.run("DELETE FROM accounts WHERE id = ?", [id]);
   return result.changes > 0;
 }
@@ -169,6 +205,7 @@ function unscaleAccountRow(row) {
     user_id: row.user_id,
     account_type: row.account_type,
     account_ref: row.account_ref,
+    provider: row.provider || null,
     cash_balance: unscaleCash(row.cash_balance),
     warn_cash: unscaleCash(row.warn_cash),
     cash_balance_scaled: row.cash_balance,
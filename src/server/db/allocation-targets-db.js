@@ -0,0 +1,73 @@
+import { getDatabase } from "./connection.js";
+import { CURRENCY_SCALE_FACTOR } from "../../shared/server-constants.js";
+
+/**
+ * @description Get the allocation targets set for a person or one of their
+ * accounts, largest target first. A person's targets (accountId null) cover
+ * all of their accounts together and are separate from any account's targets.
+ * @param {number} userId - The user ID
+ * @param {number|null} accountId - The account ID, or null for the person's targets
+ * @returns {Object[]} Targets with target_percent as a decimal and a display label
+ */
+export function getAllocationTargets(userId, accountId) {
+  const db = getDatabase();
+  const rows = db
+    .query(
+      `SELECT t.id, t.user_id, t.account_id, t.investment_id, t.allocation_tag, t.target_percent,
+              i.description AS investment_description
+       FROM allocation_targets t
+       LEFT JOIN investments i ON t.investment_id = i.id
+       WHERE t.user_id = ? AND t.account_id IS ?
+       ORDER BY t.target_percent DESC, t.id`,
+    )
+    .all(userId, accountId || null);
+
+  return rows.map(function (row) {
+    return {
+      id: row.id,
+      user_id: row.user_id,
+      account_id: row.account_id,
+      investment_id: row.investment_id,
+      allocation_tag: row.allocation_tag,
+      target_percent: row.target_percent / CURRENCY_SCALE_FACTOR,
+      label: row.investment_id ? row.investment_description : row.allocation_tag,
+    };
+  });
+}
+
+/**
+ * @description Replace the allocation targets for a person or one of their
+ * accounts. The existing set is removed and the new one written in a single
+ * transaction; an empty array clears the targets.
+ * @param {number} userId - The user ID
+ * @param {number|null} accountId - The account ID, or null for the person's targets
+ * @param {Array<{investment_id?: number, allocation_tag?: string, target_percent: number}>} targets - The new targets
+ * @returns {Object[]} The saved targets, as returned by getAllocationTargets
+ */
+export function setAllocationTargets(userId, accountId, targets) {
+  const db = getDatabase();
+  const insert = db.prepare(
+    `INSERT INTO allocation_targets (user_id, account_id, investment_id, allocation_tag, target_percent)
+     VALUES (?, ?, ?, ?, ?)`,
+  );
+
+  db.exec("BEGIN");
+  try {
+    db.run("DELETE FROM allocation_targets WHERE user_id = ? AND account_id IS ?", [userId, accountId || null]);
+    for (const target of targets) {
+      insert.run(
+        userId,
+        accountId || null,
+        target.investment_id ? Number(target.investment_id) : null,
+        target.investment_id ? null : String(target.allocation_tag).trim(),
+        Math.round(Number(target.target_percent) * CURRENCY_SCALE_FACTOR),
+      );
+    }
+    db.exec("COMMIT");
+  } catch (err) {
+    db.exec("ROLLBACK");
+    throw err;
+  }
+
+  return getAllocationTargets(userId, accountId);
+}
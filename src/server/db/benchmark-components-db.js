@@ -0,0 +1,82 @@
+import { getDatabase } from "./connection.js";
+import { CURRENCY_SCALE_FACTOR } from "../../shared/server-constants.js";
+
+/**
+ * @description Get the components of a composite benchmark, largest weight first.
+ * @param {number} benchmarkId - The composite benchmark ID
+ * @returns {Object[]} Components with weight_percent as a decimal and the component's description
+ */
+export function getBenchmarkComponents(benchmarkId) {
+  const db = getDatabase();
+  const rows = db
+    .query(
+      `SELECT bc.id, bc.benchmark_id, bc.component_benchmark_id, bc.weight_percent,
+              b.description AS component_description
+       FROM benchmark_components bc
+       JOIN benchmarks b ON bc.component_benchmark_id = b.id
+       WHERE bc.benchmark_id = ?
+       ORDER BY bc.weight_percent DESC, b.description`,
+    )
+    .all(benchmarkId);
+
+  return rows.map(function (row) {
+    return {
+      id: row.id,
+      benchmark_id: row.benchmark_id,
+      component_benchmark_id: row.component_benchmark_id,
+      description: row.component_description,
+      weight_percent: row.weight_percent / CURRENCY_SCALE_FACTOR,
+    };
+  });
+}
+
+/**
+ * @description Replace the components of a composite benchmark. The existing
+ * set is removed and the new one written in a single transaction.
+ * @param {number} benchmarkId - The composite benchmark ID
+ * @param {Array<{component_benchmark_id: number, weight_percent: number}>} components - The new components
+ * @returns {Object[]} The saved components, as returned by getBenchmarkComponents
+ */
+export function setBenchmarkComponents(benchmarkId, components) {
+  const db = getDatabase();
+  const insert = db.prepare(
+    `INSERT INTO benchmark_components (benchmark_id, component_benchmark_id, weight_percent)
+     VALUES (?, ?, ?)`,
+  );
+
+  db.exec("BEGIN");
+  try {
+    db.run("DELETE FROM benchmark_components WHERE benchmark_id = ?", [benchmarkId]);
+    for (const component of components) {
+      insert.run(benchmarkId, Number(component.component_benchmark_id), Math.round(Number(component.weight_percent) * CURRENCY_SCALE_FACTOR));
+    }
+    db.exec("COMMIT");
+  } catch (err) {
+    db.exec("ROLLBACK");
+    throw err;
+  }
+
+  return getBenchmarkComponents(benchmarkId);
+}
+
+/**
+ * @description Get the descriptions of the composite benchmarks that use a
+ * benchmark as one of their components.
+ * @param {number} componentBenchmarkId - The component benchmark ID
+ * @returns {string[]} Composite benchmark descriptions, in name order
+ */
+export function getCompositesUsingBenchmark(componentBenchmarkId) {
+  const db = getDatabase();
+  return db
+    .query(
+      `SELECT b.description
+       FROM benchmark_components bc
+       JOIN benchmarks b ON bc.benchmark_id = b.id
+       WHERE bc.component_benchmark_id = ?
+       ORDER BY b.description`,
+    )
+    .all(componentBenchmarkId)
+    .map(function (row) {
+      return row.description;
+    });
+}
@@ -1,6 +1,203 @@
 import { getDatabase } from "./connection.js";
+import { getBenchmarkComponents } from "./benchmark-components-db.js";
+import { getRatesInRange } from "./currency-rates-db.js";
+import { convertPricesToGBP } from "../services/price-utils.js";
 import { CURRENCY_SCALE_FACTOR } from "../../shared/server-constants.js";
 
+/**
+ * @description Value a composite benchmark's derived series starts from, on the
+ * first date every component has a value.
+ * @type {number}
+ */
+const COMPOSITE_BASE_VALUE = 1000;
+
+/**
+ * @description Get the rebalance frequency of a composite benchmark.
+ * @param {number} benchmarkId - The benchmark ID
+ * @returns {string|null} "monthly" or "quarterly" for a composite benchmark, null otherwise
+ */
+function getCompositeFrequency(benchmarkId) {
+  const db = getDatabase();
+  const row = db.query("SELECT benchmark_type, rebalance_frequency FROM benchmarks WHERE id = ?").get(benchmarkId);
+  if (!row || row.benchmark_type !== "composite") return null;
+  return row.rebalance_frequency || "monthly";
+}
+
+/**
+ * @description Work out the rebalancing period a date falls in.
+ * @param {string} date - ISO-8601 date (YYYY-MM-DD)
+ * @param {string} frequency - "monthly" or "quarterly"
+ * @returns {string} Period key such as "2026-03" or "2026-Q1"
+ */
+function rebalancePeriod(date, frequency) {
+  if (frequency === "quarterly") {
+    return date.slice(0, 4) + "-Q" + Math.ceil(parseInt(date.slice(5, 7), 10) / 3);
+  }
+  return date.slice(0, 7);
+}
+
+/**
+ * @description Derive the value series of a weighted blend of benchmarks. The
+ * series starts at 1000 on the first date every component has a value and runs
+ * over every date any component has a value, carrying each component's last
+ * value forward. The holdings are reset to the target weights on the first
+ * date of each new month or quarter, so between rebalances the weights drift
+ * with the components' performance.
+ * @param {Array<{component_benchmark_id: number, weight_percent: number}>} components - Components and weights
+ * @param {string} frequency - "monthly" or "quarterly"
+ * @param {Object<number, Array<{benchmark_date: string, value: number}>>} seriesByComponent -
+ *   Each component's values, date ascending, keyed by component benchmark ID
+ * @returns {Array<{benchmark_date: string, value: number}>} Derived values, date ascending
+ */
+export function deriveCompositeValues(components, frequency, seriesByComponent) {
+  if (components.length === 0) return [];
+
+  let totalWeight = 0;
+  let start = "";
+  for (const component of components) {
+    const series = seriesByComponent[component.component_benchmark_id] || [];
+    if (series.length === 0) return [];
+    if (series[0].benchmark_date > start) start = series[0].benchmark_date;
+    totalWeight += component.weight_percent;
+  }
+  if (totalWeight <= 0) return [];
+
+  const dateSet = {};
+  for (const component of components) {
+    for (const point of seriesByComponent[component.component_benchmark_id]) {
+      if (point.benchmark_date >= start) dateSet[point.benchmark_date] = true;
+    }
+  }
+  const dates = Object.keys(dateSet).sort();
+
+  const pointers = components.map(function () {
+    return 0;
+  });
+  const latest = components.map(function () {
+    return 0;
+  });
+
+  /**
+   * @description Move each component's pointer to its last value on or before a date.
+   * @param {string} date - ISO-8601 date
+   */
+  function advanceTo(date) {
+    for (let i = 0; i < components.length; i++) {
+      const series = seriesByComponent[components[i].component_benchmark_id];
+      while (pointers[i] + 1 < series.length && series[pointers[i] + 1].benchmark_date <= date) {
+        pointers[i]++;
+      }
+      latest[i] = series[pointers[i]].value;
+    }
+  }
+
+  /**
+   * @description Set the units of each component so the blend is at its target weights.
+   * @param {number} total - The blend's value to allocate
+   * @returns {number[]} Units of each component
+   */
+  function unitsAtWeights(total) {
+    return components.map(function (component, i) {
+      return latest[i] > 0 ? (total * component.weight_percent) / totalWeight / latest[i] : 0;
+    });
+  }
+
+  const result = [];
+  let units = null;
+  let period = null;
+  for (const date of dates) {
+    advanceTo(date);
+
+    let value = COMPOSITE_BASE_VALUE;
+    if (units) {
+      value = 0;
+      for (let i = 0; i < components.length; i++) {
+        value += units[i] * latest[i];
+      }
+    }
+
+    const datePeriod = rebalancePeriod(date, frequency);
+    if (!units || datePeriod !== period) {
+      units = unitsAtWeights(value);
+      period = datePeriod;
+    }
+
+    result.push({ benchmark_date: date, value: Math.round(value * CURRENCY_SCALE_FACTOR) / CURRENCY_SCALE_FACTOR });
+  }
+
+  return result;
+}
+
+/**
+ * @description Load a component benchmark's stored values up to a date,
+ * converted to GBP at the exchange rate on or before each date so that
+ * components in different currencies can be weighted together.
+ * @param {number} componentId - The component benchmark ID
+ * @param {string} upToDate - ISO-8601 date; later values are not loaded
+ * @returns {Array<{benchmark_date: string, value: number}>} GBP values, date ascending
+ */
+function getComponentValuesInGBP(componentId, upToDate) {
+  const db = getDatabase();
+  const rows = db
+    .query(
+      `SELECT benchmark_date, value
+       FROM benchmark_data
+       WHERE benchmark_id = ? AND benchmark_date <= ?
+       ORDER BY benchmark_date ASC`,
+    )
+    .all(componentId, upToDate);
+
+  let prices = rows.map(function (row) {
+    return { price_date: row.benchmark_date, price: row.value / CURRENCY_SCALE_FACTOR };
+  });
+
+  const currency = db
+    .query(
+      `SELECT b.currencies_id, c.code
+       FROM benchmarks b
+       JOIN currencies c ON b.currencies_id = c.id
+       WHERE b.id = ?`,
+    )
+    .get(componentId);
+  if (currency && currency.code !== "GBP" && prices.length > 0) {
+    prices = convertPricesToGBP(prices, getRatesInRange(currency.currencies_id, "0000-01-01", upToDate));
+  }
+
+  return prices.map(function (price) {
+    return { benchmark_date: price.price_date, value: price.price };
+  });
+}
+
+/**
+ * @description Derive the value history of a composite benchmark from its
+ * components' stored values, in the same record shape as stored benchmark data.
+ * A date's derived value depends only on values up to that date, so the
+ * history can be cut short at the last date needed.
+ * @param {number} benchmarkId - The composite benchmark ID
+ * @param {string} frequency - "monthly" or "quarterly"
+ * @param {string} [upToDate="9999-12-31"] - ISO-8601 date to derive up to (inclusive)
+ * @returns {Object[]} Benchmark data records with unscaled values, date ascending
+ */
+function getCompositeBenchmarkData(benchmarkId, frequency, upToDate = "9999-12-31") {
+  const components = getBenchmarkComponents(benchmarkId);
+
+  const seriesByComponent = {};
+  for (const component of components) {
+    seriesByComponent[component.component_benchmark_id] = getComponentValuesInGBP(component.component_benchmark_id, upToDate);
+  }
+
+  return deriveCompositeValues(components, frequency, seriesByComponent).map(function (point) {
+    return {
+      id: null,
+      benchmark_id: benchmarkId,
+      benchmark_date: point.benchmark_date,
+      benchmark_time: "00:00:00",
+      value: point.value,
+      value_scaled: Math.round(point.value * CURRENCY_SCALE_FACTOR),
+    };
+  });
+}
+
 /**
  * @description Store or update a value for a benchmark on a given date.
  * Uses INSERT OR REPLACE to overwrite any existing value for the same
@@ -23,11 +220,18 @@ export function upsertBenchmarkData(benchmarkId, benchmarkDate, benchmarkTime, v
 }
 
 /**
- * @description Get the latest value for a benchmark.
+ * @description Get the latest value for a benchmark. Composite benchmarks return
+ * their latest derived value.
  * @param {number} benchmarkId - The benchmark ID
  * @returns {Object|null} Benchmark data record with unscaled value, or null if no data exists
  */
 export function getLatestBenchmarkData(benchmarkId) {
+  const frequency = getCompositeFrequency(benchmarkId);
+  if (frequency) {
+    const derived = getCompositeBenchmarkData(benchmarkId, frequency);
+    return derived.length > 0 ? derived[derived.length - 1] : null;
+  }
+
   const db = getDatabase();
   const row = db
     .query(
@@ -53,11 +257,17 @@ export function getLatestBenchmarkData(benchmarkId) {
 
 /**
  * @description Get all values for a benchmark, ordered by date descending.
+ * Composite benchmarks return their derived values.
  * @param {number} benchmarkId - The benchmark ID
  * @param {number} [limit=100] - Maximum number of records to return
  * @returns {Object[]} Array of benchmark data records with unscaled values
  */
 export function getBenchmarkDataHistory(benchmarkId, limit = 100, offset = 0) {
+  const frequency = getCompositeFrequency(benchmarkId);
+  if (frequency) {
+    return getCompositeBenchmarkData(benchmarkId, frequency).reverse().slice(offset, offset + limit);
+  }
+
   const db = getDatabase();
   const rows = db
     .query(
@@ -82,11 +292,53 @@ export function getBenchmarkDataHistory(benchmarkId, limit = 100, offset = 0) {
 }
 
 /**
- * @description Get total number of value records for a benchmark.
+ * @description Count a composite benchmark's derived values without deriving
+ * them: one for each date any component has a value, from the first date every
+ * component has one.
+ * @param {number} benchmarkId - The composite benchmark ID
+ * @returns {number} Number of derived values
+ */
+function countCompositeValues(benchmarkId) {
+  const db = getDatabase();
+  const components = getBenchmarkComponents(benchmarkId);
+  if (components.length === 0) return 0;
+
+  let totalWeight = 0;
+  let start = "";
+  for (const component of components) {
+    const row = db.query("SELECT MIN(benchmark_date) AS first_date FROM benchmark_data WHERE benchmark_id = ?").get(component.component_benchmark_id);
+    if (!row.first_date) return 0;
+    if (row.first_date > start) start = row.first_date;
+    totalWeight += component.weight_percent;
+  }
+  if (totalWeight <= 0) return 0;
+
+  const ids = components.map(function (component) {
+    return component.component_benchmark_id;
+  });
+  const placeholders = ids.map(() => "?").join(", ");
+  const row = db
+    .query(
+      `SELECT COUNT(DISTINCT benchmark_date) AS count
+       FROM benchmark_data
+       WHERE benchmark_id IN (${placeholders}) AND benchmark_date >= ?`,
+    )
+    .get(...ids, start);
+  return row.count;
+}
+
+/**
+ * @description Get total number of value records for a benchmark. For a
+ * composite benchmark this is the number of derived values.
  * @param {number} benchmarkId - The benchmark ID
  * @returns {number} Total count of benchmark data records
  */
 export function getBenchmarkDataCount(benchmarkId) {
+  const frequency = getCompositeFrequency(benchmarkId);
+  if (frequency) {
+    return countCompositeValues(benchmarkId);
+  }
+
   const db = getDatabase();
   const row = db.query("SELECT COUNT(*) AS count FROM benchmark_data WHERE benchmark_id = ?").get(benchmarkId);
   return row.count;
@@ -109,6 +361,13 @@ export function getTotalBenchmarkDataCount() {
  * @returns {Object|null} Benchmark data record or null if not found
  */
 export function getBenchmarkDataByDate(benchmarkId, benchmarkDate) {
+  const frequency = getCompositeFrequency(benchmarkId);
+  if (frequency) {
+    const derived = getCompositeBenchmarkData(benchmarkId, frequency, benchmarkDate);
+    const last = derived.length > 0 ? derived[derived.length - 1] : null;
+    return last && last.benchmark_date === benchmarkDate ? last : null;
+  }
+
   const db = getDatabase();
   const row = db
     .query(
@@ -132,13 +391,22 @@ export function getBenchmarkDataByDate(benchmarkId, benchmarkDate) {
 
 /**
  * @description Get all benchmark values within a date range, ordered by date
- * ascending. Used for chart data where we need chronological values.
+ * ascending. Used for chart data where we need chronological values. Composite
+ * benchmarks are derived from their first value up to the end of the range and
+ * then cut to it, so a date's value does not depend on the range asked for.
  * @param {number} benchmarkId - The benchmark ID
  * @param {string} fromDate - ISO-8601 start date (inclusive)
  * @param {string} toDate - ISO-8601 end date (inclusive)
  * @returns {Object[]} Array of benchmark data records with unscaled values, date ascending
  */
 export function getBenchmarkDataInRange(benchmarkId, fromDate, toDate) {
+  const frequency = getCompositeFrequency(benchmarkId);
+  if (frequency) {
+    return getCompositeBenchmarkData(benchmarkId, frequency, toDate).filter(function (row) {
+      return row.benchmark_date >= fromDate;
+    });
+  }
+
   const db = getDatabase();
   const rows = db
     .query(
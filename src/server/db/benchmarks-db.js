@@ -1,4 +1,5 @@
 import { getDatabase } from "./connection.js";
+import { getCompositesUsingBenchmark } from "./benchmark-components-db.js";
 
 /**
  * @description Get all benchmarks with their currency details, ordered by description.
@@ -15,9 +16,14 @@ export function getAllBenchmarks() {
         b.description,
         b.benchmark_url,
         b.selector,
+        b.rebalance_frequency,
         c.code AS currency_code,
         c.description AS currency_description,
-        (SELECT MIN(bd.benchmark_date) FROM benchmark_data bd WHERE bd.benchmark_id = b.id) AS oldest_value_date
+        CASE WHEN b.benchmark_type = 'composite'
+          THEN (SELECT MAX((SELECT MIN(bd.benchmark_date) FROM benchmark_data bd WHERE bd.benchmark_id = bc.component_benchmark_id))
+                FROM benchmark_components bc WHERE bc.benchmark_id = b.id)
+          ELSE (SELECT MIN(bd.benchmark_date) FROM benchmark_data bd WHERE bd.benchmark_id = b.id)
+        END AS oldest_value_date
       FROM benchmarks b
       JOIN currencies c ON b.currencies_id = c.id
       ORDER BY b.description`,
@@ -41,6 +47,7 @@ export function getBenchmarkById(id) {
         b.description,
         b.benchmark_url,
         b.selector,
+        b.rebalance_frequency,
         c.code AS currency_code,
         c.description AS currency_description
       FROM benchmarks b
@@ -71,23 +78,25 @@ export function getBenchmarkByDescription(description) {
  * @description Create a new benchmark.
  * @param {Object} data - The benchmark data
  * @param {number} data.currencies_id - FK to currencies table
- * @param {string} data.benchmark_type - Either 'index' or 'price'
+ * @param {string} data.benchmark_type - 'index', 'price' or 'composite'
  * @param {string} data.description - Benchmark description (max 60 chars)
  * @param {string|null} data.benchmark_url - URL for value scraping (max 255 chars)
  * @param {string|null} data.selector - CSS selector for value element (max 255 chars)
+ * @param {string|null} [data.rebalance_frequency] - 'monthly' or 'quarterly' for a composite benchmark
  * @returns {Object} The created benchmark with its new ID and joined fields
  */
 export function createBenchmark(data) {
   const db = getDatabase();
   const result = db.run(
-    `INSERT INTO benchmarks (currencies_id, benchmark_type, description, benchmark_url, selector)
-     VALUES (?, ?, ?, ?, ?)`,
+    `INSERT INTO benchmarks (currencies_id, benchmark_type, description, benchmark_url, selector, rebalance_frequency)
+     VALUES (?, ?, ?, ?, ?, ?)`,
     [
       data.currencies_id,
       data.benchmark_type,
       data.description,
       data.benchmark_url || null,
       data.selector || null,
+      data.benchmark_type === "composite" ? data.rebalance_frequency || "monthly" : null,
     ],
   );
 
@@ -105,7 +114,7 @@ export function updateBenchmark(id, data) {
   const result = db.run(
     `UPDATE benchmarks SET
        currencies_id = ?, benchmark_type = ?, description = ?,
-       benchmark_url = ?, selector = ?
+       benchmark_url = ?, selector = ?, rebalance_frequency = ?
      WHERE id = ?`,
     [
       data.currencies_id,
@@ -113,6 +122,7 @@ export function updateBenchmark(id, data) {
       data.description,
       data.benchmark_url || null,
       data.selector || null,
+      data.benchmark_type === "composite" ? data.rebalance_frequency || "monthly" : null,
       id,
     ],
   );
@@ -125,7 +135,9 @@ export function updateBenchmark(id, data) {
 }
 
 /**
- * @description Delete a benchmark by ID.
+ * @description Delete a benchmark by ID. A composite benchmark's components are
+ * removed with it. A benchmark that is a component of a composite cannot be
+ * deleted until it is taken out of the composite.
  * @param {number} id - The benchmark ID to delete
  * @returns {{deleted: boolean, reason?: string}} Result object indicating success or failure reason
  */
@@ -138,8 +150,12 @@ export function deleteBenchmark(id) {
     return { deleted: false, reason: "Benchmark not found" };
   }
 
-  // Future-proofing: check for usage in benchmark_data table when it exists
-  // For now, no referential integrity checks needed
+  const composites = getCompositesUsingBenchmark(id);
+  if (composites.length > 0) {
+    return { deleted: false, reason: "Benchmark is part of the composite " + composites.join(", ") };
+  }
+
+  db.run("DELETE FROM benchmark_components WHERE benchmark_id = ?", [id]);
 
   const result = db.run("DELETE FROM benchmarks WHERE id = ?", [id]);
   return { deleted: result.changes > 0 };
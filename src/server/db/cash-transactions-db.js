@@ -1,5 +1,6 @@
 import { getDatabase } from "./connection.js";
 import { CURRENCY_SCALE_FACTOR } from "../../shared/server-constants.js";
+import { invalidateAccountValuations } from "./portfolio-valuations-db.js";
 
 /**
  * @description Scale a cash amount for storage (multiply by CURRENCY_SCALE_FACTOR).
@@ -19,31 +20,74 @@ export function unscaleCashAmount(scaledValue) {
   return scaledValue / CURRENCY_SCALE_FACTOR;
 }
 
+/**
+ * @description Cash transaction types that record income paid into the account.
+ * @type {string[]}
+ */
+export const INCOME_TRANSACTION_TYPES = ["dividend", "interest"];
+
+/**
+ * @description Determine whether a stored cash transaction increased the
+ * account's cash balance. Deposits, sells, income (dividends and interest),
+ * pension tax relief and credit adjustments add to the balance; everything
+ * else subtracts.
+ * @param {Object} txn - Transaction row with transaction_type and notes
+ * @returns {boolean} True if the transaction added to the balance
+ */
+export function addsToCashBalance(txn) {
+  const isCreditAdj = txn.transaction_type === "adjustment" && txn.notes && txn.notes.startsWith("[Credit]");
+  return txn.transaction_type === "deposit" || txn.transaction_type === "sell" || txn.transaction_type === "tax_relief" || INCOME_TRANSACTION_TYPES.includes(txn.transaction_type) || isCreditAdj;
+}
+
 /**
  * @description Create a cash transaction and atomically update the account's
- * cash balance. Deposits increase the balance; withdrawals, drawdowns and
- * adjustments with negative amounts decrease it.
+ * cash balance. Deposits, income (dividends, interest) and pension tax relief
+ * increase the balance; withdrawals, drawdowns and adjustments with negative
+ * amounts decrease it.
  *
  * The insert and balance update are wrapped in a single database transaction
  * for atomicity — either both succeed or neither does.
  *
  * @param {Object} data - The transaction data
  * @param {number} data.account_id - FK to accounts table
- * @param {string} data.transaction_type - One of 'deposit', 'withdrawal', 'drawdown', 'adjustment'
+ * @param {string} data.transaction_type - One of 'deposit', 'withdrawal', 'drawdown', 'adjustment', 'dividend', 'interest', 'tax_relief'
  * @param {string} data.transaction_date - ISO-8601 date (YYYY-MM-DD)
  * @param {number} data.amount - Amount as a positive decimal (e.g. 1500.00)
  * @param {string} [data.notes] - Optional notes (max 255 chars)
+ * @param {number} [data.investment_id] - FK to the investment that paid a dividend or interest
+ * @param {string} [data.isa_transfer] - For a deposit or withdrawal that is an ISA transfer:
+ *   'current_year' or 'previous_years' subscriptions
+ * @param {string} [data.contribution_type] - For a deposit that is a pension contribution:
+ *   'personal' or 'employer'
+ * @param {number} [data.gross_amount] - For a pension contribution, the gross amount as a decimal
+ * @param {number} [data.tax_withheld] - For a drawdown, the PAYE deducted from the gross amount
+ * @param {string} [data.tax_code] - For a drawdown, the tax code or basis the deduction was worked out on
  * @returns {Object} The created transaction with its new ID and unscaled amount
  */
 export function createCashTransaction(data) {
   const db = getDatabase();
-  const scaledAmount = scaleCashAmount(data.amount);
 
-  // Determine the balance change direction based on transaction type
-  // Deposits add to balance; withdrawals, drawdowns and adjustments subtract
-  // Exception: adjustment with direction='credit' adds to balance (rare provider refunds)
-  const addsToBalance = data.transaction_type === "deposit" || (data.transaction_type === "adjustment" && data.direction === "credit");
-  const balanceChange = addsToBalance ? scaledAmount : -scaledAmount;
+  db.exec("BEGIN");
+  try {
+    const id = insertCashTransaction(data);
+    db.exec("COMMIT");
+    return getCashTransactionById(id);
+  } catch (err) {
+    db.exec("ROLLBACK");
+    throw err;
+  }
+}
+
+/**
+ * @description Insert a cash transaction and apply it to the account's cash
+ * balance. Must be called inside an open database transaction.
+ * @param {Object} data - The transaction data, as for createCashTransaction
+ * @param {number} [data.transfer_account_id] - The other ISA in a transfer, when held here
+ * @returns {number} The new transaction ID
+ */
+export function insertCashTransaction(data) {
+  const db = getDatabase();
+  const scaledAmount = scaleCashAmount(data.amount);
 
   // For credit adjustments, prefix notes with [Credit] so that running-balance
   // calculations and display logic can detect the direction without a schema change
@@ -52,21 +96,160 @@ export function createCashTransaction(data) {
     storedNotes = storedNotes ? "[Credit] " + storedNotes : "[Credit]";
   }
 
+  // Determine the balance change direction based on transaction type
+  // Deposits and income add to balance; withdrawals, drawdowns and adjustments subtract
+  // Exception: adjustment with direction='credit' adds to balance (rare provider refunds)
+  const addsToBalance = addsToCashBalance({ transaction_type: data.transaction_type, notes: storedNotes });
+  const balanceChange = addsToBalance ? scaledAmount : -scaledAmount;
+
+  // Only income rows are linked to an investment
+  const investmentId = INCOME_TRANSACTION_TYPES.includes(data.transaction_type) && data.investment_id ? data.investment_id : null;
+
+  // Only deposits and withdrawals can be ISA transfers
+  const isTransferType = data.transaction_type === "deposit" || data.transaction_type === "withdrawal";
+  const isaTransfer = isTransferType && data.isa_transfer ? data.isa_transfer : null;
+  const transferAccountId = isaTransfer && data.transfer_account_id ? data.transfer_account_id : null;
+
+  // Only deposits can be pension contributions
+  const contributionType = data.transaction_type === "deposit" && data.contribution_type ? data.contribution_type : null;
+  const grossAmount = contributionType ? scaleCashAmount(data.gross_amount !== undefined && data.gross_amount !== null ? data.gross_amount : data.amount) : null;
+
+  // Only drawdowns carry PAYE; the amount is always the gross debited from the account
+  const isDrawdown = data.transaction_type === "drawdown";
+  const taxWithheld = isDrawdown && data.tax_withheld !== undefined && data.tax_withheld !== null ? scaleCashAmount(data.tax_withheld) : null;
+  const taxCode = isDrawdown && data.tax_code ? data.tax_code : null;
+
+  const result = db.run(
+    `INSERT INTO cash_transactions (account_id, transaction_type, transaction_date, amount, notes, investment_id, isa_transfer, transfer_account_id, contribution_type, gross_amount, tax_withheld, tax_code)
+     VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)`,
+    [data.account_id, data.transaction_type, data.transaction_date, scaledAmount, storedNotes, investmentId, isaTransfer, transferAccountId, contributionType, grossAmount, taxWithheld, taxCode],
+  );
+
+  db.run(`UPDATE accounts SET cash_balance = cash_balance + ? WHERE id = ?`, [balanceChange, data.account_id]);
+
+  recalculateBalanceAfter(data.account_id);
+  invalidateAccountValuations(data.account_id, data.transaction_date);
+
+  return result.lastInsertRowid;
+}
+
+/**
+ * @description Record a transfer between ISAs. When both ISAs are held here a
+ * withdrawal from the source and a deposit into the destination are created
+ * together and linked to each other; when only one side is held here, just
+ * that side is recorded. Both sides are flagged with the kind of subscriptions
+ * moved, which decides whether the transfer counts towards the ISA allowance.
+ *
+ * @param {Object} data - The transfer data
+ * @param {number|null} data.from_account_id - Source ISA, or null if held elsewhere
+ * @param {number|null} data.to_account_id - Destination ISA, or null if held elsewhere
+ * @param {string} data.transaction_date - ISO-8601 date (YYYY-MM-DD)
+ * @param {number} data.amount - Amount as a positive decimal
+ * @param {string} data.isa_transfer - 'current_year' or 'previous_years'
+ * @param {string} [data.notes] - Optional notes (max 255 chars)
+ * @returns {{ withdrawal: Object|null, deposit: Object|null }} The created transactions
+ */
+export function createIsaTransfer(data) {
+  const db = getDatabase();
+
+  if (data.from_account_id) {
+    const source = db.query("SELECT cash_balance FROM accounts WHERE id = ?").get(data.from_account_id);
+    if (!source || source.cash_balance < scaleCashAmount(data.amount)) {
+      throw new Error("Insufficient cash balance");
+    }
+  }
+
   db.exec("BEGIN");
   try {
-    const result = db.run(
-      `INSERT INTO cash_transactions (account_id, transaction_type, transaction_date, amount, notes)
-       VALUES (?, ?, ?, ?, ?)`,
-      [data.account_id, data.transaction_type, data.transaction_date, scaledAmount, storedNotes],
-    );
+    let withdrawalId = null;
+    let depositId = null;
+
+    if (data.from_account_id) {
+      withdrawalId = insertCashTransaction({
+        account_id: data.from_account_id,
+        transaction_type: "withdrawal",
+        transaction_date: data.transaction_date,
+        amount: data.amount,
+        notes: data.notes,
+        isa_transfer: data.isa_transfer,
+        transfer_account_id: data.to_account_id,
+      });
+    }
+
+    if (data.to_account_id) {
+      depositId = insertCashTransaction({
+        account_id: data.to_account_id,
+        transaction_type: "deposit",
+        transaction_date: data.transaction_date,
+        amount: data.amount,
+        notes: data.notes,
+        isa_transfer: data.isa_transfer,
+        transfer_account_id: data.from_account_id,
+      });
+    }
+
+    db.exec("COMMIT");
+
+    return {
+      withdrawal: withdrawalId ? getCashTransactionById(withdrawalId) : null,
+      deposit: depositId ? getCashTransactionById(depositId) : null,
+    };
+  } catch (err) {
+    db.exec("ROLLBACK");
+    throw err;
+  }
+}
 
-    db.run(`UPDATE accounts SET cash_balance = cash_balance + ? WHERE id = ?`, [balanceChange, data.account_id]);
+/**
+ * @description Record a SIPP contribution. The amount actually paid in is
+ * deposited and flagged with the contribution type and its gross amount. For
+ * a personal contribution under relief at source, the gross amount includes
+ * the basic rate relief that the provider claims from HMRC; when relief_date
+ * is given, that top-up is recorded as a separate 'tax_relief' transaction.
+ *
+ * @param {Object} data - The contribution data
+ * @param {number} data.account_id - The SIPP account
+ * @param {string} data.contribution_type - 'personal' or 'employer'
+ * @param {string} data.transaction_date - ISO-8601 date (YYYY-MM-DD) the contribution was paid
+ * @param {number} data.amount - Amount paid in as a positive decimal (net of relief for personal contributions)
+ * @param {number} data.gross_amount - Gross contribution as a positive decimal
+ * @param {string} [data.relief_date] - ISO-8601 date the relief top-up was received
+ * @param {string} [data.notes] - Optional notes (max 255 chars)
+ * @returns {{ contribution: Object, tax_relief: Object|null }} The created transactions
+ */
+export function createPensionContribution(data) {
+  const db = getDatabase();
 
-    recalculateBalanceAfter(data.account_id);
+  db.exec("BEGIN");
+  try {
+    const contributionId = insertCashTransaction({
+      account_id: data.account_id,
+      transaction_type: "deposit",
+      transaction_date: data.transaction_date,
+      amount: data.amount,
+      notes: data.notes,
+      contribution_type: data.contribution_type,
+      gross_amount: data.gross_amount,
+    });
+
+    let reliefId = null;
+    const relief = Math.round((data.gross_amount - data.amount) * 100) / 100;
+    if (data.relief_date && relief > 0) {
+      reliefId = insertCashTransaction({
+        account_id: data.account_id,
+        transaction_type: "tax_relief",
+        transaction_date: data.relief_date,
+        amount: relief,
+        notes: data.notes,
+      });
+    }
 
     db.exec("COMMIT");
 
-    return getCashTransactionById(result.lastInsertRowid);
+    return {
+      contribution: getCashTransactionById(contributionId),
+      tax_relief: reliefId ? getCashTransactionById(reliefId) : null,
+    };
   } catch (err) {
     db.exec("ROLLBACK");
     throw err;
@@ -82,9 +265,13 @@ export function getCashTransactionById(id) {
   const db = getDatabase();
   const row = db
     .query(
-      `SELECT id, account_id, holding_movement_id, transaction_type, transaction_date, amount, notes, balance_after
-       FROM cash_transactions
-       WHERE id = ?`,
+      `SELECT ct.id, ct.account_id, ct.holding_movement_id, ct.transaction_type, ct.transaction_date, ct.amount, ct.notes, ct.balance_after,
+              ct.investment_id, i.description AS investment_description, ct.isa_transfer, ct.transfer_account_id,
+              ct.contribution_type, ct.gross_amount, ct.tax_withheld, ct.tax_code, g.id AS gift_id
+       FROM cash_transactions ct
+       LEFT JOIN investments i ON ct.investment_id = i.id
+       LEFT JOIN gifts g ON g.cash_transaction_id = ct.id
+       WHERE ct.id = ?`,
     )
     .get(id);
 
@@ -103,9 +290,13 @@ export function getCashTransactionsByAccountId(accountId, limit = 50) {
   const rows = db
     .query(
       `SELECT ct.id, ct.account_id, ct.holding_movement_id, ct.transaction_type, ct.transaction_date, ct.amount, ct.notes, ct.balance_after,
+              ct.investment_id, i.description AS investment_description, ct.isa_transfer, ct.transfer_account_id,
+              ct.contribution_type, ct.gross_amount, ct.tax_withheld, ct.tax_code, g.id AS gift_id,
               hm.quantity AS movement_quantity, hm.movement_value AS movement_total_consideration, hm.deductible_costs AS movement_deductible_costs, hm.revised_avg_cost AS movement_revised_avg_cost
        FROM cash_transactions ct
        LEFT JOIN holding_movements hm ON ct.holding_movement_id = hm.id
+       LEFT JOIN investments i ON ct.investment_id = i.id
+       LEFT JOIN gifts g ON g.cash_transaction_id = ct.id
        WHERE ct.account_id = ?
        ORDER BY ct.transaction_date DESC, ct.id DESC
        LIMIT ?`,
@@ -125,24 +316,11 @@ export function getCashTransactionsByAccountId(accountId, limit = 50) {
 export function deleteCashTransaction(id) {
   const db = getDatabase();
 
-  // Load the transaction first to determine the balance reversal
-  const row = db.query("SELECT id, account_id, transaction_type, amount, notes FROM cash_transactions WHERE id = ?").get(id);
-
-  if (!row) return false;
-
-  // Reverse the original balance change
-  // Deposits, sells, and credit adjustments add to balance; everything else subtracts
-  const isCreditAdjustment = row.transaction_type === "adjustment" && row.notes && row.notes.startsWith("[Credit]");
-  const addsToBalance = row.transaction_type === "deposit" || row.transaction_type === "sell" || isCreditAdjustment;
-  const balanceReversal = addsToBalance ? -row.amount : row.amount;
-
   db.exec("BEGIN");
   try {
-    db.run("DELETE FROM cash_transactions WHERE id = ?", [id]);
-    db.run("UPDATE accounts SET cash_balance = cash_balance + ? WHERE id = ?", [balanceReversal, row.account_id]);
-    recalculateBalanceAfter(row.account_id);
+    const deleted = removeCashTransaction(id);
     db.exec("COMMIT");
-    return true;
+    return deleted;
   } catch (err) {
     db.exec("ROLLBACK");
     throw err;
@@ -150,27 +328,265 @@ export function deleteCashTransaction(id) {
 }
 
 /**
- * @description Get the total deposits for an ISA account within a date range.
- * Used to calculate ISA allowance usage for a tax year.
+ * @description Delete a cash transaction and reverse its effect on the
+ * account's cash balance. Must be called inside an open database transaction.
+ * @param {number} id - The transaction ID to delete
+ * @returns {boolean} True if the transaction was deleted, false if not found
+ */
+export function removeCashTransaction(id) {
+  const db = getDatabase();
+
+  // Load the transaction first to determine the balance reversal
+  const row = db.query("SELECT id, account_id, transaction_type, transaction_date, amount, notes FROM cash_transactions WHERE id = ?").get(id);
+
+  if (!row) return false;
+
+  // Reverse the original balance change
+  // Deposits, sells, income and credit adjustments add to balance; everything else subtracts
+  const addsToBalance = addsToCashBalance(row);
+  const balanceReversal = addsToBalance ? -row.amount : row.amount;
+
+  // A crystallisation paid out by this withdrawal stays recorded, just no longer linked
+  db.run("UPDATE sipp_crystallisations SET cash_transaction_id = NULL WHERE cash_transaction_id = ?", [id]);
+  // Likewise a gift recorded from this withdrawal stays in the gifts register
+  db.run("UPDATE gifts SET cash_transaction_id = NULL WHERE cash_transaction_id = ?", [id]);
+  db.run("DELETE FROM cash_transactions WHERE id = ?", [id]);
+  db.run("UPDATE accounts SET cash_balance = cash_balance + ? WHERE id = ?", [balanceReversal, row.account_id]);
+  recalculateBalanceAfter(row.account_id);
+  invalidateAccountValuations(row.account_id, row.transaction_date);
+  return true;
+}
+
+/**
+ * @description SQL condition selecting the cash transactions that use ISA
+ * allowance: ordinary deposits, plus transfers in of this tax year's
+ * subscriptions from an ISA not held here. Transfers of earlier years'
+ * subscriptions, and transfers between two ISAs both held here, do not use
+ * allowance — the original deposit has already been counted.
+ * @type {string}
+ */
+const ISA_SUBSCRIPTION_CONDITION = `ct.transaction_type = 'deposit'
+         AND (ct.isa_transfer IS NULL OR (ct.isa_transfer = 'current_year' AND ct.transfer_account_id IS NULL))`;
+
+/**
+ * @description Get the total ISA subscriptions for an account within a date range.
+ * Used to calculate ISA allowance usage for a tax year. Counts deposits and
+ * transfers in of current-year subscriptions from ISAs held elsewhere.
  * @param {number} accountId - The account ID (should be an ISA account)
  * @param {string} taxYearStart - Start date (inclusive) in YYYY-MM-DD format
  * @param {string} taxYearEnd - End date (inclusive) in YYYY-MM-DD format
- * @returns {number} Total deposit amount as unscaled decimal
+ * @returns {number} Total subscription amount as unscaled decimal
  */
 export function getIsaDepositsForTaxYear(accountId, taxYearStart, taxYearEnd) {
   const db = getDatabase();
   const row = db
     .query(
-      `SELECT COALESCE(SUM(amount), 0) AS total
+      `SELECT COALESCE(SUM(ct.amount), 0) AS total
+       FROM cash_transactions ct
+       WHERE ct.account_id = ?
+         AND ` + ISA_SUBSCRIPTION_CONDITION + `
+         AND ct.transaction_date >= ?
+         AND ct.transaction_date <= ?`,
+    )
+    .get(accountId, taxYearStart, taxYearEnd);
+
+  return unscaleCashAmount(row.total);
+}
+
+/**
+ * @description Get the cash transactions that used ISA allowance across every
+ * ISA a user holds, oldest first. Optionally restricted to a date range.
+ * @param {number} userId - The user ID
+ * @param {string} [startDate] - Start date (inclusive) in YYYY-MM-DD format
+ * @param {string} [endDate] - End date (inclusive) in YYYY-MM-DD format
+ * @returns {Object[]} Transactions with unscaled amounts, each with is_transfer set
+ *   when it is a transfer in rather than a deposit
+ */
+export function getIsaSubscriptionsForUser(userId, startDate, endDate) {
+  const db = getDatabase();
+  const rows = db
+    .query(
+      `SELECT ct.id, ct.account_id, ct.holding_movement_id, ct.transaction_type, ct.transaction_date, ct.amount, ct.notes, ct.balance_after,
+              ct.investment_id, ct.isa_transfer, ct.transfer_account_id
+       FROM cash_transactions ct
+       JOIN accounts a ON ct.account_id = a.id
+       WHERE a.user_id = ?
+         AND a.account_type = 'isa'
+         AND ` + ISA_SUBSCRIPTION_CONDITION + `
+         AND ct.transaction_date >= ?
+         AND ct.transaction_date <= ?
+       ORDER BY ct.transaction_date, ct.id`,
+    )
+    .all(userId, startDate || "0000-01-01", endDate || "9999-12-31");
+
+  return rows.map(function (row) {
+    const tx = unscaleTransactionRow(row);
+    tx.is_transfer = row.isa_transfer !== null;
+    return tx;
+  });
+}
+
+/**
+ * @description Get the pension transactions across every SIPP a user holds,
+ * oldest first: contributions (deposits flagged with a contribution type),
+ * relief-at-source top-ups and drawdowns. Optionally restricted to a date range.
+ * @param {number} userId - The user ID
+ * @param {string} [startDate] - Start date (inclusive) in YYYY-MM-DD format
+ * @param {string} [endDate] - End date (inclusive) in YYYY-MM-DD format
+ * @returns {Object[]} Transactions with unscaled amounts
+ */
+export function getPensionTransactionsForUser(userId, startDate, endDate) {
+  const db = getDatabase();
+  const rows = db
+    .query(
+      `SELECT ct.id, ct.account_id, ct.holding_movement_id, ct.transaction_type, ct.transaction_date, ct.amount, ct.notes, ct.balance_after,
+              ct.investment_id, ct.contribution_type, ct.gross_amount, ct.tax_withheld, ct.tax_code
+       FROM cash_transactions ct
+       JOIN accounts a ON ct.account_id = a.id
+       WHERE a.user_id = ?
+         AND a.account_type = 'sipp'
+         AND (ct.contribution_type IS NOT NULL OR ct.transaction_type IN ('tax_relief', 'drawdown'))
+         AND ct.transaction_date >= ?
+         AND ct.transaction_date <= ?
+       ORDER BY ct.transaction_date, ct.id`,
+    )
+    .all(userId, startDate || "0000-01-01", endDate || "9999-12-31");
+
+  return rows.map(unscaleTransactionRow);
+}
+
+/**
+ * @description Get the drawdowns paid from an account within a date range,
+ * oldest first, with the PAYE withheld from each.
+ * @param {number} accountId - The SIPP account ID
+ * @param {string} startDate - Start date (inclusive) in YYYY-MM-DD format
+ * @param {string} endDate - End date (inclusive) in YYYY-MM-DD format
+ * @returns {Object[]} Drawdown transactions with unscaled amounts
+ */
+export function getDrawdownsBetween(accountId, startDate, endDate) {
+  const db = getDatabase();
+  const rows = db
+    .query(
+      `SELECT id, account_id, holding_movement_id, transaction_type, transaction_date, amount, notes, balance_after,
+              investment_id, tax_withheld, tax_code
        FROM cash_transactions
        WHERE account_id = ?
-         AND transaction_type = 'deposit'
+         AND transaction_type = 'drawdown'
          AND transaction_date >= ?
-         AND transaction_date <= ?`,
+         AND transaction_date <= ?
+       ORDER BY transaction_date, id`,
     )
-    .get(accountId, taxYearStart, taxYearEnd);
+    .all(accountId, startDate, endDate);
 
-  return unscaleCashAmount(row.total);
+  return rows.map(unscaleTransactionRow);
+}
+
+/**
+ * @description Get income transactions (dividends and interest) within a date
+ * range, joined with the paying investment. Pass null for accountId to include
+ * every account belonging to the given user IDs.
+ * @param {Object} filter - Filter options
+ * @param {number} [filter.accountId] - Restrict to a single account
+ * @param {number} [filter.investmentId] - Restrict to a single investment
+ * @param {string} [filter.startDate] - Start date (inclusive) in YYYY-MM-DD format
+ * @param {string} [filter.endDate] - End date (inclusive) in YYYY-MM-DD format
+ * @returns {Object[]} Income transactions with unscaled amounts, oldest first
+ */
+export function getIncomeTransactions(filter) {
+  const db = getDatabase();
+  const conditions = ["ct.transaction_type IN ('dividend', 'interest')"];
+  const params = [];
+
+  if (filter.accountId) {
+    conditions.push("ct.account_id = ?");
+    params.push(filter.accountId);
+  }
+  if (filter.investmentId) {
+    conditions.push("ct.investment_id = ?");
+    params.push(filter.investmentId);
+  }
+  if (filter.startDate) {
+    conditions.push("ct.transaction_date >= ?");
+    params.push(filter.startDate);
+  }
+  if (filter.endDate) {
+    conditions.push("ct.transaction_date <= ?");
+    params.push(filter.endDate);
+  }
+
+  const rows = db
+    .query(
+      `SELECT ct.id, ct.account_id, ct.holding_movement_id, ct.transaction_type, ct.transaction_date, ct.amount, ct.notes, ct.balance_after,
+              ct.investment_id, i.description AS investment_description, i.unit_type AS investment_unit_type
+       FROM cash_transactions ct
+       LEFT JOIN investments i ON ct.investment_id = i.id
+       WHERE ` + conditions.join(" AND ") + `
+       ORDER BY ct.transaction_date, ct.id`,
+    )
+    .all(...params);
+
+  return rows.map(function (row) {
+    const result = unscaleTransactionRow(row);
+    result.investment_unit_type = row.investment_unit_type || null;
+    return result;
+  });
+}
+
+/**
+ * @description Get external cash flows (deposits, withdrawals and drawdowns)
+ * for a set of accounts within a date range. These are the transactions that
+ * move money into or out of the portfolio, as opposed to buys, sells and
+ * income which move value around inside it.
+ * @param {number[]} accountIds - The account IDs to include
+ * @param {string} startDate - Start date (exclusive) in YYYY-MM-DD format
+ * @param {string} endDate - End date (inclusive) in YYYY-MM-DD format
+ * @returns {Object[]} Transactions with unscaled amounts, oldest first
+ */
+export function getExternalCashFlows(accountIds, startDate, endDate) {
+  if (accountIds.length === 0) return [];
+
+  const db = getDatabase();
+  const placeholders = accountIds.map(function () {
+    return "?";
+  }).join(", ");
+
+  const rows = db
+    .query(
+      `SELECT ct.id, ct.account_id, ct.holding_movement_id, ct.transaction_type, ct.transaction_date, ct.amount, ct.notes, ct.balance_after, ct.investment_id
+       FROM cash_transactions ct
+       WHERE ct.account_id IN (` + placeholders + `)
+         AND ct.transaction_type IN ('deposit', 'withdrawal', 'drawdown', 'tax_relief')
+         AND ct.transaction_date > ?
+         AND ct.transaction_date <= ?
+       ORDER BY ct.transaction_date, ct.id`,
+    )
+    .all(...accountIds, startDate, endDate);
+
+  return rows.map(unscaleTransactionRow);
+}
+
+/**
+ * @description Get every cash transaction for an account within a date range,
+ * oldest first. Used by the broker import to detect rows already recorded.
+ * @param {number} accountId - The account ID
+ * @param {string} startDate - Start date (inclusive) in YYYY-MM-DD format
+ * @param {string} endDate - End date (inclusive) in YYYY-MM-DD format
+ * @returns {Object[]} Transactions with unscaled amounts, oldest first
+ */
+export function getCashTransactionsBetween(accountId, startDate, endDate) {
+  const db = getDatabase();
+  const rows = db
+    .query(
+      `SELECT ct.id, ct.account_id, ct.holding_movement_id, ct.transaction_type, ct.transaction_date, ct.amount, ct.notes, ct.balance_after, ct.investment_id
+       FROM cash_transactions ct
+       WHERE ct.account_id = ?
+         AND ct.transaction_date >= ?
+         AND ct.transaction_date <= ?
+       ORDER BY ct.transaction_date, ct.id`,
+    )
+    .all(accountId, startDate, endDate);
+
+  return rows.map(unscaleTransactionRow);
 }
 
 /**
@@ -204,7 +620,7 @@ export function drawdownExistsForDate(accountId, transactionDate) {
  *
  * @param {number} accountId - The account ID to recalculate
  */
-function recalculateBalanceAfter(accountId) {
+export function recalculateBalanceAfter(accountId) {
   const db = getDatabase();
 
   // Get the current account balance
@@ -225,9 +641,7 @@ function recalculateBalanceAfter(accountId) {
     db.run("UPDATE cash_transactions SET balance_after = ? WHERE id = ?", [runningBalance, txn.id]);
 
     // Subtract this transaction's effect to get the balance before it
-    const isCreditAdj = txn.transaction_type === "adjustment" && txn.notes && txn.notes.startsWith("[Credit]");
-    const addsToBalance = txn.transaction_type === "deposit" || txn.transaction_type === "sell" || isCreditAdj;
-    if (addsToBalance) {
+    if (addsToCashBalance(txn)) {
       runningBalance -= txn.amount;
     } else {
       runningBalance += txn.amount;
@@ -272,8 +686,38 @@ function unscaleTransactionRow(row) {
     amount_scaled: row.amount,
     notes: row.notes,
     balance_after: row.balance_after != null ? unscaleCashAmount(row.balance_after) : null,
+    investment_id: row.investment_id || null,
   };
 
+  // Include the paying investment for income transactions
+  if (row.investment_description !== undefined && row.investment_description !== null) {
+    result.investment_description = row.investment_description;
+  }
+
+  // Include the transfer details for ISA transfers
+  if (row.isa_transfer !== undefined && row.isa_transfer !== null) {
+    result.isa_transfer = row.isa_transfer;
+    result.transfer_account_id = row.transfer_account_id || null;
+  }
+
+  // Include the contribution details for pension contributions
+  if (row.contribution_type !== undefined && row.contribution_type !== null) {
+    result.contribution_type = row.contribution_type;
+    result.gross_amount = unscaleCashAmount(row.gross_amount);
+  }
+
+  // Include the PAYE details for drawdowns taxed at source
+  if (row.tax_withheld !== undefined && row.tax_withheld !== null) {
+    result.tax_withheld = unscaleCashAmount(row.tax_withheld);
+    result.net_amount = unscaleCashAmount(row.amount - row.tax_withheld);
+    result.tax_code = row.tax_code || null;
+  }
+
+  // Include the gift in the gifts register a withdrawal was recorded as
+  if (row.transaction_type === "withdrawal" && row.gift_id !== undefined) {
+    result.gift_id = row.gift_id || null;
+  }
+
   // Include holding movement details when available (buy/sell transactions)
   if (row.movement_quantity !== undefined && row.movement_quantity !== null) {
     result.quantity = row.movement_quantity / CURRENCY_SCALE_FACTOR;
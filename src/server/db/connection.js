@@ -819,6 +819,581 @@ function runMigrations(database) {
       database.exec("PRAGMA foreign_keys = ON");
     }
   }
+
+  // Migration 29: Add unit_type column to investments (v0.1.10)
+  // Distinguishes income units (distributions paid as cash) from accumulation
+  // units (income reinvested into the price). NULL means not applicable.
+  const investmentCols29 = database.query("PRAGMA table_info(investments)").all();
+  const hasUnitType29 = investmentCols29.some(function (col) {
+    return col.name === "unit_type";
+  });
+
+  if (!hasUnitType29) {
+    database.exec("ALTER TABLE investments ADD COLUMN unit_type TEXT CHECK(unit_type IS NULL OR unit_type IN ('income', 'accumulation'))");
+  }
+
+  // Migration 30: Add 'dividend' and 'interest' income types and investment_id to cash_transactions (v0.1.10)
+  // SQLite cannot ALTER CHECK constraints — requires table rebuild. Column order differs
+  // between databases created from schema.sql and those built up by earlier migrations,
+  // so the copy names every column explicitly.
+  const ctTableInfo30 = database.query("SELECT sql FROM sqlite_master WHERE type='table' AND name='cash_transactions'").get();
+  if (ctTableInfo30 && ctTableInfo30.sql && !ctTableInfo30.sql.includes("'dividend'")) {
+    database.exec("PRAGMA foreign_keys = OFF");
+    database.exec("BEGIN TRANSACTION");
+    try {
+      database.exec(`
+        CREATE TABLE cash_transactions_new (
+          id INTEGER PRIMARY KEY AUTOINCREMENT,
+          account_id INTEGER NOT NULL,
+          holding_movement_id INTEGER,
+          transaction_type TEXT NOT NULL CHECK(transaction_type IN ('deposit', 'withdrawal', 'drawdown', 'adjustment', 'buy', 'sell', 'dividend', 'interest')),
+          transaction_date TEXT NOT NULL,
+          amount INTEGER NOT NULL,
+          notes TEXT CHECK(notes IS NULL OR length(notes) <= 255),
+          balance_after INTEGER,
+          investment_id INTEGER,
+          FOREIGN KEY (account_id) REFERENCES accounts(id),
+          FOREIGN KEY (holding_movement_id) REFERENCES holding_movements(id),
+          FOREIGN KEY (investment_id) REFERENCES investments(id)
+        )
+      `);
+      database.exec(`
+        INSERT INTO cash_transactions_new (id, account_id, holding_movement_id, transaction_type, transaction_date, amount, notes, balance_after)
+        SELECT id, account_id, holding_movement_id, transaction_type, transaction_date, amount, notes, balance_after FROM cash_transactions
+      `);
+      database.exec("DROP TABLE cash_transactions");
+      database.exec("ALTER TABLE cash_transactions_new RENAME TO cash_transactions");
+      database.exec("CREATE INDEX IF NOT EXISTS idx_cash_transactions_account ON cash_transactions(account_id, transaction_date DESC)");
+      database.exec("CREATE INDEX IF NOT EXISTS idx_cash_transactions_investment ON cash_transactions(investment_id)");
+      database.exec("COMMIT");
+    } catch (err) {
+      database.exec("ROLLBACK");
+      throw err;
+    } finally {
+      database.exec("PRAGMA foreign_keys = ON");
+    }
+  }
+
+  // Migration 31: Add portfolio_valuations snapshot table (v0.1.10)
+  // Materialised per-date valuations so charts and historic summaries do not
+  // re-price every holding on every sample date. Rows are invalidated from a
+  // date onwards whenever prices, rates, cash or holdings change on or before it.
+  const valuationsTable = database.query(
+    "SELECT name FROM sqlite_master WHERE type='table' AND name='portfolio_valuations'"
+  ).get();
+
+  if (!valuationsTable) {
+    database.exec(`
+      CREATE TABLE IF NOT EXISTS portfolio_valuations (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        valuation_date TEXT NOT NULL,
+        account_id INTEGER NOT NULL,
+        investment_id INTEGER,
+        holding_id INTEGER,
+        quantity INTEGER NOT NULL DEFAULT 0,
+        average_cost INTEGER NOT NULL DEFAULT 0,
+        price INTEGER NOT NULL DEFAULT 0,
+        price_date TEXT,
+        rate INTEGER,
+        rate_date TEXT,
+        value_local INTEGER,
+        value_gbp INTEGER,
+        FOREIGN KEY (account_id) REFERENCES accounts(id),
+        FOREIGN KEY (investment_id) REFERENCES investments(id)
+      )
+    `);
+    database.exec(
+      "CREATE UNIQUE INDEX IF NOT EXISTS idx_portfolio_valuations_key ON portfolio_valuations(valuation_date, account_id, IFNULL(investment_id, 0))"
+    );
+    database.exec(
+      "CREATE INDEX IF NOT EXISTS idx_portfolio_valuations_account ON portfolio_valuations(account_id, valuation_date)"
+    );
+  }
+
+  // Migration 32: Allow several accounts of each type per user and record the provider per account (v0.1.10)
+  // Replaces UNIQUE(user_id, account_type) with UNIQUE(user_id, account_type, account_ref),
+  // which needs a table rebuild. Each account's provider is copied from its user.
+  const accountCols32 = database.query("PRAGMA table_info(accounts)").all();
+  const hasAccountProvider32 = accountCols32.some(function (col) {
+    return col.name === "provider";
+  });
+
+  if (!hasAccountProvider32) {
+    database.exec("PRAGMA foreign_keys = OFF");
+    database.exec("BEGIN TRANSACTION");
+    try {
+      database.exec(`
+        CREATE TABLE accounts_new (
+          id INTEGER PRIMARY KEY AUTOINCREMENT,
+          user_id INTEGER NOT NULL,
+          account_type TEXT NOT NULL CHECK(account_type IN ('trading', 'isa', 'sipp')),
+          account_ref TEXT NOT NULL CHECK(length(account_ref) <= 15),
+          provider TEXT CHECK(provider IS NULL OR length(provider) <= 5),
+          cash_balance INTEGER NOT NULL DEFAULT 0,
+          warn_cash INTEGER NOT NULL DEFAULT 0,
+          FOREIGN KEY (user_id) REFERENCES users(id),
+          UNIQUE(user_id, account_type, account_ref)
+        )
+      `);
+      database.exec(`
+        INSERT INTO accounts_new (id, user_id, account_type, account_ref, provider, cash_balance, warn_cash)
+        SELECT a.id, a.user_id, a.account_type, a.account_ref,
+               (SELECT NULLIF(u.provider, '-') FROM users u WHERE u.id = a.user_id),
+               a.cash_balance, a.warn_cash
+        FROM accounts a
+      `);
+      database.exec("DROP TABLE accounts");
+      database.exec("ALTER TABLE accounts_new RENAME TO accounts");
+      database.exec("CREATE INDEX IF NOT EXISTS idx_accounts_user ON accounts(user_id)");
+      database.exec("COMMIT");
+    } catch (err) {
+      database.exec("ROLLBACK");
+      throw err;
+    } finally {
+      database.exec("PRAGMA foreign_keys = ON");
+    }
+  }
+
+  // Migration 33: Add ISA transfer columns to cash_transactions (v0.1.10)
+  // A transfer between ISA providers is recorded as a withdrawal from the source
+  // and a deposit into the destination. isa_transfer says whether the money is
+  // this tax year's subscriptions ('current_year') or earlier years' ('previous_years');
+  // transfer_account_id links the two sides when both ISAs are held here.
+  const ctCols33 = database.query("PRAGMA table_info(cash_transactions)").all();
+  const hasIsaTransfer33 = ctCols33.some(function (col) {
+    return col.name === "isa_transfer";
+  });
+
+  if (!hasIsaTransfer33) {
+    database.exec("ALTER TABLE cash_transactions ADD COLUMN isa_transfer TEXT CHECK(isa_transfer IS NULL OR isa_transfer IN ('current_year', 'previous_years'))");
+    database.exec("ALTER TABLE cash_transactions ADD COLUMN transfer_account_id INTEGER REFERENCES accounts(id)");
+  }
+
+  // Migration 34: Add 'tax_relief' type and pension contribution columns to cash_transactions (v0.1.10)
+  // A SIPP contribution is a deposit flagged with contribution_type ('personal' or
+  // 'employer') and the gross_amount it represents; relief-at-source top-ups from
+  // HMRC are recorded separately as 'tax_relief'. The new type needs a table rebuild.
+  const ctTableInfo34 = database.query("SELECT sql FROM sqlite_master WHERE type='table' AND name='cash_transactions'").get();
+  if (ctTableInfo34 && ctTableInfo34.sql && !ctTableInfo34.sql.includes("'tax_relief'")) {
+    database.exec("PRAGMA foreign_keys = OFF");
+    database.exec("BEGIN TRANSACTION");
+    try {
+      database.exec(`
+        CREATE TABLE cash_transactions_new (
+          id INTEGER PRIMARY KEY AUTOINCREMENT,
+          account_id INTEGER NOT NULL,
+          holding_movement_id INTEGER,
+          transaction_type TEXT NOT NULL CHECK(transaction_type IN ('deposit', 'withdrawal', 'drawdown', 'adjustment', 'buy', 'sell', 'dividend', 'interest', 'tax_relief')),
+          transaction_date TEXT NOT NULL,
+          amount INTEGER NOT NULL,
+          notes TEXT CHECK(notes IS NULL OR length(notes) <= 255),
+          balance_after INTEGER,
+          investment_id INTEGER,
+          isa_transfer TEXT CHECK(isa_transfer IS NULL OR isa_transfer IN ('current_year', 'previous_years')),
+          transfer_account_id INTEGER,
+          contribution_type TEXT CHECK(contribution_type IS NULL OR contribution_type IN ('personal', 'employer')),
+          gross_amount INTEGER,
+          FOREIGN KEY (account_id) REFERENCES accounts(id),
+          FOREIGN KEY (holding_movement_id) REFERENCES holding_movements(id),
+          FOREIGN KEY (investment_id) REFERENCES investments(id),
+          FOREIGN KEY (transfer_account_id) REFERENCES accounts(id)
+        )
+      `);
+      database.exec(`
+        INSERT INTO cash_transactions_new (id, account_id, holding_movement_id, transaction_type, transaction_date, amount, notes, balance_after, investment_id, isa_transfer, transfer_account_id)
+        SELECT id, account_id, holding_movement_id, transaction_type, transaction_date, amount, notes, balance_after, investment_id, isa_transfer, transfer_account_id FROM cash_transactions
+      `);
+      database.exec("DROP TABLE cash_transactions");
+      database.exec("ALTER TABLE cash_transactions_new RENAME TO cash_transactions");
+      database.exec("CREATE INDEX IF NOT EXISTS idx_cash_transactions_account ON cash_transactions(account_id, transaction_date DESC)");
+      database.exec("CREATE INDEX IF NOT EXISTS idx_cash_transactions_investment ON cash_transactions(investment_id)");
+      database.exec("COMMIT");
+    } catch (err) {
+      database.exec("ROLLBACK");
+      throw err;
+    } finally {
+      database.exec("PRAGMA foreign_keys = ON");
+    }
+  }
+
+  // Migration 35: Add PAYE tax treatment to drawdown_schedules and tax withheld to cash_transactions (v0.1.10)
+  // Each schedule says how the provider deducts income tax (a tax code, a flat rate
+  // or the emergency code); each drawdown records the tax withheld alongside its gross amount.
+  const ddCols35 = database.query("PRAGMA table_info(drawdown_schedules)").all();
+  const hasTaxTreatment35 = ddCols35.some(function (col) {
+    return col.name === "tax_treatment";
+  });
+
+  if (!hasTaxTreatment35) {
+    database.exec("ALTER TABLE drawdown_schedules ADD COLUMN tax_treatment TEXT NOT NULL DEFAULT 'none' CHECK(tax_treatment IN ('none', 'tax_code', 'flat', 'emergency'))");
+    database.exec("ALTER TABLE drawdown_schedules ADD COLUMN tax_code TEXT CHECK(tax_code IS NULL OR length(tax_code) <= 20)");
+    database.exec("ALTER TABLE drawdown_schedules ADD COLUMN flat_tax_rate INTEGER");
+  }
+
+  const ctCols35 = database.query("PRAGMA table_info(cash_transactions)").all();
+  const hasTaxWithheld35 = ctCols35.some(function (col) {
+    return col.name === "tax_withheld";
+  });
+
+  if (!hasTaxWithheld35) {
+    database.exec("ALTER TABLE cash_transactions ADD COLUMN tax_withheld INTEGER");
+    database.exec("ALTER TABLE cash_transactions ADD COLUMN tax_code TEXT CHECK(tax_code IS NULL OR length(tax_code) <= 20)");
+  }
+
+  // Migration 36: Add sipp_crystallisations table (v0.1.10)
+  // Records each designation of SIPP funds into drawdown and the tax-free cash taken with it.
+  const crystallisationsTable = database.query(
+    "SELECT name FROM sqlite_master WHERE type='table' AND name='sipp_crystallisations'"
+  ).get();
+
+  if (!crystallisationsTable) {
+    database.exec(`
+      CREATE TABLE IF NOT EXISTS sipp_crystallisations (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        account_id INTEGER NOT NULL,
+        crystallisation_date TEXT NOT NULL,
+        tax_free_cash INTEGER NOT NULL DEFAULT 0,
+        drawdown_amount INTEGER NOT NULL DEFAULT 0,
+        cash_transaction_id INTEGER,
+        notes TEXT CHECK(notes IS NULL OR length(notes) <= 255),
+        FOREIGN KEY (account_id) REFERENCES accounts(id),
+        FOREIGN KEY (cash_transaction_id) REFERENCES cash_transactions(id)
+      )
+    `);
+    database.exec(
+      "CREATE INDEX IF NOT EXISTS idx_sipp_crystallisations_account ON sipp_crystallisations(account_id, crystallisation_date)"
+    );
+  }
+
+  // Migration 37: Add date_of_birth to users (v0.1.10)
+  // Optional; lets retirement projections report ages rather than years from now.
+  const userCols37 = database.query("PRAGMA table_info(users)").all();
+  const hasDateOfBirth37 = userCols37.some(function (col) {
+    return col.name === "date_of_birth";
+  });
+
+  if (!hasDateOfBirth37) {
+    database.exec("ALTER TABLE users ADD COLUMN date_of_birth TEXT");
+  }
+
+  // Migration 38: Add index series and escalation rules (v0.1.10)
+  // Drawdown schedules and recurring other assets can rise each year on an anniversary,
+  // by a fixed percentage or in line with an index series (such as CPI) loaded from CSV.
+  const indexSeriesTable = database.query(
+    "SELECT name FROM sqlite_master WHERE type='table' AND name='index_series'"
+  ).get();
+
+  if (!indexSeriesTable) {
+    database.exec(`
+      CREATE TABLE IF NOT EXISTS index_series (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        name TEXT NOT NULL UNIQUE CHECK(length(name) <= 30),
+        description TEXT CHECK(description IS NULL OR length(description) <= 80)
+      )
+    `);
+    database.exec(`
+      CREATE TABLE IF NOT EXISTS index_values (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        index_series_id INTEGER NOT NULL,
+        value_date TEXT NOT NULL,
+        value INTEGER NOT NULL,
+        FOREIGN KEY (index_series_id) REFERENCES index_series(id) ON DELETE CASCADE,
+        UNIQUE(index_series_id, value_date)
+      )
+    `);
+  }
+
+  const ddCols38 = database.query("PRAGMA table_info(drawdown_schedules)").all();
+  const ddHasEscalation38 = ddCols38.some(function (col) {
+    return col.name === "escalation_type";
+  });
+
+  if (!ddHasEscalation38) {
+    database.exec("ALTER TABLE drawdown_schedules ADD COLUMN escalation_type TEXT NOT NULL DEFAULT 'none' CHECK(escalation_type IN ('none', 'fixed', 'index'))");
+    database.exec("ALTER TABLE drawdown_schedules ADD COLUMN escalation_rate INTEGER");
+    database.exec("ALTER TABLE drawdown_schedules ADD COLUMN escalation_index_id INTEGER REFERENCES index_series(id)");
+    database.exec("ALTER TABLE drawdown_schedules ADD COLUMN escalation_anniversary TEXT");
+  }
+
+  const oaCols38 = database.query("PRAGMA table_info(other_assets)").all();
+  const oaHasEscalation38 = oaCols38.some(function (col) {
+    return col.name === "escalation_type";
+  });
+
+  if (!oaHasEscalation38) {
+    database.exec("ALTER TABLE other_assets ADD COLUMN escalation_type TEXT NOT NULL DEFAULT 'none' CHECK(escalation_type IN ('none', 'fixed', 'index'))");
+    database.exec("ALTER TABLE other_assets ADD COLUMN escalation_rate INTEGER");
+    database.exec("ALTER TABLE other_assets ADD COLUMN escalation_index_id INTEGER REFERENCES index_series(id)");
+    database.exec("ALTER TABLE other_assets ADD COLUMN escalation_anniversary TEXT");
+    database.exec("ALTER TABLE other_assets ADD COLUMN escalation_last_date TEXT");
+  }
+
+  const ohCols38 = database.query("PRAGMA table_info(other_assets_history)").all();
+  const ohHasReason38 = ohCols38.some(function (col) {
+    return col.name === "reason";
+  });
+
+  if (!ohHasReason38) {
+    database.exec("ALTER TABLE other_assets_history ADD COLUMN reason TEXT CHECK(reason IS NULL OR length(reason) <= 80)");
+  }
+
+  // Migration 39: Add allocation_tag column to investments (v0.1.10)
+  // A user-assigned region or asset-class label (e.g. "UK Equity", "Global Bonds")
+  // used to group holdings in the allocation breakdown. NULL means untagged.
+  const investmentCols39 = database.query("PRAGMA table_info(investments)").all();
+  const hasAllocationTag39 = investmentCols39.some(function (col) {
+    return col.name === "allocation_tag";
+  });
+
+  if (!hasAllocationTag39) {
+    database.exec("ALTER TABLE investments ADD COLUMN allocation_tag TEXT CHECK(allocation_tag IS NULL OR length(allocation_tag) <= 30)");
+  }
+
+  // Migration 40: Add allocation_targets table (v0.1.10)
+  // Target weights for a person or a single account, by investment or by allocation
+  // tag, compared with current valuations by the rebalancing planner.
+  const allocationTargetsTable = database.query(
+    "SELECT name FROM sqlite_master WHERE type='table' AND name='allocation_targets'"
+  ).get();
+
+  if (!allocationTargetsTable) {
+    database.exec(`
+      CREATE TABLE IF NOT EXISTS allocation_targets (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        user_id INTEGER NOT NULL,
+        account_id INTEGER,
+        investment_id INTEGER,
+        allocation_tag TEXT CHECK(allocation_tag IS NULL OR length(allocation_tag) <= 30),
+        target_percent INTEGER NOT NULL,
+        CHECK((investment_id IS NULL) != (allocation_tag IS NULL)),
+        FOREIGN KEY (user_id) REFERENCES users(id),
+        FOREIGN KEY (account_id) REFERENCES accounts(id),
+        FOREIGN KEY (investment_id) REFERENCES investments(id)
+      )
+    `);
+    database.exec("CREATE INDEX IF NOT EXISTS idx_allocation_targets_user ON allocation_targets(user_id, account_id)");
+  }
+
+  // Migration 41: Add composite benchmarks (v0.1.10)
+  // A composite benchmark blends other benchmarks by weight (e.g. 60/40), rebalanced
+  // monthly or quarterly. Its values are derived from the components' benchmark_data
+  // rather than fetched, so 'composite' is added to the benchmark_type CHECK constraint.
+  // SQLite cannot ALTER CHECK constraints — requires table rebuild.
+  const benchmarksTableInfo41 = database.query("SELECT sql FROM sqlite_master WHERE type='table' AND name='benchmarks'").get();
+  if (benchmarksTableInfo41 && benchmarksTableInfo41.sql && !benchmarksTableInfo41.sql.includes("composite")) {
+    database.exec("PRAGMA foreign_keys = OFF");
+    database.exec("BEGIN TRANSACTION");
+    try {
+      database.exec(`
+        CREATE TABLE benchmarks_new (
+          id INTEGER PRIMARY KEY AUTOINCREMENT,
+          currencies_id INTEGER NOT NULL,
+          benchmark_type TEXT NOT NULL CHECK(benchmark_type IN ('index', 'price', 'composite')),
+          description TEXT NOT NULL CHECK(length(description) <= 60),
+          benchmark_url TEXT CHECK(benchmark_url IS NULL OR length(benchmark_url) <= 255),
+          selector TEXT CHECK(selector IS NULL OR length(selector) <= 255),
+          yahoo_ticker TEXT,
+          rebalance_frequency TEXT CHECK(rebalance_frequency IS NULL OR rebalance_frequency IN ('monthly', 'quarterly')),
+          FOREIGN KEY (currencies_id) REFERENCES currencies(id)
+        )
+      `);
+      database.exec(`
+        INSERT INTO benchmarks_new (id, currencies_id, benchmark_type, description, benchmark_url, selector, yahoo_ticker)
+        SELECT id, currencies_id, benchmark_type, description, benchmark_url, selector, yahoo_ticker FROM benchmarks
+      `);
+      database.exec("DROP TABLE benchmarks");
+      database.exec("ALTER TABLE benchmarks_new RENAME TO benchmarks");
+      database.exec("CREATE INDEX IF NOT EXISTS idx_benchmarks_type ON benchmarks(benchmark_type)");
+      database.exec("CREATE INDEX IF NOT EXISTS idx_benchmarks_currency ON benchmarks(currencies_id)");
+      database.exec("COMMIT");
+    } catch (err) {
+      database.exec("ROLLBACK");
+      throw err;
+    } finally {
+      database.exec("PRAGMA foreign_keys = ON");
+    }
+  }
+
+  const benchmarkComponentsTable = database.query(
+    "SELECT name FROM sqlite_master WHERE type='table' AND name='benchmark_components'"
+  ).get();
+
+  if (!benchmarkComponentsTable) {
+    database.exec(`
+      CREATE TABLE IF NOT EXISTS benchmark_components (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        benchmark_id INTEGER NOT NULL,
+        component_benchmark_id INTEGER NOT NULL,
+        weight_percent INTEGER NOT NULL,
+        FOREIGN KEY (benchmark_id) REFERENCES benchmarks(id),
+        FOREIGN KEY (component_benchmark_id) REFERENCES benchmarks(id),
+        UNIQUE(benchmark_id, component_benchmark_id)
+      )
+    `);
+    database.exec("CREATE INDEX IF NOT EXISTS idx_benchmark_components_benchmark ON benchmark_components(benchmark_id)");
+  }
+
+  // Migration 42: Add liabilities to other_assets (v0.1.10)
+  // Mortgages, loans and credit balances are held as other_assets with category
+  // 'liability' — value is the balance owed, with an optional annual interest rate
+  // and monthly repayment (both × 10000) for the amortisation schedule.
+  // SQLite cannot ALTER CHECK constraints — requires table rebuild.
+  const otherAssetsTableInfo42 = database.query("SELECT sql FROM sqlite_master WHERE type='table' AND name='other_assets'").get();
+  if (otherAssetsTableInfo42 && otherAssetsTableInfo42.sql && !otherAssetsTableInfo42.sql.includes("liability")) {
+    database.exec("PRAGMA foreign_keys = OFF");
+    database.exec("BEGIN TRANSACTION");
+    try {
+      database.exec(`
+        CREATE TABLE other_assets_new (
+          id INTEGER PRIMARY KEY AUTOINCREMENT,
+          user_id INTEGER NOT NULL,
+          description TEXT NOT NULL CHECK(length(description) <= 40),
+          category TEXT NOT NULL CHECK(category IN ('pension', 'property', 'savings', 'alternative', 'liability')),
+          value_type TEXT NOT NULL CHECK(value_type IN ('recurring', 'value')),
+          frequency TEXT CHECK(frequency IS NULL OR frequency IN ('weekly', 'fortnightly', '4_weeks', 'monthly', 'quarterly', '6_monthly', 'annually')),
+          value INTEGER NOT NULL DEFAULT 0,
+          notes TEXT CHECK(notes IS NULL OR length(notes) <= 60),
+          executor_reference TEXT CHECK(executor_reference IS NULL OR length(executor_reference) <= 80),
+          last_updated TEXT NOT NULL,
+          escalation_type TEXT NOT NULL DEFAULT 'none' CHECK(escalation_type IN ('none', 'fixed', 'index')),
+          escalation_rate INTEGER,
+          escalation_index_id INTEGER,
+          escalation_anniversary TEXT,
+          escalation_last_date TEXT,
+          interest_rate INTEGER,
+          repayment INTEGER,
+          FOREIGN KEY (user_id) REFERENCES users(id),
+          FOREIGN KEY (escalation_index_id) REFERENCES index_series(id)
+        )
+      `);
+      database.exec(`
+        INSERT INTO other_assets_new (id, user_id, description, category, value_type, frequency, value, notes, executor_reference, last_updated,
+                                      escalation_type, escalation_rate, escalation_index_id, escalation_anniversary, escalation_last_date)
+        SELECT id, user_id, description, category, value_type, frequency, value, notes, executor_reference, last_updated,
+               escalation_type, escalation_rate, escalation_index_id, escalation_anniversary, escalation_last_date
+        FROM other_assets
+      `);
+      database.exec("DROP TABLE other_assets");
+      database.exec("ALTER TABLE other_assets_new RENAME TO other_assets");
+      database.exec("CREATE INDEX IF NOT EXISTS idx_other_assets_user ON other_assets(user_id)");
+      database.exec("CREATE INDEX IF NOT EXISTS idx_other_assets_category ON other_assets(category)");
+      database.exec("COMMIT");
+    } catch (err) {
+      database.exec("ROLLBACK");
+      throw err;
+    } finally {
+      database.exec("PRAGMA foreign_keys = ON");
+    }
+  }
+
+  // Migration 43: Add revalue_months column to other_assets (v0.1.10)
+  // How often, in months, an asset's value should be checked. An asset whose
+  // last_updated is older than this is flagged as due for revaluation. NULL means no rule.
+  const oaCols43 = database.query("PRAGMA table_info(other_assets)").all();
+  const hasRevalueMonths43 = oaCols43.some(function (col) {
+    return col.name === "revalue_months";
+  });
+
+  if (!hasRevalueMonths43) {
+    database.exec("ALTER TABLE other_assets ADD COLUMN revalue_months INTEGER");
+  }
+
+  // Migration 44: Add house price indexation of property (v0.1.10)
+  // index_series.region picks one region's rows out of a UK House Price Index file.
+  // A property linked to a series by valuation_index_id has its value estimated
+  // from surveyed_value (GBP × 10000) on surveyed_date; value_source says whether
+  // the current value is 'surveyed' or 'indexed', and revised_value_source does
+  // the same for each history row.
+  const seriesCols44 = database.query("PRAGMA table_info(index_series)").all();
+  const hasRegion44 = seriesCols44.some(function (col) {
+    return col.name === "region";
+  });
+
+  if (!hasRegion44) {
+    database.exec("ALTER TABLE index_series ADD COLUMN region TEXT CHECK(region IS NULL OR length(region) <= 60)");
+  }
+
+  const oaCols44 = database.query("PRAGMA table_info(other_assets)").all();
+  const hasValuationIndex44 = oaCols44.some(function (col) {
+    return col.name === "valuation_index_id";
+  });
+
+  if (!hasValuationIndex44) {
+    database.exec("ALTER TABLE other_assets ADD COLUMN valuation_index_id INTEGER REFERENCES index_series(id)");
+    database.exec("ALTER TABLE other_assets ADD COLUMN surveyed_value INTEGER");
+    database.exec("ALTER TABLE other_assets ADD COLUMN surveyed_date TEXT");
+    database.exec("ALTER TABLE other_assets ADD COLUMN value_source TEXT NOT NULL DEFAULT 'surveyed' CHECK(value_source IN ('surveyed', 'indexed'))");
+  }
+
+  const historyCols44 = database.query("PRAGMA table_info(other_assets_history)").all();
+  const hasRevisedValueSource44 = historyCols44.some(function (col) {
+    return col.name === "revised_value_source";
+  });
+
+  if (!hasRevisedValueSource44) {
+    database.exec("ALTER TABLE other_assets_history ADD COLUMN revised_value_source TEXT CHECK(revised_value_source IS NULL OR revised_value_source IN ('surveyed', 'indexed'))");
+  }
+
+  // Migration 45: Add inheritance tax estate estimate fields (v0.1.10)
+  // users.spouse_user_id links spouses or civil partners, so unused nil-rate
+  // bands can pass to the survivor; other_assets.main_residence marks the home
+  // that qualifies for the residence nil-rate band.
+  const userCols45 = database.query("PRAGMA table_info(users)").all();
+  const hasSpouse45 = userCols45.some(function (col) {
+    return col.name === "spouse_user_id";
+  });
+
+  if (!hasSpouse45) {
+    database.exec("ALTER TABLE users ADD COLUMN spouse_user_id INTEGER REFERENCES users(id)");
+  }
+
+  const oaCols45 = database.query("PRAGMA table_info(other_assets)").all();
+  const hasMainResidence45 = oaCols45.some(function (col) {
+    return col.name === "main_residence";
+  });
+
+  if (!hasMainResidence45) {
+    database.exec("ALTER TABLE other_assets ADD COLUMN main_residence INTEGER NOT NULL DEFAULT 0 CHECK(main_residence IN (0, 1))");
+  }
+
+  // Migration 46: Add gifts register (v0.1.10)
+  // Records gifts made by each family member for inheritance tax planning, with the
+  // exemption claimed; cash_transaction_id links the withdrawal the gift was paid by.
+  const giftsTable = database.query(
+    "SELECT name FROM sqlite_master WHERE type='table' AND name='gifts'"
+  ).get();
+
+  if (!giftsTable) {
+    database.exec(`
+      CREATE TABLE IF NOT EXISTS gifts (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        user_id INTEGER NOT NULL,
+        recipient TEXT NOT NULL CHECK(length(recipient) <= 60),
+        gift_date TEXT NOT NULL,
+        amount INTEGER NOT NULL,
+        exemption_type TEXT NOT NULL CHECK(exemption_type IN ('annual', 'small_gift', 'normal_expenditure')),
+        cash_transaction_id INTEGER UNIQUE,
+        notes TEXT CHECK(notes IS NULL OR length(notes) <= 255),
+        FOREIGN KEY (user_id) REFERENCES users(id),
+        FOREIGN KEY (cash_transaction_id) REFERENCES cash_transactions(id)
+      )
+    `);
+    database.exec("CREATE INDEX IF NOT EXISTS idx_gifts_user ON gifts(user_id, gift_date)");
+  }
+
+  // Migration 47: Add held dates to other_assets (v0.1.10)
+  // start_date is when an item was first held, so past net worth leaves it out
+  // before then (NULL for existing items, which are treated as always held).
+  // closed_date marks a deleted item, kept so past net worth does not change.
+  const oaCols47 = database.query("PRAGMA table_info(other_assets)").all();
+  const hasStartDate47 = oaCols47.some(function (col) {
+    return col.name === "start_date";
+  });
+
+  if (!hasStartDate47) {
+    database.exec("ALTER TABLE other_assets ADD COLUMN start_date TEXT");
+    database.exec("ALTER TABLE other_assets ADD COLUMN closed_date TEXT");
+  }
 }
 
 /**
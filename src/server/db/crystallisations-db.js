@@ -0,0 +1,161 @@
+import { getDatabase } from "./connection.js";
+import { insertCashTransaction, removeCashTransaction, scaleCashAmount, unscaleCashAmount } from "./cash-transactions-db.js";
+
+/**
+ * @description Record the crystallisation of part of a SIPP: the tax-free cash
+ * (pension commencement lump sum) taken and the amount designated for drawdown.
+ * When pay_from_cash is set, the tax-free cash is paid out as a withdrawal from
+ * the account's cash balance in the same database transaction and linked to the
+ * crystallisation.
+ *
+ * @param {Object} data - The crystallisation data
+ * @param {number} data.account_id - The SIPP account
+ * @param {string} data.crystallisation_date - ISO-8601 date (YYYY-MM-DD)
+ * @param {number} data.tax_free_cash - Tax-free cash taken as a decimal (may be 0)
+ * @param {number} data.drawdown_amount - Amount moved into drawdown as a decimal (may be 0)
+ * @param {boolean} [data.pay_from_cash=false] - Debit the tax-free cash from the account's cash
+ * @param {string} [data.notes] - Optional notes (max 255 chars)
+ * @returns {Object} The created crystallisation with unscaled amounts
+ * @throws {Error} If paying from cash and the account's cash balance is insufficient
+ */
+export function createCrystallisation(data) {
+  const db = getDatabase();
+  const payFromCash = data.pay_from_cash === true && data.tax_free_cash > 0;
+
+  if (payFromCash) {
+    const account = db.query("SELECT cash_balance FROM accounts WHERE id = ?").get(data.account_id);
+    if (!account || account.cash_balance < scaleCashAmount(data.tax_free_cash)) {
+      throw new Error("Insufficient cash balance");
+    }
+  }
+
+  db.exec("BEGIN");
+  try {
+    let cashTransactionId = null;
+    if (payFromCash) {
+      cashTransactionId = insertCashTransaction({
+        account_id: data.account_id,
+        transaction_type: "withdrawal",
+        transaction_date: data.crystallisation_date,
+        amount: data.tax_free_cash,
+        notes: data.notes ? "Tax-free cash: " + data.notes : "Tax-free cash",
+      });
+    }
+
+    const result = db.run(
+      `INSERT INTO sipp_crystallisations (account_id, crystallisation_date, tax_free_cash, drawdown_amount, cash_transaction_id, notes)
+       VALUES (?, ?, ?, ?, ?, ?)`,
+      [data.account_id, data.crystallisation_date, scaleCashAmount(data.tax_free_cash), scaleCashAmount(data.drawdown_amount), cashTransactionId, data.notes || null],
+    );
+
+    db.exec("COMMIT");
+    return getCrystallisationById(result.lastInsertRowid);
+  } catch (err) {
+    db.exec("ROLLBACK");
+    throw err;
+  }
+}
+
+/**
+ * @description Get a single crystallisation by ID with unscaled amounts.
+ * @param {number} id - The crystallisation ID
+ * @returns {Object|null} The crystallisation, or null if not found
+ */
+export function getCrystallisationById(id) {
+  const db = getDatabase();
+  const row = db
+    .query(
+      `SELECT id, account_id, crystallisation_date, tax_free_cash, drawdown_amount, cash_transaction_id, notes
+       FROM sipp_crystallisations
+       WHERE id = ?`,
+    )
+    .get(id);
+
+  if (!row) return null;
+  return unscaleCrystallisationRow(row);
+}
+
+/**
+ * @description Get all crystallisations for a SIPP account, oldest first.
+ * @param {number} accountId - The account ID
+ * @returns {Object[]} Array of crystallisations with unscaled amounts
+ */
+export function getCrystallisationsByAccountId(accountId) {
+  const db = getDatabase();
+  const rows = db
+    .query(
+      `SELECT id, account_id, crystallisation_date, tax_free_cash, drawdown_amount, cash_transaction_id, notes
+       FROM sipp_crystallisations
+       WHERE account_id = ?
+       ORDER BY crystallisation_date ASC, id ASC`,
+    )
+    .all(accountId);
+
+  return rows.map(unscaleCrystallisationRow);
+}
+
+/**
+ * @description Get the total tax-free cash a user has taken across all their SIPPs.
+ * @param {number} userId - The user ID
+ * @returns {number} Total tax-free cash as an unscaled decimal
+ */
+export function getTaxFreeCashTakenByUser(userId) {
+  const db = getDatabase();
+  const row = db
+    .query(
+      `SELECT COALESCE(SUM(sc.tax_free_cash), 0) AS total
+       FROM sipp_crystallisations sc
+       JOIN accounts a ON a.id = sc.account_id
+       WHERE a.user_id = ?`,
+    )
+    .get(userId);
+
+  return unscaleCashAmount(row.total);
+}
+
+/**
+ * @description Delete a crystallisation. The withdrawal that paid out its
+ * tax-free cash, if any, is deleted in the same database transaction so the
+ * cash balance is restored.
+ * @param {number} id - The crystallisation ID
+ * @returns {boolean} True if deleted, false if not found
+ */
+export function deleteCrystallisation(id) {
+  const db = getDatabase();
+  const row = db.query("SELECT cash_transaction_id FROM sipp_crystallisations WHERE id = ?").get(id);
+  if (!row) return false;
+
+  db.exec("BEGIN");
+  try {
+    if (row.cash_transaction_id) {
+      removeCashTransaction(row.cash_transaction_id);
+    }
+    const result = db.run("DELETE FROM sipp_crystallisations WHERE id = ?", [id]);
+    db.exec("COMMIT");
+    return result.changes > 0;
+  } catch (err) {
+    db.exec("ROLLBACK");
+    throw err;
+  }
+}
+
+/**
+ * @description Convert a raw crystallisation row to unscaled amounts, adding
+ * the total amount crystallised.
+ * @param {Object} row - Raw database row
+ * @returns {Object} Row with unscaled amounts and amount_crystallised
+ */
+function unscaleCrystallisationRow(row) {
+  const taxFreeCash = unscaleCashAmount(row.tax_free_cash);
+  const drawdownAmount = unscaleCashAmount(row.drawdown_amount);
+  return {
+    id: row.id,
+    account_id: row.account_id,
+    crystallisation_date: row.crystallisation_date,
+    tax_free_cash: taxFreeCash,
+    drawdown_amount: drawdownAmount,
+    amount_crystallised: Math.round((taxFreeCash + drawdownAmount) * 100) / 100,
+    cash_transaction_id: row.cash_transaction_id,
+    notes: row.notes,
+  };
+}
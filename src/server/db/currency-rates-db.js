@@ -1,5 +1,6 @@
 import { getDatabase } from "./connection.js";
 import { CURRENCY_SCALE_FACTOR } from "../../shared/server-constants.js";
+import { invalidateCurrencyValuations } from "./portfolio-valuations-db.js";
 
 /**
  * @description Insert or replace a currency rate for a given currency and date.
@@ -13,6 +14,7 @@ import { CURRENCY_SCALE_FACTOR } from "../../shared/server-constants.js";
 export function upsertRate(currenciesId, rateDate, rateTime, scaledRate) {
   const db = getDatabase();
   db.run("INSERT OR REPLACE INTO currency_rates (currencies_id, rate_date, rate_time, rate) VALUES (?, ?, ?, ?)", [currenciesId, rateDate, rateTime, scaledRate]);
+  invalidateCurrencyValuations(currenciesId, rateDate);
 }
 
 /**
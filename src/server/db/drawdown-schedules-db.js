@@ -1,5 +1,6 @@
 import { getDatabase } from "./connection.js";
 import { CURRENCY_SCALE_FACTOR } from "../../shared/server-constants.js";
+import { escalateAmount, getEscalationRule } from "./escalation-db.js";
 
 /**
  * @description Scale a monetary value for storage (multiply by CURRENCY_SCALE_FACTOR).
@@ -29,20 +30,33 @@ export function unscaleAmount(scaledValue) {
  * @param {string} data.to_date - End date as YYYY-MM-DD (day component ignored, stored as YYYY-MM-01)
  * @param {number} data.amount - Drawdown amount as a decimal (e.g. 1200.00)
  * @param {string} [data.notes] - Optional notes (max 255 chars)
+ * @param {string} [data.tax_treatment='none'] - How PAYE is deducted: 'none', 'tax_code', 'flat' or 'emergency'
+ * @param {string} [data.tax_code] - The tax code, when tax_treatment is 'tax_code'
+ * @param {number} [data.flat_tax_rate] - Percentage deducted, when tax_treatment is 'flat'
+ * @param {string} [data.escalation_type='none'] - How the amount rises: 'none', 'fixed' or 'index'
+ * @param {number} [data.escalation_rate] - Percentage rise each year, when escalation_type is 'fixed'
+ * @param {number} [data.escalation_index_id] - FK to index_series, when escalation_type is 'index'
+ * @param {string} [data.escalation_anniversary] - Date the amount rises each year as MM-DD
  * @returns {Object} The created schedule with its new ID and unscaled amount
  */
 export function createDrawdownSchedule(data) {
   const db = getDatabase();
   const scaledAmount = scaleAmount(data.amount);
+  const tax = normaliseTaxTreatment(data);
+  const escalation = normaliseEscalation(data);
 
   // Normalise dates to first of month for consistency
   const fromDate = normaliseToFirstOfMonth(data.from_date);
   const toDate = normaliseToFirstOfMonth(data.to_date);
 
   const result = db.run(
-    `INSERT INTO drawdown_schedules (account_id, frequency, trigger_day, from_date, to_date, amount, notes, active)
-     VALUES (?, ?, ?, ?, ?, ?, ?, 1)`,
-    [data.account_id, data.frequency, data.trigger_day, fromDate, toDate, scaledAmount, data.notes || null],
+    `INSERT INTO drawdown_schedules (account_id, frequency, trigger_day, from_date, to_date, amount, notes, active, tax_treatment, tax_code, flat_tax_rate,
+                                     escalation_type, escalation_rate, escalation_index_id, escalation_anniversary)
+     VALUES (?, ?, ?, ?, ?, ?, ?, 1, ?, ?, ?, ?, ?, ?, ?)`,
+    [
+      data.account_id, data.frequency, data.trigger_day, fromDate, toDate, scaledAmount, data.notes || null, tax.treatment, tax.code, tax.rate,
+      escalation.type, escalation.rate, escalation.index_id, escalation.anniversary,
+    ],
   );
 
   return getDrawdownScheduleById(result.lastInsertRowid);
@@ -59,20 +73,34 @@ export function createDrawdownSchedule(data) {
  * @param {number} data.amount - Drawdown amount as a decimal
  * @param {string} [data.notes] - Optional notes (max 255 chars)
  * @param {number} [data.active] - 1 for active, 0 for paused
+ * @param {string} [data.tax_treatment='none'] - How PAYE is deducted: 'none', 'tax_code', 'flat' or 'emergency'
+ * @param {string} [data.tax_code] - The tax code, when tax_treatment is 'tax_code'
+ * @param {number} [data.flat_tax_rate] - Percentage deducted, when tax_treatment is 'flat'
+ * @param {string} [data.escalation_type='none'] - How the amount rises: 'none', 'fixed' or 'index'
+ * @param {number} [data.escalation_rate] - Percentage rise each year, when escalation_type is 'fixed'
+ * @param {number} [data.escalation_index_id] - FK to index_series, when escalation_type is 'index'
+ * @param {string} [data.escalation_anniversary] - Date the amount rises each year as MM-DD
  * @returns {Object|null} The updated schedule, or null if not found
  */
 export function updateDrawdownSchedule(id, data) {
   const db = getDatabase();
   const scaledAmount = scaleAmount(data.amount);
+  const tax = normaliseTaxTreatment(data);
+  const escalation = normaliseEscalation(data);
   const fromDate = normaliseToFirstOfMonth(data.from_date);
   const toDate = normaliseToFirstOfMonth(data.to_date);
   const active = data.active !== undefined ? data.active : 1;
 
   const result = db.run(
     `UPDATE drawdown_schedules
-     SET frequency = ?, trigger_day = ?, from_date = ?, to_date = ?, amount = ?, notes = ?, active = ?
+     SET frequency = ?, trigger_day = ?, from_date = ?, to_date = ?, amount = ?, notes = ?, active = ?,
+         tax_treatment = ?, tax_code = ?, flat_tax_rate = ?,
+         escalation_type = ?, escalation_rate = ?, escalation_index_id = ?, escalation_anniversary = ?
      WHERE id = ?`,
-    [data.frequency, data.trigger_day, fromDate, toDate, scaledAmount, data.notes || null, active, id],
+    [
+      data.frequency, data.trigger_day, fromDate, toDate, scaledAmount, data.notes || null, active, tax.treatment, tax.code, tax.rate,
+      escalation.type, escalation.rate, escalation.index_id, escalation.anniversary, id,
+    ],
   );
 
   if (result.changes === 0) {
@@ -102,7 +130,8 @@ export function getDrawdownScheduleById(id) {
   const db = getDatabase();
   const row = db
     .query(
-      `SELECT id, account_id, frequency, trigger_day, from_date, to_date, amount, notes, active
+      `SELECT id, account_id, frequency, trigger_day, from_date, to_date, amount, notes, active, tax_treatment, tax_code, flat_tax_rate,
+              escalation_type, escalation_rate, escalation_index_id, escalation_anniversary
        FROM drawdown_schedules
        WHERE id = ?`,
     )
@@ -121,7 +150,8 @@ export function getDrawdownSchedulesByAccountId(accountId) {
   const db = getDatabase();
   const rows = db
     .query(
-      `SELECT id, account_id, frequency, trigger_day, from_date, to_date, amount, notes, active
+      `SELECT id, account_id, frequency, trigger_day, from_date, to_date, amount, notes, active, tax_treatment, tax_code, flat_tax_rate,
+              escalation_type, escalation_rate, escalation_index_id, escalation_anniversary
        FROM drawdown_schedules
        WHERE account_id = ?
        ORDER BY from_date`,
@@ -140,7 +170,8 @@ export function getActiveDrawdownSchedules() {
   const db = getDatabase();
   const rows = db
     .query(
-      `SELECT id, account_id, frequency, trigger_day, from_date, to_date, amount, notes, active
+      `SELECT id, account_id, frequency, trigger_day, from_date, to_date, amount, notes, active, tax_treatment, tax_code, flat_tax_rate,
+              escalation_type, escalation_rate, escalation_index_id, escalation_anniversary
        FROM drawdown_schedules
        WHERE active = 1
        ORDER BY from_date`,
@@ -152,19 +183,64 @@ export function getActiveDrawdownSchedules() {
 
 /**
  * @description Calculate all trigger dates for a drawdown schedule up to a
- * given date. Skips dates in the future (after upToDate).
+ * given date, with the amount due on each. Skips dates in the future (after upToDate).
  *
  * - Monthly: trigger on trigger_day of every month from from_date to to_date.
  * - Quarterly: trigger every 3 months from from_date month.
  * - Annually: trigger once per year from from_date month.
  *
- * @param {Object} schedule - The drawdown schedule (with from_date, to_date, frequency, trigger_day)
+ * When the schedule escalates, the amount rises at each anniversary after the
+ * first trigger date; a payment falling on an anniversary gets the new amount.
+ * When an index-linked rise cannot be worked out yet because the index has not
+ * been loaded far enough, dates from that anniversary on carry its date in
+ * pending_rise: their amount is only provisional and they should be held back
+ * until the index is updated.
+ *
+ * @param {Object} schedule - The drawdown schedule (with from_date, to_date, frequency, trigger_day,
+ *   amount and the escalation fields)
  * @param {string} upToDate - The latest date to include (YYYY-MM-DD), typically today
- * @returns {string[]} Array of trigger dates in YYYY-MM-DD format
+ * @returns {Array<{date: string, amount: number, pending_rise: string|null}>} Trigger dates in
+ *   YYYY-MM-DD format, the amount due on each, and the anniversary whose rise is still pending
  */
 export function getDueDrawdownDates(schedule, upToDate) {
-  const dates = [];
+  const dates = listTriggerDates(schedule, upToDate);
+  if (dates.length === 0) return [];
+
+  const escalation = escalateAmount(schedule.amount, getEscalationRule(schedule), dates[0], dates[dates.length - 1]);
+  let stepIndex = 0;
+  let amount = schedule.amount;
+
+  return dates.map(function (dateStr) {
+    while (stepIndex < escalation.steps.length && escalation.steps[stepIndex].date <= dateStr) {
+      amount = escalation.steps[stepIndex].amount;
+      stepIndex++;
+    }
+    const pendingRise = escalation.pending_date && dateStr >= escalation.pending_date ? escalation.pending_date : null;
+    return { date: dateStr, amount: amount, pending_rise: pendingRise };
+  });
+}
 
+/**
+ * @description Get the amount a drawdown schedule pays on a date, after any
+ * escalation from its first trigger date. Dates before the first trigger date
+ * get the schedule's starting amount.
+ * @param {Object} schedule - The drawdown schedule
+ * @param {string} dateStr - ISO-8601 date (YYYY-MM-DD)
+ * @returns {number} The amount in GBP
+ */
+export function getDrawdownAmountOn(schedule, dateStr) {
+  const firstTrigger = schedule.from_date.slice(0, 8) + String(schedule.trigger_day).padStart(2, "0");
+  return escalateAmount(schedule.amount, getEscalationRule(schedule), firstTrigger, dateStr).amount;
+}
+
+/**
+ * @description List the trigger dates of a drawdown schedule up to a given date.
+ * @param {Object} schedule - The drawdown schedule (with from_date, to_date, frequency, trigger_day)
+ * @param {string} upToDate - The latest date to include (YYYY-MM-DD)
+ * @returns {string[]} Array of trigger dates in YYYY-MM-DD format
+ */
+function listTriggerDates(schedule, upToDate) {
+  const dates = [];
   // Parse from_date and to_date to extract year and month
   const fromParts = schedule.from_date.split("-");
   const fromYear = parseInt(fromParts[0], 10);
@@ -226,7 +302,8 @@ export function getOverlappingSchedule(accountId, fromDate, toDate, excludeId) {
   const normFrom = normaliseToFirstOfMonth(fromDate);
   const normTo = normaliseToFirstOfMonth(toDate);
 
-  let sql = `SELECT id, account_id, frequency, trigger_day, from_date, to_date, amount, notes, active
+  let sql = `SELECT id, account_id, frequency, trigger_day, from_date, to_date, amount, notes, active, tax_treatment, tax_code, flat_tax_rate,
+              escalation_type, escalation_rate, escalation_index_id, escalation_anniversary
      FROM drawdown_schedules
      WHERE account_id = ? AND active = 1
        AND from_date <= ? AND to_date >= ?`;
@@ -255,10 +332,42 @@ function normaliseToFirstOfMonth(dateStr) {
   return `${parts[0]}-${parts[1]}-01`;
 }
 
+/**
+ * @description Resolve the tax fields to store for a schedule. Only the field
+ * that goes with the chosen treatment is kept; tax codes are stored upper case.
+ * @param {Object} data - The schedule data
+ * @returns {{ treatment: string, code: string|null, rate: number|null }} Values for the tax columns (rate scaled)
+ */
+function normaliseTaxTreatment(data) {
+  const treatment = data.tax_treatment || "none";
+  return {
+    treatment: treatment,
+    code: treatment === "tax_code" && data.tax_code ? String(data.tax_code).toUpperCase().replace(/\s+/g, " ").trim() : null,
+    rate: treatment === "flat" && data.flat_tax_rate !== undefined && data.flat_tax_rate !== null ? scaleAmount(data.flat_tax_rate) : null,
+  };
+}
+
+/**
+ * @description Resolve the escalation fields to store for a schedule. Only the
+ * field that goes with the chosen type is kept.
+ * @param {Object} data - The schedule data
+ * @returns {{ type: string, rate: number|null, index_id: number|null, anniversary: string|null }} Values for
+ *   the escalation columns (rate scaled)
+ */
+function normaliseEscalation(data) {
+  const type = data.escalation_type || "none";
+  return {
+    type: type,
+    rate: type === "fixed" && data.escalation_rate !== undefined && data.escalation_rate !== null ? scaleAmount(data.escalation_rate) : null,
+    index_id: type === "index" ? Number(data.escalation_index_id) || null : null,
+    anniversary: type !== "none" ? data.escalation_anniversary || null : null,
+  };
+}
+
 /**
  * @description Convert a raw database row to an object with unscaled amount.
  * @param {Object} row - The raw database row
- * @returns {Object} Row with amount as a decimal
+ * @returns {Object} Row with amount, flat tax rate and escalation rate as decimals
  */
 function unscaleScheduleRow(row) {
   return {
@@ -272,5 +381,12 @@ function unscaleScheduleRow(row) {
     amount_scaled: row.amount,
     notes: row.notes,
     active: row.active,
+    tax_treatment: row.tax_treatment || "none",
+    tax_code: row.tax_code || null,
+    flat_tax_rate: row.flat_tax_rate !== null && row.flat_tax_rate !== undefined ? unscaleAmount(row.flat_tax_rate) : null,
+    escalation_type: row.escalation_type || "none",
+    escalation_rate: row.escalation_rate !== null && row.escalation_rate !== undefined ? unscaleAmount(row.escalation_rate) : null,
+    escalation_index_id: row.escalation_index_id || null,
+    escalation_anniversary: row.escalation_anniversary || null,
   };
 }
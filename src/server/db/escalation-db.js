@@ -0,0 +1,141 @@
+import { getIndexSeriesById, getIndexValueOn } from "./index-series-db.js";
+
+/**
+ * @description Escalation types that can be applied to drawdown schedules and
+ * recurring other assets.
+ * @type {string[]}
+ */
+export const ESCALATION_TYPES = ["none", "fixed", "index"];
+
+/**
+ * @description Round a decimal to 2 decimal places (pence).
+ * @param {number} value - The value to round
+ * @returns {number} The value rounded to pence
+ */
+function roundToPence(value) {
+  return Math.round(value * 100) / 100;
+}
+
+/**
+ * @description Move an ISO-8601 date back by one year. 29 February becomes 28 February.
+ * @param {string} dateStr - ISO-8601 date (YYYY-MM-DD)
+ * @returns {string} The same day a year earlier
+ */
+function oneYearBefore(dateStr) {
+  const year = parseInt(dateStr.slice(0, 4), 10) - 1;
+  const monthDay = dateStr.slice(5) === "02-29" ? "02-28" : dateStr.slice(5);
+  return year + "-" + monthDay;
+}
+
+/**
+ * @description List the anniversary dates that fall strictly after one date
+ * and on or before another. A 29 February anniversary falls on 28 February
+ * in years that are not leap years.
+ * @param {string} anniversary - Anniversary as MM-DD (e.g. "04-06")
+ * @param {string} afterDate - ISO-8601 date; anniversaries on this date are excluded
+ * @param {string} upToDate - ISO-8601 date; anniversaries on this date are included
+ * @returns {string[]} Anniversary dates (YYYY-MM-DD), oldest first
+ */
+export function getAnniversaryDates(anniversary, afterDate, upToDate) {
+  const dates = [];
+  if (!anniversary || upToDate <= afterDate) return dates;
+
+  const firstYear = parseInt(afterDate.slice(0, 4), 10);
+  const lastYear = parseInt(upToDate.slice(0, 4), 10);
+  for (let year = firstYear; year <= lastYear; year++) {
+    const isLeapYear = (year % 4 === 0 && year % 100 !== 0) || year % 400 === 0;
+    const dateStr = year + "-" + (anniversary === "02-29" && !isLeapYear ? "02-28" : anniversary);
+    if (dateStr > afterDate && dateStr <= upToDate) {
+      dates.push(dateStr);
+    }
+  }
+  return dates;
+}
+
+/**
+ * @description Extract the escalation rule from a drawdown schedule or other
+ * asset row, or null when the row does not escalate.
+ * @param {Object} row - Row with escalation_type, escalation_rate (decimal percent),
+ *   escalation_index_id and escalation_anniversary
+ * @returns {{type: string, rate: number|null, index_id: number|null, anniversary: string}|null} The rule
+ */
+export function getEscalationRule(row) {
+  if (!row || !row.escalation_type || row.escalation_type === "none" || !row.escalation_anniversary) return null;
+  return {
+    type: row.escalation_type,
+    rate: row.escalation_rate !== undefined ? row.escalation_rate : null,
+    index_id: row.escalation_index_id || null,
+    anniversary: row.escalation_anniversary,
+  };
+}
+
+/**
+ * @description Work out the rise due on an anniversary under an index-linked
+ * rule: the change in the index over the year to the latest value published
+ * on or before the anniversary. The rise is pending when the index has no value
+ * in the year to the anniversary, or none a year before that value.
+ * @param {number} indexId - The index series ID
+ * @param {string} anniversaryDate - ISO-8601 anniversary date
+ * @returns {{factor: number, label: string}|null} The factor to apply, or null if pending
+ */
+function indexFactorOn(indexId, anniversaryDate) {
+  const series = getIndexSeriesById(indexId);
+  if (!series) return null;
+
+  const latest = getIndexValueOn(indexId, anniversaryDate);
+  if (!latest || latest.value_date <= oneYearBefore(anniversaryDate)) return null;
+
+  const base = getIndexValueOn(indexId, oneYearBefore(latest.value_date));
+  if (!base || base.value <= 0) return null;
+
+  return { factor: latest.value / base.value, label: series.name };
+}
+
+/**
+ * @description Escalate an amount from one date to another. At each
+ * anniversary after fromDate and on or before toDate the amount rises by the
+ * fixed percentage, or by the change in the index series over the year; index
+ * falls are ignored, so the amount never goes down. The amount is rounded to
+ * pence at each step. An index-linked rise that cannot be worked out yet (the
+ * index has not been loaded far enough) is left pending and stops further
+ * steps, so it is picked up once the index is updated.
+ *
+ * @param {number} amount - The starting amount in GBP
+ * @param {Object|null} rule - The rule from getEscalationRule
+ * @param {string} fromDate - ISO-8601 date the amount applies from
+ * @param {string} toDate - ISO-8601 date to escalate up to (inclusive)
+ * @returns {{amount: number, steps: Object[], pending_date: string|null}} The escalated amount,
+ *   each step applied ({date, rate, amount, reason}), and the first anniversary left pending
+ */
+export function escalateAmount(amount, rule, fromDate, toDate) {
+  const result = { amount: amount, steps: [], pending_date: null };
+  if (!rule) return result;
+
+  for (const date of getAnniversaryDates(rule.anniversary, fromDate, toDate)) {
+    let factor;
+    let label;
+    if (rule.type === "fixed") {
+      factor = 1 + (rule.rate || 0) / 100;
+      label = "fixed";
+    } else {
+      const indexed = indexFactorOn(rule.index_id, date);
+      if (!indexed) {
+        result.pending_date = date;
+        break;
+      }
+      factor = Math.max(1, indexed.factor);
+      label = indexed.label;
+    }
+
+    const rate = Math.round((factor - 1) * 10000) / 100;
+    result.amount = roundToPence(result.amount * factor);
+    result.steps.push({
+      date: date,
+      rate: rate,
+      amount: result.amount,
+      reason: "Escalated " + rate.toFixed(2) + "% (" + label + ")",
+    });
+  }
+
+  return result;
+}
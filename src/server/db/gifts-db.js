@@ -0,0 +1,135 @@
+import { getDatabase } from "./connection.js";
+import { scaleCashAmount, unscaleCashAmount } from "./cash-transactions-db.js";
+
+/**
+ * @description Exemption type display labels, in the order they are offered.
+ * @type {Object<string, string>}
+ */
+export const GIFT_EXEMPTION_TYPE_LABELS = {
+  annual: "Annual exemption",
+  small_gift: "Small gift",
+  normal_expenditure: "Normal expenditure out of income",
+};
+
+/**
+ * @description Base SQL for selecting gifts with the donor's name and the
+ * account of the withdrawal that paid the gift, if linked.
+ * @type {string}
+ */
+const GIFT_SELECT = `
+  SELECT g.id, g.user_id, u.initials, u.first_name, u.last_name,
+         g.recipient, g.gift_date, g.amount, g.exemption_type, g.cash_transaction_id, ct.account_id, g.notes
+  FROM gifts g
+  JOIN users u ON g.user_id = u.id
+  LEFT JOIN cash_transactions ct ON g.cash_transaction_id = ct.id
+`;
+
+/**
+ * @description Convert a raw gift row to an unscaled amount.
+ * @param {Object} row - The raw database row
+ * @returns {Object} Gift with amount as a decimal
+ */
+function unscaleGiftRow(row) {
+  return {
+    id: row.id,
+    user_id: row.user_id,
+    initials: row.initials,
+    first_name: row.first_name,
+    last_name: row.last_name,
+    recipient: row.recipient,
+    gift_date: row.gift_date,
+    amount: unscaleCashAmount(row.amount),
+    exemption_type: row.exemption_type,
+    cash_transaction_id: row.cash_transaction_id,
+    account_id: row.account_id,
+    notes: row.notes,
+  };
+}
+
+/**
+ * @description Get the gifts in the register, oldest first, optionally for one donor.
+ * @param {number} [userId] - Only gifts made by this user
+ * @returns {Object[]} Array of gifts with unscaled amounts
+ */
+export function getAllGifts(userId) {
+  const db = getDatabase();
+  if (userId) {
+    return db.query(GIFT_SELECT + " WHERE g.user_id = ? ORDER BY g.gift_date, g.id").all(userId).map(unscaleGiftRow);
+  }
+  return db.query(GIFT_SELECT + " ORDER BY g.gift_date, g.id").all().map(unscaleGiftRow);
+}
+
+/**
+ * @description Get a single gift by ID.
+ * @param {number} id - The gift ID
+ * @returns {Object|null} The gift with unscaled amount, or null if not found
+ */
+export function getGiftById(id) {
+  const db = getDatabase();
+  const row = db.query(GIFT_SELECT + " WHERE g.id = ?").get(id);
+  if (!row) return null;
+  return unscaleGiftRow(row);
+}
+
+/**
+ * @description Get the gift recorded from a cash transaction, if any.
+ * @param {number} cashTransactionId - The cash transaction ID
+ * @returns {Object|null} The gift with unscaled amount, or null if none
+ */
+export function getGiftByCashTransactionId(cashTransactionId) {
+  const db = getDatabase();
+  const row = db.query(GIFT_SELECT + " WHERE g.cash_transaction_id = ?").get(cashTransactionId);
+  if (!row) return null;
+  return unscaleGiftRow(row);
+}
+
+/**
+ * @description Record a gift in the register.
+ * @param {Object} data - The gift data
+ * @param {number} data.user_id - The donor
+ * @param {string} data.recipient - Who received the gift (max 60 chars)
+ * @param {string} data.gift_date - ISO-8601 date (YYYY-MM-DD)
+ * @param {number} data.amount - Amount as a decimal
+ * @param {string} data.exemption_type - 'annual', 'small_gift' or 'normal_expenditure'
+ * @param {number} [data.cash_transaction_id] - The withdrawal that paid the gift
+ * @param {string} [data.notes] - Optional notes (max 255 chars)
+ * @returns {Object} The created gift with unscaled amount
+ */
+export function createGift(data) {
+  const db = getDatabase();
+  const result = db.run(
+    `INSERT INTO gifts (user_id, recipient, gift_date, amount, exemption_type, cash_transaction_id, notes)
+     VALUES (?, ?, ?, ?, ?, ?, ?)`,
+    [data.user_id, data.recipient.trim(), data.gift_date, scaleCashAmount(data.amount), data.exemption_type, data.cash_transaction_id || null, data.notes || null],
+  );
+  return getGiftById(result.lastInsertRowid);
+}
+
+/**
+ * @description Update a gift. The link to the withdrawal that paid it is kept.
+ * @param {number} id - The gift ID
+ * @param {Object} data - The updated gift data, as for createGift
+ * @returns {Object|null} The updated gift, or null if not found
+ */
+export function updateGift(id, data) {
+  const db = getDatabase();
+  const result = db.run(
+    `UPDATE gifts SET user_id = ?, recipient = ?, gift_date = ?, amount = ?, exemption_type = ?, notes = ?
+     WHERE id = ?`,
+    [data.user_id, data.recipient.trim(), data.gift_date, scaleCashAmount(data.amount), data.exemption_type, data.notes || null, id],
+  );
+  if (result.changes === 0) return null;
+  return getGiftById(id);
+}
+
+/**
+ * @description Delete a gift from the register. The withdrawal that paid it,
+ * if linked, is left in place.
+ * @param {number} id - The gift ID
+ * @returns {boolean} True if deleted, false if not found
+ */
+export function deleteGift(id) {
+  const db = getDatabase();
+  const result = db.run("DELETE FROM gifts WHERE id = ?", [id]);
+  return result.changes > 0;
+}
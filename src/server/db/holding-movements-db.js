@@ -2,14 +2,8 @@ import { getDatabase } from "./connection.js";
 import { CURRENCY_SCALE_FACTOR } from "../../shared/server-constants.js";
 import { prorateHistoricalPrices } from "./prices-db.js";
 import { updateInvestmentNotes, markInvestmentReplaced, updateAutoFetch } from "./investments-db.js";
-
-/**
- * @description Get the current date as ISO-8601 string (YYYY-MM-DD).
- * @returns {string} Today's date
- */
-function today() {
-  return new Date().toISOString().slice(0, 10);
-}
+import { recalculateBalanceAfter } from "./cash-transactions-db.js";
+import { invalidateAccountValuations } from "./portfolio-valuations-db.js";
 
 /**
  * @description Scale a decimal value for storage (multiply by CURRENCY_SCALE_FACTOR).
@@ -30,49 +24,286 @@ export function unscaleValue(scaledValue) {
 }
 
 /**
- * @description Close the current active holding row and create a new one with
- * updated values. If the holding was created today (same effective_from),
- * updates in place instead (daily granularity — no intra-day SCD2 rows).
+ * @description Tolerance used when comparing unscaled quantities, to absorb
+ * floating-point noise from replaying movements (half of one scaled unit).
+ * @type {number}
+ */
+const QUANTITY_EPSILON = 0.5 / CURRENCY_SCALE_FACTOR;
+
+/**
+ * @description Load the continuous SCD2 chain of holding rows that contains a
+ * given row, oldest first. A chain is a run of rows for the same account and
+ * investment where each row's effective_to matches the next row's
+ * effective_from — a full sale followed by a new holding starts a new chain.
+ * @param {Database} db - The database connection
+ * @param {Object} holding - Any row in the chain (raw/scaled, needs id, account_id, investment_id)
+ * @returns {{ chain: Object[], earlierRows: Object[], laterRows: Object[] }} The chain and the rows either side of it
+ */
+function loadHoldingChain(db, holding) {
+  const rows = db
+    .query(
+      `SELECT id, account_id, investment_id, quantity, average_cost, effective_from, effective_to
+       FROM holdings
+       WHERE account_id = ? AND investment_id = ?
+       ORDER BY effective_from, id`,
+    )
+    .all(holding.account_id, holding.investment_id);
+
+  const index = rows.findIndex(function (r) {
+    return r.id === holding.id;
+  });
+  let startIndex = index;
+  while (startIndex > 0 && rows[startIndex - 1].effective_to === rows[startIndex].effective_from) {
+    startIndex--;
+  }
+  let endIndex = index;
+  while (endIndex < rows.length - 1 && rows[endIndex].effective_to !== null && rows[endIndex].effective_to === rows[endIndex + 1].effective_from) {
+    endIndex++;
+  }
+
+  return {
+    chain: rows.slice(startIndex, endIndex + 1),
+    earlierRows: rows.slice(0, startIndex),
+    laterRows: rows.slice(endIndex + 1),
+  };
+}
+
+/**
+ * @description Find the holding row in force on a movement date, together with
+ * any later rows in the same continuous SCD2 chain.
+ *
+ * If the movement date is before the chain's first row, that row's
+ * effective_from is moved back to the movement date so that the opening
+ * position is treated as held from then. This is the usual case when a
+ * holding is added with zero quantity for a purchase made earlier.
+ * Must be called within an existing transaction.
+ * @param {Database} db - The database connection
+ * @param {Object} holding - The active holding row (raw/scaled from DB)
+ * @param {string} movementDate - The movement date (YYYY-MM-DD)
+ * @returns {{ base: Object, laterRows: Object[] }} The row in force and the rows that start after the movement date
+ * @throws {Error} If the movement date overlaps an earlier, closed holding period
+ */
+function resolveRowForDate(db, holding, movementDate) {
+  const { chain, earlierRows } = loadHoldingChain(db, holding);
+
+  if (movementDate < chain[0].effective_from) {
+    const overlaps = earlierRows.some(function (r) {
+      return r.effective_to > movementDate || r.effective_from === movementDate;
+    });
+    if (overlaps) {
+      throw new Error("Movement date overlaps an earlier holding period for this investment");
+    }
+
+    db.run("UPDATE holdings SET effective_from = ? WHERE id = ?", [movementDate, chain[0].id]);
+    return {
+      base: { ...chain[0], effective_from: movementDate },
+      laterRows: chain.slice(1),
+    };
+  }
+
+  let baseIndex = 0;
+  for (let i = 0; i < chain.length; i++) {
+    if (chain[i].effective_from <= movementDate) {
+      baseIndex = i;
+    }
+  }
+
+  return {
+    base: chain[baseIndex],
+    laterRows: chain.slice(baseIndex + 1),
+  };
+}
+
+/**
+ * @description Write the position resulting from a movement into the SCD2
+ * history at the movement date, then carry the change forward through every
+ * later row in the chain.
+ *
+ * Later movements are replayed twice — once from the old position and once
+ * from the new — so that later sells pick up the revised average cost and
+ * later splits scale the extra quantity by their ratio. Each later row is then
+ * shifted by the difference between the two replays, which keeps any manual
+ * edits made to those rows. The affected movements' book_cost, revised_avg_cost
+ * and (for splits) quantity are rewritten to match.
  * Must be called within an existing transaction.
  * @param {Database} db - The database connection
- * @param {Object} holding - The current holding row (raw/scaled from DB)
- * @param {number} newScaledQuantity - New quantity (scaled)
- * @param {number} newScaledAvgCost - New average cost (scaled)
- * @param {string} dateToday - Today's date (YYYY-MM-DD)
- * @returns {number} The holding row's ID (new row if closed/reopened, same row if updated in place)
+ * @param {Object} base - The row in force on the movement date (raw/scaled from DB)
+ * @param {Object[]} laterRows - Rows starting after the movement date, oldest first (raw/scaled)
+ * @param {number} newQuantity - Quantity after the movement (decimal, unscaled)
+ * @param {number} newAvgCost - Average cost after the movement (decimal, unscaled)
+ * @param {string} movementDate - The movement date (YYYY-MM-DD)
+ * @param {number} movementId - The ID of the movement being applied (excluded from the replay)
+ * @throws {Error} If the change would leave a later position with negative quantity
  */
-function closeAndReopenHolding(db, holding, newScaledQuantity, newScaledAvgCost, dateToday) {
-  // Check the current row's effective_from
-  const currentRow = db.query("SELECT effective_from FROM holdings WHERE id = ?").get(holding.id);
+function applyMovementAtDate(db, base, laterRows, newQuantity, newAvgCost, movementDate, movementId) {
+  const scaledNewQuantity = scaleValue(newQuantity);
+  const scaledNewAvgCost = scaleValue(newAvgCost);
+  const closesPosition = scaledNewQuantity === 0 && laterRows.length === 0;
 
-  if (currentRow && currentRow.effective_from === dateToday) {
+  if (base.effective_from === movementDate) {
     // Same day — update in place (daily granularity, no intra-day SCD2 rows)
-    db.run(
-      "UPDATE holdings SET quantity = ?, average_cost = ? WHERE id = ?",
-      [newScaledQuantity, newScaledAvgCost, holding.id],
-    );
-    return holding.id;
+    if (closesPosition) {
+      db.run("UPDATE holdings SET quantity = 0, effective_to = ? WHERE id = ?", [movementDate, base.id]);
+    } else {
+      db.run("UPDATE holdings SET quantity = ?, average_cost = ? WHERE id = ?", [scaledNewQuantity, scaledNewAvgCost, base.id]);
+    }
+  } else {
+    // Different day — close the row in force and open a new one that ends where it ended
+    db.run("UPDATE holdings SET effective_to = ? WHERE id = ?", [movementDate, base.id]);
+    if (!closesPosition) {
+      db.run(
+        `INSERT INTO holdings (account_id, investment_id, quantity, average_cost, effective_from, effective_to)
+         VALUES (?, ?, ?, ?, ?, ?)`,
+        [base.account_id, base.investment_id, scaledNewQuantity, scaledNewAvgCost, movementDate, base.effective_to],
+      );
+    }
   }
 
-  // Different day — close current row and create new one
-  db.run("UPDATE holdings SET effective_to = ? WHERE id = ?", [dateToday, holding.id]);
+  const laterMovements = db
+    .query(
+      `SELECT hm.id, hm.movement_type, hm.movement_date, hm.quantity, hm.book_cost
+       FROM holding_movements hm
+       JOIN holdings h ON hm.holding_id = h.id
+       WHERE h.account_id = ? AND h.investment_id = ?
+         AND hm.movement_date > ? AND hm.id != ?
+         AND hm.movement_type IN ('buy', 'sell', 'adjustment')
+       ORDER BY hm.movement_date, hm.id`,
+    )
+    .all(base.account_id, base.investment_id, movementDate, movementId);
 
-  const result = db.run(
-    `INSERT INTO holdings (account_id, investment_id, quantity, average_cost, effective_from)
-     VALUES (?, ?, ?, ?, ?)`,
-    [holding.account_id, holding.investment_id, newScaledQuantity, newScaledAvgCost, dateToday],
-  );
+  if (laterRows.length === 0 && laterMovements.length === 0) {
+    return;
+  }
+
+  // Running positions (decimal) without and with the new movement
+  const baseQuantity = unscaleValue(base.quantity);
+  const oldPosition = { quantity: baseQuantity, bookCost: baseQuantity * unscaleValue(base.average_cost) };
+  const newPosition = { quantity: newQuantity, bookCost: newQuantity * newAvgCost };
+
+  function replay(m) {
+    const quantity = unscaleValue(m.quantity);
+
+    if (m.movement_type === "buy") {
+      const addedBookCost = unscaleValue(m.book_cost);
+      oldPosition.quantity += quantity;
+      oldPosition.bookCost += addedBookCost;
+      newPosition.quantity += quantity;
+      newPosition.bookCost += addedBookCost;
+      const revisedAvgCost = newPosition.quantity > 0 ? newPosition.bookCost / newPosition.quantity : 0;
+      db.run("UPDATE holding_movements SET revised_avg_cost = ? WHERE id = ?", [scaleValue(revisedAvgCost), m.id]);
+    } else if (m.movement_type === "sell") {
+      if (newPosition.quantity < quantity - QUANTITY_EPSILON) {
+        throw new Error("Insufficient holding quantity for a later sell on " + m.movement_date);
+      }
+      const oldAvgCost = oldPosition.quantity > 0 ? oldPosition.bookCost / oldPosition.quantity : 0;
+      const newAvgCostAtSell = newPosition.quantity > 0 ? newPosition.bookCost / newPosition.quantity : 0;
+      oldPosition.quantity -= quantity;
+      oldPosition.bookCost -= quantity * oldAvgCost;
+      newPosition.quantity -= quantity;
+      newPosition.bookCost -= quantity * newAvgCostAtSell;
+      db.run("UPDATE holding_movements SET book_cost = ? WHERE id = ?", [scaleValue(quantity * newAvgCostAtSell), m.id]);
+    } else {
+      // Stock split — scale the new position by the same ratio as the recorded one
+      const ratio = oldPosition.quantity > 0 ? quantity / oldPosition.quantity : 1;
+      oldPosition.quantity = quantity;
+      newPosition.quantity = newPosition.quantity * ratio;
+      const revisedAvgCost = newPosition.quantity > 0 ? newPosition.bookCost / newPosition.quantity : 0;
+      db.run(
+        "UPDATE holding_movements SET quantity = ?, book_cost = ?, revised_avg_cost = ? WHERE id = ?",
+        [scaleValue(newPosition.quantity), scaleValue(newPosition.bookCost), scaleValue(revisedAvgCost), m.id],
+      );
+    }
+  }
+
+  let movementIndex = 0;
+  for (const row of laterRows) {
+    // Movements up to and including the row's start date are reflected in the row
+    while (movementIndex < laterMovements.length && laterMovements[movementIndex].movement_date <= row.effective_from) {
+      replay(laterMovements[movementIndex]);
+      movementIndex++;
+    }
+
+    const storedQuantity = unscaleValue(row.quantity);
+    const storedBookCost = storedQuantity * unscaleValue(row.average_cost);
+    const quantity = storedQuantity + (newPosition.quantity - oldPosition.quantity);
+    const bookCost = storedBookCost + (newPosition.bookCost - oldPosition.bookCost);
+
+    if (quantity < -QUANTITY_EPSILON) {
+      throw new Error("Insufficient holding quantity from " + row.effective_from);
+    }
+
+    const scaledQuantity = Math.max(scaleValue(quantity), 0);
+    const avgCost = scaledQuantity > 0 ? bookCost / quantity : unscaleValue(row.average_cost);
 
-  return Number(result.lastInsertRowid);
+    if (row.effective_to === null && scaledQuantity === 0) {
+      // The position is now fully sold — close the active row as a same-day full sale would
+      db.run("UPDATE holdings SET quantity = 0, effective_to = ? WHERE id = ?", [row.effective_from, row.id]);
+    } else {
+      db.run("UPDATE holdings SET quantity = ?, average_cost = ? WHERE id = ?", [scaledQuantity, scaleValue(avgCost), row.id]);
+    }
+
+    // Re-anchor both replays on the stored row so manual edits are respected
+    oldPosition.quantity = storedQuantity;
+    oldPosition.bookCost = storedBookCost;
+    newPosition.quantity = quantity;
+    newPosition.bookCost = bookCost;
+  }
+
+  while (movementIndex < laterMovements.length) {
+    replay(laterMovements[movementIndex]);
+    movementIndex++;
+  }
+}
+
+/**
+ * @description Get the cash an account could spend on a date without its
+ * balance going negative then or later: the lowest of the balance on the date
+ * and every later running balance. The cash history starts at the first cash
+ * transaction recorded for the account (normally its opening balance deposit).
+ * Before that, or when the account has no cash transactions because its cash
+ * was only set on the account itself, only the current balance counts.
+ * Must be called within an existing transaction.
+ * @param {Database} db - The database connection
+ * @param {Object} account - The account row with id and cash_balance (scaled)
+ * @param {string} date - ISO-8601 date (YYYY-MM-DD)
+ * @returns {number} Available cash (scaled)
+ */
+function getAvailableCashFrom(db, account, date) {
+  const first = db.query("SELECT transaction_date FROM cash_transactions WHERE account_id = ? ORDER BY id LIMIT 1").get(account.id);
+  if (!first || date < first.transaction_date) {
+    return account.cash_balance;
+  }
+
+  const atDate = db
+    .query(
+      `SELECT balance_after FROM cash_transactions
+       WHERE account_id = ? AND transaction_date <= ?
+       ORDER BY transaction_date DESC, id DESC
+       LIMIT 1`,
+    )
+    .get(account.id, date);
+  const later = db
+    .query("SELECT MIN(balance_after) AS lowest FROM cash_transactions WHERE account_id = ? AND transaction_date > ?")
+    .get(account.id, date);
+
+  let available = atDate ? atDate.balance_after : account.cash_balance;
+  if (later.lowest !== null) {
+    available = Math.min(available, later.lowest);
+  }
+  return Math.min(available, account.cash_balance);
 }
 
 /**
  * @description Create a buy movement and atomically update the holding and account.
  *
- * SCD2: The current holding row is closed and a new row is created with the
- * updated quantity and average cost. The movement references the old (closed) row.
+ * SCD2: The holding row in force on the movement date is closed and a new row
+ * is created from that date with the updated quantity and average cost. The
+ * movement references the old (closed) row. Backdated buys are inserted at
+ * their true date and every later row for the account and investment is
+ * recomputed, so historic valuations stay correct.
  *
- * The full Total Consideration is deducted from the account cash balance.
+ * The full Total Consideration is deducted from the account cash balance,
+ * which must cover it on the movement date and at every later date.
  * The holding quantity is increased by the buy quantity.
  * The average cost is recalculated as:
  *   (Old Book Cost + Total Consideration - Deductible Costs) / New Quantity
@@ -92,86 +323,106 @@ function closeAndReopenHolding(db, holding, newScaledQuantity, newScaledAvgCost,
 export function createBuyMovement(data) {
   const db = getDatabase();
 
+  db.exec("BEGIN");
+  try {
+    const movementId = insertBuyMovement(data);
+    db.exec("COMMIT");
+    return getMovementById(movementId);
+  } catch (err) {
+    db.exec("ROLLBACK");
+    throw err;
+  }
+}
+
+/**
+ * @description Insert a buy movement and apply it to the holding and the
+ * account's cash. Must be called inside an open database transaction.
+ * @param {Object} data - The movement data, as for createBuyMovement
+ * @returns {number} The new movement ID
+ * @throws {Error} If holding not found, or insufficient cash balance
+ */
+export function insertBuyMovement(data) {
+  const db = getDatabase();
+
   const deductibleCosts = data.deductible_costs || 0;
   const scaledQuantity = scaleValue(data.quantity);
   const scaledConsideration = scaleValue(data.total_consideration);
   const scaledDeductible = scaleValue(deductibleCosts);
 
-  db.exec("BEGIN");
-  try {
-    // Read the current holding (scaled values direct from DB)
-    const holding = db.query("SELECT id, account_id, investment_id, quantity, average_cost FROM holdings WHERE id = ? AND effective_to IS NULL").get(data.holding_id);
+  // Read the current holding (scaled values direct from DB)
+  const holding = db.query("SELECT id, account_id, investment_id, quantity, average_cost FROM holdings WHERE id = ? AND effective_to IS NULL").get(data.holding_id);
 
-    if (!holding) {
-      throw new Error("Holding not found");
-    }
+  if (!holding) {
+    throw new Error("Holding not found");
+  }
 
-    // Read the current account cash balance
-    const account = db.query("SELECT id, cash_balance FROM accounts WHERE id = ?").get(holding.account_id);
+  // Read the current account cash balance
+  const account = db.query("SELECT id, cash_balance FROM accounts WHERE id = ?").get(holding.account_id);
 
-    if (!account) {
-      throw new Error("Account not found");
-    }
+  if (!account) {
+    throw new Error("Account not found");
+  }
 
-    if (account.cash_balance < scaledConsideration) {
-      throw new Error("Insufficient cash balance");
-    }
+  if (getAvailableCashFrom(db, account, data.movement_date) < scaledConsideration) {
+    throw new Error("Insufficient cash balance");
+  }
 
-    // Calculate new average cost using unscaled decimals to avoid overflow
-    const oldQuantity = unscaleValue(holding.quantity);
-    const oldAvgCost = unscaleValue(holding.average_cost);
-    const oldBookCost = oldQuantity * oldAvgCost;
-    const addedBookCost = data.total_consideration - deductibleCosts;
-    const newQuantity = oldQuantity + data.quantity;
+  // Find the row in force on the movement date (earlier than the active row if backdated)
+  const { base, laterRows } = resolveRowForDate(db, holding, data.movement_date);
 
-    // Guard against divide-by-zero (shouldn't happen on a buy, but be safe)
-    const newAvgCost = newQuantity > 0 ? (oldBookCost + addedBookCost) / newQuantity : 0;
+  // Calculate new average cost using unscaled decimals to avoid overflow
+  const oldQuantity = unscaleValue(base.quantity);
+  const oldAvgCost = unscaleValue(base.average_cost);
+  const oldBookCost = oldQuantity * oldAvgCost;
+  const addedBookCost = data.total_consideration - deductibleCosts;
+  const newQuantity = oldQuantity + data.quantity;
 
-    const scaledNewQuantity = scaleValue(newQuantity);
-    const scaledNewAvgCost = scaleValue(newAvgCost);
-    const scaledBookCost = scaleValue(addedBookCost);
+  // Guard against divide-by-zero (shouldn't happen on a buy, but be safe)
+  const newAvgCost = newQuantity > 0 ? (oldBookCost + addedBookCost) / newQuantity : 0;
 
-    // Insert the movement record (references the old/closing holding row)
-    const result = db.run(
-      `INSERT INTO holding_movements (holding_id, movement_type, movement_date, quantity, movement_value, book_cost, deductible_costs, revised_avg_cost, notes)
-       VALUES (?, 'buy', ?, ?, ?, ?, ?, ?, ?)`,
-      [data.holding_id, data.movement_date, scaledQuantity, scaledConsideration, scaledBookCost, scaledDeductible, scaledNewAvgCost, data.notes || null],
-    );
+  const scaledNewAvgCost = scaleValue(newAvgCost);
+  const scaledBookCost = scaleValue(addedBookCost);
 
-    // SCD2: close current holding row and create new one
-    const dateToday = today();
-    closeAndReopenHolding(db, holding, scaledNewQuantity, scaledNewAvgCost, dateToday);
+  // Insert the movement record (references the old/closing holding row)
+  const result = db.run(
+    `INSERT INTO holding_movements (holding_id, movement_type, movement_date, quantity, movement_value, book_cost, deductible_costs, revised_avg_cost, notes)
+     VALUES (?, 'buy', ?, ?, ?, ?, ?, ?, ?)`,
+    [base.id, data.movement_date, scaledQuantity, scaledConsideration, scaledBookCost, scaledDeductible, scaledNewAvgCost, data.notes || null],
+  );
+  const movementId = Number(result.lastInsertRowid);
 
-    // Deduct total consideration from account cash balance
-    db.run("UPDATE accounts SET cash_balance = cash_balance - ? WHERE id = ?", [scaledConsideration, holding.account_id]);
+  // SCD2: write the new position at the movement date and recompute later rows
+  applyMovementAtDate(db, base, laterRows, newQuantity, newAvgCost, data.movement_date, movementId);
 
-    // Create matching cash_transaction for audit trail (balance already adjusted above)
-    const movementId = result.lastInsertRowid;
-    const investmentRow = db.query("SELECT i.description FROM holdings h JOIN investments i ON h.investment_id = i.id WHERE h.id = ?").get(data.holding_id);
-    const investmentName = investmentRow ? investmentRow.description : "Unknown";
-    const cashNotes = "Buy: " + investmentName + (data.notes ? " — " + data.notes : "");
+  // Deduct total consideration from account cash balance
+  db.run("UPDATE accounts SET cash_balance = cash_balance - ? WHERE id = ?", [scaledConsideration, holding.account_id]);
 
-    db.run(
-      `INSERT INTO cash_transactions (account_id, holding_movement_id, transaction_type, transaction_date, amount, notes)
-       VALUES (?, ?, 'buy', ?, ?, ?)`,
-      [holding.account_id, movementId, data.movement_date, scaledConsideration, cashNotes],
-    );
+  // Create matching cash_transaction for audit trail (balance already adjusted above)
+  const investmentRow = db.query("SELECT i.description FROM holdings h JOIN investments i ON h.investment_id = i.id WHERE h.id = ?").get(data.holding_id);
+  const investmentName = investmentRow ? investmentRow.description : "Unknown";
+  const cashNotes = "Buy: " + investmentName + (data.notes ? " — " + data.notes : "");
 
-    db.exec("COMMIT");
+  db.run(
+    `INSERT INTO cash_transactions (account_id, holding_movement_id, transaction_type, transaction_date, amount, notes)
+     VALUES (?, ?, 'buy', ?, ?, ?)`,
+    [holding.account_id, movementId, data.movement_date, scaledConsideration, cashNotes],
+  );
 
-    return getMovementById(movementId);
-  } catch (err) {
-    db.exec("ROLLBACK");
-    throw err;
-  }
+  // Keep the running balance correct for historic valuations
+  recalculateBalanceAfter(holding.account_id);
+  invalidateAccountValuations(holding.account_id, data.movement_date);
+
+  return movementId;
 }
 
 /**
  * @description Create a sell movement and atomically update the holding and account.
  *
- * SCD2: The current holding row is closed and a new row is created with the
- * reduced quantity (unless it's a full sale, in which case no new row is created).
- * The movement references the old (closed) row.
+ * SCD2: The holding row in force on the movement date is closed and a new row
+ * is created from that date with the reduced quantity (unless it's a full sale
+ * of the current position, in which case no new row is created). The movement
+ * references the old (closed) row. Backdated sells are inserted at their true
+ * date and every later row for the account and investment is recomputed.
  *
  * The Total Consideration is added to the account cash balance.
  * The holding quantity is reduced by the sell quantity.
@@ -193,97 +444,94 @@ export function createBuyMovement(data) {
 export function createSellMovement(data) {
   const db = getDatabase();
 
+  db.exec("BEGIN");
+  try {
+    const movementId = insertSellMovement(data);
+    db.exec("COMMIT");
+    return getMovementById(movementId);
+  } catch (err) {
+    db.exec("ROLLBACK");
+    throw err;
+  }
+}
+
+/**
+ * @description Insert a sell movement and apply it to the holding and the
+ * account's cash. Must be called inside an open database transaction.
+ * @param {Object} data - The movement data, as for createSellMovement
+ * @returns {number} The new movement ID
+ * @throws {Error} If holding not found, or insufficient holding quantity
+ */
+export function insertSellMovement(data) {
+  const db = getDatabase();
+
   const deductibleCosts = data.deductible_costs || 0;
   const scaledQuantity = scaleValue(data.quantity);
   const scaledConsideration = scaleValue(data.total_consideration);
   const scaledDeductible = scaleValue(deductibleCosts);
 
-  db.exec("BEGIN");
-  try {
-    // Read the current holding (scaled values direct from DB)
-    const holding = db.query("SELECT id, account_id, investment_id, quantity, average_cost FROM holdings WHERE id = ? AND effective_to IS NULL").get(data.holding_id);
+  // Read the current holding (scaled values direct from DB)
+  const holding = db.query("SELECT id, account_id, investment_id, quantity, average_cost FROM holdings WHERE id = ? AND effective_to IS NULL").get(data.holding_id);
 
-    if (!holding) {
-      throw new Error("Holding not found");
-    }
+  if (!holding) {
+    throw new Error("Holding not found");
+  }
 
-    if (holding.quantity < scaledQuantity) {
-      throw new Error("Insufficient holding quantity");
-    }
+  // Find the row in force on the movement date (earlier than the active row if backdated)
+  const { base, laterRows } = resolveRowForDate(db, holding, data.movement_date);
 
-    // Book cost = sell quantity x average cost (unscaled to avoid overflow)
-    const sellQuantity = data.quantity;
-    const avgCost = unscaleValue(holding.average_cost);
-    const bookCost = sellQuantity * avgCost;
-    const scaledBookCost = scaleValue(bookCost);
+  if (base.quantity < scaledQuantity) {
+    throw new Error("Insufficient holding quantity");
+  }
 
-    // New quantity after sell
-    const newScaledQuantity = holding.quantity - scaledQuantity;
+  // Book cost = sell quantity x average cost (unscaled to avoid overflow)
+  const sellQuantity = data.quantity;
+  const avgCost = unscaleValue(base.average_cost);
+  const bookCost = sellQuantity * avgCost;
+  const scaledBookCost = scaleValue(bookCost);
 
-    // Insert the movement record (references the old/closing holding row)
-    const result = db.run(
-      `INSERT INTO holding_movements (holding_id, movement_type, movement_date, quantity, movement_value, book_cost, deductible_costs, notes)
-       VALUES (?, 'sell', ?, ?, ?, ?, ?, ?)`,
-      [data.holding_id, data.movement_date, scaledQuantity, scaledConsideration, scaledBookCost, scaledDeductible, data.notes || null],
-    );
+  // Insert the movement record (references the old/closing holding row)
+  const result = db.run(
+    `INSERT INTO holding_movements (holding_id, movement_type, movement_date, quantity, movement_value, book_cost, deductible_costs, notes)
+     VALUES (?, 'sell', ?, ?, ?, ?, ?, ?)`,
+    [base.id, data.movement_date, scaledQuantity, scaledConsideration, scaledBookCost, scaledDeductible, data.notes || null],
+  );
+  const movementId = Number(result.lastInsertRowid);
 
-    // SCD2: update the holding
-    const dateToday = today();
-    const currentRow = db.query("SELECT effective_from FROM holdings WHERE id = ?").get(holding.id);
-
-    if (currentRow && currentRow.effective_from === dateToday) {
-      // Same day — update in place
-      if (newScaledQuantity > 0) {
-        db.run("UPDATE holdings SET quantity = ? WHERE id = ?", [newScaledQuantity, holding.id]);
-      } else {
-        // Full sale on same day — close the row
-        db.run("UPDATE holdings SET quantity = 0, effective_to = ? WHERE id = ?", [dateToday, holding.id]);
-      }
-    } else {
-      // Different day — close current row
-      db.run("UPDATE holdings SET effective_to = ? WHERE id = ?", [dateToday, holding.id]);
-
-      // Only create new row if there's remaining quantity (not a full sale)
-      if (newScaledQuantity > 0) {
-        db.run(
-          `INSERT INTO holdings (account_id, investment_id, quantity, average_cost, effective_from)
-           VALUES (?, ?, ?, ?, ?)`,
-          [holding.account_id, holding.investment_id, newScaledQuantity, holding.average_cost, dateToday],
-        );
-      }
-    }
+  // SCD2: write the reduced position at the movement date and recompute later rows
+  const newQuantity = unscaleValue(base.quantity - scaledQuantity);
+  applyMovementAtDate(db, base, laterRows, newQuantity, avgCost, data.movement_date, movementId);
 
-    // Add net proceeds (total consideration minus deductible costs) to account cash balance
-    const scaledNetProceeds = scaledConsideration - scaledDeductible;
-    db.run("UPDATE accounts SET cash_balance = cash_balance + ? WHERE id = ?", [scaledNetProceeds, holding.account_id]);
+  // Add net proceeds (total consideration minus deductible costs) to account cash balance
+  const scaledNetProceeds = scaledConsideration - scaledDeductible;
+  db.run("UPDATE accounts SET cash_balance = cash_balance + ? WHERE id = ?", [scaledNetProceeds, holding.account_id]);
 
-    // Create matching cash_transaction for audit trail (balance already adjusted above)
-    const movementId = result.lastInsertRowid;
-    const investmentRow = db.query("SELECT i.description FROM holdings h JOIN investments i ON h.investment_id = i.id WHERE h.id = ?").get(data.holding_id);
-    const investmentName = investmentRow ? investmentRow.description : "Unknown";
-    const cashNotes = "Sell: " + investmentName + (data.notes ? " — " + data.notes : "");
+  // Create matching cash_transaction for audit trail (balance already adjusted above)
+  const investmentRow = db.query("SELECT i.description FROM holdings h JOIN investments i ON h.investment_id = i.id WHERE h.id = ?").get(data.holding_id);
+  const investmentName = investmentRow ? investmentRow.description : "Unknown";
+  const cashNotes = "Sell: " + investmentName + (data.notes ? " — " + data.notes : "");
 
-    db.run(
-      `INSERT INTO cash_transactions (account_id, holding_movement_id, transaction_type, transaction_date, amount, notes)
-       VALUES (?, ?, 'sell', ?, ?, ?)`,
-      [holding.account_id, movementId, data.movement_date, scaledNetProceeds, cashNotes],
-    );
+  db.run(
+    `INSERT INTO cash_transactions (account_id, holding_movement_id, transaction_type, transaction_date, amount, notes)
+     VALUES (?, ?, 'sell', ?, ?, ?)`,
+    [holding.account_id, movementId, data.movement_date, scaledNetProceeds, cashNotes],
+  );
 
-    db.exec("COMMIT");
+  // Keep the running balance correct for historic valuations
+  recalculateBalanceAfter(holding.account_id);
+  invalidateAccountValuations(holding.account_id, data.movement_date);
 
-    return getMovementById(movementId);
-  } catch (err) {
-    db.exec("ROLLBACK");
-    throw err;
-  }
+  return movementId;
 }
 
 /**
  * @description Create a stock split adjustment and atomically update the holding.
  *
- * SCD2: The current holding row is closed and a new row is created with the
- * new quantity and recalculated average cost (preserving book cost).
- * The movement references the old (closed) row.
+ * SCD2: The holding row in force on the movement date is closed and a new
+ * row is created from that date with the new quantity and recalculated
+ * average cost (preserving book cost). The movement references the old
+ * (closed) row. A backdated split is inserted at its true date and every
+ * later row for the account and investment is recomputed.
  *
  * A stock split changes the quantity and average cost of a holding such that
  * the total book cost remains constant. For example, a 1:100 forward split
@@ -316,9 +564,12 @@ export function createSplitMovement(data) {
       throw new Error("Holding not found");
     }
 
+    // Find the row in force on the movement date (earlier than the active row if backdated)
+    const { base, laterRows } = resolveRowForDate(db, holding, data.movement_date);
+
     // Work in unscaled decimals to avoid overflow
-    const oldQuantity = unscaleValue(holding.quantity);
-    const oldAvgCost = unscaleValue(holding.average_cost);
+    const oldQuantity = unscaleValue(base.quantity);
+    const oldAvgCost = unscaleValue(base.average_cost);
     const bookCost = oldQuantity * oldAvgCost;
 
     if (data.new_quantity === oldQuantity) {
@@ -336,16 +587,17 @@ export function createSplitMovement(data) {
     const result = db.run(
       `INSERT INTO holding_movements (holding_id, movement_type, movement_date, quantity, movement_value, book_cost, deductible_costs, revised_avg_cost, notes)
        VALUES (?, 'adjustment', ?, ?, 0, ?, 0, ?, ?)`,
-      [data.holding_id, data.movement_date, scaledNewQuantity, scaledBookCost, scaledNewAvgCost, data.notes || null],
+      [base.id, data.movement_date, scaledNewQuantity, scaledBookCost, scaledNewAvgCost, data.notes || null],
     );
+    const movementId = Number(result.lastInsertRowid);
 
-    // SCD2: close current holding row and create new one
-    const dateToday = today();
-    closeAndReopenHolding(db, holding, scaledNewQuantity, scaledNewAvgCost, dateToday);
+    // SCD2: write the split position at the movement date and recompute later rows
+    applyMovementAtDate(db, base, laterRows, data.new_quantity, newAvgCost, data.movement_date, movementId);
+    invalidateAccountValuations(holding.account_id, data.movement_date);
 
     db.exec("COMMIT");
 
-    return getMovementById(result.lastInsertRowid);
+    return getMovementById(movementId);
   } catch (err) {
     db.exec("ROLLBACK");
     throw err;
@@ -443,6 +695,8 @@ export function createReplacementMovement(data) {
       [holding.account_id, data.new_investment_id, scaledNewQuantity, scaledNewAvgCost, data.movement_date],
     );
 
+    invalidateAccountValuations(holding.account_id, data.movement_date);
+
     db.exec("COMMIT");
 
     // Post-transaction operations (not rolled back if they fail, but these are
@@ -509,6 +763,433 @@ export function createReplacementMovement(data) {
   }
 }
 
+/**
+ * @description Work out the position held at the start of a holding chain by
+ * walking its movements backwards from the final position. A closed chain
+ * (fully sold) ends at zero; an open chain ends at the active row. Any manual
+ * edits to the holding are therefore folded into the opening position.
+ * @param {Object[]} chain - The chain's holding rows, oldest first (raw/scaled)
+ * @param {Object[]} movements - The chain's movements, oldest first (raw/scaled)
+ * @returns {{ quantity: number, bookCost: number }} Opening position (decimal, unscaled)
+ */
+function deriveChainOpening(chain, movements) {
+  const last = chain[chain.length - 1];
+  let quantity = 0;
+  let bookCost = 0;
+  if (last.effective_to === null) {
+    quantity = unscaleValue(last.quantity);
+    bookCost = quantity * unscaleValue(last.average_cost);
+  }
+
+  const rowsById = {};
+  for (const row of chain) {
+    rowsById[row.id] = row;
+  }
+
+  for (let i = movements.length - 1; i >= 0; i--) {
+    const m = movements[i];
+    if (m.movement_type === "buy") {
+      quantity -= unscaleValue(m.quantity);
+      bookCost -= unscaleValue(m.book_cost);
+    } else if (m.movement_type === "sell") {
+      quantity += unscaleValue(m.quantity);
+      bookCost += unscaleValue(m.book_cost);
+    } else if (m.movement_type === "adjustment") {
+      // The referenced row holds the pre-split quantity unless the split was applied in place
+      const ref = rowsById[m.holding_id];
+      if (ref && ref.quantity !== m.quantity) {
+        quantity = unscaleValue(ref.quantity);
+      }
+    }
+  }
+
+  return {
+    quantity: Math.max(quantity, 0),
+    bookCost: Math.max(bookCost, 0),
+  };
+}
+
+/**
+ * @description Regenerate a holding chain's SCD2 rows by replaying its full
+ * movement sequence from the opening position. One row is written per
+ * movement date (daily granularity). Existing rows are reused where their
+ * effective_from still matches, new rows are inserted for new dates and
+ * rows no longer needed are deleted. Each movement's holding_id, book_cost
+ * and revised_avg_cost are rewritten along with any edited fields.
+ * Must be called within an existing transaction.
+ * @param {Database} db - The database connection
+ * @param {{ chain: Object[], earlierRows: Object[], laterRows: Object[] }} chainInfo - From loadHoldingChain
+ * @param {{ quantity: number, bookCost: number }} opening - Opening position (decimal, unscaled)
+ * @param {Object[]} movements - The chain's buy, sell and adjustment movements after the change (raw/scaled)
+ * @throws {Error} If the sequence sells more than is held, or would reopen a holding bought again since
+ */
+function replayHoldingChain(db, chainInfo, opening, movements) {
+  const { chain, earlierRows, laterRows } = chainInfo;
+  const first = chain[0];
+
+  movements.sort(function (a, b) {
+    if (a.movement_date !== b.movement_date) return a.movement_date < b.movement_date ? -1 : 1;
+    return a.id - b.id;
+  });
+
+  let openingDate = first.effective_from;
+  if (movements.length > 0 && movements[0].movement_date < openingDate) {
+    openingDate = movements[0].movement_date;
+  }
+  const overlaps = earlierRows.some(function (r) {
+    return r.effective_to > openingDate || r.effective_from === openingDate;
+  });
+  if (overlaps) {
+    throw new Error("Movement date overlaps an earlier holding period for this investment");
+  }
+
+  // Replay the movements, collecting one position per movement date
+  const position = { quantity: opening.quantity, bookCost: opening.bookCost };
+  const states = [{ date: openingDate, quantity: position.quantity, bookCost: position.bookCost, movements: [] }];
+
+  for (const m of movements) {
+    let state = states[states.length - 1];
+    if (m.movement_date !== state.date) {
+      state = { date: m.movement_date, movements: [] };
+      states.push(state);
+    }
+
+    const quantity = unscaleValue(m.quantity);
+    if (m.movement_type === "buy") {
+      position.quantity += quantity;
+      position.bookCost += unscaleValue(m.book_cost);
+      m.revised_avg_cost = scaleValue(position.quantity > 0 ? position.bookCost / position.quantity : 0);
+    } else if (m.movement_type === "sell") {
+      if (position.quantity < quantity - QUANTITY_EPSILON) {
+        throw new Error("Insufficient holding quantity on " + m.movement_date);
+      }
+      const avgCost = position.quantity > 0 ? position.bookCost / position.quantity : 0;
+      m.book_cost = scaleValue(quantity * avgCost);
+      position.quantity -= quantity;
+      position.bookCost -= quantity * avgCost;
+    } else {
+      // Stock split — the recorded quantity is the new total, book cost is unchanged
+      position.quantity = quantity;
+      m.book_cost = scaleValue(position.bookCost);
+      m.revised_avg_cost = scaleValue(quantity > 0 ? position.bookCost / quantity : 0);
+    }
+
+    state.quantity = position.quantity;
+    state.bookCost = position.bookCost;
+    state.movements.push(m);
+  }
+
+  // A full sale closes the previous row rather than opening an empty one
+  const closesPosition = scaleValue(position.quantity) <= 0;
+  let closingState = null;
+  if (closesPosition && states.length > 1) {
+    closingState = states.pop();
+  }
+
+  if (!closesPosition && laterRows.length > 0) {
+    throw new Error("Change would reopen a holding that has since been bought again");
+  }
+
+  const reusableRows = {};
+  for (const row of chain.slice(1)) {
+    reusableRows[row.effective_from] = row;
+  }
+
+  const rowIds = [];
+  let lastAvgCost = 0;
+  for (let i = 0; i < states.length; i++) {
+    const state = states[i];
+    let effectiveTo = null;
+    if (i < states.length - 1) {
+      effectiveTo = states[i + 1].date;
+    } else if (closingState) {
+      effectiveTo = closingState.date;
+    } else if (closesPosition) {
+      effectiveTo = state.date;
+    }
+
+    const scaledQuantity = Math.max(scaleValue(state.quantity), 0);
+    if (scaledQuantity > 0) {
+      lastAvgCost = state.bookCost / state.quantity;
+    }
+    const scaledAvgCost = scaleValue(lastAvgCost);
+
+    if (i === 0) {
+      db.run(
+        "UPDATE holdings SET quantity = ?, average_cost = ?, effective_from = ?, effective_to = ? WHERE id = ?",
+        [scaledQuantity, scaledAvgCost, state.date, effectiveTo, first.id],
+      );
+      rowIds.push(first.id);
+    } else if (reusableRows[state.date]) {
+      const row = reusableRows[state.date];
+      delete reusableRows[state.date];
+      db.run(
+        "UPDATE holdings SET quantity = ?, average_cost = ?, effective_to = ? WHERE id = ?",
+        [scaledQuantity, scaledAvgCost, effectiveTo, row.id],
+      );
+      rowIds.push(row.id);
+    } else {
+      const result = db.run(
+        `INSERT INTO holdings (account_id, investment_id, quantity, average_cost, effective_from, effective_to)
+         VALUES (?, ?, ?, ?, ?, ?)`,
+        [first.account_id, first.investment_id, scaledQuantity, scaledAvgCost, state.date, effectiveTo],
+      );
+      rowIds.push(Number(result.lastInsertRowid));
+    }
+  }
+
+  // Each movement references the row it closed: the first movement on a date
+  // closes the previous row, later same-day movements update the new row in place
+  function writeMovement(m, holdingId) {
+    db.run(
+      `UPDATE holding_movements
+       SET holding_id = ?, movement_date = ?, quantity = ?, movement_value = ?, book_cost = ?, deductible_costs = ?, revised_avg_cost = ?, notes = ?
+       WHERE id = ?`,
+      [holdingId, m.movement_date, m.quantity, m.movement_value, m.book_cost, m.deductible_costs, m.revised_avg_cost, m.notes, m.id],
+    );
+  }
+
+  for (let i = 0; i < states.length; i++) {
+    states[i].movements.forEach(function (m, index) {
+      writeMovement(m, i > 0 && index === 0 ? rowIds[i - 1] : rowIds[i]);
+    });
+  }
+  if (closingState) {
+    for (const m of closingState.movements) {
+      writeMovement(m, rowIds[rowIds.length - 1]);
+    }
+  }
+
+  // Remove rows for dates that no longer have any movements
+  for (const date of Object.keys(reusableRows)) {
+    db.run("DELETE FROM holdings WHERE id = ?", [reusableRows[date].id]);
+  }
+}
+
+/**
+ * @description Load a movement together with its holding chain, the chain's
+ * full movement sequence and the opening position, ready for a replay.
+ * Must be called within an existing transaction.
+ * @param {Database} db - The database connection
+ * @param {number} id - The movement ID
+ * @returns {Object|null} Object with { accountId, chainInfo, movements, opening, movement }, or null if not found
+ * @throws {Error} If the movement is, or belongs to a holding affected by, a replacement
+ */
+function loadMovementForReplay(db, id) {
+  const movement = db.query("SELECT id, holding_id, movement_type FROM holding_movements WHERE id = ?").get(id);
+  if (!movement) return null;
+
+  if (movement.movement_type === "replacement") {
+    throw new Error("Replacement movements cannot be edited or deleted");
+  }
+
+  const holding = db.query("SELECT id, account_id, investment_id FROM holdings WHERE id = ?").get(movement.holding_id);
+  const chainInfo = loadHoldingChain(db, holding);
+  const rowIds = chainInfo.chain.map(function (r) {
+    return r.id;
+  });
+
+  const movements = db
+    .query(
+      `SELECT id, holding_id, movement_type, movement_date, quantity, movement_value, book_cost, deductible_costs, revised_avg_cost, notes
+       FROM holding_movements
+       WHERE holding_id IN (` + rowIds.map(function () { return "?"; }).join(", ") + `)
+       ORDER BY movement_date, id`,
+    )
+    .all(...rowIds);
+
+  if (movements.some(function (m) { return m.movement_type === "replacement"; })) {
+    throw new Error("Movements on a replaced holding cannot be edited or deleted");
+  }
+
+  return {
+    accountId: holding.account_id,
+    chainInfo: chainInfo,
+    movements: movements,
+    opening: deriveChainOpening(chainInfo.chain, movements),
+    movement: movements.find(function (m) { return m.id === id; }),
+  };
+}
+
+/**
+ * @description Bring the cash transaction linked to a buy or sell movement into
+ * line with the movement, adjusting the account cash balance by the difference.
+ * When removing, the cash transaction is deleted and its effect reversed.
+ * The running balances are checked afterwards by checkCashFrom.
+ * Must be called within an existing transaction.
+ * @param {Database} db - The database connection
+ * @param {number} accountId - The account ID
+ * @param {Object} movement - The movement (raw/scaled)
+ * @param {boolean} remove - True to delete the cash transaction
+ */
+function syncMovementCashTransaction(db, accountId, movement, remove) {
+  if (movement.movement_type !== "buy" && movement.movement_type !== "sell") return;
+
+  const tx = db.query("SELECT id, amount FROM cash_transactions WHERE holding_movement_id = ?").get(movement.id);
+  if (!tx) return;
+
+  const isBuy = movement.movement_type === "buy";
+  let amount = 0;
+  if (!remove) {
+    amount = isBuy ? movement.movement_value : movement.movement_value - movement.deductible_costs;
+  }
+
+  // Buys take cash out of the account, sells put it in
+  const balanceChange = isBuy ? tx.amount - amount : amount - tx.amount;
+  db.run("UPDATE accounts SET cash_balance = cash_balance + ? WHERE id = ?", [balanceChange, accountId]);
+
+  if (remove) {
+    db.run("DELETE FROM cash_transactions WHERE id = ?", [tx.id]);
+    return;
+  }
+
+  const investmentRow = db.query("SELECT i.description FROM holdings h JOIN investments i ON h.investment_id = i.id WHERE h.id = ?").get(movement.holding_id);
+  const investmentName = investmentRow ? investmentRow.description : "Unknown";
+  const cashNotes = (isBuy ? "Buy: " : "Sell: ") + investmentName + (movement.notes ? " — " + movement.notes : "");
+
+  db.run(
+    "UPDATE cash_transactions SET transaction_date = ?, amount = ?, notes = ? WHERE id = ?",
+    [movement.movement_date, amount, cashNotes, tx.id],
+  );
+}
+
+/**
+ * @description Check that an account's cash balance does not go below zero on
+ * a date or at any later date, once balance_after has been recalculated after
+ * a buy or sell is edited or deleted. Uses the same rule as a new buy.
+ * Must be called within an existing transaction.
+ * @param {Database} db - The database connection
+ * @param {number} accountId - The account ID
+ * @param {string} date - ISO-8601 date to check from (YYYY-MM-DD)
+ * @throws {Error} If the cash balance would go below zero
+ */
+function checkCashFrom(db, accountId, date) {
+  const account = db.query("SELECT id, cash_balance FROM accounts WHERE id = ?").get(accountId);
+  if (getAvailableCashFrom(db, account, date) < 0) {
+    throw new Error("Insufficient cash balance");
+  }
+}
+
+/**
+ * @description Edit a buy, sell or adjustment (stock split) movement.
+ *
+ * The holding's full movement sequence is replayed to regenerate its SCD2
+ * rows, every movement's book_cost and revised_avg_cost, the linked cash
+ * transaction and the account's balance_after values. All changes are made
+ * in a single database transaction.
+ *
+ * @param {number} id - The movement ID
+ * @param {Object} data - The edited fields (omitted fields are left unchanged)
+ * @param {string} [data.movement_date] - ISO-8601 date (YYYY-MM-DD)
+ * @param {number} [data.quantity] - Buy/sell quantity (decimal, unscaled)
+ * @param {number} [data.total_consideration] - Buy/sell total consideration in GBP (decimal, unscaled)
+ * @param {number} [data.deductible_costs] - Buy/sell deductible costs in GBP (decimal, unscaled)
+ * @param {number} [data.new_quantity] - Adjustment quantity after the split (decimal, unscaled)
+ * @param {string|null} [data.notes] - Notes (max 255 chars)
+ * @returns {Object|null} The updated movement with unscaled values, or null if not found
+ * @throws {Error} If the edit would oversell the holding or overdraw the cash balance
+ */
+export function updateMovement(id, data) {
+  const db = getDatabase();
+
+  db.exec("BEGIN");
+  try {
+    const context = loadMovementForReplay(db, id);
+    if (!context) {
+      db.exec("ROLLBACK");
+      return null;
+    }
+
+    const m = context.movement;
+    const originalDate = m.movement_date;
+    if (data.movement_date !== undefined) {
+      m.movement_date = data.movement_date;
+    }
+    if (data.notes !== undefined) {
+      m.notes = data.notes || null;
+    }
+
+    if (m.movement_type === "adjustment") {
+      if (data.new_quantity !== undefined) {
+        if (!data.new_quantity || data.new_quantity <= 0) {
+          throw new Error("New quantity must be greater than zero");
+        }
+        m.quantity = scaleValue(data.new_quantity);
+      }
+    } else {
+      if (data.quantity !== undefined) {
+        m.quantity = scaleValue(data.quantity);
+      }
+      if (data.total_consideration !== undefined) {
+        m.movement_value = scaleValue(data.total_consideration);
+      }
+      if (data.deductible_costs !== undefined) {
+        m.deductible_costs = scaleValue(data.deductible_costs || 0);
+      }
+      if (m.movement_type === "buy") {
+        m.book_cost = m.movement_value - m.deductible_costs;
+      }
+    }
+
+    replayHoldingChain(db, context.chainInfo, context.opening, context.movements);
+    syncMovementCashTransaction(db, context.accountId, m, false);
+    recalculateBalanceAfter(context.accountId);
+    const fromDate = originalDate < m.movement_date ? originalDate : m.movement_date;
+    if (m.movement_type !== "adjustment") {
+      checkCashFrom(db, context.accountId, fromDate);
+    }
+    invalidateAccountValuations(context.accountId, fromDate);
+
+    db.exec("COMMIT");
+    return getMovementById(id);
+  } catch (err) {
+    db.exec("ROLLBACK");
+    throw err;
+  }
+}
+
+/**
+ * @description Delete a buy, sell or adjustment (stock split) movement and
+ * reverse its effects. The linked cash transaction is removed, then the
+ * holding's remaining movements are replayed to regenerate its SCD2 rows and
+ * the account's balance_after values. All changes are made in a single
+ * database transaction.
+ * @param {number} id - The movement ID
+ * @returns {boolean} True if deleted, false if not found
+ * @throws {Error} If removing the movement would oversell the holding or overdraw the cash balance
+ */
+export function deleteMovement(id) {
+  const db = getDatabase();
+
+  db.exec("BEGIN");
+  try {
+    const context = loadMovementForReplay(db, id);
+    if (!context) {
+      db.exec("ROLLBACK");
+      return false;
+    }
+
+    syncMovementCashTransaction(db, context.accountId, context.movement, true);
+    db.run("DELETE FROM holding_movements WHERE id = ?", [id]);
+
+    const remaining = context.movements.filter(function (m) {
+      return m.id !== id;
+    });
+    replayHoldingChain(db, context.chainInfo, context.opening, remaining);
+    recalculateBalanceAfter(context.accountId);
+    if (context.movement.movement_type !== "adjustment") {
+      checkCashFrom(db, context.accountId, context.movement.movement_date);
+    }
+    invalidateAccountValuations(context.accountId, context.movement.movement_date);
+
+    db.exec("COMMIT");
+    return true;
+  } catch (err) {
+    db.exec("ROLLBACK");
+    throw err;
+  }
+}
+
 /**
  * @description Get a single holding movement by ID with unscaled values.
  * @param {number} id - The movement ID
@@ -565,6 +1246,36 @@ export function getMovementsByHoldingId(holdingId, limit = 50) {
   return rows.map(unscaleMovementRow);
 }
 
+/**
+ * @description Get buy and sell movements for every holding in an account
+ * within a date range, oldest first. Each movement carries its investment_id.
+ * @param {number} accountId - The account ID
+ * @param {string} startDate - Start date (inclusive) in YYYY-MM-DD format
+ * @param {string} endDate - End date (inclusive) in YYYY-MM-DD format
+ * @returns {Object[]} Array of movement objects with unscaled values and investment_id
+ */
+export function getTradeMovementsByAccountId(accountId, startDate, endDate) {
+  const db = getDatabase();
+  const rows = db
+    .query(
+      `SELECT hm.id, hm.holding_id, hm.movement_type, hm.movement_date, hm.quantity,
+              hm.movement_value, hm.book_cost, hm.deductible_costs, hm.revised_avg_cost, hm.notes,
+              h.investment_id
+       FROM holding_movements hm
+       JOIN holdings h ON hm.holding_id = h.id
+       WHERE h.account_id = ?
+         AND hm.movement_type IN ('buy', 'sell')
+         AND hm.movement_date >= ?
+         AND hm.movement_date <= ?
+       ORDER BY hm.movement_date, hm.id`,
+    )
+    .all(accountId, startDate, endDate);
+
+  return rows.map(function (row) {
+    return { ...unscaleMovementRow(row), investment_id: row.investment_id };
+  });
+}
+
 /**
  * @description Convert a raw database row to an object with unscaled values.
  * @param {Object} row - The raw database row
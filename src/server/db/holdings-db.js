@@ -1,5 +1,6 @@
 import { getDatabase } from "./connection.js";
 import { CURRENCY_SCALE_FACTOR } from "../../shared/server-constants.js";
+import { invalidateAccountValuations } from "./portfolio-valuations-db.js";
 
 /**
  * @description Get the current date as ISO-8601 string (YYYY-MM-DD).
@@ -133,6 +134,22 @@ export function getActiveHoldingRaw(accountId, investmentId) {
     .get(accountId, investmentId);
 }
 
+/**
+ * @description Check whether an account holds, or has ever held, an investment.
+ * Considers every SCD2 row, so closed (fully sold) holdings still count —
+ * a dividend can be paid after the shares have been sold.
+ * @param {number} accountId - The account ID
+ * @param {number} investmentId - The investment ID
+ * @returns {boolean} True if any holding row exists for the pair
+ */
+export function accountHasHeldInvestment(accountId, investmentId) {
+  const db = getDatabase();
+  const row = db
+    .query("SELECT COUNT(*) AS cnt FROM holdings WHERE account_id = ? AND investment_id = ?")
+    .get(accountId, investmentId);
+  return row.cnt > 0;
+}
+
 /**
  * @description Create a new holding with effective_from set to today.
  * @param {Object} data - The holding data
@@ -149,6 +166,7 @@ export function createHolding(data) {
      VALUES (?, ?, ?, ?, ?)`,
     [data.account_id, data.investment_id, scaleQuantity(data.quantity || 0), scaleQuantity(data.average_cost || 0), today()],
   );
+  invalidateAccountValuations(data.account_id, today());
 
   return getHoldingById(result.lastInsertRowid);
 }
@@ -174,6 +192,7 @@ export function updateHolding(id, data) {
   }
 
   const dateToday = today();
+  invalidateAccountValuations(existing.account_id, dateToday);
 
   if (existing.effective_from === dateToday) {
     // Same day — update in place (daily granularity, no intra-day SCD2 rows)
@@ -213,7 +232,7 @@ export function updateHolding(id, data) {
 export function deleteHolding(id) {
   const db = getDatabase();
   const existing = db.query(
-    "SELECT id, effective_to FROM holdings WHERE id = ?"
+    "SELECT id, account_id, effective_to FROM holdings WHERE id = ?"
   ).get(id);
 
   if (!existing) {
@@ -226,6 +245,7 @@ export function deleteHolding(id) {
   }
 
   db.run("UPDATE holdings SET effective_to = ? WHERE id = ?", [today(), id]);
+  invalidateAccountValuations(existing.account_id, today());
   return true;
 }
 
@@ -237,6 +257,10 @@ export function deleteHolding(id) {
  */
 export function hardDeleteHolding(id) {
   const db = getDatabase();
+  const existing = db.query("SELECT account_id, effective_from FROM holdings WHERE id = ?").get(id);
+  if (existing) {
+    invalidateAccountValuations(existing.account_id, existing.effective_from);
+  }
   db.run("DELETE FROM cash_transactions WHERE holding_movement_id IN (SELECT id FROM holding_movements WHERE holding_id = ?)", [id]);
   db.run("DELETE FROM holding_movements WHERE holding_id = ?", [id]);
   const result = db.run("DELETE FROM holdings WHERE id = ?", [id]);
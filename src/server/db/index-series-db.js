@@ -0,0 +1,169 @@
+import { getDatabase } from "./connection.js";
+import { CURRENCY_SCALE_FACTOR } from "../../shared/server-constants.js";
+
+/**
+ * @description Base SQL for selecting index series with a summary of their values.
+ * @type {string}
+ */
+const SERIES_SELECT = `
+  SELECT s.id, s.name, s.description, s.region,
+         (SELECT COUNT(*) FROM index_values v WHERE v.index_series_id = s.id) AS value_count,
+         (SELECT MIN(value_date) FROM index_values v WHERE v.index_series_id = s.id) AS first_date,
+         (SELECT MAX(value_date) FROM index_values v WHERE v.index_series_id = s.id) AS last_date,
+         (SELECT value FROM index_values v WHERE v.index_series_id = s.id ORDER BY value_date DESC LIMIT 1) AS latest_value
+  FROM index_series s
+`;
+
+/**
+ * @description Convert a raw series row so the latest value is a decimal.
+ * @param {Object} row - The raw database row
+ * @returns {Object} Series with latest_value unscaled
+ */
+function unscaleSeriesRow(row) {
+  return {
+    id: row.id,
+    name: row.name,
+    description: row.description,
+    region: row.region,
+    value_count: row.value_count,
+    first_date: row.first_date,
+    last_date: row.last_date,
+    latest_value: row.latest_value !== null ? row.latest_value / CURRENCY_SCALE_FACTOR : null,
+  };
+}
+
+/**
+ * @description Get all index series ordered by name, with the number of values
+ * held, the date range covered and the latest value.
+ * @returns {Object[]} Array of index series objects
+ */
+export function getAllIndexSeries() {
+  const db = getDatabase();
+  return db.query(SERIES_SELECT + " ORDER BY s.name").all().map(unscaleSeriesRow);
+}
+
+/**
+ * @description Get a single index series by ID.
+ * @param {number} id - The index series ID
+ * @returns {Object|null} The index series, or null if not found
+ */
+export function getIndexSeriesById(id) {
+  const db = getDatabase();
+  const row = db.query(SERIES_SELECT + " WHERE s.id = ?").get(id);
+  if (!row) return null;
+  return unscaleSeriesRow(row);
+}
+
+/**
+ * @description Create a new index series (e.g. CPI). Values are added with
+ * upsertIndexValues or by importing a CSV.
+ * @param {Object} data - The series data
+ * @param {string} data.name - Short name shown in escalation reasons (max 30 chars)
+ * @param {string} [data.description] - Optional description (max 80 chars)
+ * @param {string} [data.region] - Region to load from a UK House Price Index file (max 60 chars)
+ * @returns {Object} The created index series
+ */
+export function createIndexSeries(data) {
+  const db = getDatabase();
+  const result = db.run("INSERT INTO index_series (name, description, region) VALUES (?, ?, ?)", [data.name.trim(), data.description || null, data.region || null]);
+  return getIndexSeriesById(result.lastInsertRowid);
+}
+
+/**
+ * @description Update the name, description and region of an index series.
+ * @param {number} id - The index series ID
+ * @param {Object} data - The updated series data
+ * @param {string} data.name - Short name (max 30 chars)
+ * @param {string} [data.description] - Optional description (max 80 chars)
+ * @param {string} [data.region] - Region to load from a UK House Price Index file (max 60 chars)
+ * @returns {Object|null} The updated index series, or null if not found
+ */
+export function updateIndexSeries(id, data) {
+  const db = getDatabase();
+  const result = db.run("UPDATE index_series SET name = ?, description = ?, region = ? WHERE id = ?", [data.name.trim(), data.description || null, data.region || null, id]);
+  if (result.changes === 0) return null;
+  return getIndexSeriesById(id);
+}
+
+/**
+ * @description Count the drawdown schedules and other assets that escalate
+ * with an index series, and the properties valued by it.
+ * @param {number} id - The index series ID
+ * @returns {number} Number of schedules and assets using the series
+ */
+export function countIndexSeriesUsage(id) {
+  const db = getDatabase();
+  const schedules = db.query("SELECT COUNT(*) AS count FROM drawdown_schedules WHERE escalation_index_id = ?").get(id);
+  const assets = db.query("SELECT COUNT(*) AS count FROM other_assets WHERE escalation_index_id = ? OR valuation_index_id = ?").get(id, id);
+  return schedules.count + assets.count;
+}
+
+/**
+ * @description Delete an index series and its values. Values are removed
+ * automatically via ON DELETE CASCADE. Callers should first check the series
+ * is not used with countIndexSeriesUsage.
+ * @param {number} id - The index series ID
+ * @returns {boolean} True if the series was deleted, false if not found
+ */
+export function deleteIndexSeries(id) {
+  const db = getDatabase();
+  const result = db.run("DELETE FROM index_series WHERE id = ?", [id]);
+  return result.changes > 0;
+}
+
+/**
+ * @description Insert or replace values in an index series. A value for a date
+ * that is already held replaces it. All values are written in one transaction.
+ * @param {number} seriesId - The index series ID
+ * @param {Array<{value_date: string, value: number}>} values - Dates (YYYY-MM-DD) and decimal index values
+ * @returns {number} Number of values written
+ */
+export function upsertIndexValues(seriesId, values) {
+  const db = getDatabase();
+  const statement = db.prepare(
+    `INSERT INTO index_values (index_series_id, value_date, value)
+     VALUES (?, ?, ?)
+     ON CONFLICT(index_series_id, value_date) DO UPDATE SET value = excluded.value`,
+  );
+
+  db.exec("BEGIN");
+  try {
+    for (const item of values) {
+      statement.run(seriesId, item.value_date, Math.round(item.value * CURRENCY_SCALE_FACTOR));
+    }
+    db.exec("COMMIT");
+  } catch (err) {
+    db.exec("ROLLBACK");
+    throw err;
+  }
+
+  return values.length;
+}
+
+/**
+ * @description Get the values of an index series, oldest first.
+ * @param {number} seriesId - The index series ID
+ * @returns {Array<{value_date: string, value: number}>} Dates and decimal index values
+ */
+export function getIndexValues(seriesId) {
+  const db = getDatabase();
+  const rows = db.query("SELECT value_date, value FROM index_values WHERE index_series_id = ? ORDER BY value_date").all(seriesId);
+  return rows.map(function (row) {
+    return { value_date: row.value_date, value: row.value / CURRENCY_SCALE_FACTOR };
+  });
+}
+
+/**
+ * @description Get the latest value of an index series on or before a date.
+ * @param {number} seriesId - The index series ID
+ * @param {string} date - ISO-8601 date (YYYY-MM-DD)
+ * @returns {{value_date: string, value: number}|null} The value and the date it applies from, or null if none
+ */
+export function getIndexValueOn(seriesId, date) {
+  const db = getDatabase();
+  const row = db
+    .query("SELECT value_date, value FROM index_values WHERE index_series_id = ? AND value_date <= ? ORDER BY value_date DESC LIMIT 1")
+    .get(seriesId, date);
+  if (!row) return null;
+  return { value_date: row.value_date, value: row.value / CURRENCY_SCALE_FACTOR };
+}
@@ -1,5 +1,6 @@
 import { getDatabase } from "./connection.js";
 import { CURRENCY_SCALE_FACTOR } from "../../shared/server-constants.js";
+import { invalidateInvestmentValuations } from "./portfolio-valuations-db.js";
 
 /**
  * @description Get all investments with their currency and investment type details,
@@ -22,6 +23,8 @@ export function getAllInvestments() {
         i.auto_fetch,
         i.notes,
         i.replaced,
+        i.unit_type,
+        i.allocation_tag,
         c.code AS currency_code,
         c.description AS currency_description,
         it.short_description AS type_short,
@@ -56,6 +59,8 @@ export function getInvestmentById(id) {
         i.auto_fetch,
         i.notes,
         i.replaced,
+        i.unit_type,
+        i.allocation_tag,
         c.code AS currency_code,
         c.description AS currency_description,
         it.short_description AS type_short,
@@ -68,6 +73,17 @@ export function getInvestmentById(id) {
     .get(id);
 }
 
+/**
+ * @description Trim an allocation tag, treating a blank tag as untagged.
+ * @param {string|null|undefined} tag - The tag as entered
+ * @returns {string|null} The trimmed tag, or null
+ */
+function normaliseAllocationTag(tag) {
+  if (tag === undefined || tag === null) return null;
+  const trimmed = String(tag).trim();
+  return trimmed === "" ? null : trimmed;
+}
+
 /**
  * @description Create a new investment.
  * @param {Object} data - The investment data
@@ -76,39 +92,51 @@ export function getInvestmentById(id) {
  * @param {string} data.description - Investment description (max 60 chars)
  * @param {string|null} data.investment_url - URL for price scraping (max 255 chars)
  * @param {string|null} data.selector - CSS selector for price element (max 255 chars)
+ * @param {string|null} [data.unit_type] - 'income' or 'accumulation' for funds, null if not applicable
+ * @param {string|null} [data.allocation_tag] - Region or asset-class tag for the allocation breakdown (max 30 chars)
  * @returns {Object} The created investment with its new ID and joined fields
  */
 export function createInvestment(data) {
   const db = getDatabase();
   const result = db.run(
-    `INSERT INTO investments (currencies_id, investment_type_id, description, public_id, investment_url, selector)
-     VALUES (?, ?, ?, ?, ?, ?)`,
-    [data.currencies_id, data.investment_type_id, data.description, data.public_id || null, data.investment_url || null, data.selector || null],
+    `INSERT INTO investments (currencies_id, investment_type_id, description, public_id, investment_url, selector, unit_type, allocation_tag)
+     VALUES (?, ?, ?, ?, ?, ?, ?, ?)`,
+    [data.currencies_id, data.investment_type_id, data.description, data.public_id || null, data.investment_url || null, data.selector || null, data.unit_type || null, normaliseAllocationTag(data.allocation_tag)],
   );
 
   return getInvestmentById(result.lastInsertRowid);
 }
 
 /**
- * @description Update an existing investment.
+ * @description Update an existing investment. When its currency changes,
+ * valuation snapshots from its first price are invalidated.
  * @param {number} id - The investment ID to update
  * @param {Object} data - The updated investment data (same fields as createInvestment)
  * @returns {Object|null} The updated investment with joined fields, or null if not found
  */
 export function updateInvestment(id, data) {
   const db = getDatabase();
+  const current = db.query("SELECT currencies_id FROM investments WHERE id = ?").get(id);
   const result = db.run(
     `UPDATE investments SET
        currencies_id = ?, investment_type_id = ?, description = ?,
-       public_id = ?, investment_url = ?, selector = ?
+       public_id = ?, investment_url = ?, selector = ?, unit_type = ?, allocation_tag = ?
      WHERE id = ?`,
-    [data.currencies_id, data.investment_type_id, data.description, data.public_id || null, data.investment_url || null, data.selector || null, id],
+    [data.currencies_id, data.investment_type_id, data.description, data.public_id || null, data.investment_url || null, data.selector || null, data.unit_type || null, normaliseAllocationTag(data.allocation_tag), id],
   );
 
   if (result.changes === 0) {
     return null;
   }
 
+  // Every price is now converted at a different rate, so snapshots from the first price are stale
+  if (Number(data.currencies_id) !== current.currencies_id) {
+    const first = db.query("SELECT MIN(price_date) AS first_date FROM prices WHERE investment_id = ?").get(id);
+    if (first.first_date) {
+      invalidateInvestmentValuations(id, first.first_date);
+    }
+  }
+
   return getInvestmentById(id);
 }
 
@@ -165,7 +193,8 @@ export function getInvestmentUsage(id) {
 }
 
 /**
- * @description Delete an investment by ID. Also deletes associated price history.
+ * @description Delete an investment by ID. Also deletes associated price history
+ * and any allocation targets set for it.
  * Throws an error if the investment is currently held in any account.
  * @param {number} id - The investment ID to delete
  * @returns {boolean} True if the investment was deleted, false if not found
@@ -181,6 +210,7 @@ export function deleteInvestment(id) {
   }
 
   db.run("DELETE FROM prices WHERE investment_id = ?", [id]);
+  db.run("DELETE FROM allocation_targets WHERE investment_id = ?", [id]);
   const result = db.run("DELETE FROM investments WHERE id = ?", [id]);
   return result.changes > 0;
 }
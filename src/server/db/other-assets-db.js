@@ -1,4 +1,7 @@
 import { getDatabase } from "./connection.js";
+import { escalateAmount, getEscalationRule } from "./escalation-db.js";
+import { getIndexSeriesById, getIndexValueOn } from "./index-series-db.js";
+import { addMonths } from "../services/tax-year-utils.js";
 
 /**
  * @description Multipliers to annualise recurring income by frequency.
@@ -14,6 +17,34 @@ const ANNUAL_MULTIPLIERS = {
   annually: 1,
 };
 
+/**
+ * @description Annualise the value of a recurring other asset using its frequency.
+ * @param {Object} asset - Other asset row (value_type, frequency, value)
+ * @returns {number} Annual amount in GBP × 10000 (0 for assets that are not recurring)
+ */
+export function annualiseRecurringValue(asset) {
+  if (asset.value_type !== "recurring" || !asset.frequency) return 0;
+  return asset.value * (ANNUAL_MULTIPLIERS[asset.frequency] || 1);
+}
+
+/**
+ * @description Category display labels, in the order categories are reported.
+ * @type {Object<string, string>}
+ */
+export const OTHER_ASSET_CATEGORY_LABELS = {
+  pension: "Pensions",
+  property: "Property",
+  savings: "Savings",
+  alternative: "Alternative Assets",
+  liability: "Liabilities",
+};
+
+/**
+ * @description Month abbreviations for the index month in indexation reasons.
+ * @type {string[]}
+ */
+const MONTH_NAMES = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+
 /**
  * @description Get today's date in ISO-8601 format (YYYY-MM-DD).
  * @returns {string} Today's date string
@@ -27,9 +58,25 @@ function getTodayDate() {
 }
 
 /**
- * @description Base SQL for selecting other_assets joined with user info.
+ * @description Get the date an asset is next due to be revalued: its
+ * revalue_months after it was last updated. A property valued from a house
+ * price index is due that long after it was last surveyed, as the indexed
+ * estimates are not valuations.
+ * @param {Object} asset - Other asset row (last_updated, revalue_months, and
+ *   valuation_index_id and surveyed_date for an indexed property)
+ * @returns {string|null} ISO-8601 due date, or null if the asset has no revaluation rule
+ */
+export function getRevaluationDueDate(asset) {
+  const fromDate = asset.valuation_index_id && asset.surveyed_date ? asset.surveyed_date : asset.last_updated;
+  if (!asset.revalue_months || !fromDate) return null;
+  return addMonths(fromDate.slice(0, 10), asset.revalue_months);
+}
+
+/**
+ * @description Base SQL for selecting open other_assets joined with user info.
  * Returns user initials and first_name so the UI can display "Joint" for
- * the Joint user row and initials for everyone else.
+ * the Joint user row and initials for everyone else. Closed items are left
+ * out; callers add further conditions with AND.
  * @type {string}
  */
 const BASE_SELECT = `
@@ -38,8 +85,110 @@ const BASE_SELECT = `
          u.first_name AS user_first_name
   FROM other_assets oa
   JOIN users u ON u.id = oa.user_id
+  WHERE oa.closed_date IS NULL
 `;
 
+/**
+ * @description Convert the stored escalation and interest rates (percent × 10000)
+ * to decimal percentages, and add the revaluation due date and whether it has
+ * passed. Other asset values and repayments stay scaled.
+ * @param {Object|null} row - The raw other asset row
+ * @returns {Object|null} The row with escalation_rate and interest_rate as decimals,
+ *   revalue_due_date and revalue_overdue, or null
+ */
+function unscaleEscalationRate(row) {
+  if (!row) return row;
+  row.escalation_rate = row.escalation_rate !== null && row.escalation_rate !== undefined ? row.escalation_rate / 10000 : null;
+  row.interest_rate = row.interest_rate !== null && row.interest_rate !== undefined ? row.interest_rate / 10000 : null;
+  row.revalue_due_date = getRevaluationDueDate(row);
+  row.revalue_overdue = row.revalue_due_date !== null && row.revalue_due_date < getTodayDate();
+  return row;
+}
+
+/**
+ * @description Resolve the loan fields to store for an item. Only liabilities
+ * carry an interest rate and repayment.
+ * @param {Object} data - The asset data (interest_rate as a percentage, repayment as GBP × 10000)
+ * @returns {{ interest_rate: number|null, repayment: number|null }} Values for the loan columns (rate × 10000)
+ */
+function normaliseLoanTerms(data) {
+  const isLiability = data.category === "liability";
+  const hasRate = isLiability && data.interest_rate !== undefined && data.interest_rate !== null && data.interest_rate !== "";
+  const hasRepayment = isLiability && data.repayment !== undefined && data.repayment !== null && data.repayment !== "";
+  return {
+    interest_rate: hasRate ? Math.round(Number(data.interest_rate) * 10000) : null,
+    repayment: hasRepayment ? Math.round(Number(data.repayment)) : null,
+  };
+}
+
+/**
+ * @description Resolve the revaluation interval to store for an asset.
+ * @param {Object} data - The asset data
+ * @returns {number|null} Whole months between revaluations, or null for no reminder
+ */
+function normaliseRevalueMonths(data) {
+  if (data.revalue_months === undefined || data.revalue_months === null || data.revalue_months === "") return null;
+  return Number(data.revalue_months);
+}
+
+/**
+ * @description Resolve the main residence flag to store for an asset. Only a
+ * value-type property can be the main residence.
+ * @param {Object} data - The asset data
+ * @returns {number} 1 for the main residence, otherwise 0
+ */
+function normaliseMainResidence(data) {
+  const flagged = data.main_residence === true || data.main_residence === 1 || data.main_residence === "1";
+  return flagged && data.category === "property" && data.value_type === "value" ? 1 : 0;
+}
+
+/**
+ * @description Resolve the house price indexation fields to store for an
+ * asset. Only property can be valued from an index, and the value entered for
+ * it is the surveyed value. While the survey and index are unchanged the value
+ * held (which may be an indexed estimate) is kept; otherwise the surveyed value
+ * is stored until it is indexed.
+ * @param {Object} data - The asset data
+ * @param {Object|null} current - The raw row being updated, or null for a new asset
+ * @param {string} today - Today's date, used when no survey date is given
+ * @returns {{ index_id: number|null, surveyed_value: number|null, surveyed_date: string|null,
+ *   value: number, value_source: string }} Values for the indexation and value columns
+ */
+function normaliseValuation(data, current, today) {
+  const indexId = data.category === "property" ? Number(data.valuation_index_id) || null : null;
+  if (indexId === null) {
+    return { index_id: null, surveyed_value: null, surveyed_date: null, value: data.value, value_source: "surveyed" };
+  }
+
+  const surveyedDate = data.surveyed_date || today;
+  const unchanged = current !== null && current.valuation_index_id === indexId && current.surveyed_value === data.value && current.surveyed_date === surveyedDate;
+  return {
+    index_id: indexId,
+    surveyed_value: data.value,
+    surveyed_date: surveyedDate,
+    value: unchanged ? current.value : data.value,
+    value_source: unchanged ? current.value_source : "surveyed",
+  };
+}
+
+/**
+ * @description Resolve the escalation fields to store for an asset. Only
+ * recurring assets escalate, and only the field that goes with the chosen
+ * type is kept.
+ * @param {Object} data - The asset data
+ * @returns {{ type: string, rate: number|null, index_id: number|null, anniversary: string|null }} Values for
+ *   the escalation columns (rate × 10000)
+ */
+function normaliseEscalation(data) {
+  const type = data.value_type === "recurring" && data.escalation_type ? data.escalation_type : "none";
+  return {
+    type: type,
+    rate: type === "fixed" && data.escalation_rate !== undefined && data.escalation_rate !== null ? Math.round(data.escalation_rate * 10000) : null,
+    index_id: type === "index" ? Number(data.escalation_index_id) || null : null,
+    anniversary: type !== "none" ? data.escalation_anniversary || null : null,
+  };
+}
+
 /**
  * @description Get all other assets, ordered by category then description.
  * Includes user initials and first_name for display.
@@ -49,7 +198,7 @@ export function getAllOtherAssets() {
   const db = getDatabase();
   return db.query(
     BASE_SELECT + " ORDER BY oa.category, oa.description"
-  ).all();
+  ).all().map(unscaleEscalationRate);
 }
 
 /**
@@ -59,21 +208,21 @@ export function getAllOtherAssets() {
  */
 export function getOtherAssetById(id) {
   const db = getDatabase();
-  return db.query(
-    BASE_SELECT + " WHERE oa.id = ?"
-  ).get(id);
+  return unscaleEscalationRate(db.query(
+    BASE_SELECT + " AND oa.id = ?"
+  ).get(id));
 }
 
 /**
  * @description Get all other assets for a given category, with user info.
- * @param {string} category - One of: pension, property, savings, alternative
+ * @param {string} category - One of: pension, property, savings, alternative, liability
  * @returns {Object[]} Array of other asset objects
  */
 export function getOtherAssetsByCategory(category) {
   const db = getDatabase();
   return db.query(
-    BASE_SELECT + " WHERE oa.category = ? ORDER BY oa.description"
-  ).all(category);
+    BASE_SELECT + " AND oa.category = ? ORDER BY oa.description"
+  ).all(category).map(unscaleEscalationRate);
 }
 
 /**
@@ -81,39 +230,78 @@ export function getOtherAssetsByCategory(category) {
  * @param {Object} data - The asset data
  * @param {number} data.user_id - FK to users.id (including Joint user)
  * @param {string} data.description - Asset description (max 40 chars)
- * @param {string} data.category - One of: pension, property, savings, alternative
- * @param {string} data.value_type - One of: recurring, value
+ * @param {string} data.category - One of: pension, property, savings, alternative, liability
+ * @param {string} data.value_type - One of: recurring, value (always value for a liability)
  * @param {string|null} data.frequency - Payment frequency (required for recurring, null for value)
  * @param {number} data.value - Amount in GBP × 10000
  * @param {string|null} data.notes - Optional notes (max 60 chars)
  * @param {string|null} data.executor_reference - Optional executor ref (max 80 chars)
+ * @param {string} [data.escalation_type='none'] - How a recurring value rises: 'none', 'fixed' or 'index'
+ * @param {number} [data.escalation_rate] - Percentage rise each year, when escalation_type is 'fixed'
+ * @param {number} [data.escalation_index_id] - FK to index_series, when escalation_type is 'index'
+ * @param {string} [data.escalation_anniversary] - Date the value rises each year as MM-DD
+ * @param {number} [data.interest_rate] - Annual interest rate as a percentage, for a liability
+ * @param {number} [data.repayment] - Monthly repayment in GBP × 10000, for a liability
+ * @param {number} [data.revalue_months] - Months between revaluations, or null for no reminder
+ * @param {number} [data.valuation_index_id] - FK to index_series, for a property valued from a house price index
+ * @param {string} [data.surveyed_date] - Date the property was valued at data.value, when indexed (default today)
+ * @param {boolean} [data.main_residence] - Whether a property is the home that qualifies for the residence nil-rate band
+ * @param {string} [data.start_date] - Date the item was first held (default today)
  * @returns {Object} The created asset with its new ID and user info
  */
 export function createOtherAsset(data) {
   const db = getDatabase();
+  const today = getTodayDate();
+  const escalation = normaliseEscalation(data);
+  const loan = normaliseLoanTerms(data);
+  const valuation = normaliseValuation(data, null, today);
   const result = db.run(
-    `INSERT INTO other_assets (user_id, description, category, value_type, frequency, value, notes, executor_reference, last_updated)
-     VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)`,
+    `INSERT INTO other_assets (user_id, description, category, value_type, frequency, value, notes, executor_reference, last_updated,
+                               escalation_type, escalation_rate, escalation_index_id, escalation_anniversary, escalation_last_date,
+                               interest_rate, repayment, revalue_months,
+                               valuation_index_id, surveyed_value, surveyed_date, value_source, main_residence, start_date)
+     VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)`,
     [
       data.user_id,
       data.description,
       data.category,
       data.value_type,
       data.frequency || null,
-      data.value,
+      valuation.value,
       data.notes || null,
       data.executor_reference || null,
-      getTodayDate(),
+      today,
+      escalation.type,
+      escalation.rate,
+      escalation.index_id,
+      escalation.anniversary,
+      escalation.type !== "none" ? today : null,
+      loan.interest_rate,
+      loan.repayment,
+      normaliseRevalueMonths(data),
+      valuation.index_id,
+      valuation.surveyed_value,
+      valuation.surveyed_date,
+      valuation.value_source,
+      normaliseMainResidence(data),
+      data.start_date || today,
     ]
   );
 
+  if (valuation.index_id !== null) {
+    indexOtherAssetValue(db.query("SELECT * FROM other_assets WHERE id = ?").get(result.lastInsertRowid), today);
+  }
+
   return getOtherAssetById(result.lastInsertRowid);
 }
 
 /**
  * @description Update an existing other asset. If value, notes, or
  * executor_reference changed, the old values are written to
- * other_assets_history before the update.
+ * other_assets_history before the update — for a liability this is its
+ * balance history. When the escalation rule changes, escalation starts again
+ * from today. A property valued from a house price index is re-estimated
+ * when its survey or index changes.
  * @param {number} id - The asset ID to update
  * @param {Object} data - The updated asset data
  * @returns {Object|null} The updated asset with user info, or null if not found
@@ -122,24 +310,41 @@ export function updateOtherAsset(id, data) {
   const db = getDatabase();
 
   // Fetch current row to compare tracked fields
-  const current = db.query("SELECT * FROM other_assets WHERE id = ?").get(id);
+  const current = db.query("SELECT * FROM other_assets WHERE id = ? AND closed_date IS NULL").get(id);
   if (!current) {
     return null;
   }
 
   const today = getTodayDate();
+  const valuation = normaliseValuation(data, current, today);
 
   // Check if any of the three tracked fields changed
-  const valueChanged = data.value !== current.value;
+  const valueChanged = valuation.value !== current.value;
   const notesChanged = (data.notes || null) !== (current.notes || null);
   const execRefChanged = (data.executor_reference || null) !== (current.executor_reference || null);
 
+  const loan = normaliseLoanTerms(data);
+
+  // Restart escalation from today when the rule changes
+  const escalation = normaliseEscalation(data);
+  const ruleChanged =
+    escalation.type !== current.escalation_type ||
+    escalation.rate !== current.escalation_rate ||
+    escalation.index_id !== current.escalation_index_id ||
+    escalation.anniversary !== current.escalation_anniversary;
+  let lastEscalated = current.escalation_last_date;
+  if (escalation.type === "none") {
+    lastEscalated = null;
+  } else if (ruleChanged || !lastEscalated) {
+    lastEscalated = today;
+  }
+
   if (valueChanged || notesChanged || execRefChanged) {
     // Write old values to history before overwriting
     db.run(
-      `INSERT INTO other_assets_history (other_asset_id, change_date, revised_value, revised_notes, revised_executor_reference)
-       VALUES (?, ?, ?, ?, ?)`,
-      [id, today, current.value, current.notes || null, current.executor_reference || null]
+      `INSERT INTO other_assets_history (other_asset_id, change_date, revised_value, revised_notes, revised_executor_reference, revised_value_source)
+       VALUES (?, ?, ?, ?, ?, ?)`,
+      [id, today, current.value, current.notes || null, current.executor_reference || null, current.valuation_index_id ? current.value_source : null]
     );
   }
 
@@ -147,7 +352,11 @@ export function updateOtherAsset(id, data) {
     `UPDATE other_assets
      SET user_id = ?, description = ?, category = ?, value_type = ?,
          frequency = ?, value = ?, notes = ?, executor_reference = ?,
-         last_updated = ?
+         last_updated = ?, escalation_type = ?, escalation_rate = ?,
+         escalation_index_id = ?, escalation_anniversary = ?, escalation_last_date = ?,
+         interest_rate = ?, repayment = ?, revalue_months = ?,
+         valuation_index_id = ?, surveyed_value = ?, surveyed_date = ?, value_source = ?,
+         main_residence = ?, start_date = ?
      WHERE id = ?`,
     [
       data.user_id,
@@ -155,10 +364,24 @@ export function updateOtherAsset(id, data) {
       data.category,
       data.value_type,
       data.frequency || null,
-      data.value,
+      valuation.value,
       data.notes || null,
       data.executor_reference || null,
       today,
+      escalation.type,
+      escalation.rate,
+      escalation.index_id,
+      escalation.anniversary,
+      lastEscalated,
+      loan.interest_rate,
+      loan.repayment,
+      normaliseRevalueMonths(data),
+      valuation.index_id,
+      valuation.surveyed_value,
+      valuation.surveyed_date,
+      valuation.value_source,
+      normaliseMainResidence(data),
+      data.start_date || current.start_date,
       id,
     ]
   );
@@ -167,9 +390,156 @@ export function updateOtherAsset(id, data) {
     return null;
   }
 
+  if (valuation.index_id !== null) {
+    indexOtherAssetValue(db.query("SELECT * FROM other_assets WHERE id = ?").get(id), today);
+  }
+
   return getOtherAssetById(id);
 }
 
+/**
+ * @description Apply escalation rules to recurring other assets. For each
+ * anniversary since an asset last escalated, up to and including today, the
+ * old value is written to other_assets_history (dated the anniversary, with
+ * the reason) and the value is raised. Index-linked rises that cannot be
+ * worked out yet are left until the index is updated. Safe to call on every
+ * restart: anniversaries already applied are not applied again.
+ * @param {string} [todayStr] - ISO-8601 date to use as today (for testing)
+ * @returns {{ escalated: number, pending: number }} Number of rises applied, and assets waiting for index values
+ */
+export function applyOtherAssetEscalations(todayStr) {
+  const db = getDatabase();
+  const today = todayStr || getTodayDate();
+  const assets = db
+    .query("SELECT * FROM other_assets WHERE value_type = 'recurring' AND escalation_type != 'none' AND escalation_last_date IS NOT NULL AND closed_date IS NULL")
+    .all()
+    .map(unscaleEscalationRate);
+
+  let escalated = 0;
+  let pending = 0;
+
+  for (const asset of assets) {
+    const result = escalateAmount(asset.value / 10000, getEscalationRule(asset), asset.escalation_last_date, today);
+    if (result.pending_date) pending++;
+    if (result.steps.length === 0) continue;
+
+    db.exec("BEGIN");
+    try {
+      let previousValue = asset.value;
+      for (const step of result.steps) {
+        db.run(
+          `INSERT INTO other_assets_history (other_asset_id, change_date, revised_value, revised_notes, revised_executor_reference, reason)
+           VALUES (?, ?, ?, ?, ?, ?)`,
+          [asset.id, step.date, previousValue, asset.notes || null, asset.executor_reference || null, step.reason]
+        );
+        previousValue = Math.round(step.amount * 10000);
+      }
+
+      const lastStep = result.steps[result.steps.length - 1];
+      db.run(
+        "UPDATE other_assets SET value = ?, escalation_last_date = ?, last_updated = ? WHERE id = ?",
+        [previousValue, lastStep.date, lastStep.date, asset.id]
+      );
+      db.exec("COMMIT");
+    } catch (err) {
+      db.exec("ROLLBACK");
+      throw err;
+    }
+
+    escalated += result.steps.length;
+    console.log("[Escalation] " + asset.description + " raised to £" + result.amount.toFixed(2) + " (" + result.steps.length + " rise(s))");
+  }
+
+  return { escalated, pending };
+}
+
+/**
+ * @description Estimate a property's value from its surveyed value moved in
+ * line with its house price index: the surveyed value × the latest index value
+ * ÷ the index value for the survey month, to the nearest pound. When the
+ * estimate differs from the value held, the old value is written to
+ * other_assets_history (marked surveyed or indexed, with the movement as the
+ * reason) and the estimate is stored as an indexed value. last_updated is
+ * left alone, as an estimate is not a revaluation.
+ * @param {Object} asset - Raw other asset row with valuation_index_id, surveyed_value and surveyed_date
+ * @param {string} today - ISO-8601 date to estimate the value at
+ * @returns {string} "indexed" when a new estimate was stored, "pending" when the index has
+ *   no value for the survey month, otherwise "unchanged"
+ */
+function indexOtherAssetValue(asset, today) {
+  const base = getIndexValueOn(asset.valuation_index_id, asset.surveyed_date);
+  const latest = getIndexValueOn(asset.valuation_index_id, today);
+  if (!base || !latest) return "pending";
+
+  // Nothing has moved until the index has a value after the survey month
+  if (latest.value_date <= base.value_date) return "unchanged";
+
+  const estimate = Math.round(((asset.surveyed_value / 10000) * latest.value) / base.value) * 10000;
+  if (estimate === asset.value) return "unchanged";
+
+  const series = getIndexSeriesById(asset.valuation_index_id);
+  const change = (latest.value / base.value - 1) * 100;
+  const month = MONTH_NAMES[parseInt(latest.value_date.slice(5, 7), 10) - 1] + " " + latest.value_date.slice(0, 4);
+  const reason = "Indexed " + (change >= 0 ? "+" : "") + change.toFixed(2) + "% since survey (" + series.name + " " + month + ")";
+
+  const db = getDatabase();
+  db.exec("BEGIN");
+  try {
+    db.run(
+      `INSERT INTO other_assets_history (other_asset_id, change_date, revised_value, revised_notes, revised_executor_reference, reason, revised_value_source)
+       VALUES (?, ?, ?, ?, ?, ?, ?)`,
+      [asset.id, today, asset.value, asset.notes || null, asset.executor_reference || null, reason, asset.value_source]
+    );
+    db.run("UPDATE other_assets SET value = ?, value_source = 'indexed' WHERE id = ?", [estimate, asset.id]);
+    db.exec("COMMIT");
+  } catch (err) {
+    db.exec("ROLLBACK");
+    throw err;
+  }
+
+  console.log("[Indexation] " + asset.description + " estimated at £" + (estimate / 10000).toFixed(0) + " (" + reason + ")");
+  return "indexed";
+}
+
+/**
+ * @description Re-estimate every property valued from a house price index,
+ * as in indexOtherAssetValue. Safe to call on every restart and after each
+ * index import: a value is only recorded when the estimate changes.
+ * @param {string} [todayStr] - ISO-8601 date to use as today (for testing)
+ * @returns {{ indexed: number, pending: number }} Number of new estimates stored, and properties
+ *   whose index has no value for the survey month
+ */
+export function applyOtherAssetIndexation(todayStr) {
+  const db = getDatabase();
+  const today = todayStr || getTodayDate();
+  const assets = db.query("SELECT * FROM other_assets WHERE valuation_index_id IS NOT NULL AND surveyed_date IS NOT NULL AND closed_date IS NULL").all();
+
+  let indexed = 0;
+  let pending = 0;
+
+  for (const asset of assets) {
+    const outcome = indexOtherAssetValue(asset, today);
+    if (outcome === "indexed") indexed++;
+    if (outcome === "pending") pending++;
+  }
+
+  return { indexed, pending };
+}
+
+/**
+ * @description Close an other asset that has been sold or paid off. The row
+ * and its history are kept, so net worth on earlier dates still includes it;
+ * a closed item no longer appears in lists or reports of current holdings.
+ * @param {number} id - The asset ID to close
+ * @param {string} [closedDate] - ISO-8601 date the item stopped being held (default today)
+ * @returns {boolean} True if the asset was closed, false if not found or already closed
+ */
+export function closeOtherAsset(id, closedDate) {
+  const db = getDatabase();
+  const result = db.run("UPDATE other_assets SET closed_date = ? WHERE id = ? AND closed_date IS NULL", [closedDate || getTodayDate(), id]);
+  return result.changes > 0;
+}
+
 /**
  * @description Delete an other asset by ID. History rows are removed
  * automatically via ON DELETE CASCADE.
@@ -194,42 +564,101 @@ export function getOtherAssetHistory(assetId) {
   ).all(assetId);
 }
 
+/**
+ * @description Get the value of every other asset and liability on a date,
+ * from the current values and the change history. A history row holds the
+ * value an item had until its change_date, so the value on a date is the one
+ * recorded by the first change after it, or the current value if none.
+ * Items are left out before their start_date (when known) and from their
+ * closed_date onwards.
+ * @param {string} date - ISO-8601 date (YYYY-MM-DD)
+ * @returns {Object[]} Rows of { id, description, category, value_type, value_at_date (GBP × 10000) },
+ *   ordered by category then description
+ */
+export function getOtherAssetValuesAtDate(date) {
+  const db = getDatabase();
+  return db.query(
+    `SELECT oa.id, oa.description, oa.category, oa.value_type,
+            COALESCE(
+              (SELECT h.revised_value FROM other_assets_history h
+               WHERE h.other_asset_id = oa.id AND h.change_date > ?
+               ORDER BY h.change_date ASC, h.id ASC LIMIT 1),
+              oa.value
+            ) AS value_at_date
+     FROM other_assets oa
+     WHERE (oa.start_date IS NULL OR oa.start_date <= ?)
+       AND (oa.closed_date IS NULL OR oa.closed_date > ?)
+     ORDER BY oa.category, oa.description`
+  ).all(date, date, date);
+}
+
+/**
+ * @description Total the value-type other assets and the liabilities on a
+ * date, valued as in getOtherAssetValuesAtDate.
+ * @param {string} date - ISO-8601 date (YYYY-MM-DD)
+ * @returns {{ assets: number, liabilities: number }} Totals in GBP × 10000
+ */
+export function getOtherAssetTotalsAtDate(date) {
+  let assets = 0;
+  let liabilities = 0;
+  for (const row of getOtherAssetValuesAtDate(date)) {
+    if (row.value_type !== "value") continue;
+    if (row.category === "liability") {
+      liabilities += row.value_at_date;
+    } else {
+      assets += row.value_at_date;
+    }
+  }
+  return { assets, liabilities };
+}
+
+/**
+ * @description Get the other assets with a revaluation rule whose due date
+ * has passed, the longest overdue first.
+ * @returns {Object[]} Other asset objects with user info, revalue_due_date and revalue_overdue
+ */
+export function getOverdueOtherAssets() {
+  const db = getDatabase();
+  return db.query(
+    BASE_SELECT + " AND oa.revalue_months IS NOT NULL ORDER BY oa.category, oa.description"
+  ).all().map(unscaleEscalationRate).filter(function (asset) {
+    return asset.revalue_overdue;
+  }).sort(function (a, b) {
+    return a.revalue_due_date < b.revalue_due_date ? -1 : a.revalue_due_date > b.revalue_due_date ? 1 : 0;
+  });
+}
+
 /**
  * @description Get all other assets grouped by category with summary totals.
- * Used by the Household Assets report block.
+ * Liabilities are kept out of the asset total and reported separately, with
+ * the other assets net of liabilities. Used by the Household Assets report block.
  * @returns {Object} Categories with items and summary totals
  */
 export function getHouseholdAssetsSummary() {
   const db = getDatabase();
   const rows = db.query(
     BASE_SELECT + " ORDER BY oa.category, oa.description"
-  ).all();
-
-  const categoryOrder = ["pension", "property", "savings", "alternative"];
-  const categoryLabels = {
-    pension: "Pensions",
-    property: "Property",
-    savings: "Savings",
-    alternative: "Alternative Assets",
-  };
+  ).all().map(unscaleEscalationRate);
 
   /** @type {Object<string, {label: string, items: Object[]}>} */
   const categories = {};
-  for (const cat of categoryOrder) {
-    categories[cat] = { label: categoryLabels[cat], items: [] };
+  for (const cat of Object.keys(OTHER_ASSET_CATEGORY_LABELS)) {
+    categories[cat] = { label: OTHER_ASSET_CATEGORY_LABELS[cat], items: [] };
   }
 
   let recurringAnnual = 0;
   let valueTotal = 0;
+  let liabilitiesTotal = 0;
 
   for (const row of rows) {
     if (categories[row.category]) {
       categories[row.category].items.push(row);
     }
 
-    if (row.value_type === "recurring" && row.frequency) {
-      const multiplier = ANNUAL_MULTIPLIERS[row.frequency] || 1;
-      recurringAnnual += row.value * multiplier;
+    if (row.category === "liability") {
+      liabilitiesTotal += row.value;
+    } else if (row.value_type === "recurring" && row.frequency) {
+      recurringAnnual += annualiseRecurringValue(row);
     } else if (row.value_type === "value") {
       valueTotal += row.value;
     }
@@ -240,6 +669,8 @@ export function getHouseholdAssetsSummary() {
     totals: {
       recurring_annual: recurringAnnual,
       value_total: valueTotal,
+      liabilities_total: liabilitiesTotal,
+      net_assets: valueTotal - liabilitiesTotal,
     },
   };
 }
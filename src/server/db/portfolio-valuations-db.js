@@ -0,0 +1,213 @@
+import { getDatabase } from "./connection.js";
+import { CURRENCY_SCALE_FACTOR } from "../../shared/server-constants.js";
+
+/**
+ * @description Scale a decimal value for storage (multiply by CURRENCY_SCALE_FACTOR).
+ * @param {number|null} value - The decimal value
+ * @returns {number|null} Scaled integer value, or null if no value
+ */
+function scale(value) {
+  if (value === null || value === undefined) return null;
+  return Math.round(value * CURRENCY_SCALE_FACTOR);
+}
+
+/**
+ * @description Unscale a stored value (divide by CURRENCY_SCALE_FACTOR).
+ * @param {number|null} scaledValue - The scaled integer value from the database
+ * @returns {number|null} The decimal value, or null if no value
+ */
+function unscale(scaledValue) {
+  if (scaledValue === null || scaledValue === undefined) return null;
+  return scaledValue / CURRENCY_SCALE_FACTOR;
+}
+
+/**
+ * @description Get the stored valuation snapshot for an account on a date.
+ * A snapshot is only complete if its cash row (NULL investment_id) exists —
+ * otherwise null is returned and the caller must value the account afresh.
+ * @param {number} accountId - The account ID
+ * @param {string} date - ISO-8601 valuation date (YYYY-MM-DD)
+ * @returns {Object|null} Object with { cash_balance, holdings }, or null if no snapshot exists.
+ *   cash_balance is null when no cash transactions existed by that date.
+ *   Each holding has { holding_id, investment_id, public_id, description, currency_code,
+ *   quantity, average_cost, price (major units), price_date, rate, rate_date, value_local, value_gbp }.
+ */
+export function getValuationSnapshot(accountId, date) {
+  const db = getDatabase();
+  const rows = db
+    .query(
+      `SELECT pv.investment_id, pv.holding_id, pv.quantity, pv.average_cost, pv.price, pv.price_date,
+              pv.rate, pv.rate_date, pv.value_local, pv.value_gbp,
+              i.public_id AS investment_public_id, i.description AS investment_description,
+              c.code AS currency_code
+       FROM portfolio_valuations pv
+       LEFT JOIN investments i ON pv.investment_id = i.id
+       LEFT JOIN currencies c ON i.currencies_id = c.id
+       WHERE pv.account_id = ? AND pv.valuation_date = ?
+       ORDER BY i.description`,
+    )
+    .all(accountId, date);
+
+  const cashRow = rows.find(function (r) {
+    return r.investment_id === null;
+  });
+  if (!cashRow) return null;
+
+  const holdings = rows
+    .filter(function (r) {
+      return r.investment_id !== null;
+    })
+    .map(function (r) {
+      return {
+        holding_id: r.holding_id,
+        investment_id: r.investment_id,
+        public_id: r.investment_public_id,
+        description: r.investment_description,
+        currency_code: r.currency_code,
+        quantity: unscale(r.quantity),
+        average_cost: unscale(r.average_cost),
+        // Stored in minor units (pence/cents) — convert to major units
+        price: unscale(r.price) / 100,
+        price_date: r.price_date,
+        rate: unscale(r.rate),
+        rate_date: r.rate_date,
+        value_local: unscale(r.value_local),
+        value_gbp: unscale(r.value_gbp),
+      };
+    });
+
+  return {
+    cash_balance: unscale(cashRow.value_gbp),
+    holdings: holdings,
+  };
+}
+
+/**
+ * @description Store a valuation snapshot for an account on a date, replacing
+ * any existing snapshot for that account and date. The writes are wrapped in
+ * a savepoint, so this is safe to call inside an existing database transaction.
+ * @param {number} accountId - The account ID
+ * @param {string} date - ISO-8601 valuation date (YYYY-MM-DD)
+ * @param {Object} snapshot - The snapshot, in the shape returned by getValuationSnapshot
+ * @param {number|null} snapshot.cash_balance - Cash balance in GBP, or null if unknown
+ * @param {Object[]} snapshot.holdings - Holding valuations (price in major units)
+ */
+export function saveValuationSnapshot(accountId, date, snapshot) {
+  const db = getDatabase();
+
+  db.exec("SAVEPOINT save_valuation_snapshot");
+  try {
+    db.run("DELETE FROM portfolio_valuations WHERE account_id = ? AND valuation_date = ?", [accountId, date]);
+
+    db.run(
+      "INSERT INTO portfolio_valuations (valuation_date, account_id, investment_id, value_gbp) VALUES (?, ?, NULL, ?)",
+      [date, accountId, scale(snapshot.cash_balance)],
+    );
+
+    for (const h of snapshot.holdings) {
+      db.run(
+        `INSERT INTO portfolio_valuations
+           (valuation_date, account_id, investment_id, holding_id, quantity, average_cost, price, price_date, rate, rate_date, value_local, value_gbp)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)`,
+        [
+          date,
+          accountId,
+          h.investment_id,
+          h.holding_id,
+          scale(h.quantity),
+          scale(h.average_cost || 0),
+          scale(h.price * 100),
+          h.price_date,
+          scale(h.rate),
+          h.rate_date,
+          scale(h.value_local),
+          scale(h.value_gbp),
+        ],
+      );
+    }
+
+    db.exec("RELEASE save_valuation_snapshot");
+  } catch (err) {
+    db.exec("ROLLBACK TO save_valuation_snapshot");
+    db.exec("RELEASE save_valuation_snapshot");
+    throw err;
+  }
+}
+
+/**
+ * @description Delete an account's snapshots from a date onwards. Call whenever
+ * the account's holdings or cash change on or before later snapshot dates.
+ * Safe to call inside an existing database transaction.
+ * @param {number} accountId - The account ID
+ * @param {string} fromDate - ISO-8601 date (inclusive)
+ * @returns {number} Number of rows deleted
+ */
+export function invalidateAccountValuations(accountId, fromDate) {
+  const db = getDatabase();
+  const result = db.run("DELETE FROM portfolio_valuations WHERE account_id = ? AND valuation_date >= ?", [accountId, fromDate]);
+  return result.changes;
+}
+
+/**
+ * @description Delete snapshots from a date onwards for every account that has
+ * held an investment. Call when a price for the investment is added or changed.
+ * Whole account snapshots are removed so a partial snapshot is never read.
+ * @param {number} investmentId - The investment ID
+ * @param {string} fromDate - ISO-8601 date (inclusive)
+ * @returns {number} Number of rows deleted
+ */
+export function invalidateInvestmentValuations(investmentId, fromDate) {
+  const db = getDatabase();
+  const result = db.run(
+    `DELETE FROM portfolio_valuations
+     WHERE valuation_date >= ?
+       AND account_id IN (SELECT DISTINCT account_id FROM holdings WHERE investment_id = ?)`,
+    [fromDate, investmentId],
+  );
+  return result.changes;
+}
+
+/**
+ * @description Delete snapshots from a date onwards for every account that has
+ * held an investment priced in a currency. Call when an exchange rate changes.
+ * @param {number} currenciesId - The currency ID
+ * @param {string} fromDate - ISO-8601 date (inclusive)
+ * @returns {number} Number of rows deleted
+ */
+export function invalidateCurrencyValuations(currenciesId, fromDate) {
+  const db = getDatabase();
+  const result = db.run(
+    `DELETE FROM portfolio_valuations
+     WHERE valuation_date >= ?
+       AND account_id IN (
+         SELECT DISTINCT h.account_id
+         FROM holdings h
+         JOIN investments i ON h.investment_id = i.id
+         WHERE i.currencies_id = ?
+       )`,
+    [fromDate, currenciesId],
+  );
+  return result.changes;
+}
+
+/**
+ * @description Delete every snapshot for an account. Used when the account is deleted.
+ * @param {number} accountId - The account ID
+ */
+export function deleteAccountValuations(accountId) {
+  const db = getDatabase();
+  db.run("DELETE FROM portfolio_valuations WHERE account_id = ?", [accountId]);
+}
+
+/**
+ * @description Count the stored snapshot dates for an account.
+ * @param {number} accountId - The account ID
+ * @returns {number} Number of dates with a complete snapshot
+ */
+export function getValuationSnapshotCount(accountId) {
+  const db = getDatabase();
+  const row = db
+    .query("SELECT COUNT(*) AS cnt FROM portfolio_valuations WHERE account_id = ? AND investment_id IS NULL")
+    .get(accountId);
+  return row.cnt;
+}
@@ -1,5 +1,6 @@
 import { getDatabase } from "./connection.js";
 import { CURRENCY_SCALE_FACTOR } from "../../shared/server-constants.js";
+import { invalidateInvestmentValuations } from "./portfolio-valuations-db.js";
 
 /**
  * @description Store or update a price for an investment on a given date.
@@ -22,6 +23,8 @@ export function upsertPrice(investmentId, priceDate, priceTime, priceMinorUnit)
      VALUES (?, ?, ?, ?)`,
     [investmentId, priceDate, priceTime, scaledPrice],
   );
+
+  invalidateInvestmentValuations(investmentId, priceDate);
 }
 
 /**
@@ -275,6 +278,8 @@ export function prorateHistoricalPrices(oldInvestmentId, newInvestmentId, oldQua
   const scaledNewPrice = Math.round(newPrice * CURRENCY_SCALE_FACTOR);
   insertStmt.run(newInvestmentId, executionDate, "00:00:00", scaledNewPrice);
 
+  invalidateInvestmentValuations(newInvestmentId, oldPrices.length > 0 ? oldPrices[0].price_date : executionDate);
+
   return oldPrices.length + 1;
 }
 
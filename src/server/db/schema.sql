@@ -1,7 +1,8 @@
 -- Portfolio 60 database schema (v0.1.0)
 -- SQLite with WAL mode, foreign keys enforced via PRAGMA
 
--- Users: family members with provider account details
+-- Users: family members. provider is the default for new accounts; each account records its own.
+-- spouse_user_id links spouses or civil partners (both rows point at each other).
 CREATE TABLE IF NOT EXISTS users (
     id INTEGER PRIMARY KEY AUTOINCREMENT,
     initials TEXT NOT NULL CHECK(length(initials) <= 5),
@@ -12,7 +13,10 @@ CREATE TABLE IF NOT EXISTS users (
     provider TEXT NOT NULL CHECK(length(provider) <= 5),
     trading_ref TEXT CHECK(trading_ref IS NULL OR length(trading_ref) <= 15),
     isa_ref TEXT CHECK(isa_ref IS NULL OR length(isa_ref) <= 15),
-    sipp_ref TEXT CHECK(sipp_ref IS NULL OR length(sipp_ref) <= 15)
+    sipp_ref TEXT CHECK(sipp_ref IS NULL OR length(sipp_ref) <= 15),
+    date_of_birth TEXT,
+    spouse_user_id INTEGER,
+    FOREIGN KEY (spouse_user_id) REFERENCES users(id)
 );
 
 -- Investment types: hard-coded categories (seeded, no CRUD UI)
@@ -43,6 +47,8 @@ CREATE TABLE IF NOT EXISTS investments (
     morningstar_id TEXT,
     notes TEXT CHECK(notes IS NULL OR length(notes) <= 255),
     replaced INTEGER NOT NULL DEFAULT 0,
+    unit_type TEXT CHECK(unit_type IS NULL OR unit_type IN ('income', 'accumulation')),
+    allocation_tag TEXT CHECK(allocation_tag IS NULL OR length(allocation_tag) <= 30),
     FOREIGN KEY (currencies_id) REFERENCES currencies(id),
     FOREIGN KEY (investment_type_id) REFERENCES investment_types(id)
 );
@@ -69,14 +75,27 @@ CREATE TABLE IF NOT EXISTS global_events (
 CREATE TABLE IF NOT EXISTS benchmarks (
     id INTEGER PRIMARY KEY AUTOINCREMENT,
     currencies_id INTEGER NOT NULL,
-    benchmark_type TEXT NOT NULL CHECK(benchmark_type IN ('index', 'price')),
+    benchmark_type TEXT NOT NULL CHECK(benchmark_type IN ('index', 'price', 'composite')),
     description TEXT NOT NULL CHECK(length(description) <= 60),
     benchmark_url TEXT CHECK(benchmark_url IS NULL OR length(benchmark_url) <= 255),
     selector TEXT CHECK(selector IS NULL OR length(selector) <= 255),
     yahoo_ticker TEXT,
+    rebalance_frequency TEXT CHECK(rebalance_frequency IS NULL OR rebalance_frequency IN ('monthly', 'quarterly')),
     FOREIGN KEY (currencies_id) REFERENCES currencies(id)
 );
 
+-- Benchmark components: the weighted benchmarks a composite benchmark blends,
+-- weight stored as percent x 10000
+CREATE TABLE IF NOT EXISTS benchmark_components (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    benchmark_id INTEGER NOT NULL,
+    component_benchmark_id INTEGER NOT NULL,
+    weight_percent INTEGER NOT NULL,
+    FOREIGN KEY (benchmark_id) REFERENCES benchmarks(id),
+    FOREIGN KEY (component_benchmark_id) REFERENCES benchmarks(id),
+    UNIQUE(benchmark_id, component_benchmark_id)
+);
+
 -- Prices: historical investment prices, stored as integer x 10000
 CREATE TABLE IF NOT EXISTS prices (
     id INTEGER PRIMARY KEY AUTOINCREMENT,
@@ -113,16 +132,18 @@ CREATE TABLE IF NOT EXISTS fetch_history (
     error_message TEXT
 );
 
--- Accounts: user investment accounts (trading, ISA, SIPP)
+-- Accounts: user investment accounts (trading, ISA, SIPP). A user may hold
+-- several accounts of the same type, each with its own provider.
 CREATE TABLE IF NOT EXISTS accounts (
     id INTEGER PRIMARY KEY AUTOINCREMENT,
     user_id INTEGER NOT NULL,
     account_type TEXT NOT NULL CHECK(account_type IN ('trading', 'isa', 'sipp')),
     account_ref TEXT NOT NULL CHECK(length(account_ref) <= 15),
+    provider TEXT CHECK(provider IS NULL OR length(provider) <= 5),
     cash_balance INTEGER NOT NULL DEFAULT 0,
     warn_cash INTEGER NOT NULL DEFAULT 0,
     FOREIGN KEY (user_id) REFERENCES users(id),
-    UNIQUE(user_id, account_type)
+    UNIQUE(user_id, account_type, account_ref)
 );
 
 -- Holdings: investment positions within an account (SCD2 — temporal history)
@@ -139,18 +160,34 @@ CREATE TABLE IF NOT EXISTS holdings (
     UNIQUE(account_id, investment_id, effective_from)
 );
 
--- Cash transactions: deposits, withdrawals, drawdowns and adjustments (future UI)
+-- Cash transactions: deposits, withdrawals, drawdowns, adjustments, buys/sells and income
+-- (dividend/interest rows carry investment_id to the paying investment).
+-- ISA transfers are a withdrawal from the source ISA and a deposit into the destination,
+-- flagged with isa_transfer; transfer_account_id links the two when both ISAs are held here.
+-- SIPP contributions are deposits flagged with contribution_type and their gross_amount;
+-- relief-at-source top-ups are separate 'tax_relief' rows.
+-- Drawdowns debit the gross amount; tax_withheld is the PAYE deducted by the provider
+-- (net paid = amount - tax_withheld) and tax_code the code or basis it was worked out on.
 CREATE TABLE IF NOT EXISTS cash_transactions (
     id INTEGER PRIMARY KEY AUTOINCREMENT,
     account_id INTEGER NOT NULL,
     holding_movement_id INTEGER,
-    transaction_type TEXT NOT NULL CHECK(transaction_type IN ('deposit', 'withdrawal', 'drawdown', 'adjustment', 'buy', 'sell')),
+    transaction_type TEXT NOT NULL CHECK(transaction_type IN ('deposit', 'withdrawal', 'drawdown', 'adjustment', 'buy', 'sell', 'dividend', 'interest', 'tax_relief')),
     transaction_date TEXT NOT NULL,
     amount INTEGER NOT NULL,
     notes TEXT CHECK(notes IS NULL OR length(notes) <= 255),
     balance_after INTEGER,
+    investment_id INTEGER,
+    isa_transfer TEXT CHECK(isa_transfer IS NULL OR isa_transfer IN ('current_year', 'previous_years')),
+    transfer_account_id INTEGER,
+    contribution_type TEXT CHECK(contribution_type IS NULL OR contribution_type IN ('personal', 'employer')),
+    gross_amount INTEGER,
+    tax_withheld INTEGER,
+    tax_code TEXT CHECK(tax_code IS NULL OR length(tax_code) <= 20),
     FOREIGN KEY (account_id) REFERENCES accounts(id),
-    FOREIGN KEY (holding_movement_id) REFERENCES holding_movements(id)
+    FOREIGN KEY (holding_movement_id) REFERENCES holding_movements(id),
+    FOREIGN KEY (investment_id) REFERENCES investments(id),
+    FOREIGN KEY (transfer_account_id) REFERENCES accounts(id)
 );
 
 -- Holding movements: buy, sell and adjustment transactions (future UI)
@@ -168,7 +205,31 @@ CREATE TABLE IF NOT EXISTS holding_movements (
     FOREIGN KEY (holding_id) REFERENCES holdings(id)
 );
 
+-- Index series: published indices such as CPI, loaded from CSV, used to escalate
+-- drawdown schedules and recurring other assets, and to index property values.
+-- region picks one region's rows from a UK House Price Index file (e.g. 'South West').
+CREATE TABLE IF NOT EXISTS index_series (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE CHECK(length(name) <= 30),
+    description TEXT CHECK(description IS NULL OR length(description) <= 80),
+    region TEXT CHECK(region IS NULL OR length(region) <= 60)
+);
+
+-- Index values: one value per series per date, scaled by 10000
+CREATE TABLE IF NOT EXISTS index_values (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    index_series_id INTEGER NOT NULL,
+    value_date TEXT NOT NULL,
+    value INTEGER NOT NULL,
+    FOREIGN KEY (index_series_id) REFERENCES index_series(id) ON DELETE CASCADE,
+    UNIQUE(index_series_id, value_date)
+);
+
 -- Drawdown schedules: recurring SIPP pension withdrawals
+-- tax_treatment says how PAYE is deducted: by tax_code, at flat_tax_rate (percent x 10000),
+-- on the emergency code, or 'none' when the gross amount is paid without deduction.
+-- escalation_type raises the amount each year on escalation_anniversary (MM-DD): by
+-- escalation_rate (percent x 10000) when 'fixed', or in line with an index series.
 CREATE TABLE IF NOT EXISTS drawdown_schedules (
     id INTEGER PRIMARY KEY AUTOINCREMENT,
     account_id INTEGER NOT NULL,
@@ -179,25 +240,79 @@ CREATE TABLE IF NOT EXISTS drawdown_schedules (
     amount INTEGER NOT NULL,
     notes TEXT CHECK(notes IS NULL OR length(notes) <= 255),
     active INTEGER NOT NULL DEFAULT 1,
-    FOREIGN KEY (account_id) REFERENCES accounts(id)
+    tax_treatment TEXT NOT NULL DEFAULT 'none' CHECK(tax_treatment IN ('none', 'tax_code', 'flat', 'emergency')),
+    tax_code TEXT CHECK(tax_code IS NULL OR length(tax_code) <= 20),
+    flat_tax_rate INTEGER,
+    escalation_type TEXT NOT NULL DEFAULT 'none' CHECK(escalation_type IN ('none', 'fixed', 'index')),
+    escalation_rate INTEGER,
+    escalation_index_id INTEGER,
+    escalation_anniversary TEXT,
+    FOREIGN KEY (account_id) REFERENCES accounts(id),
+    FOREIGN KEY (escalation_index_id) REFERENCES index_series(id)
+);
+
+-- SIPP crystallisations: funds designated for drawdown, with the tax-free cash (PCLS) taken
+-- Amounts are scaled by 10000. cash_transaction_id links the withdrawal paying out the
+-- tax-free cash, when it was paid from the account's cash.
+CREATE TABLE IF NOT EXISTS sipp_crystallisations (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    account_id INTEGER NOT NULL,
+    crystallisation_date TEXT NOT NULL,
+    tax_free_cash INTEGER NOT NULL DEFAULT 0,
+    drawdown_amount INTEGER NOT NULL DEFAULT 0,
+    cash_transaction_id INTEGER,
+    notes TEXT CHECK(notes IS NULL OR length(notes) <= 255),
+    FOREIGN KEY (account_id) REFERENCES accounts(id),
+    FOREIGN KEY (cash_transaction_id) REFERENCES cash_transactions(id)
 );
 
 -- Other assets: non-portfolio financial assets (pensions, property, savings, alternatives)
+-- and liabilities (mortgages, loans, credit balances — value is the balance owed).
+-- Recurring assets can escalate in the same way as drawdown schedules; escalation_last_date
+-- is the last anniversary applied (or the date the rule was set).
+-- interest_rate (annual %, × 10000) and repayment (monthly, GBP × 10000) are for liabilities only.
+-- revalue_months is how often the value should be checked; NULL means no reminder.
+-- A property linked to a house price index by valuation_index_id is valued at
+-- surveyed_value (GBP × 10000, on surveyed_date) moved in line with the index;
+-- value_source says whether value is 'surveyed' or 'indexed'.
+-- main_residence (property only) marks the home that qualifies for the
+-- inheritance tax residence nil-rate band.
+-- start_date is when the item was first held (NULL: always held); closed_date
+-- is set when the item is deleted, keeping it in past net worth.
 CREATE TABLE IF NOT EXISTS other_assets (
     id INTEGER PRIMARY KEY AUTOINCREMENT,
     user_id INTEGER NOT NULL,
     description TEXT NOT NULL CHECK(length(description) <= 40),
-    category TEXT NOT NULL CHECK(category IN ('pension', 'property', 'savings', 'alternative')),
+    category TEXT NOT NULL CHECK(category IN ('pension', 'property', 'savings', 'alternative', 'liability')),
     value_type TEXT NOT NULL CHECK(value_type IN ('recurring', 'value')),
     frequency TEXT CHECK(frequency IS NULL OR frequency IN ('weekly', 'fortnightly', '4_weeks', 'monthly', 'quarterly', '6_monthly', 'annually')),
     value INTEGER NOT NULL DEFAULT 0,
     notes TEXT CHECK(notes IS NULL OR length(notes) <= 60),
     executor_reference TEXT CHECK(executor_reference IS NULL OR length(executor_reference) <= 80),
     last_updated TEXT NOT NULL,
-    FOREIGN KEY (user_id) REFERENCES users(id)
+    escalation_type TEXT NOT NULL DEFAULT 'none' CHECK(escalation_type IN ('none', 'fixed', 'index')),
+    escalation_rate INTEGER,
+    escalation_index_id INTEGER,
+    escalation_anniversary TEXT,
+    escalation_last_date TEXT,
+    interest_rate INTEGER,
+    repayment INTEGER,
+    revalue_months INTEGER,
+    valuation_index_id INTEGER,
+    surveyed_value INTEGER,
+    surveyed_date TEXT,
+    value_source TEXT NOT NULL DEFAULT 'surveyed' CHECK(value_source IN ('surveyed', 'indexed')),
+    main_residence INTEGER NOT NULL DEFAULT 0 CHECK(main_residence IN (0, 1)),
+    start_date TEXT,
+    closed_date TEXT,
+    FOREIGN KEY (user_id) REFERENCES users(id),
+    FOREIGN KEY (escalation_index_id) REFERENCES index_series(id),
+    FOREIGN KEY (valuation_index_id) REFERENCES index_series(id)
 );
 
 -- Other assets history: tracks changes to value, notes, and executor_reference
+-- reason describes automatic changes, such as an escalation or indexation;
+-- revised_value_source says whether revised_value was 'surveyed' or 'indexed'
 CREATE TABLE IF NOT EXISTS other_assets_history (
     id INTEGER PRIMARY KEY AUTOINCREMENT,
     other_asset_id INTEGER NOT NULL,
@@ -205,9 +320,28 @@ CREATE TABLE IF NOT EXISTS other_assets_history (
     revised_value INTEGER NOT NULL,
     revised_notes TEXT CHECK(revised_notes IS NULL OR length(revised_notes) <= 80),
     revised_executor_reference TEXT CHECK(revised_executor_reference IS NULL OR length(revised_executor_reference) <= 80),
+    reason TEXT CHECK(reason IS NULL OR length(reason) <= 80),
+    revised_value_source TEXT CHECK(revised_value_source IS NULL OR revised_value_source IN ('surveyed', 'indexed')),
     FOREIGN KEY (other_asset_id) REFERENCES other_assets(id) ON DELETE CASCADE
 );
 
+-- Gifts register: gifts made by each family member, for inheritance tax planning
+-- amount is GBP × 10000. exemption_type is the exemption claimed: the 'annual' exemption
+-- (any excess is a potentially exempt transfer), a 'small_gift' to one recipient, or
+-- 'normal_expenditure' out of income. cash_transaction_id links the withdrawal that paid it.
+CREATE TABLE IF NOT EXISTS gifts (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER NOT NULL,
+    recipient TEXT NOT NULL CHECK(length(recipient) <= 60),
+    gift_date TEXT NOT NULL,
+    amount INTEGER NOT NULL,
+    exemption_type TEXT NOT NULL CHECK(exemption_type IN ('annual', 'small_gift', 'normal_expenditure')),
+    cash_transaction_id INTEGER UNIQUE,
+    notes TEXT CHECK(notes IS NULL OR length(notes) <= 255),
+    FOREIGN KEY (user_id) REFERENCES users(id),
+    FOREIGN KEY (cash_transaction_id) REFERENCES cash_transactions(id)
+);
+
 -- Scheduler log: timestamped log entries from the scheduled fetcher
 CREATE TABLE IF NOT EXISTS scheduler_log (
     id INTEGER PRIMARY KEY AUTOINCREMENT,
@@ -225,6 +359,44 @@ CREATE TABLE IF NOT EXISTS daily_visitors (
     other_count INTEGER NOT NULL DEFAULT 0
 );
 
+-- Allocation targets: target weights for a person (account_id NULL, across all their
+-- accounts) or a single account, set either by investment or by allocation tag.
+-- target_percent is scaled by 10000 (e.g. 25.5% = 255000).
+CREATE TABLE IF NOT EXISTS allocation_targets (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER NOT NULL,
+    account_id INTEGER,
+    investment_id INTEGER,
+    allocation_tag TEXT CHECK(allocation_tag IS NULL OR length(allocation_tag) <= 30),
+    target_percent INTEGER NOT NULL,
+    CHECK((investment_id IS NULL) != (allocation_tag IS NULL)),
+    FOREIGN KEY (user_id) REFERENCES users(id),
+    FOREIGN KEY (account_id) REFERENCES accounts(id),
+    FOREIGN KEY (investment_id) REFERENCES investments(id)
+);
+
+-- Portfolio valuations: materialised daily snapshot of each account's holdings
+-- Keyed by date, account and investment — the row with a NULL investment_id holds
+-- the account's cash balance and marks the snapshot as complete for that date.
+-- All money and quantity columns are scaled by 10000; price is in minor units.
+CREATE TABLE IF NOT EXISTS portfolio_valuations (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    valuation_date TEXT NOT NULL,
+    account_id INTEGER NOT NULL,
+    investment_id INTEGER,
+    holding_id INTEGER,
+    quantity INTEGER NOT NULL DEFAULT 0,
+    average_cost INTEGER NOT NULL DEFAULT 0,
+    price INTEGER NOT NULL DEFAULT 0,
+    price_date TEXT,
+    rate INTEGER,
+    rate_date TEXT,
+    value_local INTEGER,
+    value_gbp INTEGER,
+    FOREIGN KEY (account_id) REFERENCES accounts(id),
+    FOREIGN KEY (investment_id) REFERENCES investments(id)
+);
+
 -- Indexes for query performance
 CREATE INDEX IF NOT EXISTS idx_currency_rates_lookup ON currency_rates(currencies_id, rate_date DESC);
 CREATE INDEX IF NOT EXISTS idx_investments_type ON investments(investment_type_id);
@@ -240,10 +412,17 @@ CREATE INDEX IF NOT EXISTS idx_accounts_user ON accounts(user_id);
 CREATE INDEX IF NOT EXISTS idx_holdings_account ON holdings(account_id);
 CREATE INDEX IF NOT EXISTS idx_holdings_investment ON holdings(investment_id);
 CREATE INDEX IF NOT EXISTS idx_cash_transactions_account ON cash_transactions(account_id, transaction_date DESC);
+CREATE INDEX IF NOT EXISTS idx_cash_transactions_investment ON cash_transactions(investment_id);
 CREATE INDEX IF NOT EXISTS idx_holding_movements_holding ON holding_movements(holding_id, movement_date DESC);
 CREATE INDEX IF NOT EXISTS idx_drawdown_schedules_account ON drawdown_schedules(account_id);
+CREATE INDEX IF NOT EXISTS idx_sipp_crystallisations_account ON sipp_crystallisations(account_id, crystallisation_date);
+CREATE INDEX IF NOT EXISTS idx_allocation_targets_user ON allocation_targets(user_id, account_id);
+CREATE INDEX IF NOT EXISTS idx_benchmark_components_benchmark ON benchmark_components(benchmark_id);
 CREATE INDEX IF NOT EXISTS idx_other_assets_user ON other_assets(user_id);
 CREATE INDEX IF NOT EXISTS idx_other_assets_category ON other_assets(category);
 CREATE INDEX IF NOT EXISTS idx_other_assets_history_asset ON other_assets_history(other_asset_id, change_date DESC);
+CREATE INDEX IF NOT EXISTS idx_gifts_user ON gifts(user_id, gift_date);
 CREATE INDEX IF NOT EXISTS idx_scheduler_log_datetime ON scheduler_log(log_datetime DESC);
 CREATE INDEX IF NOT EXISTS idx_daily_visitors_date ON daily_visitors(visit_date DESC);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_portfolio_valuations_key ON portfolio_valuations(valuation_date, account_id, IFNULL(investment_id, 0));
+CREATE INDEX IF NOT EXISTS idx_portfolio_valuations_account ON portfolio_valuations(account_id, valuation_date);
@@ -143,13 +143,13 @@ INSERT INTO users (initials, first_name, last_name, ni_number, utr, provider, tr
 -- cash_balance and warn_cash are stored as GBP × 10000.
 -- ============================================================================
 
-INSERT INTO accounts (user_id, account_type, account_ref, cash_balance, warn_cash) VALUES
-    (2, 'sipp',    '3366521', 48500000, 20000000),
-    (2, 'isa',     '3366527',  9857500,  1500000),
-    (2, 'trading', '3366524', 17500000,  1500000),
-    (3, 'sipp',    '3633175', 36540000, 16000000),
-    (3, 'trading', '3633173',  2140000,  1500000),
-    (3, 'isa',     '3633174',  7880000,  1500000);
+INSERT INTO accounts (user_id, account_type, account_ref, provider, cash_balance, warn_cash) VALUES
+    (2, 'sipp',    '3366521', 'ii', 48500000, 20000000),
+    (2, 'isa',     '3366527', 'ii',  9857500,  1500000),
+    (2, 'trading', '3366524', 'ii', 17500000,  1500000),
+    (3, 'sipp',    '3633175', 'ii', 36540000, 16000000),
+    (3, 'trading', '3633173', 'ii',  2140000,  1500000),
+    (3, 'isa',     '3633174', 'ii',  7880000,  1500000);
 
 -- ============================================================================
 -- HOLDINGS
@@ -232,7 +232,7 @@ INSERT INTO cash_transactions (account_id, transaction_type, transaction_date, a
 
 -- ============================================================================
 -- OTHER ASSETS
--- Non-portfolio financial assets: pensions, property, savings, alternatives.
+-- Non-portfolio financial assets: pensions, property, savings, alternatives, and liabilities.
 -- User IDs: Joint=1, Ben=2, Alexis=3
 -- value is stored as GBP × 10000.
 -- ============================================================================
@@ -249,6 +249,54 @@ INSERT INTO other_assets (user_id, description, category, value_type, frequency,
     (2, 'Barclays Saving A/c',    'savings', 'value', NULL,   37500000, NULL, NULL, '2026-03-01'),
     (3, 'Premium Bonds',          'savings', 'value', NULL,   25000000, NULL, NULL, '2010-12-01');
 
+-- Liabilities: value is the balance owed, interest_rate is annual % × 10000, repayment is monthly GBP × 10000
+INSERT INTO other_assets (user_id, description, category, value_type, frequency, value, notes, executor_reference, last_updated, interest_rate, repayment) VALUES
+    (1, 'Mortgage - Nationwide',  'liability', 'value', NULL,  865000000, 'Fixed to 2028', NULL, '2026-03-01', 42900, 6150000),
+    (2, 'Car Loan',               'liability', 'value', NULL,   84000000, NULL, NULL, '2026-03-01', 69000, 3250000);
+
+-- Revaluation reminders: months between value checks (the house is overdue)
+UPDATE other_assets SET revalue_months = 12 WHERE description IN ('12 Primrose Av', 'Barclays Saving A/c');
+UPDATE other_assets SET revalue_months = 6 WHERE description = 'Mortgage - Nationwide';
+
+-- House price index: UK HPI for the South East, quarterly values scaled by 10000.
+-- The house is valued from its January 2024 survey in line with the index.
+INSERT INTO index_series (name, description, region) VALUES
+    ('SE HPI', 'UK House Price Index, South East (Jan 2023=100)', 'South East');
+
+INSERT INTO index_values (index_series_id, value_date, value)
+SELECT id, v.value_date, v.value FROM index_series, (
+    SELECT '2024-01-01' AS value_date, 1002000 AS value
+    UNION ALL SELECT '2024-04-01', 1009000
+    UNION ALL SELECT '2024-07-01', 1021000
+    UNION ALL SELECT '2024-10-01', 1018000
+    UNION ALL SELECT '2025-01-01', 1024000
+    UNION ALL SELECT '2025-04-01', 1035000
+    UNION ALL SELECT '2025-07-01', 1047000
+    UNION ALL SELECT '2025-10-01', 1043000
+    UNION ALL SELECT '2026-01-01', 1051000
+    UNION ALL SELECT '2026-04-01', 1062000
+    UNION ALL SELECT '2026-07-01', 1074000
+) v WHERE index_series.name = 'SE HPI';
+
+UPDATE other_assets
+SET valuation_index_id = (SELECT id FROM index_series WHERE name = 'SE HPI'),
+    surveyed_value = value, surveyed_date = '2024-01-01'
+WHERE description = '12 Primrose Av';
+
+-- Inheritance tax: Ben and Alexis are married, and the house is their main residence
+UPDATE users SET spouse_user_id = 3 WHERE id = 2;
+UPDATE users SET spouse_user_id = 2 WHERE id = 3;
+UPDATE other_assets SET main_residence = 1 WHERE description = '12 Primrose Av';
+
+-- Gifts register: amount is GBP × 10000. Ben's gift to Sophie is partly a
+-- potentially exempt transfer; the others are covered by exemptions.
+INSERT INTO gifts (user_id, recipient, gift_date, amount, exemption_type, notes) VALUES
+    (2, 'Sophie Wilson',    '2022-09-14', 200000000, 'annual',             'House deposit'),
+    (3, 'Sophie Wilson',    '2025-12-18',   2500000, 'small_gift',         'Christmas'),
+    (3, 'Oliver Wilson',    '2025-12-18',   2500000, 'small_gift',         'Christmas'),
+    (2, 'Junior ISA - Mia', '2026-04-30',   1000000, 'normal_expenditure', 'Monthly from pension income'),
+    (3, 'Oliver Wilson',    '2026-07-02',  50000000, 'annual',             'Wedding');
+
 -- ============================================================================
 -- REPORT PARAMS
 -- Token mappings for report template substitution in user-reports.json.
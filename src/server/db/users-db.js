@@ -31,19 +31,39 @@ export function getUserById(id) {
  * @param {string|null} data.trading_ref - Trading account reference (max 15 chars)
  * @param {string|null} data.isa_ref - ISA account reference (max 15 chars)
  * @param {string|null} data.sipp_ref - SIPP account reference (max 15 chars)
+ * @param {string|null} [data.date_of_birth] - Date of birth (YYYY-MM-DD)
+ * @param {number|null} [data.spouse_user_id] - Spouse or civil partner (FK to users.id)
  * @returns {Object} The created user with its new ID
  */
 export function createUser(data) {
   const db = getDatabase();
   const result = db.run(
-    `INSERT INTO users (initials, first_name, last_name, ni_number, utr, provider, trading_ref, isa_ref, sipp_ref)
-     VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)`,
-    [data.initials, data.first_name, data.last_name, data.ni_number || null, data.utr || null, data.provider, data.trading_ref || null, data.isa_ref || null, data.sipp_ref || null],
+    `INSERT INTO users (initials, first_name, last_name, ni_number, utr, provider, trading_ref, isa_ref, sipp_ref, date_of_birth)
+     VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)`,
+    [data.initials, data.first_name, data.last_name, data.ni_number || null, data.utr || null, data.provider, data.trading_ref || null, data.isa_ref || null, data.sipp_ref || null, data.date_of_birth || null],
   );
 
+  linkSpouse(Number(result.lastInsertRowid), data.spouse_user_id ? Number(data.spouse_user_id) : null);
   return getUserById(result.lastInsertRowid);
 }
 
+/**
+ * @description Set a user's spouse or civil partner, keeping the link the same
+ * on both users: the spouse points back at the user, and anyone either of them
+ * was previously linked to is unlinked.
+ * @param {number} id - The user ID
+ * @param {number|null} spouseId - The spouse's user ID, or null for none
+ */
+function linkSpouse(id, spouseId) {
+  const db = getDatabase();
+  db.run("UPDATE users SET spouse_user_id = NULL WHERE (spouse_user_id = ? AND id != ?) OR id = ?", [id, spouseId || 0, id]);
+  if (spouseId) {
+    db.run("UPDATE users SET spouse_user_id = NULL WHERE spouse_user_id = ? AND id != ?", [spouseId, id]);
+    db.run("UPDATE users SET spouse_user_id = ? WHERE id = ?", [spouseId, id]);
+    db.run("UPDATE users SET spouse_user_id = ? WHERE id = ?", [id, spouseId]);
+  }
+}
+
 /**
  * @description Update an existing user.
  * @param {number} id - The user ID to update
@@ -56,33 +76,41 @@ export function updateUser(id, data) {
     `UPDATE users SET
        initials = ?, first_name = ?, last_name = ?,
        ni_number = ?, utr = ?, provider = ?,
-       trading_ref = ?, isa_ref = ?, sipp_ref = ?
+       trading_ref = ?, isa_ref = ?, sipp_ref = ?,
+       date_of_birth = ?
      WHERE id = ?`,
-    [data.initials, data.first_name, data.last_name, data.ni_number || null, data.utr || null, data.provider, data.trading_ref || null, data.isa_ref || null, data.sipp_ref || null, id],
+    [data.initials, data.first_name, data.last_name, data.ni_number || null, data.utr || null, data.provider, data.trading_ref || null, data.isa_ref || null, data.sipp_ref || null, data.date_of_birth || null, id],
   );
 
   if (result.changes === 0) {
     return null;
   }
 
+  linkSpouse(id, data.spouse_user_id ? Number(data.spouse_user_id) : null);
   return getUserById(id);
 }
 
 /**
  * @description Delete a user by ID. Also deletes all associated child records
- * (holding movements, holdings, cash transactions, drawdown schedules, accounts).
+ * (holding movements, holdings, cash transactions, drawdown schedules, accounts, gifts).
  * @param {number} id - The user ID to delete
  * @returns {boolean} True if the user was deleted, false if not found
  */
 export function deleteUser(id) {
   const db = getDatabase();
   // Delete in dependency order (no ON DELETE CASCADE in schema)
-  // cash_transactions references holding_movements via holding_movement_id FK, so must go first
+  // sipp_crystallisations references cash_transactions, and cash_transactions references
+  // holding_movements via holding_movement_id FK, so these must go first
+  db.run("DELETE FROM sipp_crystallisations WHERE account_id IN (SELECT id FROM accounts WHERE user_id = ?)", [id]);
+  db.run("DELETE FROM gifts WHERE user_id = ?", [id]);
+  db.run("UPDATE gifts SET cash_transaction_id = NULL WHERE cash_transaction_id IN (SELECT ct.id FROM cash_transactions ct JOIN accounts a ON ct.account_id = a.id WHERE a.user_id = ?)", [id]);
   db.run("DELETE FROM cash_transactions WHERE account_id IN (SELECT id FROM accounts WHERE user_id = ?)", [id]);
   db.run("DELETE FROM holding_movements WHERE holding_id IN (SELECT h.id FROM holdings h JOIN accounts a ON h.account_id = a.id WHERE a.user_id = ?)", [id]);
   db.run("DELETE FROM holdings WHERE account_id IN (SELECT id FROM accounts WHERE user_id = ?)", [id]);
   db.run("DELETE FROM drawdown_schedules WHERE account_id IN (SELECT id FROM accounts WHERE user_id = ?)", [id]);
+  db.run("DELETE FROM allocation_targets WHERE user_id = ?", [id]);
   db.run("DELETE FROM accounts WHERE user_id = ?", [id]);
+  db.run("UPDATE users SET spouse_user_id = NULL WHERE spouse_user_id = ?", [id]);
   const result = db.run("DELETE FROM users WHERE id = ?", [id]);
   return result.changes > 0;
 }
@@ -196,6 +196,7 @@ export function getYahooFetchableBenchmarks() {
         c.description AS currency_description
       FROM benchmarks b
       JOIN currencies c ON b.currencies_id = c.id
+      WHERE b.benchmark_type != 'composite'
       ORDER BY b.description`,
     )
     .all();
@@ -25,10 +25,25 @@ import { handleReportsRoute } from "./routes/reports-routes.js";
 import { handlePortfolioDetailRoute } from "./routes/portfolio-detail-routes.js";
 import { handleAnalysisRoute } from "./routes/analysis-routes.js";
 import { handleTestSetupRoute } from "./routes/test-setup-routes.js";
+import { handleCgtRoute } from "./routes/cgt-routes.js";
+import { handleIsaAllowanceRoute } from "./routes/isa-allowance-routes.js";
+import { handlePensionAllowanceRoute } from "./routes/pension-allowance-routes.js";
+import { handleP60Route } from "./routes/p60-routes.js";
+import { handleRetirementProjectionRoute } from "./routes/retirement-projection-routes.js";
+import { handleIhtEstateRoute } from "./routes/iht-estate-routes.js";
+import { handleGiftsRoute } from "./routes/gifts-routes.js";
+import { handleCrystallisationsRoute } from "./routes/crystallisations-routes.js";
+import { handleCashBufferRoute } from "./routes/cash-buffer-routes.js";
+import { handleReturnsRoute } from "./routes/returns-routes.js";
+import { handleIncomeRoute } from "./routes/income-routes.js";
+import { handleBrokerImportRoute } from "./routes/broker-import-routes.js";
+import { handleIndexSeriesRoute } from "./routes/index-series-routes.js";
+import { handleRebalancingRoute } from "./routes/rebalancing-routes.js";
 import { isPublicDemoHost, isTestMode, isDemoMode, activateTestMode, setDemoMode } from "./test-mode.js";
 import { initScheduledFetcher, stopScheduledFetcher } from "./services/scheduled-fetcher.js";
 import { initVisitorTracker, stopVisitorTracker, trackVisitor } from "./services/visitor-tracker.js";
 import { processDrawdowns } from "./services/drawdown-processor.js";
+import { applyOtherAssetEscalations, applyOtherAssetIndexation } from "./db/other-assets-db.js";
 import { databaseExists, closeDatabase } from "./db/connection.js";
 import { getFetchServerConfig, getDocsConfig, getListsDir } from "./config.js";
 import { pushConfigToFetchServer } from "./services/fetch-server-push.js";
@@ -355,6 +370,54 @@ async function handleRequest(request, server) {
         }
       }
 
+      // Pension contribution routes (nested under accounts)
+      if (path.includes("/pension-contributions")) {
+        const pensionResult = await handlePensionAllowanceRoute(method, path, request);
+        if (pensionResult) {
+          return pensionResult;
+        }
+      }
+
+      // SIPP crystallisation routes (nested under accounts)
+      if (path.includes("/crystallisations")) {
+        const crystallisationResult = await handleCrystallisationsRoute(method, path, request);
+        if (crystallisationResult) {
+          return crystallisationResult;
+        }
+      }
+
+      // Cash buffer planner routes (nested under accounts)
+      if (path.includes("/cash-buffer")) {
+        const cashBufferResult = await handleCashBufferRoute(method, path, request);
+        if (cashBufferResult) {
+          return cashBufferResult;
+        }
+      }
+
+      // P60 pension income summary route (nested under accounts)
+      if (path.endsWith("/p60")) {
+        const p60Result = await handleP60Route(method, path, request);
+        if (p60Result) {
+          return p60Result;
+        }
+      }
+
+      // Income routes (nested under accounts)
+      if (path.includes("/income")) {
+        const incomeResult = await handleIncomeRoute(method, path, request);
+        if (incomeResult) {
+          return incomeResult;
+        }
+      }
+
+      // Broker CSV import routes (nested under accounts)
+      if (path.includes("/import")) {
+        const importResult = await handleBrokerImportRoute(method, path, request);
+        if (importResult) {
+          return importResult;
+        }
+      }
+
       // Holdings routes (nested under accounts)
       if (path.includes("/holdings")) {
         const holdingsResult = await handleHoldingsRoute(method, path, request);
@@ -396,6 +459,14 @@ async function handleRequest(request, server) {
 
     // Holdings routes (standalone) — must check for /movements sub-path first
     if (path.startsWith("/api/holdings")) {
+      // Income nested under holdings
+      if (path.includes("/income")) {
+        const incomeResult = await handleIncomeRoute(method, path, request);
+        if (incomeResult) {
+          return incomeResult;
+        }
+      }
+
       // Holding movements nested under holdings
       if (path.includes("/movements")) {
         const movementsResult = await handleHoldingMovementsRoute(method, path, request);
@@ -410,6 +481,78 @@ async function handleRequest(request, server) {
       }
     }
 
+    // Capital gains tax routes
+    if (path.startsWith("/api/cgt/")) {
+      const cgtResult = await handleCgtRoute(method, path, request);
+      if (cgtResult) {
+        return cgtResult;
+      }
+    }
+
+    // ISA allowance routes (per-person usage, history and transfers)
+    if (path.startsWith("/api/isa-allowance") || path === "/api/isa-transfers") {
+      const isaResult = await handleIsaAllowanceRoute(method, path, request);
+      if (isaResult) {
+        return isaResult;
+      }
+    }
+
+    // Pension annual allowance routes (per-person usage and history)
+    if (path.startsWith("/api/pension-allowance")) {
+      const pensionResult = await handlePensionAllowanceRoute(method, path, request);
+      if (pensionResult) {
+        return pensionResult;
+      }
+    }
+
+    // SIPP crystallisation routes (delete by ID)
+    if (path.startsWith("/api/crystallisations")) {
+      const crystallisationResult = await handleCrystallisationsRoute(method, path, request);
+      if (crystallisationResult) {
+        return crystallisationResult;
+      }
+    }
+
+    // P60 pension income summary routes (per-person drawdowns and PAYE)
+    if (path === "/api/p60" || path.startsWith("/api/p60/")) {
+      const p60Result = await handleP60Route(method, path, request);
+      if (p60Result) {
+        return p60Result;
+      }
+    }
+
+    // Retirement projection routes (how long SIPPs last at current drawdowns)
+    if (path === "/api/retirement-projection" || path.startsWith("/api/retirement-projection/")) {
+      const projectionResult = await handleRetirementProjectionRoute(method, path, request);
+      if (projectionResult) {
+        return projectionResult;
+      }
+    }
+
+    // Inheritance tax estate estimate routes (estate per family member, nil-rate bands, spouse transfer)
+    if (path === "/api/iht-estate" || path.startsWith("/api/iht-estate/")) {
+      const ihtResult = await handleIhtEstateRoute(method, path, request);
+      if (ihtResult) {
+        return ihtResult;
+      }
+    }
+
+    // Gifts register routes (gifts per donor, exemptions, seven-year taper)
+    if (path === "/api/gifts" || path.startsWith("/api/gifts/")) {
+      const giftsResult = await handleGiftsRoute(method, path, request);
+      if (giftsResult) {
+        return giftsResult;
+      }
+    }
+
+    // Portfolio returns routes (XIRR and TWR)
+    if (path === "/api/returns") {
+      const returnsResult = await handleReturnsRoute(method, path, request);
+      if (returnsResult) {
+        return returnsResult;
+      }
+    }
+
     // Users routes (CRUD)
     if (path.startsWith("/api/users")) {
       const usersResult = await handleUsersRoute(method, path, request);
@@ -442,6 +585,22 @@ async function handleRequest(request, server) {
       }
     }
 
+    // Index series routes (CPI and other indices used for escalation)
+    if (path === "/api/index-series" || path.startsWith("/api/index-series/")) {
+      const indexSeriesResult = await handleIndexSeriesRoute(method, path, request);
+      if (indexSeriesResult) {
+        return indexSeriesResult;
+      }
+    }
+
+    // Target allocation and rebalancing planner routes
+    if (path.startsWith("/api/rebalancing/")) {
+      const rebalancingResult = await handleRebalancingRoute(method, path, request);
+      if (rebalancingResult) {
+        return rebalancingResult;
+      }
+    }
+
     // Views (HTML composite reports) and Reports (PDF reports)
     if (path.startsWith("/api/views") || path.startsWith("/api/reports")) {
       const reportsResult = await handleReportsRoute(method, path, request);
@@ -541,6 +700,20 @@ if (databaseExists()) {
   } catch (err) {
     console.warn("[Drawdown] Failed to process drawdowns on startup:", err.message);
   }
+
+  // Raise recurring other assets whose escalation anniversary has passed
+  try {
+    applyOtherAssetEscalations();
+  } catch (err) {
+    console.warn("[Escalation] Failed to apply other asset escalations on startup:", err.message);
+  }
+
+  // Re-estimate properties valued from a house price index
+  try {
+    applyOtherAssetIndexation();
+  } catch (err) {
+    console.warn("[Indexation] Failed to index property values on startup:", err.message);
+  }
 }
 
 // Initialise scheduled fetching (after server is ready)
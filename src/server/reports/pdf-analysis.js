@@ -98,6 +98,16 @@ function formatChange(pct) {
   return sign + pct.toFixed(1) + "%";
 }
 
+/**
+ * @description Format a risk ratio (Sharpe, Sortino) for display.
+ * @param {number|null} value - The ratio
+ * @returns {string} Formatted string like "0.85", or a dash when not available
+ */
+function formatRatio(value) {
+  if (value === null || value === undefined) return "\u2014";
+  return value.toFixed(2);
+}
+
 /**
  * @description Get the colour for a percentage change value.
  * @param {number} pct - The percentage change
@@ -843,7 +853,25 @@ export async function generateLeagueTablePdf(period, sort, dir, limit, benchmark
   const typeColWidth = 40;
   const returnColWidth = 55;
   const sparklineColWidth = 70;
-  const tableWidth = rankColWidth + nameColWidth + typeColWidth + returnColWidth + sparklineColWidth;
+  const drawdownColWidth = 50;
+  const ratioColWidth = 42;
+  const tableWidth = rankColWidth + nameColWidth + typeColWidth + returnColWidth + sparklineColWidth + drawdownColWidth + ratioColWidth * 2;
+  const riskColX = MARGIN_LEFT + rankColWidth + nameColWidth + typeColWidth + returnColWidth + sparklineColWidth;
+
+  /**
+   * @description Draw the max drawdown, Sharpe and Sortino cells of a row.
+   * @param {Object} item - Investment or benchmark row with the risk metrics
+   * @param {number} rowTextY - Baseline of the row text
+   */
+  function drawRiskCells(item, rowTextY) {
+    if (item.maxDrawdownPct !== null && item.maxDrawdownPct !== undefined) {
+      drawRightAligned(page, formatChange(item.maxDrawdownPct), riskColX, drawdownColWidth, rowTextY, fonts.medium, FONT_SIZE_ROW, changeColour(item.maxDrawdownPct));
+    } else {
+      drawRightAligned(page, "\u2014", riskColX, drawdownColWidth, rowTextY, fonts.medium, FONT_SIZE_ROW, COLOURS.brand300);
+    }
+    drawRightAligned(page, formatRatio(item.sharpe), riskColX + drawdownColWidth, ratioColWidth, rowTextY, fonts.medium, FONT_SIZE_ROW, item.sharpe !== null ? COLOURS.brand800 : COLOURS.brand300);
+    drawRightAligned(page, formatRatio(item.sortino), riskColX + drawdownColWidth + ratioColWidth, ratioColWidth, rowTextY, fonts.medium, FONT_SIZE_ROW, item.sortino !== null ? COLOURS.brand800 : COLOURS.brand300);
+  }
 
   // Header row
   const headerY = y - HEADER_ROW_HEIGHT + 4;
@@ -878,6 +906,9 @@ export async function generateLeagueTablePdf(period, sort, dir, limit, benchmark
     size: FONT_SIZE_HEADER,
     color: COLOURS.brand800,
   });
+  drawRightAligned(page, "Max DD", riskColX, drawdownColWidth, headerY, fonts.bold, FONT_SIZE_HEADER, COLOURS.brand800);
+  drawRightAligned(page, "Sharpe", riskColX + drawdownColWidth, ratioColWidth, headerY, fonts.bold, FONT_SIZE_HEADER, COLOURS.brand800);
+  drawRightAligned(page, "Sortino", riskColX + drawdownColWidth + ratioColWidth, ratioColWidth, headerY, fonts.bold, FONT_SIZE_HEADER, COLOURS.brand800);
   y -= HEADER_ROW_HEIGHT;
 
   page.drawLine({
@@ -923,6 +954,7 @@ export async function generateLeagueTablePdf(period, sort, dir, limit, benchmark
         MARGIN_LEFT + rankColWidth + nameColWidth + typeColWidth,
         returnColWidth, bmRowY + 3, fonts.medium, FONT_SIZE_ROW, changeColour(bmData[b].returnPct));
     }
+    drawRiskCells(bmData[b], bmRowY + 3);
     y = bmRowY;
   }
 
@@ -1006,6 +1038,7 @@ export async function generateLeagueTablePdf(period, sort, dir, limit, benchmark
       }
     }
 
+    drawRiskCells(investment, rowY + 3);
     y = rowY;
   }
 
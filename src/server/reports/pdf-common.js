@@ -1,6 +1,8 @@
 import { rgb } from "@libpdf/core";
 import { readFileSync } from "node:fs";
 import { resolve } from "node:path";
+import { getAllUsers } from "../db/users-db.js";
+import { getReportParams } from "../db/report-params-db.js";
 
 /**
  * @description Shared constants and utility functions for PDF report generation.
@@ -155,3 +157,48 @@ export function drawPageFooters(pages, reportTitle, marginLeft, usableWidth, fon
     });
   }
 }
+
+/**
+ * @description Resolve report params tokens to build the params array.
+ * Replaces tokens like "USER1" with actual values from report_params.
+ * @param {Array<string>} params - Raw params array from the report definition
+ * @returns {Array<string>} Params with tokens substituted
+ */
+export function resolveParams(params) {
+  if (!params || params.length === 0) return [];
+  try {
+    const tokenMap = getReportParams();
+    const tokens = Object.keys(tokenMap);
+    return params.map(function (param) {
+      let result = param;
+      for (let i = 0; i < tokens.length; i++) {
+        result = result.split(tokens[i]).join(tokenMap[tokens[i]]);
+      }
+      return result;
+    });
+  } catch {
+    return params;
+  }
+}
+
+/**
+ * @description Convert params (user initials, in report order) to user IDs.
+ * Unknown initials are ignored. An empty params list selects every user.
+ * @param {Array<string>} params - Resolved params
+ * @returns {Array<number>|null} User IDs in params order, or null for all users
+ */
+export function resolveUserIds(params) {
+  if (params.length === 0) return null;
+
+  const byInitials = {};
+  for (const user of getAllUsers()) {
+    byInitials[user.initials.toUpperCase()] = user.id;
+  }
+
+  const ids = [];
+  for (const param of params) {
+    const id = byInitials[param.trim().toUpperCase()];
+    if (id && ids.indexOf(id) === -1) ids.push(id);
+  }
+  return ids;
+}
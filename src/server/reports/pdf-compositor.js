@@ -6,6 +6,12 @@ import { renderPortfolioSummaryBlock } from "./pdf-portfolio-summary.js";
 import { renderPortfolioDetailBlock } from "./pdf-portfolio-detail.js";
 import { renderChartBlock, renderChartGroupBlock, getChartGroupLayout } from "./pdf-chart.js";
 import { renderPortfolioValueChartBlock } from "./pdf-portfolio-value-chart.js";
+import { renderIsaAllowanceBlock } from "./pdf-isa-allowance.js";
+import { renderP60SummaryBlock } from "./pdf-p60-summary.js";
+import { renderRetirementProjectionBlock } from "./pdf-retirement-projection.js";
+import { renderCorrelationMatrixBlock } from "./pdf-correlation-matrix.js";
+import { renderOtherAssetsChartBlock } from "./pdf-other-assets-chart.js";
+import { renderIhtEstateBlock } from "./pdf-iht-estate.js";
 
 /**
  * @description Block type registry mapping type names to their renderer
@@ -52,6 +58,42 @@ const BLOCK_TYPES = {
     pageHeight: 595.28,
     usableWidth: 761.89,
   },
+  isa_allowance: {
+    render: renderIsaAllowanceBlock,
+    orientation: "portrait",
+    pageHeight: 841.89,
+    usableWidth: 515.28,
+  },
+  p60_summary: {
+    render: renderP60SummaryBlock,
+    orientation: "portrait",
+    pageHeight: 841.89,
+    usableWidth: 515.28,
+  },
+  retirement_projection: {
+    render: renderRetirementProjectionBlock,
+    orientation: "landscape",
+    pageHeight: 595.28,
+    usableWidth: 761.89,
+  },
+  correlation_matrix: {
+    render: renderCorrelationMatrixBlock,
+    orientation: "landscape",
+    pageHeight: 595.28,
+    usableWidth: 761.89,
+  },
+  other_assets_chart: {
+    render: renderOtherAssetsChartBlock,
+    orientation: "landscape",
+    pageHeight: 595.28,
+    usableWidth: 761.89,
+  },
+  iht_estate: {
+    render: renderIhtEstateBlock,
+    orientation: "portrait",
+    pageHeight: 841.89,
+    usableWidth: 515.28,
+  },
 };
 
 /** @description Shared margins (same for all page orientations) */
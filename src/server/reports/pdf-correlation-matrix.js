@@ -0,0 +1,375 @@
+import { PDF, rgb } from "@libpdf/core";
+import { getAllUsers } from "../db/users-db.js";
+import { getBenchmarkByDescription } from "../db/benchmarks-db.js";
+import { buildCorrelationMatrix, resolveInvestmentIds, PERIOD_WEEKS } from "../services/analysis-service.js";
+import { isTestMode } from "../test-mode.js";
+import { drawPageHeader, drawPageFooters, resolveParams } from "./pdf-common.js";
+import { embedRobotoFonts } from "./pdf-fonts.js";
+
+/**
+ * @description Brand colours converted to RGB 0-1 range for PDF rendering.
+ * The heatmap runs from red (-1) through white (0) to blue (+1).
+ */
+const COLOURS = {
+  brand800: rgb(0.15, 0.23, 0.42),
+  brand700: rgb(0.2, 0.3, 0.5),
+  brand600: rgb(0.35, 0.42, 0.55),
+  brand200: rgb(0.82, 0.85, 0.9),
+  brand100: rgb(0.91, 0.93, 0.96),
+  emerald900: rgb(0.02, 0.32, 0.21),
+  black: rgb(0, 0, 0),
+  white: rgb(1, 1, 1),
+  green100: rgb(0.86, 0.94, 0.87),
+  benchmarkBg: rgb(0.95, 0.95, 0.96),
+};
+
+/** @description End points of the heatmap colour scale as 0-1 RGB triples */
+const NEGATIVE_RGB = [0.86, 0.15, 0.15];
+const POSITIVE_RGB = [0.15, 0.39, 0.92];
+
+/** @description A4 landscape dimensions in points */
+const A4_LANDSCAPE_WIDTH = 841.89;
+const A4_LANDSCAPE_HEIGHT = 595.28;
+const MARGIN_LEFT = 40;
+const MARGIN_RIGHT = 40;
+const MARGIN_TOP = 40;
+const MARGIN_BOTTOM = 40;
+const USABLE_WIDTH = A4_LANDSCAPE_WIDTH - MARGIN_LEFT - MARGIN_RIGHT;
+
+/** @description Font sizes used in the report */
+const FONT_SIZE_TITLE = 12;
+const FONT_SIZE_SUBTITLE = 8;
+const FONT_SIZE_HEADING = 8;
+const FONT_SIZE_LABEL = 7;
+const FONT_SIZE_CELL = 6;
+
+/** @description Layout dimensions in points */
+const TITLE_BAR_HEIGHT = 28;
+const SUBTITLE_HEIGHT = 14;
+const LABEL_WIDTH = 170;
+const COLUMN_HEADER_HEIGHT = 14;
+const MAX_CELL_SIZE = 28;
+const MIN_CELL_SIZE_FOR_VALUES = 16;
+const PANEL_WIDTH = 230;
+const PANEL_GAP = 20;
+const KEY_HEIGHT = 8;
+
+/** @description Number of most-correlated pairs listed beside the heatmap */
+const PAIRS_SHOWN = 10;
+
+/**
+ * @description Format a correlation to two decimal places with an explicit sign.
+ * @param {number|null} value - The correlation
+ * @returns {string} Formatted value, or an en-dash when null
+ */
+function formatCorrelation(value) {
+  if (value === null || value === undefined) return "–";
+  return (value > 0 ? "+" : "") + value.toFixed(2);
+}
+
+/**
+ * @description Work out the heatmap fill for a correlation by blending white
+ * towards red for negative values and towards blue for positive ones.
+ * @param {number|null} value - The correlation, from -1 to +1
+ * @returns {Object} RGB colour (light grey when null)
+ */
+function heatColour(value) {
+  if (value === null || value === undefined) return COLOURS.benchmarkBg;
+  const strength = Math.min(1, Math.abs(value));
+  const end = value < 0 ? NEGATIVE_RGB : POSITIVE_RGB;
+  return rgb(1 + (end[0] - 1) * strength, 1 + (end[1] - 1) * strength, 1 + (end[2] - 1) * strength);
+}
+
+/**
+ * @description Shorten text with an ellipsis so it fits a width.
+ * @param {string} text - The text
+ * @param {Object} font - Embedded font instance
+ * @param {number} fontSize - Font size in points
+ * @param {number} maxWidth - Available width in points
+ * @returns {string} The text, truncated if needed
+ */
+function truncateText(text, font, fontSize, maxWidth) {
+  if (font.widthOfTextAtSize(text, fontSize) <= maxWidth) return text;
+  let truncated = text;
+  while (truncated.length > 1 && font.widthOfTextAtSize(truncated + "…", fontSize) > maxWidth) {
+    truncated = truncated.slice(0, -1);
+  }
+  return truncated + "…";
+}
+
+/**
+ * @description Draw text centred on a point.
+ * @param {Object} page - PDFPage instance
+ * @param {string} text - The text to draw
+ * @param {number} cx - Centre x
+ * @param {number} y - Baseline y
+ * @param {Object} font - Embedded font instance
+ * @param {number} fontSize - Font size in points
+ * @param {Object} color - RGB colour
+ */
+function drawCentred(page, text, cx, y, font, fontSize, color) {
+  page.drawText(text, { x: cx - font.widthOfTextAtSize(text, fontSize) / 2, y: y, font: font, size: fontSize, color: color });
+}
+
+/**
+ * @description Split resolved params into user IDs (from initials) and benchmark
+ * IDs (from "bm:DESCRIPTION" entries). Unknown initials and benchmarks are
+ * ignored. With no initials given every user is included.
+ * @param {Array<string>} params - Resolved params
+ * @returns {{ userIds: Array<number>, benchmarkIds: Array<number> }} The selection
+ */
+function resolveSelection(params) {
+  const users = getAllUsers();
+  const byInitials = {};
+  for (const user of users) {
+    byInitials[user.initials.toUpperCase()] = user.id;
+  }
+
+  const userIds = [];
+  const benchmarkIds = [];
+  let initialsGiven = false;
+  for (const param of params) {
+    const value = param.trim();
+    if (value.indexOf("bm:") === 0) {
+      const bm = getBenchmarkByDescription(value.slice(3).trim());
+      if (bm && benchmarkIds.indexOf(bm.id) === -1) benchmarkIds.push(bm.id);
+    } else if (value) {
+      initialsGiven = true;
+      const id = byInitials[value.toUpperCase()];
+      if (id && userIds.indexOf(id) === -1) userIds.push(id);
+    }
+  }
+
+  if (!initialsGiven) {
+    return {
+      userIds: users.map(function (u) {
+        return u.id;
+      }),
+      benchmarkIds: benchmarkIds,
+    };
+  }
+  return { userIds: userIds, benchmarkIds: benchmarkIds };
+}
+
+/**
+ * @description Draw the colour key for the heatmap: a strip from -1 to +1.
+ * @param {Object} page - PDFPage instance
+ * @param {number} x - Left edge
+ * @param {number} y - Bottom of the strip
+ * @param {number} width - Width of the strip
+ * @param {Object} fonts - Roboto font objects
+ */
+function drawColourKey(page, x, y, width, fonts) {
+  const steps = 20;
+  const stepWidth = width / steps;
+  for (let i = 0; i < steps; i++) {
+    const value = -1 + (2 * (i + 0.5)) / steps;
+    page.drawRectangle({ x: x + i * stepWidth, y: y, width: stepWidth + 0.2, height: KEY_HEIGHT, color: heatColour(value) });
+  }
+  drawCentred(page, "-1", x, y - 9, fonts.regular, FONT_SIZE_LABEL, COLOURS.brand600);
+  drawCentred(page, "0", x + width / 2, y - 9, fonts.regular, FONT_SIZE_LABEL, COLOURS.brand600);
+  drawCentred(page, "+1", x + width, y - 9, fonts.regular, FONT_SIZE_LABEL, COLOURS.brand600);
+}
+
+/**
+ * @description Draw the correlation heatmap and the side panel listing the most
+ * correlated pairs. Rows are labelled with a number and name; columns carry the
+ * same numbers, so labels never need to be rotated.
+ * @param {Object} page - PDFPage instance
+ * @param {Object} data - Matrix data from buildCorrelationMatrix
+ * @param {number} top - Top of the drawing area
+ * @param {Object} fonts - Roboto font objects
+ * @param {Object} headerRowColour - Fill for the column header row
+ */
+function drawHeatmap(page, data, top, fonts, headerRowColour) {
+  const count = data.series.length;
+  const gridWidth = USABLE_WIDTH - PANEL_WIDTH - PANEL_GAP - LABEL_WIDTH;
+  const gridHeight = top - MARGIN_BOTTOM - COLUMN_HEADER_HEIGHT - 10;
+  const cell = Math.min(MAX_CELL_SIZE, gridWidth / count, gridHeight / count);
+  const gridLeft = MARGIN_LEFT + LABEL_WIDTH;
+  const gridTop = top - COLUMN_HEADER_HEIGHT;
+  const showValues = cell >= MIN_CELL_SIZE_FOR_VALUES;
+
+  // Column numbers
+  page.drawRectangle({ x: gridLeft, y: gridTop, width: cell * count, height: COLUMN_HEADER_HEIGHT, color: headerRowColour });
+  for (let c = 0; c < count; c++) {
+    drawCentred(page, String(c + 1), gridLeft + c * cell + cell / 2, gridTop + 4, fonts.bold, FONT_SIZE_LABEL, COLOURS.brand700);
+  }
+
+  for (let r = 0; r < count; r++) {
+    const rowBottom = gridTop - (r + 1) * cell;
+    const s = data.series[r];
+    const labelFont = s.kind === "benchmark" ? fonts.bold : fonts.medium;
+    const label = truncateText(r + 1 + ". " + s.label, labelFont, FONT_SIZE_LABEL, LABEL_WIDTH - 6);
+    page.drawText(label, { x: MARGIN_LEFT + 2, y: rowBottom + cell / 2 - 2.5, font: labelFont, size: FONT_SIZE_LABEL, color: COLOURS.black });
+
+    for (let c = 0; c < count; c++) {
+      const value = data.matrix[r][c];
+      const x = gridLeft + c * cell;
+      // Cells are drawn slightly smaller than their pitch to leave a white gap
+      page.drawRectangle({ x: x + 0.5, y: rowBottom + 0.5, width: cell - 1, height: cell - 1, color: heatColour(value) });
+      if (showValues && r !== c) {
+        const textColour = value !== null && Math.abs(value) > 0.6 ? COLOURS.white : COLOURS.black;
+        drawCentred(page, formatCorrelation(value), x + cell / 2, rowBottom + cell / 2 - 2, fonts.regular, FONT_SIZE_CELL, textColour);
+      }
+    }
+  }
+
+  // --- Side panel: colour key and the most correlated pairs ---
+  const panelX = MARGIN_LEFT + USABLE_WIDTH - PANEL_WIDTH;
+  let panelY = top;
+
+  page.drawText("Correlation of weekly returns", { x: panelX, y: panelY - FONT_SIZE_HEADING, font: fonts.bold, size: FONT_SIZE_HEADING, color: COLOURS.brand800 });
+  panelY -= FONT_SIZE_HEADING + 8 + KEY_HEIGHT;
+  drawColourKey(page, panelX + 6, panelY, PANEL_WIDTH - 12, fonts);
+  panelY -= 24;
+
+  page.drawText("Most closely correlated holdings", { x: panelX, y: panelY - FONT_SIZE_HEADING, font: fonts.bold, size: FONT_SIZE_HEADING, color: COLOURS.brand800 });
+  panelY -= FONT_SIZE_HEADING + 6;
+
+  const pairs = data.pairs.slice(0, PAIRS_SHOWN);
+  if (pairs.length === 0) {
+    page.drawText("Fewer than two investments to compare.", { x: panelX, y: panelY - FONT_SIZE_LABEL, font: fonts.regular, size: FONT_SIZE_LABEL, color: COLOURS.brand600 });
+    panelY -= FONT_SIZE_LABEL + 5;
+  }
+  for (const pair of pairs) {
+    const value = formatCorrelation(pair.correlation);
+    const valueWidth = fonts.bold.widthOfTextAtSize(value, FONT_SIZE_LABEL);
+    const names = truncateText(pair.a + " / " + pair.b, fonts.regular, FONT_SIZE_LABEL, PANEL_WIDTH - valueWidth - 8);
+    page.drawText(names, { x: panelX, y: panelY - FONT_SIZE_LABEL, font: fonts.regular, size: FONT_SIZE_LABEL, color: COLOURS.brand700 });
+    page.drawText(value, { x: panelX + PANEL_WIDTH - valueWidth, y: panelY - FONT_SIZE_LABEL, font: fonts.bold, size: FONT_SIZE_LABEL, color: COLOURS.black });
+    panelY -= FONT_SIZE_LABEL + 5;
+  }
+  panelY -= 8;
+
+  const notes = [
+    "Values near +1 move together, near 0 independently,",
+    "and near -1 in opposite directions. Pairs with fewer than",
+    "eight shared weeks are shown blank.",
+  ];
+  if (!showValues) notes.push("Too many series to print the values in each cell.");
+  for (const note of notes) {
+    page.drawText(note, { x: panelX, y: panelY - FONT_SIZE_LABEL, font: fonts.regular, size: FONT_SIZE_LABEL, color: COLOURS.brand600 });
+    panelY -= FONT_SIZE_LABEL + 4;
+  }
+}
+
+/**
+ * @description Render the Correlation Matrix block into a shared PDF context.
+ * Shows a heatmap of the correlation of weekly GBP returns between the
+ * investments held by the chosen family members (and any benchmarks named in
+ * the params) over the block's period, with the most correlated pairs listed
+ * alongside. Fills the rest of the landscape page. Does not add footers — the
+ * caller is responsible for that.
+ * @param {Object} ctx - Shared rendering context
+ * @param {Object} ctx.pdf - The PDF document
+ * @param {Object} ctx.page - Current page (updated in place on ctx)
+ * @param {Array<Object>} ctx.pages - Array of all pages
+ * @param {number} ctx.y - Current y position (updated in place on ctx)
+ * @param {Array<string>} [params] - User initials (or tokens) and "bm:DESCRIPTION" entries; no initials means everyone
+ * @param {Object} [block] - Block definition; may set period ("1y" by default) and holdings ("current", "historic" or "all")
+ * @param {Object} [overrides] - Pre-resolved { investmentIds, benchmarkIds, filterText } used by the analysis page
+ */
+export function renderCorrelationMatrixBlock(ctx, params, block, overrides) {
+  const page = ctx.page;
+  let y = ctx.y;
+  const fonts = ctx.fonts;
+
+  const blockDef = block || {};
+  const period = PERIOD_WEEKS[blockDef.period] ? blockDef.period : "1y";
+
+  let investmentIds;
+  let benchmarkIds;
+  let filterText;
+  if (overrides) {
+    investmentIds = overrides.investmentIds;
+    benchmarkIds = overrides.benchmarkIds;
+    filterText = overrides.filterText;
+  } else {
+    const holdings = ["current", "historic", "all"].indexOf(blockDef.holdings) !== -1 ? blockDef.holdings : "current";
+    const selection = resolveSelection(resolveParams(params));
+    investmentIds = resolveInvestmentIds(holdings, selection.userIds, []);
+    benchmarkIds = selection.benchmarkIds;
+    filterText = holdings === "all" ? "All investments" : holdings === "historic" ? "Historic holdings only" : "Current holdings";
+  }
+
+  const data = buildCorrelationMatrix(period, investmentIds, benchmarkIds);
+
+  const testMode = isTestMode();
+  const titleBarColour = testMode ? COLOURS.emerald900 : COLOURS.brand800;
+  const headerRowColour = testMode ? COLOURS.green100 : COLOURS.brand100;
+
+  page.drawRectangle({ x: MARGIN_LEFT, y: y - TITLE_BAR_HEIGHT, width: USABLE_WIDTH, height: TITLE_BAR_HEIGHT, color: titleBarColour });
+  page.drawText("Correlation Matrix — " + data.periodLabel, {
+    x: MARGIN_LEFT + 10,
+    y: y - TITLE_BAR_HEIGHT + 9,
+    font: fonts.bold,
+    size: FONT_SIZE_TITLE,
+    color: COLOURS.white,
+  });
+  y -= TITLE_BAR_HEIGHT;
+
+  const subtitle = (filterText ? filterText + " — " : "") + "weekly returns in GBP to " + data.asOf.split("-").reverse().join("/");
+  page.drawText(subtitle, { x: MARGIN_LEFT + 4, y: y - SUBTITLE_HEIGHT + 3, font: fonts.regular, size: FONT_SIZE_SUBTITLE, color: COLOURS.brand600 });
+  y -= SUBTITLE_HEIGHT + 8;
+
+  if (data.series.length < 2) {
+    page.drawText("Not enough price history to compare investments over this period.", {
+      x: MARGIN_LEFT,
+      y: y - FONT_SIZE_LABEL,
+      font: fonts.medium,
+      size: FONT_SIZE_LABEL,
+      color: COLOURS.brand600,
+    });
+    ctx.y = y - 20;
+    return;
+  }
+
+  drawHeatmap(page, data, y, fonts, headerRowColour);
+
+  ctx.y = MARGIN_BOTTOM;
+}
+
+/**
+ * @description Generate a standalone PDF for the Correlation Matrix report.
+ * Creates a landscape PDF document, renders the block, adds footers, and returns bytes.
+ * @param {Array<string>} [params] - Optional user initials (or tokens) and "bm:DESCRIPTION" entries
+ * @param {Object} [options] - Optional { period, holdings }
+ * @returns {Promise<Uint8Array>} The PDF file bytes
+ */
+export async function generateCorrelationMatrixPdf(params, options) {
+  const pdf = PDF.create();
+  const fonts = embedRobotoFonts(pdf);
+  const page = pdf.addPage({ size: "a4", orientation: "landscape" });
+  const pages = [page];
+  const y = drawPageHeader(pdf, page, MARGIN_LEFT, A4_LANDSCAPE_HEIGHT, MARGIN_TOP, fonts);
+
+  const ctx = { pdf: pdf, page: page, pages: pages, y: y, pageWidths: [USABLE_WIDTH], fonts: fonts };
+  renderCorrelationMatrixBlock(ctx, params || [], options || {});
+
+  drawPageFooters(ctx.pages, "Correlation Matrix", MARGIN_LEFT, USABLE_WIDTH, fonts);
+  return await pdf.save();
+}
+
+/**
+ * @description Generate the Correlation Matrix PDF for the analysis page, using
+ * the investments and benchmarks already resolved from the page's filters.
+ * @param {string} period - Period code
+ * @param {Array<number>} benchmarkIds - Benchmark IDs to include
+ * @param {Array<number>|null} investmentIds - Filtered investment IDs, or null for all
+ * @param {string} filterText - Description of the active filters
+ * @returns {Promise<Uint8Array>} The PDF file bytes
+ */
+export async function generateAnalysisCorrelationPdf(period, benchmarkIds, investmentIds, filterText) {
+  const pdf = PDF.create();
+  const fonts = embedRobotoFonts(pdf);
+  const page = pdf.addPage({ size: "a4", orientation: "landscape" });
+  const pages = [page];
+  const y = drawPageHeader(pdf, page, MARGIN_LEFT, A4_LANDSCAPE_HEIGHT, MARGIN_TOP, fonts);
+
+  const ctx = { pdf: pdf, page: page, pages: pages, y: y, pageWidths: [USABLE_WIDTH], fonts: fonts };
+  renderCorrelationMatrixBlock(ctx, [], { period: period }, { investmentIds: investmentIds, benchmarkIds: benchmarkIds, filterText: filterText });
+
+  drawPageFooters(ctx.pages, "Correlation Matrix", MARGIN_LEFT, USABLE_WIDTH, fonts);
+  return await pdf.save();
+}
@@ -1,8 +1,9 @@
 import { PDF, rgb } from "@libpdf/core";
-import { getHouseholdAssetsSummary } from "../db/other-assets-db.js";
+import { getNetWorthSummary, buildNetWorthHistory } from "../services/net-worth-service.js";
 import { isTestMode } from "../test-mode.js";
 import { drawPageHeader, drawPageFooters } from "./pdf-common.js";
 import { embedRobotoFonts } from "./pdf-fonts.js";
+import { renderChartBlock } from "./pdf-chart.js";
 
 /**
  * @description Frequency display labels matching the HTML report block.
@@ -31,6 +32,7 @@ const COLOURS = {
   black: rgb(0, 0, 0),
   white: rgb(1, 1, 1),
   green100: rgb(0.86, 0.94, 0.87),
+  red600: rgb(0.86, 0.15, 0.15),
 };
 
 /** @description A4 page dimensions in points */
@@ -70,6 +72,12 @@ const ROW_HEIGHT = 14;
 const HEADER_ROW_HEIGHT = 16;
 const CATEGORY_GAP = 18;
 
+/** @description Height of the net worth over time chart, in points */
+const NET_WORTH_CHART_HEIGHT = 230;
+
+/** @description Months of net worth history shown in the chart */
+const NET_WORTH_MONTHS = 12;
+
 /**
  * @description Format a scaled integer (x 10000) as a whole-pounds string
  * with thousand separators. No currency symbol.
@@ -94,6 +102,33 @@ function formatDate(dateStr) {
   return parts[2] + "/" + parts[1] + "/" + parts[0];
 }
 
+/**
+ * @description Format a scaled integer (x 10000) as a whole-pounds string,
+ * with a leading minus sign when negative.
+ * @param {number} scaledValue - The value x 10000
+ * @returns {string} Formatted string like "-1,234"
+ */
+function formatSignedGBP(scaledValue) {
+  return (scaledValue < 0 ? "-" : "") + formatGBP(Math.abs(scaledValue));
+}
+
+/**
+ * @description Get the "Every" and "Notes" cells for an item. A liability shows
+ * its interest rate and monthly repayment in place of a frequency.
+ * @param {Object} item - Asset item
+ * @returns {{ every: string, notes: string }} Cell text
+ */
+function getTermsCells(item) {
+  let notes = (item.notes || "") + (item.executor_reference ? " [" + item.executor_reference + "]" : "");
+  if (item.category !== "liability") {
+    return { every: item.frequency ? (FREQUENCY_LABELS[item.frequency] || item.frequency) : "", notes: notes };
+  }
+  if (item.repayment) {
+    notes = "Repays " + formatGBP(item.repayment) + " a month" + (notes ? " — " + notes : "");
+  }
+  return { every: item.interest_rate !== null ? item.interest_rate + "%" : "", notes: notes };
+}
+
 /**
  * @description Get user display name — "Joint" for Joint user, initials otherwise.
  * @param {Object} item - Asset item with user_first_name and user_initials
@@ -146,7 +181,9 @@ function truncateText(text, font, fontSize, maxWidth) {
 
 /**
  * @description Render the Household Assets block into a shared PDF context.
- * Draws the block title, category tables, and summary section.
+ * Draws the block title, category tables (liabilities last), the summary with
+ * net worth, any values overdue for revaluation, and a chart of net worth at
+ * each month end over the last year. Overdue edited dates are shown in red.
  * Does not add footers — the caller is responsible for that.
  * @param {Object} ctx - Shared rendering context
  * @param {Object} ctx.pdf - The PDF document
@@ -162,8 +199,8 @@ export function renderHouseholdAssetsBlock(ctx) {
   let y = ctx.y;
   const fonts = ctx.fonts;
 
-  const data = getHouseholdAssetsSummary();
-  const categoryOrder = ["pension", "property", "savings", "alternative"];
+  const data = getNetWorthSummary();
+  const categoryOrder = ["pension", "property", "savings", "alternative", "liability"];
   const activeCats = categoryOrder.filter(function (key) {
     const cat = data.categories[key];
     return cat && cat.items.length > 0;
@@ -266,19 +303,21 @@ export function renderHouseholdAssetsBlock(ctx) {
       const font = fonts.medium;
 
       // Row values
+      const terms = getTermsCells(item);
       const cellValues = {
         user: getUserDisplay(item),
         description: item.description || "",
         value: formatGBP(item.value),
-        every: item.frequency ? (FREQUENCY_LABELS[item.frequency] || item.frequency) : "",
+        every: terms.every,
         edited: formatDate(item.last_updated),
-        notes: (item.notes || "") + (item.executor_reference ? " [" + item.executor_reference + "]" : ""),
+        notes: terms.notes,
       };
 
       for (const col of COLUMNS) {
         let cellText = cellValues[col.key] || "";
         // Truncate to fit column width (with 4pt padding)
         cellText = truncateText(cellText, font, FONT_SIZE_ROW, col.width - 4);
+        const cellColour = col.key === "edited" && item.revalue_overdue ? COLOURS.red600 : COLOURS.black;
 
         if (col.align === "right") {
           drawRightAligned(
@@ -289,7 +328,7 @@ export function renderHouseholdAssetsBlock(ctx) {
             textY,
             font,
             FONT_SIZE_ROW,
-            COLOURS.black,
+            cellColour,
           );
         } else {
           page.drawText(cellText, {
@@ -297,7 +336,7 @@ export function renderHouseholdAssetsBlock(ctx) {
             y: textY,
             font: font,
             size: FONT_SIZE_ROW,
-            color: COLOURS.black,
+            color: cellColour,
           });
         }
       }
@@ -317,7 +356,7 @@ export function renderHouseholdAssetsBlock(ctx) {
   }
 
   // --- Summary section ---
-  ensureSpace(60);
+  ensureSpace(130);
 
   // Summary separator line
   page.drawLine({
@@ -365,25 +404,101 @@ export function renderHouseholdAssetsBlock(ctx) {
   });
   y -= 14;
 
-  // Assets total
-  page.drawText("Assets", {
-    x: MARGIN_LEFT,
-    y: y,
-    font: fonts.medium,
-    size: FONT_SIZE_SUMMARY_LABEL,
-    color: COLOURS.black,
+  // Assets, liabilities, portfolios and the net worth they add up to
+  const summaryLines = [
+    { label: "Assets", value: formatGBP(data.totals.value_total) },
+    { label: "Liabilities", value: formatSignedGBP(-data.totals.liabilities_total) },
+    { label: "Portfolios", value: formatGBP(data.totals.portfolio_total) },
+    { label: "Net worth", value: formatSignedGBP(data.totals.net_worth), rule: true },
+  ];
+  for (const line of summaryLines) {
+    if (line.rule) {
+      page.drawLine({
+        start: { x: MARGIN_LEFT + 80, y: y + 10 },
+        end: { x: MARGIN_LEFT + 160, y: y + 10 },
+        color: COLOURS.brand200,
+        thickness: 0.5,
+      });
+    }
+    page.drawText(line.label, {
+      x: MARGIN_LEFT,
+      y: y,
+      font: line.rule ? fonts.bold : fonts.medium,
+      size: FONT_SIZE_SUMMARY_LABEL,
+      color: COLOURS.black,
+    });
+    drawRightAligned(
+      page,
+      line.value,
+      MARGIN_LEFT + 80,
+      80,
+      y,
+      fonts.bold,
+      FONT_SIZE_SUMMARY_VALUE,
+      COLOURS.black,
+    );
+    y -= 14;
+  }
+  y -= 10;
+
+  // --- Values overdue for revaluation ---
+  const overdue = [];
+  for (const catKey of categoryOrder) {
+    const cat = data.categories[catKey];
+    if (!cat) continue;
+    for (const item of cat.items) {
+      if (item.revalue_overdue) overdue.push(item);
+    }
+  }
+  if (overdue.length > 0) {
+    ensureSpace(FONT_SIZE_CATEGORY + 14 + overdue.length * ROW_HEIGHT);
+    page.drawText("Revaluation Due", {
+      x: MARGIN_LEFT,
+      y: y,
+      font: fonts.bold,
+      size: FONT_SIZE_CATEGORY,
+      color: COLOURS.red600,
+    });
+    y -= FONT_SIZE_CATEGORY + 6;
+    for (const item of overdue) {
+      const line = item.description + " — last updated " + formatDate(item.last_updated) +
+        ", due " + formatDate(item.revalue_due_date) + " (every " + item.revalue_months + " months)";
+      page.drawText(truncateText(line, fonts.medium, FONT_SIZE_ROW + 1, USABLE_WIDTH), {
+        x: MARGIN_LEFT,
+        y: y,
+        font: fonts.medium,
+        size: FONT_SIZE_ROW + 1,
+        color: COLOURS.black,
+      });
+      y -= ROW_HEIGHT - 2;
+    }
+    y -= 10;
+  }
+
+  // --- Net worth over time ---
+  ensureSpace(NET_WORTH_CHART_HEIGHT + 10);
+  const history = buildNetWorthHistory(NET_WORTH_MONTHS);
+  ctx.page = page;
+  ctx.y = y;
+  renderChartBlock(ctx, [], {
+    _bounds: { left: MARGIN_LEFT, width: USABLE_WIDTH, bottom: y - NET_WORTH_CHART_HEIGHT },
+    _chartData: {
+      title: "Net Worth",
+      subTitle: "Month ends over the last " + NET_WORTH_MONTHS + " months, valued at the time",
+      monthsToShow: NET_WORTH_MONTHS,
+      sampleDates: history.dates,
+      series: [
+        { label: "Net worth", type: "portfolio", values: history.net_worth },
+        { label: "Portfolios", type: "portfolio", values: history.portfolio },
+        { label: "Other assets", type: "portfolio", values: history.other_assets },
+        { label: "Liabilities", type: "portfolio", values: history.liabilities },
+      ],
+      events: [],
+      valueMode: "value",
+    },
   });
-  drawRightAligned(
-    page,
-    formatGBP(data.totals.value_total),
-    MARGIN_LEFT + 80,
-    80,
-    y,
-    fonts.bold,
-    FONT_SIZE_SUMMARY_VALUE,
-    COLOURS.black,
-  );
-  y -= 24;
+  page = ctx.page;
+  y = ctx.y;
 
   // Write back modified state
   ctx.page = page;
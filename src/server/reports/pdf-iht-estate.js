@@ -0,0 +1,403 @@
+import { PDF, rgb } from "@libpdf/core";
+import { buildEstateEstimates } from "../services/iht-service.js";
+import { isTestMode } from "../test-mode.js";
+import { drawPageHeader, drawPageFooters, resolveParams, resolveUserIds } from "./pdf-common.js";
+import { embedRobotoFonts } from "./pdf-fonts.js";
+
+/**
+ * @description Brand colours converted to RGB 0-1 range for PDF rendering.
+ * Matches the Tailwind brand palette used in the HTML report.
+ */
+const COLOURS = {
+  brand800: rgb(0.15, 0.23, 0.42),
+  brand700: rgb(0.2, 0.3, 0.5),
+  brand600: rgb(0.35, 0.42, 0.55),
+  brand200: rgb(0.82, 0.85, 0.9),
+  brand100: rgb(0.91, 0.93, 0.96),
+  black: rgb(0, 0, 0),
+  white: rgb(1, 1, 1),
+  green100: rgb(0.86, 0.94, 0.87),
+  red600: rgb(0.76, 0.07, 0.12),
+};
+
+/** @description A4 page dimensions in points */
+const A4_WIDTH = 595.28;
+const A4_HEIGHT = 841.89;
+const MARGIN_LEFT = 40;
+const MARGIN_RIGHT = 40;
+const MARGIN_TOP = 40;
+const MARGIN_BOTTOM = 40;
+const USABLE_WIDTH = A4_WIDTH - MARGIN_LEFT - MARGIN_RIGHT;
+
+/**
+ * @description Column definitions for the estate items tables.
+ * x is relative to MARGIN_LEFT, width in points.
+ * @type {Array<{key: string, label: string, x: number, width: number, align: string}>}
+ */
+const ITEM_COLUMNS = [
+  { key: "description", label: "Description", x: 0, width: 230, align: "left" },
+  { key: "type", label: "Type", x: 230, width: 80, align: "left" },
+  { key: "share", label: "Share", x: 310, width: 70, align: "right" },
+  { key: "value", label: "Value", x: 380, width: 90, align: "right" },
+];
+
+/**
+ * @description Column definitions for the inheritance tax table. The second
+ * death column is drawn only for a person with a spouse or civil partner.
+ * x is relative to MARGIN_LEFT, width in points.
+ * @type {Array<{key: string, label: string, x: number, width: number, align: string}>}
+ */
+const TAX_COLUMNS = [
+  { key: "label", label: "", x: 0, width: 200, align: "left" },
+  { key: "alone", label: "Estate alone", x: 200, width: 130, align: "right" },
+  { key: "second", label: "Second death (couple)", x: 330, width: 140, align: "right" },
+];
+
+/** @description Font sizes used in the report */
+const FONT_SIZE_TITLE = 14;
+const FONT_SIZE_USER_HEADING = 10;
+const FONT_SIZE_SUBHEADING = 8;
+const FONT_SIZE_HEADER = 7;
+const FONT_SIZE_ROW = 7;
+
+/** @description Row heights in points */
+const ROW_HEIGHT = 14;
+const HEADER_ROW_HEIGHT = 16;
+const USER_HEADING_HEIGHT = 20;
+
+/**
+ * @description Format a decimal GBP value as a whole-pounds string
+ * with thousand separators. No currency symbol.
+ * @param {number} value - Decimal GBP value (e.g. 1234.56)
+ * @returns {string} Formatted string like "1,234"
+ */
+function formatGBP(value) {
+  if (!value) return "0";
+  return Math.round(value).toLocaleString("en-GB");
+}
+
+/**
+ * @description Format an item's share of the estate: blank for an item held
+ * outright, otherwise the percentage of a joint item.
+ * @param {number} share - Fraction of the item in the estate (0-1)
+ * @returns {string} Formatted share like "50% joint"
+ */
+function formatShare(share) {
+  if (share >= 1) return "";
+  return Math.round(share * 100) + "% joint";
+}
+
+/**
+ * @description Draw text right-aligned within a column.
+ * @param {Object} page - PDFPage instance
+ * @param {string} text - The text to draw
+ * @param {number} x - Left edge of column (absolute)
+ * @param {number} colWidth - Column width in points
+ * @param {number} y - Y position (baseline)
+ * @param {Object} font - Embedded font instance
+ * @param {number} fontSize - Font size in points
+ * @param {Object} color - RGB colour
+ */
+function drawRightAligned(page, text, x, colWidth, y, font, fontSize, color) {
+  const textWidth = font.widthOfTextAtSize(text, fontSize);
+  page.drawText(text, {
+    x: x + colWidth - textWidth - 2,
+    y: y,
+    font: font,
+    size: fontSize,
+    color: color,
+  });
+}
+
+/**
+ * @description Render the Inheritance Tax Estate block into a shared PDF
+ * context. For each family member, lists the assets in their estate (with
+ * their share of joint items), the pensions left outside it and the
+ * liabilities deducted, then the nil-rate bands and tax on the estate alone
+ * and, with a spouse or civil partner, on the second death. Does not add
+ * footers — the caller is responsible for that.
+ * @param {Object} ctx - Shared rendering context
+ * @param {Object} ctx.pdf - The PDF document
+ * @param {Object} ctx.page - Current page (updated in place on ctx)
+ * @param {Array<Object>} ctx.pages - Array of all pages (pushed to when new pages added)
+ * @param {number} ctx.y - Current y position (updated in place on ctx)
+ * @param {Array<number>} ctx.pageWidths - Per-page usable widths (pushed to when new pages added)
+ * @param {Array<string>} [params] - User initials (or tokens like USER1); empty for everyone
+ */
+export function renderIhtEstateBlock(ctx, params) {
+  const pdf = ctx.pdf;
+  let page = ctx.page;
+  const pages = ctx.pages;
+  let y = ctx.y;
+  const fonts = ctx.fonts;
+
+  const estimates = buildEstateEstimates(resolveUserIds(resolveParams(params)));
+
+  const testMode = isTestMode();
+  const headerRowColour = testMode ? COLOURS.green100 : COLOURS.brand100;
+
+  /**
+   * @description Check if there is enough vertical space for the next section.
+   * If not, add a new page with header and reset y.
+   * @param {number} needed - Points of vertical space needed
+   */
+  function ensureSpace(needed) {
+    if (y - needed < MARGIN_BOTTOM) {
+      page = pdf.addPage({ size: "a4", orientation: "portrait" });
+      pages.push(page);
+      if (ctx.pageWidths) ctx.pageWidths.push(USABLE_WIDTH);
+      y = drawPageHeader(pdf, page, MARGIN_LEFT, A4_HEIGHT, MARGIN_TOP, fonts);
+    }
+  }
+
+  /**
+   * @description Draw a table header row for the given columns.
+   * @param {Array<Object>} columns - Column definitions
+   */
+  function drawHeaderRow(columns) {
+    page.drawRectangle({
+      x: MARGIN_LEFT,
+      y: y - HEADER_ROW_HEIGHT,
+      width: USABLE_WIDTH,
+      height: HEADER_ROW_HEIGHT,
+      color: headerRowColour,
+    });
+
+    for (const col of columns) {
+      if (col.align === "right") {
+        drawRightAligned(page, col.label, MARGIN_LEFT + col.x, col.width, y - HEADER_ROW_HEIGHT + 5, fonts.bold, FONT_SIZE_HEADER, COLOURS.brand700);
+      } else {
+        page.drawText(col.label, {
+          x: MARGIN_LEFT + col.x + 2,
+          y: y - HEADER_ROW_HEIGHT + 5,
+          font: fonts.bold,
+          size: FONT_SIZE_HEADER,
+          color: COLOURS.brand700,
+        });
+      }
+    }
+
+    page.drawLine({
+      start: { x: MARGIN_LEFT, y: y - HEADER_ROW_HEIGHT },
+      end: { x: MARGIN_LEFT + USABLE_WIDTH, y: y - HEADER_ROW_HEIGHT },
+      color: COLOURS.brand200,
+      thickness: 0.5,
+    });
+    y -= HEADER_ROW_HEIGHT;
+  }
+
+  /**
+   * @description Draw one table data row.
+   * @param {Array<Object>} columns - Column definitions
+   * @param {Object} cellValues - Cell text keyed by column key
+   * @param {Object} [options] - { bold: boolean, colours: { key: rgb } }
+   */
+  function drawDataRow(columns, cellValues, options) {
+    const opts = options || {};
+    ensureSpace(ROW_HEIGHT + 2);
+
+    const rowY = y - ROW_HEIGHT;
+    const textY = rowY + 4;
+    const font = opts.bold ? fonts.bold : fonts.medium;
+
+    for (const col of columns) {
+      const cellText = cellValues[col.key] || "";
+      const colour = (opts.colours && opts.colours[col.key]) || COLOURS.black;
+      if (col.align === "right") {
+        drawRightAligned(page, cellText, MARGIN_LEFT + col.x, col.width, textY, font, FONT_SIZE_ROW, colour);
+      } else {
+        page.drawText(cellText, {
+          x: MARGIN_LEFT + col.x + 2,
+          y: textY,
+          font: font,
+          size: FONT_SIZE_ROW,
+          color: colour,
+        });
+      }
+    }
+
+    page.drawLine({
+      start: { x: MARGIN_LEFT, y: rowY },
+      end: { x: MARGIN_LEFT + USABLE_WIDTH, y: rowY },
+      color: COLOURS.brand100,
+      thickness: 0.3,
+    });
+    y -= ROW_HEIGHT;
+  }
+
+  /**
+   * @description Draw a small subheading above a table.
+   * @param {string} text - The subheading text
+   */
+  function drawSubheading(text) {
+    page.drawText(text, {
+      x: MARGIN_LEFT,
+      y: y - FONT_SIZE_SUBHEADING,
+      font: fonts.bold,
+      size: FONT_SIZE_SUBHEADING,
+      color: COLOURS.brand700,
+    });
+    y -= FONT_SIZE_SUBHEADING + 6;
+  }
+
+  /**
+   * @description Draw a line of explanatory text in a muted colour.
+   * @param {string} text - The note text
+   */
+  function drawNote(text) {
+    ensureSpace(ROW_HEIGHT);
+    page.drawText(text, {
+      x: MARGIN_LEFT,
+      y: y - FONT_SIZE_ROW - 2,
+      font: fonts.medium,
+      size: FONT_SIZE_ROW,
+      color: COLOURS.brand600,
+    });
+    y -= ROW_HEIGHT;
+  }
+
+  /**
+   * @description Draw a table of estate items with a total row.
+   * @param {string} heading - Subheading above the table
+   * @param {Array<Object>} items - Estate items
+   * @param {string} totalLabel - Label for the total row
+   * @param {number} total - Total value in GBP
+   */
+  function drawItemTable(heading, items, totalLabel, total) {
+    ensureSpace(FONT_SIZE_SUBHEADING + 6 + HEADER_ROW_HEIGHT + ROW_HEIGHT * 2);
+    drawSubheading(heading);
+    drawHeaderRow(ITEM_COLUMNS);
+    for (const item of items) {
+      drawDataRow(ITEM_COLUMNS, {
+        description: item.description + (item.main_residence ? " (main residence)" : ""),
+        type: item.type_label,
+        share: formatShare(item.share),
+        value: formatGBP(item.value),
+      });
+    }
+    drawDataRow(ITEM_COLUMNS, { description: totalLabel, value: formatGBP(total) }, { bold: true });
+    y -= 8;
+  }
+
+  // --- Report title ---
+  page.drawText("Inheritance Tax Estate Estimate", {
+    x: MARGIN_LEFT,
+    y: y - FONT_SIZE_TITLE,
+    font: fonts.bold,
+    size: FONT_SIZE_TITLE,
+    color: COLOURS.brand800,
+  });
+  y -= FONT_SIZE_TITLE + 12;
+
+  if (estimates.length === 0) {
+    page.drawText("No family members found.", {
+      x: MARGIN_LEFT,
+      y: y - FONT_SIZE_ROW,
+      font: fonts.medium,
+      size: FONT_SIZE_ROW,
+      color: COLOURS.brand600,
+    });
+    y -= ROW_HEIGHT;
+  }
+
+  for (const estimate of estimates) {
+    // Space needed: user heading + subheading + header row + at least one data row
+    ensureSpace(USER_HEADING_HEIGHT + FONT_SIZE_SUBHEADING + 6 + HEADER_ROW_HEIGHT + ROW_HEIGHT * 2);
+
+    const user = estimate.user;
+    page.drawText(user.first_name + " " + user.last_name + " (" + user.initials + ")", {
+      x: MARGIN_LEFT,
+      y: y - FONT_SIZE_USER_HEADING,
+      font: fonts.bold,
+      size: FONT_SIZE_USER_HEADING,
+      color: COLOURS.brand800,
+    });
+    y -= USER_HEADING_HEIGHT;
+
+    // --- Estate, liabilities and items outside the estate ---
+    drawItemTable("Assets in the estate", estimate.assets, "Total assets", estimate.totals.assets);
+    if (estimate.liabilities.length > 0) {
+      drawItemTable("Liabilities", estimate.liabilities, "Total liabilities", estimate.totals.liabilities);
+    }
+    if (estimate.outside_estate.length > 0) {
+      drawItemTable("Outside the estate (pensions are generally outside the estate)", estimate.outside_estate, "Total outside the estate", estimate.totals.outside_estate);
+    }
+
+    // --- Nil-rate bands and tax ---
+    const alone = estimate.estate_alone;
+    const second = estimate.second_death;
+    const columns = second ? TAX_COLUMNS : TAX_COLUMNS.slice(0, 2);
+    const rate = estimate.config.rate;
+
+    const hasGifts = alone.gifts > 0 || (second && second.gifts > 0);
+    ensureSpace(FONT_SIZE_SUBHEADING + 6 + HEADER_ROW_HEIGHT + ROW_HEIGHT * (hasGifts ? 7 : 6));
+    drawSubheading("Inheritance tax");
+    drawHeaderRow(columns);
+    drawDataRow(columns, { label: "Net estate", alone: formatGBP(alone.estate), second: second ? formatGBP(second.estate) : "" });
+    drawDataRow(columns, { label: "Main residence", alone: formatGBP(alone.residence), second: second ? formatGBP(second.residence) : "" });
+    if (hasGifts) {
+      drawDataRow(columns, { label: "Gifts within seven years", alone: formatGBP(alone.gifts), second: second ? formatGBP(second.gifts) : "" });
+    }
+    drawDataRow(columns, { label: "Nil-rate band", alone: formatGBP(alone.nil_rate_band), second: second ? formatGBP(second.nil_rate_band) : "" });
+    drawDataRow(columns, {
+      label: "Residence nil-rate band",
+      alone: formatGBP(alone.residence_nil_rate_band),
+      second: second ? formatGBP(second.residence_nil_rate_band) : "",
+    });
+    drawDataRow(columns, { label: "Taxable", alone: formatGBP(alone.taxable), second: second ? formatGBP(second.taxable) : "" });
+    drawDataRow(columns, {
+      label: "Inheritance tax at " + rate + "%",
+      alone: formatGBP(alone.tax),
+      second: second ? formatGBP(second.tax) : "",
+    }, {
+      bold: true,
+      colours: { alone: alone.tax > 0 ? COLOURS.red600 : null, second: second && second.tax > 0 ? COLOURS.red600 : null },
+    });
+    y -= 4;
+
+    if (estimate.spouse) {
+      drawNote(
+        "Left to " + estimate.spouse.first_name + " on the first death: no tax (spouse exemption), and the unused nil-rate bands pass to the survivor.",
+      );
+    }
+    if (alone.taper_reduction > 0 || (second && second.taper_reduction > 0)) {
+      drawNote("The residence nil-rate band is reduced by £1 for every £2 the estate is over " + formatGBP(estimate.config.taperThreshold) + ".");
+    }
+    if (hasGifts) {
+      drawNote("Gifts in the gifts register made within the last seven years use up the nil-rate band before the estate.");
+    }
+    if (estimate.gifts.tax_on_death > 0) {
+      drawNote("Tax on the gifts themselves, after taper relief, would be " + formatGBP(estimate.gifts.tax_on_death) + ", payable by the recipients.");
+    }
+
+    y -= 12;
+  }
+
+  if (estimates.length > 0) {
+    drawNote("An estimate only: reliefs and exemptions other than the spouse exemption and the gift exemptions are not taken into account.");
+  }
+
+  // Write back modified state
+  ctx.page = page;
+  ctx.y = y;
+}
+
+/**
+ * @description Generate a standalone PDF for the Inheritance Tax Estate report.
+ * Creates a PDF document, renders the block, adds footers, and returns bytes.
+ * @param {Array<string>} [params] - Optional user initials (or tokens) to include
+ * @returns {Promise<Uint8Array>} The PDF file bytes
+ */
+export async function generateIhtEstatePdf(params) {
+  const pdf = PDF.create();
+  const fonts = embedRobotoFonts(pdf);
+  const page = pdf.addPage({ size: "a4", orientation: "portrait" });
+  const pages = [page];
+  const y = drawPageHeader(pdf, page, MARGIN_LEFT, A4_HEIGHT, MARGIN_TOP, fonts);
+
+  const ctx = { pdf: pdf, page: page, pages: pages, y: y, fonts: fonts };
+  renderIhtEstateBlock(ctx, params || []);
+
+  drawPageFooters(ctx.pages, "Inheritance Tax Estate", MARGIN_LEFT, USABLE_WIDTH, fonts);
+  return await pdf.save();
+}
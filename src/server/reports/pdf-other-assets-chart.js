@@ -0,0 +1,120 @@
+import { PDF } from "@libpdf/core";
+import { embedRobotoFonts } from "./pdf-fonts.js";
+import { drawPageHeader, drawPageFooters } from "./pdf-common.js";
+import { renderChartBlock } from "./pdf-chart.js";
+import { buildOtherAssetValueHistory } from "../services/net-worth-service.js";
+import { OTHER_ASSET_CATEGORY_LABELS } from "../db/other-assets-db.js";
+
+/** @description A4 landscape dimensions in points */
+const A4_LANDSCAPE_HEIGHT = 595.28;
+const MARGIN_LEFT = 40;
+const MARGIN_TOP = 40;
+const USABLE_WIDTH = 841.89 - MARGIN_LEFT - 40;
+
+/** @description Months of history shown when the block does not say */
+const DEFAULT_MONTHS = 24;
+
+/**
+ * @description Build the line chart data for other asset values over time.
+ * Params are category keys (e.g. "property", "liability"); leave them empty
+ * for every category. Lines are category totals, or one per value-type asset
+ * when seriesBy is "asset".
+ * @param {Array<string>} params - Category keys to include
+ * @param {Object} [blockDef] - Block definition with title, subTitle, monthsToShow and seriesBy
+ * @returns {Object} Chart data in the shape the line chart renderer expects
+ */
+export function getOtherAssetsChartData(params, blockDef) {
+  const months = parseInt(blockDef && blockDef.monthsToShow, 10) || DEFAULT_MONTHS;
+  const categories = (params || [])
+    .map(function (p) {
+      return String(p).trim().toLowerCase();
+    })
+    .filter(function (key) {
+      return OTHER_ASSET_CATEGORY_LABELS[key];
+    });
+  const byAsset = blockDef && blockDef.seriesBy === "asset";
+  const history = buildOtherAssetValueHistory(months, { categories: categories });
+
+  let series;
+  if (byAsset) {
+    series = history.assets
+      .filter(function (asset) {
+        return asset.value_type === "value";
+      })
+      .map(function (asset) {
+        return { label: asset.description, type: "portfolio", values: asset.values };
+      });
+  } else {
+    series = history.categories.map(function (category) {
+      return { label: category.label, type: "portfolio", values: category.values };
+    });
+  }
+
+  const scope = categories.length > 0
+    ? categories.map(function (key) {
+      return OTHER_ASSET_CATEGORY_LABELS[key];
+    }).join(", ")
+    : "All categories";
+
+  return {
+    title: (blockDef && blockDef.title) || "Other Assets",
+    subTitle: (blockDef && blockDef.subTitle) || scope + " — value at each month end, from the change history",
+    monthsToShow: months,
+    sampleDates: history.dates,
+    series: series,
+    events: [],
+    valueMode: "value",
+  };
+}
+
+/**
+ * @description Render an other assets value chart block into a shared PDF
+ * context. Builds the values at each month end from the change history, then
+ * delegates to the standard line chart renderer with pre-built chart data.
+ *
+ * @param {Object} ctx - Shared rendering context
+ * @param {Object} ctx.pdf - The PDF document
+ * @param {Object} ctx.page - Current page (updated in place on ctx)
+ * @param {Array<Object>} ctx.pages - Array of all pages
+ * @param {number} ctx.y - Current y position (updated in place on ctx)
+ * @param {Array<number>} ctx.pageWidths - Per-page usable widths
+ * @param {Array<string>} params - Category keys to include (empty for all)
+ * @param {Object} [blockDef] - Block definition with title, subTitle, monthsToShow and seriesBy
+ */
+export function renderOtherAssetsChartBlock(ctx, params, blockDef) {
+  const chartData = getOtherAssetsChartData(params, blockDef);
+
+  const rendererDef = {
+    title: chartData.title,
+    subTitle: chartData.subTitle,
+    monthsToShow: chartData.monthsToShow,
+    _chartData: chartData,
+  };
+
+  // Preserve bounds for multi-chart layouts
+  if (blockDef && blockDef._bounds) {
+    rendererDef._bounds = blockDef._bounds;
+  }
+
+  renderChartBlock(ctx, params, rendererDef);
+}
+
+/**
+ * @description Generate a standalone PDF for an other assets value chart.
+ * Creates a landscape A4 PDF with a single chart of other asset values over time.
+ * @param {Object} chartDef - Chart definition from user-reports.json
+ * @returns {Promise<Uint8Array>} The PDF file bytes
+ */
+export async function generateOtherAssetsChartPdf(chartDef) {
+  const pdf = PDF.create();
+  const fonts = embedRobotoFonts(pdf);
+  const page = pdf.addPage({ size: "a4", orientation: "landscape" });
+  const pages = [page];
+  const y = drawPageHeader(pdf, page, MARGIN_LEFT, A4_LANDSCAPE_HEIGHT, MARGIN_TOP, fonts);
+
+  const ctx = { pdf: pdf, page: page, pages: pages, y: y, pageWidths: [USABLE_WIDTH], fonts: fonts };
+  renderOtherAssetsChartBlock(ctx, chartDef.params || [], chartDef);
+
+  drawPageFooters(ctx.pages, chartDef.title || "Other Assets", MARGIN_LEFT, USABLE_WIDTH, fonts);
+  return await pdf.save();
+}
@@ -0,0 +1,345 @@
+import { PDF, rgb } from "@libpdf/core";
+import { getP60SummariesForAllUsers } from "../services/drawdown-tax-service.js";
+import { parseTaxYearLabel, getTaxYearForDate, getTaxYearByStartYear } from "../services/tax-year-utils.js";
+import { isTestMode } from "../test-mode.js";
+import { drawPageHeader, drawPageFooters, resolveParams, resolveUserIds } from "./pdf-common.js";
+import { embedRobotoFonts } from "./pdf-fonts.js";
+
+/**
+ * @description Brand colours converted to RGB 0-1 range for PDF rendering.
+ * Matches the Tailwind brand palette used in the HTML report.
+ */
+const COLOURS = {
+  brand800: rgb(0.15, 0.23, 0.42),
+  brand700: rgb(0.2, 0.3, 0.5),
+  brand600: rgb(0.35, 0.42, 0.55),
+  brand200: rgb(0.82, 0.85, 0.9),
+  brand100: rgb(0.91, 0.93, 0.96),
+  black: rgb(0, 0, 0),
+  white: rgb(1, 1, 1),
+  green100: rgb(0.86, 0.94, 0.87),
+  red600: rgb(0.76, 0.07, 0.12),
+};
+
+/** @description A4 page dimensions in points */
+const A4_WIDTH = 595.28;
+const A4_HEIGHT = 841.89;
+const MARGIN_LEFT = 40;
+const MARGIN_RIGHT = 40;
+const MARGIN_TOP = 40;
+const MARGIN_BOTTOM = 40;
+const USABLE_WIDTH = A4_WIDTH - MARGIN_LEFT - MARGIN_RIGHT;
+
+/**
+ * @description Column definitions for the payments table of each SIPP.
+ * x is relative to MARGIN_LEFT, width in points.
+ * @type {Array<{key: string, label: string, x: number, width: number, align: string}>}
+ */
+const PAYMENT_COLUMNS = [
+  { key: "date", label: "Date", x: 0, width: 80, align: "left" },
+  { key: "tax_code", label: "Tax Code", x: 80, width: 120, align: "left" },
+  { key: "gross", label: "Gross Pay", x: 200, width: 90, align: "right" },
+  { key: "tax", label: "Tax Deducted", x: 290, width: 90, align: "right" },
+  { key: "net", label: "Net Pay", x: 380, width: 90, align: "right" },
+];
+
+/** @description Font sizes used in the report */
+const FONT_SIZE_TITLE = 14;
+const FONT_SIZE_USER_HEADING = 10;
+const FONT_SIZE_SUBHEADING = 8;
+const FONT_SIZE_HEADER = 7;
+const FONT_SIZE_ROW = 7;
+
+/** @description Row heights in points */
+const ROW_HEIGHT = 14;
+const HEADER_ROW_HEIGHT = 16;
+const USER_HEADING_HEIGHT = 20;
+
+/**
+ * @description Format a decimal GBP value to pence with thousand separators,
+ * as P60 figures are reported. No currency symbol.
+ * @param {number} value - Decimal GBP value (e.g. 1234.56)
+ * @returns {string} Formatted string like "1,234.56"
+ */
+function formatGBP(value) {
+  return (value || 0).toLocaleString("en-GB", { minimumFractionDigits: 2, maximumFractionDigits: 2 });
+}
+
+/**
+ * @description Format an ISO date as DD/MM/YYYY for UK display.
+ * @param {string} isoDate - ISO date string (YYYY-MM-DD)
+ * @returns {string} Formatted date like "23/12/2025"
+ */
+function formatDateUK(isoDate) {
+  if (!isoDate) return "";
+  const parts = isoDate.split("-");
+  if (parts.length !== 3) return isoDate;
+  return parts[2] + "/" + parts[1] + "/" + parts[0];
+}
+
+/**
+ * @description Draw text right-aligned within a column.
+ * @param {Object} page - PDFPage instance
+ * @param {string} text - The text to draw
+ * @param {number} x - Left edge of column (absolute)
+ * @param {number} colWidth - Column width in points
+ * @param {number} y - Y position (baseline)
+ * @param {Object} font - Embedded font instance
+ * @param {number} fontSize - Font size in points
+ * @param {Object} color - RGB colour
+ */
+function drawRightAligned(page, text, x, colWidth, y, font, fontSize, color) {
+  const textWidth = font.widthOfTextAtSize(text, fontSize);
+  page.drawText(text, {
+    x: x + colWidth - textWidth - 2,
+    y: y,
+    font: font,
+    size: fontSize,
+    color: color,
+  });
+}
+
+/**
+ * @description Render the P60 Summary block into a shared PDF context. For
+ * each family member paid a pension from a SIPP in the tax year, lists each
+ * drawdown by SIPP with gross pay, tax deducted under PAYE and net pay, the
+ * totals per SIPP as they appear on the provider's P60, and the total across
+ * all SIPPs for self-assessment. Does not add footers — the caller is
+ * responsible for that.
+ * @param {Object} ctx - Shared rendering context
+ * @param {Object} ctx.pdf - The PDF document
+ * @param {Object} ctx.page - Current page (updated in place on ctx)
+ * @param {Array<Object>} ctx.pages - Array of all pages (pushed to when new pages added)
+ * @param {number} ctx.y - Current y position (updated in place on ctx)
+ * @param {Array<number>} ctx.pageWidths - Per-page usable widths (pushed to when new pages added)
+ * @param {Array<string>} [params] - User initials (or tokens like USER1); empty for everyone
+ * @param {Object} [block] - Block definition; may set taxYear ("2025/2026")
+ */
+export function renderP60SummaryBlock(ctx, params, block) {
+  const pdf = ctx.pdf;
+  let page = ctx.page;
+  const pages = ctx.pages;
+  let y = ctx.y;
+  const fonts = ctx.fonts;
+
+  const blockDef = block || {};
+  const taxYearStart = blockDef.taxYear ? parseTaxYearLabel(blockDef.taxYear) : null;
+
+  const userIds = resolveUserIds(resolveParams(params));
+  let summaries = getP60SummariesForAllUsers(userIds, taxYearStart);
+  if (userIds) {
+    summaries = summaries.slice().sort(function (a, b) {
+      return userIds.indexOf(a.user.id) - userIds.indexOf(b.user.id);
+    });
+  }
+
+  const testMode = isTestMode();
+  const headerRowColour = testMode ? COLOURS.green100 : COLOURS.brand100;
+
+  /**
+   * @description Check if there is enough vertical space for the next section.
+   * If not, add a new page with header and reset y.
+   * @param {number} needed - Points of vertical space needed
+   */
+  function ensureSpace(needed) {
+    if (y - needed < MARGIN_BOTTOM) {
+      page = pdf.addPage({ size: "a4", orientation: "portrait" });
+      pages.push(page);
+      if (ctx.pageWidths) ctx.pageWidths.push(USABLE_WIDTH);
+      y = drawPageHeader(pdf, page, MARGIN_LEFT, A4_HEIGHT, MARGIN_TOP, fonts);
+    }
+  }
+
+  /**
+   * @description Draw a table header row for the given columns.
+   * @param {Array<Object>} columns - Column definitions
+   */
+  function drawHeaderRow(columns) {
+    page.drawRectangle({
+      x: MARGIN_LEFT,
+      y: y - HEADER_ROW_HEIGHT,
+      width: USABLE_WIDTH,
+      height: HEADER_ROW_HEIGHT,
+      color: headerRowColour,
+    });
+
+    for (const col of columns) {
+      if (col.align === "right") {
+        drawRightAligned(page, col.label, MARGIN_LEFT + col.x, col.width, y - HEADER_ROW_HEIGHT + 5, fonts.bold, FONT_SIZE_HEADER, COLOURS.brand700);
+      } else {
+        page.drawText(col.label, {
+          x: MARGIN_LEFT + col.x + 2,
+          y: y - HEADER_ROW_HEIGHT + 5,
+          font: fonts.bold,
+          size: FONT_SIZE_HEADER,
+          color: COLOURS.brand700,
+        });
+      }
+    }
+
+    page.drawLine({
+      start: { x: MARGIN_LEFT, y: y - HEADER_ROW_HEIGHT },
+      end: { x: MARGIN_LEFT + USABLE_WIDTH, y: y - HEADER_ROW_HEIGHT },
+      color: COLOURS.brand200,
+      thickness: 0.5,
+    });
+    y -= HEADER_ROW_HEIGHT;
+  }
+
+  /**
+   * @description Draw one table data row.
+   * @param {Array<Object>} columns - Column definitions
+   * @param {Object} cellValues - Cell text keyed by column key
+   * @param {Object} [options] - { bold: boolean, colours: { key: rgb } }
+   */
+  function drawDataRow(columns, cellValues, options) {
+    const opts = options || {};
+    ensureSpace(ROW_HEIGHT + 2);
+
+    const rowY = y - ROW_HEIGHT;
+    const textY = rowY + 4;
+    const font = opts.bold ? fonts.bold : fonts.medium;
+
+    for (const col of columns) {
+      const cellText = cellValues[col.key] || "";
+      const colour = (opts.colours && opts.colours[col.key]) || COLOURS.black;
+      if (col.align === "right") {
+        drawRightAligned(page, cellText, MARGIN_LEFT + col.x, col.width, textY, font, FONT_SIZE_ROW, colour);
+      } else {
+        page.drawText(cellText, {
+          x: MARGIN_LEFT + col.x + 2,
+          y: textY,
+          font: font,
+          size: FONT_SIZE_ROW,
+          color: colour,
+        });
+      }
+    }
+
+    page.drawLine({
+      start: { x: MARGIN_LEFT, y: rowY },
+      end: { x: MARGIN_LEFT + USABLE_WIDTH, y: rowY },
+      color: COLOURS.brand100,
+      thickness: 0.3,
+    });
+    y -= ROW_HEIGHT;
+  }
+
+  /**
+   * @description Draw a small subheading above a table.
+   * @param {string} text - The subheading text
+   */
+  function drawSubheading(text) {
+    page.drawText(text, {
+      x: MARGIN_LEFT,
+      y: y - FONT_SIZE_SUBHEADING,
+      font: fonts.bold,
+      size: FONT_SIZE_SUBHEADING,
+      color: COLOURS.brand700,
+    });
+    y -= FONT_SIZE_SUBHEADING + 6;
+  }
+
+  // --- Report title ---
+  const taxYear = taxYearStart ? getTaxYearByStartYear(taxYearStart) : getTaxYearForDate(new Date().toISOString().slice(0, 10));
+  const yearToDate = taxYear.end >= new Date().toISOString().slice(0, 10);
+  page.drawText("Pension Income (P60) " + taxYear.label + (yearToDate ? " (year to date)" : ""), {
+    x: MARGIN_LEFT,
+    y: y - FONT_SIZE_TITLE,
+    font: fonts.bold,
+    size: FONT_SIZE_TITLE,
+    color: COLOURS.brand800,
+  });
+  y -= FONT_SIZE_TITLE + 12;
+
+  if (summaries.length === 0) {
+    page.drawText("No SIPP drawdowns in this tax year.", {
+      x: MARGIN_LEFT,
+      y: y - FONT_SIZE_ROW,
+      font: fonts.medium,
+      size: FONT_SIZE_ROW,
+      color: COLOURS.brand600,
+    });
+    y -= ROW_HEIGHT;
+  }
+
+  for (const summary of summaries) {
+    // Space needed: user heading + subheading + header row + at least one data row
+    ensureSpace(USER_HEADING_HEIGHT + FONT_SIZE_SUBHEADING + 6 + HEADER_ROW_HEIGHT + ROW_HEIGHT * 2);
+
+    const user = summary.user;
+    page.drawText(user.first_name + " " + user.last_name + " (" + user.initials + ")", {
+      x: MARGIN_LEFT,
+      y: y - FONT_SIZE_USER_HEADING,
+      font: fonts.bold,
+      size: FONT_SIZE_USER_HEADING,
+      color: COLOURS.brand800,
+    });
+    y -= USER_HEADING_HEIGHT;
+
+    // --- Each SIPP, as its P60 ---
+    for (const account of summary.accounts) {
+      ensureSpace(FONT_SIZE_SUBHEADING + 6 + HEADER_ROW_HEIGHT + ROW_HEIGHT * 2);
+      const provider = account.provider ? account.provider.toUpperCase() + " " : "";
+      drawSubheading(provider + account.account_ref + ": tax code " + (account.tax_code || "none"));
+      drawHeaderRow(PAYMENT_COLUMNS);
+      for (const payment of account.payments) {
+        drawDataRow(PAYMENT_COLUMNS, {
+          date: formatDateUK(payment.transaction_date),
+          tax_code: payment.tax_code || "",
+          gross: formatGBP(payment.gross),
+          tax: formatGBP(payment.tax_withheld),
+          net: formatGBP(payment.net),
+        }, {
+          colours: payment.tax_withheld < 0 ? { tax: COLOURS.red600 } : null,
+        });
+      }
+      drawDataRow(PAYMENT_COLUMNS, {
+        date: "Total",
+        gross: formatGBP(account.gross),
+        tax: formatGBP(account.tax_withheld),
+        net: formatGBP(account.net),
+      }, { bold: true });
+      y -= 8;
+    }
+
+    // --- Total across SIPPs ---
+    if (summary.accounts.length > 1) {
+      drawDataRow(PAYMENT_COLUMNS, {
+        date: "All SIPPs",
+        gross: formatGBP(summary.gross),
+        tax: formatGBP(summary.tax_withheld),
+        net: formatGBP(summary.net),
+      }, { bold: true });
+      y -= 8;
+    }
+
+    y -= 8;
+  }
+
+  // Write back modified state
+  ctx.page = page;
+  ctx.y = y;
+}
+
+/**
+ * @description Generate a standalone PDF for the P60 Summary report.
+ * Creates a PDF document, renders the block, adds footers, and returns bytes.
+ * @param {Array<string>} [params] - Optional user initials (or tokens) to include
+ * @param {string} [taxYear] - Optional tax year label (e.g. "2025/2026"); defaults to the current tax year
+ * @returns {Promise<Uint8Array>} The PDF file bytes
+ */
+export async function generateP60SummaryPdf(params, taxYear) {
+  const pdf = PDF.create();
+  const fonts = embedRobotoFonts(pdf);
+  const page = pdf.addPage({ size: "a4", orientation: "portrait" });
+  const pages = [page];
+  const y = drawPageHeader(pdf, page, MARGIN_LEFT, A4_HEIGHT, MARGIN_TOP, fonts);
+
+  const blockDef = taxYear ? { taxYear: taxYear } : {};
+  const ctx = { pdf: pdf, page: page, pages: pages, y: y, fonts: fonts };
+  renderP60SummaryBlock(ctx, params || [], blockDef);
+
+  drawPageFooters(ctx.pages, "Pension Income (P60)", MARGIN_LEFT, USABLE_WIDTH, fonts);
+  return await pdf.save();
+}
@@ -1,9 +1,8 @@
 import { PDF, rgb } from "@libpdf/core";
 import { embedRobotoFonts } from "./pdf-fonts.js";
-import { getPortfolioDetail } from "../services/portfolio-detail-service.js";
-import { getReportParams } from "../db/report-params-db.js";
+import { getPortfolioDetails } from "../services/portfolio-detail-service.js";
 import { isTestMode } from "../test-mode.js";
-import { drawPageHeader, drawPageFooters } from "./pdf-common.js";
+import { drawPageHeader, drawPageFooters, resolveParams } from "./pdf-common.js";
 import { buildFtMarketsUrl, buildMorningstarUrl } from "../../shared/public-id-utils.js";
 
 /**
@@ -171,10 +170,25 @@ function truncateText(text, font, fontSize, maxWidth) {
   return truncated + "...";
 }
 
+/**
+ * @description Build the short qualifier that tells apart two accounts of the
+ * same type, e.g. "(HL 3366521)".
+ * @param {Object} account - Account with provider and account_ref
+ * @returns {string} The qualifier in brackets
+ */
+function accountQualifier(account) {
+  const parts = [];
+  if (account.provider) parts.push(account.provider.toUpperCase());
+  if (account.account_ref) parts.push(account.account_ref);
+  return "(" + parts.join(" ") + ")";
+}
+
 /**
  * @description Parse a single param string into its components.
  * Format: "USER:ACCOUNT_TYPE" or "USER:ACCOUNT_TYPE:period1,period2,..."
- * When account type contains "+", it is a combined totals request.
+ * When account type contains "+", it is a combined totals request. Each
+ * account type may be narrowed to a provider or account reference, e.g.
+ * "BW:isa@hl" or "BW:sipp~3366521".
  * @param {string} param - The param string
  * @returns {Object} Parsed object
  */
@@ -194,28 +208,6 @@ function parseDetailParam(param) {
   };
 }
 
-/**
- * @description Resolve report params tokens in the params array.
- * @param {Array<string>} params - Raw params array
- * @returns {Array<string>} Params with tokens substituted
- */
-function resolveParams(params) {
-  if (!params || params.length === 0) return [];
-  try {
-    const tokenMap = getReportParams();
-    const tokens = Object.keys(tokenMap);
-    return params.map(function (param) {
-      let result = param;
-      for (let i = 0; i < tokens.length; i++) {
-        result = result.split(tokens[i]).join(tokenMap[tokens[i]]);
-      }
-      return result;
-    });
-  } catch {
-    return params;
-  }
-}
-
 /**
  * @description Build column definitions including any change period columns.
  * @param {Array<Object>} periods - Period definitions from the detail data
@@ -344,11 +336,24 @@ export function renderPortfolioDetailBlock(ctx, params) {
   }
 
   /**
-   * @description Render a single account's detail table with holdings.
+   * @description Render the detail tables for every account matched by a
+   * single-account param. A plain type such as "isa" renders one table per
+   * ISA when the user holds more than one.
    * @param {Object} parsed - Parsed param object
    */
   function renderAccountSection(parsed) {
-    const data = getPortfolioDetail(parsed.user, parsed.accountType, parsed.periods);
+    const details = getPortfolioDetails(parsed.user, parsed.accountType, parsed.periods);
+    for (let d = 0; d < details.length; d++) {
+      renderAccountTable(details[d], details.length > 1);
+    }
+  }
+
+  /**
+   * @description Render a single account's detail table with holdings.
+   * @param {Object} data - Detail object from getPortfolioDetails
+   * @param {boolean} showAccountRef - Whether to name the provider and reference in the heading
+   */
+  function renderAccountTable(data, showAccountRef) {
     if (!data || !data.holdings || data.holdings.length === 0) return;
 
     const periods = data.periods || [];
@@ -358,7 +363,10 @@ export function renderPortfolioDetailBlock(ctx, params) {
 
     // Section heading
     const typeLabel = ACCOUNT_TYPE_LABELS[data.account.account_type] || data.account.account_type;
-    const heading = data.user.first_name + " " + data.user.last_name + " " + typeLabel;
+    let heading = data.user.first_name + " " + data.user.last_name + " " + typeLabel;
+    if (showAccountRef) {
+      heading += " " + accountQualifier(data.account);
+    }
 
     ensureSpace(SECTION_HEADING_HEIGHT + HEADER_ROW_HEIGHT + ROW_HEIGHT);
     page.drawText(heading, {
@@ -571,15 +579,24 @@ export function renderPortfolioDetailBlock(ctx, params) {
     let combinedPeriods = [];
     let combinedUserName = "";
 
+    const seenAccountIds = {};
+
     for (let a = 0; a < parsed.accountTypes.length; a++) {
-      const data = getPortfolioDetail(parsed.user, parsed.accountTypes[a], parsed.periods);
-      if (data && data.holdings && data.holdings.length > 0) {
-        combinedResults.push(data);
-        if (combinedPeriods.length === 0 && data.periods) {
-          combinedPeriods = data.periods;
-        }
-        if (!combinedUserName) {
-          combinedUserName = data.user.first_name + " " + data.user.last_name;
+      const details = getPortfolioDetails(parsed.user, parsed.accountTypes[a], parsed.periods);
+      for (let d = 0; d < details.length; d++) {
+        const data = details[d];
+        // "isa+isa@hl" names the HL ISA twice — count each account once
+        if (seenAccountIds[data.account.id]) continue;
+        seenAccountIds[data.account.id] = true;
+
+        if (data.holdings && data.holdings.length > 0) {
+          combinedResults.push(data);
+          if (combinedPeriods.length === 0 && data.periods) {
+            combinedPeriods = data.periods;
+          }
+          if (!combinedUserName) {
+            combinedUserName = data.user.first_name + " " + data.user.last_name;
+          }
         }
       }
     }
@@ -600,7 +617,13 @@ export function renderPortfolioDetailBlock(ctx, params) {
     for (let d = 0; d < combinedResults.length; d++) {
       const detail = combinedResults[d];
       combinedValueGBP += detail.totals.value_gbp;
-      const typeLabel = ACCOUNT_TYPE_LABELS[detail.account.account_type] || detail.account.account_type;
+      let typeLabel = ACCOUNT_TYPE_LABELS[detail.account.account_type] || detail.account.account_type;
+      const sameTypeCount = combinedResults.filter(function (r) {
+        return r.account.account_type === detail.account.account_type;
+      }).length;
+      if (sameTypeCount > 1) {
+        typeLabel += " " + accountQualifier(detail.account);
+      }
       accountLabels.push(typeLabel);
 
       for (let h = 0; h < detail.holdings.length; h++) {
@@ -2,9 +2,8 @@ import { PDF, rgb } from "@libpdf/core";
 import { embedRobotoFonts } from "./pdf-fonts.js";
 import { getPortfolioSummary, getPortfolioSummaryAtDate } from "../services/portfolio-service.js";
 import { getAllUsers } from "../db/users-db.js";
-import { getReportParams } from "../db/report-params-db.js";
 import { isTestMode } from "../test-mode.js";
-import { drawPageHeader, drawPageFooters } from "./pdf-common.js";
+import { drawPageHeader, drawPageFooters, resolveParams } from "./pdf-common.js";
 
 /**
  * @description Brand colours converted to RGB 0-1 range for PDF rendering.
@@ -182,29 +181,6 @@ function formatDiffCell(diff, base) {
   return valueStr + " " + pctStr;
 }
 
-/**
- * @description Resolve report params tokens to build the params array.
- * Replaces tokens like "USER1" with actual values from report_params.
- * @param {Array<string>} params - Raw params array from the report definition
- * @returns {Array<string>} Params with tokens substituted
- */
-function resolveParams(params) {
-  if (!params || params.length === 0) return [];
-  try {
-    const tokenMap = getReportParams();
-    const tokens = Object.keys(tokenMap);
-    return params.map(function (param) {
-      let result = param;
-      for (let i = 0; i < tokens.length; i++) {
-        result = result.split(tokens[i]).join(tokenMap[tokens[i]]);
-      }
-      return result;
-    });
-  } catch {
-    return params;
-  }
-}
-
 /**
  * @description Build a lookup map of user initials (uppercase) to their
  * portfolio summary object.
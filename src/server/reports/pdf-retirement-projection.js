@@ -0,0 +1,604 @@
+import { PDF, rgb } from "@libpdf/core";
+import { projectRetirementForUsers } from "../services/retirement-projection-service.js";
+import { isTestMode } from "../test-mode.js";
+import { drawPageHeader, drawPageFooters, resolveParams, resolveUserIds } from "./pdf-common.js";
+import { embedRobotoFonts } from "./pdf-fonts.js";
+
+/**
+ * @description Brand colours converted to RGB 0-1 range for PDF rendering.
+ * The percentile bands use two tints of the brand blue.
+ */
+const COLOURS = {
+  brand800: rgb(0.15, 0.23, 0.42),
+  brand700: rgb(0.2, 0.3, 0.5),
+  brand600: rgb(0.35, 0.42, 0.55),
+  brand200: rgb(0.82, 0.85, 0.9),
+  brand100: rgb(0.91, 0.93, 0.96),
+  emerald900: rgb(0.02, 0.32, 0.21),
+  black: rgb(0, 0, 0),
+  white: rgb(1, 1, 1),
+  green100: rgb(0.86, 0.94, 0.87),
+  gridLine: rgb(0.9, 0.9, 0.9),
+  outerBand: rgb(0.85, 0.9, 0.98),
+  innerBand: rgb(0.66, 0.77, 0.95),
+  median: rgb(0.23, 0.51, 0.96),
+  deterministic: rgb(0.98, 0.45, 0.09),
+  red600: rgb(0.76, 0.07, 0.12),
+};
+
+/** @description A4 landscape dimensions in points */
+const A4_LANDSCAPE_WIDTH = 841.89;
+const A4_LANDSCAPE_HEIGHT = 595.28;
+const MARGIN_LEFT = 40;
+const MARGIN_RIGHT = 40;
+const MARGIN_TOP = 40;
+const MARGIN_BOTTOM = 40;
+const USABLE_WIDTH = A4_LANDSCAPE_WIDTH - MARGIN_LEFT - MARGIN_RIGHT;
+
+/** @description Font sizes used in the report */
+const FONT_SIZE_TITLE = 12;
+const FONT_SIZE_SUBTITLE = 8;
+const FONT_SIZE_HEADING = 8;
+const FONT_SIZE_HEADER = 7;
+const FONT_SIZE_ROW = 7;
+const FONT_SIZE_AXIS = 7;
+
+/** @description Layout dimensions in points */
+const TITLE_BAR_HEIGHT = 28;
+const SUBTITLE_HEIGHT = 14;
+const LEGEND_HEIGHT = 16;
+const X_AXIS_HEIGHT = 28;
+const Y_AXIS_WIDTH = 45;
+const PANEL_WIDTH = 220;
+const PANEL_GAP = 20;
+const ROW_HEIGHT = 14;
+const HEADER_ROW_HEIGHT = 16;
+
+/**
+ * @description Column definitions for the depletion table in the side panel.
+ * x is relative to the panel's left edge, width in points.
+ * @type {Array<{key: string, label: string, x: number, width: number, align: string}>}
+ */
+const DEPLETION_COLUMNS = [
+  { key: "percentile", label: "Outcome", x: 0, width: 90, align: "left" },
+  { key: "years", label: "Runs out after", x: 90, width: 75, align: "right" },
+  { key: "age", label: "Age", x: 165, width: 55, align: "right" },
+];
+
+/** @description Descriptions of the depletion percentiles, from poor to strong markets */
+const PERCENTILE_LABELS = {
+  10: "10th (poor)",
+  25: "25th",
+  50: "50th (median)",
+  75: "75th",
+  90: "90th (strong)",
+};
+
+/**
+ * @description Format a decimal GBP value with thousand separators and no pence.
+ * @param {number} value - Decimal GBP value
+ * @returns {string} Formatted string like "£123,456"
+ */
+function formatGBP(value) {
+  return "£" + Math.round(value || 0).toLocaleString("en-GB");
+}
+
+/**
+ * @description Format a GBP value for a chart axis label (e.g. "£250k", "£1.2M").
+ * @param {number} value - Decimal GBP value
+ * @returns {string} Short axis label
+ */
+function formatGBPAxis(value) {
+  if (value >= 1000000) {
+    const millions = value / 1000000;
+    return "£" + (millions === Math.floor(millions) ? millions.toFixed(0) : millions.toFixed(1)) + "M";
+  }
+  if (value >= 1000) {
+    const thousands = value / 1000;
+    return "£" + (thousands === Math.floor(thousands) ? thousands.toFixed(0) : thousands.toFixed(1)) + "k";
+  }
+  return "£" + Math.round(value);
+}
+
+/**
+ * @description Round a value up to a "nice" number (1, 2 or 5 times a power of ten)
+ * for axis tick intervals.
+ * @param {number} value - The raw interval
+ * @returns {number} A nice interval
+ */
+function niceNumber(value) {
+  if (value <= 0) return 1;
+  const exponent = Math.floor(Math.log10(value));
+  const fraction = value / Math.pow(10, exponent);
+  let nice;
+  if (fraction <= 1) nice = 1;
+  else if (fraction <= 2) nice = 2;
+  else if (fraction <= 5) nice = 5;
+  else nice = 10;
+  return nice * Math.pow(10, exponent);
+}
+
+/**
+ * @description Draw text right-aligned within a column.
+ * @param {Object} page - PDFPage instance
+ * @param {string} text - The text to draw
+ * @param {number} x - Left edge of column (absolute)
+ * @param {number} colWidth - Column width in points
+ * @param {number} y - Y position (baseline)
+ * @param {Object} font - Embedded font instance
+ * @param {number} fontSize - Font size in points
+ * @param {Object} color - RGB colour
+ */
+function drawRightAligned(page, text, x, colWidth, y, font, fontSize, color) {
+  const textWidth = font.widthOfTextAtSize(text, fontSize);
+  page.drawText(text, {
+    x: x + colWidth - textWidth - 2,
+    y: y,
+    font: font,
+    size: fontSize,
+    color: color,
+  });
+}
+
+/**
+ * @description Draw a dashed line between two points.
+ * @param {Object} page - PDFPage instance
+ * @param {number} x1 - Start X
+ * @param {number} y1 - Start Y
+ * @param {number} x2 - End X
+ * @param {number} y2 - End Y
+ * @param {Object} colour - RGB colour
+ * @param {number} thickness - Line thickness
+ */
+function drawDashedLine(page, x1, y1, x2, y2, colour, thickness) {
+  const dx = x2 - x1;
+  const dy = y2 - y1;
+  const len = Math.sqrt(dx * dx + dy * dy);
+  if (len < 1) return;
+
+  const dashLen = 4;
+  const gapLen = 3;
+  const ux = dx / len;
+  const uy = dy / len;
+  let pos = 0;
+
+  while (pos < len) {
+    const end = Math.min(pos + dashLen, len);
+    page.drawLine({
+      start: { x: x1 + ux * pos, y: y1 + uy * pos },
+      end: { x: x1 + ux * end, y: y1 + uy * end },
+      color: colour,
+      thickness: thickness,
+    });
+    pos = end + gapLen;
+  }
+}
+
+/**
+ * @description Read the projection options set on a block definition. Values
+ * may be strings as saved by the reports manager; blank values are ignored so
+ * the config and price history apply.
+ * @param {Object} blockDef - Block definition
+ * @returns {Object} Options for projectRetirement
+ */
+function readBlockOptions(blockDef) {
+  const options = {};
+  if (blockDef.years !== undefined && blockDef.years !== "") {
+    const years = Number(blockDef.years);
+    if (Number.isInteger(years) && years >= 1 && years <= 60) options.years = years;
+  }
+  if (blockDef.expectedReturn !== undefined && blockDef.expectedReturn !== "") {
+    const expectedReturn = Number(blockDef.expectedReturn);
+    if (!isNaN(expectedReturn)) options.expected_return = expectedReturn;
+  }
+  if (blockDef.volatility !== undefined && blockDef.volatility !== "") {
+    const volatility = Number(blockDef.volatility);
+    if (!isNaN(volatility) && volatility >= 0) options.volatility = volatility;
+  }
+  return options;
+}
+
+/**
+ * @description Draw the percentile band chart for one projection: the 10th-90th
+ * and 25th-75th percentile ranges of the SIPP value as shaded bands, the median
+ * as a solid line and the deterministic projection as a dashed line. The x-axis
+ * shows age when the date of birth is known, otherwise years from today.
+ * @param {Object} page - PDFPage instance
+ * @param {Object} projection - Projection from projectRetirement
+ * @param {number} left - Left edge of the chart area (including the y-axis labels)
+ * @param {number} bottom - Bottom edge of the chart area (including the x-axis labels)
+ * @param {number} width - Width of the chart area
+ * @param {number} height - Height of the chart area
+ * @param {Object} fonts - Roboto font objects
+ */
+function drawBandChart(page, projection, left, bottom, width, height, fonts) {
+  const bands = projection.bands;
+  const chartLeft = left + Y_AXIS_WIDTH;
+  const chartBottom = bottom + X_AXIS_HEIGHT;
+  const chartWidth = width - Y_AXIS_WIDTH;
+  const chartHeight = height - X_AXIS_HEIGHT;
+  const years = bands.length;
+
+  // Y-axis range from zero to the highest value shown
+  let maxValue = projection.sipp_value;
+  for (const band of bands) {
+    if (band.p90 > maxValue) maxValue = band.p90;
+    if (band.deterministic > maxValue) maxValue = band.deterministic;
+  }
+  const tickInterval = niceNumber((maxValue || 1) / 6);
+  const yMax = Math.ceil((maxValue || 1) / tickInterval) * tickInterval;
+
+  /**
+   * @description Convert a year (0 = today) to an x position.
+   * @param {number} year - Years from today
+   * @returns {number} X position
+   */
+  function xFor(year) {
+    return chartLeft + (year / years) * chartWidth;
+  }
+
+  /**
+   * @description Convert a GBP value to a y position.
+   * @param {number} value - Value in GBP
+   * @returns {number} Y position
+   */
+  function yFor(value) {
+    return chartBottom + (Math.max(0, Math.min(value, yMax)) / yMax) * chartHeight;
+  }
+
+  // Grid lines and y-axis labels
+  for (let tick = 0; tick <= yMax + tickInterval * 0.01; tick += tickInterval) {
+    const ty = yFor(tick);
+    page.drawLine({
+      start: { x: chartLeft, y: ty },
+      end: { x: chartLeft + chartWidth, y: ty },
+      color: tick === 0 ? COLOURS.brand200 : COLOURS.gridLine,
+      thickness: tick === 0 ? 0.5 : 0.3,
+    });
+    const label = formatGBPAxis(tick);
+    page.drawText(label, {
+      x: chartLeft - fonts.regular.widthOfTextAtSize(label, FONT_SIZE_AXIS) - 4,
+      y: ty - 3,
+      font: fonts.regular,
+      size: FONT_SIZE_AXIS,
+      color: COLOURS.brand600,
+    });
+  }
+
+  // Percentile bands, one column per projected year
+  const columnWidth = chartWidth / years;
+  for (const band of bands) {
+    const x = xFor(band.year - 1);
+    if (band.p90 > band.p10) {
+      page.drawRectangle({ x: x, y: yFor(band.p10), width: columnWidth, height: yFor(band.p90) - yFor(band.p10), color: COLOURS.outerBand });
+    }
+    if (band.p75 > band.p25) {
+      page.drawRectangle({ x: x, y: yFor(band.p25), width: columnWidth, height: yFor(band.p75) - yFor(band.p25), color: COLOURS.innerBand });
+    }
+  }
+
+  // Median and deterministic lines, starting from today's value
+  let previousMedian = { x: xFor(0), y: yFor(projection.sipp_value) };
+  let previousDeterministic = previousMedian;
+  for (const band of bands) {
+    const medianPoint = { x: xFor(band.year), y: yFor(band.p50) };
+    const deterministicPoint = { x: xFor(band.year), y: yFor(band.deterministic) };
+    page.drawLine({ start: previousMedian, end: medianPoint, color: COLOURS.median, thickness: 1.2 });
+    drawDashedLine(page, previousDeterministic.x, previousDeterministic.y, deterministicPoint.x, deterministicPoint.y, COLOURS.deterministic, 1);
+    previousMedian = medianPoint;
+    previousDeterministic = deterministicPoint;
+  }
+
+  // X-axis labels: age or years from today, at a nice interval
+  const labelEvery = niceNumber(years / 10);
+  for (let year = 0; year <= years; year += labelEvery) {
+    const tx = xFor(year);
+    let label;
+    if (projection.current_age !== null) {
+      label = String(Math.floor(projection.current_age + year));
+    } else {
+      label = String(year);
+    }
+    page.drawLine({
+      start: { x: tx, y: chartBottom },
+      end: { x: tx, y: chartBottom - 4 },
+      color: COLOURS.brand200,
+      thickness: 0.5,
+    });
+    page.drawText(label, {
+      x: tx - fonts.regular.widthOfTextAtSize(label, FONT_SIZE_AXIS) / 2,
+      y: chartBottom - 13,
+      font: fonts.regular,
+      size: FONT_SIZE_AXIS,
+      color: COLOURS.brand600,
+    });
+  }
+
+  const axisTitle = projection.current_age !== null ? "Age" : "Years from today";
+  page.drawText(axisTitle, {
+    x: chartLeft + chartWidth / 2 - fonts.regular.widthOfTextAtSize(axisTitle, FONT_SIZE_AXIS) / 2,
+    y: chartBottom - 24,
+    font: fonts.regular,
+    size: FONT_SIZE_AXIS,
+    color: COLOURS.brand600,
+  });
+}
+
+/**
+ * @description Draw the chart legend on one line.
+ * @param {Object} page - PDFPage instance
+ * @param {number} x - Left edge
+ * @param {number} y - Baseline of the legend text
+ * @param {Object} fonts - Roboto font objects
+ */
+function drawLegend(page, x, y, fonts) {
+  const items = [
+    { label: "10th to 90th percentile", type: "box", colour: COLOURS.outerBand },
+    { label: "25th to 75th percentile", type: "box", colour: COLOURS.innerBand },
+    { label: "Median", type: "line", colour: COLOURS.median },
+    { label: "At expected return", type: "dashed", colour: COLOURS.deterministic },
+  ];
+
+  let lx = x;
+  for (const item of items) {
+    if (item.type === "box") {
+      page.drawRectangle({ x: lx, y: y - 1, width: 14, height: 7, color: item.colour });
+    } else if (item.type === "line") {
+      page.drawLine({ start: { x: lx, y: y + 2.5 }, end: { x: lx + 14, y: y + 2.5 }, color: item.colour, thickness: 1.2 });
+    } else {
+      drawDashedLine(page, lx, y + 2.5, lx + 14, y + 2.5, item.colour, 1);
+    }
+    page.drawText(item.label, { x: lx + 18, y: y, font: fonts.regular, size: FONT_SIZE_ROW, color: COLOURS.brand700 });
+    lx += 18 + fonts.regular.widthOfTextAtSize(item.label, FONT_SIZE_ROW) + 16;
+  }
+}
+
+/**
+ * @description Render the Retirement Projection block into a shared PDF
+ * context. For each family member with a SIPP, shows a chart of the projected
+ * SIPP value with Monte Carlo percentile bands, the deterministic projection
+ * at the expected return, and a table of the age (or years from today) at
+ * which the SIPPs run out at each percentile. Each family member after the
+ * first starts a new landscape page. Does not add footers — the caller is
+ * responsible for that.
+ * @param {Object} ctx - Shared rendering context
+ * @param {Object} ctx.pdf - The PDF document
+ * @param {Object} ctx.page - Current page (updated in place on ctx)
+ * @param {Array<Object>} ctx.pages - Array of all pages (pushed to when new pages added)
+ * @param {number} ctx.y - Current y position (updated in place on ctx)
+ * @param {Array<number>} ctx.pageWidths - Per-page usable widths (pushed to when new pages added)
+ * @param {Array<string>} [params] - User initials (or tokens like USER1); empty for everyone
+ * @param {Object} [block] - Block definition; may set years, expectedReturn and volatility (percent)
+ */
+export function renderRetirementProjectionBlock(ctx, params, block) {
+  const pdf = ctx.pdf;
+  let page = ctx.page;
+  const pages = ctx.pages;
+  let y = ctx.y;
+  const fonts = ctx.fonts;
+
+  const blockDef = block || {};
+  const userIds = resolveUserIds(resolveParams(params));
+  let projections = projectRetirementForUsers(userIds, readBlockOptions(blockDef));
+  if (userIds) {
+    projections = projections.slice().sort(function (a, b) {
+      return userIds.indexOf(a.user.id) - userIds.indexOf(b.user.id);
+    });
+  }
+
+  const testMode = isTestMode();
+  const titleBarColour = testMode ? COLOURS.emerald900 : COLOURS.brand800;
+  const headerRowColour = testMode ? COLOURS.green100 : COLOURS.brand100;
+
+  /**
+   * @description Draw the title bar across the full width.
+   * @param {string} text - The title text
+   */
+  function drawTitleBar(text) {
+    page.drawRectangle({
+      x: MARGIN_LEFT,
+      y: y - TITLE_BAR_HEIGHT,
+      width: USABLE_WIDTH,
+      height: TITLE_BAR_HEIGHT,
+      color: titleBarColour,
+    });
+    page.drawText(text, {
+      x: MARGIN_LEFT + 10,
+      y: y - TITLE_BAR_HEIGHT + 9,
+      font: fonts.bold,
+      size: FONT_SIZE_TITLE,
+      color: COLOURS.white,
+    });
+    y -= TITLE_BAR_HEIGHT;
+  }
+
+  let panelY = 0;
+
+  /**
+   * @description Draw one line of text in the side panel and move down.
+   * @param {number} x - Left edge of the panel
+   * @param {string} text - The text to draw
+   * @param {Object} [options] - { bold: boolean, colour: rgb }
+   */
+  function drawPanelLine(x, text, options) {
+    const opts = options || {};
+    page.drawText(text, {
+      x: x,
+      y: panelY - FONT_SIZE_ROW,
+      font: opts.bold ? fonts.bold : fonts.regular,
+      size: FONT_SIZE_ROW,
+      color: opts.colour || COLOURS.brand700,
+    });
+    panelY -= FONT_SIZE_ROW + 5;
+  }
+
+  if (projections.length === 0) {
+    drawTitleBar("Retirement Projection");
+    page.drawText("No SIPPs to project.", {
+      x: MARGIN_LEFT,
+      y: y - 12 - FONT_SIZE_ROW,
+      font: fonts.medium,
+      size: FONT_SIZE_ROW,
+      color: COLOURS.brand600,
+    });
+    y -= 12 + ROW_HEIGHT;
+  }
+
+  for (let i = 0; i < projections.length; i++) {
+    const projection = projections[i];
+    if (i > 0) {
+      page = pdf.addPage({ size: "a4", orientation: "landscape" });
+      pages.push(page);
+      if (ctx.pageWidths) ctx.pageWidths.push(USABLE_WIDTH);
+      y = drawPageHeader(pdf, page, MARGIN_LEFT, A4_LANDSCAPE_HEIGHT, MARGIN_TOP, fonts);
+    }
+
+    const user = projection.user;
+    drawTitleBar("Retirement Projection: " + user.first_name + " " + user.last_name + " (" + user.initials + ")");
+
+    // --- Subtitle: starting position and assumptions ---
+    const source =
+      projection.return_source === "history"
+        ? "from " + projection.history_period + " price history"
+        : projection.return_source === "override"
+          ? "as set for this report"
+          : "default assumptions";
+    const subtitle =
+      "Return " +
+      projection.expected_return.toFixed(1) +
+      "% a year, volatility " +
+      projection.volatility.toFixed(1) +
+      "% (" +
+      source +
+      "), " +
+      projection.simulations.toLocaleString("en-GB") +
+      " simulations over " +
+      projection.years +
+      " years from " +
+      projection.projection_date.split("-").reverse().join("/") +
+      (projection.recurring_income.length > 0 ? "; other income is not drawn from the SIPPs and does not affect depletion" : "");
+    page.drawText(subtitle, {
+      x: MARGIN_LEFT + 4,
+      y: y - SUBTITLE_HEIGHT + 3,
+      font: fonts.regular,
+      size: FONT_SIZE_SUBTITLE,
+      color: COLOURS.brand600,
+    });
+    y -= SUBTITLE_HEIGHT;
+
+    drawLegend(page, MARGIN_LEFT + 4, y - LEGEND_HEIGHT + 5, fonts);
+    y -= LEGEND_HEIGHT + 6;
+
+    // --- Chart on the left, depletion panel on the right ---
+    const chartWidth = USABLE_WIDTH - PANEL_WIDTH - PANEL_GAP;
+    drawBandChart(page, projection, MARGIN_LEFT, MARGIN_BOTTOM + 10, chartWidth, y - MARGIN_BOTTOM - 10, fonts);
+
+    const panelX = MARGIN_LEFT + chartWidth + PANEL_GAP;
+    panelY = y;
+
+    page.drawText("When the SIPPs run out", {
+      x: panelX,
+      y: panelY - FONT_SIZE_HEADING,
+      font: fonts.bold,
+      size: FONT_SIZE_HEADING,
+      color: COLOURS.brand800,
+    });
+    panelY -= FONT_SIZE_HEADING + 6;
+
+    page.drawRectangle({ x: panelX, y: panelY - HEADER_ROW_HEIGHT, width: PANEL_WIDTH, height: HEADER_ROW_HEIGHT, color: headerRowColour });
+    for (const col of DEPLETION_COLUMNS) {
+      if (col.align === "right") {
+        drawRightAligned(page, col.label, panelX + col.x, col.width, panelY - HEADER_ROW_HEIGHT + 5, fonts.bold, FONT_SIZE_HEADER, COLOURS.brand700);
+      } else {
+        page.drawText(col.label, { x: panelX + col.x + 2, y: panelY - HEADER_ROW_HEIGHT + 5, font: fonts.bold, size: FONT_SIZE_HEADER, color: COLOURS.brand700 });
+      }
+    }
+    panelY -= HEADER_ROW_HEIGHT;
+
+    const rows = projection.depletion.map(function (d) {
+      return { label: PERCENTILE_LABELS[d.percentile] || d.percentile + "th", years_lasted: d.years_lasted, age: d.age, bold: false };
+    });
+    rows.push({ label: "At expected return", years_lasted: projection.deterministic.years_lasted, age: projection.deterministic.age, bold: true });
+
+    for (const row of rows) {
+      const font = row.bold ? fonts.bold : fonts.medium;
+      const textY = panelY - ROW_HEIGHT + 4;
+      const lasts = row.years_lasted === null;
+      page.drawText(row.label, { x: panelX + 2, y: textY, font: font, size: FONT_SIZE_ROW, color: COLOURS.black });
+      drawRightAligned(page, lasts ? "Lasts " + projection.years + "+ yrs" : row.years_lasted.toFixed(1) + " yrs", panelX + 90, 75, textY, font, FONT_SIZE_ROW, lasts ? COLOURS.brand700 : COLOURS.black);
+      drawRightAligned(page, row.age !== null ? row.age.toFixed(1) : "", panelX + 165, 55, textY, font, FONT_SIZE_ROW, COLOURS.black);
+      page.drawLine({
+        start: { x: panelX, y: panelY - ROW_HEIGHT },
+        end: { x: panelX + PANEL_WIDTH, y: panelY - ROW_HEIGHT },
+        color: COLOURS.brand100,
+        thickness: 0.3,
+      });
+      panelY -= ROW_HEIGHT;
+    }
+    panelY -= 8;
+
+    drawPanelLine(panelX, "Lasts " + projection.years + " years in " + projection.success_rate + "% of simulations", {
+      bold: true,
+      colour: projection.success_rate < 50 ? COLOURS.red600 : COLOURS.brand800,
+    });
+    panelY -= 6;
+
+    // --- Starting position ---
+    page.drawText("Starting position", {
+      x: panelX,
+      y: panelY - FONT_SIZE_HEADING,
+      font: fonts.bold,
+      size: FONT_SIZE_HEADING,
+      color: COLOURS.brand800,
+    });
+    panelY -= FONT_SIZE_HEADING + 6;
+
+    if (projection.current_age !== null) {
+      drawPanelLine(panelX, "Age today: " + projection.current_age.toFixed(1));
+    }
+    for (const account of projection.accounts) {
+      drawPanelLine(panelX, account.account_ref + ": " + formatGBP(account.value) + ", drawing " + formatGBP(account.annual_drawdown) + " a year");
+    }
+    drawPanelLine(panelX, "Other income: " + formatGBP(projection.annual_recurring_income) + " a year", { bold: true });
+    for (const income of projection.recurring_income) {
+      drawPanelLine(panelX + 6, income.description + ": " + formatGBP(income.annual_amount));
+    }
+    panelY -= 6;
+    drawPanelLine(panelX, "Drawdowns and other income are held at");
+    drawPanelLine(panelX, "today's levels, with no allowance for inflation.");
+    if (projection.recurring_income.length > 0) {
+      drawPanelLine(panelX, "Other income is shown for reference only and");
+      drawPanelLine(panelX, "does not change when the SIPPs run out.");
+    }
+    if (projection.current_age === null) {
+      drawPanelLine(panelX, "Add a date of birth to show ages.");
+    }
+
+    y = MARGIN_BOTTOM;
+  }
+
+  // Write back modified state
+  ctx.page = page;
+  ctx.y = y;
+}
+
+/**
+ * @description Generate a standalone PDF for the Retirement Projection report.
+ * Creates a landscape PDF document, renders the block, adds footers, and returns bytes.
+ * @param {Array<string>} [params] - Optional user initials (or tokens) to include
+ * @param {Object} [options] - Optional { years, expectedReturn, volatility } overrides
+ * @returns {Promise<Uint8Array>} The PDF file bytes
+ */
+export async function generateRetirementProjectionPdf(params, options) {
+  const pdf = PDF.create();
+  const fonts = embedRobotoFonts(pdf);
+  const page = pdf.addPage({ size: "a4", orientation: "landscape" });
+  const pages = [page];
+  const y = drawPageHeader(pdf, page, MARGIN_LEFT, A4_LANDSCAPE_HEIGHT, MARGIN_TOP, fonts);
+
+  const ctx = { pdf: pdf, page: page, pages: pages, y: y, pageWidths: [USABLE_WIDTH], fonts: fonts };
+  renderRetirementProjectionBlock(ctx, params || [], options || {});
+
+  drawPageFooters(ctx.pages, "Retirement Projection", MARGIN_LEFT, USABLE_WIDTH, fonts);
+  return await pdf.save();
+}
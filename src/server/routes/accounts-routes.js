@@ -71,6 +71,7 @@ accountsRouter.post("/api/users/:userId/accounts", async function (request, para
       user_id: userId,
       account_type: body.account_type,
       account_ref: body.account_ref,
+      provider: body.provider ? String(body.provider).toLowerCase() : null,
       cash_balance: Number(body.cash_balance) || 0,
       warn_cash: Number(body.warn_cash) || 0,
     });
@@ -79,9 +80,9 @@ accountsRouter.post("/api/users/:userId/accounts", async function (request, para
       headers: { "Content-Type": "application/json" },
     });
   } catch (err) {
-    // Handle unique constraint violation (duplicate account type for user)
+    // Handle unique constraint violation (same type and reference already used by this user)
     if (err.message && err.message.includes("UNIQUE constraint")) {
-      return new Response(JSON.stringify({ error: "Validation failed", detail: "This user already has a " + body.account_type.toUpperCase() + " account" }), { status: 400, headers: { "Content-Type": "application/json" } });
+      return new Response(JSON.stringify({ error: "Validation failed", detail: "This user already has a " + body.account_type.toUpperCase() + " account with reference " + body.account_ref }), { status: 400, headers: { "Content-Type": "application/json" } });
     }
     return new Response(JSON.stringify({ error: "Failed to create account", detail: err.message }), { status: 500, headers: { "Content-Type": "application/json" } });
   }
@@ -99,7 +100,7 @@ accountsRouter.put("/api/accounts/:id", async function (request, params) {
   // For updates, account_type is not changeable — only validate ref and cash fields
   const errors = [];
   if (body.account_ref !== undefined) {
-    const refErr = validateAccount({ account_type: "trading", account_ref: body.account_ref, cash_balance: body.cash_balance, warn_cash: body.warn_cash });
+    const refErr = validateAccount({ account_type: "trading", account_ref: body.account_ref, provider: body.provider, cash_balance: body.cash_balance, warn_cash: body.warn_cash });
     // Filter out account_type errors (not relevant for update)
     for (const e of refErr) {
       if (!e.includes("Account type")) {
@@ -115,6 +116,7 @@ accountsRouter.put("/api/accounts/:id", async function (request, params) {
   try {
     const account = updateAccount(Number(params.id), {
       account_ref: body.account_ref,
+      provider: body.provider ? String(body.provider).toLowerCase() : null,
       cash_balance: Number(body.cash_balance) || 0,
       warn_cash: Number(body.warn_cash) || 0,
     });
@@ -126,6 +128,9 @@ accountsRouter.put("/api/accounts/:id", async function (request, params) {
       headers: { "Content-Type": "application/json" },
     });
   } catch (err) {
+    if (err.message && err.message.includes("UNIQUE constraint")) {
+      return new Response(JSON.stringify({ error: "Validation failed", detail: "This user already has an account of this type with reference " + body.account_ref }), { status: 400, headers: { "Content-Type": "application/json" } });
+    }
     return new Response(JSON.stringify({ error: "Failed to update account", detail: err.message }), { status: 500, headers: { "Content-Type": "application/json" } });
   }
 });
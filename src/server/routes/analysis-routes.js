@@ -1,7 +1,7 @@
 /**
  * @description API routes for the investment analysis feature.
  * Provides league table, risk/return scatter, top/bottom performer,
- * and multi-period comparison data.
+ * multi-period comparison and correlation matrix data.
  */
 
 import { Router } from "../router.js";
@@ -12,6 +12,7 @@ import {
   buildBenchmarkReturnData,
   buildBenchmarkRebasedSeries,
   buildComparisonTable,
+  buildCorrelationMatrix,
   resolveInvestmentIds,
   PERIOD_WEEKS,
 } from "../services/analysis-service.js";
@@ -21,6 +22,8 @@ import {
   generateTopBottomPdf,
   generateRiskReturnPdf,
 } from "../reports/pdf-analysis.js";
+import { generateAnalysisCorrelationPdf } from "../reports/pdf-correlation-matrix.js";
+import { buildAllocation, buildAllocationHistory, ALLOCATION_DIMENSIONS } from "../services/allocation-service.js";
 import { getAllUsers } from "../db/users-db.js";
 import { getDistinctAccountTypes } from "../db/accounts-db.js";
 
@@ -53,6 +56,16 @@ function parseBenchmarkIds(param) {
   return ids;
 }
 
+/**
+ * @description Parse the benchmark that beta and correlation are measured against.
+ * @param {string|null} param - The raw betaBenchmark query parameter
+ * @returns {number|null} Benchmark ID, or null if none chosen
+ */
+function parseBetaBenchmark(param) {
+  const id = parseInt(param, 10);
+  return id > 0 ? id : null;
+}
+
 /**
  * @description Parse and validate the holdings filter query parameter.
  * Accepts "current", "historic", or "all". Defaults to "current".
@@ -186,13 +199,13 @@ analysisRouter.get("/api/analysis/account-types", function (request) {
   }
 });
 
-// GET /api/analysis/league-table?period=1y — return ranked investments with sparklines
+// GET /api/analysis/league-table?period=1y&betaBenchmark=2 — return ranked investments with sparklines and risk metrics
 analysisRouter.get("/api/analysis/league-table", function (request) {
   try {
     const url = new URL(request.url);
     const period = validatePeriod(url.searchParams.get("period")) || "1y";
     const filters = resolveFilters(url);
-    const data = buildLeagueTable(period, filters.investmentIds);
+    const data = buildLeagueTable(period, filters.investmentIds, parseBetaBenchmark(url.searchParams.get("betaBenchmark")));
 
     return new Response(JSON.stringify(data), {
       headers: { "Content-Type": "application/json" },
@@ -205,14 +218,14 @@ analysisRouter.get("/api/analysis/league-table", function (request) {
   }
 });
 
-// GET /api/analysis/risk-return?period=1y&benchmarks=1,3 — return risk vs return scatter data
+// GET /api/analysis/risk-return?period=1y&benchmarks=1,3&betaBenchmark=1 — return risk vs return scatter data
 analysisRouter.get("/api/analysis/risk-return", function (request) {
   try {
     const url = new URL(request.url);
     const period = validatePeriod(url.searchParams.get("period")) || "1y";
     const benchmarkIds = parseBenchmarkIds(url.searchParams.get("benchmarks"));
     const filters = resolveFilters(url);
-    const data = buildRiskReturnData(period, filters.investmentIds);
+    const data = buildRiskReturnData(period, filters.investmentIds, parseBetaBenchmark(url.searchParams.get("betaBenchmark")));
 
     // Add benchmark scatter points if requested
     if (benchmarkIds.length > 0) {
@@ -260,7 +273,7 @@ analysisRouter.get("/api/analysis/top-bottom", function (request) {
   }
 });
 
-// GET /api/analysis/comparison?periods=3m,6m,1y,3y&benchmarks=1,3 — multi-period comparison table
+// GET /api/analysis/comparison?periods=3m,6m,1y,3y&benchmarks=1,3&betaBenchmark=1 — multi-period comparison table
 analysisRouter.get("/api/analysis/comparison", function (request) {
   try {
     const url = new URL(request.url);
@@ -277,7 +290,7 @@ analysisRouter.get("/api/analysis/comparison", function (request) {
     if (periodCodes.length === 0) periodCodes = ["3m", "6m", "1y", "3y"];
     const filters = resolveFilters(url);
 
-    const data = buildComparisonTable(periodCodes, benchmarkIds, filters.investmentIds);
+    const data = buildComparisonTable(periodCodes, benchmarkIds, filters.investmentIds, parseBetaBenchmark(url.searchParams.get("betaBenchmark")));
 
     return new Response(JSON.stringify(data), {
       headers: { "Content-Type": "application/json" },
@@ -290,6 +303,89 @@ analysisRouter.get("/api/analysis/comparison", function (request) {
   }
 });
 
+// GET /api/analysis/correlation?period=1y&benchmarks=1,3 — correlation matrix of weekly GBP returns
+analysisRouter.get("/api/analysis/correlation", function (request) {
+  try {
+    const url = new URL(request.url);
+    const period = validatePeriod(url.searchParams.get("period")) || "1y";
+    const benchmarkIds = parseBenchmarkIds(url.searchParams.get("benchmarks"));
+    const filters = resolveFilters(url);
+    const data = buildCorrelationMatrix(period, filters.investmentIds, benchmarkIds);
+
+    return new Response(JSON.stringify(data), {
+      headers: { "Content-Type": "application/json" },
+    });
+  } catch (err) {
+    return new Response(
+      JSON.stringify({ error: "Failed to build correlation matrix", detail: err.message }),
+      { status: 500, headers: { "Content-Type": "application/json" } },
+    );
+  }
+});
+
+/**
+ * @description Resolve the allocation scope from the users, accountTypes,
+ * accountId and cash query parameters. With no users given the whole
+ * household is included.
+ * @param {URL} url - The parsed request URL
+ * @returns {Object} Scope with userIds, accountTypes, accountId and includeCash
+ */
+function resolveAllocationScope(url) {
+  const accountId = parseInt(url.searchParams.get("accountId"), 10);
+  return {
+    userIds: parseUserIds(url.searchParams.get("users")),
+    accountTypes: parseAccountTypes(url.searchParams.get("accountTypes")),
+    accountId: accountId > 0 ? accountId : null,
+    includeCash: url.searchParams.get("cash") !== "exclude",
+  };
+}
+
+// GET /api/analysis/allocation?users=1,2&accountTypes=isa&accountId=3&cash=exclude — current allocation breakdown
+analysisRouter.get("/api/analysis/allocation", function (request) {
+  try {
+    const url = new URL(request.url);
+    const data = buildAllocation(resolveAllocationScope(url));
+
+    return new Response(JSON.stringify(data), {
+      headers: { "Content-Type": "application/json" },
+    });
+  } catch (err) {
+    return new Response(
+      JSON.stringify({ error: "Failed to build allocation", detail: err.message }),
+      { status: 500, headers: { "Content-Type": "application/json" } },
+    );
+  }
+});
+
+// GET /api/analysis/allocation/history?dimension=type&months=12&users=1,2 — month-end allocation drift
+analysisRouter.get("/api/analysis/allocation/history", function (request) {
+  try {
+    const url = new URL(request.url);
+    const dimension = url.searchParams.get("dimension") || "type";
+    if (ALLOCATION_DIMENSIONS.indexOf(dimension) === -1) {
+      return new Response(
+        JSON.stringify({ error: "Invalid dimension", detail: "Dimension must be one of: " + ALLOCATION_DIMENSIONS.join(", ") }),
+        { status: 400, headers: { "Content-Type": "application/json" } },
+      );
+    }
+
+    let months = parseInt(url.searchParams.get("months"), 10) || 12;
+    if (months < 1) months = 1;
+    if (months > 60) months = 60;
+
+    const data = buildAllocationHistory(resolveAllocationScope(url), dimension, months);
+
+    return new Response(JSON.stringify(data), {
+      headers: { "Content-Type": "application/json" },
+    });
+  } catch (err) {
+    return new Response(
+      JSON.stringify({ error: "Failed to build allocation history", detail: err.message }),
+      { status: 500, headers: { "Content-Type": "application/json" } },
+    );
+  }
+});
+
 // ─── PDF endpoints ──────────────────────────────────────────────
 
 // GET /api/analysis/pdf/comparison?periods=3m,6m,1y,3y&benchmarks=1,3
@@ -404,6 +500,31 @@ analysisRouter.get("/api/analysis/pdf/top-bottom", async function (request) {
   }
 });
 
+// GET /api/analysis/pdf/correlation?period=1y&benchmarks=1,3
+analysisRouter.get("/api/analysis/pdf/correlation", async function (request) {
+  try {
+    const url = new URL(request.url);
+    const period = validatePeriod(url.searchParams.get("period")) || "1y";
+    const benchmarkIds = parseBenchmarkIds(url.searchParams.get("benchmarks"));
+
+    const filters = resolveFilters(url);
+    const filterText = buildFilterText(filters);
+
+    const pdfBytes = await generateAnalysisCorrelationPdf(period, benchmarkIds, filters.investmentIds, filterText);
+    return new Response(pdfBytes, {
+      headers: {
+        "Content-Type": "application/pdf",
+        "Content-Disposition": 'inline; filename="analysis-correlation.pdf"',
+      },
+    });
+  } catch (err) {
+    return new Response(
+      JSON.stringify({ error: "Failed to generate correlation PDF", detail: err.message }),
+      { status: 500, headers: { "Content-Type": "application/json" } },
+    );
+  }
+});
+
 /**
  * @description Route handler for analysis API requests.
  * @param {string} method - HTTP method
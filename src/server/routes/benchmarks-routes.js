@@ -8,6 +8,7 @@ import {
   getGbpCurrencyId,
 } from "../db/benchmarks-db.js";
 import { getBenchmarkDataHistory, getBenchmarkDataCount } from "../db/benchmark-data-db.js";
+import { getBenchmarkComponents, setBenchmarkComponents, getCompositesUsingBenchmark } from "../db/benchmark-components-db.js";
 import { validateBenchmark } from "../validation.js";
 import { pushConfigToFetchServer } from "../services/fetch-server-push.js";
 
@@ -18,27 +19,74 @@ import { pushConfigToFetchServer } from "../services/fetch-server-push.js";
 const benchmarksRouter = new Router();
 
 /**
- * @description Validate that index benchmarks use GBP currency.
+ * @description Validate that index and composite benchmarks use GBP currency.
  * @param {Object} body - The request body with benchmark_type and currencies_id
  * @returns {string|null} Error message if invalid, null if valid
  */
 function validateIndexCurrency(body) {
-  if (body.benchmark_type === "index") {
+  if (body.benchmark_type === "index" || body.benchmark_type === "composite") {
+    const label = body.benchmark_type === "index" ? "Index" : "Composite";
     const gbpId = getGbpCurrencyId();
     if (gbpId === null) {
-      return "GBP currency must exist before creating index benchmarks";
+      return "GBP currency must exist before creating " + label.toLowerCase() + " benchmarks";
     }
     if (Number(body.currencies_id) !== gbpId) {
-      return "Index benchmarks must use GBP currency";
+      return label + " benchmarks must use GBP currency";
     }
   }
   return null;
 }
 
-// GET /api/benchmarks — list all benchmarks with currency details
+/**
+ * @description Check a composite benchmark's components against the database:
+ * each must exist and be a fetched benchmark, not a composite, and a benchmark
+ * that is itself a component cannot become a composite.
+ * @param {Object} body - The request body with benchmark_type and components
+ * @param {number|null} benchmarkId - The benchmark being updated, or null when creating
+ * @returns {string|null} Error message if invalid, null if valid
+ */
+function validateComposite(body, benchmarkId) {
+  if (body.benchmark_type !== "composite") return null;
+
+  if (benchmarkId) {
+    const usedBy = getCompositesUsingBenchmark(benchmarkId);
+    if (usedBy.length > 0) {
+      return "Benchmark is part of the composite " + usedBy.join(", ") + " and cannot be a composite itself";
+    }
+  }
+
+  for (const component of body.components) {
+    const componentId = Number(component.component_benchmark_id);
+    if (componentId === benchmarkId) {
+      return "A composite benchmark cannot include itself";
+    }
+    const componentBenchmark = getBenchmarkById(componentId);
+    if (!componentBenchmark) {
+      return "Component benchmark " + componentId + " not found";
+    }
+    if (componentBenchmark.benchmark_type === "composite") {
+      return componentBenchmark.description + " is a composite and cannot be a component";
+    }
+  }
+  return null;
+}
+
+/**
+ * @description Add the components to a composite benchmark for the response.
+ * @param {Object} benchmark - Benchmark from the database
+ * @returns {Object} The benchmark, with a components array if it is a composite
+ */
+function withComponents(benchmark) {
+  if (benchmark && benchmark.benchmark_type === "composite") {
+    benchmark.components = getBenchmarkComponents(benchmark.id);
+  }
+  return benchmark;
+}
+
+// GET /api/benchmarks — list all benchmarks with currency details (composites include their components)
 benchmarksRouter.get("/api/benchmarks", function () {
   try {
-    const benchmarks = getAllBenchmarks();
+    const benchmarks = getAllBenchmarks().map(withComponents);
     return new Response(JSON.stringify(benchmarks), {
       status: 200,
       headers: { "Content-Type": "application/json" },
@@ -96,7 +144,7 @@ benchmarksRouter.get("/api/benchmarks/:id/values", function (request, params) {
 // GET /api/benchmarks/:id — get a single benchmark
 benchmarksRouter.get("/api/benchmarks/:id", function (request, params) {
   try {
-    const benchmark = getBenchmarkById(Number(params.id));
+    const benchmark = withComponents(getBenchmarkById(Number(params.id)));
     if (!benchmark) {
       return new Response(
         JSON.stringify({ error: "Benchmark not found" }),
@@ -141,7 +189,7 @@ benchmarksRouter.post("/api/benchmarks", async function (request) {
     );
   }
 
-  // Business rule: index benchmarks must use GBP
+  // Business rule: index and composite benchmarks must use GBP
   const indexCurrencyError = validateIndexCurrency(body);
   if (indexCurrencyError) {
     return new Response(
@@ -150,8 +198,20 @@ benchmarksRouter.post("/api/benchmarks", async function (request) {
     );
   }
 
+  const compositeError = validateComposite(body, null);
+  if (compositeError) {
+    return new Response(
+      JSON.stringify({ error: "Validation failed", detail: compositeError }),
+      { status: 400, headers: { "Content-Type": "application/json" } },
+    );
+  }
+
   try {
-    const benchmark = createBenchmark(body);
+    let benchmark = createBenchmark(body);
+    if (body.benchmark_type === "composite") {
+      setBenchmarkComponents(benchmark.id, body.components);
+      benchmark = withComponents(benchmark);
+    }
     pushConfigToFetchServer().catch(function () {});
     return new Response(JSON.stringify(benchmark), {
       status: 201,
@@ -198,7 +258,7 @@ benchmarksRouter.put("/api/benchmarks/:id", async function (request, params) {
     );
   }
 
-  // Business rule: index benchmarks must use GBP
+  // Business rule: index and composite benchmarks must use GBP
   const indexCurrencyError = validateIndexCurrency(body);
   if (indexCurrencyError) {
     return new Response(
@@ -207,6 +267,14 @@ benchmarksRouter.put("/api/benchmarks/:id", async function (request, params) {
     );
   }
 
+  const compositeError = validateComposite(body, Number(params.id));
+  if (compositeError) {
+    return new Response(
+      JSON.stringify({ error: "Validation failed", detail: compositeError }),
+      { status: 400, headers: { "Content-Type": "application/json" } },
+    );
+  }
+
   try {
     const benchmark = updateBenchmark(Number(params.id), body);
     if (!benchmark) {
@@ -215,6 +283,9 @@ benchmarksRouter.put("/api/benchmarks/:id", async function (request, params) {
         { status: 404, headers: { "Content-Type": "application/json" } },
       );
     }
+    // Components are kept only while the benchmark is a composite
+    setBenchmarkComponents(benchmark.id, body.benchmark_type === "composite" ? body.components : []);
+    withComponents(benchmark);
     pushConfigToFetchServer().catch(function () {});
     return new Response(JSON.stringify(benchmark), {
       status: 200,
@@ -0,0 +1,88 @@
+import { Router } from "../router.js";
+import { previewBrokerImport, commitBrokerImport } from "../services/broker-import-service.js";
+
+/**
+ * @description Router instance for broker CSV import API routes.
+ * @type {Router}
+ */
+const importRouter = new Router();
+
+/**
+ * @description Read and check the JSON body of an import request.
+ * @param {Request} request - The incoming request
+ * @returns {Promise<{ body: Object|null, error: Response|null }>} The body, or an error response
+ */
+async function readImportBody(request) {
+  let body;
+  try {
+    body = await request.json();
+  } catch {
+    return { body: null, error: new Response(JSON.stringify({ error: "Invalid request", detail: "Request body must be valid JSON" }), { status: 400, headers: { "Content-Type": "application/json" } }) };
+  }
+
+  if (!body || typeof body.csv !== "string" || body.csv.trim() === "") {
+    return { body: null, error: new Response(JSON.stringify({ error: "Validation failed", detail: "CSV file contents are required" }), { status: 400, headers: { "Content-Type": "application/json" } }) };
+  }
+
+  return { body: body, error: null };
+}
+
+// POST /api/accounts/:accountId/import/preview — dry run: parse, match and flag duplicates
+// Body: { provider: "ii" | "hl" | "aj" (optional — defaults to the account's provider), csv: "<file contents>" }
+importRouter.post("/api/accounts/:accountId/import/preview", async function (request, params) {
+  const { body, error } = await readImportBody(request);
+  if (error) return error;
+
+  try {
+    const preview = previewBrokerImport(Number(params.accountId), body.provider || null, body.csv);
+    if (!preview) {
+      return new Response(JSON.stringify({ error: "Account not found" }), { status: 404, headers: { "Content-Type": "application/json" } });
+    }
+    return new Response(JSON.stringify(preview), {
+      status: 200,
+      headers: { "Content-Type": "application/json" },
+    });
+  } catch (err) {
+    return new Response(JSON.stringify({ error: "Import failed", detail: err.message }), { status: 400, headers: { "Content-Type": "application/json" } });
+  }
+});
+
+// POST /api/accounts/:accountId/import — apply the new rows of the account's last preview
+// Body: { file_hash: "<file_hash from the preview>" }
+importRouter.post("/api/accounts/:accountId/import", async function (request, params) {
+  let body;
+  try {
+    body = await request.json();
+  } catch {
+    return new Response(JSON.stringify({ error: "Invalid request", detail: "Request body must be valid JSON" }), { status: 400, headers: { "Content-Type": "application/json" } });
+  }
+
+  if (!body || typeof body.file_hash !== "string" || body.file_hash === "") {
+    return new Response(JSON.stringify({ error: "Validation failed", detail: "The file_hash of the preview is required" }), { status: 400, headers: { "Content-Type": "application/json" } });
+  }
+
+  try {
+    const result = commitBrokerImport(Number(params.accountId), body.file_hash);
+    if (!result) {
+      return new Response(JSON.stringify({ error: "Account not found" }), { status: 404, headers: { "Content-Type": "application/json" } });
+    }
+    return new Response(JSON.stringify(result), {
+      status: result.failed ? 409 : 200,
+      headers: { "Content-Type": "application/json" },
+    });
+  } catch (err) {
+    const status = err.message.startsWith("The file does not match") ? 409 : 400;
+    return new Response(JSON.stringify({ error: "Import failed", detail: err.message }), { status: status, headers: { "Content-Type": "application/json" } });
+  }
+});
+
+/**
+ * @description Handle a broker import API request. Delegates to the import router.
+ * @param {string} method - HTTP method
+ * @param {string} path - URL pathname
+ * @param {Request} request - The full Request object
+ * @returns {Promise<Response|null>} Response if matched, null otherwise
+ */
+export async function handleBrokerImportRoute(method, path, request) {
+  return await importRouter.match(method, path, request);
+}
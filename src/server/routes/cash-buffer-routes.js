@@ -0,0 +1,133 @@
+import { Router } from "../router.js";
+import { getAccountById } from "../db/accounts-db.js";
+import { planCashBuffer, commitCashBufferSales } from "../services/cash-buffer-service.js";
+import { validateCashBufferSales } from "../validation.js";
+
+/**
+ * @description Router instance for the cash buffer planner API routes.
+ * @type {Router}
+ */
+const cashBufferRouter = new Router();
+
+/**
+ * @description Look up a SIPP account for the cash buffer routes.
+ * @param {number} accountId - The account ID
+ * @returns {{ account: Object|null, error: Response|null }} The account, or an error response
+ */
+function findSippAccount(accountId) {
+  const account = getAccountById(accountId);
+  if (!account) {
+    return { account: null, error: new Response(JSON.stringify({ error: "Account not found" }), { status: 404, headers: { "Content-Type": "application/json" } }) };
+  }
+  if (account.account_type !== "sipp") {
+    return {
+      account: null,
+      error: new Response(JSON.stringify({ error: "Not a SIPP account", detail: "The cash buffer planner covers drawdowns from SIPP accounts" }), { status: 400, headers: { "Content-Type": "application/json" } }),
+    };
+  }
+  return { account: account, error: null };
+}
+
+// GET /api/accounts/:accountId/cash-buffer — forecast drawdowns and propose sales to cover any shortfall
+// Optional query params: ?months=12&method=priority|pro_rata&holdings=3,1,2&minimumCash=500
+// holdings lists the holding IDs that may be sold, in priority order
+cashBufferRouter.get("/api/accounts/:accountId/cash-buffer", function (request, params) {
+  try {
+    const accountId = Number(params.accountId);
+    const found = findSippAccount(accountId);
+    if (found.error) return found.error;
+
+    const url = new URL(request.url);
+    const options = {};
+
+    const months = url.searchParams.get("months");
+    if (months !== null) {
+      options.months = Number(months);
+      if (!Number.isInteger(options.months) || options.months < 1 || options.months > 60) {
+        return new Response(JSON.stringify({ error: "Invalid months — use a whole number from 1 to 60" }), { status: 400, headers: { "Content-Type": "application/json" } });
+      }
+    }
+
+    const method = url.searchParams.get("method");
+    if (method !== null) {
+      if (method !== "priority" && method !== "pro_rata") {
+        return new Response(JSON.stringify({ error: "Invalid method — use priority or pro_rata" }), { status: 400, headers: { "Content-Type": "application/json" } });
+      }
+      options.method = method;
+    }
+
+    const holdings = url.searchParams.get("holdings");
+    if (holdings) {
+      options.holding_ids = holdings.split(",").map(Number);
+      if (options.holding_ids.some((id) => !Number.isInteger(id) || id <= 0)) {
+        return new Response(JSON.stringify({ error: "Invalid holdings — use a comma-separated list of holding IDs" }), { status: 400, headers: { "Content-Type": "application/json" } });
+      }
+    }
+
+    const minimumCash = url.searchParams.get("minimumCash");
+    if (minimumCash !== null && minimumCash !== "") {
+      options.minimum_cash = Number(minimumCash);
+      if (isNaN(options.minimum_cash) || options.minimum_cash < 0) {
+        return new Response(JSON.stringify({ error: "Invalid minimumCash — use an amount of zero or more" }), { status: 400, headers: { "Content-Type": "application/json" } });
+      }
+    }
+
+    const plan = planCashBuffer(accountId, options);
+    return new Response(JSON.stringify(plan), {
+      status: 200,
+      headers: { "Content-Type": "application/json", "Cache-Control": "no-cache, no-store" },
+    });
+  } catch (err) {
+    return new Response(JSON.stringify({ error: "Failed to plan cash buffer", detail: err.message }), { status: 500, headers: { "Content-Type": "application/json" } });
+  }
+});
+
+// POST /api/accounts/:accountId/cash-buffer/sales — commit proposed sales as sell movements
+// Body: { movement_date, sales: [{ holding_id, quantity, total_consideration }] }
+cashBufferRouter.post("/api/accounts/:accountId/cash-buffer/sales", async function (request, params) {
+  const accountId = Number(params.accountId);
+  const found = findSippAccount(accountId);
+  if (found.error) return found.error;
+
+  let body;
+  try {
+    body = await request.json();
+  } catch {
+    return new Response(JSON.stringify({ error: "Invalid request", detail: "Request body must be valid JSON" }), { status: 400, headers: { "Content-Type": "application/json" } });
+  }
+
+  const errors = validateCashBufferSales(body);
+  if (errors.length > 0) {
+    return new Response(JSON.stringify({ error: "Validation failed", detail: errors.join("; ") }), { status: 400, headers: { "Content-Type": "application/json" } });
+  }
+
+  const sales = body.sales.map(function (sale) {
+    return {
+      holding_id: Number(sale.holding_id),
+      quantity: Number(sale.quantity),
+      total_consideration: Number(sale.total_consideration),
+    };
+  });
+
+  try {
+    const movements = commitCashBufferSales(accountId, sales, body.movement_date);
+    return new Response(JSON.stringify({ movements: movements, account: getAccountById(accountId) }), {
+      status: 201,
+      headers: { "Content-Type": "application/json" },
+    });
+  } catch (err) {
+    const status = err.message.includes("not in this account") || err.message.startsWith("Insufficient") ? 400 : 500;
+    return new Response(JSON.stringify({ error: "Failed to record sales", detail: err.message }), { status: status, headers: { "Content-Type": "application/json" } });
+  }
+});
+
+/**
+ * @description Handle a cash buffer planner API request. Delegates to the cash buffer router.
+ * @param {string} method - HTTP method
+ * @param {string} path - URL pathname
+ * @param {Request} request - The full Request object
+ * @returns {Promise<Response|null>} Response if matched, null otherwise
+ */
+export async function handleCashBufferRoute(method, path, request) {
+  return await cashBufferRouter.match(method, path, request);
+}
@@ -1,8 +1,10 @@
 import { Router } from "../router.js";
-import { createCashTransaction, getCashTransactionById, getCashTransactionsByAccountId, getIsaDepositsForTaxYear } from "../db/cash-transactions-db.js";
+import { createCashTransaction, getCashTransactionById, getCashTransactionsByAccountId } from "../db/cash-transactions-db.js";
 import { getAccountById } from "../db/accounts-db.js";
-import { getIsaAllowanceConfig } from "../config.js";
+import { getInvestmentById } from "../db/investments-db.js";
+import { accountHasHeldInvestment } from "../db/holdings-db.js";
 import { validateCashTransaction } from "../validation.js";
+import { getIsaAllowanceForUser, checkIsaSubscription } from "../services/isa-allowance-service.js";
 
 /**
  * @description Router instance for cash transaction API routes.
@@ -47,7 +49,8 @@ cashTxRouter.get("/api/cash-transactions/:id", function (request, params) {
   }
 });
 
-// POST /api/accounts/:accountId/cash-transactions — create a deposit or withdrawal
+// POST /api/accounts/:accountId/cash-transactions — create a deposit, withdrawal, drawdown,
+// adjustment, income (dividend/interest) or pension tax relief transaction
 cashTxRouter.post("/api/accounts/:accountId/cash-transactions", async function (request, params) {
   let body;
   try {
@@ -67,6 +70,11 @@ cashTxRouter.post("/api/accounts/:accountId/cash-transactions", async function (
     return new Response(JSON.stringify({ error: "Validation failed", detail: errors.join("; ") }), { status: 400, headers: { "Content-Type": "application/json" } });
   }
 
+  // Relief-at-source top-ups are only paid into pensions
+  if (body.transaction_type === "tax_relief" && account.account_type !== "sipp") {
+    return new Response(JSON.stringify({ error: "Not a SIPP account", detail: "Tax relief can only be paid into a SIPP" }), { status: 400, headers: { "Content-Type": "application/json" } });
+  }
+
   // Hard check: withdrawals, drawdowns and adjustment debits must not exceed available cash balance
   const amount = Number(body.amount);
   if (body.transaction_type === "drawdown" && amount > account.cash_balance) {
@@ -101,6 +109,48 @@ cashTxRouter.post("/api/accounts/:accountId/cash-transactions", async function (
     );
   }
 
+  // Income must come from an investment held in this account. Accumulation units
+  // reinvest income into the price, so there is no cash payment to record.
+  let investmentId = null;
+  if ((body.transaction_type === "dividend" || body.transaction_type === "interest") && body.investment_id) {
+    investmentId = Number(body.investment_id);
+    const investment = getInvestmentById(investmentId);
+    if (!investment) {
+      return new Response(JSON.stringify({ error: "Investment not found" }), { status: 404, headers: { "Content-Type": "application/json" } });
+    }
+    if (investment.unit_type === "accumulation") {
+      return new Response(
+        JSON.stringify({
+          error: "Accumulation units",
+          detail: `${investment.description} is held as accumulation units — income is reinvested into the price, not paid as cash`,
+        }),
+        { status: 400, headers: { "Content-Type": "application/json" } },
+      );
+    }
+    if (!accountHasHeldInvestment(accountId, investmentId)) {
+      return new Response(
+        JSON.stringify({ error: "Investment not held", detail: `${investment.description} has never been held in this account` }),
+        { status: 400, headers: { "Content-Type": "application/json" } },
+      );
+    }
+  }
+
+  // Warn before an ISA deposit takes the owner over the annual subscription limit.
+  // The deposit is only recorded once the caller confirms with confirm_over_allowance.
+  if (body.transaction_type === "deposit" && account.account_type === "isa" && body.confirm_over_allowance !== true) {
+    const check = checkIsaSubscription(accountId, body.transaction_date, amount);
+    if (check && check.exceeds) {
+      return new Response(
+        JSON.stringify({
+          error: "ISA allowance exceeded",
+          detail: `Deposit of £${amount.toFixed(2)} exceeds the remaining ${check.tax_year} ISA allowance of £${check.remaining.toFixed(2)}`,
+          allowance: check,
+        }),
+        { status: 409, headers: { "Content-Type": "application/json" } },
+      );
+    }
+  }
+
   try {
     const tx = createCashTransaction({
       account_id: accountId,
@@ -109,6 +159,9 @@ cashTxRouter.post("/api/accounts/:accountId/cash-transactions", async function (
       amount: amount,
       notes: body.notes || null,
       direction: adjustmentDirection,
+      investment_id: investmentId,
+      tax_withheld: body.tax_withheld !== undefined && body.tax_withheld !== null && body.tax_withheld !== "" ? Number(body.tax_withheld) : null,
+      tax_code: body.tax_code ? String(body.tax_code).toUpperCase().trim() : null,
     });
     return new Response(JSON.stringify(tx), {
       status: 201,
@@ -124,7 +177,8 @@ cashTxRouter.delete("/api/cash-transactions/:id", function () {
   return new Response(JSON.stringify({ error: "Cannot delete transaction", detail: "Transactions cannot be deleted. Use an adjustment transaction to correct errors." }), { status: 400, headers: { "Content-Type": "application/json" } });
 });
 
-// GET /api/accounts/:accountId/isa-allowance — get ISA allowance usage for current tax year
+// GET /api/accounts/:accountId/isa-allowance — get ISA allowance usage for current tax year.
+// deposits_this_year is this account's share; used and remaining cover all of the owner's ISAs.
 cashTxRouter.get("/api/accounts/:accountId/isa-allowance", function (request, params) {
   try {
     const accountId = Number(params.accountId);
@@ -137,19 +191,22 @@ cashTxRouter.get("/api/accounts/:accountId/isa-allowance", function (request, pa
       return new Response(JSON.stringify({ error: "Not an ISA account", detail: "ISA allowance is only available for ISA accounts" }), { status: 400, headers: { "Content-Type": "application/json" } });
     }
 
-    const isaConfig = getIsaAllowanceConfig();
-    const taxYear = getCurrentTaxYear(isaConfig.taxYearStartMonth, isaConfig.taxYearStartDay);
-    const deposits = getIsaDepositsForTaxYear(accountId, taxYear.start, taxYear.end);
-    const remaining = isaConfig.annualLimit - deposits;
+    const usage = getIsaAllowanceForUser(account.user_id);
+    const accountUsage = usage.accounts.find(function (a) {
+      return a.account_id === accountId;
+    });
 
     return new Response(
       JSON.stringify({
-        annual_limit: isaConfig.annualLimit,
-        tax_year: taxYear.label,
-        tax_year_start: taxYear.start,
-        tax_year_end: taxYear.end,
-        deposits_this_year: deposits,
-        remaining: remaining,
+        annual_limit: usage.annual_limit,
+        tax_year: usage.tax_year,
+        tax_year_start: usage.tax_year_start,
+        tax_year_end: usage.tax_year_end,
+        deposits_this_year: accountUsage ? accountUsage.total : 0,
+        used: usage.used,
+        remaining: usage.remaining,
+        over_limit: usage.over_limit,
+        accounts: usage.accounts,
       }),
       { status: 200, headers: { "Content-Type": "application/json" } },
     );
@@ -158,45 +215,6 @@ cashTxRouter.get("/api/accounts/:accountId/isa-allowance", function (request, pa
   }
 });
 
-/**
- * @description Calculate the current UK tax year boundaries based on today's date.
- * UK tax year runs from taxYearStartDay/taxYearStartMonth to the day before next year.
- * @param {number} startMonth - Month the tax year starts (1-12, typically 4 for April)
- * @param {number} startDay - Day the tax year starts (typically 6)
- * @returns {{ start: string, end: string, label: string }} Tax year boundaries as ISO-8601 dates
- */
-export function getCurrentTaxYear(startMonth, startDay) {
-  const today = new Date();
-  const currentYear = today.getFullYear();
-  const currentMonth = today.getMonth() + 1; // 1-indexed
-  const currentDay = today.getDate();
-
-  // Determine the tax year start year
-  // If we're before the tax year start date, the tax year started last calendar year
-  let taxYearStartYear;
-  if (currentMonth < startMonth || (currentMonth === startMonth && currentDay < startDay)) {
-    taxYearStartYear = currentYear - 1;
-  } else {
-    taxYearStartYear = currentYear;
-  }
-
-  const taxYearEndYear = taxYearStartYear + 1;
-
-  // Build ISO date strings
-  const startStr = `${taxYearStartYear}-${String(startMonth).padStart(2, "0")}-${String(startDay).padStart(2, "0")}`;
-
-  // End date is the day before the next tax year starts
-  const endDate = new Date(taxYearEndYear, startMonth - 1, startDay);
-  endDate.setDate(endDate.getDate() - 1);
-  const endStr = `${endDate.getFullYear()}-${String(endDate.getMonth() + 1).padStart(2, "0")}-${String(endDate.getDate()).padStart(2, "0")}`;
-
-  return {
-    start: startStr,
-    end: endStr,
-    label: `${taxYearStartYear}/${taxYearEndYear}`,
-  };
-}
-
 /**
  * @description Handle a cash transaction API request. Delegates to the cash tx router.
  * @param {string} method - HTTP method
@@ -0,0 +1,65 @@
+import { Router } from "../router.js";
+import { getCapitalGainsReport } from "../services/cgt-service.js";
+import { parseTaxYearLabel } from "../services/tax-year-utils.js";
+
+/**
+ * @description Router instance for capital gains tax API routes.
+ * @type {Router}
+ */
+const cgtRouter = new Router();
+
+// GET /api/cgt/:userId — capital gains report for a user's trading accounts
+// Optional query param: ?taxYear=2025/2026 to restrict to a single tax year
+cgtRouter.get("/api/cgt/:userId", function (request, params) {
+  try {
+    const userId = Number(params.userId);
+    if (!userId || userId <= 0) {
+      return new Response(
+        JSON.stringify({ error: "Invalid user ID" }),
+        { status: 400, headers: { "Content-Type": "application/json" } },
+      );
+    }
+
+    const url = new URL(request.url);
+    const taxYearParam = url.searchParams.get("taxYear");
+    let taxYearStart = null;
+    if (taxYearParam) {
+      taxYearStart = parseTaxYearLabel(taxYearParam);
+      if (!taxYearStart) {
+        return new Response(
+          JSON.stringify({ error: "Invalid tax year — use YYYY/YYYY (e.g. 2025/2026)" }),
+          { status: 400, headers: { "Content-Type": "application/json" } },
+        );
+      }
+    }
+
+    const report = getCapitalGainsReport(userId, taxYearStart);
+    if (!report) {
+      return new Response(
+        JSON.stringify({ error: "User not found" }),
+        { status: 404, headers: { "Content-Type": "application/json" } },
+      );
+    }
+
+    return new Response(JSON.stringify(report), {
+      status: 200,
+      headers: { "Content-Type": "application/json" },
+    });
+  } catch (err) {
+    return new Response(
+      JSON.stringify({ error: "Failed to calculate capital gains", detail: err.message }),
+      { status: 500, headers: { "Content-Type": "application/json" } },
+    );
+  }
+});
+
+/**
+ * @description Handle a capital gains tax API request. Delegates to the CGT router.
+ * @param {string} method - HTTP method
+ * @param {string} path - URL pathname
+ * @param {Request} request - The full Request object
+ * @returns {Promise<Response|null>} Response if matched, null otherwise
+ */
+export async function handleCgtRoute(method, path, request) {
+  return await cgtRouter.match(method, path, request);
+}
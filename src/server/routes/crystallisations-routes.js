@@ -0,0 +1,152 @@
+import { Router } from "../router.js";
+import { getAccountById } from "../db/accounts-db.js";
+import { createCrystallisation, deleteCrystallisation } from "../db/crystallisations-db.js";
+import { checkTaxFreeCash, checkAmountCrystallised } from "../services/crystallisation-service.js";
+import { getPortfolioSummary } from "../services/portfolio-service.js";
+import { validateCrystallisation } from "../validation.js";
+
+/**
+ * @description Router instance for SIPP crystallisation API routes.
+ * @type {Router}
+ */
+const crystallisationsRouter = new Router();
+
+// GET /api/accounts/:accountId/crystallisations — crystallisation events and the
+// crystallised/uncrystallised split for a SIPP, valued at latest prices
+crystallisationsRouter.get("/api/accounts/:accountId/crystallisations", function (request, params) {
+  try {
+    const accountId = Number(params.accountId);
+    const account = getAccountById(accountId);
+    if (!account) {
+      return new Response(JSON.stringify({ error: "Account not found" }), { status: 404, headers: { "Content-Type": "application/json" } });
+    }
+
+    if (account.account_type !== "sipp") {
+      return new Response(JSON.stringify({ error: "Not a SIPP account", detail: "Only SIPP accounts can be crystallised" }), { status: 400, headers: { "Content-Type": "application/json" } });
+    }
+
+    // The portfolio summary already values the account and attaches its crystallisation summary
+    const summary = getPortfolioSummary(account.user_id);
+    const accountSummary = summary.accounts.find(function (a) {
+      return a.id === accountId;
+    });
+
+    return new Response(JSON.stringify(accountSummary.crystallisation), {
+      status: 200,
+      headers: { "Content-Type": "application/json", "Cache-Control": "no-cache, no-store" },
+    });
+  } catch (err) {
+    return new Response(JSON.stringify({ error: "Failed to fetch crystallisations", detail: err.message }), { status: 500, headers: { "Content-Type": "application/json" } });
+  }
+});
+
+// POST /api/accounts/:accountId/crystallisations — record a crystallisation.
+// Body: { crystallisation_date, tax_free_cash, drawdown_amount, pay_from_cash?, notes? }
+// With pay_from_cash, the tax-free cash is debited from the SIPP's cash as a withdrawal.
+crystallisationsRouter.post("/api/accounts/:accountId/crystallisations", async function (request, params) {
+  const accountId = Number(params.accountId);
+  const account = getAccountById(accountId);
+  if (!account) {
+    return new Response(JSON.stringify({ error: "Account not found" }), { status: 404, headers: { "Content-Type": "application/json" } });
+  }
+
+  if (account.account_type !== "sipp") {
+    return new Response(JSON.stringify({ error: "Not a SIPP account", detail: "Only SIPP accounts can be crystallised" }), { status: 400, headers: { "Content-Type": "application/json" } });
+  }
+
+  let body;
+  try {
+    body = await request.json();
+  } catch {
+    return new Response(JSON.stringify({ error: "Invalid request", detail: "Request body must be valid JSON" }), { status: 400, headers: { "Content-Type": "application/json" } });
+  }
+
+  const errors = validateCrystallisation(body);
+  if (errors.length > 0) {
+    return new Response(JSON.stringify({ error: "Validation failed", detail: errors.join("; ") }), { status: 400, headers: { "Content-Type": "application/json" } });
+  }
+
+  const taxFreeCash = Number(body.tax_free_cash || 0);
+  const drawdownAmount = Number(body.drawdown_amount || 0);
+  const payFromCash = body.pay_from_cash === true;
+
+  // The account's value at latest prices sets how much is still uncrystallised
+  const accountSummary = getPortfolioSummary(account.user_id).accounts.find(function (a) {
+    return a.id === accountId;
+  });
+  const fundCheck = checkAmountCrystallised(accountId, taxFreeCash + drawdownAmount, accountSummary.account_total);
+  if (fundCheck.exceeds) {
+    return new Response(
+      JSON.stringify({
+        error: "Exceeds uncrystallised fund",
+        detail: `Crystallising £${(taxFreeCash + drawdownAmount).toFixed(2)} exceeds the uncrystallised fund of £${fundCheck.uncrystallised_fund.toFixed(2)}`,
+      }),
+      { status: 400, headers: { "Content-Type": "application/json" } },
+    );
+  }
+
+  const check = checkTaxFreeCash(accountId, taxFreeCash);
+  if (check.exceeds) {
+    return new Response(
+      JSON.stringify({
+        error: "Exceeds lump sum allowance",
+        detail: `Tax-free cash of £${taxFreeCash.toFixed(2)} exceeds the remaining lump sum allowance of £${check.remaining.toFixed(2)}`,
+      }),
+      { status: 400, headers: { "Content-Type": "application/json" } },
+    );
+  }
+
+  if (payFromCash && taxFreeCash > account.cash_balance) {
+    return new Response(
+      JSON.stringify({
+        error: "Insufficient cash",
+        detail: `Tax-free cash of £${taxFreeCash.toFixed(2)} exceeds available balance of £${account.cash_balance.toFixed(2)}`,
+      }),
+      { status: 400, headers: { "Content-Type": "application/json" } },
+    );
+  }
+
+  try {
+    const crystallisation = createCrystallisation({
+      account_id: accountId,
+      crystallisation_date: body.crystallisation_date,
+      tax_free_cash: taxFreeCash,
+      drawdown_amount: drawdownAmount,
+      pay_from_cash: payFromCash,
+      notes: body.notes || null,
+    });
+    return new Response(JSON.stringify(crystallisation), {
+      status: 201,
+      headers: { "Content-Type": "application/json" },
+    });
+  } catch (err) {
+    return new Response(JSON.stringify({ error: "Failed to record crystallisation", detail: err.message }), { status: 500, headers: { "Content-Type": "application/json" } });
+  }
+});
+
+// DELETE /api/crystallisations/:id — delete a crystallisation and the withdrawal paying its tax-free cash
+crystallisationsRouter.delete("/api/crystallisations/:id", function (request, params) {
+  try {
+    const deleted = deleteCrystallisation(Number(params.id));
+    if (!deleted) {
+      return new Response(JSON.stringify({ error: "Crystallisation not found" }), { status: 404, headers: { "Content-Type": "application/json" } });
+    }
+    return new Response(JSON.stringify({ message: "Crystallisation deleted" }), {
+      status: 200,
+      headers: { "Content-Type": "application/json" },
+    });
+  } catch (err) {
+    return new Response(JSON.stringify({ error: "Failed to delete crystallisation", detail: err.message }), { status: 500, headers: { "Content-Type": "application/json" } });
+  }
+});
+
+/**
+ * @description Handle a SIPP crystallisation API request. Delegates to the crystallisations router.
+ * @param {string} method - HTTP method
+ * @param {string} path - URL pathname
+ * @param {Request} request - The full Request object
+ * @returns {Promise<Response|null>} Response if matched, null otherwise
+ */
+export async function handleCrystallisationsRoute(method, path, request) {
+  return await crystallisationsRouter.match(method, path, request);
+}
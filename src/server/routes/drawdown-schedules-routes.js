@@ -1,6 +1,7 @@
 import { Router } from "../router.js";
-import { createDrawdownSchedule, updateDrawdownSchedule, deleteDrawdownSchedule, getDrawdownScheduleById, getDrawdownSchedulesByAccountId, getOverlappingSchedule } from "../db/drawdown-schedules-db.js";
+import { createDrawdownSchedule, updateDrawdownSchedule, deleteDrawdownSchedule, getDrawdownScheduleById, getDrawdownSchedulesByAccountId, getOverlappingSchedule, getDrawdownAmountOn } from "../db/drawdown-schedules-db.js";
 import { getAccountById } from "../db/accounts-db.js";
+import { getIndexSeriesById } from "../db/index-series-db.js";
 import { validateDrawdownSchedule } from "../validation.js";
 import { previewDrawdowns } from "../services/drawdown-processor.js";
 
@@ -10,6 +11,35 @@ import { previewDrawdowns } from "../services/drawdown-processor.js";
  */
 const drawdownRouter = new Router();
 
+/**
+ * @description Read the escalation fields from a request body. Only the field
+ * that goes with the chosen type is passed on.
+ * @param {Object} body - The parsed request body
+ * @returns {{ escalation_type: string, escalation_rate: number|null, escalation_index_id: number|null, escalation_anniversary: string|null }}
+ *   Escalation fields for the schedule
+ */
+function readEscalationFields(body) {
+  const type = body.escalation_type || "none";
+  return {
+    escalation_type: type,
+    escalation_rate: type === "fixed" ? Number(body.escalation_rate) : null,
+    escalation_index_id: type === "index" ? Number(body.escalation_index_id) : null,
+    escalation_anniversary: type !== "none" ? String(body.escalation_anniversary).trim() : null,
+  };
+}
+
+/**
+ * @description Check that the index series chosen for escalation exists.
+ * @param {Object} body - The parsed request body
+ * @returns {Response|null} A 400 response if the index series is not found, otherwise null
+ */
+function checkEscalationIndex(body) {
+  if (body.escalation_type === "index" && !getIndexSeriesById(Number(body.escalation_index_id))) {
+    return new Response(JSON.stringify({ error: "Validation failed", detail: "Escalation index must be a valid selection" }), { status: 400, headers: { "Content-Type": "application/json" } });
+  }
+  return null;
+}
+
 // GET /api/accounts/:accountId/drawdown-schedules — list schedules for an account
 drawdownRouter.get("/api/accounts/:accountId/drawdown-schedules", function (request, params) {
   try {
@@ -19,7 +49,11 @@ drawdownRouter.get("/api/accounts/:accountId/drawdown-schedules", function (requ
       return new Response(JSON.stringify({ error: "Account not found" }), { status: 404, headers: { "Content-Type": "application/json" } });
     }
 
-    const schedules = getDrawdownSchedulesByAccountId(accountId);
+    // Include the amount paid now, after any escalation
+    const today = new Date().toISOString().slice(0, 10);
+    const schedules = getDrawdownSchedulesByAccountId(accountId).map(function (schedule) {
+      return { ...schedule, current_amount: getDrawdownAmountOn(schedule, today) };
+    });
     return new Response(JSON.stringify(schedules), {
       status: 200,
       headers: { "Content-Type": "application/json" },
@@ -83,6 +117,9 @@ drawdownRouter.post("/api/accounts/:accountId/drawdown-schedules", async functio
     return new Response(JSON.stringify({ error: "Validation failed", detail: errors.join("; ") }), { status: 400, headers: { "Content-Type": "application/json" } });
   }
 
+  const indexError = checkEscalationIndex(body);
+  if (indexError) return indexError;
+
   // Check for overlapping active schedule on this account
   const overlap = getOverlappingSchedule(accountId, body.from_date, body.to_date);
   if (overlap) {
@@ -102,6 +139,10 @@ drawdownRouter.post("/api/accounts/:accountId/drawdown-schedules", async functio
       to_date: body.to_date,
       amount: Number(body.amount),
       notes: body.notes || null,
+      tax_treatment: body.tax_treatment || "none",
+      tax_code: body.tax_code || null,
+      flat_tax_rate: body.flat_tax_rate !== undefined && body.flat_tax_rate !== null && body.flat_tax_rate !== "" ? Number(body.flat_tax_rate) : null,
+      ...readEscalationFields(body),
     });
     return new Response(JSON.stringify(schedule), {
       status: 201,
@@ -126,6 +167,9 @@ drawdownRouter.put("/api/drawdown-schedules/:id", async function (request, param
     return new Response(JSON.stringify({ error: "Validation failed", detail: errors.join("; ") }), { status: 400, headers: { "Content-Type": "application/json" } });
   }
 
+  const indexError = checkEscalationIndex(body);
+  if (indexError) return indexError;
+
   // Check for overlapping active schedule (exclude self)
   const scheduleId = Number(params.id);
   const existing = getDrawdownScheduleById(scheduleId);
@@ -154,6 +198,10 @@ drawdownRouter.put("/api/drawdown-schedules/:id", async function (request, param
       to_date: body.to_date,
       amount: Number(body.amount),
       notes: body.notes || null,
+      tax_treatment: body.tax_treatment || "none",
+      tax_code: body.tax_code || null,
+      flat_tax_rate: body.flat_tax_rate !== undefined && body.flat_tax_rate !== null && body.flat_tax_rate !== "" ? Number(body.flat_tax_rate) : null,
+      ...readEscalationFields(body),
       active: body.active !== undefined ? Number(body.active) : 1,
     });
     if (!schedule) {
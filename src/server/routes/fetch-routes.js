@@ -15,7 +15,8 @@ import { backfillInvestmentPrices, backfillBenchmarkValues, backfillCurrencyRate
 import { syncFromFetchServer, fetchServerStatus, fetchServerLog, triggerServerFetchAll } from "../services/fetch-server-sync.js";
 import { getFetchServerConfig } from "../config.js";
 import { isDemoMode } from "../test-mode.js";
-import { CURRENCY_SCALE_FACTOR } from "../../shared/server-constants.js";
+import { CURRENCY_SCALE_FACTOR, toLocalDateStr } from "../../shared/server-constants.js";
+import { refreshValuationSnapshots } from "../services/portfolio-service.js";
 
 /**
  * @description Sleep for a given number of milliseconds.
@@ -492,6 +493,13 @@ fetchRouter.get("/api/fetch/prices/stream", async function (request) {
               }
             }
 
+            // Materialise today's portfolio valuations from the freshly fetched prices
+            try {
+              refreshValuationSnapshots(toLocalDateStr(new Date()));
+            } catch (err) {
+              console.warn("[FetchService/MS] Failed to refresh valuation snapshots: " + err.message);
+            }
+
             checkpointDatabase();
 
             const total = investments.length;
@@ -0,0 +1,222 @@
+import { Router } from "../router.js";
+import { getUserById } from "../db/users-db.js";
+import { getAccountById } from "../db/accounts-db.js";
+import { getCashTransactionById } from "../db/cash-transactions-db.js";
+import { getAllGifts, getGiftById, getGiftByCashTransactionId, createGift, updateGift, deleteGift } from "../db/gifts-db.js";
+import { buildGiftsSummary, buildGiftsSummaries } from "../services/gifts-service.js";
+import { validateGift } from "../validation.js";
+
+/**
+ * @description Router instance for gifts register API routes.
+ * @type {Router}
+ */
+const giftsRouter = new Router();
+
+/**
+ * @description Read the JSON body of a request.
+ * @param {Request} request - The incoming request
+ * @returns {Promise<{ body: Object|null, error: Response|null }>} The body, or an error response
+ */
+async function readBody(request) {
+  try {
+    return { body: await request.json(), error: null };
+  } catch {
+    return { body: null, error: new Response(JSON.stringify({ error: "Invalid request", detail: "Request body must be valid JSON" }), { status: 400, headers: { "Content-Type": "application/json" } }) };
+  }
+}
+
+/**
+ * @description Check the donor of a gift is an existing family member. Gifts
+ * are made by people, so the Joint household user cannot be the donor.
+ * @param {Object} body - The gift data with user_id
+ * @returns {string|null} Error message, or null if the donor is valid
+ */
+function checkDonor(body) {
+  const user = getUserById(Number(body.user_id));
+  if (!user) return "Donor must be an existing user";
+  if (user.first_name === "Joint") return "Choose the family member making the gift — the Joint household user cannot be the donor";
+  return null;
+}
+
+/**
+ * @description Build the gift fields stored from a request body.
+ * @param {Object} body - The request body
+ * @returns {Object} Gift data for createGift or updateGift
+ */
+function giftData(body) {
+  return {
+    user_id: Number(body.user_id),
+    recipient: String(body.recipient).trim(),
+    gift_date: String(body.gift_date).trim(),
+    amount: Number(body.amount),
+    exemption_type: String(body.exemption_type).trim(),
+    notes: body.notes ? String(body.notes).trim() : null,
+  };
+}
+
+// GET /api/gifts — all gifts in the register, oldest first
+giftsRouter.get("/api/gifts", function () {
+  try {
+    return new Response(JSON.stringify(getAllGifts()), { status: 200, headers: { "Content-Type": "application/json" } });
+  } catch (err) {
+    return new Response(JSON.stringify({ error: "Failed to fetch gifts", detail: err.message }), { status: 500, headers: { "Content-Type": "application/json" } });
+  }
+});
+
+// GET /api/gifts/summary — each family member's gifts with the exemptions, PETs and taper position,
+// and the annual exemption left this tax year
+giftsRouter.get("/api/gifts/summary", function () {
+  try {
+    return new Response(JSON.stringify(buildGiftsSummaries()), {
+      status: 200,
+      headers: { "Content-Type": "application/json", "Cache-Control": "no-cache, no-store" },
+    });
+  } catch (err) {
+    return new Response(JSON.stringify({ error: "Failed to summarise gifts", detail: err.message }), { status: 500, headers: { "Content-Type": "application/json" } });
+  }
+});
+
+// GET /api/gifts/summary/:userId — one family member's gifts summary
+giftsRouter.get("/api/gifts/summary/:userId", function (request, params) {
+  try {
+    const summary = buildGiftsSummary(Number(params.userId));
+    if (!summary) {
+      return new Response(JSON.stringify({ error: "User not found", detail: "No family member with this ID — the Joint household user makes no gifts" }), { status: 404, headers: { "Content-Type": "application/json" } });
+    }
+    return new Response(JSON.stringify(summary), {
+      status: 200,
+      headers: { "Content-Type": "application/json", "Cache-Control": "no-cache, no-store" },
+    });
+  } catch (err) {
+    return new Response(JSON.stringify({ error: "Failed to summarise gifts", detail: err.message }), { status: 500, headers: { "Content-Type": "application/json" } });
+  }
+});
+
+// POST /api/gifts — record a gift
+// Body: { user_id, recipient, gift_date, amount, exemption_type, notes? }
+giftsRouter.post("/api/gifts", async function (request) {
+  const { body, error } = await readBody(request);
+  if (error) return error;
+
+  const errors = validateGift(body);
+  if (errors.length === 0) {
+    const donorError = checkDonor(body);
+    if (donorError) errors.push(donorError);
+  }
+  if (errors.length > 0) {
+    return new Response(JSON.stringify({ error: "Validation failed", detail: errors.join("; ") }), { status: 400, headers: { "Content-Type": "application/json" } });
+  }
+
+  try {
+    return new Response(JSON.stringify(createGift(giftData(body))), { status: 201, headers: { "Content-Type": "application/json" } });
+  } catch (err) {
+    return new Response(JSON.stringify({ error: "Failed to create gift", detail: err.message }), { status: 500, headers: { "Content-Type": "application/json" } });
+  }
+});
+
+// POST /api/gifts/from-transaction/:cashTransactionId — record a gift paid by a withdrawal.
+// Body: { recipient, exemption_type?, user_id?, notes? }
+// The date and amount come from the withdrawal; the donor defaults to the account holder,
+// and must be given for a withdrawal from a Joint account.
+giftsRouter.post("/api/gifts/from-transaction/:cashTransactionId", async function (request, params) {
+  const { body, error } = await readBody(request);
+  if (error) return error;
+
+  const transaction = getCashTransactionById(Number(params.cashTransactionId));
+  if (!transaction) {
+    return new Response(JSON.stringify({ error: "Cash transaction not found" }), { status: 404, headers: { "Content-Type": "application/json" } });
+  }
+  if (transaction.transaction_type !== "withdrawal") {
+    return new Response(JSON.stringify({ error: "Not a withdrawal", detail: "Only a withdrawal can be recorded as a gift" }), { status: 400, headers: { "Content-Type": "application/json" } });
+  }
+  if (getGiftByCashTransactionId(transaction.id)) {
+    return new Response(JSON.stringify({ error: "Already recorded", detail: "This withdrawal is already in the gifts register" }), { status: 409, headers: { "Content-Type": "application/json" } });
+  }
+
+  const account = getAccountById(transaction.account_id);
+  const data = {
+    user_id: body.user_id || account.user_id,
+    recipient: body.recipient,
+    gift_date: transaction.transaction_date,
+    amount: transaction.amount,
+    exemption_type: body.exemption_type || "annual",
+    notes: body.notes !== undefined ? body.notes : transaction.notes,
+  };
+
+  const errors = validateGift(data);
+  if (errors.length === 0) {
+    const donorError = checkDonor(data);
+    if (donorError) errors.push(donorError);
+  }
+  if (errors.length > 0) {
+    return new Response(JSON.stringify({ error: "Validation failed", detail: errors.join("; ") }), { status: 400, headers: { "Content-Type": "application/json" } });
+  }
+
+  try {
+    return new Response(JSON.stringify(createGift({ ...giftData(data), cash_transaction_id: transaction.id })), { status: 201, headers: { "Content-Type": "application/json" } });
+  } catch (err) {
+    return new Response(JSON.stringify({ error: "Failed to create gift", detail: err.message }), { status: 500, headers: { "Content-Type": "application/json" } });
+  }
+});
+
+// GET /api/gifts/:id — a single gift
+giftsRouter.get("/api/gifts/:id", function (request, params) {
+  try {
+    const gift = getGiftById(Number(params.id));
+    if (!gift) {
+      return new Response(JSON.stringify({ error: "Gift not found" }), { status: 404, headers: { "Content-Type": "application/json" } });
+    }
+    return new Response(JSON.stringify(gift), { status: 200, headers: { "Content-Type": "application/json" } });
+  } catch (err) {
+    return new Response(JSON.stringify({ error: "Failed to fetch gift", detail: err.message }), { status: 500, headers: { "Content-Type": "application/json" } });
+  }
+});
+
+// PUT /api/gifts/:id — update a gift; a link to the withdrawal that paid it is kept
+giftsRouter.put("/api/gifts/:id", async function (request, params) {
+  const { body, error } = await readBody(request);
+  if (error) return error;
+
+  const errors = validateGift(body);
+  if (errors.length === 0) {
+    const donorError = checkDonor(body);
+    if (donorError) errors.push(donorError);
+  }
+  if (errors.length > 0) {
+    return new Response(JSON.stringify({ error: "Validation failed", detail: errors.join("; ") }), { status: 400, headers: { "Content-Type": "application/json" } });
+  }
+
+  try {
+    const gift = updateGift(Number(params.id), giftData(body));
+    if (!gift) {
+      return new Response(JSON.stringify({ error: "Gift not found" }), { status: 404, headers: { "Content-Type": "application/json" } });
+    }
+    return new Response(JSON.stringify(gift), { status: 200, headers: { "Content-Type": "application/json" } });
+  } catch (err) {
+    return new Response(JSON.stringify({ error: "Failed to update gift", detail: err.message }), { status: 500, headers: { "Content-Type": "application/json" } });
+  }
+});
+
+// DELETE /api/gifts/:id — remove a gift from the register; the withdrawal that paid it stays
+giftsRouter.delete("/api/gifts/:id", function (request, params) {
+  try {
+    const deleted = deleteGift(Number(params.id));
+    if (!deleted) {
+      return new Response(JSON.stringify({ error: "Gift not found" }), { status: 404, headers: { "Content-Type": "application/json" } });
+    }
+    return new Response(JSON.stringify({ message: "Gift deleted" }), { status: 200, headers: { "Content-Type": "application/json" } });
+  } catch (err) {
+    return new Response(JSON.stringify({ error: "Failed to delete gift", detail: err.message }), { status: 500, headers: { "Content-Type": "application/json" } });
+  }
+});
+
+/**
+ * @description Handle a gifts register API request. Delegates to the gifts router.
+ * @param {string} method - HTTP method
+ * @param {string} path - URL pathname
+ * @param {Request} request - The full Request object
+ * @returns {Promise<Response|null>} Response if matched, null otherwise
+ */
+export async function handleGiftsRoute(method, path, request) {
+  return await giftsRouter.match(method, path, request);
+}
@@ -1,9 +1,10 @@
 import { Router } from "../router.js";
-import { createBuyMovement, createSellMovement, createSplitMovement, createReplacementMovement, getMovementById, getMovementsByHoldingId } from "../db/holding-movements-db.js";
+import { createBuyMovement, createSellMovement, createSplitMovement, createReplacementMovement, updateMovement, deleteMovement, getMovementById, getMovementsByHoldingId } from "../db/holding-movements-db.js";
 import { getInvestmentById } from "../db/investments-db.js";
 import { getHoldingById } from "../db/holdings-db.js";
 import { getAccountById } from "../db/accounts-db.js";
 import { validateHoldingMovement } from "../validation.js";
+import { refreshValuationSnapshots } from "../services/portfolio-service.js";
 
 /**
  * @description Router instance for holding movement API routes.
@@ -11,6 +12,21 @@ import { validateHoldingMovement } from "../validation.js";
  */
 const movementsRouter = new Router();
 
+/**
+ * @description Re-materialise today's valuation snapshot for an account after
+ * a movement is entered, edited or deleted. Earlier snapshots affected by a
+ * backdated change are invalidated by the db layer and rebuilt on next read.
+ * Best-effort — a failure here must not fail the movement itself.
+ * @param {number} accountId - The account ID
+ */
+function refreshAccountValuation(accountId) {
+  try {
+    refreshValuationSnapshots(new Date().toISOString().slice(0, 10), [accountId]);
+  } catch (err) {
+    console.warn("[Movements] Failed to refresh valuation snapshot: " + err.message);
+  }
+}
+
 // GET /api/holdings/:holdingId/movements — list movements for a holding
 movementsRouter.get("/api/holdings/:holdingId/movements", function (request, params) {
   try {
@@ -79,6 +95,7 @@ movementsRouter.post("/api/holdings/:holdingId/movements", async function (reque
       });
 
       const updatedHolding = getHoldingById(holdingId);
+      refreshAccountValuation(updatedHolding.account_id);
       const updatedAccount = getAccountById(updatedHolding.account_id);
 
       return new Response(
@@ -119,6 +136,7 @@ movementsRouter.post("/api/holdings/:holdingId/movements", async function (reque
         notes: body.notes || null,
       });
 
+      refreshAccountValuation(holding.account_id);
       const updatedAccount = getAccountById(holding.account_id);
 
       return new Response(
@@ -198,6 +216,7 @@ movementsRouter.post("/api/holdings/:holdingId/movements", async function (reque
 
     // Return the movement plus updated holding and account for the UI to refresh
     const updatedHolding = getHoldingById(holdingId);
+    refreshAccountValuation(updatedHolding.account_id);
     const updatedAccount = getAccountById(updatedHolding.account_id);
 
     return new Response(
@@ -217,6 +236,123 @@ movementsRouter.post("/api/holdings/:holdingId/movements", async function (reque
   }
 });
 
+/**
+ * @description Map a business rule error from an edit or delete to a 400
+ * response. Returns null for unexpected errors.
+ * @param {Error} err - The error thrown by the db layer
+ * @returns {Response|null} A 400 response, or null if the error is not a known business error
+ */
+function businessErrorResponse(err) {
+  const knownPrefixes = [
+    "Insufficient cash balance",
+    "Insufficient holding quantity",
+    "New quantity must be greater than zero",
+    "Movement date overlaps",
+    "Change would reopen",
+    "Replacement movements cannot",
+    "Movements on a replaced holding",
+  ];
+  const known = knownPrefixes.some(function (prefix) {
+    return err.message.startsWith(prefix);
+  });
+  if (!known) return null;
+  return new Response(JSON.stringify({ error: err.message }), { status: 400, headers: { "Content-Type": "application/json" } });
+}
+
+// PUT /api/holding-movements/:id — edit a buy, sell or adjustment movement
+// Omitted fields keep their current values. The holding's movements are replayed.
+movementsRouter.put("/api/holding-movements/:id", async function (request, params) {
+  let body;
+  try {
+    body = await request.json();
+  } catch {
+    return new Response(JSON.stringify({ error: "Invalid request", detail: "Request body must be valid JSON" }), { status: 400, headers: { "Content-Type": "application/json" } });
+  }
+
+  const id = Number(params.id);
+  const existing = getMovementById(id);
+  if (!existing) {
+    return new Response(JSON.stringify({ error: "Movement not found" }), { status: 404, headers: { "Content-Type": "application/json" } });
+  }
+
+  if (existing.movement_type === "replacement") {
+    return new Response(JSON.stringify({ error: "Replacement movements cannot be edited or deleted" }), { status: 400, headers: { "Content-Type": "application/json" } });
+  }
+
+  // Validate the edited movement as a whole, with the movement type fixed
+  const merged = {
+    movement_type: existing.movement_type,
+    movement_date: body.movement_date !== undefined ? body.movement_date : existing.movement_date,
+    quantity: body.quantity !== undefined ? body.quantity : existing.quantity,
+    total_consideration: body.total_consideration !== undefined ? body.total_consideration : existing.movement_value,
+    deductible_costs: body.deductible_costs !== undefined ? body.deductible_costs : existing.deductible_costs,
+    new_quantity: body.new_quantity !== undefined ? body.new_quantity : existing.quantity,
+    notes: body.notes !== undefined ? body.notes : existing.notes,
+  };
+
+  const errors = validateHoldingMovement(merged);
+  if (errors.length > 0) {
+    return new Response(JSON.stringify({ error: "Validation failed", detail: errors.join("; ") }), { status: 400, headers: { "Content-Type": "application/json" } });
+  }
+
+  try {
+    const movement = updateMovement(id, {
+      movement_date: merged.movement_date,
+      quantity: Number(merged.quantity),
+      total_consideration: Number(merged.total_consideration),
+      deductible_costs: Number(merged.deductible_costs) || 0,
+      new_quantity: Number(merged.new_quantity),
+      notes: merged.notes || null,
+    });
+
+    // Return the movement plus updated holding and account for the UI to refresh
+    const updatedHolding = getHoldingById(movement.holding_id);
+    refreshAccountValuation(updatedHolding.account_id);
+    const updatedAccount = getAccountById(updatedHolding.account_id);
+
+    return new Response(
+      JSON.stringify({
+        movement: movement,
+        holding: updatedHolding,
+        account: updatedAccount,
+      }),
+      { status: 200, headers: { "Content-Type": "application/json" } },
+    );
+  } catch (err) {
+    const response = businessErrorResponse(err);
+    if (response) return response;
+    return new Response(JSON.stringify({ error: "Failed to update movement", detail: err.message }), { status: 500, headers: { "Content-Type": "application/json" } });
+  }
+});
+
+// DELETE /api/holding-movements/:id — delete a buy, sell or adjustment movement
+// Reverses its cash transaction and replays the holding's remaining movements.
+movementsRouter.delete("/api/holding-movements/:id", function (request, params) {
+  try {
+    const id = Number(params.id);
+    const existing = getMovementById(id);
+    if (!existing) {
+      return new Response(JSON.stringify({ error: "Movement not found" }), { status: 404, headers: { "Content-Type": "application/json" } });
+    }
+
+    const holding = getHoldingById(existing.holding_id);
+    deleteMovement(id);
+    refreshAccountValuation(holding.account_id);
+
+    return new Response(
+      JSON.stringify({
+        message: "Movement deleted",
+        account: getAccountById(holding.account_id),
+      }),
+      { status: 200, headers: { "Content-Type": "application/json" } },
+    );
+  } catch (err) {
+    const response = businessErrorResponse(err);
+    if (response) return response;
+    return new Response(JSON.stringify({ error: "Failed to delete movement", detail: err.message }), { status: 500, headers: { "Content-Type": "application/json" } });
+  }
+});
+
 /**
  * @description Handle a holding movement API request. Delegates to the movements router.
  * @param {string} method - HTTP method
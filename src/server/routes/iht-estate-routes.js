@@ -0,0 +1,64 @@
+import { Router } from "../router.js";
+import { getUserById } from "../db/users-db.js";
+import { buildEstateEstimate, buildEstateEstimates } from "../services/iht-service.js";
+
+/**
+ * @description Router instance for inheritance tax estate estimate API routes.
+ * @type {Router}
+ */
+const ihtEstateRouter = new Router();
+
+// GET /api/iht-estate — estate and inheritance tax estimate for each family member
+ihtEstateRouter.get("/api/iht-estate", function () {
+  try {
+    const estimates = buildEstateEstimates(null);
+    return new Response(JSON.stringify(estimates), {
+      status: 200,
+      headers: { "Content-Type": "application/json", "Cache-Control": "no-cache, no-store" },
+    });
+  } catch (err) {
+    return new Response(
+      JSON.stringify({ error: "Failed to estimate inheritance tax", detail: err.message }),
+      { status: 500, headers: { "Content-Type": "application/json" } },
+    );
+  }
+});
+
+// GET /api/iht-estate/:userId — one family member's estate, with the spouse transfer when
+// a spouse or civil partner is recorded
+ihtEstateRouter.get("/api/iht-estate/:userId", function (request, params) {
+  try {
+    const user = getUserById(Number(params.userId));
+    if (!user) {
+      return new Response(JSON.stringify({ error: "User not found" }), { status: 404, headers: { "Content-Type": "application/json" } });
+    }
+    if (user.first_name === "Joint") {
+      return new Response(
+        JSON.stringify({ error: "Invalid user", detail: "The Joint household user has no estate — joint items are shared between the family members" }),
+        { status: 400, headers: { "Content-Type": "application/json" } },
+      );
+    }
+
+    const estimate = buildEstateEstimate(user.id);
+    return new Response(JSON.stringify(estimate), {
+      status: 200,
+      headers: { "Content-Type": "application/json", "Cache-Control": "no-cache, no-store" },
+    });
+  } catch (err) {
+    return new Response(
+      JSON.stringify({ error: "Failed to estimate inheritance tax", detail: err.message }),
+      { status: 500, headers: { "Content-Type": "application/json" } },
+    );
+  }
+});
+
+/**
+ * @description Handle an inheritance tax estate API request. Delegates to the IHT estate router.
+ * @param {string} method - HTTP method
+ * @param {string} path - URL pathname
+ * @param {Request} request - The full Request object
+ * @returns {Promise<Response|null>} Response if matched, null otherwise
+ */
+export async function handleIhtEstateRoute(method, path, request) {
+  return await ihtEstateRouter.match(method, path, request);
+}
@@ -0,0 +1,68 @@
+import { Router } from "../router.js";
+import { getAccountIncome, getHoldingIncome } from "../services/income-service.js";
+import { parseTaxYearLabel } from "../services/tax-year-utils.js";
+
+/**
+ * @description Router instance for dividend and interest income API routes.
+ * @type {Router}
+ */
+const incomeRouter = new Router();
+
+// GET /api/accounts/:accountId/income — income totals by tax year, per holding
+// Optional query param: ?taxYear=2025/2026 to restrict to a single tax year
+incomeRouter.get("/api/accounts/:accountId/income", function (request, params) {
+  try {
+    const url = new URL(request.url);
+    const taxYearParam = url.searchParams.get("taxYear");
+    let taxYearStart = null;
+    if (taxYearParam) {
+      taxYearStart = parseTaxYearLabel(taxYearParam);
+      if (!taxYearStart) {
+        return new Response(
+          JSON.stringify({ error: "Invalid tax year — use YYYY/YYYY (e.g. 2025/2026)" }),
+          { status: 400, headers: { "Content-Type": "application/json" } },
+        );
+      }
+    }
+
+    const income = getAccountIncome(Number(params.accountId), taxYearStart);
+    if (!income) {
+      return new Response(JSON.stringify({ error: "Account not found" }), { status: 404, headers: { "Content-Type": "application/json" } });
+    }
+
+    return new Response(JSON.stringify(income), {
+      status: 200,
+      headers: { "Content-Type": "application/json" },
+    });
+  } catch (err) {
+    return new Response(JSON.stringify({ error: "Failed to fetch income", detail: err.message }), { status: 500, headers: { "Content-Type": "application/json" } });
+  }
+});
+
+// GET /api/holdings/:holdingId/income — income paid by a holding, by tax year
+incomeRouter.get("/api/holdings/:holdingId/income", function (request, params) {
+  try {
+    const income = getHoldingIncome(Number(params.holdingId));
+    if (!income) {
+      return new Response(JSON.stringify({ error: "Holding not found" }), { status: 404, headers: { "Content-Type": "application/json" } });
+    }
+
+    return new Response(JSON.stringify(income), {
+      status: 200,
+      headers: { "Content-Type": "application/json" },
+    });
+  } catch (err) {
+    return new Response(JSON.stringify({ error: "Failed to fetch income", detail: err.message }), { status: 500, headers: { "Content-Type": "application/json" } });
+  }
+});
+
+/**
+ * @description Handle an income API request. Delegates to the income router.
+ * @param {string} method - HTTP method
+ * @param {string} path - URL pathname
+ * @param {Request} request - The full Request object
+ * @returns {Promise<Response|null>} Response if matched, null otherwise
+ */
+export async function handleIncomeRoute(method, path, request) {
+  return await incomeRouter.match(method, path, request);
+}
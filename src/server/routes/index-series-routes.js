@@ -0,0 +1,139 @@
+import { Router } from "../router.js";
+import { getAllIndexSeries, getIndexSeriesById, createIndexSeries, updateIndexSeries, deleteIndexSeries, countIndexSeriesUsage, getIndexValues } from "../db/index-series-db.js";
+import { importIndexCsv } from "../services/index-series-service.js";
+import { validateIndexSeries } from "../validation.js";
+
+/**
+ * @description Router instance for index series API routes.
+ * @type {Router}
+ */
+const indexSeriesRouter = new Router();
+
+/**
+ * @description Read the JSON body of a request.
+ * @param {Request} request - The incoming request
+ * @returns {Promise<{ body: Object|null, error: Response|null }>} The body, or an error response
+ */
+async function readBody(request) {
+  try {
+    return { body: await request.json(), error: null };
+  } catch {
+    return { body: null, error: new Response(JSON.stringify({ error: "Invalid request", detail: "Request body must be valid JSON" }), { status: 400, headers: { "Content-Type": "application/json" } }) };
+  }
+}
+
+// GET /api/index-series — list index series with the range of values held
+indexSeriesRouter.get("/api/index-series", function () {
+  try {
+    return new Response(JSON.stringify(getAllIndexSeries()), { status: 200, headers: { "Content-Type": "application/json" } });
+  } catch (err) {
+    return new Response(JSON.stringify({ error: "Failed to fetch index series", detail: err.message }), { status: 500, headers: { "Content-Type": "application/json" } });
+  }
+});
+
+// POST /api/index-series — create an index series
+// Body: { name: "CPI", description: "ONS CPI index (D7BT)", region: null }
+indexSeriesRouter.post("/api/index-series", async function (request) {
+  const { body, error } = await readBody(request);
+  if (error) return error;
+
+  const errors = validateIndexSeries(body);
+  if (errors.length > 0) {
+    return new Response(JSON.stringify({ error: "Validation failed", detail: errors.join("; ") }), { status: 400, headers: { "Content-Type": "application/json" } });
+  }
+
+  try {
+    return new Response(JSON.stringify(createIndexSeries({ name: String(body.name).trim(), description: body.description ? String(body.description).trim() : null, region: body.region ? String(body.region).trim() : null })), { status: 201, headers: { "Content-Type": "application/json" } });
+  } catch (err) {
+    if (err.message && err.message.includes("UNIQUE")) {
+      return new Response(JSON.stringify({ error: "Validation failed", detail: "An index series with this name already exists" }), { status: 409, headers: { "Content-Type": "application/json" } });
+    }
+    return new Response(JSON.stringify({ error: "Failed to create index series", detail: err.message }), { status: 500, headers: { "Content-Type": "application/json" } });
+  }
+});
+
+// GET /api/index-series/:id/values — all values of an index series, oldest first
+indexSeriesRouter.get("/api/index-series/:id/values", function (request, params) {
+  try {
+    const seriesId = Number(params.id);
+    if (!getIndexSeriesById(seriesId)) {
+      return new Response(JSON.stringify({ error: "Index series not found" }), { status: 404, headers: { "Content-Type": "application/json" } });
+    }
+    return new Response(JSON.stringify(getIndexValues(seriesId)), { status: 200, headers: { "Content-Type": "application/json" } });
+  } catch (err) {
+    return new Response(JSON.stringify({ error: "Failed to fetch index values", detail: err.message }), { status: 500, headers: { "Content-Type": "application/json" } });
+  }
+});
+
+// POST /api/index-series/:id/import — load values from a CSV of dates and index values
+// Body: { csv: "<file contents>" }
+indexSeriesRouter.post("/api/index-series/:id/import", async function (request, params) {
+  const { body, error } = await readBody(request);
+  if (error) return error;
+
+  if (!body || typeof body.csv !== "string" || body.csv.trim() === "") {
+    return new Response(JSON.stringify({ error: "Validation failed", detail: "CSV file contents are required" }), { status: 400, headers: { "Content-Type": "application/json" } });
+  }
+
+  try {
+    const result = importIndexCsv(Number(params.id), body.csv);
+    if (!result) {
+      return new Response(JSON.stringify({ error: "Index series not found" }), { status: 404, headers: { "Content-Type": "application/json" } });
+    }
+    return new Response(JSON.stringify(result), { status: 200, headers: { "Content-Type": "application/json" } });
+  } catch (err) {
+    return new Response(JSON.stringify({ error: "Import failed", detail: err.message }), { status: 400, headers: { "Content-Type": "application/json" } });
+  }
+});
+
+// PUT /api/index-series/:id — rename an index series
+indexSeriesRouter.put("/api/index-series/:id", async function (request, params) {
+  const { body, error } = await readBody(request);
+  if (error) return error;
+
+  const errors = validateIndexSeries(body);
+  if (errors.length > 0) {
+    return new Response(JSON.stringify({ error: "Validation failed", detail: errors.join("; ") }), { status: 400, headers: { "Content-Type": "application/json" } });
+  }
+
+  try {
+    const series = updateIndexSeries(Number(params.id), { name: String(body.name).trim(), description: body.description ? String(body.description).trim() : null, region: body.region ? String(body.region).trim() : null });
+    if (!series) {
+      return new Response(JSON.stringify({ error: "Index series not found" }), { status: 404, headers: { "Content-Type": "application/json" } });
+    }
+    return new Response(JSON.stringify(series), { status: 200, headers: { "Content-Type": "application/json" } });
+  } catch (err) {
+    if (err.message && err.message.includes("UNIQUE")) {
+      return new Response(JSON.stringify({ error: "Validation failed", detail: "An index series with this name already exists" }), { status: 409, headers: { "Content-Type": "application/json" } });
+    }
+    return new Response(JSON.stringify({ error: "Failed to update index series", detail: err.message }), { status: 500, headers: { "Content-Type": "application/json" } });
+  }
+});
+
+// DELETE /api/index-series/:id — delete an index series and its values, if nothing escalates with it
+indexSeriesRouter.delete("/api/index-series/:id", function (request, params) {
+  try {
+    const seriesId = Number(params.id);
+    const usage = countIndexSeriesUsage(seriesId);
+    if (usage > 0) {
+      return new Response(JSON.stringify({ error: "Index series in use", detail: "Used by " + usage + " drawdown schedule(s) or other asset(s)" }), { status: 409, headers: { "Content-Type": "application/json" } });
+    }
+    if (!deleteIndexSeries(seriesId)) {
+      return new Response(JSON.stringify({ error: "Index series not found" }), { status: 404, headers: { "Content-Type": "application/json" } });
+    }
+    return new Response(JSON.stringify({ message: "Index series deleted" }), { status: 200, headers: { "Content-Type": "application/json" } });
+  } catch (err) {
+    return new Response(JSON.stringify({ error: "Failed to delete index series", detail: err.message }), { status: 500, headers: { "Content-Type": "application/json" } });
+  }
+});
+
+/**
+ * @description Handle an index series API request. Delegates to the index series router.
+ * @param {string} method - HTTP method
+ * @param {string} path - URL pathname
+ * @param {Request} request - The full Request object
+ * @returns {Promise<Response|null>} Response if matched, null otherwise
+ */
+export async function handleIndexSeriesRoute(method, path, request) {
+  return await indexSeriesRouter.match(method, path, request);
+}
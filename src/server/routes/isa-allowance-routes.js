@@ -0,0 +1,188 @@
+import { Router } from "../router.js";
+import { getAccountById } from "../db/accounts-db.js";
+import { createIsaTransfer } from "../db/cash-transactions-db.js";
+import { getIsaAllowanceForUser, getIsaAllowanceForAllUsers, getIsaAllowanceHistory, checkIsaSubscription } from "../services/isa-allowance-service.js";
+import { parseTaxYearLabel } from "../services/tax-year-utils.js";
+import { validateIsaTransfer } from "../validation.js";
+
+/**
+ * @description Router instance for ISA allowance API routes.
+ * @type {Router}
+ */
+const isaRouter = new Router();
+
+/**
+ * @description Read the optional ?taxYear= query parameter.
+ * @param {Request} request - The incoming request
+ * @returns {{ startYear: number|null, error: Response|null }} The tax year start, or an error response
+ */
+function readTaxYearParam(request) {
+  const url = new URL(request.url);
+  const taxYearParam = url.searchParams.get("taxYear");
+  if (!taxYearParam) return { startYear: null, error: null };
+
+  const startYear = parseTaxYearLabel(taxYearParam);
+  if (!startYear) {
+    return {
+      startYear: null,
+      error: new Response(
+        JSON.stringify({ error: "Invalid tax year — use YYYY/YYYY (e.g. 2025/2026)" }),
+        { status: 400, headers: { "Content-Type": "application/json" } },
+      ),
+    };
+  }
+  return { startYear: startYear, error: null };
+}
+
+// GET /api/isa-allowance — ISA allowance usage for every user holding an ISA
+// Optional query param: ?taxYear=2025/2026 (defaults to the current tax year)
+isaRouter.get("/api/isa-allowance", function (request) {
+  try {
+    const taxYear = readTaxYearParam(request);
+    if (taxYear.error) return taxYear.error;
+
+    const usage = getIsaAllowanceForAllUsers(null, taxYear.startYear);
+    return new Response(JSON.stringify(usage), {
+      status: 200,
+      headers: { "Content-Type": "application/json" },
+    });
+  } catch (err) {
+    return new Response(
+      JSON.stringify({ error: "Failed to fetch ISA allowance", detail: err.message }),
+      { status: 500, headers: { "Content-Type": "application/json" } },
+    );
+  }
+});
+
+// GET /api/isa-allowance/:userId — a user's ISA allowance usage across all their ISAs
+// Optional query param: ?taxYear=2025/2026 (defaults to the current tax year)
+isaRouter.get("/api/isa-allowance/:userId", function (request, params) {
+  try {
+    const taxYear = readTaxYearParam(request);
+    if (taxYear.error) return taxYear.error;
+
+    const usage = getIsaAllowanceForUser(Number(params.userId), taxYear.startYear);
+    if (!usage) {
+      return new Response(JSON.stringify({ error: "User not found" }), { status: 404, headers: { "Content-Type": "application/json" } });
+    }
+
+    return new Response(JSON.stringify(usage), {
+      status: 200,
+      headers: { "Content-Type": "application/json" },
+    });
+  } catch (err) {
+    return new Response(
+      JSON.stringify({ error: "Failed to fetch ISA allowance", detail: err.message }),
+      { status: 500, headers: { "Content-Type": "application/json" } },
+    );
+  }
+});
+
+// GET /api/isa-allowance/:userId/history — a user's ISA allowance usage for every tax year
+isaRouter.get("/api/isa-allowance/:userId/history", function (request, params) {
+  try {
+    const history = getIsaAllowanceHistory(Number(params.userId));
+    if (!history) {
+      return new Response(JSON.stringify({ error: "User not found" }), { status: 404, headers: { "Content-Type": "application/json" } });
+    }
+
+    return new Response(JSON.stringify(history), {
+      status: 200,
+      headers: { "Content-Type": "application/json" },
+    });
+  } catch (err) {
+    return new Response(
+      JSON.stringify({ error: "Failed to fetch ISA allowance history", detail: err.message }),
+      { status: 500, headers: { "Content-Type": "application/json" } },
+    );
+  }
+});
+
+// POST /api/isa-transfers — record a transfer between ISA providers.
+// Body: { from_account_id?, to_account_id?, transaction_date, amount, isa_transfer, notes?, confirm_over_allowance? }
+// Omit from_account_id or to_account_id when that ISA is held elsewhere.
+isaRouter.post("/api/isa-transfers", async function (request) {
+  let body;
+  try {
+    body = await request.json();
+  } catch {
+    return new Response(JSON.stringify({ error: "Invalid request", detail: "Request body must be valid JSON" }), { status: 400, headers: { "Content-Type": "application/json" } });
+  }
+
+  const errors = validateIsaTransfer(body);
+  if (errors.length > 0) {
+    return new Response(JSON.stringify({ error: "Validation failed", detail: errors.join("; ") }), { status: 400, headers: { "Content-Type": "application/json" } });
+  }
+
+  const fromAccount = body.from_account_id ? getAccountById(Number(body.from_account_id)) : null;
+  const toAccount = body.to_account_id ? getAccountById(Number(body.to_account_id)) : null;
+
+  if ((body.from_account_id && !fromAccount) || (body.to_account_id && !toAccount)) {
+    return new Response(JSON.stringify({ error: "Account not found" }), { status: 404, headers: { "Content-Type": "application/json" } });
+  }
+
+  for (const account of [fromAccount, toAccount]) {
+    if (account && account.account_type !== "isa") {
+      return new Response(JSON.stringify({ error: "Not an ISA account", detail: "Account " + account.account_ref + " is not an ISA" }), { status: 400, headers: { "Content-Type": "application/json" } });
+    }
+  }
+
+  if (fromAccount && toAccount && fromAccount.user_id !== toAccount.user_id) {
+    return new Response(JSON.stringify({ error: "Validation failed", detail: "ISA transfers must be between ISAs held by the same person" }), { status: 400, headers: { "Content-Type": "application/json" } });
+  }
+
+  const amount = Number(body.amount);
+  if (fromAccount && amount > fromAccount.cash_balance) {
+    return new Response(
+      JSON.stringify({
+        error: "Insufficient cash",
+        detail: `Transfer of £${amount.toFixed(2)} exceeds available balance of £${fromAccount.cash_balance.toFixed(2)}`,
+      }),
+      { status: 400, headers: { "Content-Type": "application/json" } },
+    );
+  }
+
+  // A transfer in of this year's subscriptions from an ISA held elsewhere uses
+  // allowance like a deposit, so warn in the same way before recording it
+  if (toAccount && !fromAccount && body.isa_transfer === "current_year" && body.confirm_over_allowance !== true) {
+    const check = checkIsaSubscription(toAccount.id, body.transaction_date, amount);
+    if (check && check.exceeds) {
+      return new Response(
+        JSON.stringify({
+          error: "ISA allowance exceeded",
+          detail: `Transfer of £${amount.toFixed(2)} exceeds the remaining ${check.tax_year} ISA allowance of £${check.remaining.toFixed(2)}`,
+          allowance: check,
+        }),
+        { status: 409, headers: { "Content-Type": "application/json" } },
+      );
+    }
+  }
+
+  try {
+    const result = createIsaTransfer({
+      from_account_id: fromAccount ? fromAccount.id : null,
+      to_account_id: toAccount ? toAccount.id : null,
+      transaction_date: body.transaction_date,
+      amount: amount,
+      isa_transfer: body.isa_transfer,
+      notes: body.notes || null,
+    });
+    return new Response(JSON.stringify(result), {
+      status: 201,
+      headers: { "Content-Type": "application/json" },
+    });
+  } catch (err) {
+    return new Response(JSON.stringify({ error: "Failed to record ISA transfer", detail: err.message }), { status: 500, headers: { "Content-Type": "application/json" } });
+  }
+});
+
+/**
+ * @description Handle an ISA allowance API request. Delegates to the ISA router.
+ * @param {string} method - HTTP method
+ * @param {string} path - URL pathname
+ * @param {Request} request - The full Request object
+ * @returns {Promise<Response|null>} Response if matched, null otherwise
+ */
+export async function handleIsaAllowanceRoute(method, path, request) {
+  return await isaRouter.match(method, path, request);
+}
@@ -5,10 +5,14 @@ import {
   createOtherAsset,
   updateOtherAsset,
   deleteOtherAsset,
+  closeOtherAsset,
   getOtherAssetHistory,
-  getHouseholdAssetsSummary,
+  getOverdueOtherAssets,
+  OTHER_ASSET_CATEGORY_LABELS,
 } from "../db/other-assets-db.js";
-import { validateOtherAsset } from "../validation.js";
+import { getIndexSeriesById } from "../db/index-series-db.js";
+import { getNetWorthSummary, buildNetWorthHistory, buildOtherAssetValueHistory, buildAmortisationSchedule } from "../services/net-worth-service.js";
+import { validateOtherAsset, validateOtherAssetClose } from "../validation.js";
 
 /**
  * @description Router instance for other assets API routes.
@@ -16,6 +20,28 @@ import { validateOtherAsset } from "../validation.js";
  */
 const otherAssetsRouter = new Router();
 
+/**
+ * @description Read the "months" query parameter of a history request.
+ * @param {URL} url - The request URL
+ * @returns {number|null} Whole months from 1 to 120 (12 when omitted), or null if invalid
+ */
+function parseMonthsParam(url) {
+  const monthsParam = url.searchParams.get("months");
+  const months = monthsParam ? Number(monthsParam) : 12;
+  return Number.isInteger(months) && months >= 1 && months <= 120 ? months : null;
+}
+
+/**
+ * @description Build the 400 response for an invalid "months" query parameter.
+ * @returns {Response} The error response
+ */
+function invalidMonthsResponse() {
+  return new Response(
+    JSON.stringify({ error: "Invalid months — use a whole number from 1 to 120" }),
+    { status: 400, headers: { "Content-Type": "application/json" } }
+  );
+}
+
 // GET /api/other-assets — list all other assets (with user info)
 otherAssetsRouter.get("/api/other-assets", function () {
   try {
@@ -32,11 +58,11 @@ otherAssetsRouter.get("/api/other-assets", function () {
   }
 });
 
-// GET /api/other-assets/summary — household assets summary for report block
+// GET /api/other-assets/summary — household assets summary with net worth for report block
 // Must be registered before /:id to avoid "summary" being parsed as an id
 otherAssetsRouter.get("/api/other-assets/summary", function () {
   try {
-    const summary = getHouseholdAssetsSummary();
+    const summary = getNetWorthSummary();
     return new Response(JSON.stringify(summary), {
       status: 200,
       headers: { "Content-Type": "application/json" },
@@ -49,6 +75,78 @@ otherAssetsRouter.get("/api/other-assets/summary", function () {
   }
 });
 
+// GET /api/other-assets/net-worth-history?months=12 — household net worth at each month end and today
+// Must be registered before /:id
+otherAssetsRouter.get("/api/other-assets/net-worth-history", function (request) {
+  const months = parseMonthsParam(new URL(request.url));
+  if (months === null) {
+    return invalidMonthsResponse();
+  }
+
+  try {
+    return new Response(JSON.stringify(buildNetWorthHistory(months)), {
+      status: 200,
+      headers: { "Content-Type": "application/json" },
+    });
+  } catch (err) {
+    return new Response(
+      JSON.stringify({ error: "Failed to build net worth history", detail: err.message }),
+      { status: 500, headers: { "Content-Type": "application/json" } }
+    );
+  }
+});
+
+// GET /api/other-assets/value-history?months=24&category=property,liability — value of each
+// other asset and each category total at each month end and today. Omit category for all.
+// Must be registered before /:id
+otherAssetsRouter.get("/api/other-assets/value-history", function (request) {
+  const url = new URL(request.url);
+  const months = parseMonthsParam(url);
+  if (months === null) {
+    return invalidMonthsResponse();
+  }
+
+  const categoryParam = url.searchParams.get("category");
+  const categories = categoryParam ? categoryParam.split(",").map(function (s) { return s.trim(); }).filter(Boolean) : [];
+  const unknown = categories.filter(function (key) {
+    return !OTHER_ASSET_CATEGORY_LABELS[key];
+  });
+  if (unknown.length > 0) {
+    return new Response(
+      JSON.stringify({ error: "Invalid category", detail: "Unknown category: " + unknown.join(", ") }),
+      { status: 400, headers: { "Content-Type": "application/json" } }
+    );
+  }
+
+  try {
+    return new Response(JSON.stringify(buildOtherAssetValueHistory(months, { categories: categories })), {
+      status: 200,
+      headers: { "Content-Type": "application/json" },
+    });
+  } catch (err) {
+    return new Response(
+      JSON.stringify({ error: "Failed to build value history", detail: err.message }),
+      { status: 500, headers: { "Content-Type": "application/json" } }
+    );
+  }
+});
+
+// GET /api/other-assets/revaluations-due — assets whose revaluation date has passed
+// Must be registered before /:id
+otherAssetsRouter.get("/api/other-assets/revaluations-due", function () {
+  try {
+    return new Response(JSON.stringify(getOverdueOtherAssets()), {
+      status: 200,
+      headers: { "Content-Type": "application/json" },
+    });
+  } catch (err) {
+    return new Response(
+      JSON.stringify({ error: "Failed to fetch revaluations due", detail: err.message }),
+      { status: 500, headers: { "Content-Type": "application/json" } }
+    );
+  }
+});
+
 // GET /api/other-assets/:id — get a single other asset
 otherAssetsRouter.get("/api/other-assets/:id", function (request, params) {
   try {
@@ -96,6 +194,71 @@ otherAssetsRouter.get("/api/other-assets/:id/history", function (request, params
   }
 });
 
+// GET /api/other-assets/:id/value-history?months=24 — value of one asset at each month end and today
+otherAssetsRouter.get("/api/other-assets/:id/value-history", function (request, params) {
+  const months = parseMonthsParam(new URL(request.url));
+  if (months === null) {
+    return invalidMonthsResponse();
+  }
+
+  try {
+    const asset = getOtherAssetById(Number(params.id));
+    if (!asset) {
+      return new Response(
+        JSON.stringify({ error: "Other asset not found" }),
+        { status: 404, headers: { "Content-Type": "application/json" } }
+      );
+    }
+    const history = buildOtherAssetValueHistory(months, { assetId: asset.id });
+    return new Response(JSON.stringify({ id: asset.id, description: asset.description, value_type: asset.value_type, dates: history.dates, values: history.assets[0].values }), {
+      status: 200,
+      headers: { "Content-Type": "application/json" },
+    });
+  } catch (err) {
+    return new Response(
+      JSON.stringify({ error: "Failed to build value history", detail: err.message }),
+      { status: 500, headers: { "Content-Type": "application/json" } }
+    );
+  }
+});
+
+// GET /api/other-assets/:id/amortisation — repayment schedule for a liability from today's balance
+otherAssetsRouter.get("/api/other-assets/:id/amortisation", function (request, params) {
+  try {
+    const asset = getOtherAssetById(Number(params.id));
+    if (!asset) {
+      return new Response(
+        JSON.stringify({ error: "Other asset not found" }),
+        { status: 404, headers: { "Content-Type": "application/json" } }
+      );
+    }
+    if (asset.category !== "liability") {
+      return new Response(
+        JSON.stringify({ error: "Only liabilities have an amortisation schedule" }),
+        { status: 400, headers: { "Content-Type": "application/json" } }
+      );
+    }
+    if (!asset.repayment) {
+      return new Response(
+        JSON.stringify({ error: "Set a monthly repayment to see the amortisation schedule" }),
+        { status: 400, headers: { "Content-Type": "application/json" } }
+      );
+    }
+
+    const today = new Date().toISOString().slice(0, 10);
+    const schedule = buildAmortisationSchedule(asset.value / 10000, asset.interest_rate, asset.repayment / 10000, today);
+    return new Response(JSON.stringify(Object.assign({ id: asset.id, description: asset.description, balance: asset.value / 10000, interest_rate: asset.interest_rate, repayment: asset.repayment / 10000 }, schedule)), {
+      status: 200,
+      headers: { "Content-Type": "application/json" },
+    });
+  } catch (err) {
+    return new Response(
+      JSON.stringify({ error: "Failed to build amortisation schedule", detail: err.message }),
+      { status: 500, headers: { "Content-Type": "application/json" } }
+    );
+  }
+});
+
 // POST /api/other-assets — create a new other asset
 otherAssetsRouter.post("/api/other-assets", async function (request) {
   let body;
@@ -115,6 +278,8 @@ otherAssetsRouter.post("/api/other-assets", async function (request) {
   if (body.frequency) body.frequency = String(body.frequency).trim();
   if (body.notes) body.notes = String(body.notes).trim();
   if (body.executor_reference) body.executor_reference = String(body.executor_reference).trim();
+  if (body.escalation_anniversary) body.escalation_anniversary = String(body.escalation_anniversary).trim();
+  if (body.surveyed_date) body.surveyed_date = String(body.surveyed_date).trim();
 
   const errors = validateOtherAsset(body);
   if (errors.length > 0) {
@@ -124,6 +289,20 @@ otherAssetsRouter.post("/api/other-assets", async function (request) {
     );
   }
 
+  // An index-linked asset must use an index series that exists
+  if (body.escalation_type === "index" && !getIndexSeriesById(Number(body.escalation_index_id))) {
+    return new Response(
+      JSON.stringify({ error: "Validation failed", detail: "Escalation index must be a valid selection" }),
+      { status: 400, headers: { "Content-Type": "application/json" } }
+    );
+  }
+  if (body.valuation_index_id && !getIndexSeriesById(Number(body.valuation_index_id))) {
+    return new Response(
+      JSON.stringify({ error: "Validation failed", detail: "House price index must be a valid selection" }),
+      { status: 400, headers: { "Content-Type": "application/json" } }
+    );
+  }
+
   try {
     const asset = createOtherAsset(body);
     return new Response(JSON.stringify(asset), {
@@ -164,6 +343,8 @@ otherAssetsRouter.put("/api/other-assets/:id", async function (request, params)
   if (body.frequency) body.frequency = String(body.frequency).trim();
   if (body.notes) body.notes = String(body.notes).trim();
   if (body.executor_reference) body.executor_reference = String(body.executor_reference).trim();
+  if (body.escalation_anniversary) body.escalation_anniversary = String(body.escalation_anniversary).trim();
+  if (body.surveyed_date) body.surveyed_date = String(body.surveyed_date).trim();
 
   const errors = validateOtherAsset(body);
   if (errors.length > 0) {
@@ -173,6 +354,20 @@ otherAssetsRouter.put("/api/other-assets/:id", async function (request, params)
     );
   }
 
+  // An index-linked asset must use an index series that exists
+  if (body.escalation_type === "index" && !getIndexSeriesById(Number(body.escalation_index_id))) {
+    return new Response(
+      JSON.stringify({ error: "Validation failed", detail: "Escalation index must be a valid selection" }),
+      { status: 400, headers: { "Content-Type": "application/json" } }
+    );
+  }
+  if (body.valuation_index_id && !getIndexSeriesById(Number(body.valuation_index_id))) {
+    return new Response(
+      JSON.stringify({ error: "Validation failed", detail: "House price index must be a valid selection" }),
+      { status: 400, headers: { "Content-Type": "application/json" } }
+    );
+  }
+
   try {
     const asset = updateOtherAsset(Number(params.id), body);
     if (!asset) {
@@ -199,6 +394,45 @@ otherAssetsRouter.put("/api/other-assets/:id", async function (request, params)
   }
 });
 
+// POST /api/other-assets/:id/close — close a sold or paid-off asset, keeping it in past net worth
+otherAssetsRouter.post("/api/other-assets/:id/close", async function (request, params) {
+  let body;
+  try {
+    body = await request.json();
+  } catch {
+    body = {};
+  }
+
+  const asset = getOtherAssetById(Number(params.id));
+  if (!asset) {
+    return new Response(
+      JSON.stringify({ error: "Other asset not found" }),
+      { status: 404, headers: { "Content-Type": "application/json" } }
+    );
+  }
+
+  const errors = validateOtherAssetClose(body, asset.start_date);
+  if (errors.length > 0) {
+    return new Response(
+      JSON.stringify({ error: "Validation failed", detail: errors.join("; ") }),
+      { status: 400, headers: { "Content-Type": "application/json" } }
+    );
+  }
+
+  try {
+    closeOtherAsset(asset.id, body.closed_date ? String(body.closed_date).trim() : null);
+    return new Response(JSON.stringify({ message: "Other asset closed" }), {
+      status: 200,
+      headers: { "Content-Type": "application/json" },
+    });
+  } catch (err) {
+    return new Response(
+      JSON.stringify({ error: "Failed to close other asset", detail: err.message }),
+      { status: 500, headers: { "Content-Type": "application/json" } }
+    );
+  }
+});
+
 // DELETE /api/other-assets/:id — delete an other asset (cascade deletes history)
 otherAssetsRouter.delete("/api/other-assets/:id", function (request, params) {
   try {
@@ -0,0 +1,111 @@
+import { Router } from "../router.js";
+import { getP60Summary, getP60SummariesForUser, getP60SummariesForAllUsers } from "../services/drawdown-tax-service.js";
+import { parseTaxYearLabel } from "../services/tax-year-utils.js";
+
+/**
+ * @description Router instance for P60 pension income summary API routes.
+ * @type {Router}
+ */
+const p60Router = new Router();
+
+/**
+ * @description Read the optional ?taxYear= query parameter.
+ * @param {Request} request - The incoming request
+ * @returns {{ startYear: number|null, error: Response|null }} The tax year start, or an error response
+ */
+function readTaxYearParam(request) {
+  const url = new URL(request.url);
+  const taxYearParam = url.searchParams.get("taxYear");
+  if (!taxYearParam) return { startYear: null, error: null };
+
+  const startYear = parseTaxYearLabel(taxYearParam);
+  if (!startYear) {
+    return {
+      startYear: null,
+      error: new Response(
+        JSON.stringify({ error: "Invalid tax year — use YYYY/YYYY (e.g. 2025/2026)" }),
+        { status: 400, headers: { "Content-Type": "application/json" } },
+      ),
+    };
+  }
+  return { startYear: startYear, error: null };
+}
+
+// GET /api/p60 — pension paid, tax deducted and net pay for every user drawing from a SIPP
+// Optional query param: ?taxYear=2025/2026 (defaults to the current tax year)
+p60Router.get("/api/p60", function (request) {
+  try {
+    const taxYear = readTaxYearParam(request);
+    if (taxYear.error) return taxYear.error;
+
+    const summaries = getP60SummariesForAllUsers(null, taxYear.startYear);
+    return new Response(JSON.stringify(summaries), {
+      status: 200,
+      headers: { "Content-Type": "application/json" },
+    });
+  } catch (err) {
+    return new Response(
+      JSON.stringify({ error: "Failed to fetch P60 summaries", detail: err.message }),
+      { status: 500, headers: { "Content-Type": "application/json" } },
+    );
+  }
+});
+
+// GET /api/p60/:userId — a user's pension income across all their SIPPs for a tax year
+// Optional query param: ?taxYear=2025/2026 (defaults to the current tax year)
+p60Router.get("/api/p60/:userId", function (request, params) {
+  try {
+    const taxYear = readTaxYearParam(request);
+    if (taxYear.error) return taxYear.error;
+
+    const summary = getP60SummariesForUser(Number(params.userId), taxYear.startYear);
+    if (!summary) {
+      return new Response(JSON.stringify({ error: "User not found" }), { status: 404, headers: { "Content-Type": "application/json" } });
+    }
+
+    return new Response(JSON.stringify(summary), {
+      status: 200,
+      headers: { "Content-Type": "application/json" },
+    });
+  } catch (err) {
+    return new Response(
+      JSON.stringify({ error: "Failed to fetch P60 summary", detail: err.message }),
+      { status: 500, headers: { "Content-Type": "application/json" } },
+    );
+  }
+});
+
+// GET /api/accounts/:accountId/p60 — pension income from one SIPP for a tax year, with each payment
+// Optional query param: ?taxYear=2025/2026 (defaults to the current tax year)
+p60Router.get("/api/accounts/:accountId/p60", function (request, params) {
+  try {
+    const taxYear = readTaxYearParam(request);
+    if (taxYear.error) return taxYear.error;
+
+    const summary = getP60Summary(Number(params.accountId), taxYear.startYear);
+    if (!summary) {
+      return new Response(JSON.stringify({ error: "SIPP account not found" }), { status: 404, headers: { "Content-Type": "application/json" } });
+    }
+
+    return new Response(JSON.stringify(summary), {
+      status: 200,
+      headers: { "Content-Type": "application/json" },
+    });
+  } catch (err) {
+    return new Response(
+      JSON.stringify({ error: "Failed to fetch P60 summary", detail: err.message }),
+      { status: 500, headers: { "Content-Type": "application/json" } },
+    );
+  }
+});
+
+/**
+ * @description Handle a P60 summary API request. Delegates to the P60 router.
+ * @param {string} method - HTTP method
+ * @param {string} path - URL pathname
+ * @param {Request} request - The full Request object
+ * @returns {Promise<Response|null>} Response if matched, null otherwise
+ */
+export async function handleP60Route(method, path, request) {
+  return await p60Router.match(method, path, request);
+}
@@ -0,0 +1,168 @@
+import { Router } from "../router.js";
+import { getAccountById } from "../db/accounts-db.js";
+import { createPensionContribution } from "../db/cash-transactions-db.js";
+import { getPensionAllowanceForUser, getPensionAllowanceForAllUsers, getPensionAllowanceHistory, splitReliefAtSource, FIRST_ANNUAL_ALLOWANCE_YEAR } from "../services/pension-allowance-service.js";
+import { parseTaxYearLabel } from "../services/tax-year-utils.js";
+import { validatePensionContribution } from "../validation.js";
+
+/**
+ * @description Router instance for pension annual allowance API routes.
+ * @type {Router}
+ */
+const pensionRouter = new Router();
+
+/**
+ * @description Read the optional ?taxYear= query parameter.
+ * @param {Request} request - The incoming request
+ * @returns {{ startYear: number|null, error: Response|null }} The tax year start, or an error response
+ */
+function readTaxYearParam(request) {
+  const url = new URL(request.url);
+  const taxYearParam = url.searchParams.get("taxYear");
+  if (!taxYearParam) return { startYear: null, error: null };
+
+  const startYear = parseTaxYearLabel(taxYearParam);
+  if (!startYear) {
+    return {
+      startYear: null,
+      error: new Response(
+        JSON.stringify({ error: "Invalid tax year — use YYYY/YYYY (e.g. 2025/2026)" }),
+        { status: 400, headers: { "Content-Type": "application/json" } },
+      ),
+    };
+  }
+  if (startYear < FIRST_ANNUAL_ALLOWANCE_YEAR) {
+    return {
+      startYear: null,
+      error: new Response(
+        JSON.stringify({ error: "The annual allowance began in the 2006/2007 tax year" }),
+        { status: 400, headers: { "Content-Type": "application/json" } },
+      ),
+    };
+  }
+  return { startYear: startYear, error: null };
+}
+
+// GET /api/pension-allowance — annual allowance usage for every user holding a SIPP
+// Optional query param: ?taxYear=2025/2026 (defaults to the current tax year)
+pensionRouter.get("/api/pension-allowance", function (request) {
+  try {
+    const taxYear = readTaxYearParam(request);
+    if (taxYear.error) return taxYear.error;
+
+    const usage = getPensionAllowanceForAllUsers(taxYear.startYear);
+    return new Response(JSON.stringify(usage), {
+      status: 200,
+      headers: { "Content-Type": "application/json" },
+    });
+  } catch (err) {
+    return new Response(
+      JSON.stringify({ error: "Failed to fetch pension allowance", detail: err.message }),
+      { status: 500, headers: { "Content-Type": "application/json" } },
+    );
+  }
+});
+
+// GET /api/pension-allowance/:userId — a user's annual allowance usage across all their SIPPs
+// Optional query param: ?taxYear=2025/2026 (defaults to the current tax year)
+pensionRouter.get("/api/pension-allowance/:userId", function (request, params) {
+  try {
+    const taxYear = readTaxYearParam(request);
+    if (taxYear.error) return taxYear.error;
+
+    const usage = getPensionAllowanceForUser(Number(params.userId), taxYear.startYear);
+    if (!usage) {
+      return new Response(JSON.stringify({ error: "User not found" }), { status: 404, headers: { "Content-Type": "application/json" } });
+    }
+
+    return new Response(JSON.stringify(usage), {
+      status: 200,
+      headers: { "Content-Type": "application/json" },
+    });
+  } catch (err) {
+    return new Response(
+      JSON.stringify({ error: "Failed to fetch pension allowance", detail: err.message }),
+      { status: 500, headers: { "Content-Type": "application/json" } },
+    );
+  }
+});
+
+// GET /api/pension-allowance/:userId/history — a user's annual allowance usage for every tax year
+pensionRouter.get("/api/pension-allowance/:userId/history", function (request, params) {
+  try {
+    const history = getPensionAllowanceHistory(Number(params.userId));
+    if (!history) {
+      return new Response(JSON.stringify({ error: "User not found" }), { status: 404, headers: { "Content-Type": "application/json" } });
+    }
+
+    return new Response(JSON.stringify(history), {
+      status: 200,
+      headers: { "Content-Type": "application/json" },
+    });
+  } catch (err) {
+    return new Response(
+      JSON.stringify({ error: "Failed to fetch pension allowance history", detail: err.message }),
+      { status: 500, headers: { "Content-Type": "application/json" } },
+    );
+  }
+});
+
+// POST /api/accounts/:accountId/pension-contributions — record a SIPP contribution.
+// Body: { contribution_type, transaction_date, gross_amount, relief_date?, notes? }
+// Personal contributions are paid net of basic rate relief; give relief_date to record
+// the provider's relief-at-source top-up as a separate tax_relief transaction.
+pensionRouter.post("/api/accounts/:accountId/pension-contributions", async function (request, params) {
+  const accountId = Number(params.accountId);
+  const account = getAccountById(accountId);
+  if (!account) {
+    return new Response(JSON.stringify({ error: "Account not found" }), { status: 404, headers: { "Content-Type": "application/json" } });
+  }
+
+  if (account.account_type !== "sipp") {
+    return new Response(JSON.stringify({ error: "Not a SIPP account", detail: "Pension contributions can only be recorded against a SIPP" }), { status: 400, headers: { "Content-Type": "application/json" } });
+  }
+
+  let body;
+  try {
+    body = await request.json();
+  } catch {
+    return new Response(JSON.stringify({ error: "Invalid request", detail: "Request body must be valid JSON" }), { status: 400, headers: { "Content-Type": "application/json" } });
+  }
+
+  const errors = validatePensionContribution(body);
+  if (errors.length > 0) {
+    return new Response(JSON.stringify({ error: "Validation failed", detail: errors.join("; ") }), { status: 400, headers: { "Content-Type": "application/json" } });
+  }
+
+  const grossAmount = Number(body.gross_amount);
+  const netAmount = body.contribution_type === "personal" ? splitReliefAtSource(grossAmount).net : grossAmount;
+
+  try {
+    const result = createPensionContribution({
+      account_id: accountId,
+      contribution_type: body.contribution_type,
+      transaction_date: body.transaction_date,
+      amount: netAmount,
+      gross_amount: grossAmount,
+      relief_date: body.relief_date || null,
+      notes: body.notes || null,
+    });
+    return new Response(JSON.stringify(result), {
+      status: 201,
+      headers: { "Content-Type": "application/json" },
+    });
+  } catch (err) {
+    return new Response(JSON.stringify({ error: "Failed to record pension contribution", detail: err.message }), { status: 500, headers: { "Content-Type": "application/json" } });
+  }
+});
+
+/**
+ * @description Handle a pension allowance API request. Delegates to the pension router.
+ * @param {string} method - HTTP method
+ * @param {string} path - URL pathname
+ * @param {Request} request - The full Request object
+ * @returns {Promise<Response|null>} Response if matched, null otherwise
+ */
+export async function handlePensionAllowanceRoute(method, path, request) {
+  return await pensionRouter.match(method, path, request);
+}
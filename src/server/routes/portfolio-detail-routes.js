@@ -1,5 +1,5 @@
 import { Router } from "../router.js";
-import { getPortfolioDetail } from "../services/portfolio-detail-service.js";
+import { getPortfolioDetail, getPortfolioDetails } from "../services/portfolio-detail-service.js";
 
 /**
  * @description Router instance for portfolio detail API routes.
@@ -14,7 +14,8 @@ const detailRouter = new Router();
  *
  * Query parameters:
  *   - user: User initials (required, case-insensitive)
- *   - account: Account type — isa, sipp, or trading (required, case-insensitive)
+ *   - account: Account type — isa, sipp, or trading — optionally narrowed to a
+ *     provider or account reference, e.g. "isa@hl" or "sipp~3366521" (required, case-insensitive)
  *   - periods: Comma-separated period codes (optional, e.g. "1m,3m,1y,3y")
  */
 detailRouter.get("/api/portfolio/detail", function (request) {
@@ -56,6 +57,52 @@ detailRouter.get("/api/portfolio/detail", function (request) {
   }
 });
 
+/**
+ * @description GET /api/portfolio/detail/accounts?user=BW&account=isa&periods=1m,3m
+ * Returns an array of detail objects, one for each of the user's accounts
+ * matched by the account token — e.g. every ISA when the user holds ISAs
+ * with more than one provider. Takes the same query parameters as
+ * /api/portfolio/detail.
+ */
+detailRouter.get("/api/portfolio/detail/accounts", function (request) {
+  try {
+    const url = new URL(request.url);
+    const userInitials = url.searchParams.get("user");
+    const accountToken = url.searchParams.get("account");
+    const periodsParam = url.searchParams.get("periods");
+
+    if (!userInitials || !accountToken) {
+      return new Response(
+        JSON.stringify({ error: "Missing required parameters: user and account" }),
+        { status: 400, headers: { "Content-Type": "application/json" } },
+      );
+    }
+
+    const periods = periodsParam
+      ? periodsParam.split(",").map(function (s) { return s.trim(); }).filter(Boolean)
+      : [];
+
+    const details = getPortfolioDetails(userInitials, accountToken, periods);
+
+    if (details.length === 0) {
+      return new Response(
+        JSON.stringify({ error: "User or account not found" }),
+        { status: 404, headers: { "Content-Type": "application/json" } },
+      );
+    }
+
+    return new Response(JSON.stringify(details), {
+      status: 200,
+      headers: { "Content-Type": "application/json", "Cache-Control": "no-cache, no-store" },
+    });
+  } catch (err) {
+    return new Response(
+      JSON.stringify({ error: "Failed to load portfolio detail", detail: err.message }),
+      { status: 500, headers: { "Content-Type": "application/json", "Cache-Control": "no-cache, no-store" } },
+    );
+  }
+});
+
 /**
  * @description Handle incoming portfolio detail API requests.
  * @param {string} method - HTTP method
@@ -0,0 +1,129 @@
+import { Router } from "../router.js";
+import { getUserById } from "../db/users-db.js";
+import { getAccountById } from "../db/accounts-db.js";
+import { getInvestmentById } from "../db/investments-db.js";
+import { getAllocationTargets, setAllocationTargets } from "../db/allocation-targets-db.js";
+import { buildRebalancePlan } from "../services/rebalancing-service.js";
+import { validateAllocationTargets } from "../validation.js";
+
+/**
+ * @description Router instance for the target allocation and rebalancing API routes.
+ * @type {Router}
+ */
+const rebalancingRouter = new Router();
+
+/**
+ * @description Resolve the person and optional account a request is for, checking
+ * that both exist and that the account belongs to the person.
+ * @param {*} userIdValue - The user ID from the query string or body
+ * @param {*} accountIdValue - The account ID from the query string or body, if any
+ * @returns {{ userId: number|null, accountId: number|null, error: Response|null }} The scope, or an error response
+ */
+function resolveScope(userIdValue, accountIdValue) {
+  const userId = Number(userIdValue);
+  if (!Number.isInteger(userId) || userId <= 0 || !getUserById(userId)) {
+    return { userId: null, accountId: null, error: new Response(JSON.stringify({ error: "User not found" }), { status: 404, headers: { "Content-Type": "application/json" } }) };
+  }
+
+  if (accountIdValue === undefined || accountIdValue === null || accountIdValue === "") {
+    return { userId: userId, accountId: null, error: null };
+  }
+
+  const accountId = Number(accountIdValue);
+  const account = Number.isInteger(accountId) && accountId > 0 ? getAccountById(accountId) : null;
+  if (!account || account.user_id !== userId) {
+    return { userId: null, accountId: null, error: new Response(JSON.stringify({ error: "Account not found" }), { status: 404, headers: { "Content-Type": "application/json" } }) };
+  }
+  return { userId: userId, accountId: accountId, error: null };
+}
+
+// GET /api/rebalancing/targets?userId=1&accountId=3 — targets for a person (no accountId) or one of their accounts
+rebalancingRouter.get("/api/rebalancing/targets", function (request) {
+  try {
+    const url = new URL(request.url);
+    const scope = resolveScope(url.searchParams.get("userId"), url.searchParams.get("accountId"));
+    if (scope.error) return scope.error;
+
+    return new Response(JSON.stringify(getAllocationTargets(scope.userId, scope.accountId)), { status: 200, headers: { "Content-Type": "application/json" } });
+  } catch (err) {
+    return new Response(JSON.stringify({ error: "Failed to fetch allocation targets", detail: err.message }), { status: 500, headers: { "Content-Type": "application/json" } });
+  }
+});
+
+// PUT /api/rebalancing/targets — replace the targets for a person or one of their accounts
+// Body: { user_id: 1, account_id: 3 | null, targets: [{ investment_id: 5, target_percent: 40 }, ...] }
+//   or targets by allocation tag: [{ allocation_tag: "UK Equity", target_percent: 40 }, ...]
+rebalancingRouter.put("/api/rebalancing/targets", async function (request) {
+  let body;
+  try {
+    body = await request.json();
+  } catch {
+    return new Response(JSON.stringify({ error: "Invalid request", detail: "Request body must be valid JSON" }), { status: 400, headers: { "Content-Type": "application/json" } });
+  }
+
+  const errors = validateAllocationTargets(body || {});
+  if (errors.length > 0) {
+    return new Response(JSON.stringify({ error: "Validation failed", detail: errors.join("; ") }), { status: 400, headers: { "Content-Type": "application/json" } });
+  }
+
+  const scope = resolveScope(body.user_id, body.account_id);
+  if (scope.error) return scope.error;
+
+  for (const target of body.targets) {
+    if (target.investment_id && !getInvestmentById(Number(target.investment_id))) {
+      return new Response(JSON.stringify({ error: "Validation failed", detail: "Investment " + target.investment_id + " not found" }), { status: 400, headers: { "Content-Type": "application/json" } });
+    }
+  }
+
+  try {
+    return new Response(JSON.stringify(setAllocationTargets(scope.userId, scope.accountId, body.targets)), { status: 200, headers: { "Content-Type": "application/json" } });
+  } catch (err) {
+    return new Response(JSON.stringify({ error: "Failed to save allocation targets", detail: err.message }), { status: 500, headers: { "Content-Type": "application/json" } });
+  }
+});
+
+// GET /api/rebalancing/plan?userId=1&accountId=3 — compare holdings with targets and propose trades
+// Optional query params: ?tolerance=5&minimumTrade=250 (default from the rebalancing config)
+rebalancingRouter.get("/api/rebalancing/plan", function (request) {
+  try {
+    const url = new URL(request.url);
+    const scope = resolveScope(url.searchParams.get("userId"), url.searchParams.get("accountId"));
+    if (scope.error) return scope.error;
+
+    const options = {};
+    const tolerance = url.searchParams.get("tolerance");
+    if (tolerance !== null && tolerance !== "") {
+      options.tolerance_percent = Number(tolerance);
+      if (isNaN(options.tolerance_percent) || options.tolerance_percent < 0 || options.tolerance_percent > 50) {
+        return new Response(JSON.stringify({ error: "Invalid tolerance — use a percentage from 0 to 50" }), { status: 400, headers: { "Content-Type": "application/json" } });
+      }
+    }
+
+    const minimumTrade = url.searchParams.get("minimumTrade");
+    if (minimumTrade !== null && minimumTrade !== "") {
+      options.minimum_trade = Number(minimumTrade);
+      if (isNaN(options.minimum_trade) || options.minimum_trade < 0) {
+        return new Response(JSON.stringify({ error: "Invalid minimum trade — use an amount of zero or more" }), { status: 400, headers: { "Content-Type": "application/json" } });
+      }
+    }
+
+    const plan = buildRebalancePlan(scope.userId, scope.accountId, options);
+    if (!plan) {
+      return new Response(JSON.stringify({ error: "Account not found" }), { status: 404, headers: { "Content-Type": "application/json" } });
+    }
+    return new Response(JSON.stringify(plan), { status: 200, headers: { "Content-Type": "application/json" } });
+  } catch (err) {
+    return new Response(JSON.stringify({ error: "Failed to build rebalancing plan", detail: err.message }), { status: 500, headers: { "Content-Type": "application/json" } });
+  }
+});
+
+/**
+ * @description Handle a rebalancing API request. Delegates to the rebalancing router.
+ * @param {string} method - HTTP method
+ * @param {string} path - URL pathname
+ * @param {Request} request - The full Request object
+ * @returns {Promise<Response|null>} Response if matched, null otherwise
+ */
+export async function handleRebalancingRoute(method, path, request) {
+  return await rebalancingRouter.match(method, path, request);
+}
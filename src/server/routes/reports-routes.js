@@ -9,6 +9,12 @@ import { generatePortfolioDetailPdf } from "../reports/pdf-portfolio-detail.js";
 import { generateCompositePdf } from "../reports/pdf-compositor.js";
 import { generateChartPdf, generateChartGroupPdf } from "../reports/pdf-chart.js";
 import { generatePortfolioValueChartPdf } from "../reports/pdf-portfolio-value-chart.js";
+import { generateIsaAllowancePdf } from "../reports/pdf-isa-allowance.js";
+import { generateP60SummaryPdf } from "../reports/pdf-p60-summary.js";
+import { generateRetirementProjectionPdf } from "../reports/pdf-retirement-projection.js";
+import { generateCorrelationMatrixPdf } from "../reports/pdf-correlation-matrix.js";
+import { generateOtherAssetsChartPdf } from "../reports/pdf-other-assets-chart.js";
+import { generateIhtEstatePdf } from "../reports/pdf-iht-estate.js";
 import { isTestMode } from "../test-mode.js";
 
 /**
@@ -386,6 +392,172 @@ reportsRouter.get("/api/reports/pdf/portfolio-detail", async function (request)
   }
 });
 
+// GET /api/reports/pdf/isa-allowance — generate ISA allowance PDF.
+// Accepts optional "params" query parameter as a comma-separated list of
+// user initials (e.g. "AW,BW"); omit for every user holding an ISA. Tokens
+// like USER1 are resolved from the report_params table inside the generator.
+// Optional "taxYear" query parameter (e.g. "2025/2026") defaults to the current tax year.
+// Must be registered before /api/reports/:id so "pdf" is not matched as an :id param
+reportsRouter.get("/api/reports/pdf/isa-allowance", async function (request) {
+  try {
+    const url = new URL(request.url);
+    const paramsStr = url.searchParams.get("params");
+    const taxYear = url.searchParams.get("taxYear") || null;
+    let params = [];
+    if (paramsStr) {
+      params = paramsStr.split(",").map(function (s) { return s.trim(); }).filter(Boolean);
+    }
+
+    const pdfBytes = await generateIsaAllowancePdf(params, taxYear);
+    return new Response(pdfBytes, {
+      status: 200,
+      headers: {
+        "Content-Type": "application/pdf",
+        "Content-Disposition": 'inline; filename="isa-allowance.pdf"',
+      },
+    });
+  } catch (err) {
+    return new Response(
+      JSON.stringify({ error: "Failed to generate PDF", detail: err.message }),
+      { status: 500, headers: { "Content-Type": "application/json" } },
+    );
+  }
+});
+
+// GET /api/reports/pdf/p60-summary — generate the pension income (P60) summary PDF.
+// Accepts optional "params" query parameter as a comma-separated list of
+// user initials (e.g. "AW,BW"); omit for everyone drawing from a SIPP. Tokens
+// like USER1 are resolved from the report_params table inside the generator.
+// Optional "taxYear" query parameter (e.g. "2025/2026") defaults to the current tax year.
+// Must be registered before /api/reports/:id so "pdf" is not matched as an :id param
+reportsRouter.get("/api/reports/pdf/p60-summary", async function (request) {
+  try {
+    const url = new URL(request.url);
+    const paramsStr = url.searchParams.get("params");
+    const taxYear = url.searchParams.get("taxYear") || null;
+    let params = [];
+    if (paramsStr) {
+      params = paramsStr.split(",").map(function (s) { return s.trim(); }).filter(Boolean);
+    }
+
+    const pdfBytes = await generateP60SummaryPdf(params, taxYear);
+    return new Response(pdfBytes, {
+      status: 200,
+      headers: {
+        "Content-Type": "application/pdf",
+        "Content-Disposition": 'inline; filename="p60-summary.pdf"',
+      },
+    });
+  } catch (err) {
+    return new Response(
+      JSON.stringify({ error: "Failed to generate PDF", detail: err.message }),
+      { status: 500, headers: { "Content-Type": "application/json" } },
+    );
+  }
+});
+
+// GET /api/reports/pdf/retirement-projection — generate the retirement projection PDF.
+// Accepts optional "params" query parameter as a comma-separated list of
+// user initials (e.g. "AW,BW"); omit for everyone with a SIPP. Tokens like
+// USER1 are resolved from the report_params table inside the generator.
+// Optional "years", "return" and "volatility" query parameters override the
+// projection period and the return and volatility estimated from price history.
+// Must be registered before /api/reports/:id so "pdf" is not matched as an :id param
+reportsRouter.get("/api/reports/pdf/retirement-projection", async function (request) {
+  try {
+    const url = new URL(request.url);
+    const paramsStr = url.searchParams.get("params");
+    let params = [];
+    if (paramsStr) {
+      params = paramsStr.split(",").map(function (s) { return s.trim(); }).filter(Boolean);
+    }
+    const options = {
+      years: url.searchParams.get("years") || "",
+      expectedReturn: url.searchParams.get("return") || "",
+      volatility: url.searchParams.get("volatility") || "",
+    };
+
+    const pdfBytes = await generateRetirementProjectionPdf(params, options);
+    return new Response(pdfBytes, {
+      status: 200,
+      headers: {
+        "Content-Type": "application/pdf",
+        "Content-Disposition": 'inline; filename="retirement-projection.pdf"',
+      },
+    });
+  } catch (err) {
+    return new Response(
+      JSON.stringify({ error: "Failed to generate PDF", detail: err.message }),
+      { status: 500, headers: { "Content-Type": "application/json" } },
+    );
+  }
+});
+
+// GET /api/reports/pdf/iht-estate — generate the inheritance tax estate estimate PDF.
+// Accepts optional "params" query parameter as a comma-separated list of
+// user initials (e.g. "AW,BW"); omit for every family member. Tokens like
+// USER1 are resolved from the report_params table inside the generator.
+// Must be registered before /api/reports/:id so "pdf" is not matched as an :id param
+reportsRouter.get("/api/reports/pdf/iht-estate", async function (request) {
+  try {
+    const url = new URL(request.url);
+    const paramsStr = url.searchParams.get("params");
+    let params = [];
+    if (paramsStr) {
+      params = paramsStr.split(",").map(function (s) { return s.trim(); }).filter(Boolean);
+    }
+
+    const pdfBytes = await generateIhtEstatePdf(params);
+    return new Response(pdfBytes, {
+      status: 200,
+      headers: {
+        "Content-Type": "application/pdf",
+        "Content-Disposition": 'inline; filename="iht-estate.pdf"',
+      },
+    });
+  } catch (err) {
+    return new Response(
+      JSON.stringify({ error: "Failed to generate PDF", detail: err.message }),
+      { status: 500, headers: { "Content-Type": "application/json" } },
+    );
+  }
+});
+
+// GET /api/reports/pdf/correlation-matrix — generate the correlation matrix PDF.
+// Accepts optional "params" query parameter as a comma-separated list of user
+// initials and "bm:DESCRIPTION" benchmarks (e.g. "AW,BW,bm:FTSE 100"); omit the
+// initials for everyone. Tokens like USER1 are resolved inside the generator.
+// Optional "period" (default 1y) and "holdings" (current, historic or all).
+// Must be registered before /api/reports/:id so "pdf" is not matched as an :id param
+reportsRouter.get("/api/reports/pdf/correlation-matrix", async function (request) {
+  try {
+    const url = new URL(request.url);
+    const paramsStr = url.searchParams.get("params");
+    let params = [];
+    if (paramsStr) {
+      params = paramsStr.split(",").map(function (s) { return s.trim(); }).filter(Boolean);
+    }
+    const options = {
+      period: url.searchParams.get("period") || "",
+      holdings: url.searchParams.get("holdings") || "",
+    };
+
+    const pdfBytes = await generateCorrelationMatrixPdf(params, options);
+    return new Response(pdfBytes, {
+      status: 200,
+      headers: {
+        "Content-Type": "application/pdf",
+        "Content-Disposition": 'inline; filename="correlation-matrix.pdf"',
+      },
+    });
+  } catch (err) {
+    return new Response(
+      JSON.stringify({ error: "Failed to generate PDF", detail: err.message }),
+      { status: 500, headers: { "Content-Type": "application/json" } },
+    );
+  }
+});
+
 // GET /api/reports/pdf/composite — generate a composite PDF from a report
 // definition that contains a "blocks" array. Accepts the report ID as a
 // query parameter (e.g. /api/reports/pdf/composite?id=weekly_pdf).
@@ -527,6 +699,50 @@ reportsRouter.get("/api/reports/pdf/portfolio-value-chart", async function (requ
   }
 });
 
+// GET /api/reports/pdf/other-assets-chart — generate an other assets value chart PDF.
+// Plots category totals (or each asset) at each month end from the change history.
+// Accepts the report ID as a query parameter (e.g. ?id=property_values).
+reportsRouter.get("/api/reports/pdf/other-assets-chart", async function (request) {
+  try {
+    const url = new URL(request.url);
+    const reportId = url.searchParams.get("id");
+
+    if (!reportId) {
+      return new Response(
+        JSON.stringify({ error: "Report ID is required (use ?id=...)" }),
+        { status: 400, headers: { "Content-Type": "application/json" } },
+      );
+    }
+
+    const reports = loadReportDefinitions();
+    const report = reports.find(function (r) {
+      return r.id === reportId;
+    });
+
+    if (!report) {
+      return new Response(
+        JSON.stringify({ error: "Report not found: " + reportId }),
+        { status: 404, headers: { "Content-Type": "application/json" } },
+      );
+    }
+
+    const pdfBytes = await generateOtherAssetsChartPdf(report);
+    const filename = (report.id || "other-assets-chart").replace(/[^a-z0-9_-]/gi, "-") + ".pdf";
+    return new Response(pdfBytes, {
+      status: 200,
+      headers: {
+        "Content-Type": "application/pdf",
+        "Content-Disposition": 'inline; filename="' + filename + '"',
+      },
+    });
+  } catch (err) {
+    return new Response(
+      JSON.stringify({ error: "Failed to generate other assets chart PDF", detail: err.message }),
+      { status: 500, headers: { "Content-Type": "application/json" } },
+    );
+  }
+});
+
 // GET /api/reports/pdf/chart-group — generate a multi-chart PDF (1-4 charts on one page).
 // Accepts the report ID as a query parameter (e.g. /api/reports/pdf/chart-group?id=multi_charts).
 // Layout depends on chart count: 1→landscape, 2→portrait stacked, 3-4→landscape 2×2 grid.
@@ -0,0 +1,117 @@
+import { Router } from "../router.js";
+import { projectRetirement } from "../services/retirement-projection-service.js";
+
+/**
+ * @description Router instance for retirement projection API routes.
+ * @type {Router}
+ */
+const retirementProjectionRouter = new Router();
+
+/**
+ * @description Read the optional projection query parameters: ?years=,
+ * ?simulations=, ?return= and ?volatility= (percent a year).
+ * @param {Request} request - The incoming request
+ * @returns {{ options: Object, error: Response|null }} Projection options, or an error response
+ */
+function readProjectionParams(request) {
+  const url = new URL(request.url);
+  const options = {};
+
+  /**
+   * @description Build a 400 response for an invalid query parameter.
+   * @param {string} message - The error message
+   * @returns {{ options: Object, error: Response }} The error result
+   */
+  function invalid(message) {
+    return { options: options, error: new Response(JSON.stringify({ error: message }), { status: 400, headers: { "Content-Type": "application/json" } }) };
+  }
+
+  const years = url.searchParams.get("years");
+  if (years !== null) {
+    options.years = Number(years);
+    if (!Number.isInteger(options.years) || options.years < 1 || options.years > 60) {
+      return invalid("Invalid years — use a whole number from 1 to 60");
+    }
+  }
+
+  const simulations = url.searchParams.get("simulations");
+  if (simulations !== null) {
+    options.simulations = Number(simulations);
+    if (!Number.isInteger(options.simulations) || options.simulations < 100 || options.simulations > 10000) {
+      return invalid("Invalid simulations — use a whole number from 100 to 10000");
+    }
+  }
+
+  const expectedReturn = url.searchParams.get("return");
+  if (expectedReturn !== null && expectedReturn !== "") {
+    options.expected_return = Number(expectedReturn);
+    if (isNaN(options.expected_return) || options.expected_return < -10 || options.expected_return > 20) {
+      return invalid("Invalid return — use a percentage from -10 to 20");
+    }
+  }
+
+  const volatility = url.searchParams.get("volatility");
+  if (volatility !== null && volatility !== "") {
+    options.volatility = Number(volatility);
+    if (isNaN(options.volatility) || options.volatility < 0 || options.volatility > 50) {
+      return invalid("Invalid volatility — use a percentage from 0 to 50");
+    }
+  }
+
+  return { options: options, error: null };
+}
+
+// GET /api/retirement-projection — how long the household's SIPPs last at current drawdowns
+// Optional query params: ?years=40&simulations=1000&return=5&volatility=12
+retirementProjectionRouter.get("/api/retirement-projection", function (request) {
+  try {
+    const params = readProjectionParams(request);
+    if (params.error) return params.error;
+
+    const projection = projectRetirement(null, params.options);
+    return new Response(JSON.stringify(projection), {
+      status: 200,
+      headers: { "Content-Type": "application/json", "Cache-Control": "no-cache, no-store" },
+    });
+  } catch (err) {
+    return new Response(
+      JSON.stringify({ error: "Failed to project retirement income", detail: err.message }),
+      { status: 500, headers: { "Content-Type": "application/json" } },
+    );
+  }
+});
+
+// GET /api/retirement-projection/:userId — a person's SIPPs, with depletion given as ages
+// when their date of birth is recorded. Same optional query params as above.
+retirementProjectionRouter.get("/api/retirement-projection/:userId", function (request, params) {
+  try {
+    const query = readProjectionParams(request);
+    if (query.error) return query.error;
+
+    const projection = projectRetirement(Number(params.userId), query.options);
+    if (!projection) {
+      return new Response(JSON.stringify({ error: "User not found" }), { status: 404, headers: { "Content-Type": "application/json" } });
+    }
+
+    return new Response(JSON.stringify(projection), {
+      status: 200,
+      headers: { "Content-Type": "application/json", "Cache-Control": "no-cache, no-store" },
+    });
+  } catch (err) {
+    return new Response(
+      JSON.stringify({ error: "Failed to project retirement income", detail: err.message }),
+      { status: 500, headers: { "Content-Type": "application/json" } },
+    );
+  }
+});
+
+/**
+ * @description Handle a retirement projection API request. Delegates to the retirement projection router.
+ * @param {string} method - HTTP method
+ * @param {string} path - URL pathname
+ * @param {Request} request - The full Request object
+ * @returns {Promise<Response|null>} Response if matched, null otherwise
+ */
+export async function handleRetirementProjectionRoute(method, path, request) {
+  return await retirementProjectionRouter.match(method, path, request);
+}
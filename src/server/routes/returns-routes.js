@@ -0,0 +1,64 @@
+import { Router } from "../router.js";
+import { getPortfolioReturns } from "../services/returns-service.js";
+
+/**
+ * @description Router instance for portfolio returns API routes.
+ * @type {Router}
+ */
+const returnsRouter = new Router();
+
+// GET /api/returns — XIRR and TWR for a portfolio param over one or more periods
+// Query params: ?portfolio=BW:isa+sipp (required) &periods=1y,3y (default 1y)
+returnsRouter.get("/api/returns", function (request) {
+  try {
+    const url = new URL(request.url);
+    // An unencoded "+" in the query string arrives as a space
+    const portfolioParam = (url.searchParams.get("portfolio") || "").trim().replace(/\s+/g, "+");
+    if (!portfolioParam) {
+      return new Response(
+        JSON.stringify({ error: "Portfolio is required — use USER:account_types (e.g. BW:isa+sipp)" }),
+        { status: 400, headers: { "Content-Type": "application/json" } },
+      );
+    }
+
+    const periods = (url.searchParams.get("periods") || "1y").split(",").map(function (p) {
+      return p.trim();
+    }).filter(Boolean);
+
+    const returns = getPortfolioReturns(portfolioParam, periods);
+    if (!returns) {
+      return new Response(
+        JSON.stringify({ error: "Portfolio not found", detail: "No user matches " + portfolioParam }),
+        { status: 404, headers: { "Content-Type": "application/json" } },
+      );
+    }
+
+    if (returns.periods.length === 0) {
+      return new Response(
+        JSON.stringify({ error: "Invalid periods", detail: "Use period codes such as 1m, 3m, 1y, 3y" }),
+        { status: 400, headers: { "Content-Type": "application/json" } },
+      );
+    }
+
+    return new Response(JSON.stringify(returns), {
+      status: 200,
+      headers: { "Content-Type": "application/json" },
+    });
+  } catch (err) {
+    return new Response(
+      JSON.stringify({ error: "Failed to calculate returns", detail: err.message }),
+      { status: 500, headers: { "Content-Type": "application/json" } },
+    );
+  }
+});
+
+/**
+ * @description Handle a portfolio returns API request. Delegates to the returns router.
+ * @param {string} method - HTTP method
+ * @param {string} path - URL pathname
+ * @param {Request} request - The full Request object
+ * @returns {Promise<Response|null>} Response if matched, null otherwise
+ */
+export async function handleReturnsRoute(method, path, request) {
+  return await returnsRouter.match(method, path, request);
+}
@@ -10,6 +10,22 @@ import { isTestMode } from "../test-mode.js";
  */
 const usersRouter = new Router();
 
+/**
+ * @description Check the spouse or civil partner chosen for a user: it must be
+ * another existing user, and not the Joint household user.
+ * @param {Object} body - The user data
+ * @param {number|null} userId - The user being updated, or null when creating
+ * @returns {string|null} An error message, or null when the spouse is valid or not set
+ */
+function checkSpouse(body, userId) {
+  if (!body.spouse_user_id) return null;
+  const spouse = getUserById(Number(body.spouse_user_id));
+  if (!spouse) return "Spouse must be an existing user";
+  if (spouse.id === userId) return "A user cannot be their own spouse";
+  if (spouse.first_name === "Joint") return "The Joint household user cannot be a spouse";
+  return null;
+}
+
 // GET /api/users — list all users
 usersRouter.get("/api/users", function () {
   try {
@@ -49,6 +65,10 @@ usersRouter.post("/api/users", async function (request) {
   }
 
   const errors = validateUser(body);
+  if (errors.length === 0) {
+    const spouseError = checkSpouse(body, null);
+    if (spouseError) errors.push(spouseError);
+  }
   if (errors.length > 0) {
     return new Response(JSON.stringify({ error: "Validation failed", detail: errors.join("; ") }), { status: 400, headers: { "Content-Type": "application/json" } });
   }
@@ -74,6 +94,10 @@ usersRouter.put("/api/users/:id", async function (request, params) {
   }
 
   const errors = validateUser(body);
+  if (errors.length === 0) {
+    const spouseError = checkSpouse(body, Number(params.id));
+    if (spouseError) errors.push(spouseError);
+  }
   if (errors.length > 0) {
     return new Response(JSON.stringify({ error: "Validation failed", detail: errors.join("; ") }), { status: 400, headers: { "Content-Type": "application/json" } });
   }
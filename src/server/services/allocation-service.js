@@ -0,0 +1,263 @@
+import { getDatabase } from "../db/connection.js";
+import { getPortfolioSummary, getPortfolioSummaryAtDate } from "./portfolio-service.js";
+
+/**
+ * @description Dimensions the allocation can be broken down by: investment
+ * type, currency exposure and the user-assigned allocation tag.
+ * @type {string[]}
+ */
+export const ALLOCATION_DIMENSIONS = ["type", "currency", "tag"];
+
+/**
+ * @description Label used for uninvested cash in every dimension except currency,
+ * where cash counts as GBP exposure.
+ * @type {string}
+ */
+const CASH_LABEL = "Cash";
+
+/**
+ * @description Label used for investments that have no allocation tag.
+ * @type {string}
+ */
+const UNTAGGED_LABEL = "Untagged";
+
+/**
+ * @description Round a value to 2 decimal places (pence precision).
+ * @param {number} value - The value to round
+ * @returns {number} Value rounded to 2 decimal places
+ */
+function roundToPence(value) {
+  return Math.round(value * 100) / 100;
+}
+
+/**
+ * @description Load the type, currency and allocation tag of every investment,
+ * keyed by investment ID. Tags are current attributes, so historic allocations
+ * are grouped by today's tags. Untagged investments carry the tag "Untagged".
+ * @returns {Object} Map of investment ID to { type, currency, tag }
+ */
+export function loadInvestmentAttributes() {
+  const db = getDatabase();
+  const rows = db
+    .query(
+      `SELECT i.id, i.allocation_tag, c.code AS currency_code, it.description AS type_description
+       FROM investments i
+       JOIN currencies c ON i.currencies_id = c.id
+       JOIN investment_types it ON i.investment_type_id = it.id`,
+    )
+    .all();
+
+  const map = {};
+  for (const row of rows) {
+    map[row.id] = {
+      type: row.type_description,
+      currency: row.currency_code,
+      tag: row.allocation_tag || UNTAGGED_LABEL,
+    };
+  }
+  return map;
+}
+
+/**
+ * @description Decide whether an account falls within the allocation scope.
+ * @param {Object} account - Account summary from the portfolio service
+ * @param {Object} scope - The allocation scope
+ * @returns {boolean} True if the account is included
+ */
+function accountInScope(account, scope) {
+  if (scope.accountId && account.id !== scope.accountId) return false;
+  if (scope.accountTypes && scope.accountTypes.length > 0 && scope.accountTypes.indexOf(account.account_type) === -1) return false;
+  return true;
+}
+
+/**
+ * @description Add a value to the running totals of each dimension.
+ * @param {Object} buckets - Map of dimension to { label: value }
+ * @param {Object} labels - The label for each dimension ({ type, currency, tag })
+ * @param {number} value - The GBP value to add
+ */
+function addToBuckets(buckets, labels, value) {
+  for (const dimension of ALLOCATION_DIMENSIONS) {
+    const label = labels[dimension];
+    buckets[dimension][label] = (buckets[dimension][label] || 0) + value;
+  }
+}
+
+/**
+ * @description Total the holdings and cash of the in-scope accounts across the
+ * given portfolio summaries, grouped by each dimension.
+ * @param {Object[]} summaries - Portfolio summaries, one per user
+ * @param {Object} scope - The allocation scope
+ * @param {Object} attributes - Investment attributes from loadInvestmentAttributes
+ * @returns {{ buckets: Object, investments: number, cash: number, cash_available: boolean }} The grouped totals
+ */
+function totalSummaries(summaries, scope, attributes) {
+  const buckets = { type: {}, currency: {}, tag: {} };
+  let investments = 0;
+  let cash = 0;
+  let cashAvailable = true;
+
+  for (const summary of summaries) {
+    for (const account of summary.accounts) {
+      if (!accountInScope(account, scope)) continue;
+
+      for (const holding of account.holdings) {
+        if (!holding.value_gbp) continue;
+        const attrs = attributes[holding.investment_id] || { type: "Other", currency: holding.currency_code, tag: UNTAGGED_LABEL };
+        addToBuckets(buckets, attrs, holding.value_gbp);
+        investments += holding.value_gbp;
+      }
+
+      if (account.cash_balance === null) {
+        cashAvailable = false;
+      } else if (scope.includeCash && account.cash_balance > 0) {
+        addToBuckets(buckets, { type: CASH_LABEL, currency: "GBP", tag: CASH_LABEL }, account.cash_balance);
+        cash += account.cash_balance;
+      }
+    }
+  }
+
+  return { buckets: buckets, investments: roundToPence(investments), cash: roundToPence(cash), cash_available: cashAvailable };
+}
+
+/**
+ * @description Convert a map of label to value into rows with the percentage
+ * of the total, largest first.
+ * @param {Object} bucket - Map of label to GBP value
+ * @param {number} total - The total value the percentages are of
+ * @returns {Array<{label: string, value: number, percent: number}>} Allocation rows
+ */
+function toAllocationRows(bucket, total) {
+  const rows = Object.keys(bucket).map(function (label) {
+    const value = roundToPence(bucket[label]);
+    return {
+      label: label,
+      value: value,
+      percent: total > 0 ? Math.round((value / total) * 10000) / 100 : 0,
+    };
+  });
+  rows.sort(function (a, b) {
+    return b.value - a.value || a.label.localeCompare(b.label);
+  });
+  return rows;
+}
+
+/**
+ * @description Build the current allocation of household value by investment
+ * type, currency exposure and allocation tag. Uninvested cash is included as
+ * its own "Cash" group (and as GBP exposure) unless excluded in the scope.
+ * @param {Object} scope - Which part of the household to include
+ * @param {number[]} scope.userIds - The users whose accounts are included
+ * @param {string[]} [scope.accountTypes] - Account types to include; empty means all
+ * @param {number|null} [scope.accountId] - A single account to include
+ * @param {boolean} [scope.includeCash=true] - Whether to include account cash balances
+ * @returns {Object} Allocation with { valuation_date, total, investments, cash, by_type, by_currency, by_tag }
+ */
+export function buildAllocation(scope) {
+  const fullScope = Object.assign({ includeCash: true }, scope);
+  const attributes = loadInvestmentAttributes();
+
+  const summaries = [];
+  for (const userId of fullScope.userIds) {
+    const summary = getPortfolioSummary(userId);
+    if (summary) summaries.push(summary);
+  }
+
+  const totals = totalSummaries(summaries, fullScope, attributes);
+  const total = roundToPence(totals.investments + totals.cash);
+
+  return {
+    valuation_date: new Date().toISOString().slice(0, 10),
+    total: total,
+    investments: totals.investments,
+    cash: totals.cash,
+    by_type: toAllocationRows(totals.buckets.type, total),
+    by_currency: toAllocationRows(totals.buckets.currency, total),
+    by_tag: toAllocationRows(totals.buckets.tag, total),
+  };
+}
+
+/**
+ * @description Build the sample dates for an allocation history: the last day
+ * of each of the previous months, then today.
+ * @param {number} months - Number of whole months to look back
+ * @param {string} today - ISO-8601 date (YYYY-MM-DD) of the final sample
+ * @returns {string[]} ISO-8601 dates, oldest first
+ */
+export function buildMonthEndDates(months, today) {
+  const year = parseInt(today.slice(0, 4), 10);
+  const month = parseInt(today.slice(5, 7), 10) - 1;
+  const dates = [];
+
+  for (let i = months; i >= 1; i--) {
+    // Day 0 of the following month is the last day of the month
+    const monthEnd = new Date(Date.UTC(year, month - i + 1, 0));
+    dates.push(monthEnd.toISOString().slice(0, 10));
+  }
+  dates.push(today);
+  return dates;
+}
+
+/**
+ * @description Build the allocation drift over time for one dimension, from
+ * the SCD2 holdings held at each month end and the prices and rates on or
+ * before that date. Where a historic cash balance cannot be reconstructed the
+ * point is built from investments only and flagged.
+ * @param {Object} scope - Which part of the household to include (as buildAllocation)
+ * @param {string} dimension - "type", "currency" or "tag"
+ * @param {number} months - Number of months to look back
+ * @returns {Object} History with { dimension, dates, totals, cash_available, series: [{label, percents}] }
+ */
+export function buildAllocationHistory(scope, dimension, months) {
+  const fullScope = Object.assign({ includeCash: true }, scope);
+  const attributes = loadInvestmentAttributes();
+  const dates = buildMonthEndDates(months, new Date().toISOString().slice(0, 10));
+
+  const points = dates.map(function (date) {
+    const summaries = [];
+    for (const userId of fullScope.userIds) {
+      const summary = getPortfolioSummaryAtDate(userId, date);
+      if (summary) summaries.push(summary);
+    }
+    const totals = totalSummaries(summaries, fullScope, attributes);
+    return {
+      bucket: totals.buckets[dimension],
+      total: roundToPence(totals.investments + totals.cash),
+      cash_available: totals.cash_available,
+    };
+  });
+
+  // Order the series by their latest value, largest first, then by name
+  const labels = [];
+  for (const point of points) {
+    for (const label of Object.keys(point.bucket)) {
+      if (labels.indexOf(label) === -1) labels.push(label);
+    }
+  }
+  const latest = points[points.length - 1].bucket;
+  labels.sort(function (a, b) {
+    return (latest[b] || 0) - (latest[a] || 0) || a.localeCompare(b);
+  });
+
+  const series = labels.map(function (label) {
+    return {
+      label: label,
+      percents: points.map(function (point) {
+        const value = point.bucket[label] || 0;
+        return point.total > 0 ? Math.round((value / point.total) * 10000) / 100 : 0;
+      }),
+    };
+  });
+
+  return {
+    dimension: dimension,
+    dates: dates,
+    totals: points.map(function (point) {
+      return point.total;
+    }),
+    cash_available: points.map(function (point) {
+      return point.cash_available;
+    }),
+    series: series,
+  };
+}
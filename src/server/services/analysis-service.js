@@ -1,7 +1,8 @@
 /**
  * @description Analysis service for Portfolio 60.
- * Computes investment returns, volatility, league tables, risk/return scatter
- * data, and top/bottom performer series for the analysis page.
+ * Computes investment returns, volatility and risk metrics, league tables,
+ * risk/return scatter data, top/bottom performer series and correlation
+ * matrices for the analysis page.
  */
 
 import { getInvestmentsWithPrices, getInvestmentsWithPricesByIds } from "../db/investments-db.js";
@@ -10,6 +11,8 @@ import { getRatesInRange } from "../db/currency-rates-db.js";
 import { getBenchmarkById } from "../db/benchmarks-db.js";
 import { getBenchmarkDataInRange } from "../db/benchmark-data-db.js";
 import { getCurrentHoldingInvestmentIds, getHistoricHoldingInvestmentIds } from "../db/holdings-db.js";
+import { getAllIndexSeries, getIndexValues } from "../db/index-series-db.js";
+import { getRiskMetricsConfig } from "../config.js";
 import { convertPricesToGBP, sampleWeekly, rebaseToZero, generateWeeklyDates, formatISODate } from "./price-utils.js";
 
 /**
@@ -40,6 +43,13 @@ const PERIOD_LABELS = {
   "3y": "3 Years",
 };
 
+/**
+ * @description Fewest weekly returns needed before the Sharpe, Sortino, beta and
+ * correlation figures are given. Shorter periods are too noisy to be useful.
+ * @type {number}
+ */
+const MIN_RATIO_RETURNS = 8;
+
 /**
  * @description Resolve the set of investment IDs to include based on the
  * holdings filter and selected user IDs. Returns null when no filtering
@@ -191,6 +201,275 @@ function calculateVolatility(prices) {
   };
 }
 
+/**
+ * @description Round a value to 2 decimal places, keeping null as null.
+ * @param {number|null} value - The value to round
+ * @returns {number|null} The rounded value
+ */
+function roundTo2(value) {
+  return value === null ? null : Math.round(value * 100) / 100;
+}
+
+/**
+ * @description Convert weekly sampled values into weekly returns. The result is
+ * aligned with the input: entry i is the return from week i-1 to week i, or null
+ * where either value is missing (the first entry is always null).
+ * @param {Array<number|null>} values - Weekly sampled values
+ * @returns {Array<number|null>} Weekly returns as decimals (0.01 = 1%)
+ */
+function toWeeklyReturns(values) {
+  const returns = [null];
+  for (let i = 1; i < values.length; i++) {
+    if (values[i] !== null && values[i - 1] !== null && values[i - 1] > 0) {
+      returns.push((values[i] - values[i - 1]) / values[i - 1]);
+    } else {
+      returns.push(null);
+    }
+  }
+  return returns;
+}
+
+/**
+ * @description Calculate the maximum drawdown — the largest fall from a peak to
+ * a later trough — and how long the price took to climb back to that peak.
+ * @param {Array<Object>} prices - Price records with price_date and price, sorted ascending
+ * @returns {Object} Object with maxDrawdownPct (0 or negative), peakDate, troughDate and
+ *   recoveryDays (days from the trough back to the peak level, null if not yet recovered)
+ */
+function calculateMaxDrawdown(prices) {
+  if (!prices || prices.length < 2) {
+    return { maxDrawdownPct: null, peakDate: null, troughDate: null, recoveryDays: null };
+  }
+
+  let peak = prices[0];
+  let worst = 0;
+  let worstPeak = null;
+  let worstTrough = null;
+
+  for (let i = 1; i < prices.length; i++) {
+    if (prices[i].price > peak.price) {
+      peak = prices[i];
+      continue;
+    }
+    const fall = peak.price > 0 ? (prices[i].price - peak.price) / peak.price : 0;
+    if (fall < worst) {
+      worst = fall;
+      worstPeak = peak;
+      worstTrough = prices[i];
+    }
+  }
+
+  if (!worstTrough) {
+    return { maxDrawdownPct: 0, peakDate: null, troughDate: null, recoveryDays: null };
+  }
+
+  let recoveryDays = null;
+  for (let j = 0; j < prices.length; j++) {
+    if (prices[j].price_date > worstTrough.price_date && prices[j].price >= worstPeak.price) {
+      recoveryDays = Math.round((new Date(prices[j].price_date) - new Date(worstTrough.price_date)) / 86400000);
+      break;
+    }
+  }
+
+  return {
+    maxDrawdownPct: worst * 100,
+    peakDate: worstPeak.price_date,
+    troughDate: worstTrough.price_date,
+    recoveryDays: recoveryDays,
+  };
+}
+
+/**
+ * @description Calculate the annualised Sharpe and Sortino ratios from weekly
+ * returns. Both divide the mean weekly return above the risk-free rate by a
+ * measure of risk — the standard deviation of all weekly returns for Sharpe,
+ * the downside deviation (falls below the risk-free rate only) for Sortino —
+ * and scale by the square root of 52.
+ * @param {Array<number|null>} weeklyReturns - Weekly returns as decimals (nulls ignored)
+ * @param {number} riskFreeRate - Annual risk-free rate in percent
+ * @returns {{ sharpe: number|null, sortino: number|null }} The ratios, or null when there are too few returns
+ */
+function calculateRiskRatios(weeklyReturns, riskFreeRate) {
+  const weeklyRiskFree = Math.pow(1 + riskFreeRate / 100, 1 / 52) - 1;
+  const excess = [];
+  for (let i = 0; i < weeklyReturns.length; i++) {
+    if (weeklyReturns[i] !== null) excess.push(weeklyReturns[i] - weeklyRiskFree);
+  }
+  if (excess.length < MIN_RATIO_RETURNS) return { sharpe: null, sortino: null };
+
+  let sum = 0;
+  for (let j = 0; j < excess.length; j++) {
+    sum += excess[j];
+  }
+  const mean = sum / excess.length;
+
+  let sumSquaredDiffs = 0;
+  let sumSquaredShortfalls = 0;
+  for (let k = 0; k < excess.length; k++) {
+    sumSquaredDiffs += (excess[k] - mean) * (excess[k] - mean);
+    if (excess[k] < 0) sumSquaredShortfalls += excess[k] * excess[k];
+  }
+  const stdDev = Math.sqrt(sumSquaredDiffs / (excess.length - 1));
+  const downsideDev = Math.sqrt(sumSquaredShortfalls / excess.length);
+
+  return {
+    sharpe: stdDev > 0 ? (mean / stdDev) * Math.sqrt(52) : null,
+    sortino: downsideDev > 0 ? (mean / downsideDev) * Math.sqrt(52) : null,
+  };
+}
+
+/**
+ * @description Calculate beta and correlation against a benchmark from weekly
+ * returns over the same weeks. Weeks missing from either series are skipped.
+ * @param {Array<number|null>} weeklyReturns - Investment weekly returns
+ * @param {Array<number|null>} benchmarkReturns - Benchmark weekly returns, aligned with weeklyReturns
+ * @returns {{ beta: number|null, correlation: number|null }} Beta and correlation, or null when there are too few weeks
+ */
+function calculateBeta(weeklyReturns, benchmarkReturns) {
+  const xs = [];
+  const ys = [];
+  for (let i = 0; i < weeklyReturns.length && i < benchmarkReturns.length; i++) {
+    if (weeklyReturns[i] !== null && benchmarkReturns[i] !== null) {
+      xs.push(benchmarkReturns[i]);
+      ys.push(weeklyReturns[i]);
+    }
+  }
+  if (xs.length < MIN_RATIO_RETURNS) return { beta: null, correlation: null };
+
+  let sumX = 0;
+  let sumY = 0;
+  for (let j = 0; j < xs.length; j++) {
+    sumX += xs[j];
+    sumY += ys[j];
+  }
+  const meanX = sumX / xs.length;
+  const meanY = sumY / ys.length;
+
+  let covariance = 0;
+  let varianceX = 0;
+  let varianceY = 0;
+  for (let k = 0; k < xs.length; k++) {
+    covariance += (xs[k] - meanX) * (ys[k] - meanY);
+    varianceX += (xs[k] - meanX) * (xs[k] - meanX);
+    varianceY += (ys[k] - meanY) * (ys[k] - meanY);
+  }
+
+  return {
+    beta: varianceX > 0 ? covariance / varianceX : null,
+    correlation: varianceX > 0 && varianceY > 0 ? covariance / Math.sqrt(varianceX * varianceY) : null,
+  };
+}
+
+/**
+ * @description Work out the annual risk-free rate for a period. When the
+ * riskMetrics config names an index series (such as Bank Rate loaded from the
+ * Bank of England database), the average of its weekly values over the period
+ * is used; otherwise, or if the series has no values for the period, the
+ * configured fixed rate.
+ * @param {Array<string>} sampleDates - Weekly sample dates covering the period
+ * @returns {{ rate: number, source: string }} Rate in percent, and the series name or "config"
+ */
+export function resolveRiskFreeRate(sampleDates) {
+  const config = getRiskMetricsConfig();
+
+  if (config.riskFreeSeries) {
+    const wanted = config.riskFreeSeries.toLowerCase();
+    const series = getAllIndexSeries().find(function (s) {
+      return s.name.toLowerCase() === wanted;
+    });
+    const values = series ? getIndexValues(series.id) : [];
+
+    if (values.length > 0) {
+      const sampled = sampleWeekly(sampleDates, values, "value_date", "value");
+      let sum = 0;
+      let count = 0;
+      for (let i = 0; i < sampled.length; i++) {
+        if (sampled[i] !== null) {
+          sum += sampled[i];
+          count++;
+        }
+      }
+      if (count > 0) {
+        return { rate: Math.round((sum / count) * 100) / 100, source: series.name };
+      }
+    }
+  }
+
+  return { rate: config.riskFreeRate, source: "config" };
+}
+
+/**
+ * @description Gather what the risk metrics of a period share: the risk-free
+ * rate and, when a beta benchmark is chosen, its weekly returns.
+ * @param {Array<string>} sampleDates - Weekly sample dates covering the period
+ * @param {Object} range - Date range from getDateRange()
+ * @param {number|null} betaBenchmarkId - Benchmark to measure beta against, or null
+ * @returns {Object} Context with riskFreeRate, riskFreeSource, benchmark ({ id, description } or null)
+ *   and benchmarkReturns (aligned weekly returns or null)
+ */
+function buildRiskContext(sampleDates, range, betaBenchmarkId) {
+  const riskFree = resolveRiskFreeRate(sampleDates);
+  const context = {
+    riskFreeRate: riskFree.rate,
+    riskFreeSource: riskFree.source,
+    benchmark: null,
+    benchmarkReturns: null,
+  };
+
+  const bm = betaBenchmarkId ? getBenchmarkById(betaBenchmarkId) : null;
+  if (bm) {
+    const bmValues = getBenchmarkDataInRange(bm.id, range.fromStr, range.toStr);
+    if (bmValues && bmValues.length >= 2) {
+      context.benchmark = { id: bm.id, description: bm.description };
+      context.benchmarkReturns = toWeeklyReturns(sampleWeekly(sampleDates, bmValues, "benchmark_date", "value"));
+    }
+  }
+
+  return context;
+}
+
+/**
+ * @description Calculate the risk metrics of a price series over a period:
+ * maximum drawdown and recovery time, Sharpe and Sortino ratios and, when the
+ * context has a benchmark, beta and correlation against it.
+ * @param {Array<Object>} prices - GBP price records with price_date and price, sorted ascending
+ * @param {Array<string>} sampleDates - Weekly sample dates covering the period
+ * @param {Object} context - Shared context from buildRiskContext()
+ * @returns {Object} Metrics with maxDrawdownPct, drawdownPeakDate, drawdownTroughDate,
+ *   recoveryDays, sharpe, sortino, beta and correlation (null where not available)
+ */
+function buildRiskMetrics(prices, sampleDates, context) {
+  const drawdown = calculateMaxDrawdown(prices);
+  const weeklyReturns = toWeeklyReturns(sampleWeekly(sampleDates, prices, "price_date", "price"));
+  const ratios = calculateRiskRatios(weeklyReturns, context.riskFreeRate);
+  const beta = context.benchmarkReturns ? calculateBeta(weeklyReturns, context.benchmarkReturns) : { beta: null, correlation: null };
+
+  return {
+    maxDrawdownPct: roundTo2(drawdown.maxDrawdownPct),
+    drawdownPeakDate: drawdown.peakDate,
+    drawdownTroughDate: drawdown.troughDate,
+    recoveryDays: drawdown.recoveryDays,
+    sharpe: roundTo2(ratios.sharpe),
+    sortino: roundTo2(ratios.sortino),
+    beta: roundTo2(beta.beta),
+    correlation: roundTo2(beta.correlation),
+  };
+}
+
+/**
+ * @description Describe the risk context for API responses, without the
+ * benchmark's weekly returns.
+ * @param {Object} context - Context from buildRiskContext()
+ * @returns {{ riskFreeRate: number, riskFreeSource: string, benchmark: Object|null }} Risk context summary
+ */
+function describeRiskContext(context) {
+  return {
+    riskFreeRate: context.riskFreeRate,
+    riskFreeSource: context.riskFreeSource,
+    benchmark: context.benchmark,
+  };
+}
+
 /**
  * @description Build sparkline data for an investment — an array of normalised
  * values sampled weekly, rebased to 100 at the start.
@@ -228,15 +507,18 @@ function buildSparkline(prices, sampleDates) {
 /**
  * @description Build the league table data for all investments with price data.
  * Returns investments ranked by return for the given period.
+ * Each row carries the risk metrics for the period (see buildRiskMetrics).
  * @param {string} periodCode - One of "1w", "1m", "3m", "6m", "1y", "3y"
  * @param {Array<number>|null} investmentIds - Optional array of investment IDs to filter by
- * @returns {Object} League table data with period info and ranked investments
+ * @param {number|null} [betaBenchmarkId] - Optional benchmark to measure beta and correlation against
+ * @returns {Object} League table data with period info, risk context and ranked investments
  */
-export function buildLeagueTable(periodCode, investmentIds) {
+export function buildLeagueTable(periodCode, investmentIds, betaBenchmarkId) {
   const range = getDateRange(periodCode);
   const investments = getFilteredInvestments(investmentIds);
   const allPricesMap = getAllInvestmentPricesInRange(range.fromStr, range.toStr);
   const sampleDates = generateWeeklyDates(range.startDate, range.endDate);
+  const riskContext = buildRiskContext(sampleDates, range, betaBenchmarkId || null);
 
   const rows = [];
 
@@ -250,18 +532,23 @@ export function buildLeagueTable(periodCode, investmentIds) {
 
     const sparkline = buildSparkline(prices, sampleDates);
 
-    rows.push({
-      id: inv.id,
-      description: inv.description,
-      publicId: inv.public_id || null,
-      morningstarId: inv.morningstar_id || null,
-      currencyCode: inv.currency_code,
-      typeShort: inv.type_short,
-      returnPct: Math.round(returnData.returnPct * 100) / 100,
-      startDate: returnData.startDate,
-      endDate: returnData.endDate,
-      sparkline: sparkline,
-    });
+    rows.push(
+      Object.assign(
+        {
+          id: inv.id,
+          description: inv.description,
+          publicId: inv.public_id || null,
+          morningstarId: inv.morningstar_id || null,
+          currencyCode: inv.currency_code,
+          typeShort: inv.type_short,
+          returnPct: Math.round(returnData.returnPct * 100) / 100,
+          startDate: returnData.startDate,
+          endDate: returnData.endDate,
+          sparkline: sparkline,
+        },
+        buildRiskMetrics(prices, sampleDates, riskContext),
+      ),
+    );
   }
 
   // Sort by return descending (best first)
@@ -273,21 +560,26 @@ export function buildLeagueTable(periodCode, investmentIds) {
     period: periodCode,
     periodLabel: PERIOD_LABELS[periodCode] || periodCode,
     asOf: range.toStr,
+    risk: describeRiskContext(riskContext),
     investments: rows,
   };
 }
 
 /**
  * @description Build risk vs return scatter data for all investments.
- * Each investment gets a return percentage and annualised volatility.
+ * Each investment gets a return percentage and annualised volatility, plus
+ * the risk metrics for the period (see buildRiskMetrics).
  * @param {string} periodCode - One of "1w", "1m", "3m", "6m", "1y", "3y"
  * @param {Array<number>|null} investmentIds - Optional array of investment IDs to filter by
- * @returns {Object} Scatter data with period info and investment data points
+ * @param {number|null} [betaBenchmarkId] - Optional benchmark to measure beta and correlation against
+ * @returns {Object} Scatter data with period info, risk context and investment data points
  */
-export function buildRiskReturnData(periodCode, investmentIds) {
+export function buildRiskReturnData(periodCode, investmentIds, betaBenchmarkId) {
   const range = getDateRange(periodCode);
   const investments = getFilteredInvestments(investmentIds);
   const allPricesMap = getAllInvestmentPricesInRange(range.fromStr, range.toStr);
+  const sampleDates = generateWeeklyDates(range.startDate, range.endDate);
+  const riskContext = buildRiskContext(sampleDates, range, betaBenchmarkId || null);
 
   const points = [];
 
@@ -302,21 +594,27 @@ export function buildRiskReturnData(periodCode, investmentIds) {
     const volData = calculateVolatility(prices);
     if (volData.volatility === null) continue;
 
-    points.push({
-      id: inv.id,
-      description: inv.description,
-      publicId: inv.public_id || null,
-      morningstarId: inv.morningstar_id || null,
-      typeShort: inv.type_short,
-      returnPct: Math.round(returnData.returnPct * 100) / 100,
-      volatility: Math.round(volData.volatility * 100) / 100,
-    });
+    points.push(
+      Object.assign(
+        {
+          id: inv.id,
+          description: inv.description,
+          publicId: inv.public_id || null,
+          morningstarId: inv.morningstar_id || null,
+          typeShort: inv.type_short,
+          returnPct: Math.round(returnData.returnPct * 100) / 100,
+          volatility: Math.round(volData.volatility * 100) / 100,
+        },
+        buildRiskMetrics(prices, sampleDates, riskContext),
+      ),
+    );
   }
 
   return {
     period: periodCode,
     periodLabel: PERIOD_LABELS[periodCode] || periodCode,
     asOf: range.toStr,
+    risk: describeRiskContext(riskContext),
     investments: points,
   };
 }
@@ -421,16 +719,19 @@ function mapBenchmarkToPrice(bmValues) {
 }
 
 /**
- * @description Build return and volatility data for a set of benchmarks.
- * Used by the scatter plot and comparison views.
+ * @description Build return, volatility and risk data for a set of benchmarks.
+ * Used by the scatter plot and comparison views. Beta and correlation are not
+ * given for benchmarks.
  * @param {Array<number>} benchmarkIds - Array of benchmark IDs to include
  * @param {string} periodCode - Period code (e.g. "1y")
- * @returns {Array<Object>} Array of benchmark data points with returnPct and volatility
+ * @returns {Array<Object>} Array of benchmark data points with returnPct, volatility and risk metrics
  */
 export function buildBenchmarkReturnData(benchmarkIds, periodCode) {
   if (!benchmarkIds || benchmarkIds.length === 0) return [];
 
   const range = getDateRange(periodCode);
+  const sampleDates = generateWeeklyDates(range.startDate, range.endDate);
+  const riskContext = buildRiskContext(sampleDates, range, null);
   const results = [];
 
   for (let i = 0; i < benchmarkIds.length; i++) {
@@ -445,12 +746,17 @@ export function buildBenchmarkReturnData(benchmarkIds, periodCode) {
     if (returnData.returnPct === null) continue;
 
     const volData = calculateVolatility(mapped);
+    const metrics = buildRiskMetrics(mapped, sampleDates, riskContext);
 
     results.push({
       id: bm.id,
       description: bm.description,
       returnPct: Math.round(returnData.returnPct * 100) / 100,
       volatility: volData.volatility !== null ? Math.round(volData.volatility * 100) / 100 : null,
+      maxDrawdownPct: metrics.maxDrawdownPct,
+      recoveryDays: metrics.recoveryDays,
+      sharpe: metrics.sharpe,
+      sortino: metrics.sortino,
     });
   }
 
@@ -497,13 +803,15 @@ export function buildBenchmarkRebasedSeries(benchmarkIds, periodCode) {
 /**
  * @description Build comparison table data showing returns for multiple periods.
  * Returns all investments with return % for each of the requested periods,
- * plus optional benchmark rows.
+ * plus optional benchmark rows. Each investment row also carries the risk
+ * metrics (see buildRiskMetrics) over the longest of the periods.
  * @param {Array<string>} periodCodes - Array of period codes (e.g. ["3m", "6m", "1y", "3y"])
  * @param {Array<number>} benchmarkIds - Optional array of benchmark IDs
  * @param {Array<number>|null} investmentIds - Optional array of investment IDs to filter by
- * @returns {Object} Comparison data with periods, investments, and benchmarks
+ * @param {number|null} [betaBenchmarkId] - Optional benchmark to measure beta and correlation against
+ * @returns {Object} Comparison data with periods, risk period and context, investments, and benchmarks
  */
-export function buildComparisonTable(periodCodes, benchmarkIds, investmentIds) {
+export function buildComparisonTable(periodCodes, benchmarkIds, investmentIds, betaBenchmarkId) {
   periodCodes = periodCodes || ["3m", "6m", "1y", "3y"];
   benchmarkIds = benchmarkIds || [];
 
@@ -522,6 +830,8 @@ export function buildComparisonTable(periodCodes, benchmarkIds, investmentIds) {
   const widestRange = getDateRange(widestCode);
   const investments = getFilteredInvestments(investmentIds);
   const allPricesMap = getAllInvestmentPricesInRange(widestRange.fromStr, widestRange.toStr);
+  const widestSampleDates = generateWeeklyDates(widestRange.startDate, widestRange.endDate);
+  const riskContext = buildRiskContext(widestSampleDates, widestRange, betaBenchmarkId || null);
 
   // Pre-compute date ranges for each period
   const ranges = {};
@@ -566,15 +876,20 @@ export function buildComparisonTable(periodCodes, benchmarkIds, investmentIds) {
 
     if (!hasAnyReturn) continue;
 
-    investmentRows.push({
-      id: inv.id,
-      description: inv.description,
-      publicId: inv.public_id || null,
-      morningstarId: inv.morningstar_id || null,
-      currencyCode: inv.currency_code,
-      typeShort: inv.type_short,
-      returns: returns,
-    });
+    investmentRows.push(
+      Object.assign(
+        {
+          id: inv.id,
+          description: inv.description,
+          publicId: inv.public_id || null,
+          morningstarId: inv.morningstar_id || null,
+          currencyCode: inv.currency_code,
+          typeShort: inv.type_short,
+          returns: returns,
+        },
+        buildRiskMetrics(widestPrices, widestSampleDates, riskContext),
+      ),
+    );
   }
 
   // Build benchmark rows
@@ -610,9 +925,113 @@ export function buildComparisonTable(periodCodes, benchmarkIds, investmentIds) {
   return {
     periods: periods,
     asOf: widestRange.toStr,
+    riskPeriod: { code: widestCode, label: PERIOD_LABELS[widestCode] || widestCode },
+    risk: describeRiskContext(riskContext),
     investments: investmentRows,
     benchmarks: benchmarkRows,
   };
 }
 
-export { PERIOD_WEEKS, PERIOD_LABELS, getDateRange, calculateReturn, calculateVolatility };
+/**
+ * @description Build a correlation matrix of weekly GBP returns over a period
+ * for the filtered investments and any selected benchmarks. Each pair is
+ * correlated over the weeks both have prices for; pairs with fewer than eight
+ * shared weekly returns are null. Investments with fewer than eight weekly
+ * returns in the period are left out altogether.
+ * @param {string} periodCode - One of "1w", "1m", "3m", "6m", "1y", "2y", "3y"
+ * @param {Array<number>|null} investmentIds - Optional array of investment IDs to filter by
+ * @param {Array<number>} [benchmarkIds] - Optional benchmark IDs to add after the investments
+ * @returns {Object} Matrix data with period info, series ({ id, kind, label, weeks }),
+ *   matrix (rows of correlations in series order) and pairs (investment pairs, most correlated first)
+ */
+export function buildCorrelationMatrix(periodCode, investmentIds, benchmarkIds) {
+  const range = getDateRange(periodCode);
+  const investments = getFilteredInvestments(investmentIds);
+  const allPricesMap = getAllInvestmentPricesInRange(range.fromStr, range.toStr);
+  const sampleDates = generateWeeklyDates(range.startDate, range.endDate);
+
+  /**
+   * @description Count the weekly returns that are present.
+   * @param {Array<number|null>} returns - Weekly returns
+   * @returns {number} Number of non-null returns
+   */
+  function countReturns(returns) {
+    let count = 0;
+    for (let i = 0; i < returns.length; i++) {
+      if (returns[i] !== null) count++;
+    }
+    return count;
+  }
+
+  const series = [];
+
+  for (let i = 0; i < investments.length; i++) {
+    const inv = investments[i];
+    const prices = getGBPPrices(inv, allPricesMap, range.fromStr, range.toStr);
+    if (!prices || prices.length < 2) continue;
+
+    const returns = toWeeklyReturns(sampleWeekly(sampleDates, prices, "price_date", "price"));
+    const weeks = countReturns(returns);
+    if (weeks < MIN_RATIO_RETURNS) continue;
+
+    series.push({ id: inv.id, kind: "investment", label: inv.description, weeks: weeks, returns: returns });
+  }
+
+  series.sort(function (a, b) {
+    return a.label.localeCompare(b.label);
+  });
+
+  const bmIds = benchmarkIds || [];
+  for (let b = 0; b < bmIds.length; b++) {
+    const bm = getBenchmarkById(bmIds[b]);
+    if (!bm) continue;
+
+    const bmValues = getBenchmarkDataInRange(bm.id, range.fromStr, range.toStr);
+    if (!bmValues || bmValues.length < 2) continue;
+
+    const returns = toWeeklyReturns(sampleWeekly(sampleDates, bmValues, "benchmark_date", "value"));
+    const weeks = countReturns(returns);
+    if (weeks < MIN_RATIO_RETURNS) continue;
+
+    series.push({ id: bm.id, kind: "benchmark", label: bm.description, weeks: weeks, returns: returns });
+  }
+
+  const matrix = [];
+  for (let r = 0; r < series.length; r++) {
+    const row = [];
+    for (let c = 0; c < series.length; c++) {
+      if (r === c) {
+        row.push(1);
+      } else if (c < r) {
+        row.push(matrix[c][r]);
+      } else {
+        row.push(roundTo2(calculateBeta(series[r].returns, series[c].returns).correlation));
+      }
+    }
+    matrix.push(row);
+  }
+
+  const pairs = [];
+  for (let pr = 0; pr < series.length; pr++) {
+    for (let pc = pr + 1; pc < series.length; pc++) {
+      if (series[pr].kind !== "investment" || series[pc].kind !== "investment" || matrix[pr][pc] === null) continue;
+      pairs.push({ a: series[pr].label, b: series[pc].label, correlation: matrix[pr][pc] });
+    }
+  }
+  pairs.sort(function (x, y) {
+    return y.correlation - x.correlation;
+  });
+
+  return {
+    period: periodCode,
+    periodLabel: PERIOD_LABELS[periodCode] || periodCode,
+    asOf: range.toStr,
+    series: series.map(function (s) {
+      return { id: s.id, kind: s.kind, label: s.label, weeks: s.weeks };
+    }),
+    matrix: matrix,
+    pairs: pairs,
+  };
+}
+
+export { PERIOD_WEEKS, PERIOD_LABELS, getDateRange, getGBPPrices, calculateReturn, calculateVolatility, calculateMaxDrawdown, calculateRiskRatios, calculateBeta, toWeeklyReturns };
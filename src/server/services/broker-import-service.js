@@ -0,0 +1,721 @@
+import { createHash } from "node:crypto";
+import { getDatabase } from "../db/connection.js";
+import { getAccountById } from "../db/accounts-db.js";
+import { getUserById } from "../db/users-db.js";
+import { getAllInvestments } from "../db/investments-db.js";
+import { getActiveHoldingRaw, createHolding } from "../db/holdings-db.js";
+import { insertBuyMovement, insertSellMovement, getTradeMovementsByAccountId } from "../db/holding-movements-db.js";
+import { insertCashTransaction, getCashTransactionsBetween, scaleCashAmount } from "../db/cash-transactions-db.js";
+import { extractIsinFromUrl } from "./historic-backfill.js";
+import { detectPublicIdType } from "../../shared/public-id-utils.js";
+
+/**
+ * @description Cash transaction type used to store each imported cash row type.
+ * Fees are recorded as debit adjustments, matching how fees are entered by hand.
+ * @type {Object<string, string>}
+ */
+const CASH_TRANSACTION_TYPE = {
+  deposit: "deposit",
+  withdrawal: "withdrawal",
+  dividend: "dividend",
+  interest: "interest",
+  fee: "adjustment",
+};
+
+/**
+ * @description Order in which rows on the same date are applied. Money coming
+ * in is applied before money going out so that a same-day deposit funds a buy.
+ * @type {Object<string, number>}
+ */
+const APPLY_ORDER = {
+  deposit: 0,
+  sell: 1,
+  dividend: 2,
+  interest: 3,
+  buy: 4,
+  fee: 5,
+  withdrawal: 6,
+};
+
+/**
+ * @description Month abbreviations used by broker date formats such as "02-Jan-2025".
+ * @type {string[]}
+ */
+const MONTHS = ["jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec"];
+
+/**
+ * @description Regex for an ISIN embedded in free text.
+ * @type {RegExp}
+ */
+const ISIN_IN_TEXT = /\b([A-Z]{2}[A-Z0-9]{9}[0-9])\b/;
+
+/**
+ * @description The latest preview for each account, keyed by account ID, as
+ * { file_hash, preview }. An import commits the rows of this preview.
+ * @type {Map<number, Object>}
+ */
+const pendingImports = new Map();
+
+/**
+ * @description Transaction-history CSV formats by provider code. Each format
+ * lists the header names accepted for each field (the first found is used) and
+ * a classify function that returns the row type, or null for rows the importer
+ * does not handle (e.g. stock transfers or corporate actions).
+ *
+ * Interactive Investor: Date, Settlement Date, Symbol, Sedol, Quantity, Price,
+ * Description, Reference, Debit, Credit, Running Balance.
+ * Hargreaves Lansdown: a few preamble lines, then Trade date, Settle date,
+ * Reference, Description, Unit cost (p), Quantity, Value (£). Buy references
+ * start with B and sell references with S; Value is negative for debits.
+ * AJ Bell: Date, Transaction type, Description, Sedol, ISIN, Quantity, Price,
+ * Value (£). Value is negative for debits.
+ * Any dealing charge and stamp duty columns are read as the trade's
+ * deductible costs; the debit or value of a trade already includes them.
+ * @type {Object<string, Object>}
+ */
+const PROVIDER_FORMATS = {
+  ii: {
+    name: "Interactive Investor",
+    columns: {
+      date: ["Date"],
+      description: ["Description"],
+      reference: ["Reference"],
+      sedol: ["Sedol", "SEDOL"],
+      isin: ["ISIN"],
+      quantity: ["Quantity"],
+      debit: ["Debit"],
+      credit: ["Credit"],
+      costs: ["Commission", "Dealing charge", "Charges"],
+      stamp_duty: ["Stamp duty", "Stamp Duty/PTM Levy"],
+    },
+    classify: function (fields) {
+      const isTrade = fields.quantity > 0 && (fields.sedol || fields.isin);
+      if (isTrade && fields.debit > 0) return "buy";
+      if (isTrade && fields.credit > 0) return "sell";
+      return classifyByDescription(fields.description, fields.credit > 0);
+    },
+  },
+  hl: {
+    name: "Hargreaves Lansdown",
+    columns: {
+      date: ["Trade date", "Date"],
+      description: ["Description"],
+      reference: ["Reference"],
+      quantity: ["Quantity"],
+      value: ["Value (£)", "Value"],
+      costs: ["Dealing charge", "Commission", "Charges"],
+      stamp_duty: ["Stamp duty", "Stamp Duty/PTM Levy"],
+    },
+    classify: function (fields) {
+      const ref = (fields.reference || "").toUpperCase();
+      if (/^B\d+/.test(ref) && fields.quantity > 0) return "buy";
+      if (/^S\d+/.test(ref) && fields.quantity > 0) return "sell";
+      return classifyByDescription(fields.description, fields.value > 0);
+    },
+  },
+  aj: {
+    name: "AJ Bell",
+    columns: {
+      date: ["Date", "Trade date"],
+      type: ["Transaction type", "Type"],
+      description: ["Description", "Details"],
+      sedol: ["Sedol", "SEDOL"],
+      isin: ["ISIN"],
+      quantity: ["Quantity"],
+      value: ["Value (£)", "Value"],
+      costs: ["Charges", "Dealing charge", "Commission"],
+      stamp_duty: ["Stamp duty", "Stamp Duty/PTM Levy"],
+    },
+    classify: function (fields) {
+      const type = (fields.type || "").toLowerCase();
+      if (/purchase|bought|^buy/.test(type)) return "buy";
+      if (/sale|sold|^sell/.test(type)) return "sell";
+      if (/dividend/.test(type)) return "dividend";
+      if (/interest/.test(type)) return "interest";
+      if (/charge|fee/.test(type)) return "fee";
+      if (/receipt|subscription|deposit|payment in/.test(type)) return "deposit";
+      if (/withdrawal|payment out/.test(type)) return "withdrawal";
+      return classifyByDescription(fields.description, fields.value > 0);
+    },
+  },
+};
+
+/**
+ * @description Classify a cash row from its description. Used where the
+ * provider has no explicit transaction type column.
+ * @param {string} description - The row description
+ * @param {boolean} isCredit - True if the row adds money to the account
+ * @returns {string|null} The row type, or null if not recognised
+ */
+function classifyByDescription(description, isCredit) {
+  const text = (description || "").toLowerCase();
+  if (/dividend|\bdiv\b|distribution/.test(text)) return isCredit ? "dividend" : null;
+  if (/interest/.test(text)) return isCredit ? "interest" : null;
+  if (/fee|charge|commission/.test(text)) return isCredit ? null : "fee";
+  if (/transfer of stock|stock transfer|corporate action|rights issue/.test(text)) return null;
+  if (/subscription|deposit|receipt|transfer in|contribution|payment received|card payment|bacs|faster payment/.test(text) && isCredit) return "deposit";
+  if (/withdrawal|payment to|transfer out|payment out/.test(text) && !isCredit) return "withdrawal";
+  return null;
+}
+
+/**
+ * @description Split CSV text into rows of fields. Handles quoted fields,
+ * embedded commas, doubled quotes and CRLF line endings. Blank lines are
+ * returned as a row with a single empty field.
+ * @param {string} text - The CSV text
+ * @returns {string[][]} Array of rows, each an array of field strings
+ */
+export function parseCsv(text) {
+  const rows = [];
+  let row = [];
+  let field = "";
+  let inQuotes = false;
+
+  for (let i = 0; i < text.length; i++) {
+    const ch = text[i];
+
+    if (inQuotes) {
+      if (ch === '"' && text[i + 1] === '"') {
+        field += '"';
+        i++;
+      } else if (ch === '"') {
+        inQuotes = false;
+      } else {
+        field += ch;
+      }
+    } else if (ch === '"') {
+      inQuotes = true;
+    } else if (ch === ",") {
+      row.push(field);
+      field = "";
+    } else if (ch === "\n" || ch === "\r") {
+      if (ch === "\r" && text[i + 1] === "\n") i++;
+      row.push(field);
+      rows.push(row);
+      row = [];
+      field = "";
+    } else {
+      field += ch;
+    }
+  }
+
+  if (field !== "" || row.length > 0) {
+    row.push(field);
+    rows.push(row);
+  }
+
+  // Drop trailing blank lines; interior ones are kept so row numbers match the file
+  while (rows.length > 0 && rows[rows.length - 1].every(function (f) {
+    return f.trim() === "";
+  })) {
+    rows.pop();
+  }
+
+  return rows;
+}
+
+/**
+ * @description Parse a broker date into ISO-8601 format. Accepts DD/MM/YYYY,
+ * DD/MM/YY, DD-MM-YYYY, DD-Mon-YYYY, DD Mon YYYY and YYYY-MM-DD.
+ * @param {string} value - The date as exported
+ * @returns {string|null} ISO-8601 date (YYYY-MM-DD), or null if not recognised
+ */
+export function parseBrokerDate(value) {
+  const text = (value || "").trim();
+  let day;
+  let month;
+  let year;
+
+  let match = text.match(/^(\d{4})-(\d{2})-(\d{2})/);
+  if (match) {
+    year = Number(match[1]);
+    month = Number(match[2]);
+    day = Number(match[3]);
+  } else if ((match = text.match(/^(\d{1,2})[/-](\d{1,2})[/-](\d{2}|\d{4})$/))) {
+    day = Number(match[1]);
+    month = Number(match[2]);
+    year = Number(match[3]);
+  } else if ((match = text.match(/^(\d{1,2})[\s-]([A-Za-z]{3})[A-Za-z]*[\s-](\d{2}|\d{4})$/))) {
+    day = Number(match[1]);
+    month = MONTHS.indexOf(match[2].toLowerCase()) + 1;
+    year = Number(match[3]);
+  } else {
+    return null;
+  }
+
+  if (year < 100) year += 2000;
+  if (month < 1 || month > 12 || day < 1 || day > 31) return null;
+
+  const iso = String(year) + "-" + String(month).padStart(2, "0") + "-" + String(day).padStart(2, "0");
+  const check = new Date(iso + "T00:00:00Z");
+  if (isNaN(check.getTime()) || check.getUTCDate() !== day) return null;
+  return iso;
+}
+
+/**
+ * @description Parse a broker money or quantity value. Strips currency
+ * symbols and thousands separators; parentheses and a leading minus mean
+ * negative. Blank, "n/a" and "-" are treated as zero.
+ * @param {string} value - The value as exported
+ * @returns {number} The parsed number (0 if blank or not numeric)
+ */
+export function parseBrokerNumber(value) {
+  let text = (value || "").trim();
+  if (text === "" || text === "-" || text.toLowerCase() === "n/a") return 0;
+
+  let negative = false;
+  if (/^\(.*\)$/.test(text)) {
+    negative = true;
+    text = text.slice(1, -1);
+  }
+  text = text.replace(/[£$€,\s]/g, "");
+  if (text.startsWith("-")) {
+    negative = !negative;
+    text = text.slice(1);
+  }
+
+  const number = Number(text);
+  if (!isFinite(number)) return 0;
+  return negative ? -number : number;
+}
+
+/**
+ * @description Normalise an investment description for matching: lower case,
+ * punctuation removed and whitespace collapsed.
+ * @param {string} text - The description
+ * @returns {string} The normalised description
+ */
+function normaliseDescription(text) {
+  return (text || "")
+    .toLowerCase()
+    .replace(/&/g, " and ")
+    .replace(/[^a-z0-9]+/g, " ")
+    .trim();
+}
+
+/**
+ * @description Find the header row and map each field to its column index.
+ * Scans past any preamble lines until a row containing the date column and
+ * the description or type column is found.
+ * @param {string[][]} rows - Parsed CSV rows
+ * @param {Object} format - The provider format
+ * @returns {{ headerIndex: number, columns: Object<string, number> }|null} Header position and column map, or null if not found
+ */
+function findHeader(rows, format) {
+  for (let i = 0; i < rows.length; i++) {
+    const headers = rows[i].map(function (h) {
+      return h.trim().toLowerCase();
+    });
+
+    const columns = {};
+    for (const [field, names] of Object.entries(format.columns)) {
+      for (const name of names) {
+        const index = headers.indexOf(name.toLowerCase());
+        if (index !== -1) {
+          columns[field] = index;
+          break;
+        }
+      }
+    }
+
+    if (columns.date !== undefined && (columns.description !== undefined || columns.type !== undefined)) {
+      return { headerIndex: i, columns: columns };
+    }
+  }
+  return null;
+}
+
+/**
+ * @description Parse a provider's transaction-history CSV export into
+ * normalised rows. Rows the importer does not recognise are returned with a
+ * null type so the preview can show them as skipped.
+ * @param {string} provider - Provider code ('ii', 'hl' or 'aj')
+ * @param {string} csvText - The CSV file contents
+ * @returns {Object[]} Array of { row_number, date, type, description, reference, isin, sedol, quantity, amount, deductible_costs }
+ * @throws {Error} If the provider is not supported or the header row cannot be found
+ */
+export function parseBrokerCsv(provider, csvText) {
+  const format = PROVIDER_FORMATS[provider];
+  if (!format) {
+    throw new Error("Unsupported provider: " + provider);
+  }
+
+  const rows = parseCsv(csvText || "");
+  const header = findHeader(rows, format);
+  if (!header) {
+    throw new Error("Could not find the " + format.name + " header row in the CSV file");
+  }
+
+  const { headerIndex, columns } = header;
+
+  function cell(row, field) {
+    const index = columns[field];
+    const value = index === undefined ? "" : (row[index] || "").trim();
+    // Providers write "n/a" in columns that do not apply to a row
+    return value.toLowerCase() === "n/a" ? "" : value;
+  }
+
+  const parsed = [];
+  for (let i = headerIndex + 1; i < rows.length; i++) {
+    const row = rows[i];
+    const date = parseBrokerDate(cell(row, "date"));
+    // Footer and summary lines have no date — ignore them
+    if (!date) continue;
+
+    const description = cell(row, "description");
+    const isinCell = cell(row, "isin").toUpperCase();
+    const isinMatch = description.toUpperCase().match(ISIN_IN_TEXT);
+    const fields = {
+      type: cell(row, "type"),
+      description: description,
+      reference: cell(row, "reference"),
+      sedol: cell(row, "sedol").toUpperCase(),
+      isin: ISIN_IN_TEXT.test(isinCell) ? isinCell : isinMatch ? isinMatch[1] : "",
+      quantity: Math.abs(parseBrokerNumber(cell(row, "quantity"))),
+      debit: Math.abs(parseBrokerNumber(cell(row, "debit"))),
+      credit: Math.abs(parseBrokerNumber(cell(row, "credit"))),
+      value: parseBrokerNumber(cell(row, "value")),
+    };
+
+    const amount = columns.value !== undefined ? Math.abs(fields.value) : fields.debit || fields.credit;
+    const costs = Math.abs(parseBrokerNumber(cell(row, "costs"))) + Math.abs(parseBrokerNumber(cell(row, "stamp_duty")));
+
+    parsed.push({
+      row_number: i + 1,
+      date: date,
+      type: format.classify(fields),
+      description: description || fields.type,
+      reference: fields.reference || null,
+      isin: fields.isin || null,
+      sedol: fields.sedol || null,
+      quantity: fields.quantity,
+      amount: Math.round(amount * 100) / 100,
+      deductible_costs: Math.round(costs * 100) / 100,
+    });
+  }
+
+  return parsed;
+}
+
+/**
+ * @description Build the lookup used to match imported rows to investments.
+ * Each investment's ISIN comes from its public_id or, failing that, its
+ * Fidelity factsheet URL. UK and Irish ISINs embed the SEDOL (characters 5-11).
+ * @returns {Object[]} Array of { investment, isin, sedol, normalised }
+ */
+function buildInvestmentIndex() {
+  return getAllInvestments()
+    .map(function (inv) {
+      let isin = null;
+      if (inv.public_id && detectPublicIdType(inv.public_id) === "isin") {
+        isin = inv.public_id.trim().toUpperCase();
+      } else {
+        isin = extractIsinFromUrl(inv.investment_url);
+      }
+      const sedol = isin && /^(GB|IE)00/.test(isin) ? isin.slice(4, 11) : null;
+      return { investment: inv, isin: isin, sedol: sedol, normalised: normaliseDescription(inv.description) };
+    })
+    .sort(function (a, b) {
+      // Prefer current investments over ones that have been replaced
+      return a.investment.replaced - b.investment.replaced;
+    });
+}
+
+/**
+ * @description Match an imported row to an investment by ISIN, then SEDOL,
+ * then description. A description matches if it equals the investment's
+ * description, or contains it (the longest such description wins).
+ * @param {Object} row - The parsed row
+ * @param {Object[]} index - Lookup from buildInvestmentIndex
+ * @returns {{ investment: Object, matched_by: string }|null} The match, or null if none
+ */
+function matchInvestment(row, index) {
+  if (row.isin) {
+    const byIsin = index.find(function (e) {
+      return e.isin === row.isin;
+    });
+    if (byIsin) return { investment: byIsin.investment, matched_by: "isin" };
+  }
+
+  if (row.sedol) {
+    const bySedol = index.find(function (e) {
+      return e.sedol === row.sedol;
+    });
+    if (bySedol) return { investment: bySedol.investment, matched_by: "sedol" };
+  }
+
+  const text = normaliseDescription(row.description);
+  if (!text) return null;
+
+  let best = null;
+  for (const entry of index) {
+    if (entry.normalised.length < 4) continue;
+    if (entry.normalised === text) {
+      return { investment: entry.investment, matched_by: "description" };
+    }
+    if ((" " + text + " ").includes(" " + entry.normalised + " ")) {
+      if (!best || entry.normalised.length > best.normalised.length) {
+        best = entry;
+      }
+    }
+  }
+
+  return best ? { investment: best.investment, matched_by: "description" } : null;
+}
+
+/**
+ * @description Build the duplicate-detection key for a row. Trades match on
+ * type, investment, date and quantity; cash rows on type, date and amount.
+ * @param {string} type - Import row type or stored transaction type
+ * @param {string} date - ISO-8601 date
+ * @param {number|null} investmentId - The investment ID (trades only)
+ * @param {number} value - Quantity for trades, amount for cash rows
+ * @returns {string} The key
+ */
+function duplicateKey(type, date, investmentId, value) {
+  return [type, date, investmentId || "", scaleCashAmount(value)].join("|");
+}
+
+/**
+ * @description Count the account's existing transactions and trades by
+ * duplicate key over a date range.
+ * @param {number} accountId - The account ID
+ * @param {string} startDate - Start date (inclusive)
+ * @param {string} endDate - End date (inclusive)
+ * @returns {Map<string, number>} Count of existing records for each key
+ */
+function loadExistingKeys(accountId, startDate, endDate) {
+  const counts = new Map();
+  function add(key) {
+    counts.set(key, (counts.get(key) || 0) + 1);
+  }
+
+  for (const tx of getCashTransactionsBetween(accountId, startDate, endDate)) {
+    // Buy and sell cash rows are covered by their movements
+    if (tx.transaction_type === "buy" || tx.transaction_type === "sell") continue;
+    // Credit adjustments are refunds, not fees
+    if (tx.transaction_type === "adjustment" && tx.notes && tx.notes.startsWith("[Credit]")) continue;
+    add(duplicateKey(tx.transaction_type, tx.transaction_date, null, tx.amount));
+  }
+
+  for (const m of getTradeMovementsByAccountId(accountId, startDate, endDate)) {
+    add(duplicateKey(m.movement_type, m.movement_date, m.investment_id, m.quantity));
+  }
+
+  return counts;
+}
+
+/**
+ * @description Build a dry-run preview of a broker CSV import into an account.
+ * Nothing is written to the database. The preview is kept, with a hash of
+ * the file, as the account's pending import. Each row is given a status:
+ *   new — will be imported
+ *   duplicate — already recorded in the account (or repeated earlier in the file
+ *     more times than it is recorded)
+ *   unmatched — a trade or dividend whose investment could not be identified
+ *   skipped — a row type the importer does not handle
+ * @param {number} accountId - The account ID
+ * @param {string|null} provider - Provider code, or null to use the account's provider
+ * @param {string} csvText - The CSV file contents
+ * @returns {Object|null} Preview with { account_id, provider, file_hash, rows, summary }, or null if the account is not found
+ * @throws {Error} If the provider is not supported or the file cannot be parsed
+ */
+export function previewBrokerImport(accountId, provider, csvText) {
+  const account = getAccountById(accountId);
+  if (!account) return null;
+
+  let providerCode = provider ? String(provider).toLowerCase() : account.provider;
+  if (!providerCode) {
+    const user = getUserById(account.user_id);
+    providerCode = user ? user.provider : null;
+  }
+
+  const parsed = parseBrokerCsv(providerCode, csvText);
+  const index = buildInvestmentIndex();
+
+  const dates = parsed.map(function (r) {
+    return r.date;
+  }).sort();
+  const existing = dates.length > 0 ? loadExistingKeys(accountId, dates[0], dates[dates.length - 1]) : new Map();
+
+  const rows = parsed.map(function (row) {
+    const result = { ...row, investment_id: null, investment_description: null, matched_by: null, status: "new", reason: null };
+
+    if (!row.type) {
+      result.status = "skipped";
+      result.reason = "Unrecognised transaction";
+      return result;
+    }
+
+    if (row.amount <= 0) {
+      result.status = "skipped";
+      result.reason = "No amount";
+      return result;
+    }
+
+    const isTrade = row.type === "buy" || row.type === "sell";
+    if (isTrade || row.type === "dividend" || row.type === "interest") {
+      const match = matchInvestment(row, index);
+      if (match) {
+        result.investment_id = match.investment.id;
+        result.investment_description = match.investment.description;
+        result.matched_by = match.matched_by;
+      } else if (row.type !== "interest") {
+        result.status = "unmatched";
+        result.reason = "No investment matches this ISIN, SEDOL or description";
+        return result;
+      }
+
+      if (!isTrade && match && match.investment.unit_type === "accumulation") {
+        result.status = "skipped";
+        result.reason = "Accumulation units — income is reinvested, not paid as cash";
+        return result;
+      }
+    }
+
+    if (isTrade && row.quantity <= 0) {
+      result.status = "skipped";
+      result.reason = "No quantity";
+      return result;
+    }
+
+    const key = isTrade
+      ? duplicateKey(row.type, row.date, result.investment_id, row.quantity)
+      : duplicateKey(CASH_TRANSACTION_TYPE[row.type], row.date, null, row.amount);
+
+    const remaining = existing.get(key) || 0;
+    if (remaining > 0) {
+      existing.set(key, remaining - 1);
+      result.status = "duplicate";
+      result.reason = "Already recorded";
+    }
+
+    return result;
+  });
+
+  const summary = { total: rows.length, new: 0, duplicate: 0, unmatched: 0, skipped: 0 };
+  for (const row of rows) {
+    summary[row.status]++;
+  }
+
+  const preview = {
+    account_id: accountId,
+    provider: providerCode,
+    provider_name: PROVIDER_FORMATS[providerCode].name,
+    file_hash: createHash("sha256").update(providerCode + "\n" + csvText).digest("hex"),
+    rows: rows,
+    summary: summary,
+  };
+  pendingImports.set(accountId, preview);
+  return preview;
+}
+
+/**
+ * @description Apply a single previewed row to the account. Must be called
+ * inside an open database transaction.
+ * @param {number} accountId - The account ID
+ * @param {string} providerName - Provider name used in notes
+ * @param {Object} row - A previewed row with status 'new'
+ */
+function applyImportRow(accountId, providerName, row) {
+  const notes = ("Imported from " + providerName + (row.reference ? " (" + row.reference + ")" : "")).slice(0, 255);
+
+  if (row.type === "buy" || row.type === "sell") {
+    let holding = getActiveHoldingRaw(accountId, row.investment_id);
+    if (!holding) {
+      if (row.type === "sell") {
+        throw new Error("No holding of " + row.investment_description + " to sell");
+      }
+      holding = createHolding({ account_id: accountId, investment_id: row.investment_id, quantity: 0, average_cost: 0 });
+    }
+
+    // A buy's debit includes its costs; a sale's credit is the proceeds after them
+    const movement = {
+      holding_id: holding.id,
+      movement_date: row.date,
+      quantity: row.quantity,
+      total_consideration: row.type === "buy" ? row.amount : Math.round((row.amount + row.deductible_costs) * 100) / 100,
+      deductible_costs: row.deductible_costs,
+      notes: notes,
+    };
+    if (row.type === "buy") {
+      insertBuyMovement(movement);
+    } else {
+      insertSellMovement(movement);
+    }
+    return;
+  }
+
+  insertCashTransaction({
+    account_id: accountId,
+    transaction_type: CASH_TRANSACTION_TYPE[row.type],
+    transaction_date: row.date,
+    amount: row.amount,
+    notes: row.type === "fee" ? (row.description + " — " + notes).slice(0, 255) : notes,
+    direction: row.type === "fee" ? "debit" : null,
+    investment_id: row.investment_id,
+  });
+}
+
+/**
+ * @description Import the previewed rows of a broker CSV into an account.
+ * The rows committed are those of the account's latest preview, which must
+ * be of the same file (matched by its hash) — the file is not parsed again.
+ * Every 'new' row is applied oldest first, money in before money out on the
+ * same date, in a single database transaction: if any row fails nothing is
+ * imported and the failing row is reported.
+ * @param {number} accountId - The account ID
+ * @param {string} fileHash - The file_hash returned by previewBrokerImport
+ * @returns {Object|null} Result with { imported, summary, failed }, or null if the account is not found.
+ *   failed is null on success, otherwise { row_number, date, type, description, error }.
+ * @throws {Error} If the account has no preview of this file
+ */
+export function commitBrokerImport(accountId, fileHash) {
+  if (!getAccountById(accountId)) return null;
+
+  const preview = pendingImports.get(accountId);
+  if (!preview || preview.file_hash !== fileHash) {
+    throw new Error("The file does not match the last preview — preview it again before importing");
+  }
+
+  const toApply = preview.rows
+    .filter(function (r) {
+      return r.status === "new";
+    })
+    .sort(function (a, b) {
+      if (a.date !== b.date) return a.date < b.date ? -1 : 1;
+      return APPLY_ORDER[a.type] - APPLY_ORDER[b.type];
+    });
+
+  const db = getDatabase();
+  let imported = 0;
+  let failed = null;
+
+  db.exec("BEGIN");
+  for (const row of toApply) {
+    try {
+      applyImportRow(accountId, preview.provider_name, row);
+      imported++;
+    } catch (err) {
+      failed = { row_number: row.row_number, date: row.date, type: row.type, description: row.description, error: err.message };
+      break;
+    }
+  }
+
+  if (failed) {
+    db.exec("ROLLBACK");
+    imported = 0;
+  } else {
+    db.exec("COMMIT");
+    // A preview can only be committed once
+    pendingImports.delete(accountId);
+  }
+
+  return {
+    account_id: accountId,
+    provider: preview.provider,
+    imported: imported,
+    summary: preview.summary,
+    failed: failed,
+  };
+}
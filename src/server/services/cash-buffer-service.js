@@ -0,0 +1,244 @@
+import { getDatabase } from "../db/connection.js";
+import { getAccountById } from "../db/accounts-db.js";
+import { insertSellMovement, getMovementById } from "../db/holding-movements-db.js";
+import { getCashBufferConfig } from "../config.js";
+import { previewDrawdowns } from "./drawdown-processor.js";
+import { getPortfolioSummary } from "./portfolio-service.js";
+import { addMonths } from "./tax-year-utils.js";
+
+/** @description Note recorded against sales committed from a cash buffer plan */
+const CASH_BUFFER_NOTE = "Cash buffer for drawdowns";
+
+/**
+ * @description Round a decimal to 2 decimal places (pence).
+ * @param {number} value - The value to round
+ * @returns {number} The value rounded to pence
+ */
+function roundToPence(value) {
+  return Math.round(value * 100) / 100;
+}
+
+/**
+ * @description Work out how many units of a holding to sell to raise an
+ * amount at its latest GBP price. Quantities are rounded up, to whole units
+ * when the holding is held in whole units and to 4 decimal places otherwise,
+ * and never exceed the quantity held.
+ * @param {Object} holding - Holding from the portfolio summary (quantity and value_gbp)
+ * @param {number} amount - Amount to raise in GBP
+ * @returns {{ quantity: number, proceeds: number }} Units to sell and estimated proceeds
+ */
+function sellQuantityFor(holding, amount) {
+  const unitPrice = holding.value_gbp / holding.quantity;
+  const step = Number.isInteger(holding.quantity) ? 1 : 10000;
+  const quantity = Math.min(holding.quantity, Math.ceil((amount / unitPrice) * step - 1e-9) / step);
+  return { quantity: quantity, proceeds: roundToPence(quantity * unitPrice) };
+}
+
+/**
+ * @description Plan fund sales to keep a SIPP's cash above its minimum while
+ * upcoming drawdowns are paid. Drawdowns due up to the end of the forecast
+ * period (including any due but not yet processed) are taken from the active
+ * schedules; where the running cash balance would fall below the minimum, sells
+ * are proposed at latest prices to cover the shortfall — either working through
+ * the chosen holdings in priority order, or across them in proportion to value.
+ *
+ * @param {number} accountId - The SIPP account ID
+ * @param {Object} [options] - Planning options; omitted values come from the cashBuffer config
+ * @param {number} [options.months] - Forecast period in months
+ * @param {string} [options.method] - 'priority' or 'pro_rata'
+ * @param {number[]} [options.holding_ids] - Holdings that may be sold, in priority order; all
+ *   holdings, largest first, if omitted
+ * @param {number} [options.minimum_cash] - Cash to keep in the account; defaults to its minimum cash warning level
+ * @param {string} [options.today] - ISO-8601 date to plan from (for testing)
+ * @returns {Object|null} The plan (forecast drawdowns, shortfall, the holdings that could be sold
+ *   largest first, and the proposed sales), or null if the account is not found or is not a SIPP
+ */
+export function planCashBuffer(accountId, options = {}) {
+  const account = getAccountById(accountId);
+  if (!account || account.account_type !== "sipp") return null;
+
+  const config = getCashBufferConfig();
+  const months = options.months || config.forecastMonths;
+  const method = options.method || config.method;
+  const minimumCash = options.minimum_cash !== undefined && options.minimum_cash !== null ? options.minimum_cash : account.warn_cash || 0;
+  const today = options.today || new Date().toISOString().slice(0, 10);
+  const horizonDate = addMonths(today, months);
+
+  // Forecast the drawdowns and the running cash balance
+  const drawdowns = previewDrawdowns(horizonDate).would_process.filter(function (item) {
+    return item.account_id === accountId;
+  });
+  drawdowns.sort(function (a, b) {
+    return a.date.localeCompare(b.date);
+  });
+
+  let balance = account.cash_balance;
+  let lowestBalance = balance;
+  let firstShortfallDate = null;
+  let totalDrawdowns = 0;
+  const forecast = drawdowns.map(function (item) {
+    balance = roundToPence(balance - item.amount);
+    totalDrawdowns += item.amount;
+    if (balance < lowestBalance) lowestBalance = balance;
+    if (balance < minimumCash && !firstShortfallDate) firstShortfallDate = item.date;
+    return { date: item.date, amount: item.amount, balance_after: balance };
+  });
+
+  const shortfall = roundToPence(Math.max(0, minimumCash - lowestBalance));
+
+  // Choose the holdings that may be sold, in priority order, ignoring any without a price
+  const accountSummary = getPortfolioSummary(account.user_id).accounts.find(function (a) {
+    return a.id === accountId;
+  });
+  const available = accountSummary.holdings.filter(function (h) {
+    return h.quantity > 0 && h.value_gbp > 0;
+  });
+  available.sort(function (a, b) {
+    return b.value_gbp - a.value_gbp;
+  });
+  let candidates = available;
+  if (options.holding_ids && options.holding_ids.length > 0) {
+    candidates = options.holding_ids
+      .map(function (id) {
+        return available.find(function (h) {
+          return h.holding_id === id;
+        });
+      })
+      .filter(Boolean);
+  }
+
+  const proposals = [];
+  if (shortfall > 0 && candidates.length > 0) {
+    if (method === "pro_rata") {
+      const totalValue = candidates.reduce(function (sum, h) {
+        return sum + h.value_gbp;
+      }, 0);
+      for (const holding of candidates) {
+        const share = Math.min(holding.value_gbp, (shortfall * holding.value_gbp) / totalValue);
+        addProposal(proposals, holding, share);
+      }
+    } else {
+      let remaining = shortfall;
+      for (const holding of candidates) {
+        if (remaining <= 0) break;
+        const proposal = addProposal(proposals, holding, Math.min(remaining, holding.value_gbp));
+        remaining = roundToPence(remaining - (proposal ? proposal.estimated_proceeds : 0));
+      }
+    }
+  }
+
+  const proposedTotal = roundToPence(
+    proposals.reduce(function (sum, p) {
+      return sum + p.estimated_proceeds;
+    }, 0),
+  );
+
+  return {
+    account_id: account.id,
+    account_ref: account.account_ref,
+    plan_date: today,
+    horizon_date: horizonDate,
+    method: method,
+    cash_balance: account.cash_balance,
+    minimum_cash: minimumCash,
+    drawdowns: forecast,
+    total_drawdowns: roundToPence(totalDrawdowns),
+    lowest_balance: roundToPence(lowestBalance),
+    first_shortfall_date: firstShortfallDate,
+    shortfall: shortfall,
+    holdings: available.map(function (h) {
+      return { holding_id: h.holding_id, description: h.description, value_gbp: h.value_gbp };
+    }),
+    proposals: proposals,
+    proposed_total: proposedTotal,
+    unfunded: roundToPence(Math.max(0, shortfall - proposedTotal)),
+  };
+}
+
+/**
+ * @description Add a sell proposal raising an amount from a holding.
+ * @param {Object[]} proposals - Proposals list to append to
+ * @param {Object} holding - Holding from the portfolio summary
+ * @param {number} amount - Amount to raise in GBP
+ * @returns {Object|null} The proposal added, or null if the amount rounds to nothing
+ */
+function addProposal(proposals, holding, amount) {
+  if (amount <= 0) return null;
+  const sale = sellQuantityFor(holding, amount);
+  if (sale.quantity <= 0) return null;
+
+  const proposal = {
+    holding_id: holding.holding_id,
+    investment_id: holding.investment_id,
+    description: holding.description,
+    quantity_held: holding.quantity,
+    value_gbp: holding.value_gbp,
+    quantity: sale.quantity,
+    estimated_proceeds: sale.proceeds,
+  };
+  proposals.push(proposal);
+  return proposal;
+}
+
+/**
+ * @description Commit cash buffer sales as sell movements on a SIPP, crediting
+ * the proceeds to the account's cash. All sales are checked against the
+ * account's holdings before any is recorded, with the quantities of several
+ * sales from one holding added together, and are then recorded in a single
+ * database transaction so that either all or none are committed.
+ * @param {number} accountId - The SIPP account ID
+ * @param {Object[]} sales - Sales to record
+ * @param {number} sales[].holding_id - Holding to sell from (must belong to the account)
+ * @param {number} sales[].quantity - Units to sell
+ * @param {number} sales[].total_consideration - Sale proceeds in GBP
+ * @param {string} movementDate - ISO-8601 date (YYYY-MM-DD) of the sales
+ * @returns {Object[]} The created sell movements
+ * @throws {Error} If a holding is not in the account or holds too few units
+ */
+export function commitCashBufferSales(accountId, sales, movementDate) {
+  const account = getAccountById(accountId);
+  const accountSummary = getPortfolioSummary(account.user_id).accounts.find(function (a) {
+    return a.id === accountId;
+  });
+
+  /** @type {Map<number, number>} Units to sell, by holding ID */
+  const quantityByHolding = new Map();
+  for (const sale of sales) {
+    quantityByHolding.set(sale.holding_id, (quantityByHolding.get(sale.holding_id) || 0) + sale.quantity);
+  }
+
+  for (const [holdingId, quantity] of quantityByHolding) {
+    const holding = accountSummary.holdings.find(function (h) {
+      return h.holding_id === holdingId;
+    });
+    if (!holding) {
+      throw new Error("Holding " + holdingId + " is not in this account");
+    }
+    if (quantity > holding.quantity) {
+      throw new Error("Insufficient holding quantity for " + holding.description);
+    }
+  }
+
+  const db = getDatabase();
+  const movementIds = [];
+  db.exec("BEGIN");
+  try {
+    for (const sale of sales) {
+      movementIds.push(
+        insertSellMovement({
+          holding_id: sale.holding_id,
+          movement_date: movementDate,
+          quantity: sale.quantity,
+          total_consideration: sale.total_consideration,
+          notes: CASH_BUFFER_NOTE,
+        }),
+      );
+    }
+    db.exec("COMMIT");
+  } catch (err) {
+    db.exec("ROLLBACK");
+    throw err;
+  }
+
+  return movementIds.map(getMovementById);
+}
@@ -0,0 +1,578 @@
+import { getDatabase } from "../db/connection.js";
+import { getUserById } from "../db/users-db.js";
+import { getCgtConfig } from "../config.js";
+import { CURRENCY_SCALE_FACTOR } from "../../shared/server-constants.js";
+import { getTaxYearForDate, getTaxYearByStartYear, addDays } from "./tax-year-utils.js";
+
+/**
+ * @description Calendar year in which the first supported tax year starts.
+ * From 6 April 2008 disposals are matched as here and taxed without indexation
+ * or taper relief; earlier years followed different rules and are not reported.
+ * @type {number}
+ */
+const FIRST_SUPPORTED_START_YEAR = 2008;
+
+/**
+ * @description Published HMRC annual exempt amounts keyed by the calendar
+ * year in which the tax year starts. Later years not listed fall back to the
+ * configured cgt.annualExemptAmount.
+ * @type {Object<number, number>}
+ */
+const HISTORIC_ANNUAL_EXEMPT_AMOUNTS = {
+  2008: 9600,
+  2009: 10100,
+  2010: 10100,
+  2011: 10600,
+  2012: 10600,
+  2013: 10900,
+  2014: 11000,
+  2015: 11100,
+  2016: 11100,
+  2017: 11300,
+  2018: 11700,
+  2019: 12000,
+  2020: 12300,
+  2021: 12300,
+  2022: 12300,
+  2023: 6000,
+  2024: 3000,
+};
+
+/**
+ * @description Number of days after a disposal during which a reacquisition
+ * is matched under the bed and breakfast rule (TCGA 1992 s106A).
+ * @type {number}
+ */
+const BED_AND_BREAKFAST_DAYS = 30;
+
+/**
+ * @description Tiny tolerance for floating-point quantity comparisons.
+ * @type {number}
+ */
+const QUANTITY_EPSILON = 1e-9;
+
+/**
+ * @description Round a decimal to 2 decimal places (pence).
+ * @param {number} value - The value to round
+ * @returns {number} The value rounded to pence
+ */
+function roundToPence(value) {
+  return Math.round(value * 100) / 100;
+}
+
+/**
+ * @description Unscale a stored integer value (divide by CURRENCY_SCALE_FACTOR).
+ * @param {number} scaledValue - The scaled integer value from the database
+ * @returns {number} The decimal value
+ */
+function unscale(scaledValue) {
+  return (scaledValue || 0) / CURRENCY_SCALE_FACTOR;
+}
+
+/**
+ * @description Get the annual exempt amount for a tax year.
+ * @param {number} startYear - Calendar year in which the tax year starts
+ * @returns {number|null} The annual exempt amount in GBP, or null for a tax year
+ *   before 2008/2009, which is not supported
+ */
+export function getAnnualExemptAmount(startYear) {
+  if (startYear < FIRST_SUPPORTED_START_YEAR) return null;
+  if (HISTORIC_ANNUAL_EXEMPT_AMOUNTS[startYear] !== undefined) {
+    return HISTORIC_ANNUAL_EXEMPT_AMOUNTS[startYear];
+  }
+  return getCgtConfig().annualExemptAmount;
+}
+
+/**
+ * @description Apply the HMRC share identification rules to the events for a
+ * single investment held by one person. Disposals are matched, in order, with:
+ *   1. acquisitions on the same day (TCGA 1992 s105),
+ *   2. acquisitions in the following 30 days, earliest first (s106A),
+ *   3. the Section 104 pool of everything else (s104).
+ *
+ * Acquisitions on the same day are treated as one acquisition, and disposals
+ * on the same day as one disposal (s105(1)); each disposal's share of the
+ * day's allowable cost is in proportion to its quantity.
+ *
+ * Events are plain objects with a type of "opening", "acquisition",
+ * "disposal", "split" or "transfer_out". Opening positions (holdings that
+ * existed before any recorded movement) go straight into the pool.
+ *
+ * @param {Object[]} events - Events for one investment, each with { seq, type, date, account_id, quantity, cost, proceeds }
+ * @returns {{ disposals: Object[], pool: { quantity: number, cost: number }, warnings: string[] }}
+ */
+export function matchDisposals(events) {
+  const sorted = events.slice().sort(function (a, b) {
+    if (a.date !== b.date) return a.date < b.date ? -1 : 1;
+    return a.seq - b.seq;
+  });
+
+  // Aggregate acquisitions by day — same-day acquisitions are treated as one
+  const acquisitionsByDate = {};
+  for (const ev of sorted) {
+    if (ev.type !== "acquisition") continue;
+    if (!acquisitionsByDate[ev.date]) {
+      acquisitionsByDate[ev.date] = { date: ev.date, quantity: 0, cost: 0, remaining: 0 };
+    }
+    acquisitionsByDate[ev.date].quantity += ev.quantity;
+    acquisitionsByDate[ev.date].cost += ev.cost;
+    acquisitionsByDate[ev.date].remaining += ev.quantity;
+  }
+  const acquisitionDates = Object.keys(acquisitionsByDate).sort();
+
+  // Aggregate disposals by day — same-day disposals are treated as one
+  const disposalsByDate = {};
+  const disposals = [];
+  for (const ev of sorted) {
+    if (ev.type !== "disposal") continue;
+    if (!disposalsByDate[ev.date]) {
+      disposalsByDate[ev.date] = { date: ev.date, quantity: 0, remaining: 0, allowable_cost: 0, matches: [], events: [] };
+      disposals.push(disposalsByDate[ev.date]);
+    }
+    disposalsByDate[ev.date].quantity += ev.quantity;
+    disposalsByDate[ev.date].remaining += ev.quantity;
+    disposalsByDate[ev.date].events.push(ev);
+  }
+
+  // Pass 1: same-day rule
+  for (const d of disposals) {
+    const acq = acquisitionsByDate[d.date];
+    if (!acq || acq.remaining <= QUANTITY_EPSILON) continue;
+    const take = Math.min(d.remaining, acq.remaining);
+    const cost = (acq.cost * take) / acq.quantity;
+    acq.remaining -= take;
+    d.remaining -= take;
+    d.allowable_cost += cost;
+    d.matches.push({ rule: "same_day", acquisition_date: acq.date, quantity: take, cost: cost });
+  }
+
+  // Pass 2: bed and breakfast rule — earliest disposal first, earliest acquisition first
+  for (const d of disposals) {
+    if (d.remaining <= QUANTITY_EPSILON) continue;
+    const windowEnd = addDays(d.date, BED_AND_BREAKFAST_DAYS);
+    for (const date of acquisitionDates) {
+      if (date <= d.date || date > windowEnd) continue;
+      const acq = acquisitionsByDate[date];
+      if (acq.remaining <= QUANTITY_EPSILON) continue;
+      const take = Math.min(d.remaining, acq.remaining);
+      const cost = (acq.cost * take) / acq.quantity;
+      acq.remaining -= take;
+      d.remaining -= take;
+      d.allowable_cost += cost;
+      d.matches.push({ rule: "bed_and_breakfast", acquisition_date: acq.date, quantity: take, cost: cost });
+      if (d.remaining <= QUANTITY_EPSILON) break;
+    }
+  }
+
+  // Pass 3: replay chronologically through the Section 104 pool
+  const pool = { quantity: 0, cost: 0 };
+  const accountQuantities = {};
+  const acquisitionPoolAdded = {};
+  const warnings = [];
+
+  for (const ev of sorted) {
+    const acctQty = accountQuantities[ev.account_id] || 0;
+
+    if (ev.type === "opening") {
+      pool.quantity += ev.quantity;
+      pool.cost += ev.cost;
+      accountQuantities[ev.account_id] = acctQty + ev.quantity;
+    } else if (ev.type === "acquisition") {
+      accountQuantities[ev.account_id] = acctQty + ev.quantity;
+      // Only the unmatched part of the day's acquisitions enters the pool (once per day)
+      if (!acquisitionPoolAdded[ev.date]) {
+        const acq = acquisitionsByDate[ev.date];
+        if (acq.remaining > QUANTITY_EPSILON) {
+          pool.quantity += acq.remaining;
+          pool.cost += (acq.cost * acq.remaining) / acq.quantity;
+        }
+        acquisitionPoolAdded[ev.date] = true;
+      }
+    } else if (ev.type === "split") {
+      // Share reorganisation: quantity changes, pool cost is unchanged
+      pool.quantity += ev.quantity - acctQty;
+      accountQuantities[ev.account_id] = ev.quantity;
+    } else if (ev.type === "transfer_out") {
+      // Corporate action replacement: the account's shares leave the pool at pool cost
+      // (the replacement investment starts its own pool with the carried-over book cost)
+      if (pool.quantity > QUANTITY_EPSILON && acctQty > 0) {
+        const take = Math.min(acctQty, pool.quantity);
+        pool.cost -= (pool.cost * take) / pool.quantity;
+        pool.quantity -= take;
+      }
+      accountQuantities[ev.account_id] = 0;
+    } else if (ev.type === "disposal") {
+      accountQuantities[ev.account_id] = acctQty - ev.quantity;
+      // The day's disposals are matched with the pool together, at the first of them
+      const d = disposalsByDate[ev.date];
+      if (d.remaining > QUANTITY_EPSILON) {
+        const take = Math.min(d.remaining, Math.max(pool.quantity, 0));
+        if (take > QUANTITY_EPSILON) {
+          const cost = (pool.cost * take) / pool.quantity;
+          pool.cost -= cost;
+          pool.quantity -= take;
+          d.allowable_cost += cost;
+          d.remaining -= take;
+          d.matches.push({ rule: "section_104", acquisition_date: null, quantity: take, cost: cost });
+        }
+        if (d.remaining > QUANTITY_EPSILON) {
+          warnings.push("Disposal on " + ev.date + " exceeds the recorded pool by " + roundToPence(d.remaining) + " units — treated as nil cost");
+          d.matches.push({ rule: "unmatched", acquisition_date: null, quantity: d.remaining, cost: 0 });
+          d.remaining = 0;
+        }
+      }
+    }
+
+    // Keep the pool tidy once it has been fully disposed of
+    if (pool.quantity <= QUANTITY_EPSILON) {
+      pool.quantity = 0;
+      pool.cost = 0;
+    }
+  }
+
+  // Share each day's allowable cost and matches between its disposals by quantity
+  const results = [];
+  for (const d of disposals) {
+    for (const ev of d.events) {
+      const share = ev.quantity / d.quantity;
+      const proceeds = roundToPence(ev.proceeds);
+      const allowableCost = roundToPence(d.allowable_cost * share);
+      results.push({
+        movement_id: ev.movement_id || null,
+        account_id: ev.account_id,
+        disposal_date: ev.date,
+        quantity: ev.quantity,
+        gross_proceeds: roundToPence(ev.gross_proceeds !== undefined ? ev.gross_proceeds : ev.proceeds),
+        disposal_costs: roundToPence(ev.disposal_costs || 0),
+        proceeds: proceeds,
+        allowable_cost: allowableCost,
+        gain: roundToPence(proceeds - allowableCost),
+        matches: d.matches.map(function (m) {
+          return { rule: m.rule, acquisition_date: m.acquisition_date, quantity: m.quantity * share, cost: roundToPence(m.cost * share) };
+        }),
+      });
+    }
+  }
+
+  return {
+    disposals: results,
+    pool: { quantity: pool.quantity, cost: roundToPence(pool.cost) },
+    warnings: warnings,
+  };
+}
+
+/**
+ * @description Work out the position held in an account before its first
+ * recorded movement by walking the movement history backwards from the
+ * latest holding row. Holdings entered directly (rather than via buys) are
+ * treated as an opening acquisition on the first holding row's effective_from
+ * date, or the first movement date if that is earlier.
+ * @param {Database} db - The database connection
+ * @param {number} accountId - The account ID
+ * @param {number} investmentId - The investment ID
+ * @param {Object[]} movements - The pair's movement rows (raw/scaled), oldest first
+ * @returns {{ date: string, quantity: number, cost: number }|null} Opening position, or null if none
+ */
+function deriveOpeningPosition(db, accountId, investmentId, movements) {
+  const rows = db
+    .query(
+      `SELECT id, quantity, average_cost, effective_from, effective_to
+       FROM holdings
+       WHERE account_id = ? AND investment_id = ?
+       ORDER BY effective_from, id`,
+    )
+    .all(accountId, investmentId);
+
+  if (rows.length === 0) return null;
+
+  const rowsById = {};
+  for (const r of rows) {
+    rowsById[r.id] = r;
+  }
+
+  const active = rows.find(function (r) { return r.effective_to === null; });
+  let quantity = active ? unscale(active.quantity) : 0;
+  let bookCost = active ? quantity * unscale(active.average_cost) : 0;
+
+  for (let i = movements.length - 1; i >= 0; i--) {
+    const m = movements[i];
+    if (m.movement_type === "buy") {
+      quantity -= unscale(m.quantity);
+      bookCost -= unscale(m.book_cost);
+    } else if (m.movement_type === "sell") {
+      quantity += unscale(m.quantity);
+      bookCost += unscale(m.book_cost);
+    } else if (m.movement_type === "adjustment") {
+      // The referenced row holds the pre-split quantity unless it was updated in place
+      const ref = rowsById[m.holding_id];
+      if (ref && ref.quantity !== m.quantity) {
+        quantity = unscale(ref.quantity);
+      }
+    } else if (m.movement_type === "replacement") {
+      const ref = rowsById[m.holding_id];
+      quantity = ref ? unscale(ref.quantity) : quantity;
+      bookCost = unscale(m.book_cost);
+    }
+  }
+
+  if (quantity <= QUANTITY_EPSILON) return null;
+
+  // The opening position must precede the first movement, even if that movement was backdated
+  let openingDate = rows[0].effective_from;
+  if (movements.length > 0 && movements[0].movement_date < openingDate) {
+    openingDate = movements[0].movement_date;
+  }
+
+  return {
+    date: openingDate,
+    quantity: quantity,
+    cost: Math.max(bookCost, 0),
+  };
+}
+
+/**
+ * @description Build the CGT event list for every investment held in a
+ * user's trading accounts. ISA and SIPP accounts are excluded because
+ * gains within them are not chargeable.
+ * @param {number} userId - The user ID
+ * @returns {Object<number, { description: string, events: Object[] }>} Events keyed by investment ID
+ */
+function buildEventsByInvestment(userId) {
+  const db = getDatabase();
+
+  const movements = db
+    .query(
+      `SELECT hm.id, hm.holding_id, hm.movement_type, hm.movement_date, hm.quantity,
+              hm.movement_value, hm.book_cost, hm.deductible_costs,
+              h.account_id, h.investment_id
+       FROM holding_movements hm
+       JOIN holdings h ON hm.holding_id = h.id
+       JOIN accounts a ON h.account_id = a.id
+       WHERE a.user_id = ? AND a.account_type = 'trading'
+       ORDER BY hm.movement_date, hm.id`,
+    )
+    .all(userId);
+
+  const pairs = db
+    .query(
+      `SELECT DISTINCT h.account_id, h.investment_id, i.description AS investment_description
+       FROM holdings h
+       JOIN accounts a ON h.account_id = a.id
+       JOIN investments i ON h.investment_id = i.id
+       WHERE a.user_id = ? AND a.account_type = 'trading'`,
+    )
+    .all(userId);
+
+  const byInvestment = {};
+  let seq = 0;
+
+  for (const pair of pairs) {
+    if (!byInvestment[pair.investment_id]) {
+      byInvestment[pair.investment_id] = { description: pair.investment_description, events: [] };
+    }
+
+    const pairMovements = movements.filter(function (m) {
+      return m.account_id === pair.account_id && m.investment_id === pair.investment_id;
+    });
+
+    const opening = deriveOpeningPosition(db, pair.account_id, pair.investment_id, pairMovements);
+    if (opening) {
+      byInvestment[pair.investment_id].events.push({
+        seq: seq++,
+        type: "opening",
+        date: opening.date,
+        account_id: pair.account_id,
+        quantity: opening.quantity,
+        cost: opening.cost,
+      });
+    }
+  }
+
+  for (const m of movements) {
+    const target = byInvestment[m.investment_id];
+    if (!target) continue;
+
+    const base = {
+      seq: seq++,
+      movement_id: m.id,
+      date: m.movement_date,
+      account_id: m.account_id,
+      quantity: unscale(m.quantity),
+    };
+
+    if (m.movement_type === "buy") {
+      // Allowable cost is the full consideration paid, including incidental costs
+      base.type = "acquisition";
+      base.cost = unscale(m.movement_value);
+    } else if (m.movement_type === "sell") {
+      base.type = "disposal";
+      base.gross_proceeds = unscale(m.movement_value);
+      base.disposal_costs = unscale(m.deductible_costs);
+      base.proceeds = base.gross_proceeds - base.disposal_costs;
+    } else if (m.movement_type === "adjustment") {
+      base.type = "split";
+    } else if (m.movement_type === "replacement") {
+      base.type = "transfer_out";
+    } else {
+      continue;
+    }
+
+    target.events.push(base);
+  }
+
+  return byInvestment;
+}
+
+/**
+ * @description Calculate capital gains for a user's trading accounts, tax
+ * year by tax year. Every disposal is matched under the same-day, 30-day
+ * and Section 104 rules; yearly totals apply brought-forward losses and
+ * the annual exempt amount. The current tax year is always included so
+ * the remaining exempt amount is visible before any disposal is made. Tax
+ * years before 2008/2009 are not reported, with a warning if they have disposals.
+ * @param {number} userId - The user ID
+ * @param {number|null} [taxYearStart=null] - Restrict output to the tax year starting in this calendar year
+ * @returns {Object|null} CGT report, or null if the user is not found
+ */
+export function getCapitalGainsReport(userId, taxYearStart = null) {
+  const user = getUserById(userId);
+  if (!user) return null;
+
+  const db = getDatabase();
+  const byInvestment = buildEventsByInvestment(userId);
+
+  const accountRefs = {};
+  const accountRows = db.query("SELECT id, account_ref FROM accounts WHERE user_id = ?").all(userId);
+  for (const a of accountRows) {
+    accountRefs[a.id] = a.account_ref;
+  }
+
+  const allDisposals = [];
+  const pools = [];
+  let warnings = [];
+
+  for (const investmentId of Object.keys(byInvestment)) {
+    const entry = byInvestment[investmentId];
+    const result = matchDisposals(entry.events);
+
+    for (const d of result.disposals) {
+      d.investment_id = Number(investmentId);
+      d.investment_description = entry.description;
+      d.account_ref = accountRefs[d.account_id] || null;
+      allDisposals.push(d);
+    }
+
+    if (result.pool.quantity > 0) {
+      pools.push({
+        investment_id: Number(investmentId),
+        investment_description: entry.description,
+        quantity: result.pool.quantity,
+        cost: result.pool.cost,
+        average_cost: result.pool.quantity > 0 ? result.pool.cost / result.pool.quantity : 0,
+      });
+    }
+
+    warnings = warnings.concat(result.warnings.map(function (w) { return entry.description + ": " + w; }));
+  }
+
+  allDisposals.sort(function (a, b) {
+    if (a.disposal_date !== b.disposal_date) return a.disposal_date < b.disposal_date ? -1 : 1;
+    return (a.movement_id || 0) - (b.movement_id || 0);
+  });
+
+  // Group disposals by tax year, always including the current year
+  const currentYear = getTaxYearForDate(new Date().toISOString().slice(0, 10));
+  const currentStartYear = Number(currentYear.start.slice(0, 4));
+  let firstStartYear = currentStartYear;
+  for (const d of allDisposals) {
+    const ty = getTaxYearForDate(d.disposal_date);
+    d.tax_year = ty.label;
+    const startYear = Number(ty.start.slice(0, 4));
+    if (startYear < firstStartYear) firstStartYear = startYear;
+  }
+  let lastStartYear = currentStartYear;
+  for (const d of allDisposals) {
+    const startYear = Number(d.tax_year.slice(0, 4));
+    if (startYear > lastStartYear) lastStartYear = startYear;
+  }
+
+  // Years before 2008/2009 had different matching rules and reliefs, so are left out
+  if (firstStartYear < FIRST_SUPPORTED_START_YEAR) {
+    const earliest = getTaxYearByStartYear(FIRST_SUPPORTED_START_YEAR);
+    warnings.push("Disposals before " + earliest.start + " are not reported — capital gains before the " + earliest.label + " tax year were worked out under earlier rules");
+    firstStartYear = FIRST_SUPPORTED_START_YEAR;
+  }
+
+  const taxYears = [];
+  let lossesCarried = 0;
+
+  for (let year = firstStartYear; year <= lastStartYear; year++) {
+    const ty = getTaxYearByStartYear(year);
+    const yearDisposals = allDisposals.filter(function (d) { return d.tax_year === ty.label; });
+
+    let gains = 0;
+    let losses = 0;
+    let proceeds = 0;
+    let cost = 0;
+    for (const d of yearDisposals) {
+      proceeds += d.proceeds;
+      cost += d.allowable_cost;
+      if (d.gain >= 0) gains += d.gain;
+      else losses += -d.gain;
+    }
+
+    const netGain = gains - losses;
+    const exemptAmount = getAnnualExemptAmount(year);
+    const lossesBroughtForward = lossesCarried;
+
+    // Brought-forward losses only reduce net gains down to the annual exempt amount
+    let lossesUsed = 0;
+    if (netGain > exemptAmount && lossesCarried > 0) {
+      lossesUsed = Math.min(lossesCarried, netGain - exemptAmount);
+    }
+    lossesCarried -= lossesUsed;
+    if (netGain < 0) {
+      lossesCarried += -netGain;
+    }
+
+    const netAfterLosses = netGain - lossesUsed;
+    const exemptUsed = Math.min(Math.max(netAfterLosses, 0), exemptAmount);
+
+    taxYears.push({
+      label: ty.label,
+      start: ty.start,
+      end: ty.end,
+      disposal_count: yearDisposals.length,
+      total_proceeds: roundToPence(proceeds),
+      total_allowable_cost: roundToPence(cost),
+      total_gains: roundToPence(gains),
+      total_losses: roundToPence(losses),
+      net_gain: roundToPence(netGain),
+      losses_brought_forward: roundToPence(lossesBroughtForward),
+      losses_used: roundToPence(lossesUsed),
+      losses_carried_forward: roundToPence(lossesCarried),
+      annual_exempt_amount: exemptAmount,
+      exempt_amount_used: roundToPence(exemptUsed),
+      exempt_amount_remaining: roundToPence(exemptAmount - exemptUsed),
+      taxable_gain: roundToPence(Math.max(netAfterLosses - exemptAmount, 0)),
+      disposals: yearDisposals,
+    });
+  }
+
+  const filteredYears = taxYearStart
+    ? taxYears.filter(function (ty) { return Number(ty.start.slice(0, 4)) === taxYearStart; })
+    : taxYears;
+
+  return {
+    user: {
+      id: user.id,
+      initials: user.initials,
+      first_name: user.first_name,
+      last_name: user.last_name,
+    },
+    current_tax_year: currentYear.label,
+    tax_years: filteredYears,
+    section_104_pools: pools,
+    warnings: warnings,
+  };
+}
@@ -0,0 +1,133 @@
+import { getUserById } from "../db/users-db.js";
+import { getAccountById } from "../db/accounts-db.js";
+import { getDrawdownsBetween } from "../db/cash-transactions-db.js";
+import { getCrystallisationsByAccountId, getTaxFreeCashTakenByUser } from "../db/crystallisations-db.js";
+import { getPensionAllowanceConfig } from "../config.js";
+
+/** @description Share of each crystallisation that can normally be taken tax-free */
+export const TAX_FREE_PROPORTION = 0.25;
+
+/**
+ * @description Round a decimal to 2 decimal places (pence).
+ * @param {number} value - The value to round
+ * @returns {number} The value rounded to pence
+ */
+function roundToPence(value) {
+  return Math.round(value * 100) / 100;
+}
+
+/**
+ * @description Get a user's lump sum allowance: the lifetime limit on tax-free
+ * cash, less the tax-free cash recorded against all their SIPPs. Tax-free cash
+ * taken before the allowance was introduced in April 2024 is counted in full.
+ * @param {number} userId - The user ID
+ * @returns {{ lump_sum_allowance: number, tax_free_cash_taken: number, remaining: number }|null}
+ *   The allowance position, or null if the user is not found
+ */
+export function getLumpSumAllowanceForUser(userId) {
+  if (!getUserById(userId)) return null;
+
+  const allowance = getPensionAllowanceConfig().lumpSumAllowance;
+  const taken = getTaxFreeCashTakenByUser(userId);
+
+  return {
+    lump_sum_allowance: allowance,
+    tax_free_cash_taken: roundToPence(taken),
+    remaining: roundToPence(Math.max(0, allowance - taken)),
+  };
+}
+
+/**
+ * @description Summarise how much of a SIPP has been crystallised. Funds moved
+ * into drawdown, less the drawdowns paid since the first crystallisation, make
+ * up the crystallised fund; the rest of the account's value is uncrystallised.
+ * Investment growth is not split between the two, so the crystallised fund is
+ * capped at the account's value. The tax-free cash still available is a
+ * quarter of the uncrystallised fund, limited by the remaining lump sum allowance.
+ * @param {number} accountId - The SIPP account ID
+ * @param {number|null} [accountValue=null] - Current account value (investments plus cash); when
+ *   omitted, the uncrystallised fund and tax-free cash available are null
+ * @returns {Object|null} The summary, or null if the account is not found or is not a SIPP
+ */
+export function getCrystallisationSummary(accountId, accountValue = null) {
+  const account = getAccountById(accountId);
+  if (!account || account.account_type !== "sipp") return null;
+
+  const events = getCrystallisationsByAccountId(accountId);
+
+  let crystallisedTotal = 0;
+  let taxFreeCash = 0;
+  let movedToDrawdown = 0;
+  for (const event of events) {
+    crystallisedTotal += event.amount_crystallised;
+    taxFreeCash += event.tax_free_cash;
+    movedToDrawdown += event.drawdown_amount;
+  }
+
+  let drawdownsPaid = 0;
+  if (events.length > 0) {
+    for (const tx of getDrawdownsBetween(accountId, events[0].crystallisation_date, "9999-12-31")) {
+      drawdownsPaid += tx.amount;
+    }
+  }
+
+  const hasValue = accountValue !== null && accountValue !== undefined;
+  let crystallisedFund = Math.max(0, movedToDrawdown - drawdownsPaid);
+  if (hasValue) {
+    crystallisedFund = Math.min(crystallisedFund, Math.max(0, accountValue));
+  }
+  const uncrystallisedFund = hasValue ? Math.max(0, accountValue - crystallisedFund) : null;
+
+  const lsa = getLumpSumAllowanceForUser(account.user_id);
+  const taxFreeCashAvailable = hasValue ? Math.min(uncrystallisedFund * TAX_FREE_PROPORTION, lsa.remaining) : null;
+
+  return {
+    account_id: account.id,
+    crystallised_total: roundToPence(crystallisedTotal),
+    tax_free_cash_taken: roundToPence(taxFreeCash),
+    moved_to_drawdown: roundToPence(movedToDrawdown),
+    drawdowns_paid: roundToPence(drawdownsPaid),
+    crystallised_fund: roundToPence(crystallisedFund),
+    uncrystallised_fund: hasValue ? roundToPence(uncrystallisedFund) : null,
+    tax_free_cash_available: hasValue ? roundToPence(taxFreeCashAvailable) : null,
+    lump_sum_allowance: lsa,
+    events: events,
+  };
+}
+
+/**
+ * @description Check a proposed tax-free cash payment against the user's
+ * remaining lump sum allowance.
+ * @param {number} accountId - The SIPP account ID
+ * @param {number} taxFreeCash - Proposed tax-free cash in GBP
+ * @returns {{ remaining: number, exceeds: boolean }|null} The check result, or null if the account is not found or is not a SIPP
+ */
+export function checkTaxFreeCash(accountId, taxFreeCash) {
+  const account = getAccountById(accountId);
+  if (!account || account.account_type !== "sipp") return null;
+
+  const lsa = getLumpSumAllowanceForUser(account.user_id);
+  return {
+    remaining: lsa.remaining,
+    exceeds: roundToPence(taxFreeCash) > lsa.remaining,
+  };
+}
+
+/**
+ * @description Check a proposed crystallisation against the SIPP's
+ * uncrystallised fund: the tax-free cash and the amount moved into drawdown
+ * together cannot exceed what has not yet been crystallised.
+ * @param {number} accountId - The SIPP account ID
+ * @param {number} amountCrystallised - Proposed tax-free cash plus drawdown amount in GBP
+ * @param {number} accountValue - Current account value (investments plus cash)
+ * @returns {{ uncrystallised_fund: number, exceeds: boolean }|null} The check result, or null if the account is not found or is not a SIPP
+ */
+export function checkAmountCrystallised(accountId, amountCrystallised, accountValue) {
+  const summary = getCrystallisationSummary(accountId, accountValue);
+  if (!summary) return null;
+
+  return {
+    uncrystallised_fund: summary.uncrystallised_fund,
+    exceeds: roundToPence(amountCrystallised) > summary.uncrystallised_fund,
+  };
+}
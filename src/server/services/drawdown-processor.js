@@ -1,6 +1,8 @@
 import { getActiveDrawdownSchedules, getDueDrawdownDates } from "../db/drawdown-schedules-db.js";
 import { createCashTransaction, drawdownExistsForDate } from "../db/cash-transactions-db.js";
 import { getAccountById } from "../db/accounts-db.js";
+import { calculateDrawdownTax } from "./drawdown-tax-service.js";
+import { getTaxYearForDate } from "./tax-year-utils.js";
 
 /**
  * @description Process all due drawdowns on app startup. For each active
@@ -12,9 +14,16 @@ import { getAccountById } from "../db/accounts-db.js";
  * silently skipped. If a drawdown would cause the cash balance to go
  * negative, a warning is logged but the drawdown is still processed.
  *
+ * The gross amount is debited from the account. PAYE is worked out from the
+ * schedule's tax treatment and recorded against each drawdown as tax withheld.
+ * Escalating schedules pay the amount that applies on each trigger date.
+ * Drawdowns after an index-linked rise that cannot be worked out yet are held
+ * back, with a warning, and recorded at the risen amount on a later run once
+ * the index has been updated.
+ *
  * @param {string} [todayStr] - Optional ISO-8601 date string (YYYY-MM-DD) to
  *   use as "today". Defaults to the current date. Useful for testing.
- * @returns {{ processed: number, skipped: number, warnings: string[] }}
+ * @returns {{ processed: number, skipped: number, held_back: number, warnings: string[] }}
  *   Summary of what happened during processing
  */
 export function processDrawdowns(todayStr) {
@@ -25,60 +34,84 @@ export function processDrawdowns(todayStr) {
   const activeSchedules = getActiveDrawdownSchedules();
 
   if (activeSchedules.length === 0) {
-    return { processed: 0, skipped: 0, warnings: [] };
+    return { processed: 0, skipped: 0, held_back: 0, warnings: [] };
   }
 
   let processed = 0;
   let skipped = 0;
+  let heldBack = 0;
   const warnings = [];
 
   for (const schedule of activeSchedules) {
     const dueDates = getDueDrawdownDates(schedule, todayStr);
+    let pendingRise = null;
+
+    for (const due of dueDates) {
+      const triggerDate = due.date;
+      const amount = due.amount;
 
-    for (const triggerDate of dueDates) {
       // Deduplication: check if this drawdown has already been created
       if (drawdownExistsForDate(schedule.account_id, triggerDate)) {
         skipped++;
         continue;
       }
 
+      // Hold back drawdowns whose amount depends on index values not yet loaded
+      if (due.pending_rise) {
+        pendingRise = due.pending_rise;
+        heldBack++;
+        continue;
+      }
+
       // Check if balance will go negative and log a warning
       const account = getAccountById(schedule.account_id);
-      if (account && account.cash_balance < schedule.amount) {
-        const msg = `[Drawdown] Warning: Account ${account.account_ref} (ID ${schedule.account_id}) ` + `balance £${account.cash_balance.toFixed(2)} is less than drawdown £${schedule.amount.toFixed(2)} ` + `on ${triggerDate}. Balance will go negative.`;
+      if (account && account.cash_balance < amount) {
+        const msg = `[Drawdown] Warning: Account ${account.account_ref} (ID ${schedule.account_id}) ` + `balance £${account.cash_balance.toFixed(2)} is less than drawdown £${amount.toFixed(2)} ` + `on ${triggerDate}. Balance will go negative.`;
         warnings.push(msg);
         console.warn(msg);
       }
 
-      // Create the drawdown transaction (deducts from cash balance)
+      // Work out PAYE, then create the drawdown transaction (deducts the gross from cash balance)
+      const tax = calculateDrawdownTax(schedule, triggerDate, amount);
       createCashTransaction({
         account_id: schedule.account_id,
         transaction_type: "drawdown",
         transaction_date: triggerDate,
-        amount: schedule.amount,
+        amount: amount,
         notes: schedule.notes || `Drawdown (${schedule.frequency})`,
+        tax_withheld: tax.tax_withheld,
+        tax_code: tax.tax_code,
       });
 
       processed++;
-      console.log(`[Drawdown] Created £${schedule.amount.toFixed(2)} drawdown for account ${schedule.account_id} on ${triggerDate}`);
+      console.log(`[Drawdown] Created £${amount.toFixed(2)} drawdown (tax £${tax.tax_withheld.toFixed(2)}, net £${tax.net.toFixed(2)}) for account ${schedule.account_id} on ${triggerDate}`);
+    }
+
+    if (pendingRise) {
+      const msg = `[Drawdown] Warning: Drawdowns for account ID ${schedule.account_id} from ${pendingRise} are held back until the index for that rise is loaded.`;
+      warnings.push(msg);
+      console.warn(msg);
     }
   }
 
   if (processed > 0 || warnings.length > 0) {
-    console.log(`[Drawdown] Processing complete: ${processed} created, ${skipped} already existed, ${warnings.length} warnings`);
+    console.log(`[Drawdown] Processing complete: ${processed} created, ${skipped} already existed, ${heldBack} held back, ${warnings.length} warnings`);
   }
 
-  return { processed, skipped, warnings };
+  return { processed, skipped, held_back: heldBack, warnings };
 }
 
 /**
  * @description Preview what drawdowns would be processed without making any
  * database changes. Same logic as processDrawdowns() but collects results
- * into an array instead of creating transactions. Used by the UI test button.
+ * into an array instead of creating transactions. Used by the UI test button
+ * and the cash buffer planner. Drawdowns that processing would hold back for
+ * a pending index rise are included with held_back set and a warning, at the
+ * amount before the rise, so forecasts still allow for them.
  *
  * @param {string} [todayStr] - Optional ISO-8601 date string (YYYY-MM-DD) to
  *   use as "today". Defaults to the current date. Useful for testing.
- * @returns {{ would_process: Object[], already_exist: number, total_amount: number }}
+ * @returns {{ would_process: Object[], already_exist: number, total_amount: number, total_tax: number }}
  *   Detailed preview of what would happen
  */
 export function previewDrawdowns(todayStr) {
@@ -91,19 +124,27 @@ export function previewDrawdowns(todayStr) {
   const wouldProcess = [];
   let alreadyExist = 0;
   let totalAmount = 0;
+  let totalTax = 0;
 
   if (activeSchedules.length === 0) {
-    return { would_process: wouldProcess, already_exist: 0, total_amount: 0 };
+    return { would_process: wouldProcess, already_exist: 0, total_amount: 0, total_tax: 0 };
   }
 
   // Track a simulated running balance per account so warnings are accurate
   // even when multiple drawdowns stack up in the preview
   const simulatedBalances = {};
 
+  // Track pay and tax per account and tax year so cumulative PAYE allows for
+  // earlier drawdowns in the preview that have not been recorded
+  const simulatedPay = {};
+
   for (const schedule of activeSchedules) {
     const dueDates = getDueDrawdownDates(schedule, todayStr);
 
-    for (const triggerDate of dueDates) {
+    for (const due of dueDates) {
+      const triggerDate = due.date;
+      const amount = due.amount;
+
       if (drawdownExistsForDate(schedule.account_id, triggerDate)) {
         alreadyExist++;
         continue;
@@ -121,23 +162,40 @@ export function previewDrawdowns(todayStr) {
       const sim = simulatedBalances[schedule.account_id];
       let warning = null;
 
-      if (sim.balance < schedule.amount) {
-        warning = `Balance £${sim.balance.toFixed(2)} is less than drawdown £${schedule.amount.toFixed(2)}. Balance would go negative.`;
+      if (sim.balance < amount) {
+        warning = `Balance £${sim.balance.toFixed(2)} is less than drawdown £${amount.toFixed(2)}. Balance would go negative.`;
+      }
+      if (due.pending_rise) {
+        const held = `Held back until the index for the ${due.pending_rise} rise is loaded; shown at the amount before the rise.`;
+        warning = warning ? warning + " " + held : held;
       }
 
       // Deduct from simulated balance so subsequent drawdowns are accurate
-      sim.balance -= schedule.amount;
+      sim.balance -= amount;
+
+      const payKey = schedule.account_id + ":" + getTaxYearForDate(triggerDate).label;
+      if (!simulatedPay[payKey]) {
+        simulatedPay[payKey] = { gross: 0, tax: 0 };
+      }
+      const tax = calculateDrawdownTax(schedule, triggerDate, amount, simulatedPay[payKey]);
+      simulatedPay[payKey].gross += amount;
+      simulatedPay[payKey].tax += tax.tax_withheld;
 
       wouldProcess.push({
         account_id: schedule.account_id,
         account_ref: sim.account_ref,
         date: triggerDate,
-        amount: schedule.amount,
+        amount: amount,
+        tax_withheld: tax.tax_withheld,
+        net: tax.net,
+        tax_code: tax.tax_code,
         notes: schedule.notes || `Drawdown (${schedule.frequency})`,
+        held_back: due.pending_rise !== null,
         warning: warning,
       });
 
-      totalAmount += schedule.amount;
+      totalAmount += amount;
+      totalTax += tax.tax_withheld;
     }
   }
 
@@ -145,6 +203,7 @@ export function previewDrawdowns(todayStr) {
     would_process: wouldProcess,
     already_exist: alreadyExist,
     total_amount: Math.round(totalAmount * 100) / 100,
+    total_tax: Math.round(totalTax * 100) / 100,
   };
 }
 
@@ -0,0 +1,295 @@
+import { getUserById, getAllUsers } from "../db/users-db.js";
+import { getAccountById, getAccountsByUserId } from "../db/accounts-db.js";
+import { getDrawdownsBetween } from "../db/cash-transactions-db.js";
+import { getIncomeTaxConfig } from "../config.js";
+import { getTaxYearForDate, getTaxYearByStartYear, getTaxMonth, addDays } from "./tax-year-utils.js";
+
+/**
+ * @description Tax codes accepted for PAYE on drawdowns: an optional Welsh "C"
+ * prefix, the code itself, and an optional non-cumulative suffix (W1, M1 or X).
+ * Scottish "S" codes are not matched because Scottish rates are not modelled.
+ * @type {RegExp}
+ */
+const TAX_CODE_PATTERN = /^(C)?(BR|D0|D1|NT|K\d{1,4}|\d{1,4}[LMNT])(?:\s*(W1|M1|X))?$/;
+
+/** @description The K code overriding limit: tax on a payment cannot exceed this share of it */
+const K_CODE_LIMIT = 0.5;
+
+/**
+ * @description Round a decimal to 2 decimal places (pence).
+ * @param {number} value - The value to round
+ * @returns {number} The value rounded to pence
+ */
+function roundToPence(value) {
+  return Math.round(value * 100) / 100;
+}
+
+/**
+ * @description Parse a PAYE tax code. Number-and-letter codes (e.g. 1257L)
+ * give a tax-free allowance of the number times ten plus nine; K codes add
+ * that amount to taxable pay instead. BR, D0 and D1 tax everything at the
+ * basic, higher and additional rate; NT deducts no tax. A W1, M1 or X suffix
+ * means each payment is taxed on its own (non-cumulative).
+ * @param {string} taxCode - The tax code (case and spacing are ignored)
+ * @returns {{ code: string, rate: string|null, allowance: number, cumulative: boolean }|null}
+ *   The parsed code, where rate is 'basic', 'higher', 'additional' or 'none' for
+ *   flat-rate codes and null for allowance-based ones; null if not recognised
+ */
+export function parseTaxCode(taxCode) {
+  if (!taxCode) return null;
+  const normalised = String(taxCode).toUpperCase().replace(/\s+/g, " ").trim();
+  const match = normalised.replace(/ /g, "").match(TAX_CODE_PATTERN);
+  if (!match) return null;
+
+  const prefix = match[1] || "";
+  const body = match[2];
+  const suffix = match[3] || null;
+
+  const flatRates = { BR: "basic", D0: "higher", D1: "additional", NT: "none" };
+  let rate = flatRates[body] || null;
+  let allowance = 0;
+
+  if (!rate) {
+    if (body.startsWith("K")) {
+      allowance = -(Number(body.slice(1)) * 10 + 9);
+    } else {
+      const number = Number(body.slice(0, -1));
+      allowance = number > 0 ? number * 10 + 9 : 0;
+    }
+  }
+
+  return {
+    code: prefix + body + (suffix ? " " + suffix : ""),
+    rate: rate,
+    allowance: allowance,
+    cumulative: suffix === null,
+  };
+}
+
+/**
+ * @description Get the emergency tax code: the standard personal allowance
+ * code applied on a month 1 (non-cumulative) basis, as used by providers for
+ * a first flexible payment before HMRC issues a code.
+ * @returns {string} The emergency tax code (e.g. "1257L M1")
+ */
+export function getEmergencyTaxCode() {
+  const config = getIncomeTaxConfig();
+  return Math.floor(config.personalAllowance / 10) + "L M1";
+}
+
+/**
+ * @description Work out income tax on taxable pay using the configured bands,
+ * scaled down to the part of the year the pay covers.
+ * @param {number} taxable - Taxable pay after the allowance
+ * @param {number} fraction - Share of the tax year covered (e.g. 3/12)
+ * @returns {number} The tax due
+ */
+function taxOnBands(taxable, fraction) {
+  const config = getIncomeTaxConfig();
+  const basicLimit = config.basicRateBand * fraction;
+  const higherLimit = config.additionalRateThreshold * fraction;
+
+  const basic = Math.min(taxable, basicLimit);
+  const higher = Math.max(0, Math.min(taxable, higherLimit) - basicLimit);
+  const additional = Math.max(0, taxable - higherLimit);
+
+  return (basic * config.basicRate + higher * config.higherRate + additional * config.additionalRate) / 100;
+}
+
+/**
+ * @description Calculate the PAYE to deduct from a pension payment. On a
+ * cumulative code, tax is worked out on total pay for the tax year to date
+ * against the allowance and bands for the tax months elapsed, less tax
+ * already deducted, so a payment can carry a refund. On a non-cumulative code
+ * each payment gets one month's allowance and bands. K code deductions are
+ * capped at half of the payment.
+ * @param {Object} data - The payment details
+ * @param {string} data.tax_code - The tax code to apply
+ * @param {number} data.gross - Gross payment
+ * @param {string} data.payment_date - ISO-8601 date (YYYY-MM-DD) of the payment
+ * @param {number} [data.previous_gross=0] - Gross already paid this tax year under the same scheme
+ * @param {number} [data.previous_tax=0] - Tax already deducted this tax year under the same scheme
+ * @returns {number} Tax to deduct in GBP (negative for a refund)
+ * @throws {Error} If the tax code is not recognised
+ */
+export function calculatePayeTax(data) {
+  const parsed = parseTaxCode(data.tax_code);
+  if (!parsed) {
+    throw new Error("Unrecognised tax code: " + data.tax_code);
+  }
+
+  const config = getIncomeTaxConfig();
+  const flatRates = { basic: config.basicRate, higher: config.higherRate, additional: config.additionalRate, none: 0 };
+  if (parsed.rate) {
+    return roundToPence((data.gross * flatRates[parsed.rate]) / 100);
+  }
+
+  const previousGross = parsed.cumulative ? data.previous_gross || 0 : 0;
+  const previousTax = parsed.cumulative ? data.previous_tax || 0 : 0;
+  const fraction = parsed.cumulative ? getTaxMonth(data.payment_date) / 12 : 1 / 12;
+
+  // Taxable pay is rounded down to whole pounds, as in the HMRC tables
+  const taxable = Math.max(0, Math.floor(previousGross + data.gross - parsed.allowance * fraction));
+  let tax = taxOnBands(taxable, fraction) - previousTax;
+
+  if (parsed.allowance < 0) {
+    tax = Math.min(tax, data.gross * K_CODE_LIMIT);
+  }
+
+  return roundToPence(tax);
+}
+
+/**
+ * @description Work out the PAYE on a scheduled drawdown according to the
+ * schedule's tax treatment. For tax code and emergency treatment, earlier
+ * drawdowns from the same account in the tax year count towards the
+ * cumulative calculation, together with any payments not yet recorded.
+ * @param {Object} schedule - The drawdown schedule
+ * @param {string} paymentDate - ISO-8601 date (YYYY-MM-DD) of the payment
+ * @param {number} gross - Gross payment
+ * @param {{ gross: number, tax: number }} [pending] - Payments earlier in the same tax year not yet recorded
+ * @returns {{ tax_withheld: number, net: number, tax_code: string|null }} The deduction and the code used
+ */
+export function calculateDrawdownTax(schedule, paymentDate, gross, pending) {
+  const treatment = schedule.tax_treatment || "none";
+
+  if (treatment === "none") {
+    return { tax_withheld: 0, net: gross, tax_code: null };
+  }
+
+  if (treatment === "flat") {
+    const flatTax = roundToPence((gross * (schedule.flat_tax_rate || 0)) / 100);
+    return { tax_withheld: flatTax, net: roundToPence(gross - flatTax), tax_code: null };
+  }
+
+  const taxCode = treatment === "emergency" ? getEmergencyTaxCode() : schedule.tax_code;
+  const ty = getTaxYearForDate(paymentDate);
+  const earlier = paymentDate > ty.start ? getDrawdownsBetween(schedule.account_id, ty.start, addDays(paymentDate, -1)) : [];
+
+  let previousGross = pending ? pending.gross : 0;
+  let previousTax = pending ? pending.tax : 0;
+  for (const tx of earlier) {
+    previousGross += tx.amount;
+    previousTax += tx.tax_withheld || 0;
+  }
+
+  const tax = calculatePayeTax({
+    tax_code: taxCode,
+    gross: gross,
+    payment_date: paymentDate,
+    previous_gross: previousGross,
+    previous_tax: previousTax,
+  });
+
+  return { tax_withheld: tax, net: roundToPence(gross - tax), tax_code: parseTaxCode(taxCode).code };
+}
+
+/**
+ * @description Get a P60-style summary of the pension paid from a SIPP in a
+ * tax year: total gross pay, tax deducted and net pay, the tax code last used,
+ * and each payment.
+ * @param {number} accountId - The SIPP account ID
+ * @param {number|null} [taxYearStart=null] - Calendar year the tax year starts in; defaults to the current tax year
+ * @returns {Object|null} The summary, or null if the account is not found or is not a SIPP
+ */
+export function getP60Summary(accountId, taxYearStart = null) {
+  const account = getAccountById(accountId);
+  if (!account || account.account_type !== "sipp") return null;
+
+  const today = new Date().toISOString().slice(0, 10);
+  const ty = taxYearStart ? getTaxYearByStartYear(taxYearStart) : getTaxYearForDate(today);
+
+  let gross = 0;
+  let taxWithheld = 0;
+  let taxCode = null;
+  const payments = getDrawdownsBetween(accountId, ty.start, ty.end).map(function (tx) {
+    const tax = tx.tax_withheld || 0;
+    gross += tx.amount;
+    taxWithheld += tax;
+    if (tx.tax_code) taxCode = tx.tax_code;
+    return {
+      transaction_id: tx.id,
+      transaction_date: tx.transaction_date,
+      gross: tx.amount,
+      tax_withheld: tax,
+      net: roundToPence(tx.amount - tax),
+      tax_code: tx.tax_code || null,
+    };
+  });
+
+  return {
+    tax_year: ty.label,
+    tax_year_start: ty.start,
+    tax_year_end: ty.end,
+    year_complete: ty.end < today,
+    account_id: account.id,
+    account_ref: account.account_ref,
+    provider: account.provider,
+    user_id: account.user_id,
+    tax_code: taxCode,
+    gross: roundToPence(gross),
+    tax_withheld: roundToPence(taxWithheld),
+    net: roundToPence(gross - taxWithheld),
+    payments: payments,
+  };
+}
+
+/**
+ * @description Get P60-style summaries for every SIPP a user holds that paid
+ * a pension in the tax year, with totals across them for self-assessment.
+ * @param {number} userId - The user ID
+ * @param {number|null} [taxYearStart=null] - Calendar year the tax year starts in; defaults to the current tax year
+ * @returns {Object|null} Object with { user, tax_year, accounts, gross, tax_withheld, net }, or null if the user is not found
+ */
+export function getP60SummariesForUser(userId, taxYearStart = null) {
+  const user = getUserById(userId);
+  if (!user) return null;
+
+  const today = new Date().toISOString().slice(0, 10);
+  const startYear = taxYearStart || Number(getTaxYearForDate(today).start.slice(0, 4));
+
+  const accounts = [];
+  let gross = 0;
+  let taxWithheld = 0;
+  for (const account of getAccountsByUserId(userId)) {
+    if (account.account_type !== "sipp") continue;
+    const summary = getP60Summary(account.id, startYear);
+    if (summary.payments.length === 0) continue;
+    accounts.push(summary);
+    gross += summary.gross;
+    taxWithheld += summary.tax_withheld;
+  }
+
+  return {
+    user: {
+      id: user.id,
+      initials: user.initials,
+      first_name: user.first_name,
+      last_name: user.last_name,
+    },
+    tax_year: getTaxYearByStartYear(startYear).label,
+    accounts: accounts,
+    gross: roundToPence(gross),
+    tax_withheld: roundToPence(taxWithheld),
+    net: roundToPence(gross - taxWithheld),
+  };
+}
+
+/**
+ * @description Get P60-style summaries for each user who was paid a pension
+ * from a SIPP in the tax year.
+ * @param {number[]|null} [userIds=null] - Restrict to these users; null for all
+ * @param {number|null} [taxYearStart=null] - Calendar year the tax year starts in; defaults to the current tax year
+ * @returns {Object[]} Summaries per user, as returned by getP60SummariesForUser
+ */
+export function getP60SummariesForAllUsers(userIds = null, taxYearStart = null) {
+  const results = [];
+  for (const user of getAllUsers()) {
+    if (userIds && !userIds.includes(user.id)) continue;
+    const summary = getP60SummariesForUser(user.id, taxYearStart);
+    if (summary && summary.accounts.length > 0) {
+      results.push(summary);
+    }
+  }
+  return results;
+}
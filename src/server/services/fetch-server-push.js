@@ -58,6 +58,7 @@ function queryFetchItems(db) {
       c.code AS currency_code
     FROM benchmarks b
     JOIN currencies c ON b.currencies_id = c.id
+    WHERE b.benchmark_type != 'composite'
     ORDER BY b.description`,
   ).all().map(function (row) {
     return {
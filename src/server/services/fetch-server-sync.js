@@ -7,6 +7,7 @@
 import { existsSync } from "node:fs";
 import { resolve, join } from "node:path";
 import { getDatabase, closeDatabase, resetDatabasePath } from "../db/connection.js";
+import { invalidateCurrencyValuations, invalidateInvestmentValuations } from "../db/portfolio-valuations-db.js";
 import { getFetchServerConfig } from "../config.js";
 import { loadEnvValue } from "../auth.js";
 import { DATA_DIR, TEST_DB_FILENAME } from "../../shared/server-constants.js";
@@ -159,7 +160,8 @@ export async function triggerServerFetchAll() {
  * integer IDs and writes prices, rates, and benchmark values.
  *
  * Values from the fetch server are already scaled x 10000, so we write
- * them directly (no additional scaling).
+ * them directly (no additional scaling). Valuation snapshots are then
+ * invalidated from the earliest synced date for each investment and currency.
  *
  * @param {Object} data - The data from GET /api/latest
  * @returns {{ prices: number, rates: number, benchmarks: number }} Count of items upserted
@@ -193,11 +195,19 @@ export function upsertIntoDatabase(data) {
       "INSERT OR REPLACE INTO currency_rates (currencies_id, rate_date, rate_time, rate) VALUES (?, ?, ?, ?)",
     );
 
+    const earliestRateDates = {};
     for (const rate of data.currencies) {
       const currencyId = currencyMap[rate.code];
       if (!currencyId) continue;
       rateStmt.run(currencyId, rate.rateDate, "00:00:00", rate.rate);
       counts.rates++;
+      if (!earliestRateDates[currencyId] || rate.rateDate < earliestRateDates[currencyId]) {
+        earliestRateDates[currencyId] = rate.rateDate;
+      }
+    }
+
+    for (const currencyId of Object.keys(earliestRateDates)) {
+      invalidateCurrencyValuations(Number(currencyId), earliestRateDates[currencyId]);
     }
   }
 
@@ -207,11 +217,19 @@ export function upsertIntoDatabase(data) {
       "INSERT OR REPLACE INTO prices (investment_id, price_date, price_time, price) VALUES (?, ?, ?, ?)",
     );
 
+    const earliestPriceDates = {};
     for (const price of data.prices) {
       const investmentId = investmentMap[price.morningstarId];
       if (!investmentId) continue;
       priceStmt.run(investmentId, price.priceDate, "00:00:00", price.price);
       counts.prices++;
+      if (!earliestPriceDates[investmentId] || price.priceDate < earliestPriceDates[investmentId]) {
+        earliestPriceDates[investmentId] = price.priceDate;
+      }
+    }
+
+    for (const investmentId of Object.keys(earliestPriceDates)) {
+      invalidateInvestmentValuations(Number(investmentId), earliestPriceDates[investmentId]);
     }
   }
 
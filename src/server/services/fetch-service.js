@@ -11,6 +11,7 @@ import { recordFetchAttempt } from "../db/fetch-history-db.js";
 import { toLocalDateStr } from "../../shared/server-constants.js";
 import { upsertPrice } from "../db/prices-db.js";
 import { upsertBenchmarkData } from "../db/benchmark-data-db.js";
+import { refreshValuationSnapshots } from "./portfolio-service.js";
 
 /** @description Trigger gap-fill if last data is older than this many days */
 const GAP_THRESHOLD_DAYS = 10;
@@ -486,6 +487,13 @@ export async function runFullPriceUpdate(options = {}) {
       }
     }
 
+    // Materialise today's portfolio valuations from the freshly fetched prices
+    try {
+      refreshValuationSnapshots(toLocalDateStr(new Date()));
+    } catch (err) {
+      console.warn("[FetchService] Failed to refresh valuation snapshots: " + err.message);
+    }
+
     checkpointDatabase();
 
     if (onComplete) {
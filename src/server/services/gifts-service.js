@@ -0,0 +1,276 @@
+/**
+ * @description Gifts service for Portfolio 60.
+ * Works through each family member's gifts in the gifts register, in date
+ * order, to find how much of each is covered by an exemption and how much is
+ * a potentially exempt transfer (PET). Gifts made as normal expenditure out of
+ * income are exempt, as are small gifts while the recipient's gifts in the tax
+ * year stay within the small gift limit. Everything else is set against the
+ * annual exemption — the current tax year's first, then any left unused from
+ * the year before — and the rest is a PET. A PET leaves the estate seven years
+ * after the gift; if the donor dies sooner it uses up the nil-rate band first,
+ * and any tax on it is reduced by taper relief from the third year.
+ */
+
+import { getAllUsers, getUserById } from "../db/users-db.js";
+import { getAllGifts, GIFT_EXEMPTION_TYPE_LABELS } from "../db/gifts-db.js";
+import { getInheritanceTaxConfig } from "../config.js";
+import { getTaxYearForDate, getTaxYearByStartYear } from "./tax-year-utils.js";
+
+/**
+ * @description Years after which a potentially exempt transfer leaves the estate.
+ * @type {number}
+ */
+const PET_YEARS = 7;
+
+/**
+ * @description Taper relief (percent off the tax on a gift) by whole years
+ * between the gift and death, for gifts within the seven years.
+ * @type {number[]}
+ */
+const TAPER_RELIEF = [0, 0, 0, 20, 40, 60, 80];
+
+/**
+ * @description Round a value to 2 decimal places (pence precision).
+ * @param {number} value - The value to round
+ * @returns {number} Value rounded to 2 decimal places
+ */
+function roundToPence(value) {
+  return Math.round(value * 100) / 100;
+}
+
+/**
+ * @description Get today's date in ISO-8601 format (YYYY-MM-DD).
+ * @returns {string} Today's date string
+ */
+function getTodayDate() {
+  const now = new Date();
+  const year = now.getFullYear();
+  const month = String(now.getMonth() + 1).padStart(2, "0");
+  const day = String(now.getDate()).padStart(2, "0");
+  return year + "-" + month + "-" + day;
+}
+
+/**
+ * @description Add whole years to an ISO-8601 date. 29 February becomes
+ * 28 February in a year that is not a leap year.
+ * @param {string} date - ISO-8601 date (YYYY-MM-DD)
+ * @param {number} years - Years to add
+ * @returns {string} ISO-8601 date
+ */
+function addYears(date, years) {
+  const year = parseInt(date.slice(0, 4), 10) + years;
+  const month = parseInt(date.slice(5, 7), 10) - 1;
+  const day = parseInt(date.slice(8, 10), 10);
+  const lastDay = new Date(Date.UTC(year, month + 1, 0)).getUTCDate();
+  return new Date(Date.UTC(year, month, Math.min(day, lastDay))).toISOString().slice(0, 10);
+}
+
+/**
+ * @description Count the whole years from one date to another.
+ * @param {string} from - ISO-8601 start date
+ * @param {string} to - ISO-8601 end date
+ * @returns {number} Whole years elapsed (0 if to is before from)
+ */
+function wholeYearsBetween(from, to) {
+  let years = parseInt(to.slice(0, 4), 10) - parseInt(from.slice(0, 4), 10);
+  if (to.slice(5) < from.slice(5)) years -= 1;
+  return Math.max(0, years);
+}
+
+/**
+ * @description Get the taper relief on the tax on a gift, by whole years
+ * between the gift and death.
+ * @param {string} giftDate - ISO-8601 date of the gift
+ * @param {string} deathDate - ISO-8601 date of death (or the date to assess at)
+ * @returns {number|null} Relief as a percentage (0 to 80), or null once seven years have passed
+ */
+export function getTaperRelief(giftDate, deathDate) {
+  const years = wholeYearsBetween(giftDate, deathDate);
+  return years >= PET_YEARS ? null : TAPER_RELIEF[years];
+}
+
+/**
+ * @description Work through one donor's gifts to find the exemption and
+ * potentially exempt transfer in each, the annual exemption used and left in
+ * each tax year, and where each PET stands against the seven years. The tax
+ * year before the first gift recorded is taken to have its annual exemption
+ * unused, as is any tax year without gifts.
+ * @param {Object[]} gifts - The donor's gifts (recipient, gift_date, amount in GBP, exemption_type)
+ * @param {Object} config - inheritanceTax config
+ * @param {string} asOf - ISO-8601 date to assess the seven years and taper relief at
+ * @returns {{ gifts: Object[], tax_years: Object[], annual_exemption: Object, totals: Object }}
+ *   Each gift gains tax_year, exemption_label, exempt_amount, pet_amount, exemption_note, status
+ *   ('exempt', 'pet' or 'past_seven_years'), years_elapsed, taper_relief, becomes_exempt_on,
+ *   chargeable and tax_on_death; amounts in GBP
+ */
+export function analyseGifts(gifts, config, asOf) {
+  const sorted = gifts.slice().sort(function (a, b) {
+    return a.gift_date < b.gift_date ? -1 : a.gift_date > b.gift_date ? 1 : (a.id || 0) - (b.id || 0);
+  });
+
+  const results = sorted.map(function (gift) {
+    return {
+      ...gift,
+      tax_year: getTaxYearForDate(gift.gift_date).label,
+      exemption_label: GIFT_EXEMPTION_TYPE_LABELS[gift.exemption_type] || gift.exemption_type,
+      exempt_amount: 0,
+      pet_amount: 0,
+      exemption_note: null,
+    };
+  });
+
+  // A small gift only qualifies while the recipient's gifts in the tax year are within the limit
+  const recipientTotals = new Map();
+  for (const gift of results) {
+    if (gift.exemption_type === "normal_expenditure") continue;
+    const key = gift.tax_year + "|" + gift.recipient.trim().toLowerCase();
+    recipientTotals.set(key, (recipientTotals.get(key) || 0) + gift.amount);
+  }
+
+  // Set each tax year's gifts against its annual exemption, then the year before's unused
+  const currentYear = getTaxYearForDate(asOf);
+  const startYears = [asOf].concat(results.map((gift) => gift.gift_date)).map(function (date) {
+    return parseInt(getTaxYearForDate(date).start.slice(0, 4), 10);
+  });
+  const firstStart = Math.min(...startYears);
+  const lastStart = Math.max(...startYears);
+
+  const taxYears = [];
+  let unusedLastYear = config.annualExemption;
+  for (let startYear = firstStart; startYear <= lastStart; startYear++) {
+    const label = getTaxYearByStartYear(startYear).label;
+    let thisYear = config.annualExemption;
+    let carried = unusedLastYear;
+
+    for (const gift of results) {
+      if (gift.tax_year !== label) continue;
+      if (gift.exemption_type === "normal_expenditure") {
+        gift.exempt_amount = gift.amount;
+        continue;
+      }
+      if (gift.exemption_type === "small_gift") {
+        if (recipientTotals.get(label + "|" + gift.recipient.trim().toLowerCase()) <= config.smallGiftLimit) {
+          gift.exempt_amount = gift.amount;
+          continue;
+        }
+        gift.exemption_note = "Gifts to this recipient in the tax year are over the £" + config.smallGiftLimit + " small gift limit";
+      }
+
+      let remaining = gift.amount;
+      const fromThisYear = Math.min(remaining, thisYear);
+      thisYear -= fromThisYear;
+      remaining -= fromThisYear;
+      const fromCarried = Math.min(remaining, carried);
+      carried -= fromCarried;
+      remaining -= fromCarried;
+      gift.exempt_amount = roundToPence(gift.amount - remaining);
+      gift.pet_amount = roundToPence(remaining);
+    }
+
+    taxYears.push({
+      tax_year: label,
+      allowance: config.annualExemption,
+      carried_forward: unusedLastYear,
+      used: roundToPence(config.annualExemption - thisYear + (unusedLastYear - carried)),
+      remaining: roundToPence(thisYear + carried),
+    });
+    // Only this year's own exemption can be carried forward, and only for one year
+    unusedLastYear = roundToPence(thisYear);
+  }
+
+  // Where each PET stands against the seven years, and the tax on it were the donor to die now.
+  // PETs use up the nil-rate band oldest first.
+  let bandLeft = config.nilRateBand;
+  const totals = { gifts: 0, exempt: 0, pets: 0, pets_within_seven_years: 0, nil_rate_band_used: 0, tax_on_death: 0 };
+  for (const gift of results) {
+    gift.years_elapsed = wholeYearsBetween(gift.gift_date, asOf);
+    gift.becomes_exempt_on = gift.pet_amount > 0 ? addYears(gift.gift_date, PET_YEARS) : null;
+    gift.taper_relief = null;
+    gift.chargeable = 0;
+    gift.tax_on_death = 0;
+
+    if (gift.pet_amount === 0) {
+      gift.status = "exempt";
+    } else if (asOf >= gift.becomes_exempt_on) {
+      gift.status = "past_seven_years";
+    } else {
+      gift.status = "pet";
+      gift.taper_relief = getTaperRelief(gift.gift_date, asOf);
+      const fromBand = Math.min(bandLeft, gift.pet_amount);
+      bandLeft -= fromBand;
+      gift.chargeable = roundToPence(gift.pet_amount - fromBand);
+      gift.tax_on_death = roundToPence(((gift.chargeable * config.rate) / 100) * (1 - gift.taper_relief / 100));
+      totals.pets_within_seven_years += gift.pet_amount;
+      totals.nil_rate_band_used += fromBand;
+      totals.tax_on_death += gift.tax_on_death;
+    }
+
+    gift.amount = roundToPence(gift.amount);
+    totals.gifts += gift.amount;
+    totals.exempt += gift.exempt_amount;
+    totals.pets += gift.pet_amount;
+  }
+
+  for (const key of Object.keys(totals)) {
+    totals[key] = roundToPence(totals[key]);
+  }
+
+  return {
+    gifts: results,
+    tax_years: taxYears,
+    annual_exemption: taxYears.find((y) => y.tax_year === currentYear.label),
+    totals: totals,
+  };
+}
+
+/**
+ * @description Build the gifts register summary for one family member: their
+ * gifts with the exemption and taper position of each, the annual exemption
+ * left this tax year and the PETs still within seven years.
+ * @param {number} userId - The donor's user ID
+ * @param {string} [asOf] - ISO-8601 date to assess at (today when omitted)
+ * @returns {Object|null} Summary with { user, gifts, tax_years, annual_exemption, totals, as_of },
+ *   amounts in GBP; null if the user is not found or is the Joint household user
+ */
+export function buildGiftsSummary(userId, asOf) {
+  const user = getUserById(userId);
+  if (!user || user.first_name === "Joint") return null;
+
+  const date = asOf || getTodayDate();
+  const analysis = analyseGifts(getAllGifts(user.id), getInheritanceTaxConfig(), date);
+  return {
+    user: { id: user.id, initials: user.initials, first_name: user.first_name, last_name: user.last_name },
+    gifts: analysis.gifts,
+    tax_years: analysis.tax_years,
+    annual_exemption: analysis.annual_exemption,
+    totals: analysis.totals,
+    as_of: date,
+  };
+}
+
+/**
+ * @description Build the gifts register summary for each family member. The
+ * Joint household user is left out, as gifts are made by people.
+ * @param {string} [asOf] - ISO-8601 date to assess at (today when omitted)
+ * @returns {Object[]} Summaries as from buildGiftsSummary
+ */
+export function buildGiftsSummaries(asOf) {
+  const summaries = [];
+  for (const user of getAllUsers()) {
+    const summary = buildGiftsSummary(user.id, asOf);
+    if (summary) summaries.push(summary);
+  }
+  return summaries;
+}
+
+/**
+ * @description Get the totals of a family member's gifts: the PETs still
+ * within seven years and the nil-rate band they use, and the tax on them were
+ * the donor to die today.
+ * @param {number} userId - The donor's user ID
+ * @returns {{ gifts: number, exempt: number, pets: number, pets_within_seven_years: number,
+ *   nil_rate_band_used: number, tax_on_death: number }} Amounts in GBP
+ */
+export function getGiftTotals(userId) {
+  return analyseGifts(getAllGifts(userId), getInheritanceTaxConfig(), getTodayDate()).totals;
+}
@@ -1042,7 +1042,8 @@ export async function fetchYahooBenchmarkHistory(yahooTicker, startDate, endDate
 export async function backfillBenchmarkValues(progressCallback) {
   const db = getDatabase();
 
-  const benchmarks = db.query("SELECT id, description, yahoo_ticker FROM benchmarks ORDER BY description").all();
+  // Composite benchmarks are derived from their components, so have nothing to fetch
+  const benchmarks = db.query("SELECT id, description, yahoo_ticker FROM benchmarks WHERE benchmark_type != 'composite' ORDER BY description").all();
 
   if (benchmarks.length === 0) {
     progressCallback({ type: "info", message: "No benchmarks found" });
@@ -1731,10 +1732,14 @@ export async function loadBackfillCurrency(currencyId) {
 export async function testBackfillBenchmark(benchmarkId) {
   const db = getDatabase();
 
-  const bm = db.query("SELECT id, description, yahoo_ticker FROM benchmarks WHERE id = ?").get(benchmarkId);
+  const bm = db.query("SELECT id, description, benchmark_type, yahoo_ticker FROM benchmarks WHERE id = ?").get(benchmarkId);
 
   if (!bm) return { success: false, description: "Unknown", rows: [], error: "Benchmark not found" };
 
+  if (bm.benchmark_type === "composite") {
+    return { success: false, description: bm.description, rows: [], error: "Composite benchmarks are derived from their components and have no historic data to fetch" };
+  }
+
   if (bm.description.toLowerCase().includes("msci")) {
     return { success: false, description: bm.description, rows: [], error: "MSCI indexes have no free historic data source (live scraping only)" };
   }
@@ -1770,10 +1775,14 @@ export async function testBackfillBenchmark(benchmarkId) {
 export async function loadBackfillBenchmark(benchmarkId) {
   const db = getDatabase();
 
-  const bm = db.query("SELECT id, description, yahoo_ticker FROM benchmarks WHERE id = ?").get(benchmarkId);
+  const bm = db.query("SELECT id, description, benchmark_type, yahoo_ticker FROM benchmarks WHERE id = ?").get(benchmarkId);
 
   if (!bm) return { success: false, description: "Unknown", count: 0, error: "Benchmark not found" };
 
+  if (bm.benchmark_type === "composite") {
+    return { success: false, description: bm.description, count: 0, error: "Composite benchmarks are derived from their components and have no historic data to fetch" };
+  }
+
   if (bm.description.toLowerCase().includes("msci")) {
     return { success: false, description: bm.description, count: 0, error: "MSCI indexes have no free historic data source (live scraping only)" };
   }
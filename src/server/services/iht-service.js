@@ -0,0 +1,277 @@
+/**
+ * @description Inheritance tax service for Portfolio 60.
+ * Estimates the estate of each family member — ISA and trading accounts and
+ * value-type other assets, less liabilities — and the inheritance tax due on
+ * it after the nil-rate band and residence nil-rate band. SIPPs and pension
+ * pots are listed but left out, as pensions generally fall outside the
+ * estate. Gifts in the gifts register still within seven years use up the
+ * nil-rate band before the estate does. Items held by the Joint household
+ * user are shared equally between the family members who have a spouse or
+ * civil partner recorded, or between all family members when none has. For
+ * spouses or civil partners, the estate passes tax free to the survivor on
+ * the first death and both sets of bands are used on the second.
+ */
+
+import { getAllUsers, getUserById } from "../db/users-db.js";
+import { getAllOtherAssets } from "../db/other-assets-db.js";
+import { getInheritanceTaxConfig } from "../config.js";
+import { CURRENCY_SCALE_FACTOR } from "../../shared/server-constants.js";
+import { getPortfolioSummary } from "./portfolio-service.js";
+import { getGiftTotals } from "./gifts-service.js";
+
+/**
+ * @description Display labels for the account types and other asset
+ * categories that make up an estate.
+ * @type {Object<string, string>}
+ */
+export const ESTATE_ITEM_TYPE_LABELS = {
+  trading: "Trading",
+  isa: "ISA",
+  sipp: "SIPP",
+  pension: "Pension",
+  property: "Property",
+  savings: "Savings",
+  alternative: "Alternative",
+  liability: "Liability",
+};
+
+/** @description Why pensions are listed but not counted in the estate */
+const PENSION_NOTE = "Pensions are generally outside the estate";
+
+/**
+ * @description Round a value to 2 decimal places (pence precision).
+ * @param {number} value - The value to round
+ * @returns {number} Value rounded to 2 decimal places
+ */
+function roundToPence(value) {
+  return Math.round(value * 100) / 100;
+}
+
+/**
+ * @description Whether a user is the Joint household user, which holds jointly
+ * owned items rather than being a person with an estate.
+ * @param {Object} user - User row
+ * @returns {boolean} True for the Joint user
+ */
+function isJointUser(user) {
+  return user.first_name === "Joint";
+}
+
+/**
+ * @description Work out each family member's share of the items held by the
+ * Joint household user: an equal share for each member with a spouse or civil
+ * partner recorded, or for every member when no spouses are recorded.
+ * @param {Object[]} users - All user rows
+ * @returns {Map<number, number>} Share (0-1) keyed by user ID; users not listed have none
+ */
+function getJointShares(users) {
+  const members = users.filter((u) => !isJointUser(u));
+  const partnered = members.filter(function (u) {
+    return u.spouse_user_id && members.some((m) => m.id === u.spouse_user_id);
+  });
+  const owners = partnered.length > 0 ? partnered : members;
+  return new Map(owners.map((u) => [u.id, 1 / owners.length]));
+}
+
+/**
+ * @description Work out the inheritance tax on an estate. Gifts made in the
+ * seven years before death use up the nil-rate band first. The residence
+ * nil-rate band is limited to the value of the home in the estate, and is
+ * reduced by £1 for every £2 the estate is over the taper threshold.
+ * @param {number} estate - Net estate in GBP (assets less liabilities)
+ * @param {number} residence - Value in GBP of the main residence within the estate
+ * @param {number} bands - Sets of nil-rate bands available: 1 for one person, 2 with a spouse's transferred
+ * @param {Object} config - inheritanceTax config
+ * @param {number} [gifts=0] - Gifts in GBP set against the nil-rate band (potentially exempt transfers within seven years)
+ * @returns {{ estate: number, residence: number, gifts: number, nil_rate_band: number, residence_nil_rate_band: number,
+ *   taper_reduction: number, taxable: number, tax: number }} Amounts in GBP; nil_rate_band is what is left for the estate
+ */
+export function calculateInheritanceTax(estate, residence, bands, config, gifts = 0) {
+  const chargeable = Math.max(0, estate);
+  const nilRateBand = Math.max(0, config.nilRateBand * bands - gifts);
+  const taperReduction = Math.max(0, (chargeable - config.taperThreshold) / 2);
+  const residenceBand = Math.min(Math.max(0, config.residenceNilRateBand * bands - taperReduction), Math.max(0, residence));
+  const taxable = Math.max(0, chargeable - nilRateBand - residenceBand);
+
+  return {
+    estate: roundToPence(estate),
+    residence: roundToPence(residence),
+    gifts: roundToPence(gifts),
+    nil_rate_band: roundToPence(nilRateBand),
+    residence_nil_rate_band: roundToPence(residenceBand),
+    taper_reduction: roundToPence(Math.min(taperReduction, config.residenceNilRateBand * bands)),
+    taxable: roundToPence(taxable),
+    tax: roundToPence((taxable * config.rate) / 100),
+  };
+}
+
+/**
+ * @description Gather a person's estate: their own accounts and other assets,
+ * plus their share of the Joint household user's. Recurring other assets are
+ * income rather than capital, so are left out.
+ * @param {Object} user - The user row
+ * @param {Object|null} jointUser - The Joint household user, if any
+ * @param {number} jointShare - The user's share (0-1) of the joint items
+ * @returns {{ assets: Object[], outside_estate: Object[], liabilities: Object[], totals: Object }}
+ *   Items with { description, type, type_label, value, share, main_residence }, values in GBP
+ */
+function gatherEstate(user, jointUser, jointShare) {
+  const owners = [{ id: user.id, share: 1 }];
+  if (jointUser && jointShare > 0) {
+    owners.push({ id: jointUser.id, share: jointShare });
+  }
+
+  const assets = [];
+  const outside = [];
+  const liabilities = [];
+
+  for (const owner of owners) {
+    const summary = getPortfolioSummary(owner.id);
+    for (const account of summary ? summary.accounts : []) {
+      const item = {
+        description: [(account.provider || "").toUpperCase(), ESTATE_ITEM_TYPE_LABELS[account.account_type], account.account_ref || ""].join(" ").trim(),
+        type: account.account_type,
+        type_label: ESTATE_ITEM_TYPE_LABELS[account.account_type],
+        value: roundToPence(account.account_total * owner.share),
+        share: owner.share,
+        main_residence: false,
+      };
+      if (account.account_type === "sipp") {
+        item.note = PENSION_NOTE;
+        outside.push(item);
+      } else {
+        assets.push(item);
+      }
+    }
+  }
+
+  const ownerShares = new Map(
+    owners.map(function (owner) {
+      return [owner.id, owner.share];
+    }),
+  );
+  for (const asset of getAllOtherAssets()) {
+    if (!ownerShares.has(asset.user_id) || asset.value_type !== "value") continue;
+    const share = ownerShares.get(asset.user_id);
+    const item = {
+      description: asset.description,
+      type: asset.category,
+      type_label: ESTATE_ITEM_TYPE_LABELS[asset.category] || asset.category,
+      value: roundToPence((asset.value / CURRENCY_SCALE_FACTOR) * share),
+      share: share,
+      main_residence: asset.main_residence === 1,
+    };
+    if (asset.category === "liability") {
+      liabilities.push(item);
+    } else if (asset.category === "pension") {
+      item.note = PENSION_NOTE;
+      outside.push(item);
+    } else {
+      assets.push(item);
+    }
+  }
+
+  /**
+   * @description Total the values of a list of items.
+   * @param {Object[]} items - Estate items
+   * @returns {number} Total in GBP
+   */
+  function total(items) {
+    return roundToPence(items.reduce((sum, item) => sum + item.value, 0));
+  }
+
+  const assetsTotal = total(assets);
+  const liabilitiesTotal = total(liabilities);
+  return {
+    assets: assets,
+    outside_estate: outside,
+    liabilities: liabilities,
+    totals: {
+      assets: assetsTotal,
+      liabilities: liabilitiesTotal,
+      net_estate: roundToPence(assetsTotal - liabilitiesTotal),
+      residence: total(assets.filter((item) => item.main_residence)),
+      outside_estate: total(outside),
+    },
+  };
+}
+
+/**
+ * @description Estimate a family member's estate and the inheritance tax due
+ * on it. The estate is taxed on its own bands if it is not left to a spouse.
+ * With a spouse or civil partner recorded, it passes tax free on the first
+ * death and the unused bands transfer to the survivor, whose estate then
+ * includes both and is taxed against two sets of bands on the second death.
+ * Gifts within seven years reduce the nil-rate band of the person who made
+ * them, and so the band transferred to a spouse.
+ * @param {number} userId - The user ID
+ * @returns {Object|null} Estimate with { user, spouse, assets, outside_estate, liabilities, totals,
+ *   gifts, estate_alone, first_death, second_death, config }, amounts in GBP; null if the user is not
+ *   found or is the Joint household user. first_death and second_death are null without a spouse
+ */
+export function buildEstateEstimate(userId) {
+  const user = getUserById(userId);
+  if (!user || isJointUser(user)) return null;
+
+  const users = getAllUsers();
+  const jointUser = users.find(isJointUser) || null;
+  const jointShares = getJointShares(users);
+  const config = getInheritanceTaxConfig();
+
+  const estate = gatherEstate(user, jointUser, jointShares.get(user.id) || 0);
+  const gifts = getGiftTotals(user.id);
+  const bandLeftForSpouse = Math.max(0, config.nilRateBand - gifts.pets_within_seven_years);
+  const spouse = user.spouse_user_id ? getUserById(user.spouse_user_id) : null;
+  const hasSpouse = spouse && !isJointUser(spouse);
+
+  let firstDeath = null;
+  let secondDeath = null;
+  if (hasSpouse) {
+    const spouseEstate = gatherEstate(spouse, jointUser, jointShares.get(spouse.id) || 0);
+    firstDeath = {
+      left_to_spouse: Math.max(0, estate.totals.net_estate),
+      tax: 0,
+      nil_rate_band_transferred: bandLeftForSpouse,
+      residence_nil_rate_band_transferred: config.residenceNilRateBand,
+    };
+    // The survivor has their own band, less their own gifts, and what was left of the first
+    secondDeath = calculateInheritanceTax(
+      estate.totals.net_estate + spouseEstate.totals.net_estate,
+      estate.totals.residence + spouseEstate.totals.residence,
+      2,
+      config,
+      config.nilRateBand - bandLeftForSpouse + getGiftTotals(spouse.id).pets_within_seven_years,
+    );
+    secondDeath.spouse_estate = spouseEstate.totals.net_estate;
+  }
+
+  return {
+    user: { id: user.id, initials: user.initials, first_name: user.first_name, last_name: user.last_name },
+    spouse: hasSpouse ? { id: spouse.id, initials: spouse.initials, first_name: spouse.first_name, last_name: spouse.last_name } : null,
+    assets: estate.assets,
+    outside_estate: estate.outside_estate,
+    liabilities: estate.liabilities,
+    totals: estate.totals,
+    gifts: gifts,
+    estate_alone: calculateInheritanceTax(estate.totals.net_estate, estate.totals.residence, 1, config, gifts.pets_within_seven_years),
+    first_death: firstDeath,
+    second_death: secondDeath,
+    config: config,
+  };
+}
+
+/**
+ * @description Estimate the estate of each family member. The Joint household
+ * user is left out, as its items are shared between the others.
+ * @param {Array<number>|null} [userIds] - Users to include (in that order), or null for everyone
+ * @returns {Object[]} Estimates as from buildEstateEstimate
+ */
+export function buildEstateEstimates(userIds) {
+  const ids = userIds || getAllUsers().map((u) => u.id);
+  const estimates = [];
+  for (const id of ids) {
+    const estimate = buildEstateEstimate(id);
+    if (estimate) estimates.push(estimate);
+  }
+  return estimates;
+}
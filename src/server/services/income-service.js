@@ -0,0 +1,194 @@
+import { getAccountById } from "../db/accounts-db.js";
+import { getHoldingById } from "../db/holdings-db.js";
+import { getIncomeTransactions } from "../db/cash-transactions-db.js";
+import { getTaxYearForDate, getTaxYearByStartYear } from "./tax-year-utils.js";
+
+/**
+ * @description Round a decimal to 2 decimal places (pence).
+ * @param {number} value - The value to round
+ * @returns {number} The value rounded to pence
+ */
+function roundToPence(value) {
+  return Math.round(value * 100) / 100;
+}
+
+/**
+ * @description Group income transactions by UK tax year, newest year first.
+ * Each year carries dividend, interest and combined totals.
+ * @param {Object[]} transactions - Income transactions (oldest first)
+ * @returns {Object[]} Array of { label, start, end, dividends, interest, total, transactions }
+ */
+function groupByTaxYear(transactions) {
+  const years = {};
+
+  for (const tx of transactions) {
+    const ty = getTaxYearForDate(tx.transaction_date);
+    if (!years[ty.label]) {
+      years[ty.label] = { label: ty.label, start: ty.start, end: ty.end, dividends: 0, interest: 0, total: 0, transactions: [] };
+    }
+    const year = years[ty.label];
+    if (tx.transaction_type === "dividend") {
+      year.dividends += tx.amount;
+    } else {
+      year.interest += tx.amount;
+    }
+    year.total += tx.amount;
+    year.transactions.push(tx);
+  }
+
+  return Object.values(years)
+    .sort(function (a, b) { return a.start < b.start ? 1 : -1; })
+    .map(function (year) {
+      year.dividends = roundToPence(year.dividends);
+      year.interest = roundToPence(year.interest);
+      year.total = roundToPence(year.total);
+      return year;
+    });
+}
+
+/**
+ * @description Build per-investment income totals from a list of income
+ * transactions. Interest not linked to an investment (e.g. cash interest)
+ * is grouped under a null investment_id.
+ * @param {Object[]} transactions - Income transactions
+ * @returns {Object[]} Array of { investment_id, description, unit_type, dividends, interest, total, payment_count }
+ */
+function totalsByInvestment(transactions) {
+  const byInvestment = {};
+
+  for (const tx of transactions) {
+    const key = tx.investment_id || "cash";
+    if (!byInvestment[key]) {
+      byInvestment[key] = {
+        investment_id: tx.investment_id || null,
+        description: tx.investment_description || "Cash interest",
+        unit_type: tx.investment_unit_type || null,
+        dividends: 0,
+        interest: 0,
+        total: 0,
+        payment_count: 0,
+      };
+    }
+    const entry = byInvestment[key];
+    if (tx.transaction_type === "dividend") {
+      entry.dividends += tx.amount;
+    } else {
+      entry.interest += tx.amount;
+    }
+    entry.total += tx.amount;
+    entry.payment_count++;
+  }
+
+  return Object.values(byInvestment)
+    .map(function (entry) {
+      entry.dividends = roundToPence(entry.dividends);
+      entry.interest = roundToPence(entry.interest);
+      entry.total = roundToPence(entry.total);
+      return entry;
+    })
+    .sort(function (a, b) { return b.total - a.total; });
+}
+
+/**
+ * @description Get dividend and interest income for an account, grouped by
+ * tax year with per-holding totals within each year.
+ * @param {number} accountId - The account ID
+ * @param {number|null} [taxYearStart=null] - Restrict to the tax year starting in this calendar year
+ * @returns {Object|null} Income summary, or null if the account is not found
+ */
+export function getAccountIncome(accountId, taxYearStart = null) {
+  const account = getAccountById(accountId);
+  if (!account) return null;
+
+  const filter = { accountId: accountId };
+  if (taxYearStart) {
+    const ty = getTaxYearByStartYear(taxYearStart);
+    filter.startDate = ty.start;
+    filter.endDate = ty.end;
+  }
+
+  const transactions = getIncomeTransactions(filter);
+  const years = groupByTaxYear(transactions);
+
+  let dividends = 0;
+  let interest = 0;
+  const taxYears = years.map(function (year) {
+    dividends += year.dividends;
+    interest += year.interest;
+    return {
+      label: year.label,
+      start: year.start,
+      end: year.end,
+      dividends: year.dividends,
+      interest: year.interest,
+      total: year.total,
+      holdings: totalsByInvestment(year.transactions),
+    };
+  });
+
+  return {
+    account: {
+      id: account.id,
+      user_id: account.user_id,
+      account_type: account.account_type,
+      account_ref: account.account_ref,
+      provider: account.provider,
+    },
+    tax_years: taxYears,
+    totals: {
+      dividends: roundToPence(dividends),
+      interest: roundToPence(interest),
+      total: roundToPence(dividends + interest),
+    },
+  };
+}
+
+/**
+ * @description Get dividend and interest income paid by a single holding's
+ * investment into its account, grouped by tax year. Any SCD2 row ID for the
+ * holding may be passed — income is matched on account and investment.
+ * @param {number} holdingId - The holding ID
+ * @returns {Object|null} Income summary, or null if the holding is not found
+ */
+export function getHoldingIncome(holdingId) {
+  const holding = getHoldingById(holdingId);
+  if (!holding) return null;
+
+  const transactions = getIncomeTransactions({
+    accountId: holding.account_id,
+    investmentId: holding.investment_id,
+  });
+  const years = groupByTaxYear(transactions);
+
+  let total = 0;
+  for (const year of years) {
+    total += year.total;
+  }
+
+  return {
+    holding_id: holding.id,
+    account_id: holding.account_id,
+    investment_id: holding.investment_id,
+    investment_description: holding.investment_description,
+    tax_years: years.map(function (year) {
+      return {
+        label: year.label,
+        start: year.start,
+        end: year.end,
+        dividends: year.dividends,
+        interest: year.interest,
+        total: year.total,
+        payments: year.transactions.map(function (tx) {
+          return {
+            id: tx.id,
+            transaction_type: tx.transaction_type,
+            transaction_date: tx.transaction_date,
+            amount: tx.amount,
+            notes: tx.notes,
+          };
+        }),
+      };
+    }),
+    total: roundToPence(total),
+  };
+}
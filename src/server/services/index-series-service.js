@@ -0,0 +1,171 @@
+import { parseCsv, parseBrokerDate, parseBrokerNumber } from "./broker-import-service.js";
+import { getIndexSeriesById, upsertIndexValues } from "../db/index-series-db.js";
+import { applyOtherAssetIndexation } from "../db/other-assets-db.js";
+import { processDrawdowns } from "./drawdown-processor.js";
+
+/**
+ * @description Month abbreviations used by ONS time series downloads (e.g. "2024 JAN").
+ * @type {string[]}
+ */
+const MONTHS = ["JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC"];
+
+/**
+ * @description Parse the date of an index value. Accepts YYYY-MM-DD, YYYY-MM,
+ * the ONS monthly form "YYYY MON", and the DD/MM/YYYY and DD-Mon-YYYY forms
+ * used by broker exports. Monthly values are dated the first of the month.
+ * @param {string} value - The date as exported
+ * @returns {string|null} ISO-8601 date (YYYY-MM-DD), or null if not recognised
+ */
+export function parseIndexDate(value) {
+  const text = String(value || "").trim();
+
+  const monthMatch = text.match(/^(\d{4})-(\d{2})$/);
+  if (monthMatch) {
+    const month = parseInt(monthMatch[2], 10);
+    return month >= 1 && month <= 12 ? text + "-01" : null;
+  }
+
+  const onsMatch = text.match(/^(\d{4})\s+([A-Za-z]{3})$/);
+  if (onsMatch) {
+    const monthIndex = MONTHS.indexOf(onsMatch[2].toUpperCase());
+    return monthIndex >= 0 ? onsMatch[1] + "-" + String(monthIndex + 1).padStart(2, "0") + "-01" : null;
+  }
+
+  return parseBrokerDate(text);
+}
+
+/**
+ * @description Find the date, region and index columns of a UK House Price
+ * Index download in a header row. Matches the full HM Land Registry file
+ * (Date, RegionName, ..., Index) and the single-region download from the
+ * UK HPI search (Name, Period, ..., House price index All property types).
+ * @param {string[]} row - A CSV row
+ * @returns {{ date: number, region: number, index: number }|null} Column positions, or null if the row is not an HPI header
+ */
+function findHpiColumns(row) {
+  const headers = row.map(function (cell) {
+    return String(cell).trim().toLowerCase();
+  });
+  const date = headers.findIndex(function (h) {
+    return h === "date" || h === "period";
+  });
+  const region = headers.findIndex(function (h) {
+    return h === "regionname" || h === "name";
+  });
+  const index = headers.findIndex(function (h) {
+    return h === "index" || h.startsWith("house price index");
+  });
+  if (date < 0 || region < 0 || index < 0) return null;
+  return { date: date, region: region, index: index };
+}
+
+/**
+ * @description Parse the rows of a UK House Price Index download that follow
+ * its header. The file covers many regions, so only rows for the chosen region
+ * (matched ignoring case) are read; a file with a single region needs none.
+ * @param {Array<string[]>} rows - CSV rows after the header
+ * @param {{ date: number, region: number, index: number }} columns - Column positions
+ * @param {string|null} region - Region name to load
+ * @returns {{ values: Array<{value_date: string, value: number}>, skipped: number }} Values read, and rows skipped
+ * @throws {Error} If the file covers several regions and none is chosen, or the chosen region is not in the file
+ */
+function parseHpiRows(rows, columns, region) {
+  const wanted = region ? String(region).trim().toLowerCase() : null;
+  const regions = [];
+  const values = [];
+  let matched = 0;
+  let skipped = 0;
+
+  for (const row of rows) {
+    if (row.length === 0 || row.every((cell) => String(cell).trim() === "")) continue;
+
+    const rowRegion = String(row[columns.region] || "").trim();
+    if (rowRegion !== "" && !regions.includes(rowRegion)) regions.push(rowRegion);
+    if (wanted !== null && rowRegion.toLowerCase() !== wanted) continue;
+    matched++;
+
+    const valueDate = parseIndexDate(row[columns.date]);
+    const value = parseBrokerNumber(row[columns.index]);
+    if (!valueDate || !(value > 0)) {
+      skipped++;
+      continue;
+    }
+    values.push({ value_date: valueDate, value: value });
+  }
+
+  if (wanted === null && regions.length > 1) {
+    throw new Error("The file covers " + regions.length + " regions — set the index Region to the RegionName to load (e.g. " + regions[0] + ")");
+  }
+  if (wanted !== null && matched === 0) {
+    throw new Error("Region '" + region + "' not found in the file");
+  }
+
+  return { values: values, skipped: skipped };
+}
+
+/**
+ * @description Parse index values from CSV text. The first column is the
+ * date and the second the index value. Rows whose date or value cannot be
+ * read are skipped, so header rows and the metadata, annual and quarterly
+ * rows in an ONS download are ignored. A UK House Price Index download is
+ * recognised by its header and read by column instead, taking the rows for
+ * the given region.
+ * @param {string} csvText - The CSV file contents
+ * @param {string|null} [region] - Region to load from a UK House Price Index file
+ * @returns {{ values: Array<{value_date: string, value: number}>, skipped: number }} Values read, and rows skipped
+ * @throws {Error} If a House Price Index file covers several regions and none is chosen,
+ *   or the chosen region is not in the file
+ */
+export function parseIndexCsv(csvText, region) {
+  const rows = parseCsv(csvText);
+  const headerAt = rows.findIndex(function (row) {
+    return findHpiColumns(row) !== null;
+  });
+  if (headerAt >= 0) {
+    return parseHpiRows(rows.slice(headerAt + 1), findHpiColumns(rows[headerAt]), region || null);
+  }
+
+  const values = [];
+  let skipped = 0;
+
+  for (const row of rows) {
+    if (row.length === 0 || row.every((cell) => String(cell).trim() === "")) continue;
+
+    const valueDate = parseIndexDate(row[0]);
+    const value = row.length > 1 ? parseBrokerNumber(row[1]) : 0;
+    if (!valueDate || !(value > 0)) {
+      skipped++;
+      continue;
+    }
+    values.push({ value_date: valueDate, value: value });
+  }
+
+  return { values: values, skipped: skipped };
+}
+
+/**
+ * @description Import index values from CSV text into an index series.
+ * Values for dates already held are replaced, properties valued by the
+ * series are re-estimated from the new values, and drawdowns held back for an
+ * index-linked rise that can now be worked out are recorded.
+ * @param {number} seriesId - The index series ID
+ * @param {string} csvText - The CSV file contents
+ * @returns {Object|null} The number of values imported and rows skipped, the number of
+ *   property values re-estimated and drawdowns recorded, with the updated series, or null if
+ *   the series is not found
+ * @throws {Error} If the CSV holds no usable values
+ */
+export function importIndexCsv(seriesId, csvText) {
+  const series = getIndexSeriesById(seriesId);
+  if (!series) return null;
+
+  const parsed = parseIndexCsv(csvText, series.region);
+  if (parsed.values.length === 0) {
+    throw new Error("No index values found — expected a date and a value on each row");
+  }
+
+  const imported = upsertIndexValues(seriesId, parsed.values);
+  const indexation = applyOtherAssetIndexation();
+  const drawdowns = processDrawdowns();
+  return { imported: imported, skipped: parsed.skipped, indexed: indexation.indexed, drawdowns: drawdowns.processed, series: getIndexSeriesById(seriesId) };
+}
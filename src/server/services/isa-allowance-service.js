@@ -0,0 +1,224 @@
+import { getUserById, getAllUsers } from "../db/users-db.js";
+import { getAccountById, getAccountsByUserId } from "../db/accounts-db.js";
+import { getIsaSubscriptionsForUser } from "../db/cash-transactions-db.js";
+import { getIsaAllowanceConfig } from "../config.js";
+import { getTaxYearForDate, getTaxYearByStartYear } from "./tax-year-utils.js";
+
+/**
+ * @description Published HMRC ISA subscription limits keyed by the calendar
+ * year in which the tax year starts. Years not listed fall back to the
+ * configured isaAllowance.annualLimit.
+ * @type {Object<number, number>}
+ */
+const HISTORIC_ISA_LIMITS = {
+  2014: 15000,
+  2015: 15240,
+  2016: 15240,
+};
+
+/**
+ * @description Round a decimal to 2 decimal places (pence).
+ * @param {number} value - The value to round
+ * @returns {number} The value rounded to pence
+ */
+function roundToPence(value) {
+  return Math.round(value * 100) / 100;
+}
+
+/**
+ * @description Get the ISA subscription limit for a tax year.
+ * @param {number} startYear - Calendar year in which the tax year starts
+ * @returns {number} The annual limit in GBP
+ */
+export function getIsaAnnualLimit(startYear) {
+  if (HISTORIC_ISA_LIMITS[startYear] !== undefined) {
+    return HISTORIC_ISA_LIMITS[startYear];
+  }
+  return getIsaAllowanceConfig().annualLimit;
+}
+
+/**
+ * @description Get the calendar year in which the current tax year starts.
+ * @returns {number} The start year
+ */
+function currentTaxYearStart() {
+  const taxYear = getTaxYearForDate(new Date().toISOString().slice(0, 10));
+  return Number(taxYear.start.slice(0, 4));
+}
+
+/**
+ * @description Summarise a user's ISA subscriptions for one tax year.
+ * @param {Object[]} isaAccounts - The user's ISA accounts
+ * @param {Object[]} subscriptions - Subscription transactions within the tax year
+ * @param {number} startYear - Calendar year in which the tax year starts
+ * @returns {Object} Usage with { tax_year, tax_year_start, tax_year_end, annual_limit,
+ *   deposits, transfers_in, used, remaining, over_limit, accounts }
+ */
+function summariseTaxYear(isaAccounts, subscriptions, startYear) {
+  const taxYear = getTaxYearByStartYear(startYear);
+  const annualLimit = getIsaAnnualLimit(startYear);
+
+  const accounts = isaAccounts.map(function (a) {
+    return {
+      account_id: a.id,
+      account_ref: a.account_ref,
+      provider: a.provider,
+      deposits: 0,
+      transfers_in: 0,
+      total: 0,
+    };
+  });
+
+  let deposits = 0;
+  let transfersIn = 0;
+  for (const tx of subscriptions) {
+    const entry = accounts.find(function (a) {
+      return a.account_id === tx.account_id;
+    });
+    if (tx.is_transfer) {
+      transfersIn += tx.amount;
+      if (entry) entry.transfers_in += tx.amount;
+    } else {
+      deposits += tx.amount;
+      if (entry) entry.deposits += tx.amount;
+    }
+  }
+
+  for (const a of accounts) {
+    a.deposits = roundToPence(a.deposits);
+    a.transfers_in = roundToPence(a.transfers_in);
+    a.total = roundToPence(a.deposits + a.transfers_in);
+  }
+
+  const used = roundToPence(deposits + transfersIn);
+
+  return {
+    tax_year: taxYear.label,
+    tax_year_start: taxYear.start,
+    tax_year_end: taxYear.end,
+    annual_limit: annualLimit,
+    deposits: roundToPence(deposits),
+    transfers_in: roundToPence(transfersIn),
+    used: used,
+    remaining: roundToPence(Math.max(0, annualLimit - used)),
+    over_limit: used > annualLimit,
+    accounts: accounts,
+  };
+}
+
+/**
+ * @description Get a user's ISA allowance usage for a tax year, across every
+ * ISA they hold. Deposits count, as do transfers in of the same year's
+ * subscriptions from an ISA held elsewhere; transfers between two of the
+ * user's own ISAs, and of earlier years' subscriptions, do not.
+ * @param {number} userId - The user ID
+ * @param {number|null} [taxYearStart=null] - Calendar year the tax year starts in; defaults to the current tax year
+ * @returns {Object|null} Usage with a user summary and per-account breakdown, or null if the user is not found
+ */
+export function getIsaAllowanceForUser(userId, taxYearStart = null) {
+  const user = getUserById(userId);
+  if (!user) return null;
+
+  const startYear = taxYearStart || currentTaxYearStart();
+  const taxYear = getTaxYearByStartYear(startYear);
+  const isaAccounts = getAccountsByUserId(userId).filter(function (a) {
+    return a.account_type === "isa";
+  });
+  const subscriptions = getIsaSubscriptionsForUser(userId, taxYear.start, taxYear.end);
+
+  const usage = summariseTaxYear(isaAccounts, subscriptions, startYear);
+  usage.user = {
+    id: user.id,
+    initials: user.initials,
+    first_name: user.first_name,
+    last_name: user.last_name,
+  };
+  return usage;
+}
+
+/**
+ * @description Get a user's ISA allowance usage for every tax year from their
+ * first subscription to the current tax year, newest first. Years with no
+ * subscriptions are included with zero usage.
+ * @param {number} userId - The user ID
+ * @returns {Object|null} Object with { user, tax_years }, or null if the user is not found
+ */
+export function getIsaAllowanceHistory(userId) {
+  const user = getUserById(userId);
+  if (!user) return null;
+
+  const isaAccounts = getAccountsByUserId(userId).filter(function (a) {
+    return a.account_type === "isa";
+  });
+  const subscriptions = getIsaSubscriptionsForUser(userId);
+
+  const lastStartYear = currentTaxYearStart();
+  let firstStartYear = lastStartYear;
+  if (subscriptions.length > 0) {
+    const first = getTaxYearForDate(subscriptions[0].transaction_date);
+    firstStartYear = Math.min(firstStartYear, Number(first.start.slice(0, 4)));
+  }
+
+  const taxYears = [];
+  for (let year = lastStartYear; year >= firstStartYear; year--) {
+    const ty = getTaxYearByStartYear(year);
+    const yearSubscriptions = subscriptions.filter(function (tx) {
+      return tx.transaction_date >= ty.start && tx.transaction_date <= ty.end;
+    });
+    taxYears.push(summariseTaxYear(isaAccounts, yearSubscriptions, year));
+  }
+
+  return {
+    user: {
+      id: user.id,
+      initials: user.initials,
+      first_name: user.first_name,
+      last_name: user.last_name,
+    },
+    tax_years: taxYears,
+  };
+}
+
+/**
+ * @description Get ISA allowance usage for a tax year for each user who holds
+ * at least one ISA, ordered by last name then first name.
+ * @param {number[]|null} [userIds=null] - Restrict to these users; defaults to everyone
+ * @param {number|null} [taxYearStart=null] - Calendar year the tax year starts in; defaults to the current tax year
+ * @returns {Object[]} Usage per user, as returned by getIsaAllowanceForUser
+ */
+export function getIsaAllowanceForAllUsers(userIds = null, taxYearStart = null) {
+  const results = [];
+  for (const user of getAllUsers()) {
+    if (userIds && userIds.indexOf(user.id) === -1) continue;
+    const usage = getIsaAllowanceForUser(user.id, taxYearStart);
+    if (usage && usage.accounts.length > 0) {
+      results.push(usage);
+    }
+  }
+  return results;
+}
+
+/**
+ * @description Check whether a new subscription into an ISA would take its
+ * owner over the annual limit for the tax year of the subscription date.
+ * @param {number} accountId - The ISA account receiving the subscription
+ * @param {string} transactionDate - ISO-8601 date of the subscription (YYYY-MM-DD)
+ * @param {number} amount - The subscription amount in GBP
+ * @returns {Object|null} Object with { tax_year, annual_limit, used, remaining, exceeds },
+ *   or null if the account is not found or is not an ISA
+ */
+export function checkIsaSubscription(accountId, transactionDate, amount) {
+  const account = getAccountById(accountId);
+  if (!account || account.account_type !== "isa") return null;
+
+  const taxYear = getTaxYearForDate(transactionDate);
+  const usage = getIsaAllowanceForUser(account.user_id, Number(taxYear.start.slice(0, 4)));
+
+  return {
+    tax_year: usage.tax_year,
+    annual_limit: usage.annual_limit,
+    used: usage.used,
+    remaining: usage.remaining,
+    exceeds: roundToPence(usage.used + amount) > usage.annual_limit,
+  };
+}
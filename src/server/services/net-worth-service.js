@@ -0,0 +1,209 @@
+import { getAllUsers } from "../db/users-db.js";
+import { getHouseholdAssetsSummary, getOtherAssetTotalsAtDate, getOtherAssetValuesAtDate, OTHER_ASSET_CATEGORY_LABELS } from "../db/other-assets-db.js";
+import { getPortfolioSummary, getPortfolioSummaryAtDate } from "./portfolio-service.js";
+import { buildMonthEndDates } from "./allocation-service.js";
+import { addMonths } from "./tax-year-utils.js";
+import { CURRENCY_SCALE_FACTOR } from "../../shared/server-constants.js";
+
+/**
+ * @description Longest amortisation schedule built, in months (50 years).
+ * A loan still outstanding after this is reported as not repaid.
+ * @type {number}
+ */
+const MAX_AMORTISATION_MONTHS = 600;
+
+/**
+ * @description Round a value to 2 decimal places (pence precision).
+ * @param {number} value - The value to round
+ * @returns {number} Value rounded to 2 decimal places
+ */
+function roundToPence(value) {
+  return Math.round(value * 100) / 100;
+}
+
+/**
+ * @description Value the household's portfolios (investments and cash across
+ * every user) today or on a past date.
+ * @param {string} [date] - ISO-8601 date to value at; today's prices when omitted
+ * @returns {{ total: number, cash_available: boolean }} Total in GBP, and false when a
+ *   historic cash balance could not be reconstructed (the total is then investments only)
+ */
+export function getHouseholdPortfolioTotal(date) {
+  let total = 0;
+  let cashAvailable = true;
+
+  for (const user of getAllUsers()) {
+    const summary = date ? getPortfolioSummaryAtDate(user.id, date) : getPortfolioSummary(user.id);
+    if (!summary) continue;
+    total += summary.totals.investments;
+    if (summary.totals.cash === null) {
+      cashAvailable = false;
+    } else {
+      total += summary.totals.cash;
+    }
+  }
+
+  return { total: roundToPence(total), cash_available: cashAvailable };
+}
+
+/**
+ * @description Build the household assets summary with the net-worth figure:
+ * the portfolios plus the value-type other assets, less liabilities.
+ * Recurring income is not capital, so is left out of net worth.
+ * @returns {Object} getHouseholdAssetsSummary() with totals.portfolio_total and
+ *   totals.net_worth added (GBP × 10000, like the other totals)
+ */
+export function getNetWorthSummary() {
+  const summary = getHouseholdAssetsSummary();
+  const portfolioTotal = Math.round(getHouseholdPortfolioTotal().total * CURRENCY_SCALE_FACTOR);
+  summary.totals.portfolio_total = portfolioTotal;
+  summary.totals.net_worth = portfolioTotal + summary.totals.net_assets;
+  return summary;
+}
+
+/**
+ * @description Build the household's net worth at the end of each previous
+ * month and today. Portfolios are valued from the holdings, prices and rates
+ * on each date; other assets and liabilities from their change history.
+ * @param {number} months - Number of whole months to look back
+ * @returns {Object} History with { dates, portfolio, other_assets, liabilities, net_worth, cash_available },
+ *   amounts in GBP, one entry per date
+ */
+export function buildNetWorthHistory(months) {
+  const dates = buildMonthEndDates(months, new Date().toISOString().slice(0, 10));
+  const history = { dates: dates, portfolio: [], other_assets: [], liabilities: [], net_worth: [], cash_available: [] };
+
+  for (const date of dates) {
+    const portfolio = getHouseholdPortfolioTotal(date);
+    const others = getOtherAssetTotalsAtDate(date);
+    const otherAssets = others.assets / CURRENCY_SCALE_FACTOR;
+    const liabilities = others.liabilities / CURRENCY_SCALE_FACTOR;
+
+    history.portfolio.push(portfolio.total);
+    history.other_assets.push(roundToPence(otherAssets));
+    history.liabilities.push(roundToPence(liabilities));
+    history.net_worth.push(roundToPence(portfolio.total + otherAssets - liabilities));
+    history.cash_available.push(portfolio.cash_available);
+  }
+
+  return history;
+}
+
+/**
+ * @description Build the value of each other asset and liability, and the
+ * total of each category, at the end of each previous month and today, from
+ * the change history. Category totals cover value-type items only, as
+ * recurring income amounts are not values that can be added up. An item is
+ * valued at zero on dates before it was held or after it was closed.
+ * @param {number} months - Number of whole months to look back
+ * @param {Object} [filter] - Optional filter
+ * @param {string[]} [filter.categories] - Category keys to include (all when omitted or empty)
+ * @param {number} [filter.assetId] - A single asset to include
+ * @returns {Object} History with { dates, assets: [{id, description, category, value_type, values}],
+ *   categories: [{key, label, values}] }, amounts in GBP, one value per date. Categories
+ *   without value-type items are left out
+ */
+export function buildOtherAssetValueHistory(months, filter) {
+  const categoryKeys = filter && filter.categories && filter.categories.length > 0 ? filter.categories : Object.keys(OTHER_ASSET_CATEGORY_LABELS);
+  const assetId = filter && filter.assetId ? Number(filter.assetId) : null;
+  const dates = buildMonthEndDates(months, new Date().toISOString().slice(0, 10));
+
+  /** @type {Map<number, Object>} */
+  const assets = new Map();
+  /** @type {Object<string, number[]>} */
+  const totals = {};
+  for (const key of categoryKeys) {
+    totals[key] = dates.map(function () {
+      return 0;
+    });
+  }
+
+  dates.forEach(function (date, i) {
+    for (const row of getOtherAssetValuesAtDate(date)) {
+      if (!totals[row.category] || (assetId !== null && row.id !== assetId)) continue;
+      if (!assets.has(row.id)) {
+        const values = dates.map(function () {
+          return 0;
+        });
+        assets.set(row.id, { id: row.id, description: row.description, category: row.category, value_type: row.value_type, values: values });
+      }
+      assets.get(row.id).values[i] = roundToPence(row.value_at_date / CURRENCY_SCALE_FACTOR);
+      if (row.value_type === "value") {
+        totals[row.category][i] += row.value_at_date;
+      }
+    }
+  });
+
+  // Report assets in category order, then by description as read
+  const assetList = Array.from(assets.values()).sort(function (a, b) {
+    return categoryKeys.indexOf(a.category) - categoryKeys.indexOf(b.category);
+  });
+  const categories = categoryKeys
+    .filter(function (key) {
+      return assetList.some(function (asset) {
+        return asset.category === key && asset.value_type === "value";
+      });
+    })
+    .map(function (key) {
+      return {
+        key: key,
+        label: OTHER_ASSET_CATEGORY_LABELS[key] || key,
+        values: totals[key].map(function (total) {
+          return roundToPence(total / CURRENCY_SCALE_FACTOR);
+        }),
+      };
+    });
+
+  return { dates: dates, assets: assetList, categories: categories };
+}
+
+/**
+ * @description Build the month-by-month repayment schedule of a loan. Interest
+ * is charged monthly at a twelfth of the annual rate on the balance, then the
+ * repayment is taken; the final payment clears what is left.
+ * @param {number} balance - Balance owed in GBP
+ * @param {number|null} annualRate - Annual interest rate as a percentage (null for interest-free)
+ * @param {number} repayment - Monthly repayment in GBP
+ * @param {string} startDate - ISO-8601 date of the balance; the first payment is a month later
+ * @returns {Object} Schedule with { rows: [{date, payment, interest, principal, balance}], months,
+ *   payoff_date, total_interest, total_paid, never_repaid }
+ */
+export function buildAmortisationSchedule(balance, annualRate, repayment, startDate) {
+  const monthlyRate = (annualRate || 0) / 100 / 12;
+  const rows = [];
+  let remaining = roundToPence(balance);
+  let totalInterest = 0;
+  let totalPaid = 0;
+
+  // A repayment that does not cover the first month's interest never clears the loan
+  if (remaining > 0 && repayment <= roundToPence(remaining * monthlyRate)) {
+    return { rows: [], months: null, payoff_date: null, total_interest: null, total_paid: null, never_repaid: true };
+  }
+
+  while (remaining > 0 && rows.length < MAX_AMORTISATION_MONTHS) {
+    const interest = roundToPence(remaining * monthlyRate);
+    const payment = roundToPence(Math.min(repayment, remaining + interest));
+    const principal = roundToPence(payment - interest);
+    remaining = roundToPence(remaining - principal);
+    totalInterest += interest;
+    totalPaid += payment;
+
+    rows.push({
+      date: addMonths(startDate, rows.length + 1),
+      payment: payment,
+      interest: interest,
+      principal: principal,
+      balance: remaining,
+    });
+  }
+
+  const neverRepaid = remaining > 0;
+  return {
+    rows: rows,
+    months: neverRepaid ? null : rows.length,
+    payoff_date: neverRepaid || rows.length === 0 ? null : rows[rows.length - 1].date,
+    total_interest: roundToPence(totalInterest),
+    total_paid: roundToPence(totalPaid),
+    never_repaid: neverRepaid,
+  };
+}
@@ -0,0 +1,398 @@
+import { getUserById, getAllUsers } from "../db/users-db.js";
+import { getAccountsByUserId } from "../db/accounts-db.js";
+import { getPensionTransactionsForUser } from "../db/cash-transactions-db.js";
+import { getPensionAllowanceConfig } from "../config.js";
+import { getTaxYearForDate, getTaxYearByStartYear } from "./tax-year-utils.js";
+
+/**
+ * @description Published HMRC annual allowances keyed by the calendar year in
+ * which the tax year starts, from its introduction on 6 April 2006. Later
+ * years not listed fall back to the configured pensionAllowance.annualAllowance.
+ * @type {Object<number, number>}
+ */
+const HISTORIC_ANNUAL_ALLOWANCES = {
+  2006: 215000,
+  2007: 225000,
+  2008: 235000,
+  2009: 245000,
+  2010: 255000,
+  2011: 50000,
+  2012: 50000,
+  2013: 50000,
+  2014: 40000,
+  2015: 40000,
+  2016: 40000,
+  2017: 40000,
+  2018: 40000,
+  2019: 40000,
+  2020: 40000,
+  2021: 40000,
+  2022: 40000,
+};
+
+/**
+ * @description Published HMRC money purchase annual allowances keyed by the
+ * calendar year in which the tax year starts. The MPAA was introduced in
+ * 2015/2016; later years not listed fall back to the configured
+ * pensionAllowance.moneyPurchaseAnnualAllowance.
+ * @type {Object<number, number>}
+ */
+const HISTORIC_MONEY_PURCHASE_ALLOWANCES = {
+  2015: 10000,
+  2016: 10000,
+  2017: 4000,
+  2018: 4000,
+  2019: 4000,
+  2020: 4000,
+  2021: 4000,
+  2022: 4000,
+};
+
+/** @description First tax year (start year) in which the MPAA applied */
+const FIRST_MPAA_YEAR = 2015;
+
+/** @description First tax year (start year) in which the annual allowance applied */
+export const FIRST_ANNUAL_ALLOWANCE_YEAR = 2006;
+
+/** @description First tax year (start year) in which unused allowance could be carried forward */
+const FIRST_CARRY_FORWARD_YEAR = 2011;
+
+/**
+ * @description Allowance HMRC treats as available in 2008/2009 to 2010/2011
+ * when working out unused allowance to carry forward into 2011/2012 onwards,
+ * in place of the much higher allowance that actually applied then.
+ */
+const NOTIONAL_CARRY_FORWARD_ALLOWANCE = 50000;
+
+/** @description Number of earlier tax years whose unused allowance can be carried forward */
+const CARRY_FORWARD_YEARS = 3;
+
+/**
+ * @description Round a decimal to 2 decimal places (pence).
+ * @param {number} value - The value to round
+ * @returns {number} The value rounded to pence
+ */
+function roundToPence(value) {
+  return Math.round(value * 100) / 100;
+}
+
+/**
+ * @description Get the annual allowance for a tax year.
+ * @param {number} startYear - Calendar year in which the tax year starts
+ * @returns {number|null} The annual allowance in GBP, or null before it was introduced
+ */
+export function getAnnualAllowance(startYear) {
+  if (startYear < FIRST_ANNUAL_ALLOWANCE_YEAR) return null;
+  if (HISTORIC_ANNUAL_ALLOWANCES[startYear] !== undefined) {
+    return HISTORIC_ANNUAL_ALLOWANCES[startYear];
+  }
+  return getPensionAllowanceConfig().annualAllowance;
+}
+
+/**
+ * @description Get the money purchase annual allowance for a tax year.
+ * @param {number} startYear - Calendar year in which the tax year starts
+ * @returns {number|null} The MPAA in GBP, or null before it was introduced
+ */
+export function getMoneyPurchaseAnnualAllowance(startYear) {
+  if (startYear < FIRST_MPAA_YEAR) return null;
+  if (HISTORIC_MONEY_PURCHASE_ALLOWANCES[startYear] !== undefined) {
+    return HISTORIC_MONEY_PURCHASE_ALLOWANCES[startYear];
+  }
+  return getPensionAllowanceConfig().moneyPurchaseAnnualAllowance;
+}
+
+/**
+ * @description Split a gross personal contribution into the amount the member
+ * pays in and the basic rate relief the provider claims from HMRC.
+ * @param {number} grossAmount - The gross contribution in GBP
+ * @returns {{ net: number, relief: number }} Net payment and relief in GBP
+ */
+export function splitReliefAtSource(grossAmount) {
+  const rate = getPensionAllowanceConfig().basicRateRelief;
+  const net = roundToPence((grossAmount * (100 - rate)) / 100);
+  return { net: net, relief: roundToPence(grossAmount - net) };
+}
+
+/**
+ * @description Get the calendar year in which the tax year containing a date starts.
+ * @param {string} dateStr - ISO-8601 date (YYYY-MM-DD)
+ * @returns {number} The start year
+ */
+function taxYearStartOf(dateStr) {
+  return Number(getTaxYearForDate(dateStr).start.slice(0, 4));
+}
+
+/**
+ * @description Find the date a user first took flexible drawdown from any of
+ * their SIPPs: the earliest drawdown payment actually recorded. Schedules are
+ * not looked at directly — a paused schedule, or a due date the drawdown
+ * processor has not yet run for, has not paid anything out.
+ * @param {Object[]} transactions - The user's pension transactions, oldest first
+ * @returns {string|null} ISO-8601 trigger date, or null if drawdown has not started
+ */
+function findMpaaTriggerDate(transactions) {
+  for (const tx of transactions) {
+    if (tx.transaction_type === "drawdown") {
+      return tx.transaction_date;
+    }
+  }
+  return null;
+}
+
+/**
+ * @description Work through a user's pension contributions tax year by tax
+ * year, oldest first, applying the annual allowance, carry-forward and the
+ * money purchase annual allowance.
+ *
+ * Carry-forward uses unused allowance from the three previous tax years,
+ * earliest first, once the current year's allowance is used up. A year only
+ * has unused allowance if the user was a pension scheme member in it, taken
+ * here as any year from their first recorded SIPP transaction. Once flexible
+ * drawdown has started, contributions made afterwards are tested against the
+ * MPAA, carry-forward cannot be used against it, and no further unused
+ * allowance arises. Carry-forward began in 2011/2012; unused allowance from
+ * the three years before it is measured against HMRC's notional £50,000, and
+ * nothing is calculated before the annual allowance began in 2006/2007. The
+ * tapered annual allowance is not modelled.
+ *
+ * @param {Object[]} sippAccounts - The user's SIPP accounts
+ * @param {Object[]} transactions - The user's pension transactions, oldest first
+ * @param {number} lastStartYear - Last tax year (start year) to calculate
+ * @returns {Object[]} Usage per tax year, oldest first, including up to three
+ *   earlier years worked out only for carry-forward
+ */
+function calculateTaxYears(sippAccounts, transactions, lastStartYear) {
+  const mpaaTriggerDate = findMpaaTriggerDate(transactions);
+  const mpaaTriggerYear = mpaaTriggerDate ? taxYearStartOf(mpaaTriggerDate) : null;
+
+  const firstMemberYear = transactions.length > 0 ? taxYearStartOf(transactions[0].transaction_date) : lastStartYear;
+  const firstStartYear = Math.max(FIRST_ANNUAL_ALLOWANCE_YEAR, Math.min(firstMemberYear, lastStartYear) - CARRY_FORWARD_YEARS);
+
+  /** @type {Object<number, number>} Unused allowance still available to carry forward, by start year */
+  const unusedByYear = {};
+  const years = [];
+
+  for (let year = firstStartYear; year <= lastStartYear; year++) {
+    const ty = getTaxYearByStartYear(year);
+    const annualAllowance = getAnnualAllowance(year);
+    const mpaa = getMoneyPurchaseAnnualAllowance(year);
+    const mpaaApplies = mpaa !== null && mpaaTriggerYear !== null && year >= mpaaTriggerYear;
+
+    const accounts = sippAccounts.map(function (a) {
+      return {
+        account_id: a.id,
+        account_ref: a.account_ref,
+        provider: a.provider,
+        personal_gross: 0,
+        employer: 0,
+        tax_relief: 0,
+        total: 0,
+      };
+    });
+
+    let personalGross = 0;
+    let personalNet = 0;
+    let employer = 0;
+    let reliefReceived = 0;
+    let afterTrigger = 0;
+
+    for (const tx of transactions) {
+      if (tx.transaction_date < ty.start || tx.transaction_date > ty.end) continue;
+      const entry = accounts.find(function (a) {
+        return a.account_id === tx.account_id;
+      });
+
+      if (tx.transaction_type === "tax_relief") {
+        reliefReceived += tx.amount;
+        if (entry) entry.tax_relief += tx.amount;
+        continue;
+      }
+      if (!tx.contribution_type) continue;
+
+      if (tx.contribution_type === "employer") {
+        employer += tx.gross_amount;
+        if (entry) entry.employer += tx.gross_amount;
+      } else {
+        personalGross += tx.gross_amount;
+        personalNet += tx.amount;
+        if (entry) entry.personal_gross += tx.gross_amount;
+      }
+      if (mpaaApplies && tx.transaction_date >= mpaaTriggerDate) {
+        afterTrigger += tx.gross_amount;
+      }
+    }
+
+    for (const a of accounts) {
+      a.personal_gross = roundToPence(a.personal_gross);
+      a.employer = roundToPence(a.employer);
+      a.tax_relief = roundToPence(a.tax_relief);
+      a.total = roundToPence(a.personal_gross + a.employer);
+    }
+
+    const total = roundToPence(personalGross + employer);
+
+    // After the trigger year only the MPAA is available, with no carry-forward
+    const mpaaOnly = mpaaApplies && year > mpaaTriggerYear;
+    const allowance = mpaaOnly ? mpaa : annualAllowance;
+
+    // Carry-forward from the previous three years, earliest first, covers
+    // contributions above this year's allowance
+    const canCarryForward = !mpaaOnly && year >= FIRST_CARRY_FORWARD_YEAR;
+    const carryForward = [];
+    let carryForwardAvailable = 0;
+    let carryForwardUsed = 0;
+    let shortfall = Math.max(0, total - allowance);
+    for (let prev = year - CARRY_FORWARD_YEARS; prev < year; prev++) {
+      const unused = canCarryForward ? unusedByYear[prev] || 0 : 0;
+      const used = Math.min(unused, shortfall);
+      shortfall = roundToPence(shortfall - used);
+      carryForwardAvailable += unused;
+      carryForwardUsed += used;
+      if (canCarryForward) unusedByYear[prev] = roundToPence(unused - used);
+      carryForward.push({
+        tax_year: getTaxYearByStartYear(prev).label,
+        unused: roundToPence(unused),
+        used: roundToPence(used),
+      });
+    }
+
+    // In the trigger year, contributions after drawdown started are tested
+    // against the MPAA and those before against the alternative annual
+    // allowance (annual allowance less MPAA); the higher excess applies
+    let excess = shortfall;
+    if (mpaaApplies && !mpaaOnly && afterTrigger > mpaa) {
+      const alternativeExcess = afterTrigger - mpaa + Math.max(0, total - afterTrigger - (annualAllowance - mpaa) - carryForwardAvailable);
+      excess = Math.max(excess, alternativeExcess);
+    }
+
+    // Unused allowance arising this year, available to the next three years
+    const isMember = year >= firstMemberYear;
+    const unusedAgainst = year < FIRST_CARRY_FORWARD_YEAR ? NOTIONAL_CARRY_FORWARD_ALLOWANCE : annualAllowance;
+    unusedByYear[year] = isMember && !mpaaApplies ? roundToPence(Math.max(0, unusedAgainst - total)) : 0;
+
+    // Further contributions this year are limited by the MPAA once drawdown has started
+    const available = roundToPence(allowance + carryForwardAvailable);
+    let remaining = Math.max(0, available - total);
+    if (mpaaApplies && !mpaaOnly) {
+      remaining = Math.min(remaining, Math.max(0, mpaa - afterTrigger));
+    }
+
+    years.push({
+      tax_year: ty.label,
+      tax_year_start: ty.start,
+      tax_year_end: ty.end,
+      annual_allowance: allowance,
+      money_purchase_annual_allowance: mpaa,
+      mpaa_applies: mpaaApplies,
+      mpaa_triggered_on: mpaaApplies ? mpaaTriggerDate : null,
+      personal_gross: roundToPence(personalGross),
+      personal_net: roundToPence(personalNet),
+      employer: roundToPence(employer),
+      total: total,
+      tax_relief_expected: roundToPence(personalGross - personalNet),
+      tax_relief_received: roundToPence(reliefReceived),
+      carry_forward: carryForward,
+      carry_forward_available: roundToPence(carryForwardAvailable),
+      carry_forward_used: roundToPence(carryForwardUsed),
+      available: available,
+      remaining: roundToPence(remaining),
+      excess: roundToPence(excess),
+      over_limit: excess > 0,
+      accounts: accounts,
+    });
+  }
+
+  return years;
+}
+
+/**
+ * @description Load what the allowance calculation needs for a user.
+ * @param {number} userId - The user ID
+ * @returns {{ user: Object, sippAccounts: Object[], transactions: Object[] }|null} Null if the user is not found
+ */
+function loadUserPensions(userId) {
+  const user = getUserById(userId);
+  if (!user) return null;
+
+  const sippAccounts = getAccountsByUserId(userId).filter(function (a) {
+    return a.account_type === "sipp";
+  });
+
+  return {
+    user: {
+      id: user.id,
+      initials: user.initials,
+      first_name: user.first_name,
+      last_name: user.last_name,
+    },
+    sippAccounts: sippAccounts,
+    transactions: getPensionTransactionsForUser(userId),
+  };
+}
+
+/**
+ * @description Get a user's pension annual allowance usage for a tax year
+ * across every SIPP they hold, including carry-forward from the three
+ * previous tax years and the money purchase annual allowance once flexible
+ * drawdown has started.
+ * @param {number} userId - The user ID
+ * @param {number|null} [taxYearStart=null] - Calendar year the tax year starts in; defaults to the current tax year
+ * @returns {Object|null} Usage with a user summary and per-account breakdown, or null if the user is not found
+ */
+export function getPensionAllowanceForUser(userId, taxYearStart = null) {
+  const pensions = loadUserPensions(userId);
+  if (!pensions) return null;
+
+  const startYear = taxYearStart || taxYearStartOf(new Date().toISOString().slice(0, 10));
+  const years = calculateTaxYears(pensions.sippAccounts, pensions.transactions, startYear);
+
+  const usage = years[years.length - 1];
+  usage.user = pensions.user;
+  return usage;
+}
+
+/**
+ * @description Get a user's pension annual allowance usage for every tax year
+ * from their first recorded SIPP transaction to the current tax year, newest first.
+ * @param {number} userId - The user ID
+ * @returns {Object|null} Object with { user, tax_years }, or null if the user is not found
+ */
+export function getPensionAllowanceHistory(userId) {
+  const pensions = loadUserPensions(userId);
+  if (!pensions) return null;
+
+  const currentStartYear = taxYearStartOf(new Date().toISOString().slice(0, 10));
+  const years = calculateTaxYears(pensions.sippAccounts, pensions.transactions, currentStartYear);
+
+  let first = currentStartYear;
+  if (pensions.transactions.length > 0) {
+    first = Math.min(first, taxYearStartOf(pensions.transactions[0].transaction_date));
+  }
+  const firstLabel = getTaxYearByStartYear(first).label;
+  const firstIndex = years.findIndex(function (ty) {
+    return ty.tax_year === firstLabel;
+  });
+
+  return {
+    user: pensions.user,
+    tax_years: years.slice(Math.max(0, firstIndex)).reverse(),
+  };
+}
+
+/**
+ * @description Get pension annual allowance usage for a tax year for each user
+ * who holds at least one SIPP.
+ * @param {number|null} [taxYearStart=null] - Calendar year the tax year starts in; defaults to the current tax year
+ * @returns {Object[]} Usage per user, as returned by getPensionAllowanceForUser
+ */
+export function getPensionAllowanceForAllUsers(taxYearStart = null) {
+  const results = [];
+  for (const user of getAllUsers()) {
+    const usage = getPensionAllowanceForUser(user.id, taxYearStart);
+    if (usage && usage.accounts.length > 0) {
+      results.push(usage);
+    }
+  }
+  return results;
+}
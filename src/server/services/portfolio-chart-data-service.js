@@ -1,7 +1,7 @@
 import { getAllUsers } from "../db/users-db.js";
 import { getPortfolioSummaryAtDate } from "./portfolio-service.js";
 import { getGlobalEventsInRange } from "../db/global-events-db.js";
-import { getReportParams } from "../db/report-params-db.js";
+import { resolveParams } from "../reports/pdf-common.js";
 import { rebaseToZero, generateWeeklyDates, generateFortnightlyDates, formatISODate } from "./price-utils.js";
 
 /**
@@ -86,11 +86,62 @@ export function getPortfolioChartData(chartDef) {
   };
 }
 
+/**
+ * @description Parse a single account token from a portfolio param. A token is
+ * an account type, optionally narrowed to one provider ("isa@hl") or to one
+ * account reference ("sipp~3366521"), for users who hold several accounts of
+ * the same type. "~" is used because it is sent as it is in a URL, where a
+ * "#" would start the fragment.
+ * @param {string} token - The account token (e.g. "isa", "isa@hl", "sipp~3366521")
+ * @returns {Object|null} Selector with { type, provider, ref }, or null if empty
+ */
+export function parseAccountToken(token) {
+  let text = (token || "").trim();
+  if (!text) return null;
+
+  let ref = null;
+  const refIdx = text.indexOf("~");
+  if (refIdx !== -1) {
+    ref = text.substring(refIdx + 1).trim() || null;
+    text = text.substring(0, refIdx);
+  }
+
+  let provider = null;
+  const atIdx = text.indexOf("@");
+  if (atIdx !== -1) {
+    provider = text.substring(atIdx + 1).trim().toLowerCase() || null;
+    text = text.substring(0, atIdx);
+  }
+
+  const type = text.trim().toLowerCase();
+  if (!type) return null;
+
+  return { type: type, provider: provider, ref: ref };
+}
+
+/**
+ * @description Check whether an account is selected by any of a list of
+ * account selectors from parseAccountToken.
+ * @param {Object} account - Account with account_type, provider and account_ref
+ * @param {Array<Object>} selectors - Account selectors
+ * @returns {boolean} True if the account matches at least one selector
+ */
+export function accountMatchesSelectors(account, selectors) {
+  return selectors.some(function (sel) {
+    if (sel.type !== account.account_type) return false;
+    if (sel.provider && sel.provider !== (account.provider || "").toLowerCase()) return false;
+    if (sel.ref && sel.ref.toUpperCase() !== String(account.account_ref).toUpperCase()) return false;
+    return true;
+  });
+}
+
 /**
  * @description Parse a portfolio chart param string into its components.
  * Handles formats like "BW:ISA", "BW:isa+sipp+trading", "BW+AW:isa+sipp+trading".
+ * Each account token may also name a provider or account reference — see
+ * parseAccountToken — e.g. "BW:isa@hl", "BW:sipp~3366521+isa".
  * @param {string} param - The param string
- * @returns {Object|null} Parsed object with userInitials and accountTypes arrays, or null if invalid
+ * @returns {Object|null} Parsed object with userInitials, accountTypes and accountSelectors arrays, or null if invalid
  */
 export function parsePortfolioParam(param) {
   if (!param || typeof param !== "string") return null;
@@ -99,7 +150,7 @@ export function parsePortfolioParam(param) {
   if (colonIdx === -1) return null;
 
   const userPart = param.substring(0, colonIdx).trim();
-  const accountPart = param.substring(colonIdx + 1).trim().toLowerCase();
+  const accountPart = param.substring(colonIdx + 1).trim();
 
   if (!userPart || !accountPart) return null;
 
@@ -108,26 +159,36 @@ export function parsePortfolioParam(param) {
     return s.trim().toUpperCase();
   }).filter(Boolean);
 
-  // Split account types by "+"
-  const accountTypes = accountPart.split("+").map(function (s) {
-    return s.trim().toLowerCase();
-  }).filter(Boolean);
+  // Split account tokens by "+"
+  const accountSelectors = accountPart.split("+").map(parseAccountToken).filter(Boolean);
+
+  if (userInitials.length === 0 || accountSelectors.length === 0) return null;
 
-  if (userInitials.length === 0 || accountTypes.length === 0) return null;
+  // Distinct account types, in the order given
+  const accountTypes = [];
+  for (const sel of accountSelectors) {
+    if (accountTypes.indexOf(sel.type) === -1) {
+      accountTypes.push(sel.type);
+    }
+  }
 
   return {
     userInitials: userInitials,
     accountTypes: accountTypes,
+    accountSelectors: accountSelectors,
   };
 }
 
 /**
  * @description Build a series label from user names and account types.
+ * When account selectors narrow any type to a provider or reference, the
+ * label names each selector instead (e.g. "Ben Wilson (ISA HL + SIPP)").
  * @param {Array<string>} userNames - Array of user full names
  * @param {Array<string>} accountTypes - Array of account types
+ * @param {Array<Object>} [accountSelectors] - Account selectors from parsePortfolioParam
  * @returns {string} Label like "Ben Wilson (ISA)" or "Combined (All accounts)"
  */
-export function buildSeriesLabel(userNames, accountTypes) {
+export function buildSeriesLabel(userNames, accountTypes, accountSelectors) {
   // Determine the name part
   let namePart;
   if (userNames.length === 1) {
@@ -136,6 +197,17 @@ export function buildSeriesLabel(userNames, accountTypes) {
     namePart = "Combined";
   }
 
+  function typeLabel(t) {
+    if (t === "isa") return "ISA";
+    if (t === "sipp") return "SIPP";
+    if (t === "trading") return "Trading";
+    return t;
+  }
+
+  const isNarrowed = (accountSelectors || []).some(function (sel) {
+    return sel.provider || sel.ref;
+  });
+
   // Determine the account part
   let accountPart;
   const allTypes = ["isa", "sipp", "trading"];
@@ -143,16 +215,17 @@ export function buildSeriesLabel(userNames, accountTypes) {
     return accountTypes.indexOf(t) !== -1;
   });
 
-  if (hasAll) {
+  if (isNarrowed) {
+    accountPart = accountSelectors.map(function (sel) {
+      let label = typeLabel(sel.type);
+      if (sel.provider) label += " " + sel.provider.toUpperCase();
+      if (sel.ref) label += " " + sel.ref;
+      return label;
+    }).join(" + ");
+  } else if (hasAll) {
     accountPart = "All accounts";
   } else {
-    const labels = accountTypes.map(function (t) {
-      if (t === "isa") return "ISA";
-      if (t === "sipp") return "SIPP";
-      if (t === "trading") return "Trading";
-      return t;
-    });
-    accountPart = labels.join(" + ");
+    accountPart = accountTypes.map(typeLabel).join(" + ");
   }
 
   return namePart + " (" + accountPart + ")";
@@ -180,7 +253,7 @@ function buildPortfolioSeries(parsed, usersByInitials, sampleDates, valueMode) {
 
   if (resolvedUsers.length === 0) return null;
 
-  const label = buildSeriesLabel(userNames, parsed.accountTypes);
+  const label = buildSeriesLabel(userNames, parsed.accountTypes, parsed.accountSelectors);
 
   // Sample values at each date
   let values = [];
@@ -193,10 +266,10 @@ function buildPortfolioSeries(parsed, usersByInitials, sampleDates, valueMode) {
       const summary = getPortfolioSummaryAtDate(resolvedUsers[ui].id, date);
       if (!summary) continue;
 
-      // Sum the matching account types
+      // Sum the matching accounts
       for (let a = 0; a < summary.accounts.length; a++) {
         const acct = summary.accounts[a];
-        if (parsed.accountTypes.indexOf(acct.account_type) === -1) continue;
+        if (!accountMatchesSelectors(acct, parsed.accountSelectors)) continue;
 
         hasAnyData = true;
         // Always include investments
@@ -222,25 +295,3 @@ function buildPortfolioSeries(parsed, usersByInitials, sampleDates, valueMode) {
     values: values,
   };
 }
-
-/**
- * @description Resolve report params tokens (e.g. USER1 → BW).
- * @param {Array<string>} params - Raw params array
- * @returns {Array<string>} Params with tokens substituted
- */
-function resolveParams(params) {
-  if (!params || params.length === 0) return [];
-  try {
-    const tokenMap = getReportParams();
-    const tokens = Object.keys(tokenMap);
-    return params.map(function (param) {
-      let result = param;
-      for (let i = 0; i < tokens.length; i++) {
-        result = result.split(tokens[i]).join(tokenMap[tokens[i]]);
-      }
-      return result;
-    });
-  } catch {
-    return params;
-  }
-}
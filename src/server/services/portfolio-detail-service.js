@@ -3,6 +3,7 @@ import { getAccountsByUserId } from "../db/accounts-db.js";
 import { getHoldingsByAccountId } from "../db/holdings-db.js";
 import { getLatestPrice, getPriceOnOrBefore } from "../db/prices-db.js";
 import { getLatestRates, unscaleRate } from "../db/currency-rates-db.js";
+import { parseAccountToken, accountMatchesSelectors } from "./portfolio-chart-data-service.js";
 
 /**
  * @description Currency symbol lookup. Returns the symbol for display
@@ -31,7 +32,7 @@ const CURRENCY_SYMBOLS = {
  * @description Supported period codes and how many months to subtract.
  * @type {Object<string, number>}
  */
-const PERIOD_MONTHS = {
+export const PERIOD_MONTHS = {
   "1m": 1,
   "3m": 3,
   "6m": 6,
@@ -47,7 +48,7 @@ const PERIOD_MONTHS = {
  * @description Display labels for each period code.
  * @type {Object<string, string>}
  */
-const PERIOD_LABELS = {
+export const PERIOD_LABELS = {
   "1m": "1 month",
   "3m": "3 months",
   "6m": "6 months",
@@ -112,36 +113,64 @@ function findUserByInitials(initials) {
 }
 
 /**
- * @description Find a specific account for a user by account type (case-insensitive).
+ * @description Find the accounts of a user matched by an account token
+ * (case-insensitive). A plain type such as "isa" matches every ISA the user
+ * holds; "isa@hl" or "isa~3366521" narrows to a provider or account reference.
  * @param {number} userId - The user ID
- * @param {string} accountType - The account type (isa, sipp, trading)
- * @returns {Object|null} The account object, or null if not found
+ * @param {string} accountToken - The account token (e.g. "isa", "isa@hl", "sipp~3366521")
+ * @returns {Object[]} Matching accounts, in account type then ID order
  */
-function findAccountByType(userId, accountType) {
-  const accounts = getAccountsByUserId(userId);
-  const target = accountType.toLowerCase();
-  return accounts.find(function (a) {
-    return a.account_type === target;
-  }) || null;
+function findAccountsByToken(userId, accountToken) {
+  const selector = parseAccountToken(accountToken);
+  if (!selector) return [];
+  return getAccountsByUserId(userId).filter(function (a) {
+    return accountMatchesSelectors(a, [selector]);
+  });
 }
 
 /**
  * @description Get the portfolio detail for a specific user account, including
  * each holding's current valuation and optional percentage changes from
- * historic prices at specified periods.
+ * historic prices at specified periods. If the token matches more than one
+ * account, the first is returned — use getPortfolioDetails for all of them.
  *
  * @param {string} userInitials - User initials (e.g. "BW")
- * @param {string} accountType - Account type (e.g. "isa", "sipp", "trading")
+ * @param {string} accountToken - Account type, optionally narrowed (e.g. "isa", "isa@hl", "sipp~3366521")
  * @param {string[]} periods - Array of period codes (e.g. ["1m", "3m", "1y", "3y"])
  * @returns {Object|null} Detail object or null if user/account not found
  */
-export function getPortfolioDetail(userInitials, accountType, periods) {
+export function getPortfolioDetail(userInitials, accountToken, periods) {
+  const details = getPortfolioDetails(userInitials, accountToken, periods);
+  return details.length > 0 ? details[0] : null;
+}
+
+/**
+ * @description Get the portfolio detail for every user account matched by an
+ * account token, e.g. both ISAs for "BW" and "isa" when one is held at ii and
+ * another at HL.
+ *
+ * @param {string} userInitials - User initials (e.g. "BW")
+ * @param {string} accountToken - Account type, optionally narrowed (e.g. "isa", "isa@hl", "sipp~3366521")
+ * @param {string[]} periods - Array of period codes (e.g. ["1m", "3m", "1y", "3y"])
+ * @returns {Object[]} Detail objects, empty if the user or accounts are not found
+ */
+export function getPortfolioDetails(userInitials, accountToken, periods) {
   const user = findUserByInitials(userInitials);
-  if (!user) return null;
+  if (!user) return [];
 
-  const account = findAccountByType(user.id, accountType);
-  if (!account) return null;
+  return findAccountsByToken(user.id, accountToken).map(function (account) {
+    return buildAccountDetail(user, account, periods);
+  });
+}
 
+/**
+ * @description Build the detail object for one account.
+ * @param {Object} user - The account owner
+ * @param {Object} account - The account
+ * @param {string[]} periods - Array of period codes
+ * @returns {Object} Detail object
+ */
+function buildAccountDetail(user, account, periods) {
   const ratesMap = buildRatesMap();
   const holdings = getHoldingsByAccountId(account.id);
   const today = new Date().toISOString().slice(0, 10);
@@ -273,6 +302,7 @@ export function getPortfolioDetail(userInitials, accountType, periods) {
       id: account.id,
       account_type: account.account_type,
       account_ref: account.account_ref,
+      provider: account.provider,
     },
     valuation_date: today,
     periods: resolvedPeriods.map(function (p) {
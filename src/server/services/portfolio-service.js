@@ -6,10 +6,13 @@ import { getLatestRates, getRateOnOrBefore, unscaleRate } from "../db/currency-r
 import { getCashBalanceAtDate } from "../db/cash-transactions-db.js";
 import { CURRENCY_SCALE_FACTOR } from "../../shared/server-constants.js";
 import { getDatabase } from "../db/connection.js";
+import { getValuationSnapshot, saveValuationSnapshot } from "../db/portfolio-valuations-db.js";
+import { getCrystallisationSummary } from "./crystallisation-service.js";
 
 /**
  * @description Build a portfolio summary for a single user, including all accounts,
- * holdings with latest prices, currency conversions to GBP, and totals.
+ * holdings with latest prices, currency conversions to GBP, and totals. SIPP
+ * accounts also carry their crystallised and uncrystallised funds.
  * @param {number} userId - The user ID
  * @returns {Object|null} The portfolio summary object, or null if user not found
  */
@@ -99,12 +102,14 @@ export function getPortfolioSummary(userId) {
       id: account.id,
       account_type: account.account_type,
       account_ref: account.account_ref,
+      provider: account.provider,
       cash_balance: cashBalance,
       warn_cash: warnCash,
       cash_warning: cashWarning,
       investments_total: accountInvestmentsTotal,
       account_total: accountTotal,
       holdings: holdingSummaries,
+      crystallisation: account.account_type === "sipp" ? getCrystallisationSummary(account.id, accountTotal) : null,
     });
   }
 
@@ -142,9 +147,113 @@ function buildRatesMap() {
   return map;
 }
 
+/**
+ * @description Value a single account's holdings and cash at a historic date
+ * from SCD2 holdings and prices/rates on or before that date.
+ * @param {number} accountId - The account ID
+ * @param {string} date - ISO-8601 date (YYYY-MM-DD) to value the account at
+ * @param {Function} getRatesMap - Returns the rates map for the date (built once, on first use)
+ * @returns {Object} Snapshot with { cash_balance, holdings }
+ */
+function valueAccountAtDate(accountId, date, getRatesMap) {
+  const holdings = getHoldingsAtDate(accountId, date);
+  const holdingSummaries = [];
+
+  for (const holding of holdings) {
+    const historicPrice = getPriceOnOrBefore(holding.investment_id, date);
+    const priceMinor = historicPrice ? historicPrice.price : 0;
+    const price = priceMinor / 100;
+    const priceDate = historicPrice ? historicPrice.price_date : null;
+
+    const currencyCode = holding.currency_code;
+    const isGBP = currencyCode === "GBP";
+
+    let rate = null;
+    let rateDate = null;
+    if (!isGBP) {
+      const rateInfo = getRatesMap()[currencyCode];
+      if (rateInfo) {
+        rate = rateInfo.rate;
+        rateDate = rateInfo.rate_date;
+      }
+    }
+
+    const valueLocal = roundToPence(price * holding.quantity);
+    let valueGBP;
+    if (isGBP) {
+      valueGBP = valueLocal;
+    } else if (rate) {
+      valueGBP = roundToPence(valueLocal / rate);
+    } else {
+      valueGBP = valueLocal;
+    }
+
+    holdingSummaries.push({
+      holding_id: holding.id,
+      investment_id: holding.investment_id,
+      public_id: holding.investment_public_id,
+      description: holding.investment_description,
+      currency_code: currencyCode,
+      quantity: holding.quantity,
+      price: price,
+      price_date: priceDate,
+      rate: rate,
+      rate_date: rateDate,
+      value_local: valueLocal,
+      value_gbp: valueGBP,
+      average_cost: holding.average_cost,
+    });
+  }
+
+  // Look up the historic cash balance from cash_transactions
+  return {
+    cash_balance: getCashBalanceAtDate(accountId, date),
+    holdings: holdingSummaries,
+  };
+}
+
+/**
+ * @description Get an account's valuation at a date from the portfolio_valuations
+ * snapshot table, valuing it afresh and storing the snapshot on a miss. Dates
+ * after today are valued but never stored, since prices may still arrive.
+ * @param {number} accountId - The account ID
+ * @param {string} date - ISO-8601 date (YYYY-MM-DD)
+ * @param {Function} getRatesMap - Returns the rates map for the date (built once, on first use)
+ * @returns {Object} Snapshot with { cash_balance, holdings }
+ */
+function getAccountValuationAtDate(accountId, date, getRatesMap) {
+  const stored = getValuationSnapshot(accountId, date);
+  if (stored) return stored;
+
+  const snapshot = valueAccountAtDate(accountId, date, getRatesMap);
+  if (date <= new Date().toISOString().slice(0, 10)) {
+    saveValuationSnapshot(accountId, date, snapshot);
+  }
+  return snapshot;
+}
+
+/**
+ * @description Create a function that builds the rates map for a date on its
+ * first call and returns the cached map thereafter. Avoids querying rates
+ * when every account valuation is served from stored snapshots.
+ * @param {string} date - ISO-8601 date (YYYY-MM-DD)
+ * @returns {Function} Function returning the rates map
+ */
+function lazyRatesMapAtDate(date) {
+  let ratesMap = null;
+  return function () {
+    if (!ratesMap) {
+      ratesMap = buildRatesMapAtDate(date);
+    }
+    return ratesMap;
+  };
+}
+
 /**
  * @description Build a portfolio summary for a single user at a specific historic date.
  * Uses SCD2 holdings active on that date and prices/rates on or before that date.
+ * Account valuations are read from the portfolio_valuations snapshot table where
+ * available and materialised into it otherwise.
  * @param {number} userId - The user ID
  * @param {string} date - ISO-8601 date (YYYY-MM-DD) to value the portfolio at
  * @returns {Object|null} The portfolio summary object, or null if user not found
@@ -153,9 +262,7 @@ export function getPortfolioSummaryAtDate(userId, date) {
   const user = getUserById(userId);
   if (!user) return null;
 
-  // Build a rates map for the given date (nearest rate on or before)
-  const ratesMap = buildRatesMapAtDate(date);
-
+  const getRatesMap = lazyRatesMapAtDate(date);
   const accounts = getAccountsByUserId(userId);
 
   let totalInvestments = 0;
@@ -165,66 +272,16 @@ export function getPortfolioSummaryAtDate(userId, date) {
   const accountSummaries = [];
 
   for (const account of accounts) {
-    const holdings = getHoldingsAtDate(account.id, date);
-    let accountInvestmentsTotal = 0;
-
-    const holdingSummaries = [];
-
-    for (const holding of holdings) {
-      const historicPrice = getPriceOnOrBefore(holding.investment_id, date);
-      const priceMinor = historicPrice ? historicPrice.price : 0;
-      const price = priceMinor / 100;
-      const priceDate = historicPrice ? historicPrice.price_date : null;
-
-      const currencyCode = holding.currency_code;
-      const isGBP = currencyCode === "GBP";
+    const valuation = getAccountValuationAtDate(account.id, date, getRatesMap);
 
-      let rate = null;
-      let rateDate = null;
-      if (!isGBP) {
-        const rateInfo = ratesMap[currencyCode];
-        if (rateInfo) {
-          rate = rateInfo.rate;
-          rateDate = rateInfo.rate_date;
-        }
-      }
-
-      const valueLocal = roundToPence(price * holding.quantity);
-      let valueGBP;
-      if (isGBP) {
-        valueGBP = valueLocal;
-      } else if (rate) {
-        valueGBP = roundToPence(valueLocal / rate);
-      } else {
-        valueGBP = valueLocal;
-      }
-
-      accountInvestmentsTotal += valueGBP;
-
-      holdingSummaries.push({
-        holding_id: holding.id,
-        investment_id: holding.investment_id,
-        public_id: holding.investment_public_id,
-        description: holding.investment_description,
-        currency_code: currencyCode,
-        quantity: holding.quantity,
-        price: price,
-        price_date: priceDate,
-        rate: rate,
-        rate_date: rateDate,
-        value_local: valueLocal,
-        value_gbp: valueGBP,
-        average_cost: holding.average_cost,
-      });
+    let accountInvestmentsTotal = 0;
+    for (const holding of valuation.holdings) {
+      accountInvestmentsTotal += holding.value_gbp;
     }
-
     accountInvestmentsTotal = roundToPence(accountInvestmentsTotal);
 
-    // Look up the historic cash balance from cash_transactions
-    const historicCash = getCashBalanceAtDate(account.id, date);
-    const cashAvailable = historicCash !== null;
-
-    const cashBalance = cashAvailable ? historicCash : null;
+    const cashAvailable = valuation.cash_balance !== null;
+    const cashBalance = cashAvailable ? valuation.cash_balance : null;
     const accountTotal = cashAvailable ? roundToPence(accountInvestmentsTotal + cashBalance) : null;
 
     totalInvestments += accountInvestmentsTotal;
@@ -238,11 +295,12 @@ export function getPortfolioSummaryAtDate(userId, date) {
       id: account.id,
       account_type: account.account_type,
       account_ref: account.account_ref,
+      provider: account.provider,
       cash_balance: cashBalance,
       cash_available: cashAvailable,
       investments_total: accountInvestmentsTotal,
       account_total: accountTotal,
-      holdings: holdingSummaries,
+      holdings: valuation.holdings,
     });
   }
 
@@ -264,6 +322,30 @@ export function getPortfolioSummaryAtDate(userId, date) {
   };
 }
 
+/**
+ * @description Materialise valuation snapshots for a date, replacing any
+ * stored snapshot. Called after each fetch run (for today's prices) and
+ * after a movement is entered, so the next chart or summary read is cheap.
+ * @param {string} date - ISO-8601 date (YYYY-MM-DD)
+ * @param {number[]} [accountIds] - Accounts to refresh; defaults to every account
+ * @returns {number} Number of account snapshots written
+ */
+export function refreshValuationSnapshots(date, accountIds) {
+  let ids = accountIds;
+  if (!ids) {
+    const db = getDatabase();
+    ids = db.query("SELECT id FROM accounts ORDER BY id").all().map(function (r) {
+      return r.id;
+    });
+  }
+
+  const getRatesMap = lazyRatesMapAtDate(date);
+  for (const accountId of ids) {
+    saveValuationSnapshot(accountId, date, valueAccountAtDate(accountId, date, getRatesMap));
+  }
+  return ids.length;
+}
+
 /**
  * @description Build a lookup map of currency rates on or before a given date.
  * For each non-GBP currency that has rates, finds the nearest rate on or before the date.
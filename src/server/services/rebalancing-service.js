@@ -0,0 +1,281 @@
+import { getRebalancingConfig } from "../config.js";
+import { getAllocationTargets } from "../db/allocation-targets-db.js";
+import { getInvestmentById } from "../db/investments-db.js";
+import { getPortfolioSummary } from "./portfolio-service.js";
+import { loadInvestmentAttributes } from "./allocation-service.js";
+
+/**
+ * @description Account types whose gains are sheltered from CGT. Trades are
+ * placed in these accounts first.
+ * @type {string[]}
+ */
+const SHELTERED_ACCOUNT_TYPES = ["isa", "sipp"];
+
+/**
+ * @description Round a decimal to 2 decimal places (pence).
+ * @param {number} value - The value to round
+ * @returns {number} The value rounded to pence
+ */
+function roundToPence(value) {
+  return Math.round(value * 100) / 100;
+}
+
+/**
+ * @description Round a percentage to 2 decimal places.
+ * @param {number} value - The percentage
+ * @returns {number} The rounded percentage
+ */
+function roundPercent(value) {
+  return Math.round(value * 100) / 100;
+}
+
+/**
+ * @description Order two accounts so ISAs and SIPPs come before trading accounts.
+ * @param {Object} a - First account summary
+ * @param {Object} b - Second account summary
+ * @returns {number} Negative if a is preferred, positive if b is preferred, 0 if equal
+ */
+function compareShelter(a, b) {
+  const aSheltered = SHELTERED_ACCOUNT_TYPES.indexOf(a.account_type) !== -1 ? 0 : 1;
+  const bSheltered = SHELTERED_ACCOUNT_TYPES.indexOf(b.account_type) !== -1 ? 0 : 1;
+  return aSheltered - bSheltered;
+}
+
+/**
+ * @description Work out the units for a trade at a holding's latest GBP price,
+ * to 4 decimal places. Sells never exceed the quantity held.
+ * @param {Object|null} holding - Holding from the portfolio summary, or null if not held
+ * @param {number} amount - Trade value in GBP
+ * @returns {number|null} Approximate units, or null when no price is known
+ */
+function quantityFor(holding, amount) {
+  if (!holding || !holding.quantity || !holding.value_gbp) return null;
+  const unitPrice = holding.value_gbp / holding.quantity;
+  return Math.min(holding.quantity, Math.round((amount / unitPrice) * 10000) / 10000);
+}
+
+/**
+ * @description Plan the trades that bring a person's or an account's holdings
+ * back to their allocation targets. Holdings are valued at latest prices and
+ * compared with the targets as a percentage of the total value including cash.
+ * Targets inside the tolerance band are left alone. For the rest, sells are
+ * proposed first — taken from ISAs and SIPPs before trading accounts, largest
+ * holding first — and the proceeds added to the cash of the account sold from.
+ * Buys are then funded from each account's cash above its minimum cash level,
+ * again preferring ISAs and SIPPs, since cash cannot move between accounts.
+ * Trades smaller than the minimum trade size are not proposed, and sells in a
+ * trading account are flagged with their estimated gain as they may incur CGT.
+ *
+ * Targets set by allocation tag are bought through the largest holding with
+ * that tag, preferring one in the account with the cash. Holdings and cash not
+ * covered by a target are not traded.
+ *
+ * @param {number} userId - The user ID
+ * @param {number|null} accountId - A single account, or null for all the person's accounts
+ * @param {Object} [options] - Planning options; omitted values come from the rebalancing config
+ * @param {number} [options.tolerance_percent] - Percentage points either side of a target
+ * @param {number} [options.minimum_trade] - Smallest trade proposed, in GBP
+ * @returns {Object|null} The plan (rows comparing current and target weights, trades and notes),
+ *   or null if the user or account is not found
+ */
+export function buildRebalancePlan(userId, accountId, options = {}) {
+  const summary = getPortfolioSummary(userId);
+  if (!summary) return null;
+
+  const accounts = summary.accounts.filter(function (account) {
+    return !accountId || account.id === accountId;
+  });
+  if (accountId && accounts.length === 0) return null;
+
+  const config = getRebalancingConfig();
+  const tolerance = options.tolerance_percent !== undefined && options.tolerance_percent !== null ? options.tolerance_percent : config.tolerancePercent;
+  const minimumTrade = options.minimum_trade !== undefined && options.minimum_trade !== null ? options.minimum_trade : config.minimumTrade;
+
+  const targets = getAllocationTargets(userId, accountId);
+  const basis = targets.length > 0 && targets[0].investment_id ? "investment" : "tag";
+  const attributes = basis === "tag" ? loadInvestmentAttributes() : {};
+
+  /**
+   * @description The target key a holding counts towards under the basis.
+   * @param {Object} holding - Holding from the portfolio summary
+   * @returns {string} Investment ID or allocation tag
+   */
+  function keyOf(holding) {
+    if (basis === "investment") return String(holding.investment_id);
+    return attributes[holding.investment_id] ? attributes[holding.investment_id].tag : "Untagged";
+  }
+
+  // Working state: cash free to spend in each account, and each priced holding
+  const cashAvailable = {};
+  const positions = [];
+  let total = 0;
+  for (const account of accounts) {
+    cashAvailable[account.id] = Math.max(0, roundToPence(account.cash_balance - account.warn_cash));
+    total += account.account_total;
+    for (const holding of account.holdings) {
+      if (holding.value_gbp > 0) {
+        positions.push({ account: account, holding: holding, key: keyOf(holding), value: holding.value_gbp });
+      }
+    }
+  }
+  total = roundToPence(total);
+
+  const rows = targets.map(function (target) {
+    const key = basis === "investment" ? String(target.investment_id) : target.allocation_tag;
+    const current = roundToPence(
+      positions
+        .filter(function (p) {
+          return p.key === key;
+        })
+        .reduce(function (sum, p) {
+          return sum + p.value;
+        }, 0),
+    );
+    const currentPercent = total > 0 ? roundPercent((current / total) * 100) : 0;
+    const drift = roundPercent(currentPercent - target.target_percent);
+    return {
+      key: key,
+      investment_id: target.investment_id,
+      allocation_tag: target.allocation_tag,
+      label: target.label,
+      target_percent: target.target_percent,
+      target_value: roundToPence((total * target.target_percent) / 100),
+      current_value: current,
+      current_percent: currentPercent,
+      drift_percent: drift,
+      in_band: Math.abs(drift) <= tolerance,
+      unfunded: 0,
+    };
+  });
+
+  const trades = [];
+  const notes = [];
+
+  // Sells first, so their proceeds can fund the buys in the same account
+  for (const row of rows) {
+    if (row.in_band || row.current_value <= row.target_value) continue;
+    let remaining = roundToPence(row.current_value - row.target_value);
+
+    const candidates = positions.filter(function (p) {
+      return p.key === row.key;
+    });
+    candidates.sort(function (a, b) {
+      return compareShelter(a.account, b.account) || b.value - a.value;
+    });
+
+    for (const position of candidates) {
+      if (remaining < minimumTrade) break;
+      const amount = roundToPence(Math.min(remaining, position.value));
+      if (amount < minimumTrade) continue;
+
+      const quantity = quantityFor(position.holding, amount);
+      const sheltered = SHELTERED_ACCOUNT_TYPES.indexOf(position.account.account_type) !== -1;
+      trades.push({
+        action: "sell",
+        account_id: position.account.id,
+        account_type: position.account.account_type,
+        account_ref: position.account.account_ref,
+        investment_id: position.holding.investment_id,
+        description: position.holding.description,
+        amount: amount,
+        quantity: quantity,
+        cgt_exposed: !sheltered,
+        estimated_gain: sheltered || quantity === null ? null : roundToPence(amount - quantity * position.holding.average_cost),
+      });
+      position.value = roundToPence(position.value - amount);
+      cashAvailable[position.account.id] = roundToPence(cashAvailable[position.account.id] + amount);
+      remaining = roundToPence(remaining - amount);
+    }
+  }
+
+  // Buys, largest shortfall first, from the cash in each account
+  const underweight = rows.filter(function (row) {
+    return !row.in_band && row.current_value < row.target_value;
+  });
+  underweight.sort(function (a, b) {
+    return b.target_value - b.current_value - (a.target_value - a.current_value);
+  });
+
+  for (const row of underweight) {
+    let remaining = roundToPence(row.target_value - row.current_value);
+    const tagged = positions.filter(function (p) {
+      return p.key === row.key;
+    });
+    tagged.sort(function (a, b) {
+      return b.holding.value_gbp - a.holding.value_gbp;
+    });
+
+    if (basis === "tag" && tagged.length === 0) {
+      notes.push("No holding is tagged " + row.label + " — add one before buying towards this target");
+      row.unfunded = remaining;
+      continue;
+    }
+
+    const buyAccounts = accounts.slice().sort(function (a, b) {
+      return compareShelter(a, b) || cashAvailable[b.id] - cashAvailable[a.id];
+    });
+
+    for (const account of buyAccounts) {
+      if (remaining < minimumTrade) break;
+      const amount = roundToPence(Math.min(remaining, cashAvailable[account.id]));
+      if (amount < minimumTrade) continue;
+
+      // Buy through a holding in this account if there is one, otherwise the largest held elsewhere
+      const inAccount = tagged.find(function (p) {
+        return p.account.id === account.id;
+      });
+      const model = inAccount || tagged[0] || null;
+      const investmentId = model ? model.holding.investment_id : row.investment_id;
+      let description = model ? model.holding.description : null;
+      if (!description) {
+        const investment = getInvestmentById(investmentId);
+        description = investment ? investment.description : row.label;
+      }
+
+      trades.push({
+        action: "buy",
+        account_id: account.id,
+        account_type: account.account_type,
+        account_ref: account.account_ref,
+        investment_id: investmentId,
+        description: description,
+        amount: amount,
+        quantity: model ? Math.round((amount / (model.holding.value_gbp / model.holding.quantity)) * 10000) / 10000 : null,
+        cgt_exposed: false,
+        estimated_gain: null,
+      });
+      cashAvailable[account.id] = roundToPence(cashAvailable[account.id] - amount);
+      remaining = roundToPence(remaining - amount);
+    }
+
+    if (remaining >= minimumTrade) {
+      row.unfunded = remaining;
+      notes.push("Not enough cash to buy " + row.label + " up to target — short by £" + remaining.toFixed(2));
+    }
+  }
+
+  if (trades.some(function (t) { return t.cgt_exposed; })) {
+    notes.push("Sells in a trading account may realise a capital gain — check the CGT position before dealing");
+  }
+
+  return {
+    user_id: userId,
+    account_id: accountId || null,
+    valuation_date: summary.valuation_date,
+    basis: basis,
+    total: total,
+    tolerance_percent: tolerance,
+    minimum_trade: minimumTrade,
+    target_total_percent: roundPercent(
+      targets.reduce(function (sum, t) {
+        return sum + t.target_percent;
+      }, 0),
+    ),
+    rows: rows,
+    trades: trades,
+    cash_after: accounts.map(function (account) {
+      return { account_id: account.id, account_type: account.account_type, available: cashAvailable[account.id] };
+    }),
+    notes: notes,
+  };
+}
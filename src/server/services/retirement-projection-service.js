@@ -0,0 +1,407 @@
+/**
+ * @description Retirement projection service for Portfolio 60.
+ * Projects how long SIPPs last at current drawdown levels, alongside recurring
+ * income such as defined benefit pensions recorded as other assets. Runs a
+ * deterministic projection at the expected return and a Monte Carlo simulation
+ * with returns drawn around it, using return and volatility estimated from the
+ * price history of the SIPP holdings.
+ */
+
+import { getAllUsers, getUserById } from "../db/users-db.js";
+import { getActiveDrawdownSchedules, getDrawdownAmountOn } from "../db/drawdown-schedules-db.js";
+import { getAllOtherAssets, annualiseRecurringValue } from "../db/other-assets-db.js";
+import { getInvestmentsWithPricesByIds } from "../db/investments-db.js";
+import { getAllInvestmentPricesInRange } from "../db/prices-db.js";
+import { getRetirementProjectionConfig } from "../config.js";
+import { CURRENCY_SCALE_FACTOR } from "../../shared/server-constants.js";
+import { getDateRange, getGBPPrices, calculateReturn, calculateVolatility } from "./analysis-service.js";
+import { getPortfolioSummary } from "./portfolio-service.js";
+
+/** @description Percentiles reported for depletion and the value bands */
+const PERCENTILES = [10, 25, 50, 75, 90];
+
+/** @description Drawdown payments per year by schedule frequency */
+const PAYMENTS_PER_YEAR = {
+  monthly: 12,
+  quarterly: 4,
+  annually: 1,
+};
+
+/** @description Seed used for the simulation unless one is given, so repeated reports agree */
+const DEFAULT_SEED = 60;
+
+/** @description Minimum days of price history before a holding's annualised return is used */
+const MIN_HISTORY_DAYS = 182;
+
+/**
+ * @description Round a decimal to 2 decimal places (pence).
+ * @param {number} value - The value to round
+ * @returns {number} The value rounded to pence
+ */
+function roundToPence(value) {
+  return Math.round(value * 100) / 100;
+}
+
+/**
+ * @description Work out a person's age in years (with fractions) on a date.
+ * @param {string|null} dateOfBirth - ISO-8601 date of birth, or null if not recorded
+ * @param {string} onDate - ISO-8601 date
+ * @returns {number|null} Age in years, or null without a date of birth
+ */
+function ageOn(dateOfBirth, onDate) {
+  if (!dateOfBirth) return null;
+  const ms = new Date(onDate + "T00:00:00Z").getTime() - new Date(dateOfBirth + "T00:00:00Z").getTime();
+  return ms / (365.25 * 24 * 60 * 60 * 1000);
+}
+
+/**
+ * @description Create a seeded pseudo-random number generator (mulberry32)
+ * returning values in [0, 1), so simulations can be repeated exactly.
+ * @param {number} seed - Integer seed
+ * @returns {Function} Generator function
+ */
+function createRandom(seed) {
+  let state = seed >>> 0;
+  return function () {
+    state = (state + 0x6d2b79f5) >>> 0;
+    let t = state;
+    t = Math.imul(t ^ (t >>> 15), t | 1);
+    t ^= t + Math.imul(t ^ (t >>> 7), t | 61);
+    return ((t ^ (t >>> 14)) >>> 0) / 4294967296;
+  };
+}
+
+/**
+ * @description Draw a standard normal value using the Box-Muller transform.
+ * @param {Function} random - Uniform generator from createRandom
+ * @returns {number} A standard normal value
+ */
+function normalRandom(random) {
+  let u = 0;
+  while (u === 0) u = random();
+  const v = random();
+  return Math.sqrt(-2 * Math.log(u)) * Math.cos(2 * Math.PI * v);
+}
+
+/**
+ * @description Value at a percentile of a sorted array (nearest rank).
+ * @param {number[]} sorted - Values sorted ascending (may include Infinity)
+ * @param {number} percentile - Percentile from 0 to 100
+ * @returns {number} The value at that percentile
+ */
+function percentileOf(sorted, percentile) {
+  const index = Math.min(sorted.length - 1, Math.max(0, Math.ceil((percentile / 100) * sorted.length) - 1));
+  return sorted[index];
+}
+
+/**
+ * @description Estimate the expected annual return and volatility of SIPP
+ * money from the price history of its holdings. Each holding with enough
+ * history contributes its annualised return and its volatility (from
+ * calculateVolatility), weighted by value; holdings without enough history use
+ * the configured defaults, and cash earns nothing. Volatilities are averaged
+ * by value, which ignores diversification between holdings and so errs on the
+ * cautious side.
+ * @param {Object[]} holdings - Holdings from the portfolio summary (investment_id, value_gbp)
+ * @param {number} cash - Cash held in the SIPPs in GBP
+ * @param {Object} config - Retirement projection config
+ * @returns {{ expected_return: number, volatility: number, history_weight: number }} Percentages a year,
+ *   and the share of the value (0-1) estimated from history
+ */
+export function estimateReturnAndVolatility(holdings, cash, config) {
+  const range = getDateRange(config.historyPeriod);
+  const investmentIds = [...new Set(holdings.map((h) => h.investment_id))];
+  const investments = getInvestmentsWithPricesByIds(investmentIds);
+  const allPricesMap = investments.length > 0 ? getAllInvestmentPricesInRange(range.fromStr, range.toStr) : new Map();
+
+  /** @type {Object<number, {annualReturn: number, volatility: number}>} */
+  const statsById = {};
+  for (const investment of investments) {
+    const prices = getGBPPrices(investment, allPricesMap, range.fromStr, range.toStr);
+    if (!prices || prices.length < 3) continue;
+
+    const returnData = calculateReturn(prices);
+    const volData = calculateVolatility(prices);
+    if (returnData.returnPct === null || volData.volatility === null) continue;
+
+    const days = (new Date(returnData.endDate).getTime() - new Date(returnData.startDate).getTime()) / (24 * 60 * 60 * 1000);
+    if (days < MIN_HISTORY_DAYS) continue;
+
+    const annualReturn = (Math.pow(1 + returnData.returnPct / 100, 365.25 / days) - 1) * 100;
+    statsById[investment.id] = { annualReturn: annualReturn, volatility: volData.volatility };
+  }
+
+  let totalValue = Math.max(0, cash);
+  let historyValue = 0;
+  let weightedReturn = 0;
+  let weightedVolatility = 0;
+  for (const holding of holdings) {
+    if (!(holding.value_gbp > 0)) continue;
+    const stats = statsById[holding.investment_id];
+    totalValue += holding.value_gbp;
+    if (stats) {
+      historyValue += holding.value_gbp;
+      weightedReturn += holding.value_gbp * stats.annualReturn;
+      weightedVolatility += holding.value_gbp * stats.volatility;
+    } else {
+      weightedReturn += holding.value_gbp * config.defaultReturn;
+      weightedVolatility += holding.value_gbp * config.defaultVolatility;
+    }
+  }
+
+  if (totalValue === 0) {
+    return { expected_return: config.defaultReturn, volatility: config.defaultVolatility, history_weight: 0 };
+  }
+
+  return {
+    expected_return: Math.round((weightedReturn / totalValue) * 100) / 100,
+    volatility: Math.round((weightedVolatility / totalValue) * 100) / 100,
+    history_weight: Math.round((historyValue / totalValue) * 10000) / 10000,
+  };
+}
+
+/**
+ * @description Run one projection of a pot paying a fixed annual drawdown.
+ * Each year the drawdown is taken at the start and the rest grows at that
+ * year's return. When the pot cannot pay a full year, it pays what is left
+ * and the years lasted include that part year.
+ * @param {number} startValue - Pot value in GBP
+ * @param {number} annualDrawdown - Drawdown a year in GBP
+ * @param {number} years - Years to project
+ * @param {Function} nextReturn - Returns the next year's return as a decimal
+ * @returns {{ values: number[], paid: number[], years_lasted: number }} End-of-year values
+ *   and drawdowns paid for each year, and years lasted (Infinity if never depleted)
+ */
+function runPath(startValue, annualDrawdown, years, nextReturn) {
+  const values = [];
+  const paid = [];
+  let pot = startValue;
+  let yearsLasted = Infinity;
+
+  for (let year = 0; year < years; year++) {
+    if (annualDrawdown > 0 && pot < annualDrawdown) {
+      if (yearsLasted === Infinity) yearsLasted = year + pot / annualDrawdown;
+      paid.push(pot);
+      values.push(0);
+      pot = 0;
+      continue;
+    }
+    pot = (pot - annualDrawdown) * (1 + nextReturn());
+    paid.push(annualDrawdown);
+    values.push(pot);
+  }
+
+  return { values: values, paid: paid, years_lasted: yearsLasted };
+}
+
+/**
+ * @description Gather the starting position for a projection: SIPP values,
+ * annual drawdowns from the drawdown schedules running now (at their current
+ * escalated amounts), and recurring
+ * income from other assets.
+ * @param {Object[]} users - Users to include
+ * @param {string} today - ISO-8601 date of the projection
+ * @returns {Object} SIPP accounts, holdings, cash, annual drawdown and recurring income
+ */
+function gatherPosition(users, today) {
+  const userIds = users.map((u) => u.id);
+  const currentMonth = today.slice(0, 7) + "-01";
+
+  // Drawdown schedules running this month, keyed by account
+  const schedulesByAccount = {};
+  for (const schedule of getActiveDrawdownSchedules()) {
+    if (schedule.from_date > currentMonth || schedule.to_date < currentMonth) continue;
+    const annual = getDrawdownAmountOn(schedule, today) * (PAYMENTS_PER_YEAR[schedule.frequency] || 0);
+    schedulesByAccount[schedule.account_id] = (schedulesByAccount[schedule.account_id] || 0) + annual;
+  }
+
+  const accounts = [];
+  const holdings = [];
+  let cash = 0;
+  for (const user of users) {
+    const summary = getPortfolioSummary(user.id);
+    for (const account of summary.accounts) {
+      if (account.account_type !== "sipp") continue;
+      accounts.push({
+        id: account.id,
+        user_id: user.id,
+        account_ref: account.account_ref,
+        value: account.account_total,
+        annual_drawdown: roundToPence(schedulesByAccount[account.id] || 0),
+      });
+      holdings.push(...account.holdings);
+      cash += account.cash_balance;
+    }
+  }
+
+  const recurring = getAllOtherAssets()
+    .filter(function (asset) {
+      return asset.value_type === "recurring" && userIds.includes(asset.user_id);
+    })
+    .map(function (asset) {
+      return {
+        id: asset.id,
+        description: asset.description,
+        category: asset.category,
+        annual_amount: roundToPence(annualiseRecurringValue(asset) / CURRENCY_SCALE_FACTOR),
+      };
+    });
+
+  return { accounts: accounts, holdings: holdings, cash: cash, recurring: recurring };
+}
+
+/**
+ * @description Project how long SIPPs will last at current drawdown levels.
+ * Starts from the current value of the SIPPs, the drawdown schedules running
+ * now (assumed to continue at the same level) and recurring other assets such
+ * as defined benefit pensions, which are paid throughout. Recurring income is
+ * reported alongside but is not drawn from the SIPPs, so it does not change
+ * the drawdowns taken or when the SIPPs run out — the drawdown schedules are
+ * taken to already allow for it. Returns a
+ * deterministic projection at the expected return, and a Monte Carlo
+ * simulation giving percentile bands of the SIPP value each year and of the
+ * age at which the SIPPs run out. Drawdowns and recurring income are held at
+ * today's levels.
+ *
+ * @param {number|null} userId - The user whose SIPPs to project, or null for the whole household
+ * @param {Object} [options] - Projection options; omitted values come from the retirementProjection config
+ * @param {number} [options.years] - Years to project
+ * @param {number} [options.simulations] - Number of Monte Carlo runs
+ * @param {number} [options.expected_return] - Expected annual return in percent, instead of the history estimate
+ * @param {number} [options.volatility] - Annual volatility in percent, instead of the history estimate
+ * @param {number} [options.seed] - Random seed
+ * @param {string} [options.today] - ISO-8601 date to project from (for testing)
+ * @returns {Object|null} The projection, or null if the user is not found
+ */
+export function projectRetirement(userId, options = {}) {
+  let users;
+  let user = null;
+  if (userId !== null && userId !== undefined) {
+    user = getUserById(userId);
+    if (!user) return null;
+    users = [user];
+  } else {
+    users = getAllUsers();
+  }
+
+  const config = getRetirementProjectionConfig();
+  const years = options.years || config.years;
+  const simulations = options.simulations || config.simulations;
+  const today = options.today || new Date().toISOString().slice(0, 10);
+  const currentAge = user ? ageOn(user.date_of_birth, today) : null;
+
+  const position = gatherPosition(users, today);
+  const sippValue = roundToPence(position.accounts.reduce((sum, a) => sum + a.value, 0));
+  const annualDrawdown = roundToPence(position.accounts.reduce((sum, a) => sum + a.annual_drawdown, 0));
+  const annualRecurring = roundToPence(position.recurring.reduce((sum, r) => sum + r.annual_amount, 0));
+
+  // Return and volatility: from price history unless given
+  const estimate = estimateReturnAndVolatility(position.holdings, position.cash, config);
+  const hasOverride = options.expected_return !== undefined || options.volatility !== undefined;
+  const expectedReturn = options.expected_return !== undefined ? options.expected_return : estimate.expected_return;
+  const volatility = options.volatility !== undefined ? options.volatility : estimate.volatility;
+
+  /**
+   * @description Age (or null) after a number of years from today.
+   * @param {number} yearsAhead - Years from today
+   * @returns {number|null} Age rounded to 1 decimal place
+   */
+  function ageAfter(yearsAhead) {
+    if (currentAge === null || yearsAhead === Infinity) return null;
+    return Math.round((currentAge + yearsAhead) * 10) / 10;
+  }
+
+  // Deterministic projection at the expected return
+  const deterministic = runPath(sippValue, annualDrawdown, years, function () {
+    return expectedReturn / 100;
+  });
+
+  // Monte Carlo: annual returns lognormally distributed with the expected return and volatility
+  const mean = 1 + expectedReturn / 100;
+  const sd = volatility / 100;
+  const sigma = Math.sqrt(Math.log(1 + (sd * sd) / (mean * mean)));
+  const mu = Math.log(mean) - (sigma * sigma) / 2;
+  const random = createRandom(options.seed !== undefined ? options.seed : DEFAULT_SEED);
+
+  const yearsLasted = [];
+  const valuesByYear = Array.from({ length: years }, () => []);
+  const paidByYear = Array.from({ length: years }, () => []);
+  for (let s = 0; s < simulations; s++) {
+    const path = runPath(sippValue, annualDrawdown, years, function () {
+      return Math.exp(mu + sigma * normalRandom(random)) - 1;
+    });
+    yearsLasted.push(path.years_lasted);
+    for (let y = 0; y < years; y++) {
+      valuesByYear[y].push(path.values[y]);
+      paidByYear[y].push(path.paid[y]);
+    }
+  }
+  yearsLasted.sort((a, b) => a - b);
+
+  const lasting = yearsLasted.filter((y) => y === Infinity).length;
+  const depletion = PERCENTILES.map(function (p) {
+    const lasted = percentileOf(yearsLasted, p);
+    return {
+      percentile: p,
+      years_lasted: lasted === Infinity ? null : Math.round(lasted * 10) / 10,
+      age: ageAfter(lasted),
+    };
+  });
+
+  const bands = [];
+  for (let y = 0; y < years; y++) {
+    const sortedValues = valuesByYear[y].slice().sort((a, b) => a - b);
+    const sortedPaid = paidByYear[y].slice().sort((a, b) => a - b);
+    const band = { year: y + 1, age: ageAfter(y + 1) };
+    for (const p of PERCENTILES) {
+      band["p" + p] = Math.round(percentileOf(sortedValues, p));
+    }
+    band.deterministic = Math.round(deterministic.values[y]);
+    band.median_drawdown = roundToPence(percentileOf(sortedPaid, 50));
+    // Reported for reference only: runPath draws the scheduled drawdowns regardless
+    band.recurring_income = annualRecurring;
+    bands.push(band);
+  }
+
+  return {
+    user: user ? { id: user.id, initials: user.initials, first_name: user.first_name, last_name: user.last_name, date_of_birth: user.date_of_birth || null } : null,
+    projection_date: today,
+    current_age: currentAge === null ? null : Math.round(currentAge * 10) / 10,
+    accounts: position.accounts,
+    sipp_value: sippValue,
+    annual_drawdown: annualDrawdown,
+    recurring_income: position.recurring,
+    annual_recurring_income: annualRecurring,
+    expected_return: expectedReturn,
+    volatility: volatility,
+    return_source: hasOverride ? "override" : estimate.history_weight > 0 ? "history" : "default",
+    history_period: config.historyPeriod,
+    years: years,
+    simulations: simulations,
+    deterministic: {
+      years_lasted: deterministic.years_lasted === Infinity ? null : Math.round(deterministic.years_lasted * 10) / 10,
+      age: ageAfter(deterministic.years_lasted),
+    },
+    success_rate: Math.round((lasting / simulations) * 1000) / 10,
+    depletion: depletion,
+    bands: bands,
+  };
+}
+
+/**
+ * @description Project retirement for each family member with a SIPP.
+ * @param {Array<number>|null} [userIds] - Users to include (in that order), or null for everyone
+ * @param {Object} [options] - Projection options, as for projectRetirement
+ * @returns {Object[]} Projections for users holding at least one SIPP
+ */
+export function projectRetirementForUsers(userIds, options = {}) {
+  const ids = userIds || getAllUsers().map((u) => u.id);
+  const projections = [];
+  for (const id of ids) {
+    const projection = projectRetirement(id, options);
+    if (projection && projection.accounts.length > 0) {
+      projections.push(projection);
+    }
+  }
+  return projections;
+}
@@ -0,0 +1,348 @@
+import { getAllUsers } from "../db/users-db.js";
+import { getAccountsByUserId } from "../db/accounts-db.js";
+import { getExternalCashFlows, addsToCashBalance } from "../db/cash-transactions-db.js";
+import { getPortfolioSummaryAtDate } from "./portfolio-service.js";
+import { parsePortfolioParam, buildSeriesLabel, accountMatchesSelectors } from "./portfolio-chart-data-service.js";
+import { PERIOD_MONTHS, PERIOD_LABELS } from "./portfolio-detail-service.js";
+
+/**
+ * @description Number of days used to annualise returns.
+ * @type {number}
+ */
+const DAYS_PER_YEAR = 365;
+
+/**
+ * @description Round a value to 2 decimal places (pence precision).
+ * @param {number} value - The value to round
+ * @returns {number} Value rounded to 2 decimal places
+ */
+function roundToPence(value) {
+  return Math.round(value * 100) / 100;
+}
+
+/**
+ * @description Convert a decimal rate to a percentage rounded to 2 decimal places.
+ * @param {number|null} rate - The rate as a decimal (e.g. 0.0525)
+ * @returns {number|null} The percentage (e.g. 5.25), or null if no rate
+ */
+function toPercent(rate) {
+  if (rate === null || !isFinite(rate)) return null;
+  return Math.round(rate * 10000) / 100;
+}
+
+/**
+ * @description Calculate the ISO-8601 date string for N months before a date.
+ * @param {string} date - ISO-8601 date (YYYY-MM-DD)
+ * @param {number} monthsAgo - Number of months to go back
+ * @returns {string} ISO-8601 date string (YYYY-MM-DD)
+ */
+function dateMonthsBefore(date, monthsAgo) {
+  const d = new Date(date + "T00:00:00Z");
+  d.setUTCMonth(d.getUTCMonth() - monthsAgo);
+  return d.toISOString().slice(0, 10);
+}
+
+/**
+ * @description Number of days between two ISO-8601 dates.
+ * @param {string} fromDate - Earlier date (YYYY-MM-DD)
+ * @param {string} toDate - Later date (YYYY-MM-DD)
+ * @returns {number} Days from fromDate to toDate
+ */
+function daysBetween(fromDate, toDate) {
+  return Math.round((Date.parse(toDate + "T00:00:00Z") - Date.parse(fromDate + "T00:00:00Z")) / 86400000);
+}
+
+/**
+ * @description Calculate the net present value of dated cash flows at a rate.
+ * @param {Array<{ days: number, amount: number }>} flows - Flows with days from the first flow
+ * @param {number} rate - Annual rate as a decimal
+ * @returns {number} The net present value
+ */
+function netPresentValue(flows, rate) {
+  let npv = 0;
+  for (const flow of flows) {
+    npv += flow.amount / Math.pow(1 + rate, flow.days / DAYS_PER_YEAR);
+  }
+  return npv;
+}
+
+/**
+ * @description Solve for the annual internal rate of return (XIRR) of a set
+ * of dated cash flows. Money paid in is negative and money taken out (including
+ * the closing valuation) is positive. Tries Newton-Raphson first and falls
+ * back to bisection if it fails to converge.
+ * @param {Array<{ date: string, amount: number }>} flows - Dated cash flows, oldest first
+ * @returns {number|null} The annual rate as a decimal, or null if there is no solution
+ */
+export function calculateXirr(flows) {
+  const hasNegative = flows.some(function (f) { return f.amount < 0; });
+  const hasPositive = flows.some(function (f) { return f.amount > 0; });
+  if (!hasNegative || !hasPositive) return null;
+
+  const firstDate = flows[0].date;
+  const dated = flows.map(function (f) {
+    return { days: daysBetween(firstDate, f.date), amount: f.amount };
+  });
+
+  // Newton-Raphson from a 10% starting guess
+  let rate = 0.1;
+  for (let i = 0; i < 100; i++) {
+    let npv = 0;
+    let derivative = 0;
+    for (const flow of dated) {
+      const years = flow.days / DAYS_PER_YEAR;
+      const discount = Math.pow(1 + rate, years);
+      npv += flow.amount / discount;
+      derivative -= (years * flow.amount) / (discount * (1 + rate));
+    }
+    if (Math.abs(npv) < 1e-7) return rate;
+    if (derivative === 0) break;
+
+    const next = rate - npv / derivative;
+    if (!isFinite(next) || next <= -1) break;
+    if (Math.abs(next - rate) < 1e-10) return next;
+    rate = next;
+  }
+
+  // Bisection between -99.99% and +10,000% a year
+  let low = -0.9999;
+  let high = 100;
+  let npvLow = netPresentValue(dated, low);
+  if (npvLow * netPresentValue(dated, high) > 0) return null;
+
+  for (let i = 0; i < 200; i++) {
+    const mid = (low + high) / 2;
+    const npvMid = netPresentValue(dated, mid);
+    if (Math.abs(npvMid) < 1e-7 || high - low < 1e-12) return mid;
+    if (npvLow * npvMid < 0) {
+      high = mid;
+    } else {
+      low = mid;
+      npvLow = npvMid;
+    }
+  }
+  return (low + high) / 2;
+}
+
+/**
+ * @description Calculate the time-weighted return from valuations taken
+ * either side of each external cash flow. Flows are treated as arriving at
+ * the end of their day, so each sub-period return is the closing value less
+ * the day's net flow, divided by the previous valuation. Daily returns
+ * between flows telescope, so a valuation on each flow date gives the same
+ * result as chaining every day. Sub-periods that start from nothing are skipped.
+ * @param {number} startValue - Valuation at the start of the period
+ * @param {Array<{ value: number, flow: number }>} points - Valuation and net flow (money in positive) at each flow date and at the period end, oldest first
+ * @returns {number|null} The cumulative return as a decimal, or null if nothing was invested
+ */
+export function calculateTwr(startValue, points) {
+  let growth = 1;
+  let previous = startValue;
+  let invested = false;
+
+  for (const point of points) {
+    if (previous > 0) {
+      growth *= (point.value - point.flow) / previous;
+      invested = true;
+    }
+    previous = point.value;
+  }
+
+  return invested ? growth - 1 : null;
+}
+
+/**
+ * @description Resolve a portfolio param (e.g. "BW:isa+sipp", "BW+AW:isa",
+ * "BW:isa@hl") into the users and accounts it covers.
+ * @param {string} param - The portfolio param string
+ * @returns {Object|null} Object with { users, accounts, accountTypes, accountSelectors, label }, or null if invalid or no users match
+ */
+function resolvePortfolio(param) {
+  const parsed = parsePortfolioParam(param);
+  if (!parsed) return null;
+
+  const allUsers = getAllUsers();
+  const users = [];
+  const accounts = [];
+  for (const initials of parsed.userInitials) {
+    const user = allUsers.find(function (u) {
+      return u.initials && u.initials.toUpperCase() === initials;
+    });
+    if (!user) continue;
+    users.push(user);
+    for (const account of getAccountsByUserId(user.id)) {
+      if (accountMatchesSelectors(account, parsed.accountSelectors)) {
+        accounts.push(account);
+      }
+    }
+  }
+
+  if (users.length === 0) return null;
+
+  const userNames = users.map(function (u) {
+    return u.first_name + " " + u.last_name;
+  });
+
+  return {
+    users: users,
+    accounts: accounts,
+    accountTypes: parsed.accountTypes,
+    accountSelectors: parsed.accountSelectors,
+    label: buildSeriesLabel(userNames, parsed.accountTypes, parsed.accountSelectors),
+  };
+}
+
+/**
+ * @description Value the selected accounts at a date: investments at the
+ * price on or before the date plus the historic cash balance. Accounts with
+ * no cash transactions by that date count as holding no cash.
+ * @param {Object} portfolio - Resolved portfolio from resolvePortfolio
+ * @param {string} date - ISO-8601 date (YYYY-MM-DD)
+ * @returns {number} Total value in GBP
+ */
+function valuePortfolioAtDate(portfolio, date) {
+  let total = 0;
+  for (const user of portfolio.users) {
+    const summary = getPortfolioSummaryAtDate(user.id, date);
+    if (!summary) continue;
+
+    for (const account of summary.accounts) {
+      if (!accountMatchesSelectors(account, portfolio.accountSelectors)) continue;
+      total += account.investments_total;
+      if (account.cash_balance !== null) {
+        total += account.cash_balance;
+      }
+    }
+  }
+  return roundToPence(total);
+}
+
+/**
+ * @description Calculate money-weighted (XIRR) and time-weighted (TWR)
+ * returns for a portfolio over one period ending on endDate.
+ * @param {Object} portfolio - Resolved portfolio from resolvePortfolio
+ * @param {string} code - Period code from PERIOD_MONTHS
+ * @param {string} endDate - ISO-8601 end date (YYYY-MM-DD)
+ * @returns {Object} Returns for the period
+ */
+function calculatePeriodReturns(portfolio, code, endDate) {
+  const startDate = dateMonthsBefore(endDate, PERIOD_MONTHS[code]);
+  const accountIds = portfolio.accounts.map(function (a) { return a.id; });
+  const transactions = getExternalCashFlows(accountIds, startDate, endDate);
+
+  // Net the flows by date: deposits and tax relief are money in, withdrawals and drawdowns money out
+  let deposits = 0;
+  let withdrawals = 0;
+  const flowsByDate = {};
+  const flows = [];
+  for (const tx of transactions) {
+    const amount = addsToCashBalance(tx) ? tx.amount : -tx.amount;
+    if (amount > 0) {
+      deposits += amount;
+    } else {
+      withdrawals -= amount;
+    }
+    flowsByDate[tx.transaction_date] = (flowsByDate[tx.transaction_date] || 0) + amount;
+    flows.push({
+      date: tx.transaction_date,
+      account_id: tx.account_id,
+      transaction_type: tx.transaction_type,
+      amount: tx.amount,
+    });
+  }
+
+  const startValue = valuePortfolioAtDate(portfolio, startDate);
+
+  // Value the portfolio on each flow date and at the period end
+  const flowDates = Object.keys(flowsByDate).sort();
+  const points = flowDates.map(function (date) {
+    return { date: date, value: valuePortfolioAtDate(portfolio, date), flow: flowsByDate[date] };
+  });
+  let endValue;
+  if (points.length > 0 && points[points.length - 1].date === endDate) {
+    endValue = points[points.length - 1].value;
+  } else {
+    endValue = valuePortfolioAtDate(portfolio, endDate);
+    points.push({ date: endDate, value: endValue, flow: 0 });
+  }
+
+  // XIRR flows are from the investor's side: money paid in is negative
+  const xirrFlows = [];
+  if (startValue > 0) {
+    xirrFlows.push({ date: startDate, amount: -startValue });
+  }
+  for (const date of flowDates) {
+    xirrFlows.push({ date: date, amount: -flowsByDate[date] });
+  }
+  xirrFlows.push({ date: endDate, amount: endValue });
+
+  const twr = calculateTwr(startValue, points);
+  const days = daysBetween(startDate, endDate);
+  let twrAnnualised = null;
+  if (twr !== null && days >= DAYS_PER_YEAR) {
+    twrAnnualised = Math.pow(1 + twr, DAYS_PER_YEAR / days) - 1;
+  }
+
+  const netFlows = deposits - withdrawals;
+
+  return {
+    code: code,
+    label: PERIOD_LABELS[code],
+    start_date: startDate,
+    end_date: endDate,
+    start_value: startValue,
+    end_value: endValue,
+    deposits: roundToPence(deposits),
+    withdrawals: roundToPence(withdrawals),
+    net_flows: roundToPence(netFlows),
+    gain: roundToPence(endValue - startValue - netFlows),
+    xirr: toPercent(calculateXirr(xirrFlows)),
+    twr: toPercent(twr),
+    twr_annualised: toPercent(twrAnnualised),
+    flows: flows,
+  };
+}
+
+/**
+ * @description Get money-weighted (XIRR) and time-weighted (TWR) returns for
+ * a portfolio over one or more periods ending today.
+ *
+ * External flows are deposits, pension tax relief, withdrawals and drawdowns
+ * from cash_transactions. XIRR is annualised and reflects the timing and size
+ * of those flows. TWR strips the flows out to show how the investments
+ * themselves performed; it is cumulative for the period and also annualised
+ * for periods of a year or more.
+ *
+ * @param {string} param - Portfolio param (e.g. "BW:isa+sipp", "BW+AW:isa+sipp+trading")
+ * @param {string[]} periods - Period codes (e.g. ["1y", "3y"]); unknown codes are ignored
+ * @param {string} [endDate] - ISO-8601 end date (YYYY-MM-DD), defaults to today
+ * @returns {Object|null} Returns object, or null if the param is invalid or no users match
+ */
+export function getPortfolioReturns(param, periods, endDate) {
+  const portfolio = resolvePortfolio(param);
+  if (!portfolio) return null;
+
+  const end = endDate || new Date().toISOString().slice(0, 10);
+
+  const results = [];
+  for (const period of periods) {
+    const code = period.toLowerCase();
+    if (!PERIOD_MONTHS[code]) continue;
+    results.push(calculatePeriodReturns(portfolio, code, end));
+  }
+
+  return {
+    portfolio: param,
+    label: portfolio.label,
+    accounts: portfolio.accounts.map(function (a) {
+      return {
+        id: a.id,
+        user_id: a.user_id,
+        account_type: a.account_type,
+        account_ref: a.account_ref,
+        provider: a.provider,
+      };
+    }),
+    periods: results,
+  };
+}
@@ -6,6 +6,7 @@ import { testReferenceExists, activateTestMode, deactivateTestMode, isDemoMode }
 import { runFullPriceUpdate, retryFailedItems } from "./fetch-service.js";
 import { writeSchedulerLog, pruneSchedulerLog } from "../db/scheduler-log-db.js";
 import { processDrawdowns } from "./drawdown-processor.js";
+import { applyOtherAssetEscalations, applyOtherAssetIndexation } from "../db/other-assets-db.js";
 
 /**
  * @description The active Croner job instance, or null if scheduling is disabled.
@@ -184,6 +185,32 @@ async function executeFetchRun(startedBy) {
     } catch (drawdownErr) {
       writeSchedulerLog("Drawdown processing failed: " + drawdownErr.message, "error");
     }
+
+    // Raise recurring other assets whose escalation anniversary has passed
+    try {
+      const escalationResult = applyOtherAssetEscalations();
+      if (escalationResult.escalated > 0) {
+        writeSchedulerLog("Other asset escalation: " + escalationResult.escalated + " rise(s) applied");
+      }
+      if (escalationResult.pending > 0) {
+        writeSchedulerLog("Other asset escalation: " + escalationResult.pending + " asset(s) waiting for index values", "warn");
+      }
+    } catch (escalationErr) {
+      writeSchedulerLog("Other asset escalation failed: " + escalationErr.message, "error");
+    }
+
+    // Re-estimate properties valued from a house price index
+    try {
+      const indexationResult = applyOtherAssetIndexation();
+      if (indexationResult.indexed > 0) {
+        writeSchedulerLog("Property indexation: " + indexationResult.indexed + " value(s) re-estimated");
+      }
+      if (indexationResult.pending > 0) {
+        writeSchedulerLog("Property indexation: " + indexationResult.pending + " property(ies) waiting for index values", "warn");
+      }
+    } catch (indexationErr) {
+      writeSchedulerLog("Property indexation failed: " + indexationErr.message, "error");
+    }
   } catch (err) {
     writeSchedulerLog("Fetch run failed with error: " + err.message, "error");
     lastRunResult = {
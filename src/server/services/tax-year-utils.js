@@ -0,0 +1,125 @@
+import { getIsaAllowanceConfig } from "../config.js";
+
+/**
+ * @description Pad a number to two digits for ISO date strings.
+ * @param {number} n - The number to pad
+ * @returns {string} Two-digit string
+ */
+function pad2(n) {
+  return String(n).padStart(2, "0");
+}
+
+/**
+ * @description Get the UK tax year containing a given date. The tax year
+ * start month and day are read from the isaAllowance config (6 April by default)
+ * unless supplied explicitly.
+ * @param {string} dateStr - ISO-8601 date (YYYY-MM-DD)
+ * @param {number} [startMonth] - Tax year start month (1-12)
+ * @param {number} [startDay] - Tax year start day (1-28)
+ * @returns {{ start: string, end: string, label: string }} Tax year bounds and label (e.g. "2025/2026")
+ */
+export function getTaxYearForDate(dateStr, startMonth, startDay) {
+  if (!startMonth || !startDay) {
+    const config = getIsaAllowanceConfig();
+    startMonth = config.taxYearStartMonth;
+    startDay = config.taxYearStartDay;
+  }
+
+  const year = Number(dateStr.slice(0, 4));
+  const month = Number(dateStr.slice(5, 7));
+  const day = Number(dateStr.slice(8, 10));
+
+  // Before the start date the tax year began in the previous calendar year
+  const startYear = month < startMonth || (month === startMonth && day < startDay) ? year - 1 : year;
+
+  return getTaxYearByStartYear(startYear, startMonth, startDay);
+}
+
+/**
+ * @description Get the tax year that starts in the given calendar year.
+ * @param {number} startYear - Calendar year in which the tax year starts (e.g. 2025)
+ * @param {number} [startMonth] - Tax year start month (1-12)
+ * @param {number} [startDay] - Tax year start day (1-28)
+ * @returns {{ start: string, end: string, label: string }} Tax year bounds and label
+ */
+export function getTaxYearByStartYear(startYear, startMonth, startDay) {
+  if (!startMonth || !startDay) {
+    const config = getIsaAllowanceConfig();
+    startMonth = config.taxYearStartMonth;
+    startDay = config.taxYearStartDay;
+  }
+
+  const start = startYear + "-" + pad2(startMonth) + "-" + pad2(startDay);
+
+  // End date is the day before the next tax year starts
+  const endDate = new Date(Date.UTC(startYear + 1, startMonth - 1, startDay));
+  endDate.setUTCDate(endDate.getUTCDate() - 1);
+  const end = endDate.toISOString().slice(0, 10);
+
+  return {
+    start: start,
+    end: end,
+    label: startYear + "/" + (startYear + 1),
+  };
+}
+
+/**
+ * @description Get the PAYE tax month (1-12) containing a date. Month 1 runs
+ * from the start of the tax year (6 April by default) to the day before the
+ * same day of the next month.
+ * @param {string} dateStr - ISO-8601 date (YYYY-MM-DD)
+ * @returns {number} The tax month, 1 to 12
+ */
+export function getTaxMonth(dateStr) {
+  const config = getIsaAllowanceConfig();
+  const ty = getTaxYearForDate(dateStr);
+
+  const startYear = Number(ty.start.slice(0, 4));
+  const year = Number(dateStr.slice(0, 4));
+  const month = Number(dateStr.slice(5, 7));
+  const day = Number(dateStr.slice(8, 10));
+
+  const monthsElapsed = (year - startYear) * 12 + (month - config.taxYearStartMonth);
+  return day >= config.taxYearStartDay ? monthsElapsed + 1 : monthsElapsed;
+}
+
+/**
+ * @description Parse a tax year label ("2025/2026" or "2025-26" or "2025")
+ * into the calendar year in which it starts.
+ * @param {string} label - The tax year label
+ * @returns {number|null} The start year, or null if the label is not recognised
+ */
+export function parseTaxYearLabel(label) {
+  if (!label) return null;
+  const match = String(label).trim().match(/^(\d{4})(?:[/-](\d{2}|\d{4}))?$/);
+  if (!match) return null;
+  return Number(match[1]);
+}
+
+/**
+ * @description Add a number of days to an ISO-8601 date string.
+ * @param {string} dateStr - ISO-8601 date (YYYY-MM-DD)
+ * @param {number} days - Days to add (may be negative)
+ * @returns {string} The resulting ISO-8601 date
+ */
+export function addDays(dateStr, days) {
+  const d = new Date(dateStr + "T00:00:00Z");
+  d.setUTCDate(d.getUTCDate() + days);
+  return d.toISOString().slice(0, 10);
+}
+
+/**
+ * @description Add whole months to an ISO-8601 date, keeping the day of the
+ * month where it exists and using the last day of the month otherwise
+ * (e.g. 31 January + 1 month is 28 February).
+ * @param {string} dateStr - ISO-8601 date (YYYY-MM-DD)
+ * @param {number} months - Months to add (may be negative)
+ * @returns {string} The resulting ISO-8601 date
+ */
+export function addMonths(dateStr, months) {
+  const year = parseInt(dateStr.slice(0, 4), 10);
+  const month = parseInt(dateStr.slice(5, 7), 10) - 1 + months;
+  const day = parseInt(dateStr.slice(8, 10), 10);
+  const lastDay = new Date(Date.UTC(year, month + 1, 0)).getUTCDate();
+  return new Date(Date.UTC(year, month, Math.min(day, lastDay))).toISOString().slice(0, 10);
+}
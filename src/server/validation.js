@@ -1,5 +1,6 @@
 import { getAllowedProviderCodes } from "./routes/config-routes.js";
 import { validatePublicId as validatePublicIdFormat } from "../shared/public-id-utils.js";
+import { parseTaxCode } from "./services/drawdown-tax-service.js";
 
 /**
  * @description Shared validation helpers for Portfolio 60 API routes.
@@ -78,6 +79,24 @@ export function validateUser(data) {
     if (error) errors.push(error);
   }
 
+  // date_of_birth is optional but must be a valid ISO-8601 date in the past
+  if (data.date_of_birth !== undefined && data.date_of_birth !== null && String(data.date_of_birth).trim() !== "") {
+    const dateStr = String(data.date_of_birth).trim();
+    if (!/^\d{4}-\d{2}-\d{2}$/.test(dateStr) || isNaN(new Date(dateStr + "T00:00:00").getTime())) {
+      errors.push("Date of birth must be a valid date in YYYY-MM-DD format");
+    } else if (dateStr > new Date().toISOString().slice(0, 10)) {
+      errors.push("Date of birth cannot be in the future");
+    }
+  }
+
+  // spouse_user_id is optional; the route checks the user exists
+  if (data.spouse_user_id !== undefined && data.spouse_user_id !== null && data.spouse_user_id !== "") {
+    const spouseId = Number(data.spouse_user_id);
+    if (!Number.isInteger(spouseId) || spouseId <= 0) {
+      errors.push("Spouse must be a valid selection");
+    }
+  }
+
   return errors;
 }
 
@@ -113,12 +132,20 @@ export function validateInvestment(data) {
   }
 
   // Max length checks
-  const lengthChecks = [validateMaxLength(data.description, 60, "Description"), validateMaxLength(data.public_id, 20, "Public ID"), validateMaxLength(data.investment_url, 255, "Investment URL"), validateMaxLength(data.selector, 255, "CSS selector")];
+  const lengthChecks = [validateMaxLength(data.description, 60, "Description"), validateMaxLength(data.public_id, 20, "Public ID"), validateMaxLength(data.investment_url, 255, "Investment URL"), validateMaxLength(data.selector, 255, "CSS selector"), validateMaxLength(data.allocation_tag ? String(data.allocation_tag).trim() : null, 30, "Allocation tag")];
 
   for (const error of lengthChecks) {
     if (error) errors.push(error);
   }
 
+  // unit_type is optional — must be 'income' or 'accumulation' if provided
+  if (data.unit_type !== undefined && data.unit_type !== null && String(data.unit_type).trim() !== "") {
+    const unitType = String(data.unit_type).trim();
+    if (unitType !== "income" && unitType !== "accumulation") {
+      errors.push("Unit type must be 'income' or 'accumulation'");
+    }
+  }
+
   // Public ID format validation (optional field — only validate if provided)
   if (data.public_id && String(data.public_id).trim() !== "") {
     const publicIdResult = validatePublicIdFormat(data.public_id);
@@ -224,6 +251,14 @@ export function validateAccount(data) {
     }
   }
 
+  // Provider is optional (defaults to the user's provider) but must be from the allowed list
+  if (data.provider && String(data.provider).trim() !== "") {
+    const allowed = getAllowedProviderCodes();
+    if (!allowed.includes(String(data.provider).toLowerCase())) {
+      errors.push("Provider must be one of: " + allowed.join(", "));
+    }
+  }
+
   // Max length checks
   const lengthChecks = [validateMaxLength(data.account_ref, 15, "Account reference")];
 
@@ -302,11 +337,24 @@ export function validateCashTransaction(data) {
     if (error) errors.push(error);
   }
 
-  // transaction_type must be 'deposit', 'withdrawal', 'drawdown', or 'adjustment'
+  // transaction_type must be 'deposit', 'withdrawal', 'drawdown', 'adjustment', 'dividend', 'interest' or 'tax_relief'
   if (data.transaction_type !== undefined && data.transaction_type !== null) {
     const txType = String(data.transaction_type).trim();
-    if (txType !== "" && txType !== "deposit" && txType !== "withdrawal" && txType !== "drawdown" && txType !== "adjustment") {
-      errors.push("Transaction type must be 'deposit', 'withdrawal', 'drawdown', or 'adjustment'");
+    const validTypes = ["deposit", "withdrawal", "drawdown", "adjustment", "dividend", "interest", "tax_relief"];
+    if (txType !== "" && !validTypes.includes(txType)) {
+      errors.push("Transaction type must be 'deposit', 'withdrawal', 'drawdown', 'adjustment', 'dividend', 'interest' or 'tax_relief'");
+    }
+  }
+
+  // Dividends must identify the paying investment; interest may optionally do so
+  if (data.transaction_type === "dividend") {
+    const investmentError = validateRequired(data.investment_id, "Investment");
+    if (investmentError) errors.push(investmentError);
+  }
+  if ((data.transaction_type === "dividend" || data.transaction_type === "interest") && data.investment_id !== undefined && data.investment_id !== null && data.investment_id !== "") {
+    const investmentId = Number(data.investment_id);
+    if (!Number.isInteger(investmentId) || investmentId <= 0) {
+      errors.push("Investment must be a valid selection");
     }
   }
 
@@ -340,6 +388,24 @@ export function validateCashTransaction(data) {
     }
   }
 
+  // tax_withheld is optional on drawdowns: the PAYE deducted from the gross amount
+  if (data.tax_withheld !== undefined && data.tax_withheld !== null && data.tax_withheld !== "") {
+    const taxWithheld = Number(data.tax_withheld);
+    if (data.transaction_type !== "drawdown") {
+      errors.push("Tax withheld only applies to drawdowns");
+    } else if (isNaN(taxWithheld) || taxWithheld < 0) {
+      errors.push("Tax withheld must be zero or more");
+    } else if (taxWithheld > Number(data.amount)) {
+      errors.push("Tax withheld cannot be more than the gross amount");
+    }
+  }
+
+  // tax_code is optional on drawdowns
+  if (data.tax_code !== undefined && data.tax_code !== null && String(data.tax_code).trim() !== "") {
+    const codeError = validateTaxCode(data.tax_code);
+    if (codeError) errors.push(codeError);
+  }
+
   // notes is optional, max 255 chars
   const lengthChecks = [validateMaxLength(data.notes, 255, "Notes")];
 
@@ -350,6 +416,140 @@ export function validateCashTransaction(data) {
   return errors;
 }
 
+/**
+ * @description Validate a PAYE tax code. Scottish (S prefix) codes are
+ * rejected because Scottish income tax rates are not modelled.
+ * @param {string} taxCode - The tax code to check
+ * @returns {string|null} Error message or null if valid
+ */
+export function validateTaxCode(taxCode) {
+  const code = String(taxCode).trim().toUpperCase();
+  if (code.startsWith("S")) {
+    return "Scottish tax codes are not supported";
+  }
+  if (!parseTaxCode(code)) {
+    return "Tax code is not recognised (e.g. 1257L, BR, D0, K475, 1257L M1)";
+  }
+  return null;
+}
+
+/**
+ * @description Validate an ISA transfer between providers. At least one side
+ * must be an ISA held here; the other may be held elsewhere.
+ * Returns an array of error messages (empty if all valid).
+ * @param {Object} data - The ISA transfer data to validate
+ * @returns {string[]} Array of validation error messages
+ */
+export function validateIsaTransfer(data) {
+  const errors = [];
+
+  const requiredChecks = [validateRequired(data.transaction_date, "Transfer date"), validateRequired(data.amount, "Amount"), validateRequired(data.isa_transfer, "Subscription year")];
+
+  for (const error of requiredChecks) {
+    if (error) errors.push(error);
+  }
+
+  const hasFrom = data.from_account_id !== undefined && data.from_account_id !== null && data.from_account_id !== "";
+  const hasTo = data.to_account_id !== undefined && data.to_account_id !== null && data.to_account_id !== "";
+  if (!hasFrom && !hasTo) {
+    errors.push("A source or destination ISA is required");
+  }
+  if (hasFrom && hasTo && Number(data.from_account_id) === Number(data.to_account_id)) {
+    errors.push("Source and destination ISA must be different");
+  }
+
+  // isa_transfer must be 'current_year' or 'previous_years'
+  if (data.isa_transfer !== undefined && data.isa_transfer !== null && String(data.isa_transfer).trim() !== "") {
+    const kind = String(data.isa_transfer).trim();
+    if (kind !== "current_year" && kind !== "previous_years") {
+      errors.push("Subscription year must be 'current_year' or 'previous_years'");
+    }
+  }
+
+  // transaction_date must be ISO-8601 format (YYYY-MM-DD)
+  if (data.transaction_date !== undefined && data.transaction_date !== null && String(data.transaction_date).trim() !== "") {
+    const dateStr = String(data.transaction_date).trim();
+    if (!/^\d{4}-\d{2}-\d{2}$/.test(dateStr) || isNaN(new Date(dateStr + "T00:00:00").getTime())) {
+      errors.push("Transfer date must be a valid date in YYYY-MM-DD format");
+    }
+  }
+
+  // amount must be a positive number
+  if (data.amount !== undefined && data.amount !== null) {
+    const amount = Number(data.amount);
+    if (isNaN(amount) || amount <= 0) {
+      errors.push("Amount must be greater than zero");
+    }
+  }
+
+  const lengthChecks = [validateMaxLength(data.notes, 255, "Notes")];
+
+  for (const error of lengthChecks) {
+    if (error) errors.push(error);
+  }
+
+  return errors;
+}
+
+/**
+ * @description Validate a SIPP contribution. The gross amount is recorded; for
+ * a personal contribution the net payment is derived from it.
+ * Returns an array of error messages (empty if all valid).
+ * @param {Object} data - The pension contribution data to validate
+ * @returns {string[]} Array of validation error messages
+ */
+export function validatePensionContribution(data) {
+  const errors = [];
+
+  const requiredChecks = [validateRequired(data.contribution_type, "Contribution type"), validateRequired(data.transaction_date, "Contribution date"), validateRequired(data.gross_amount, "Gross amount")];
+
+  for (const error of requiredChecks) {
+    if (error) errors.push(error);
+  }
+
+  // contribution_type must be 'personal' or 'employer'
+  if (data.contribution_type !== undefined && data.contribution_type !== null && String(data.contribution_type).trim() !== "") {
+    const kind = String(data.contribution_type).trim();
+    if (kind !== "personal" && kind !== "employer") {
+      errors.push("Contribution type must be 'personal' or 'employer'");
+    }
+  }
+
+  // Dates must be ISO-8601 format (YYYY-MM-DD); relief_date is optional
+  const dateChecks = [
+    { value: data.transaction_date, label: "Contribution date" },
+    { value: data.relief_date, label: "Relief date" },
+  ];
+  for (const check of dateChecks) {
+    if (check.value !== undefined && check.value !== null && String(check.value).trim() !== "") {
+      const dateStr = String(check.value).trim();
+      if (!/^\d{4}-\d{2}-\d{2}$/.test(dateStr) || isNaN(new Date(dateStr + "T00:00:00").getTime())) {
+        errors.push(check.label + " must be a valid date in YYYY-MM-DD format");
+      }
+    }
+  }
+
+  if (data.relief_date && data.contribution_type === "employer") {
+    errors.push("Relief date only applies to personal contributions");
+  }
+
+  // gross_amount must be a positive number
+  if (data.gross_amount !== undefined && data.gross_amount !== null) {
+    const amount = Number(data.gross_amount);
+    if (isNaN(amount) || amount <= 0) {
+      errors.push("Gross amount must be greater than zero");
+    }
+  }
+
+  const lengthChecks = [validateMaxLength(data.notes, 255, "Notes")];
+
+  for (const error of lengthChecks) {
+    if (error) errors.push(error);
+  }
+
+  return errors;
+}
+
 /**
  * @description Validate holding movement data for buy, sell, or adjustment operations.
  * Adjustment movements (stock splits) require new_quantity and movement_date but
@@ -479,6 +679,157 @@ export function validateHoldingMovement(data) {
   return errors;
 }
 
+/**
+ * @description Validate a SIPP crystallisation. At least one of the tax-free
+ * cash and the amount moved into drawdown must be given, and the tax-free cash
+ * cannot be more than a quarter of the total amount crystallised.
+ * Returns an array of error messages (empty if all valid).
+ * @param {Object} data - The crystallisation data to validate
+ * @returns {string[]} Array of validation error messages
+ */
+export function validateCrystallisation(data) {
+  const errors = [];
+
+  const requiredError = validateRequired(data.crystallisation_date, "Crystallisation date");
+  if (requiredError) errors.push(requiredError);
+
+  // crystallisation_date must be ISO-8601 format (YYYY-MM-DD)
+  if (data.crystallisation_date !== undefined && data.crystallisation_date !== null && String(data.crystallisation_date).trim() !== "") {
+    const dateStr = String(data.crystallisation_date).trim();
+    if (!/^\d{4}-\d{2}-\d{2}$/.test(dateStr) || isNaN(new Date(dateStr + "T00:00:00").getTime())) {
+      errors.push("Crystallisation date must be a valid date in YYYY-MM-DD format");
+    }
+  }
+
+  // Both amounts are optional individually but must be zero or more
+  const amounts = {};
+  const amountChecks = [
+    { key: "tax_free_cash", label: "Tax-free cash" },
+    { key: "drawdown_amount", label: "Amount moved into drawdown" },
+  ];
+  for (const check of amountChecks) {
+    const value = data[check.key];
+    amounts[check.key] = 0;
+    if (value !== undefined && value !== null && String(value).trim() !== "") {
+      const amount = Number(value);
+      if (isNaN(amount) || amount < 0) {
+        errors.push(check.label + " must be zero or more");
+      } else {
+        amounts[check.key] = amount;
+      }
+    }
+  }
+
+  const total = amounts.tax_free_cash + amounts.drawdown_amount;
+  if (total <= 0) {
+    errors.push("Enter the tax-free cash taken or the amount moved into drawdown");
+  } else if (Math.round(amounts.tax_free_cash * 100) > Math.round(total * 25)) {
+    errors.push("Tax-free cash cannot be more than 25% of the amount crystallised");
+  }
+
+  const lengthChecks = [validateMaxLength(data.notes, 255, "Notes")];
+
+  for (const error of lengthChecks) {
+    if (error) errors.push(error);
+  }
+
+  return errors;
+}
+
+/**
+ * @description Validate a request to commit cash buffer sales: a sale date and
+ * at least one sale, each naming a holding, a quantity and the proceeds.
+ * Returns an array of error messages (empty if all valid).
+ * @param {Object} data - The request body to validate
+ * @returns {string[]} Array of validation error messages
+ */
+export function validateCashBufferSales(data) {
+  const errors = [];
+
+  const requiredError = validateRequired(data.movement_date, "Sale date");
+  if (requiredError) {
+    errors.push(requiredError);
+  } else {
+    const dateStr = String(data.movement_date).trim();
+    if (!/^\d{4}-\d{2}-\d{2}$/.test(dateStr) || isNaN(new Date(dateStr + "T00:00:00").getTime())) {
+      errors.push("Sale date must be a valid date in YYYY-MM-DD format");
+    }
+  }
+
+  if (!Array.isArray(data.sales) || data.sales.length === 0) {
+    errors.push("At least one sale is required");
+    return errors;
+  }
+
+  data.sales.forEach(function (sale, index) {
+    const label = "Sale " + (index + 1);
+    if (!sale || !Number.isInteger(Number(sale.holding_id)) || Number(sale.holding_id) <= 0) {
+      errors.push(label + ": holding is required");
+      return;
+    }
+    const quantity = Number(sale.quantity);
+    if (isNaN(quantity) || quantity <= 0) {
+      errors.push(label + ": quantity must be greater than zero");
+    }
+    const proceeds = Number(sale.total_consideration);
+    if (isNaN(proceeds) || proceeds < 0) {
+      errors.push(label + ": proceeds must be zero or more");
+    }
+  });
+
+  return errors;
+}
+
+/**
+ * @description Validate the escalation fields shared by drawdown schedules and
+ * recurring other assets. escalation_type is optional (defaults to 'none'); a
+ * fixed rate or an index series must go with it, plus an anniversary as MM-DD.
+ * Whether the index series exists is checked at the route level.
+ * @param {Object} data - The schedule or asset data
+ * @returns {string[]} Array of validation error messages
+ */
+function validateEscalation(data) {
+  const errors = [];
+  const type = data.escalation_type !== undefined && data.escalation_type !== null && String(data.escalation_type).trim() !== "" ? String(data.escalation_type).trim() : "none";
+
+  if (!["none", "fixed", "index"].includes(type)) {
+    errors.push("Escalation type must be one of: none, fixed, index");
+    return errors;
+  }
+  if (type === "none") return errors;
+
+  if (type === "fixed") {
+    const rateError = validateRequired(data.escalation_rate, "Escalation rate");
+    const rate = Number(data.escalation_rate);
+    if (rateError) {
+      errors.push(rateError);
+    } else if (isNaN(rate) || rate <= 0 || rate > 25) {
+      errors.push("Escalation rate must be greater than 0 and no more than 25");
+    }
+  } else {
+    const indexError = validateRequired(data.escalation_index_id, "Escalation index");
+    const indexId = Number(data.escalation_index_id);
+    if (indexError) {
+      errors.push(indexError);
+    } else if (!Number.isInteger(indexId) || indexId <= 0) {
+      errors.push("Escalation index must be a valid selection");
+    }
+  }
+
+  const anniversaryError = validateRequired(data.escalation_anniversary, "Escalation anniversary");
+  if (anniversaryError) {
+    errors.push(anniversaryError);
+  } else {
+    const anniversary = String(data.escalation_anniversary).trim();
+    const parsed = new Date("2025-" + anniversary + "T00:00:00Z");
+    if (!/^\d{2}-\d{2}$/.test(anniversary) || isNaN(parsed.getTime()) || parsed.toISOString().slice(5, 10) !== anniversary) {
+      errors.push("Escalation anniversary must be a day of the year in MM-DD format (not 29 February)");
+    }
+  }
+
+  return errors;
+}
+
 /**
  * @description Validate drawdown schedule data for create or update operations.
  * Returns an array of error messages (empty if all valid).
@@ -556,6 +907,26 @@ export function validateDrawdownSchedule(data) {
     }
   }
 
+  // tax_treatment is optional (defaults to 'none'); a tax code or flat rate must go with it
+  const treatment = data.tax_treatment !== undefined && data.tax_treatment !== null && String(data.tax_treatment).trim() !== "" ? String(data.tax_treatment).trim() : "none";
+  if (!["none", "tax_code", "flat", "emergency"].includes(treatment)) {
+    errors.push("Tax treatment must be one of: none, tax_code, flat, emergency");
+  } else if (treatment === "tax_code") {
+    const codeError = validateRequired(data.tax_code, "Tax code") || validateTaxCode(data.tax_code);
+    if (codeError) errors.push(codeError);
+  } else if (treatment === "flat") {
+    const rateError = validateRequired(data.flat_tax_rate, "Flat tax rate");
+    const rate = Number(data.flat_tax_rate);
+    if (rateError) {
+      errors.push(rateError);
+    } else if (isNaN(rate) || rate < 0 || rate >= 100) {
+      errors.push("Flat tax rate must be between 0 and 100");
+    }
+  }
+
+  // escalation is optional; the amount rises each year on the anniversary
+  errors.push(...validateEscalation(data));
+
   // notes is optional, max 255 chars
   const lengthChecks = [validateMaxLength(data.notes, 255, "Notes")];
 
@@ -570,7 +941,7 @@ export function validateDrawdownSchedule(data) {
  * @description Valid categories for other assets.
  * @type {string[]}
  */
-const OTHER_ASSET_CATEGORIES = ["pension", "property", "savings", "alternative"];
+const OTHER_ASSET_CATEGORIES = ["pension", "property", "savings", "alternative", "liability"];
 
 /**
  * @description Valid frequencies for recurring other assets.
@@ -648,6 +1019,85 @@ export function validateOtherAsset(data) {
     }
   }
 
+  // a liability is a balance owed, with an optional interest rate and monthly repayment
+  const hasInterestRate = data.interest_rate !== undefined && data.interest_rate !== null && data.interest_rate !== "";
+  const hasRepayment = data.repayment !== undefined && data.repayment !== null && data.repayment !== "";
+  if (data.category === "liability") {
+    if (data.value_type === "recurring") {
+      errors.push("A liability must use the 'value' type for the balance owed");
+    }
+    if (hasInterestRate) {
+      const rate = Number(data.interest_rate);
+      if (isNaN(rate) || rate < 0 || rate > 100) {
+        errors.push("Interest rate must be between 0% and 100%");
+      }
+    }
+    if (hasRepayment) {
+      const repayment = Number(data.repayment);
+      if (isNaN(repayment) || repayment < 0) {
+        errors.push("Monthly repayment must be zero or a positive number");
+      }
+    }
+  } else if (hasInterestRate || hasRepayment) {
+    errors.push("Interest rate and repayment can only be set for liabilities");
+  }
+
+  // revaluation reminder is optional: a whole number of months up to ten years
+  if (data.revalue_months !== undefined && data.revalue_months !== null && data.revalue_months !== "") {
+    const months = Number(data.revalue_months);
+    if (!Number.isInteger(months) || months < 1 || months > 120) {
+      errors.push("Revalue every must be a whole number of months from 1 to 120");
+    }
+  }
+
+  // a property may be valued from a house price index, from the value surveyed on a date
+  const hasValuationIndex = data.valuation_index_id !== undefined && data.valuation_index_id !== null && data.valuation_index_id !== "";
+  if (hasValuationIndex) {
+    const indexId = Number(data.valuation_index_id);
+    if (data.category !== "property") {
+      errors.push("A house price index can only be set for property");
+    } else if (data.value_type === "recurring") {
+      errors.push("A property valued from a house price index must use the 'value' type");
+    }
+    if (!Number.isInteger(indexId) || indexId <= 0) {
+      errors.push("House price index must be a valid selection");
+    }
+    if (data.surveyed_date !== undefined && data.surveyed_date !== null && String(data.surveyed_date).trim() !== "") {
+      const dateStr = String(data.surveyed_date).trim();
+      if (!/^\d{4}-\d{2}-\d{2}$/.test(dateStr) || isNaN(new Date(dateStr + "T00:00:00").getTime())) {
+        errors.push("Surveyed date must be a valid date in YYYY-MM-DD format");
+      } else if (dateStr > new Date().toISOString().slice(0, 10)) {
+        errors.push("Surveyed date cannot be in the future");
+      }
+    }
+  }
+
+  // the main residence flag is for the home that qualifies for the residence nil-rate band
+  if (data.main_residence && data.main_residence !== "0") {
+    if (data.category !== "property") {
+      errors.push("Only property can be marked as the main residence");
+    } else if (data.value_type === "recurring") {
+      errors.push("A main residence must use the 'value' type");
+    }
+  }
+
+  // start_date is optional: when the item was first held
+  if (data.start_date !== undefined && data.start_date !== null && String(data.start_date).trim() !== "") {
+    const dateStr = String(data.start_date).trim();
+    if (!/^\d{4}-\d{2}-\d{2}$/.test(dateStr) || isNaN(new Date(dateStr + "T00:00:00").getTime())) {
+      errors.push("Held since must be a valid date in YYYY-MM-DD format");
+    } else if (dateStr > new Date().toISOString().slice(0, 10)) {
+      errors.push("Held since cannot be in the future");
+    }
+  }
+
+  // escalation is optional, and only applies to recurring assets
+  if (data.value_type !== "recurring" && data.escalation_type && data.escalation_type !== "none") {
+    errors.push("Escalation can only be set for recurring assets");
+  } else {
+    errors.push(...validateEscalation(data));
+  }
+
   // Max length checks
   const lengthChecks = [
     validateMaxLength(data.description, 40, "Description"),
@@ -662,11 +1112,210 @@ export function validateOtherAsset(data) {
   return errors;
 }
 
+/**
+ * @description Validate the date an other asset is closed on. The date is
+ * optional (today is used), and cannot be in the future or before the item
+ * was first held.
+ * @param {Object} data - The close request with closed_date
+ * @param {string|null} startDate - The asset's start_date, if known
+ * @returns {string[]} Array of validation error messages
+ */
+export function validateOtherAssetClose(data, startDate) {
+  const errors = [];
+  if (data.closed_date === undefined || data.closed_date === null || String(data.closed_date).trim() === "") {
+    return errors;
+  }
+
+  const dateStr = String(data.closed_date).trim();
+  if (!/^\d{4}-\d{2}-\d{2}$/.test(dateStr) || isNaN(new Date(dateStr + "T00:00:00").getTime())) {
+    errors.push("Closed date must be a valid date in YYYY-MM-DD format");
+  } else if (dateStr > new Date().toISOString().slice(0, 10)) {
+    errors.push("Closed date cannot be in the future");
+  } else if (startDate && dateStr < startDate) {
+    errors.push("Closed date cannot be before the date the asset was first held");
+  }
+  return errors;
+}
+
+/**
+ * @description Validate index series data (e.g. CPI) for create or update operations.
+ * Returns an array of error messages (empty if all valid).
+ * @param {Object} data - The index series data to validate
+ * @returns {string[]} Array of validation error messages
+ */
+export function validateIndexSeries(data) {
+  const errors = [];
+
+  const nameError = validateRequired(data.name, "Name");
+  if (nameError) errors.push(nameError);
+
+  const lengthChecks = [validateMaxLength(data.name, 30, "Name"), validateMaxLength(data.description, 80, "Description"), validateMaxLength(data.region, 60, "Region")];
+
+  for (const error of lengthChecks) {
+    if (error) errors.push(error);
+  }
+
+  return errors;
+}
+
+/**
+ * @description Valid exemption types for gifts in the gifts register.
+ * @type {string[]}
+ */
+const GIFT_EXEMPTION_TYPES = ["annual", "small_gift", "normal_expenditure"];
+
+/**
+ * @description Validate gift data for create or update operations.
+ * Whether the donor exists and is a family member is checked at the route level.
+ * Returns an array of error messages (empty if all valid).
+ * @param {Object} data - The gift data to validate
+ * @returns {string[]} Array of validation error messages
+ */
+export function validateGift(data) {
+  const errors = [];
+
+  const requiredChecks = [
+    validateRequired(data.user_id, "Donor"),
+    validateRequired(data.recipient, "Recipient"),
+    validateRequired(data.gift_date, "Gift date"),
+    validateRequired(data.amount, "Amount"),
+    validateRequired(data.exemption_type, "Exemption type"),
+  ];
+
+  for (const error of requiredChecks) {
+    if (error) errors.push(error);
+  }
+
+  // user_id must be a positive integer
+  if (data.user_id !== undefined && data.user_id !== null && String(data.user_id).trim() !== "") {
+    const userId = Number(data.user_id);
+    if (!Number.isInteger(userId) || userId <= 0) {
+      errors.push("Donor must be a valid selection");
+    }
+  }
+
+  // gift_date must be ISO-8601 format (YYYY-MM-DD)
+  if (data.gift_date !== undefined && data.gift_date !== null && String(data.gift_date).trim() !== "") {
+    const dateStr = String(data.gift_date).trim();
+    if (!/^\d{4}-\d{2}-\d{2}$/.test(dateStr) || isNaN(new Date(dateStr + "T00:00:00").getTime())) {
+      errors.push("Gift date must be a valid date in YYYY-MM-DD format");
+    }
+  }
+
+  // amount must be a positive number
+  if (data.amount !== undefined && data.amount !== null && String(data.amount).trim() !== "") {
+    const amount = Number(data.amount);
+    if (isNaN(amount) || amount <= 0) {
+      errors.push("Amount must be a positive number");
+    }
+  }
+
+  // exemption_type must be one of the allowed values
+  if (data.exemption_type !== undefined && data.exemption_type !== null) {
+    const exemptionType = String(data.exemption_type).trim();
+    if (exemptionType !== "" && !GIFT_EXEMPTION_TYPES.includes(exemptionType)) {
+      errors.push("Exemption type must be one of: " + GIFT_EXEMPTION_TYPES.join(", "));
+    }
+  }
+
+  const lengthChecks = [validateMaxLength(data.recipient, 60, "Recipient"), validateMaxLength(data.notes, 255, "Notes")];
+
+  for (const error of lengthChecks) {
+    if (error) errors.push(error);
+  }
+
+  return errors;
+}
+
+/**
+ * @description Validate a set of allocation targets for a person or account.
+ * Every target is set either by investment or by allocation tag — not a mix —
+ * with a percentage above 0, and the set must not total more than 100%.
+ * Whether the investments and account exist is checked at the route level.
+ * @param {Object} data - Body with user_id, optional account_id and a targets array
+ * @returns {string[]} Array of validation error messages
+ */
+export function validateAllocationTargets(data) {
+  const errors = [];
+
+  const userId = Number(data.user_id);
+  if (!Number.isInteger(userId) || userId <= 0) {
+    errors.push("User must be a valid selection");
+  }
+
+  if (data.account_id !== undefined && data.account_id !== null && data.account_id !== "") {
+    const accountId = Number(data.account_id);
+    if (!Number.isInteger(accountId) || accountId <= 0) {
+      errors.push("Account must be a valid selection");
+    }
+  }
+
+  if (!Array.isArray(data.targets)) {
+    errors.push("Targets must be a list");
+    return errors;
+  }
+
+  let byInvestment = 0;
+  let byTag = 0;
+  let total = 0;
+  const seen = [];
+
+  data.targets.forEach(function (target, index) {
+    const label = "Target " + (index + 1);
+    if (!target) {
+      errors.push(label + ": an investment or allocation tag is required");
+      return;
+    }
+
+    let key;
+    if (target.investment_id !== undefined && target.investment_id !== null && target.investment_id !== "") {
+      const investmentId = Number(target.investment_id);
+      if (!Number.isInteger(investmentId) || investmentId <= 0) {
+        errors.push(label + ": investment must be a valid selection");
+      }
+      byInvestment++;
+      key = "investment:" + investmentId;
+    } else if (target.allocation_tag && String(target.allocation_tag).trim() !== "") {
+      const lengthError = validateMaxLength(String(target.allocation_tag).trim(), 30, label + ": allocation tag");
+      if (lengthError) errors.push(lengthError);
+      byTag++;
+      key = "tag:" + String(target.allocation_tag).trim().toLowerCase();
+    } else {
+      errors.push(label + ": an investment or allocation tag is required");
+      return;
+    }
+
+    if (seen.indexOf(key) !== -1) {
+      errors.push(label + ": appears more than once");
+    }
+    seen.push(key);
+
+    const percent = Number(target.target_percent);
+    if (isNaN(percent) || percent <= 0 || percent > 100) {
+      errors.push(label + ": target must be above 0% and no more than 100%");
+    } else {
+      total += percent;
+    }
+  });
+
+  if (byInvestment > 0 && byTag > 0) {
+    errors.push("Targets must all be set by investment or all by allocation tag");
+  }
+  if (total > 100.0001) {
+    errors.push("Targets must not total more than 100%");
+  }
+
+  return errors;
+}
+
 /**
  * @description Validate benchmark data for create or update operations.
  * Returns an array of error messages (empty if all valid).
- * Note: The check that index benchmarks must use GBP currency is done at the route
- * level where we have access to the database to look up the GBP currency ID.
+ * A composite benchmark also needs a rebalance frequency and at least two
+ * components whose weights total 100%.
+ * Note: The check that index and composite benchmarks must use GBP currency, and
+ * that components exist and are not composites themselves, is done at the route
+ * level where we have access to the database.
  * @param {Object} data - The benchmark data to validate
  * @returns {string[]} Array of validation error messages
  */
@@ -688,11 +1337,44 @@ export function validateBenchmark(data) {
     }
   }
 
-  // benchmark_type must be 'index' or 'price'
+  // benchmark_type must be 'index', 'price' or 'composite'
   if (data.benchmark_type !== undefined && data.benchmark_type !== null) {
     const benchmarkType = String(data.benchmark_type).trim();
-    if (benchmarkType !== "" && benchmarkType !== "index" && benchmarkType !== "price") {
-      errors.push("Benchmark type must be either 'index' or 'price'");
+    if (benchmarkType !== "" && benchmarkType !== "index" && benchmarkType !== "price" && benchmarkType !== "composite") {
+      errors.push("Benchmark type must be 'index', 'price' or 'composite'");
+    }
+  }
+
+  if (data.benchmark_type === "composite") {
+    if (data.rebalance_frequency !== "monthly" && data.rebalance_frequency !== "quarterly") {
+      errors.push("Rebalance frequency must be 'monthly' or 'quarterly'");
+    }
+
+    if (!Array.isArray(data.components) || data.components.length < 2) {
+      errors.push("A composite benchmark needs at least two components");
+    } else {
+      let total = 0;
+      const seen = [];
+      data.components.forEach(function (component, index) {
+        const label = "Component " + (index + 1);
+        const componentId = Number(component && component.component_benchmark_id);
+        if (!Number.isInteger(componentId) || componentId <= 0) {
+          errors.push(label + ": benchmark must be a valid selection");
+        } else if (seen.indexOf(componentId) !== -1) {
+          errors.push(label + ": appears more than once");
+        }
+        seen.push(componentId);
+
+        const weight = Number(component && component.weight_percent);
+        if (isNaN(weight) || weight <= 0 || weight > 100) {
+          errors.push(label + ": weight must be above 0% and no more than 100%");
+        } else {
+          total += weight;
+        }
+      });
+      if (Math.abs(total - 100) > 0.0001) {
+        errors.push("Component weights must total 100%");
+      }
     }
   }
 
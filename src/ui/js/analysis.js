@@ -1,8 +1,9 @@
 /**
  * @description Analysis page logic for Portfolio 60.
- * Handles four views: Comparison, League Table, Risk vs Return scatter,
- * Top/Bottom performers. Each view supports period selection, optional
- * benchmark overlay, and Print to PDF.
+ * Handles five performance views: Comparison, League Table, Risk vs Return
+ * scatter, Top/Bottom performers and Correlation. Each supports period
+ * selection, optional benchmark overlay, and Print to PDF. The Allocation
+ * and Rebalance views work from current valuations instead.
  */
 
 /* globals apiRequest, escapeHtml, buildResearchLinkHtml, Chart */
@@ -75,6 +76,18 @@ let selectedUserIds = [];
 /** @type {string} Currently selected account type filter (empty string = all) */
 let accountTypeFilter = "";
 
+/** @type {Object|null} Cached allocation breakdown */
+let allocationData = null;
+
+/** @type {Object} Accounts of each user with accounts, keyed by user ID */
+let accountsByUser = {};
+
+/** @type {Array<Object>} All investments, for the rebalancing target selects */
+let rebalanceInvestments = [];
+
+/** @type {Array<Object>} Targets being edited in the Rebalance tab */
+let rebalanceTargets = [];
+
 // ─── Initialisation ──────────────────────────────────────────────
 
 document.addEventListener("DOMContentLoaded", async function () {
@@ -86,6 +99,8 @@ document.addEventListener("DOMContentLoaded", async function () {
   setupPrintButton();
   setupHoldingsFilter();
   setupAccountTypeFilter();
+  setupAllocationControls();
+  setupRebalanceControls();
   loadBenchmarks();
   await loadUsers();
   activateTab("comparison");
@@ -164,6 +179,54 @@ function benchmarksParam() {
   return "&benchmarks=" + selectedBenchmarkIds.join(",");
 }
 
+/**
+ * @description Build the beta benchmark query parameter string. Beta and
+ * correlation are measured against the first benchmark ticked.
+ * @returns {string} e.g. "&betaBenchmark=1" or ""
+ */
+function betaBenchmarkParam() {
+  if (selectedBenchmarkIds.length === 0) return "";
+  return "&betaBenchmark=" + selectedBenchmarkIds[0];
+}
+
+/**
+ * @description Format a risk ratio (Sharpe, Sortino, beta, correlation) for display.
+ * @param {number|null} val - The ratio
+ * @returns {string} Formatted string like "0.85" or "—"
+ */
+function formatRatio(val) {
+  if (val === null || val === undefined) return "\u2014";
+  return val.toFixed(2);
+}
+
+/**
+ * @description Format a recovery time in days as weeks or months.
+ * @param {Object} item - Row with maxDrawdownPct and recoveryDays
+ * @returns {string} e.g. "5 wks", "7 mths", "Not yet" or "—"
+ */
+function formatRecovery(item) {
+  if (item.maxDrawdownPct === null || item.maxDrawdownPct === undefined || item.maxDrawdownPct === 0) return "\u2014";
+  if (item.recoveryDays === null) return "Not yet";
+  if (item.recoveryDays < 70) return Math.max(1, Math.round(item.recoveryDays / 7)) + " wks";
+  return Math.round(item.recoveryDays / 30.4) + " mths";
+}
+
+/**
+ * @description Describe the risk-free rate and beta benchmark used for the risk
+ * metrics, for the note beneath the tables.
+ * @param {Object} risk - Risk context from the API ({ riskFreeRate, riskFreeSource, benchmark })
+ * @param {string} [periodLabel] - Period the metrics cover, if not the selected period
+ * @returns {string} Note text
+ */
+function describeRiskContext(risk, periodLabel) {
+  if (!risk) return "";
+  let text = periodLabel ? "Risk metrics over " + periodLabel + ". " : "";
+  text += "Sharpe and Sortino use a risk-free rate of " + risk.riskFreeRate.toFixed(2) + "%";
+  text += risk.riskFreeSource !== "config" ? " (average of " + risk.riskFreeSource + ")" : "";
+  text += risk.benchmark ? ". Beta and correlation are against " + risk.benchmark.description + "." : ". Tick a benchmark to see beta and correlation against it.";
+  return text;
+}
+
 // ─── Investment filters ──────────────────────────────────────────
 
 /**
@@ -188,6 +251,7 @@ function clearCachedData() {
   leagueData = null;
   comparisonData = null;
   leagueBenchmarkData = null;
+  allocationData = null;
 }
 
 /**
@@ -207,6 +271,7 @@ async function loadUsers() {
       const acctResult = await apiRequest("/api/users/" + user.id + "/accounts");
       if (acctResult.ok && Array.isArray(acctResult.data) && acctResult.data.length > 0) {
         usersWithAccounts.push(user);
+        accountsByUser[user.id] = acctResult.data;
       }
     }
 
@@ -361,7 +426,7 @@ function setupTabs() {
 
 /**
  * @description Activate a tab and show the corresponding view.
- * @param {string} tabName - "comparison", "league", "scatter", or "topbottom"
+ * @param {string} tabName - "comparison", "league", "scatter", "topbottom", "correlation", "allocation", or "rebalance"
  */
 function activateTab(tabName) {
   activeTab = tabName;
@@ -389,12 +454,23 @@ function activateTab(tabName) {
     league: "league-view",
     scatter: "scatter-view",
     topbottom: "topbottom-view",
+    correlation: "correlation-view",
+    allocation: "allocation-view",
+    rebalance: "rebalance-view",
   };
   document.getElementById(viewMap[tabName]).classList.remove("hidden");
 
   // Hide period selector for comparison tab (it has its own period dropdowns)
   const periodSelector = document.getElementById("period-selector");
-  periodSelector.style.display = tabName === "comparison" ? "none" : "";
+  const isValuationTab = tabName === "allocation" || tabName === "rebalance";
+  periodSelector.style.display = tabName === "comparison" || isValuationTab ? "none" : "";
+
+  // Allocation and rebalancing are valuations, not performance views — benchmarks and PDF do not apply
+  document.getElementById("benchmark-selector").style.display = isValuationTab ? "none" : "";
+  document.getElementById("print-pdf-btn").style.display = isValuationTab ? "none" : "";
+
+  // Rebalancing has its own person and account selection
+  document.getElementById("analysis-filters").style.display = tabName === "rebalance" ? "none" : "";
 
   loadCurrentView();
 }
@@ -466,6 +542,9 @@ function setupSortButtons() {
   }
 }
 
+/** @type {Object<string, string>} League table sort button labels */
+const LEAGUE_SORT_LABELS = { return: "Return", name: "Name", type: "Type", sharpe: "Sharpe", drawdown: "Max DD" };
+
 /**
  * @description Update sort button styles to reflect current sort state.
  */
@@ -477,10 +556,10 @@ function updateSortButtonStyles() {
     if (key === leagueSort) {
       btn.classList.add("underline");
       const arrow = leagueSortDir === "desc" ? " \u2193" : " \u2191";
-      btn.textContent = (key === "return" ? "Return" : key === "name" ? "Name" : "Type") + arrow;
+      btn.textContent = LEAGUE_SORT_LABELS[key] + arrow;
     } else {
       btn.classList.remove("underline");
-      btn.textContent = key === "return" ? "Return" : key === "name" ? "Name" : "Type";
+      btn.textContent = LEAGUE_SORT_LABELS[key];
     }
   }
 }
@@ -515,6 +594,12 @@ async function loadCurrentView() {
     await loadScatter();
   } else if (activeTab === "topbottom") {
     await loadTopBottom();
+  } else if (activeTab === "correlation") {
+    await loadCorrelation();
+  } else if (activeTab === "allocation") {
+    await loadAllocation();
+  } else if (activeTab === "rebalance") {
+    await loadRebalanceTargets();
   }
 }
 
@@ -527,7 +612,7 @@ async function loadComparison() {
   const container = document.getElementById("comparison-table-container");
   container.innerHTML = '<p class="text-brand-500">Loading comparison...</p>';
 
-  const url = "/api/analysis/comparison?periods=" + comparisonPeriods.join(",") + benchmarksParam() + filterParams();
+  const url = "/api/analysis/comparison?periods=" + comparisonPeriods.join(",") + benchmarksParam() + betaBenchmarkParam() + filterParams();
   const result = await apiRequest(url);
   if (!result.ok) {
     container.innerHTML = '<p class="text-error">' + escapeHtml(result.error) + "</p>";
@@ -585,6 +670,10 @@ function renderComparisonTable(data) {
     html += '<span class="comparison-sort-btn cursor-pointer hover:text-brand-900" data-col="' + p + '">Return' + arrow + "</span>";
     html += "</th>";
   }
+  const showBeta = data.risk && data.risk.benchmark;
+  html += '<th class="px-3 py-2 text-right w-20">Max DD</th>';
+  html += '<th class="px-3 py-2 text-right w-16">Sharpe</th>';
+  if (showBeta) html += '<th class="px-3 py-2 text-right w-16">Beta</th>';
   html += "</tr></thead><tbody>";
 
   // Benchmark rows (grey background)
@@ -598,6 +687,8 @@ function renderComparisonTable(data) {
       const bmVal = bm.returns[periods[bp].code];
       html += '<td class="px-3 py-2 text-right font-medium ' + returnColourClass(bmVal) + '">' + formatReturn(bmVal) + "</td>";
     }
+    html += '<td class="px-3 py-2"></td><td class="px-3 py-2"></td>';
+    if (showBeta) html += '<td class="px-3 py-2"></td>';
     html += "</tr>";
   }
 
@@ -615,10 +706,14 @@ function renderComparisonTable(data) {
       const val = inv.returns[periods[ip].code];
       html += '<td class="px-3 py-2 text-right font-medium ' + returnColourClass(val) + '">' + formatReturn(val) + "</td>";
     }
+    html += '<td class="px-3 py-2 text-right text-sm ' + returnColourClass(inv.maxDrawdownPct) + '">' + formatReturn(inv.maxDrawdownPct) + "</td>";
+    html += '<td class="px-3 py-2 text-right text-sm">' + formatRatio(inv.sharpe) + "</td>";
+    if (showBeta) html += '<td class="px-3 py-2 text-right text-sm">' + formatRatio(inv.beta) + "</td>";
     html += "</tr>";
   }
 
   html += "</tbody></table>";
+  html += '<p class="mt-3 text-xs text-brand-500">' + escapeHtml(describeRiskContext(data.risk, data.riskPeriod ? data.riskPeriod.label : "")) + "</p>";
   const container = document.getElementById("comparison-table-container");
   container.innerHTML = html;
 
@@ -689,7 +784,7 @@ async function loadLeagueTable() {
   const container = document.getElementById("league-table-container");
   container.innerHTML = '<p class="text-brand-500">Loading league table...</p>';
 
-  const result = await apiRequest("/api/analysis/league-table?period=" + activePeriod + filterParams());
+  const result = await apiRequest("/api/analysis/league-table?period=" + activePeriod + betaBenchmarkParam() + filterParams());
   if (!result.ok) {
     container.innerHTML = '<p class="text-error">' + escapeHtml(result.error) + "</p>";
     return;
@@ -744,6 +839,13 @@ function renderLeagueTable(data) {
       val = a.description.localeCompare(b.description);
     } else if (leagueSort === "type") {
       val = (a.typeShort || "").localeCompare(b.typeShort || "");
+    } else if (leagueSort === "sharpe" || leagueSort === "drawdown") {
+      // Missing figures go to the bottom whichever way round
+      const key = leagueSort === "sharpe" ? "sharpe" : "maxDrawdownPct";
+      if (a[key] === null && b[key] === null) return 0;
+      if (a[key] === null) return 1;
+      if (b[key] === null) return -1;
+      val = a[key] - b[key];
     } else {
       val = 0;
     }
@@ -765,6 +867,15 @@ function renderLeagueTable(data) {
   html += '<th class="px-3 py-2 text-left w-16">Type</th>';
   html += '<th class="px-3 py-2 text-center w-32">Trend</th>';
   html += '<th class="px-3 py-2 text-right w-24">Return</th>';
+  const showBeta = data.risk && data.risk.benchmark;
+  html += '<th class="px-3 py-2 text-right w-20">Max DD</th>';
+  html += '<th class="px-3 py-2 text-right w-20">Recovery</th>';
+  html += '<th class="px-3 py-2 text-right w-16">Sharpe</th>';
+  html += '<th class="px-3 py-2 text-right w-16">Sortino</th>';
+  if (showBeta) {
+    html += '<th class="px-3 py-2 text-right w-16">Beta</th>';
+    html += '<th class="px-3 py-2 text-right w-16">Corr.</th>';
+  }
   html += "</tr></thead><tbody>";
 
   // Benchmark rows at top (grey background, no sparkline)
@@ -779,6 +890,11 @@ function renderLeagueTable(data) {
     html += '<td class="px-3 py-2 text-sm text-brand-400">Benchmark</td>';
     html += '<td class="px-3 py-2 text-center"></td>';
     html += '<td class="px-3 py-2 text-right font-medium ' + bmReturnClass + '">' + bmReturnSign + bmItem.returnPct.toFixed(2) + "%</td>";
+    html += '<td class="px-3 py-2 text-right text-sm ' + returnColourClass(bmItem.maxDrawdownPct) + '">' + formatReturn(bmItem.maxDrawdownPct) + "</td>";
+    html += '<td class="px-3 py-2 text-right text-sm text-brand-500">' + formatRecovery(bmItem) + "</td>";
+    html += '<td class="px-3 py-2 text-right text-sm">' + formatRatio(bmItem.sharpe) + "</td>";
+    html += '<td class="px-3 py-2 text-right text-sm">' + formatRatio(bmItem.sortino) + "</td>";
+    if (showBeta) html += '<td class="px-3 py-2"></td><td class="px-3 py-2"></td>';
     html += "</tr>";
   }
 
@@ -795,11 +911,20 @@ function renderLeagueTable(data) {
     html += '<td class="px-3 py-2 text-sm text-brand-500">' + escapeHtml(properCase(inv.typeShort)) + "</td>";
     html += '<td class="px-3 py-2 text-center"><canvas id="spark-' + inv.id + '" width="120" height="30" class="inline-block"></canvas></td>';
     html += '<td class="px-3 py-2 text-right font-medium ' + returnClass + '">' + returnSign + inv.returnPct.toFixed(2) + "%</td>";
+    html += '<td class="px-3 py-2 text-right text-sm ' + returnColourClass(inv.maxDrawdownPct) + '">' + formatReturn(inv.maxDrawdownPct) + "</td>";
+    html += '<td class="px-3 py-2 text-right text-sm text-brand-500">' + formatRecovery(inv) + "</td>";
+    html += '<td class="px-3 py-2 text-right text-sm">' + formatRatio(inv.sharpe) + "</td>";
+    html += '<td class="px-3 py-2 text-right text-sm">' + formatRatio(inv.sortino) + "</td>";
+    if (showBeta) {
+      html += '<td class="px-3 py-2 text-right text-sm">' + formatRatio(inv.beta) + "</td>";
+      html += '<td class="px-3 py-2 text-right text-sm">' + formatRatio(inv.correlation) + "</td>";
+    }
     html += "</tr>";
   }
 
   html += "</tbody></table>";
   document.getElementById("league-table-container").innerHTML = html;
+  document.getElementById("league-risk-note").textContent = describeRiskContext(data.risk);
 
   // Render sparklines after DOM update
   requestAnimationFrame(function () {
@@ -863,7 +988,7 @@ function renderSparkline(inv) {
 async function loadScatter() {
   document.getElementById("period-info").textContent = "Loading...";
 
-  const url = "/api/analysis/risk-return?period=" + activePeriod + benchmarksParam() + filterParams();
+  const url = "/api/analysis/risk-return?period=" + activePeriod + benchmarksParam() + betaBenchmarkParam() + filterParams();
   const result = await apiRequest(url);
   if (!result.ok) {
     document.getElementById("period-info").textContent = result.error;
@@ -890,7 +1015,7 @@ function renderScatter(data) {
   const ctx = canvas.getContext("2d");
 
   const points = data.investments.map(function (inv) {
-    return { x: inv.volatility, y: inv.returnPct, label: inv.description };
+    return { x: inv.volatility, y: inv.returnPct, label: inv.description, metrics: inv };
   });
 
   // Calculate medians for quadrant lines
@@ -918,7 +1043,7 @@ function renderScatter(data) {
     for (let b = 0; b < data.benchmarks.length; b++) {
       const bm = data.benchmarks[b];
       if (bm.volatility !== null) {
-        bmPoints.push({ x: bm.volatility, y: bm.returnPct, label: bm.description });
+        bmPoints.push({ x: bm.volatility, y: bm.returnPct, label: bm.description, metrics: bm });
       }
     }
     if (bmPoints.length > 0) {
@@ -947,6 +1072,12 @@ function renderScatter(data) {
               const pt = context.raw;
               return pt.label + ": Return " + pt.y.toFixed(2) + "%, Volatility " + pt.x.toFixed(2) + "%";
             },
+            afterLabel: function (context) {
+              const m = context.raw.metrics;
+              const lines = ["Max drawdown " + formatReturn(m.maxDrawdownPct) + ", recovery " + formatRecovery(m), "Sharpe " + formatRatio(m.sharpe) + ", Sortino " + formatRatio(m.sortino)];
+              if (m.beta !== undefined && m.beta !== null) lines.push("Beta " + formatRatio(m.beta) + ", correlation " + formatRatio(m.correlation));
+              return lines;
+            },
           },
         },
       },
@@ -1025,6 +1156,7 @@ function renderScatter(data) {
   if (hasBenchmarks) {
     legendHtml += '<span class="inline-flex items-center gap-1.5"><span class="inline-block" style="width:0;height:0;border-left:6px solid transparent;border-right:6px solid transparent;border-bottom:10px solid rgba(107,114,128,0.8)"></span>Benchmark</span>';
   }
+  legendHtml += '<span class="w-full text-xs text-brand-500">' + escapeHtml(describeRiskContext(data.risk)) + " Hover over a point for its drawdown and ratios.</span>";
   document.getElementById("scatter-legend").innerHTML = legendHtml;
 }
 
@@ -1403,6 +1535,592 @@ function renderPerformanceChart(canvasId, legendId, series, colours, bmSeries, i
   }
 }
 
+// ─── Correlation ─────────────────────────────────────────────────
+
+/**
+ * @description Fetch and render the correlation matrix for the selected period,
+ * filters and benchmarks.
+ */
+async function loadCorrelation() {
+  const container = document.getElementById("correlation-container");
+  container.innerHTML = '<p class="text-brand-500">Loading correlations...</p>';
+  document.getElementById("period-info").textContent = "Loading...";
+
+  const url = "/api/analysis/correlation?period=" + activePeriod + benchmarksParam() + filterParams();
+  const result = await apiRequest(url);
+  if (!result.ok) {
+    container.innerHTML = '<p class="text-error">' + escapeHtml(result.error) + "</p>";
+    document.getElementById("period-info").textContent = "";
+    return;
+  }
+
+  const data = result.data;
+  const invCount = data.series.filter(function (s) {
+    return s.kind === "investment";
+  }).length;
+  document.getElementById("period-info").textContent = data.periodLabel + " \u2014 " + invCount + " investments \u2014 weekly returns to " + data.asOf;
+  renderCorrelation(data);
+}
+
+/**
+ * @description Work out the background colour of a heatmap cell: white at zero,
+ * shading to red for negative and to blue for positive correlations.
+ * @param {number|null} value - The correlation, from -1 to +1
+ * @returns {string} CSS colour
+ */
+function correlationColour(value) {
+  if (value === null || value === undefined) return "#f3f4f6";
+  const strength = Math.min(1, Math.abs(value));
+  const end = value < 0 ? [220, 38, 38] : [37, 99, 235];
+  const mix = end.map(function (channel) {
+    return Math.round(255 + (channel - 255) * strength);
+  });
+  return "rgb(" + mix.join(",") + ")";
+}
+
+/**
+ * @description Render the correlation heatmap as a table, with numbered columns
+ * matching the numbered rows, and the most correlated pairs beneath.
+ * @param {Object} data - Correlation data from the API
+ */
+function renderCorrelation(data) {
+  const container = document.getElementById("correlation-container");
+  if (data.series.length < 2) {
+    container.innerHTML = '<p class="text-brand-500">Not enough price history to compare investments over this period. Try a longer period.</p>';
+    return;
+  }
+
+  let html = '<div class="overflow-x-auto"><table class="text-sm border-collapse">';
+  html += '<thead><tr class="bg-brand-100 text-brand-700">';
+  html += '<th class="px-3 py-2 text-left">Investment</th>';
+  for (let c = 0; c < data.series.length; c++) {
+    html += '<th class="px-1 py-2 text-center w-12" title="' + escapeHtml(data.series[c].label) + '">' + (c + 1) + "</th>";
+  }
+  html += "</tr></thead><tbody>";
+
+  for (let r = 0; r < data.series.length; r++) {
+    const s = data.series[r];
+    const nameClass = s.kind === "benchmark" ? "font-medium text-brand-600" : "text-brand-800";
+    html += '<tr class="border-b border-white">';
+    html += '<td class="px-3 py-1 whitespace-nowrap ' + nameClass + '">' + (r + 1) + ". " + escapeHtml(s.label) + "</td>";
+    for (let c = 0; c < data.series.length; c++) {
+      const value = data.matrix[r][c];
+      const textClass = value !== null && Math.abs(value) > 0.6 ? "text-white" : "text-brand-800";
+      const title = escapeHtml(s.label + " / " + data.series[c].label);
+      const text = r === c ? "" : value === null ? "\u2013" : value.toFixed(2);
+      html += '<td class="px-1 py-1 text-center text-xs ' + textClass + '" style="background-color: ' + correlationColour(value) + '" title="' + title + '">' + text + "</td>";
+    }
+    html += "</tr>";
+  }
+  html += "</tbody></table></div>";
+
+  const pairs = data.pairs.slice(0, 10);
+  if (pairs.length > 0) {
+    html += '<h3 class="text-lg font-medium text-brand-700 mt-6 mb-2">Most closely correlated holdings</h3>';
+    html += '<table class="text-sm"><tbody>';
+    for (let p = 0; p < pairs.length; p++) {
+      html += '<tr class="border-b border-brand-100">';
+      html += '<td class="px-3 py-1">' + escapeHtml(pairs[p].a) + " / " + escapeHtml(pairs[p].b) + "</td>";
+      html += '<td class="px-3 py-1 text-right font-medium">' + pairs[p].correlation.toFixed(2) + "</td>";
+      html += "</tr>";
+    }
+    html += "</tbody></table>";
+  }
+
+  html += '<p class="mt-3 text-xs text-brand-500">Correlation of weekly returns in GBP. Values near +1 move together, near 0 independently and near \u22121 in opposite directions. Pairs with fewer than eight weeks in common are left blank.</p>';
+  container.innerHTML = html;
+}
+
+// ─── Allocation ──────────────────────────────────────────────────
+
+/**
+ * @description Build the query parameter string for the allocation API calls.
+ * The holdings filter does not apply — allocation always values what is held.
+ * @returns {string} e.g. "&users=1&accountTypes=isa&cash=exclude"
+ */
+function allocationParams() {
+  let params = "";
+  if (selectedUserIds.length > 0 && selectedUserIds.length < allUsers.length) {
+    params += "&users=" + selectedUserIds.join(",");
+  }
+  if (accountTypeFilter) {
+    params += "&accountTypes=" + accountTypeFilter;
+  }
+  if (!document.getElementById("allocation-include-cash").checked) {
+    params += "&cash=exclude";
+  }
+  return params;
+}
+
+/**
+ * @description Set up the allocation view controls: the include cash checkbox
+ * reloads everything; the drift dimension and months reload the chart only.
+ */
+function setupAllocationControls() {
+  document.getElementById("allocation-include-cash").addEventListener("change", function () {
+    allocationData = null;
+    loadAllocation();
+  });
+  document.getElementById("allocation-dimension").addEventListener("change", loadAllocationHistory);
+  document.getElementById("allocation-months").addEventListener("change", loadAllocationHistory);
+}
+
+/**
+ * @description Format a GBP amount with thousands separators and no pence.
+ * @param {number} value - Amount in pounds
+ * @returns {string} Formatted string like "£12,345"
+ */
+function formatPounds(value) {
+  return "\u00a3" + Math.round(value).toLocaleString("en-GB");
+}
+
+/**
+ * @description Fetch and render the current allocation breakdown and the drift chart.
+ */
+async function loadAllocation() {
+  const container = document.getElementById("allocation-tables");
+
+  if (!allocationData) {
+    container.innerHTML = '<p class="text-brand-500">Loading allocation...</p>';
+    const result = await apiRequest("/api/analysis/allocation?" + allocationParams().substring(1));
+    if (!result.ok) {
+      container.innerHTML = '<p class="text-error">' + escapeHtml(result.error) + "</p>";
+      return;
+    }
+    allocationData = result.data;
+  }
+
+  renderAllocationTables(allocationData);
+  await loadAllocationHistory();
+}
+
+/**
+ * @description Render one allocation breakdown table with a proportional bar per row.
+ * @param {string} title - Table heading
+ * @param {Array<{label: string, value: number, percent: number}>} rows - Allocation rows, largest first
+ * @returns {string} HTML for the table
+ */
+function buildAllocationTable(title, rows) {
+  let html = "<div>";
+  html += '<h3 class="text-lg font-medium text-brand-700 mb-2">' + escapeHtml(title) + "</h3>";
+  if (rows.length === 0) {
+    html += '<p class="text-sm text-brand-500">Nothing held.</p></div>';
+    return html;
+  }
+
+  html += '<table class="w-full text-left border-collapse">';
+  html += "<tbody>";
+  for (let i = 0; i < rows.length; i++) {
+    const row = rows[i];
+    const colour = LINE_COLOURS[i % LINE_COLOURS.length];
+    html += '<tr class="border-b border-brand-100">';
+    html += '<td class="py-2 pr-2 text-sm">';
+    html += '<span class="inline-block w-3 h-3 rounded-sm mr-2 align-middle" style="background:' + colour + '"></span>';
+    html += escapeHtml(row.label) + "</td>";
+    html += '<td class="py-2 px-2 text-sm text-right text-brand-500 whitespace-nowrap">' + formatPounds(row.value) + "</td>";
+    html += '<td class="py-2 pl-2 text-sm text-right font-medium whitespace-nowrap">' + row.percent.toFixed(1) + "%</td>";
+    html += "</tr>";
+  }
+  html += "</tbody></table></div>";
+  return html;
+}
+
+/**
+ * @description Render the three allocation breakdown tables and the total.
+ * @param {Object} data - Allocation from /api/analysis/allocation
+ */
+function renderAllocationTables(data) {
+  document.getElementById("allocation-summary").textContent = "Total " + formatPounds(data.total) + " (investments " + formatPounds(data.investments) + ", cash " + formatPounds(data.cash) + ") at " + formatDateFull(data.valuation_date);
+
+  let html = buildAllocationTable("Investment Type", data.by_type);
+  html += buildAllocationTable("Currency", data.by_currency);
+  html += buildAllocationTable("Allocation Tag", data.by_tag);
+  document.getElementById("allocation-tables").innerHTML = html;
+}
+
+/**
+ * @description Fetch and render the month-end allocation drift for the selected dimension.
+ */
+async function loadAllocationHistory() {
+  const dimension = document.getElementById("allocation-dimension").value;
+  const months = document.getElementById("allocation-months").value;
+  const note = document.getElementById("allocation-drift-note");
+  note.textContent = "Loading...";
+
+  const result = await apiRequest("/api/analysis/allocation/history?dimension=" + dimension + "&months=" + months + allocationParams());
+  if (!result.ok) {
+    note.textContent = result.error;
+    return;
+  }
+
+  const missingCash = result.data.cash_available.some(function (available) { return !available; });
+  note.textContent = missingCash ? "Some month ends predate the cash history and show investments only." : "";
+  renderAllocationChart(result.data);
+}
+
+/**
+ * @description Render the allocation drift as a stacked area chart of percentages.
+ * @param {Object} data - History from /api/analysis/allocation/history
+ */
+function renderAllocationChart(data) {
+  const canvas = document.getElementById("allocation-chart");
+  const existingChart = Chart.getChart(canvas);
+  if (existingChart) existingChart.destroy();
+
+  const datasets = data.series.map(function (s, i) {
+    const colour = LINE_COLOURS[i % LINE_COLOURS.length];
+    return {
+      label: s.label,
+      data: s.percents,
+      borderColor: colour,
+      backgroundColor: colour.replace("rgb(", "rgba(").replace(")", ", 0.5)"),
+      borderWidth: 1,
+      fill: true,
+      pointRadius: 0,
+      pointHitRadius: 8,
+    };
+  });
+
+  new Chart(canvas.getContext("2d"), {
+    type: "line",
+    data: {
+      labels: data.dates.map(function (d) { return formatDateLabel(d, true); }),
+      datasets: datasets,
+    },
+    options: {
+      responsive: true,
+      maintainAspectRatio: false,
+      interaction: { mode: "index", intersect: false },
+      plugins: {
+        legend: { position: "bottom" },
+        tooltip: {
+          callbacks: {
+            title: function (items) {
+              return items.length > 0 ? formatDateFull(data.dates[items[0].dataIndex]) : "";
+            },
+            label: function (context) {
+              return context.dataset.label + ": " + context.parsed.y.toFixed(1) + "%";
+            },
+          },
+        },
+      },
+      scales: {
+        y: {
+          stacked: true,
+          min: 0,
+          max: 100,
+          ticks: { callback: function (value) { return value + "%"; } },
+        },
+      },
+    },
+  });
+}
+
+// ─── Rebalance ───────────────────────────────────────────────────
+
+/**
+ * @description Set up the Rebalance tab: person and account selects, the target
+ * editor buttons and the Plan button.
+ */
+function setupRebalanceControls() {
+  document.getElementById("rebalance-user").addEventListener("change", function () {
+    renderRebalanceAccountSelect();
+    loadRebalanceTargets();
+  });
+  document.getElementById("rebalance-account").addEventListener("change", loadRebalanceTargets);
+  document.getElementById("rebalance-basis").addEventListener("change", function () {
+    // Targets are all by investment or all by tag, so switching starts a fresh set
+    rebalanceTargets = [];
+    renderRebalanceTargets();
+  });
+  document.getElementById("rebalance-add-target-btn").addEventListener("click", function () {
+    rebalanceTargets.push({ investment_id: null, allocation_tag: "", target_percent: "" });
+    renderRebalanceTargets();
+  });
+  document.getElementById("rebalance-save-btn").addEventListener("click", saveRebalanceTargets);
+  document.getElementById("rebalance-plan-btn").addEventListener("click", loadRebalancePlan);
+}
+
+/**
+ * @description Fill the person select from the users with accounts, once.
+ */
+function renderRebalanceUserSelect() {
+  const select = document.getElementById("rebalance-user");
+  if (select.options.length > 0) return;
+
+  let html = "";
+  for (let i = 0; i < allUsers.length; i++) {
+    html += '<option value="' + allUsers[i].id + '">' + escapeHtml(allUsers[i].first_name + " " + allUsers[i].last_name) + "</option>";
+  }
+  select.innerHTML = html;
+  renderRebalanceAccountSelect();
+}
+
+/**
+ * @description Fill the account select with "All accounts" (the person's
+ * targets) and each of the selected person's accounts.
+ */
+function renderRebalanceAccountSelect() {
+  const userId = document.getElementById("rebalance-user").value;
+  const accounts = accountsByUser[userId] || [];
+
+  let html = '<option value="">All accounts</option>';
+  for (let i = 0; i < accounts.length; i++) {
+    html += '<option value="' + accounts[i].id + '">' + escapeHtml(accounts[i].account_type.toUpperCase() + " " + accounts[i].account_ref) + "</option>";
+  }
+  document.getElementById("rebalance-account").innerHTML = html;
+}
+
+/**
+ * @description Build the user and account query parameters for the rebalancing API.
+ * @returns {string} e.g. "userId=1&accountId=3"
+ */
+function rebalanceScopeParams() {
+  let params = "userId=" + document.getElementById("rebalance-user").value;
+  const accountId = document.getElementById("rebalance-account").value;
+  if (accountId) params += "&accountId=" + accountId;
+  return params;
+}
+
+/**
+ * @description Load the investments (once) and the saved targets for the
+ * selected person or account, then render the target editor.
+ */
+async function loadRebalanceTargets() {
+  renderRebalanceUserSelect();
+  document.getElementById("rebalance-result").innerHTML = "";
+  document.getElementById("rebalance-target-messages").innerHTML = "";
+  if (allUsers.length === 0) {
+    document.getElementById("rebalance-targets").innerHTML = '<p class="text-sm text-brand-500">No one has an account to rebalance.</p>';
+    return;
+  }
+
+  if (rebalanceInvestments.length === 0) {
+    const invResult = await apiRequest("/api/investments");
+    if (invResult.ok) rebalanceInvestments = invResult.data;
+  }
+
+  const result = await apiRequest("/api/rebalancing/targets?" + rebalanceScopeParams());
+  if (!result.ok) {
+    document.getElementById("rebalance-targets").innerHTML = '<p class="text-error">' + escapeHtml(result.error) + "</p>";
+    return;
+  }
+
+  rebalanceTargets = result.data.map(function (t) {
+    return { investment_id: t.investment_id, allocation_tag: t.allocation_tag || "", target_percent: t.target_percent };
+  });
+  if (rebalanceTargets.length > 0) {
+    document.getElementById("rebalance-basis").value = rebalanceTargets[0].investment_id ? "investment" : "tag";
+  }
+  renderRebalanceTargets();
+}
+
+/**
+ * @description Render the target editor rows: an investment or tag select, the
+ * target percentage and a remove button, with the running total.
+ */
+function renderRebalanceTargets() {
+  const basis = document.getElementById("rebalance-basis").value;
+  const container = document.getElementById("rebalance-targets");
+
+  if (rebalanceTargets.length === 0) {
+    container.innerHTML = '<p class="text-sm text-brand-500">No targets set. Click Add Target to start.</p>';
+    updateRebalanceTargetTotal();
+    return;
+  }
+
+  const tags = [];
+  for (let t = 0; t < rebalanceInvestments.length; t++) {
+    const tag = rebalanceInvestments[t].allocation_tag;
+    if (tag && tags.indexOf(tag) === -1) tags.push(tag);
+  }
+  tags.sort();
+
+  let html = '<table class="w-full text-left border-collapse"><tbody>';
+  for (let i = 0; i < rebalanceTargets.length; i++) {
+    const target = rebalanceTargets[i];
+    html += '<tr class="border-b border-brand-100">';
+    html += '<td class="py-1.5 pr-2">';
+    html += '<select class="rebalance-target-key w-full text-sm border border-brand-300 rounded px-2 py-1 bg-white" data-index="' + i + '">';
+    html += '<option value="">Select...</option>';
+    if (basis === "investment") {
+      for (let j = 0; j < rebalanceInvestments.length; j++) {
+        const inv = rebalanceInvestments[j];
+        const selected = inv.id === target.investment_id ? " selected" : "";
+        html += '<option value="' + inv.id + '"' + selected + ">" + escapeHtml(inv.description) + "</option>";
+      }
+    } else {
+      for (let k = 0; k < tags.length; k++) {
+        const selected = tags[k] === target.allocation_tag ? " selected" : "";
+        html += '<option value="' + escapeHtml(tags[k]) + '"' + selected + ">" + escapeHtml(tags[k]) + "</option>";
+      }
+    }
+    html += "</select></td>";
+    html += '<td class="py-1.5 px-2 w-28"><input type="number" min="0" max="100" step="0.5" class="rebalance-target-percent w-full text-sm border border-brand-300 rounded px-2 py-1 text-right" data-index="' + i + '" value="' + escapeHtml(String(target.target_percent)) + '" /></td>';
+    html += '<td class="py-1.5 pl-1 text-sm text-brand-500">%</td>';
+    html += '<td class="py-1.5 pl-2"><button type="button" class="rebalance-target-remove text-sm text-brand-500 hover:text-error" data-index="' + i + '" title="Remove">&times;</button></td>';
+    html += "</tr>";
+  }
+  html += "</tbody></table>";
+  container.innerHTML = html;
+
+  container.querySelectorAll(".rebalance-target-key").forEach(function (el) {
+    el.addEventListener("change", function () {
+      const target = rebalanceTargets[Number(this.getAttribute("data-index"))];
+      if (basis === "investment") {
+        target.investment_id = this.value ? Number(this.value) : null;
+      } else {
+        target.allocation_tag = this.value;
+      }
+    });
+  });
+  container.querySelectorAll(".rebalance-target-percent").forEach(function (el) {
+    el.addEventListener("input", function () {
+      rebalanceTargets[Number(this.getAttribute("data-index"))].target_percent = this.value;
+      updateRebalanceTargetTotal();
+    });
+  });
+  container.querySelectorAll(".rebalance-target-remove").forEach(function (el) {
+    el.addEventListener("click", function () {
+      rebalanceTargets.splice(Number(this.getAttribute("data-index")), 1);
+      renderRebalanceTargets();
+    });
+  });
+
+  updateRebalanceTargetTotal();
+}
+
+/**
+ * @description Show the total of the targets being edited; the rest is left in cash
+ * or in holdings without a target.
+ */
+function updateRebalanceTargetTotal() {
+  let total = 0;
+  for (let i = 0; i < rebalanceTargets.length; i++) {
+    total += Number(rebalanceTargets[i].target_percent) || 0;
+  }
+  const el = document.getElementById("rebalance-target-total");
+  el.textContent = "Total " + total.toFixed(1) + "%" + (total < 100 ? " (" + (100 - total).toFixed(1) + "% untargeted)" : "");
+  el.className = total > 100 ? "text-sm text-error" : "text-sm text-brand-500";
+}
+
+/**
+ * @description Save the edited targets for the selected person or account.
+ */
+async function saveRebalanceTargets() {
+  const basis = document.getElementById("rebalance-basis").value;
+  const accountId = document.getElementById("rebalance-account").value;
+  const body = {
+    user_id: Number(document.getElementById("rebalance-user").value),
+    account_id: accountId ? Number(accountId) : null,
+    targets: rebalanceTargets.map(function (t) {
+      return basis === "investment" ? { investment_id: t.investment_id, target_percent: Number(t.target_percent) } : { allocation_tag: t.allocation_tag, target_percent: Number(t.target_percent) };
+    }),
+  };
+
+  const result = await apiRequest("/api/rebalancing/targets", { method: "PUT", body: body });
+  if (!result.ok) {
+    showError("rebalance-target-messages", result.error, result.detail);
+    return;
+  }
+  showSuccess("rebalance-target-messages", "Targets saved");
+}
+
+/**
+ * @description Fetch and render the rebalancing plan for the selected person or account.
+ */
+async function loadRebalancePlan() {
+  const container = document.getElementById("rebalance-result");
+  container.innerHTML = '<p class="text-brand-500">Planning...</p>';
+
+  let url = "/api/rebalancing/plan?" + rebalanceScopeParams();
+  const tolerance = document.getElementById("rebalance-tolerance").value;
+  const minimumTrade = document.getElementById("rebalance-minimum-trade").value;
+  if (tolerance !== "") url += "&tolerance=" + encodeURIComponent(tolerance);
+  if (minimumTrade !== "") url += "&minimumTrade=" + encodeURIComponent(minimumTrade);
+
+  const result = await apiRequest(url);
+  if (!result.ok) {
+    container.innerHTML = '<p class="text-error">' + escapeHtml(result.error) + (result.detail ? " — " + escapeHtml(result.detail) : "") + "</p>";
+    return;
+  }
+  renderRebalancePlan(result.data);
+}
+
+/**
+ * @description Render the plan: each target against the current weight, then
+ * the proposed trades and any notes.
+ * @param {Object} plan - Plan from /api/rebalancing/plan
+ */
+function renderRebalancePlan(plan) {
+  const container = document.getElementById("rebalance-result");
+  if (plan.rows.length === 0) {
+    container.innerHTML = '<p class="text-sm text-brand-500">Save some targets first, then plan.</p>';
+    return;
+  }
+
+  let html = '<p class="text-sm text-brand-500 mb-2">Valued at ' + formatPounds(plan.total) + " on " + formatDateFull(plan.valuation_date) + ", tolerance &plusmn;" + plan.tolerance_percent + "%, minimum trade " + formatPounds(plan.minimum_trade) + ".</p>";
+
+  html += '<table class="w-full text-left border-collapse mb-6">';
+  html += '<thead><tr class="border-b-2 border-brand-200">';
+  html += '<th class="py-2 pr-2 text-sm font-semibold text-brand-700">Target</th>';
+  html += '<th class="py-2 px-2 text-sm font-semibold text-brand-700 text-right">Current</th>';
+  html += '<th class="py-2 px-2 text-sm font-semibold text-brand-700 text-right">Current %</th>';
+  html += '<th class="py-2 px-2 text-sm font-semibold text-brand-700 text-right">Target %</th>';
+  html += '<th class="py-2 px-2 text-sm font-semibold text-brand-700 text-right">Drift</th>';
+  html += '<th class="py-2 pl-2 text-sm font-semibold text-brand-700">Status</th>';
+  html += "</tr></thead><tbody>";
+  for (let i = 0; i < plan.rows.length; i++) {
+    const row = plan.rows[i];
+    const sign = row.drift_percent > 0 ? "+" : "";
+    let status = row.in_band ? '<span class="text-green-700">Within tolerance</span>' : row.drift_percent > 0 ? '<span class="text-amber-700">Overweight</span>' : '<span class="text-amber-700">Underweight</span>';
+    if (row.unfunded > 0) status += ' <span class="text-xs text-error">(' + formatPounds(row.unfunded) + " unfunded)</span>";
+    html += '<tr class="border-b border-brand-100">';
+    html += '<td class="py-2 pr-2 text-sm">' + escapeHtml(row.label) + "</td>";
+    html += '<td class="py-2 px-2 text-sm text-right">' + formatPounds(row.current_value) + "</td>";
+    html += '<td class="py-2 px-2 text-sm text-right">' + row.current_percent.toFixed(1) + "%</td>";
+    html += '<td class="py-2 px-2 text-sm text-right">' + row.target_percent.toFixed(1) + "%</td>";
+    html += '<td class="py-2 px-2 text-sm text-right">' + sign + row.drift_percent.toFixed(1) + "%</td>";
+    html += '<td class="py-2 pl-2 text-sm">' + status + "</td>";
+    html += "</tr>";
+  }
+  html += "</tbody></table>";
+
+  html += '<h3 class="text-lg font-medium text-brand-700 mb-2">Proposed Trades</h3>';
+  if (plan.trades.length === 0) {
+    html += '<p class="text-sm text-brand-500 mb-4">No trades needed.</p>';
+  } else {
+    html += '<table class="w-full text-left border-collapse mb-4">';
+    html += '<thead><tr class="border-b-2 border-brand-200">';
+    html += '<th class="py-2 pr-2 text-sm font-semibold text-brand-700">Action</th>';
+    html += '<th class="py-2 px-2 text-sm font-semibold text-brand-700">Account</th>';
+    html += '<th class="py-2 px-2 text-sm font-semibold text-brand-700">Investment</th>';
+    html += '<th class="py-2 px-2 text-sm font-semibold text-brand-700 text-right">Amount</th>';
+    html += '<th class="py-2 px-2 text-sm font-semibold text-brand-700 text-right">Approx. Units</th>';
+    html += '<th class="py-2 pl-2 text-sm font-semibold text-brand-700">CGT</th>';
+    html += "</tr></thead><tbody>";
+    for (let t = 0; t < plan.trades.length; t++) {
+      const trade = plan.trades[t];
+      const cgt = trade.cgt_exposed ? '<span class="text-amber-700">Est. gain ' + (trade.estimated_gain !== null ? formatPounds(trade.estimated_gain) : "unknown") + "</span>" : '<span class="text-brand-400">Sheltered</span>';
+      html += '<tr class="border-b border-brand-100">';
+      html += '<td class="py-2 pr-2 text-sm font-medium">' + (trade.action === "sell" ? "Sell" : "Buy") + "</td>";
+      html += '<td class="py-2 px-2 text-sm">' + escapeHtml(trade.account_type.toUpperCase() + " " + trade.account_ref) + "</td>";
+      html += '<td class="py-2 px-2 text-sm">' + escapeHtml(trade.description) + "</td>";
+      html += '<td class="py-2 px-2 text-sm text-right">' + formatPounds(trade.amount) + "</td>";
+      html += '<td class="py-2 px-2 text-sm text-right">' + (trade.quantity !== null ? trade.quantity.toLocaleString("en-GB", { maximumFractionDigits: 4 }) : "\u2014") + "</td>";
+      html += '<td class="py-2 pl-2 text-sm">' + (trade.action === "sell" ? cgt : "") + "</td>";
+      html += "</tr>";
+    }
+    html += "</tbody></table>";
+  }
+
+  for (let n = 0; n < plan.notes.length; n++) {
+    html += '<p class="text-sm text-amber-700">' + escapeHtml(plan.notes[n]) + "</p>";
+  }
+
+  container.innerHTML = html;
+}
+
 // ─── Print to PDF ────────────────────────────────────────────────
 
 /**
@@ -1419,6 +2137,8 @@ function setupPrintButton() {
       url = "/api/analysis/pdf/league-table?period=" + activePeriod + "&sort=" + leagueSort + "&dir=" + leagueSortDir + "&limit=" + leagueLimit + bm + fp;
     } else if (activeTab === "scatter") {
       url = "/api/analysis/pdf/risk-return?period=" + activePeriod + bm + fp;
+    } else if (activeTab === "correlation") {
+      url = "/api/analysis/pdf/correlation?period=" + activePeriod + bm + fp;
     } else {
       url = "/api/analysis/pdf/top-bottom?period=" + activePeriod + "&count=5" + bm + fp;
     }
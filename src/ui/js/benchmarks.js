@@ -195,7 +195,7 @@ async function checkUrlSiteMatch(url) {
 
 /**
  * @description Populate the benchmark type <select> element with options.
- * @param {string} [selectedType=""] - The type to pre-select ('index' or 'price')
+ * @param {string} [selectedType=""] - The type to pre-select ('index', 'price' or 'composite')
  */
 function populateTypeDropdown(selectedType) {
   const select = document.getElementById("benchmark_type");
@@ -204,6 +204,7 @@ function populateTypeDropdown(selectedType) {
   const types = [
     { value: "index", label: "Index (e.g. FTSE 100, S&P 500)" },
     { value: "price", label: "Price (e.g. ETF tracking an index)" },
+    { value: "composite", label: "Composite (weighted blend of other benchmarks)" },
   ];
 
   for (const type of types) {
@@ -240,13 +241,16 @@ function populateCurrencyDropdown(selectedId) {
  * @description Handle benchmark type change to show/hide currency field.
  * When type is 'index', hide currency field entirely (always GBP).
  * When type is 'price', show the currency dropdown.
+ * When type is 'composite', hide currency (always GBP) and the URL and selector
+ * fields, and show the rebalance frequency and components instead.
  */
 function handleTypeChange() {
   const typeSelect = document.getElementById("benchmark_type");
   const currencySelect = document.getElementById("currencies_id");
   const currencyContainer = document.getElementById("currency-container");
+  const isComposite = typeSelect.value === "composite";
 
-  if (typeSelect.value === "index") {
+  if (typeSelect.value === "index" || isComposite) {
     // Hide currency field entirely and set to GBP
     if (gbpCurrencyId) {
       currencySelect.value = gbpCurrencyId;
@@ -256,16 +260,120 @@ function handleTypeChange() {
     // Show currency selection
     currencyContainer.classList.remove("hidden");
   }
+
+  for (const id of ["url-container", "site-container", "selector-container"]) {
+    document.getElementById(id).classList.toggle("hidden", isComposite);
+  }
+  document.getElementById("composite-container").classList.toggle("hidden", !isComposite);
+  if (isComposite && document.getElementById("components-rows").children.length === 0) {
+    renderComponentRows([{}, {}]);
+  }
+}
+
+/**
+ * @description Render the component rows of the composite benchmark form.
+ * Each row has a benchmark dropdown (index and price benchmarks only, never the
+ * benchmark being edited) and a weight.
+ * @param {Object[]} components - Components with component_benchmark_id and weight_percent
+ */
+function renderComponentRows(components) {
+  const rows = document.getElementById("components-rows");
+  rows.innerHTML = "";
+  for (const component of components) {
+    addComponentRow(component);
+  }
+  updateComponentsTotal();
+}
+
+/**
+ * @description Add one component row to the composite benchmark form.
+ * @param {Object} [component={}] - Component with component_benchmark_id and weight_percent
+ */
+function addComponentRow(component) {
+  const selected = component && component.component_benchmark_id ? Number(component.component_benchmark_id) : null;
+  const editingId = Number(document.getElementById("benchmark-id").value) || null;
+
+  const row = document.createElement("div");
+  row.className = "flex gap-2 items-center component-row";
+
+  let options = '<option value="">Select benchmark...</option>';
+  for (const bm of cachedBenchmarks) {
+    if (bm.benchmark_type === "composite" || bm.id === editingId) continue;
+    options += '<option value="' + bm.id + '"' + (bm.id === selected ? " selected" : "") + ">" + escapeHtml(bm.description) + "</option>";
+  }
+
+  row.innerHTML =
+    '<select class="component-benchmark flex-1 px-3 py-2 border border-brand-300 rounded-md text-base focus:outline-none focus:ring-2 focus:ring-brand-500 bg-white">' +
+    options +
+    "</select>" +
+    '<input type="number" class="component-weight w-24 px-3 py-2 border border-brand-300 rounded-md text-base text-right focus:outline-none focus:ring-2 focus:ring-brand-500" min="0" max="100" step="0.01" placeholder="%" value="' +
+    (component && component.weight_percent ? component.weight_percent : "") +
+    '" />' +
+    '<span class="text-sm text-brand-500">%</span>' +
+    '<button type="button" class="component-remove text-sm text-brand-400 hover:text-red-600 transition-colors px-1" title="Remove component">&times;</button>';
+
+  row.querySelector(".component-weight").addEventListener("input", updateComponentsTotal);
+  row.querySelector(".component-remove").addEventListener("click", function () {
+    row.remove();
+    updateComponentsTotal();
+  });
+
+  document.getElementById("components-rows").appendChild(row);
+}
+
+/**
+ * @description Read the component rows of the composite benchmark form.
+ * @returns {Array<{component_benchmark_id: number|null, weight_percent: number|null}>} The components entered
+ */
+function readComponentRows() {
+  const components = [];
+  for (const row of document.querySelectorAll("#components-rows .component-row")) {
+    const benchmarkValue = row.querySelector(".component-benchmark").value;
+    const weightValue = row.querySelector(".component-weight").value;
+    components.push({
+      component_benchmark_id: benchmarkValue ? Number(benchmarkValue) : null,
+      weight_percent: weightValue !== "" ? Number(weightValue) : null,
+    });
+  }
+  return components;
+}
+
+/**
+ * @description Show the running total of the component weights, highlighted
+ * when it is not 100%.
+ */
+function updateComponentsTotal() {
+  let total = 0;
+  for (const component of readComponentRows()) {
+    total += component.weight_percent || 0;
+  }
+  const totalEl = document.getElementById("components-total");
+  totalEl.textContent = "Total " + Math.round(total * 100) / 100 + "%";
+  totalEl.classList.toggle("text-error", Math.abs(total - 100) > 0.0001);
+}
+
+/**
+ * @description Describe a composite benchmark's components, e.g. "60% FTSE All-World, 40% UK Gilts".
+ * @param {Object[]} components - Components with description and weight_percent
+ * @returns {string} The components as text
+ */
+function describeComponents(components) {
+  return (components || [])
+    .map(function (c) {
+      return c.weight_percent + "% " + c.description;
+    })
+    .join(", ");
 }
 
 /**
  * @description Get the display name for a benchmark type.
- * @param {string} benchmarkType - The type ('index' or 'price')
+ * @param {string} benchmarkType - The type ('index', 'price' or 'composite')
  * @returns {string} Human-readable type name
  */
 function getTypeDisplayName(benchmarkType) {
   if (benchmarkType === "index") return "Index";
   if (benchmarkType === "price") return "Price";
+  if (benchmarkType === "composite") return "Composite";
   return benchmarkType || "";
 }
 
@@ -307,14 +415,17 @@ async function loadBenchmarks() {
     const bm = benchmarks[i];
     const rowClass = i % 2 === 0 ? "bg-white" : "bg-brand-50";
 
-    // Truncate URL for display if it's very long
-    const urlDisplay = bm.benchmark_url ? (bm.benchmark_url.length > 40 ? bm.benchmark_url.substring(0, 40) + "..." : bm.benchmark_url) : "";
+    // Truncate URL for display if it's very long — a composite shows its components instead
+    const urlText = bm.benchmark_type === "composite" ? describeComponents(bm.components) : bm.benchmark_url;
+    const urlDisplay = urlText ? (urlText.length > 40 ? urlText.substring(0, 40) + "..." : urlText) : "";
 
     // Truncate selector for display if it's very long
     const selectorDisplay = bm.selector ? (bm.selector.length > 30 ? bm.selector.substring(0, 30) + "..." : bm.selector) : "";
 
-    // For price type, show currency in parentheses after type
-    const typeDisplay = bm.benchmark_type === "price" ? getTypeDisplayName(bm.benchmark_type) + " (" + bm.currency_code + ")" : getTypeDisplayName(bm.benchmark_type);
+    // For price type, show currency in parentheses after type; for composite, the rebalance frequency
+    let typeDisplay = getTypeDisplayName(bm.benchmark_type);
+    if (bm.benchmark_type === "price") typeDisplay += " (" + bm.currency_code + ")";
+    if (bm.benchmark_type === "composite") typeDisplay += " (" + bm.rebalance_frequency + ")";
 
     html += '<tr data-id="' + bm.id + '" class="' + rowClass + ' border-b border-brand-100 hover:bg-brand-100 transition-colors cursor-pointer" ondblclick="viewBenchmark(' + bm.id + ')">';
     html += '<td class="py-3 px-3 text-base">' + escapeHtml(bm.description) + "</td>";
@@ -393,9 +504,19 @@ async function viewBenchmark(id) {
   document.getElementById("view-url").textContent = bm.benchmark_url || "—";
   document.getElementById("view-selector").textContent = bm.selector || "—";
 
+  // A composite shows its components and rebalance frequency in place of the URL and selector
+  const isComposite = bm.benchmark_type === "composite";
+  document.getElementById("view-composite-container").classList.toggle("hidden", !isComposite);
+  document.getElementById("view-url-container").classList.toggle("hidden", isComposite);
+  document.getElementById("view-selector-container").classList.toggle("hidden", isComposite);
+  if (isComposite) {
+    document.getElementById("view-type").textContent = typeDisplay + " — rebalanced " + bm.rebalance_frequency;
+    document.getElementById("view-components").textContent = describeComponents(bm.components);
+  }
+
   // Show/hide currency based on type
   const viewCurrencyContainer = document.getElementById("view-currency-container");
-  if (bm.benchmark_type === "index") {
+  if (bm.benchmark_type === "index" || isComposite) {
     viewCurrencyContainer.classList.add("hidden");
   } else {
     viewCurrencyContainer.classList.remove("hidden");
@@ -547,8 +668,10 @@ function showAddForm() {
   document.getElementById("delete-from-form-btn").classList.add("hidden");
   populateTypeDropdown();
   populateCurrencyDropdown();
+  document.getElementById("components-rows").innerHTML = "";
   // Show currency field by default (user hasn't selected type yet)
   document.getElementById("currency-container").classList.remove("hidden");
+  handleTypeChange();
   // Reset URL site status and site dropdown
   document.getElementById("url-site-status").classList.add("hidden");
   document.getElementById("selector-optional").classList.add("hidden");
@@ -582,6 +705,8 @@ async function editBenchmark(id) {
   populateCurrencyDropdown(bm.currencies_id);
   document.getElementById("benchmark_url").value = bm.benchmark_url || "";
   document.getElementById("selector").value = bm.selector || "";
+  document.getElementById("rebalance_frequency").value = bm.rebalance_frequency || "monthly";
+  renderComponentRows(bm.components || []);
   document.getElementById("form-errors").textContent = "";
 
   // Apply type-based currency restriction
@@ -642,6 +767,13 @@ async function handleFormSubmit(event) {
     selector: document.getElementById("selector").value.trim() || null,
   };
 
+  if (data.benchmark_type === "composite") {
+    data.benchmark_url = null;
+    data.selector = null;
+    data.rebalance_frequency = document.getElementById("rebalance_frequency").value;
+    data.components = readComponentRows();
+  }
+
   let result;
   if (isEditing) {
     result = await apiRequest("/api/benchmarks/" + benchmarkId, {
@@ -1011,6 +1143,10 @@ document.addEventListener("DOMContentLoaded", async function () {
 
   // Handle known site dropdown selection
   document.getElementById("site-select").addEventListener("change", handleSiteSelect);
+  document.getElementById("add-component-btn").addEventListener("click", function () {
+    addComponentRow({});
+    updateComponentsTotal();
+  });
 
   // Close modals when clicking on the backdrop (outside the modal content)
   document.getElementById("benchmark-form-container").addEventListener("click", function (event) {
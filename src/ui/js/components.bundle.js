@@ -1638,6 +1638,7 @@ class AppNavbar extends LitElement {
                   <a href="/pages/fetching.html" class="block px-4 py-2 hover:bg-brand-50 transition-colors" data-nav="fetching">Fetching</a>
                   <a href="/pages/portfolio.html?view=setup" class="block px-4 py-2 hover:bg-brand-50 transition-colors" data-nav="portfolio-setup">Portfolio Setup</a>
                   <a href="/pages/other-assets.html" class="block px-4 py-2 hover:bg-brand-50 transition-colors" data-nav="other-assets">Other Assets</a>
+                  <a href="/pages/gifts.html" class="block px-4 py-2 hover:bg-brand-50 transition-colors" data-nav="gifts">Gifts Register</a>
                   <hr class="my-1 border-brand-200" />
                   <a href="/pages/backup.html" class="block px-4 py-2 hover:bg-brand-50 transition-colors" data-nav="backup">Backup</a>
                 </div>
@@ -1748,12 +1749,12 @@ class AppNavbar extends LitElement {
   }
   _buildPdfUrl(endpoint, params, compareTo) {
     let url = endpoint;
-    let hasQuery = false;
+    let hasQuery = endpoint.indexOf("?") !== -1;
     if (params && params.length > 0) {
       const isDetail = endpoint.indexOf("portfolio-detail") !== -1;
       const separator = isDetail ? "|" : ",";
       const joined = params.join(separator);
-      url += "?params=" + encodeURIComponent(joined);
+      url += (hasQuery ? "&" : "?") + "params=" + encodeURIComponent(joined);
       hasQuery = true;
     }
     if (compareTo) {
@@ -44,6 +44,7 @@ class AppNavbar extends LitElement {
                   <a href="/pages/fetching.html" class="block px-4 py-2 hover:bg-brand-50 transition-colors" data-nav="fetching">Fetching</a>
                   <a href="/pages/portfolio.html?view=setup" class="block px-4 py-2 hover:bg-brand-50 transition-colors" data-nav="portfolio-setup">Portfolio Setup</a>
                   <a href="/pages/other-assets.html" class="block px-4 py-2 hover:bg-brand-50 transition-colors" data-nav="other-assets">Other Assets</a>
+                  <a href="/pages/gifts.html" class="block px-4 py-2 hover:bg-brand-50 transition-colors" data-nav="gifts">Gifts Register</a>
                   <hr class="my-1 border-brand-200" />
                   <a href="/pages/backup.html" class="block px-4 py-2 hover:bg-brand-50 transition-colors" data-nav="backup">Backup</a>
                 </div>
@@ -197,7 +198,8 @@ class AppNavbar extends LitElement {
    */
   _buildPdfUrl(endpoint, params, compareTo) {
     let url = endpoint;
-    let hasQuery = false;
+    // Some endpoints carry their own query (e.g. "?taxYear=2025/2026")
+    let hasQuery = endpoint.indexOf("?") !== -1;
 
     if (params && params.length > 0) {
       // Detail params contain colons and commas (e.g. "BW:ISA:1m,3m,1y,3y")
@@ -205,7 +207,7 @@ class AppNavbar extends LitElement {
       const isDetail = endpoint.indexOf("portfolio-detail") !== -1;
       const separator = isDetail ? "|" : ",";
       const joined = params.join(separator);
-      url += "?params=" + encodeURIComponent(joined);
+      url += (hasQuery ? "&" : "?") + "params=" + encodeURIComponent(joined);
       hasQuery = true;
     }
 
@@ -0,0 +1,477 @@
+/**
+ * @description Gifts Register page logic for Portfolio 60.
+ * Lists the gifts made by each family member with the exemption claimed and
+ * how much is a potentially exempt transfer (PET), shows where each PET stands
+ * in the seven years with its taper relief, and each donor's annual exemption
+ * left this tax year. Handles adding, editing and deleting gifts; gifts can
+ * also be recorded from a withdrawal on the portfolio page.
+ */
+
+/** @type {number|null} ID of the gift pending deletion */
+let deleteGiftId = null;
+
+/** @type {Object[]} Cached list of family members (not the Joint user) for the donor dropdown */
+let cachedDonors = [];
+
+/**
+ * @description Labels for where a gift stands against the seven years.
+ * @type {Object<string, string>}
+ */
+const STATUS_LABELS = {
+  exempt: "Exempt",
+  pet: "PET",
+  past_seven_years: "Over seven years",
+};
+
+/**
+ * @description Format an amount in pounds as a GBP currency string.
+ * @param {number} amount - The amount in pounds
+ * @returns {string} Formatted string like "£1,234.56" or "£1,234"
+ */
+function formatGBP(amount) {
+  if (amount === 0) return "£0";
+  const isWhole = Math.abs(amount - Math.round(amount)) < 0.005;
+  if (isWhole) {
+    return "£" + Math.round(amount).toLocaleString("en-GB");
+  }
+  return "£" + amount.toLocaleString("en-GB", { minimumFractionDigits: 2, maximumFractionDigits: 2 });
+}
+
+/**
+ * @description Format an ISO-8601 date string (YYYY-MM-DD) for display.
+ * Returns a human-readable UK date format (e.g. "5 Feb 2026").
+ * @param {string} dateStr - ISO-8601 date string
+ * @returns {string} Formatted date string
+ */
+function formatDisplayDate(dateStr) {
+  if (!dateStr) return "";
+  const parts = dateStr.split("-");
+  if (parts.length !== 3) return dateStr;
+
+  const months = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+  const year = parts[0];
+  const monthIndex = parseInt(parts[1], 10) - 1;
+  const day = parseInt(parts[2], 10);
+
+  if (monthIndex < 0 || monthIndex > 11) return dateStr;
+
+  return day + " " + months[monthIndex] + " " + year;
+}
+
+/**
+ * @description Load the family members who can make gifts. The Joint
+ * household user is left out.
+ */
+async function loadDonors() {
+  const result = await apiRequest("/api/users");
+  if (result.ok) {
+    cachedDonors = result.data.filter(function (user) {
+      return user.first_name !== "Joint";
+    });
+  }
+}
+
+/**
+ * @description Populate the donor dropdown with cached family members.
+ */
+function populateDonorDropdown() {
+  const select = document.getElementById("user_id");
+  select.innerHTML = '<option value="">Select donor...</option>';
+
+  for (const user of cachedDonors) {
+    const option = document.createElement("option");
+    option.value = user.id;
+    option.textContent = user.initials + " — " + user.first_name + " " + user.last_name;
+    select.appendChild(option);
+  }
+}
+
+/**
+ * @description Describe where a gift stands against the seven years.
+ * @param {Object} gift - Analysed gift from the summary
+ * @returns {string} e.g. "PET — 40% taper relief" or "Exempt"
+ */
+function describeStatus(gift) {
+  const label = STATUS_LABELS[gift.status] || gift.status;
+  if (gift.status === "pet") {
+    return gift.taper_relief > 0 ? label + " — " + gift.taper_relief + "% taper relief" : label + " — no taper relief yet";
+  }
+  return label;
+}
+
+/**
+ * @description Build the summary card for one donor: the annual exemption
+ * left this tax year and the PETs still within seven years.
+ * @param {Object} summary - Donor summary from /api/gifts/summary
+ * @returns {string} HTML markup
+ */
+function buildSummaryCard(summary) {
+  const exemption = summary.annual_exemption;
+  const totals = summary.totals;
+
+  let html = '<div class="bg-white border border-brand-200 rounded-lg p-4">';
+  html += '<h3 class="text-lg font-semibold text-brand-800 mb-2">' + escapeHtml(summary.user.first_name + " " + summary.user.last_name) + "</h3>";
+  html += '<dl class="grid grid-cols-2 gap-x-4 gap-y-1 text-base">';
+  html += '<dt class="text-brand-600">Annual exemption left ' + escapeHtml(exemption.tax_year) + "</dt>";
+  html += '<dd class="text-right font-mono tabular-nums">' + escapeHtml(formatGBP(exemption.remaining)) + "</dd>";
+  html += '<dt class="text-sm text-brand-400 col-span-2">' + escapeHtml(formatGBP(exemption.allowance) + " this year plus " + formatGBP(exemption.carried_forward) + " carried forward, " + formatGBP(exemption.used) + " used") + "</dt>";
+  html += '<dt class="text-brand-600">PETs within seven years</dt>';
+  html += '<dd class="text-right font-mono tabular-nums">' + escapeHtml(formatGBP(totals.pets_within_seven_years)) + "</dd>";
+  html += '<dt class="text-brand-600">Nil-rate band used</dt>';
+  html += '<dd class="text-right font-mono tabular-nums">' + escapeHtml(formatGBP(totals.nil_rate_band_used)) + "</dd>";
+  if (totals.tax_on_death > 0) {
+    html += '<dt class="text-brand-600">Tax on the gifts on death today</dt>';
+    html += '<dd class="text-right font-mono tabular-nums text-error">' + escapeHtml(formatGBP(totals.tax_on_death)) + "</dd>";
+  }
+  html += "</dl></div>";
+  return html;
+}
+
+/**
+ * @description Load the gifts summary and render the donor cards and the
+ * table of gifts, newest first.
+ */
+async function loadGifts() {
+  const summaryContainer = document.getElementById("gifts-summary-container");
+  const tableContainer = document.getElementById("gifts-table-container");
+
+  const result = await apiRequest("/api/gifts/summary");
+
+  if (!result.ok) {
+    summaryContainer.innerHTML = '<div class="bg-red-50 border border-red-300 text-error rounded-lg px-4 py-3">' +
+      '<p class="text-base font-semibold">Failed to load gifts</p>' +
+      '<p class="text-sm mt-1">' + escapeHtml(result.detail || result.error) + "</p></div>";
+    tableContainer.innerHTML = "";
+    return;
+  }
+
+  const summaries = result.data;
+  summaryContainer.innerHTML = summaries.map(buildSummaryCard).join("");
+
+  const gifts = [];
+  for (const summary of summaries) {
+    for (const gift of summary.gifts) {
+      gifts.push(gift);
+    }
+  }
+  gifts.sort(function (a, b) {
+    return a.gift_date < b.gift_date ? 1 : a.gift_date > b.gift_date ? -1 : b.id - a.id;
+  });
+
+  if (gifts.length === 0) {
+    tableContainer.innerHTML = '<p class="text-brand-500">No gifts yet. Click "Add Gift" to record one.</p>';
+    return;
+  }
+
+  let html = '<div class="overflow-x-auto">';
+  html += '<table class="w-full text-left border-collapse">';
+  html += "<thead>";
+  html += '<tr class="border-b-2 border-brand-200">';
+  html += '<th class="py-2 px-3 text-sm font-semibold text-brand-700">Date</th>';
+  html += '<th class="py-2 px-3 text-sm font-semibold text-brand-700">Donor</th>';
+  html += '<th class="py-2 px-3 text-sm font-semibold text-brand-700">Recipient</th>';
+  html += '<th class="py-2 px-3 text-sm font-semibold text-brand-700">Exemption</th>';
+  html += '<th class="py-2 px-3 text-sm font-semibold text-brand-700 text-right">Amount</th>';
+  html += '<th class="py-2 px-3 text-sm font-semibold text-brand-700 text-right">Exempt</th>';
+  html += '<th class="py-2 px-3 text-sm font-semibold text-brand-700 text-right">PET</th>';
+  html += '<th class="py-2 px-3 text-sm font-semibold text-brand-700">Seven years</th>';
+  html += '<th class="py-2 px-3 text-sm font-semibold text-brand-700">Notes</th>';
+  html += '<th class="py-2 px-3 text-sm font-semibold text-brand-700"></th>';
+  html += "</tr>";
+  html += "</thead><tbody>";
+
+  for (let i = 0; i < gifts.length; i++) {
+    const gift = gifts[i];
+    const rowClass = i % 2 === 0 ? "bg-white" : "bg-brand-50";
+
+    html += '<tr class="' + rowClass + ' border-b border-brand-100 hover:bg-brand-100 transition-colors cursor-pointer" ondblclick="editGift(' + gift.id + ')">';
+    html += '<td class="py-2 px-3 text-base align-baseline">' + escapeHtml(formatDisplayDate(gift.gift_date)) + "</td>";
+    html += '<td class="py-2 px-3 text-base align-baseline">' + escapeHtml(gift.initials) + "</td>";
+    html += '<td class="py-2 px-3 text-base align-baseline">' + escapeHtml(gift.recipient) + "</td>";
+    html += '<td class="py-2 px-3 text-base align-baseline">' + escapeHtml(gift.exemption_label);
+    if (gift.exemption_note) {
+      html += '<br><span class="text-xs text-brand-500">' + escapeHtml(gift.exemption_note) + "</span>";
+    }
+    html += "</td>";
+    html += '<td class="py-2 px-3 text-base text-right font-mono tabular-nums align-baseline">' + escapeHtml(formatGBP(gift.amount)) + "</td>";
+    html += '<td class="py-2 px-3 text-base text-right font-mono tabular-nums align-baseline">' + escapeHtml(formatGBP(gift.exempt_amount)) + "</td>";
+    html += '<td class="py-2 px-3 text-base text-right font-mono tabular-nums align-baseline">' + (gift.pet_amount > 0 ? escapeHtml(formatGBP(gift.pet_amount)) : "") + "</td>";
+    html += '<td class="py-2 px-3 text-base align-baseline">' + escapeHtml(describeStatus(gift));
+    if (gift.status === "pet") {
+      html += '<br><span class="text-xs text-brand-500">Outside the estate from ' + escapeHtml(formatDisplayDate(gift.becomes_exempt_on)) + "</span>";
+    }
+    html += "</td>";
+    html += '<td class="py-2 px-3 text-base align-baseline">' + escapeHtml(gift.notes || "");
+    if (gift.cash_transaction_id) {
+      html += ' <span class="text-xs text-brand-400" title="Recorded from a withdrawal">&#8617;</span>';
+    }
+    html += "</td>";
+    html += '<td class="py-2 px-3 text-base align-baseline">';
+    html += '<button class="bg-brand-100 hover:bg-brand-200 text-brand-700 text-sm font-medium px-3 py-1 rounded transition-colors" onclick="editGift(' + gift.id + ')">Edit</button>';
+    html += "</td>";
+    html += "</tr>";
+  }
+
+  html += "</tbody></table></div>";
+  tableContainer.innerHTML = html;
+}
+
+/**
+ * @description Set whether the date and amount can be edited. They are fixed
+ * for a gift recorded from a withdrawal.
+ * @param {boolean} fixed - True to make the date and amount read-only
+ */
+function setDateAndAmountFixed(fixed) {
+  for (const id of ["gift_date", "amount"]) {
+    const input = document.getElementById(id);
+    input.readOnly = fixed;
+    input.classList.toggle("bg-brand-50", fixed);
+  }
+}
+
+/**
+ * @description Show the add gift form modal.
+ */
+function showAddForm() {
+  document.getElementById("form-title").textContent = "Add Gift";
+  document.getElementById("gift-id").value = "";
+  document.getElementById("gift-transaction-id").value = "";
+  document.getElementById("gift-form").reset();
+  setDateAndAmountFixed(false);
+  document.getElementById("form-errors").textContent = "";
+  document.getElementById("delete-from-form-btn").classList.add("hidden");
+  document.getElementById("gift-link-note").classList.add("hidden");
+  populateDonorDropdown();
+  document.getElementById("gift-form-container").classList.remove("hidden");
+  setTimeout(function () {
+    document.getElementById("user_id").focus();
+  }, 50);
+}
+
+/**
+ * @description Show the form to record a withdrawal as a gift. The date and
+ * amount come from the withdrawal and the donor defaults to the account holder
+ * (a Joint account's withdrawal needs the donor choosing).
+ * @param {number} transactionId - The withdrawal's cash transaction ID
+ */
+async function showFromTransactionForm(transactionId) {
+  const txResult = await apiRequest("/api/cash-transactions/" + transactionId);
+  if (!txResult.ok) {
+    showError("page-messages", "Failed to load the withdrawal", txResult.detail || txResult.error);
+    return;
+  }
+
+  const tx = txResult.data;
+  if (tx.transaction_type !== "withdrawal") {
+    showError("page-messages", "Only a withdrawal can be recorded as a gift");
+    return;
+  }
+  if (tx.gift_id) {
+    await editGift(tx.gift_id);
+    return;
+  }
+
+  const accountResult = await apiRequest("/api/accounts/" + tx.account_id);
+
+  showAddForm();
+  document.getElementById("form-title").textContent = "Record Withdrawal as Gift";
+  document.getElementById("gift-transaction-id").value = tx.id;
+  document.getElementById("gift_date").value = tx.transaction_date;
+  document.getElementById("amount").value = tx.amount.toFixed(2);
+  document.getElementById("notes").value = tx.notes || "";
+  setDateAndAmountFixed(true);
+  document.getElementById("gift-link-note").classList.remove("hidden");
+
+  if (accountResult.ok && cachedDonors.some((user) => user.id === accountResult.data.user_id)) {
+    document.getElementById("user_id").value = accountResult.data.user_id;
+    setTimeout(function () {
+      document.getElementById("recipient").focus();
+    }, 50);
+  }
+}
+
+/**
+ * @description Load a gift's data into the form for editing.
+ * @param {number} id - The gift ID to edit
+ */
+async function editGift(id) {
+  const result = await apiRequest("/api/gifts/" + id);
+
+  if (!result.ok) {
+    showError("page-messages", "Failed to load gift for editing", result.detail || result.error);
+    return;
+  }
+
+  const gift = result.data;
+
+  populateDonorDropdown();
+
+  document.getElementById("form-title").textContent = "Edit Gift";
+  document.getElementById("gift-id").value = gift.id;
+  document.getElementById("gift-transaction-id").value = "";
+  setDateAndAmountFixed(false);
+  document.getElementById("user_id").value = gift.user_id;
+  document.getElementById("recipient").value = gift.recipient;
+  document.getElementById("gift_date").value = gift.gift_date;
+  document.getElementById("amount").value = gift.amount.toFixed(2);
+  document.getElementById("exemption_type").value = gift.exemption_type;
+  document.getElementById("notes").value = gift.notes || "";
+  document.getElementById("form-errors").textContent = "";
+
+  const linkNote = document.getElementById("gift-link-note");
+  if (gift.cash_transaction_id) {
+    linkNote.classList.remove("hidden");
+  } else {
+    linkNote.classList.add("hidden");
+  }
+
+  const deleteBtn = document.getElementById("delete-from-form-btn");
+  deleteBtn.classList.remove("hidden");
+  deleteBtn.onclick = function () {
+    confirmDeleteGift(gift.id, gift.recipient);
+  };
+
+  document.getElementById("gift-form-container").classList.remove("hidden");
+  setTimeout(function () {
+    document.getElementById("recipient").focus();
+  }, 50);
+}
+
+/**
+ * @description Hide the form modal.
+ */
+function hideForm() {
+  document.getElementById("gift-form-container").classList.add("hidden");
+}
+
+/**
+ * @description Handle form submission for creating or updating a gift.
+ * @param {Event} event - The form submit event
+ */
+async function handleFormSubmit(event) {
+  event.preventDefault();
+
+  const errorsDiv = document.getElementById("form-errors");
+  errorsDiv.textContent = "";
+
+  const giftId = document.getElementById("gift-id").value;
+  const transactionId = document.getElementById("gift-transaction-id").value;
+  const isEditing = giftId !== "";
+
+  const data = {
+    user_id: parseInt(document.getElementById("user_id").value, 10),
+    recipient: document.getElementById("recipient").value.trim(),
+    gift_date: document.getElementById("gift_date").value,
+    amount: parseFloat(document.getElementById("amount").value),
+    exemption_type: document.getElementById("exemption_type").value,
+    notes: document.getElementById("notes").value.trim() || null,
+  };
+
+  let result;
+  if (transactionId) {
+    // The date and amount are taken from the withdrawal by the server
+    result = await apiRequest("/api/gifts/from-transaction/" + transactionId, {
+      method: "POST",
+      body: { user_id: data.user_id, recipient: data.recipient, exemption_type: data.exemption_type, notes: data.notes },
+    });
+  } else if (isEditing) {
+    result = await apiRequest("/api/gifts/" + giftId, {
+      method: "PUT",
+      body: data,
+    });
+  } else {
+    result = await apiRequest("/api/gifts", {
+      method: "POST",
+      body: data,
+    });
+  }
+
+  if (result.ok) {
+    hideForm();
+    await loadGifts();
+    showSuccess("page-messages", isEditing ? "Gift updated successfully" : transactionId ? "Withdrawal recorded as a gift" : "Gift added successfully");
+  } else {
+    errorsDiv.textContent = result.detail || result.error;
+  }
+}
+
+/**
+ * @description Show the delete confirmation dialog.
+ * @param {number} id - The gift ID to delete
+ * @param {string} recipient - The recipient, for the confirmation message
+ */
+function confirmDeleteGift(id, recipient) {
+  deleteGiftId = id;
+  document.getElementById("delete-gift-desc").textContent = recipient;
+  document.getElementById("delete-dialog").classList.remove("hidden");
+}
+
+/**
+ * @description Hide the delete confirmation dialog.
+ */
+function hideDeleteDialog() {
+  deleteGiftId = null;
+  document.getElementById("delete-dialog").classList.add("hidden");
+}
+
+/**
+ * @description Execute the gift deletion after confirmation.
+ */
+async function executeDelete() {
+  if (!deleteGiftId) return;
+
+  const result = await apiRequest("/api/gifts/" + deleteGiftId, {
+    method: "DELETE",
+  });
+
+  hideDeleteDialog();
+  hideForm();
+
+  if (result.ok) {
+    await loadGifts();
+    showSuccess("page-messages", "Gift deleted successfully");
+  } else {
+    showError("page-messages", "Failed to delete gift", result.detail || result.error);
+  }
+}
+
+// Initialise the page
+document.addEventListener("DOMContentLoaded", async function () {
+  await loadDonors();
+  await loadGifts();
+
+  document.getElementById("add-gift-btn").addEventListener("click", showAddForm);
+  document.getElementById("cancel-btn").addEventListener("click", hideForm);
+  document.getElementById("gift-form").addEventListener("submit", handleFormSubmit);
+  document.getElementById("delete-cancel-btn").addEventListener("click", hideDeleteDialog);
+  document.getElementById("delete-confirm-btn").addEventListener("click", executeDelete);
+
+  // Opened from "Record as gift" on a withdrawal on the portfolio page
+  const params = new URLSearchParams(window.location.search);
+  if (params.get("transaction")) {
+    await showFromTransactionForm(Number(params.get("transaction")));
+  }
+
+  // Close modals when clicking on the backdrop
+  document.getElementById("gift-form-container").addEventListener("click", function (event) {
+    if (event.target === this) hideForm();
+  });
+
+  document.getElementById("delete-dialog").addEventListener("click", function (event) {
+    if (event.target === this) hideDeleteDialog();
+  });
+
+  // Close modals with Escape key
+  document.addEventListener("keydown", function (event) {
+    if (event.key === "Escape") {
+      const deleteDialog = document.getElementById("delete-dialog");
+      const formContainer = document.getElementById("gift-form-container");
+
+      if (!deleteDialog.classList.contains("hidden")) {
+        hideDeleteDialog();
+      } else if (!formContainer.classList.contains("hidden")) {
+        hideForm();
+      }
+    }
+  });
+});
@@ -40,6 +40,7 @@ async function checkDatabaseStatus() {
       }
     }, 3000);
     loadManualPriceAlert();
+    loadRevaluationAlert();
   } else {
     container.innerHTML =
       '<div class="bg-amber-50 border border-amber-300 text-warning rounded-lg px-5 py-5">' +
@@ -164,6 +165,56 @@ async function loadManualPriceAlert() {
   container.innerHTML = html;
 }
 
+/**
+ * @description Fetch the other assets whose revaluation date has passed and
+ * display an alert table on the home page if any exist. Only called when the
+ * database is ready.
+ */
+async function loadRevaluationAlert() {
+  const container = document.getElementById("revaluation-alert");
+  if (!container) return;
+
+  const result = await apiRequest("/api/other-assets/revaluations-due");
+
+  if (!result.ok || !result.data || result.data.length === 0) {
+    container.innerHTML = "";
+    return;
+  }
+
+  const assets = result.data;
+
+  let html = '<div class="bg-amber-50 border border-amber-300 rounded-lg p-4">';
+  html += '<h3 class="text-lg font-semibold text-amber-800 mb-3">Other Assets Due for Revaluation</h3>';
+  html += '<p class="text-sm text-amber-700 mb-3">These values have not been updated for longer than their revaluation interval. Check them and update them under Set Up &gt; Other Assets.</p>';
+  html += '<div class="overflow-x-auto">';
+  html += '<table class="w-full text-left border-collapse">';
+  html += "<thead>";
+  html += '<tr class="border-b-2 border-amber-200">';
+  html += '<th class="py-2 px-3 text-sm font-semibold text-brand-700">Description</th>';
+  html += '<th class="py-2 px-3 text-sm font-semibold text-brand-700">Category</th>';
+  html += '<th class="py-2 px-3 text-sm font-semibold text-brand-700">Last Updated</th>';
+  html += '<th class="py-2 px-3 text-sm font-semibold text-brand-700">Revalue Every</th>';
+  html += '<th class="py-2 px-3 text-sm font-semibold text-brand-700">Due</th>';
+  html += "</tr>";
+  html += "</thead><tbody>";
+
+  for (let i = 0; i < assets.length; i++) {
+    const asset = assets[i];
+    const rowClass = i % 2 === 0 ? "bg-white" : "bg-amber-50/50";
+
+    html += '<tr class="' + rowClass + ' border-b border-amber-100">';
+    html += '<td class="py-2 px-3 text-base">' + escapeHtml(asset.description) + "</td>";
+    html += '<td class="py-2 px-3 text-base capitalize">' + escapeHtml(asset.category) + "</td>";
+    html += '<td class="py-2 px-3 text-base">' + escapeHtml(asset.last_updated) + "</td>";
+    html += '<td class="py-2 px-3 text-base">' + escapeHtml(asset.revalue_months + " months") + "</td>";
+    html += '<td class="py-2 px-3 text-base">' + escapeHtml(asset.revalue_due_date) + "</td>";
+    html += "</tr>";
+  }
+
+  html += "</tbody></table></div></div>";
+  container.innerHTML = html;
+}
+
 // --- Screenshot thumbnails and lightbox ---
 
 /** @type {string[]} List of all thumbnail filenames from the server */
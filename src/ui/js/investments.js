@@ -317,6 +317,27 @@ function getTypeDisplayName(typeShort, typeDescription) {
   return typeDescription;
 }
 
+/**
+ * @description Fill the allocation tag suggestions with the distinct tags
+ * already used, so the same label is reused across investments.
+ */
+function populateAllocationTagOptions() {
+  const tags = [];
+  for (const inv of cachedInvestments) {
+    if (inv.allocation_tag && tags.indexOf(inv.allocation_tag) === -1) {
+      tags.push(inv.allocation_tag);
+    }
+  }
+  tags.sort();
+
+  const datalist = document.getElementById("allocation-tag-options");
+  datalist.innerHTML = tags
+    .map(function (tag) {
+      return '<option value="' + escapeHtml(tag) + '"></option>';
+    })
+    .join("");
+}
+
 /**
  * @description Load and display all investments in the table.
  */
@@ -332,6 +353,7 @@ async function loadInvestments() {
 
   const investments = result.data;
   cachedInvestments = investments;
+  populateAllocationTagOptions();
 
   if (investments.length === 0) {
     container.innerHTML = '<p class="text-brand-500">No investments yet. Click "Add Investment" to create one.</p>';
@@ -464,6 +486,8 @@ async function viewInvestment(id) {
   document.getElementById("view-type").textContent = typeDisplay;
   document.getElementById("view-currency").textContent = currencyDisplay;
   document.getElementById("view-public-id").textContent = inv.public_id || "—";
+  document.getElementById("view-unit-type").textContent = inv.unit_type === "income" ? "Income units (Inc)" : inv.unit_type === "accumulation" ? "Accumulation units (Acc)" : "—";
+  document.getElementById("view-allocation-tag").textContent = inv.allocation_tag || "—";
   document.getElementById("view-url").textContent = inv.investment_url || "—";
   document.getElementById("view-selector").textContent = inv.selector || "—";
 
@@ -655,6 +679,8 @@ async function editInvestment(id) {
   populateTypeDropdown(inv.investment_type_id);
   populateCurrencyDropdown(inv.currencies_id);
   document.getElementById("public_id").value = inv.public_id || "";
+  document.getElementById("unit_type").value = inv.unit_type || "";
+  document.getElementById("allocation_tag").value = inv.allocation_tag || "";
   document.getElementById("investment_url").value = inv.investment_url || "";
   document.getElementById("selector").value = inv.selector || "";
   document.getElementById("auto-fetch").checked = inv.auto_fetch !== 0;
@@ -722,6 +748,8 @@ async function handleFormSubmit(event) {
     investment_type_id: document.getElementById("investment_type_id").value || null,
     currencies_id: document.getElementById("currencies_id").value || null,
     public_id: document.getElementById("public_id").value.trim().toUpperCase() || null,
+    unit_type: document.getElementById("unit_type").value || null,
+    allocation_tag: document.getElementById("allocation_tag").value.trim() || null,
     investment_url: document.getElementById("investment_url").value.trim() || null,
     selector: document.getElementById("selector").value.trim() || null,
   };
@@ -1,8 +1,12 @@
 /**
  * @description Other Assets page logic for Portfolio 60.
  * Handles listing, adding, editing, and deleting other assets
- * (pensions, property, savings, alternative assets).
- * Assets are displayed grouped by category with section headers.
+ * (pensions, property, savings, alternative assets) and liabilities
+ * (mortgages, loans, credit balances), including a liability's repayment schedule.
+ * Assets are displayed grouped by category with section headers; values
+ * overdue for revaluation are flagged, and each asset's history shows a chart
+ * of its value over time.
+ * Also manages the index series (e.g. CPI) used for index-linked escalation.
  */
 
 /** @type {number|null} ID of the asset pending deletion */
@@ -11,9 +15,18 @@ let deleteAssetId = null;
 /** @type {string} Description of the asset pending deletion (for dialog) */
 let deleteAssetDesc = "";
 
+/** @type {number|null} ID of the asset pending closure */
+let closeAssetId = null;
+
 /** @type {Object[]} Cached list of users for the dropdown */
 let cachedUsers = [];
 
+/** @type {Object[]} Cached list of index series for the escalation dropdown */
+let cachedIndexSeries = [];
+
+/** @type {number|null} ID of the index series a CSV is being imported into */
+let importIndexId = null;
+
 /**
  * @description Category display labels, in the order they should appear.
  * @type {Array<{key: string, label: string}>}
@@ -23,6 +36,7 @@ const CATEGORY_ORDER = [
   { key: "property", label: "Property" },
   { key: "savings", label: "Savings" },
   { key: "alternative", label: "Alternative Assets" },
+  { key: "liability", label: "Liabilities" },
 ];
 
 /**
@@ -39,6 +53,15 @@ const FREQUENCY_LABELS = {
   annually: "Annually",
 };
 
+/**
+ * @description Labels for how a property value in the history was arrived at.
+ * @type {Object<string, string>}
+ */
+const VALUE_SOURCE_LABELS = {
+  surveyed: "Surveyed",
+  indexed: "Indexed",
+};
+
 /**
  * @description Format a scaled integer (× 10000) as a GBP currency string.
  * @param {number} scaledValue - The value × 10000
@@ -118,6 +141,115 @@ function populateUserDropdown() {
   }
 }
 
+/**
+ * @description Populate the escalation and house price index dropdowns with cached index series.
+ */
+function populateIndexDropdown() {
+  const selects = [
+    { id: "escalation_index_id", placeholder: "Select index..." },
+    { id: "valuation_index_id", placeholder: "None" },
+  ];
+
+  for (const item of selects) {
+    const select = document.getElementById(item.id);
+    select.innerHTML = '<option value="">' + item.placeholder + "</option>";
+
+    for (const series of cachedIndexSeries) {
+      const option = document.createElement("option");
+      option.value = series.id;
+      option.textContent = series.name;
+      select.appendChild(option);
+    }
+  }
+}
+
+/**
+ * @description Show the escalation fields that go with the chosen escalation type.
+ */
+function updateEscalationFields() {
+  const type = document.getElementById("escalation_type").value;
+  document.getElementById("escalation-fields").classList.toggle("hidden", type === "none");
+  document.getElementById("escalation-rate-group").classList.toggle("hidden", type !== "fixed");
+  document.getElementById("escalation-index-group").classList.toggle("hidden", type !== "index");
+}
+
+/**
+ * @description Show the interest rate and repayment inputs for a liability.
+ * A liability is always a balance owed, so the value type is set to "value"
+ * and recurring income cannot be chosen.
+ */
+function updateLiabilityFields() {
+  const isLiability = document.getElementById("category").value === "liability";
+  document.getElementById("liability-group").classList.toggle("hidden", !isLiability);
+
+  const recurringRadio = document.querySelector('input[name="value_type"][value="recurring"]');
+  recurringRadio.disabled = isLiability;
+  document.getElementById("value-type-recurring-label").classList.toggle("opacity-50", isLiability);
+  if (isLiability) {
+    document.querySelector('input[name="value_type"][value="value"]').checked = true;
+    document.getElementById("frequency-group").classList.add("hidden");
+    document.getElementById("escalation-group").classList.add("hidden");
+    document.getElementById("frequency").value = "";
+  }
+}
+
+/**
+ * @description Show the house price index input for a property, and the
+ * survey date once an index is chosen.
+ */
+function updatePropertyFields() {
+  const isProperty = document.getElementById("category").value === "property";
+  document.getElementById("property-index-group").classList.toggle("hidden", !isProperty);
+  if (!isProperty) {
+    document.getElementById("valuation_index_id").value = "";
+    document.getElementById("main_residence").checked = false;
+  }
+  document.getElementById("surveyed-date-group").classList.toggle("hidden", document.getElementById("valuation_index_id").value === "");
+}
+
+/**
+ * @description Describe how an indexed property is valued, for the assets table.
+ * @param {Object} asset - Asset with valuation_index_id, surveyed_value, surveyed_date and value_source
+ * @returns {string} e.g. "Indexed (SW HPI), surveyed £500,000.00 on 20 Jan 2024", or empty string
+ */
+function describeValuation(asset) {
+  if (!asset.valuation_index_id) return "";
+  const series = cachedIndexSeries.find(function (s) {
+    return s.id === asset.valuation_index_id;
+  });
+  const source = asset.value_source === "indexed" ? "Indexed" : "Surveyed";
+  return source + " (" + (series ? series.name : "Index") + "), surveyed " + formatGBP(asset.surveyed_value) + " on " + formatDisplayDate(asset.surveyed_date);
+}
+
+/**
+ * @description Describe a liability's loan terms for the assets table.
+ * @param {Object} asset - Asset with interest_rate and repayment
+ * @returns {string} Description such as "4.29%, £615/month", or empty string
+ */
+function describeLoanTerms(asset) {
+  const parts = [];
+  if (asset.interest_rate !== null) parts.push(asset.interest_rate + "%");
+  if (asset.repayment) parts.push(formatGBP(asset.repayment) + "/month");
+  return parts.join(", ");
+}
+
+/**
+ * @description Describe an asset's escalation rule for the assets table.
+ * @param {Object} asset - The asset object
+ * @returns {string} e.g. "+3% on 6 Apr" or "CPI on 6 Apr", or empty if the asset does not escalate
+ */
+function describeEscalation(asset) {
+  if (!asset.escalation_type || asset.escalation_type === "none" || !asset.escalation_anniversary) return "";
+  const onDate = formatDisplayDate("2000-" + asset.escalation_anniversary).replace(" 2000", "");
+  if (asset.escalation_type === "fixed") {
+    return "+" + asset.escalation_rate + "% on " + onDate;
+  }
+  const series = cachedIndexSeries.find(function (s) {
+    return s.id === asset.escalation_index_id;
+  });
+  return (series ? series.name : "Index") + " on " + onDate;
+}
+
 /**
  * @description Load and display all other assets in the table, grouped by category.
  */
@@ -197,11 +329,30 @@ async function loadAssets() {
       html += '<td class="py-2 px-3 text-base align-baseline">' + escapeHtml(getUserDisplay(asset)) + "</td>";
       html += '<td class="py-2 px-3 text-base align-baseline">' + escapeHtml(asset.description) + "</td>";
       html += '<td class="py-2 px-3 text-base text-right font-mono tabular-nums align-baseline">' + escapeHtml(formatGBP(asset.value)) + "</td>";
-      html += '<td class="py-2 px-3 text-base align-baseline">' + escapeHtml(asset.frequency ? (FREQUENCY_LABELS[asset.frequency] || asset.frequency) : "") + "</td>";
+      html += '<td class="py-2 px-3 text-base align-baseline">' + escapeHtml(asset.frequency ? (FREQUENCY_LABELS[asset.frequency] || asset.frequency) : "");
+      if (asset.category === "liability") {
+        html += '<span class="text-sm">' + escapeHtml(describeLoanTerms(asset)) + "</span>";
+        if (asset.repayment) {
+          html += '<br><button class="text-xs text-brand-600 hover:text-brand-800 hover:underline transition-colors" onclick="showSchedule(' + asset.id + ", '" + escapeHtml(asset.description) + "'" + ')">Schedule</button>';
+        }
+      }
+      const escalation = describeEscalation(asset);
+      if (escalation) {
+        html += '<br><span class="text-xs text-brand-500">' + escapeHtml(escalation) + "</span>";
+      }
+      const valuation = describeValuation(asset);
+      if (valuation) {
+        html += '<span class="text-xs text-brand-500">' + escapeHtml(valuation) + "</span>";
+      }
+      html += "</td>";
       html += '<td class="py-2 px-3 text-base align-baseline">';
       html += '<button class="text-brand-600 hover:text-brand-800 hover:underline transition-colors" onclick="showHistory(' + asset.id + ", '" + escapeHtml(asset.description) + "'" + ')">';
       html += escapeHtml(formatDisplayDate(asset.last_updated));
-      html += "</button></td>";
+      html += "</button>";
+      if (asset.revalue_overdue) {
+        html += '<br><span class="text-xs text-error" title="Revalue every ' + asset.revalue_months + ' months">Revaluation due</span>';
+      }
+      html += "</td>";
 
       // Notes column with executor reference as tooltip icon
       html += '<td class="py-2 px-3 text-base align-baseline">';
@@ -236,8 +387,14 @@ function showAddForm() {
   document.getElementById("asset-form").reset();
   document.getElementById("form-errors").textContent = "";
   document.getElementById("delete-from-form-btn").classList.add("hidden");
+  document.getElementById("close-from-form-btn").classList.add("hidden");
   document.getElementById("frequency-group").classList.add("hidden");
+  document.getElementById("escalation-group").classList.add("hidden");
   populateUserDropdown();
+  populateIndexDropdown();
+  updateEscalationFields();
+  updateLiabilityFields();
+  updatePropertyFields();
   document.getElementById("asset-form-container").classList.remove("hidden");
   setTimeout(function () {
     document.getElementById("user_id").focus();
@@ -259,6 +416,7 @@ async function editAsset(id) {
   const asset = result.data;
 
   populateUserDropdown();
+  populateIndexDropdown();
 
   document.getElementById("form-title").textContent = "Edit Asset";
   document.getElementById("asset-id").value = asset.id;
@@ -272,17 +430,36 @@ async function editAsset(id) {
     radio.checked = radio.value === asset.value_type;
   }
 
-  // Show/hide frequency
+  // Show/hide frequency and escalation
   if (asset.value_type === "recurring") {
     document.getElementById("frequency-group").classList.remove("hidden");
+    document.getElementById("escalation-group").classList.remove("hidden");
     document.getElementById("frequency").value = asset.frequency || "";
   } else {
     document.getElementById("frequency-group").classList.add("hidden");
+    document.getElementById("escalation-group").classList.add("hidden");
     document.getElementById("frequency").value = "";
   }
 
-  // Convert scaled value to pounds.pence for the input
-  document.getElementById("value").value = (asset.value / 10000).toFixed(2);
+  document.getElementById("escalation_type").value = asset.escalation_type || "none";
+  document.getElementById("escalation_rate").value = asset.escalation_rate !== null ? asset.escalation_rate : "";
+  document.getElementById("escalation_index_id").value = asset.escalation_index_id || "";
+  document.getElementById("escalation_anniversary").value = asset.escalation_anniversary || "";
+  updateEscalationFields();
+
+  document.getElementById("revalue_months").value = asset.revalue_months || "";
+  document.getElementById("start_date").value = asset.start_date || "";
+  document.getElementById("interest_rate").value = asset.interest_rate !== null ? asset.interest_rate : "";
+  document.getElementById("repayment").value = asset.repayment !== null ? (asset.repayment / 10000).toFixed(2) : "";
+  updateLiabilityFields();
+
+  document.getElementById("valuation_index_id").value = asset.valuation_index_id || "";
+  document.getElementById("surveyed_date").value = asset.surveyed_date || "";
+  document.getElementById("main_residence").checked = asset.main_residence === 1;
+  updatePropertyFields();
+
+  // Convert scaled value to pounds.pence for the input — the surveyed value for an indexed property
+  document.getElementById("value").value = ((asset.valuation_index_id ? asset.surveyed_value : asset.value) / 10000).toFixed(2);
   document.getElementById("notes").value = asset.notes || "";
   document.getElementById("executor_reference").value = asset.executor_reference || "";
   document.getElementById("form-errors").textContent = "";
@@ -293,6 +470,12 @@ async function editAsset(id) {
     confirmDeleteAsset(asset.id, asset.description);
   };
 
+  const closeBtn = document.getElementById("close-from-form-btn");
+  closeBtn.classList.remove("hidden");
+  closeBtn.onclick = function () {
+    confirmCloseAsset(asset.id, asset.description);
+  };
+
   document.getElementById("asset-form-container").classList.remove("hidden");
   setTimeout(function () {
     document.getElementById("user_id").focus();
@@ -321,6 +504,12 @@ async function handleFormSubmit(event) {
 
   // Get selected value_type radio
   const valueTypeRadio = document.querySelector('input[name="value_type"]:checked');
+  const isRecurring = valueTypeRadio && valueTypeRadio.value === "recurring";
+  const escalationType = isRecurring ? document.getElementById("escalation_type").value : "none";
+  const isLiability = document.getElementById("category").value === "liability";
+  const interestRate = document.getElementById("interest_rate").value;
+  const repayment = document.getElementById("repayment").value;
+  const valuationIndexId = document.getElementById("category").value === "property" ? document.getElementById("valuation_index_id").value : "";
 
   const data = {
     user_id: parseInt(document.getElementById("user_id").value, 10),
@@ -331,6 +520,17 @@ async function handleFormSubmit(event) {
     value: Math.round(parseFloat(document.getElementById("value").value) * 10000),
     notes: document.getElementById("notes").value.trim() || null,
     executor_reference: document.getElementById("executor_reference").value.trim() || null,
+    escalation_type: escalationType,
+    escalation_rate: escalationType === "fixed" ? document.getElementById("escalation_rate").value : null,
+    escalation_index_id: escalationType === "index" ? document.getElementById("escalation_index_id").value : null,
+    escalation_anniversary: escalationType !== "none" ? document.getElementById("escalation_anniversary").value.trim() : null,
+    interest_rate: isLiability && interestRate !== "" ? interestRate : null,
+    repayment: isLiability && repayment !== "" ? Math.round(parseFloat(repayment) * 10000) : null,
+    revalue_months: document.getElementById("revalue_months").value !== "" ? Number(document.getElementById("revalue_months").value) : null,
+    valuation_index_id: valuationIndexId !== "" ? Number(valuationIndexId) : null,
+    surveyed_date: valuationIndexId !== "" ? document.getElementById("surveyed_date").value || null : null,
+    main_residence: document.getElementById("category").value === "property" && document.getElementById("main_residence").checked,
+    start_date: document.getElementById("start_date").value || null,
   };
 
   let result;
@@ -397,12 +597,93 @@ async function executeDelete() {
 }
 
 /**
- * @description Show the change history for an asset in a modal.
+ * @description Show the close dialog, with the closed date defaulting to today.
+ * @param {number} id - The asset ID to close
+ * @param {string} desc - The asset description for the dialog message
+ */
+function confirmCloseAsset(id, desc) {
+  closeAssetId = id;
+  document.getElementById("close-asset-desc").textContent = desc;
+  document.getElementById("closed_date").value = new Date().toISOString().slice(0, 10);
+  document.getElementById("close-dialog").classList.remove("hidden");
+}
+
+/**
+ * @description Hide the close dialog.
+ */
+function hideCloseDialog() {
+  closeAssetId = null;
+  document.getElementById("close-dialog").classList.add("hidden");
+}
+
+/**
+ * @description Close the asset on the chosen date after confirmation.
+ */
+async function executeClose() {
+  if (!closeAssetId) return;
+
+  const result = await apiRequest("/api/other-assets/" + closeAssetId + "/close", {
+    method: "POST",
+    body: { closed_date: document.getElementById("closed_date").value || null },
+  });
+
+  hideCloseDialog();
+  hideForm();
+
+  if (result.ok) {
+    await loadAssets();
+    showSuccess("page-messages", "Asset closed successfully");
+  } else {
+    showError("page-messages", "Failed to close asset", result.detail || result.error);
+  }
+}
+
+/**
+ * @description Build an inline SVG line chart of an asset's value at each
+ * month end, with the first and last values labelled.
+ * @param {string[]} dates - ISO-8601 dates, oldest first
+ * @param {number[]} values - Values in pounds, one per date
+ * @returns {string} SVG markup, or empty string with fewer than two dates
+ */
+function buildValueHistoryChart(dates, values) {
+  if (dates.length < 2) return "";
+  const width = 600;
+  const height = 160;
+  const pad = { left: 8, right: 8, top: 18, bottom: 18 };
+  let min = Math.min.apply(null, values);
+  let max = Math.max.apply(null, values);
+  if (max === min) {
+    max += 1;
+    min -= 1;
+  }
+
+  const points = values.map(function (value, i) {
+    const x = pad.left + (i * (width - pad.left - pad.right)) / (dates.length - 1);
+    const y = pad.top + ((max - value) * (height - pad.top - pad.bottom)) / (max - min);
+    return x.toFixed(1) + "," + y.toFixed(1);
+  });
+
+  let svg = '<svg viewBox="0 0 ' + width + " " + height + '" class="w-full mb-4" role="img" aria-label="Value over time">';
+  svg += '<polyline fill="none" stroke="#1e3a8a" stroke-width="2" points="' + points.join(" ") + '" />';
+  svg += '<text x="' + pad.left + '" y="12" font-size="11" fill="#475569">' + escapeHtml(formatGBP(Math.round(values[0] * 10000))) + "</text>";
+  svg += '<text x="' + (width - pad.right) + '" y="12" text-anchor="end" font-size="11" fill="#475569">' + escapeHtml(formatGBP(Math.round(values[values.length - 1] * 10000))) + "</text>";
+  svg += '<text x="' + pad.left + '" y="' + (height - 4) + '" font-size="11" fill="#475569">' + escapeHtml(formatDisplayDate(dates[0])) + "</text>";
+  svg += '<text x="' + (width - pad.right) + '" y="' + (height - 4) + '" text-anchor="end" font-size="11" fill="#475569">' + escapeHtml(formatDisplayDate(dates[dates.length - 1])) + "</text>";
+  svg += "</svg>";
+  return svg;
+}
+
+/**
+ * @description Show the change history for an asset in a modal, with a chart
+ * of its value at each month end over the last two years.
  * @param {number} id - The asset ID
  * @param {string} desc - The asset description for the title
  */
 async function showHistory(id, desc) {
-  const result = await apiRequest("/api/other-assets/" + id + "/history");
+  const [result, valueResult] = await Promise.all([
+    apiRequest("/api/other-assets/" + id + "/history"),
+    apiRequest("/api/other-assets/" + id + "/value-history?months=24"),
+  ]);
 
   document.getElementById("history-title").textContent = "History: " + desc;
 
@@ -422,13 +703,16 @@ async function showHistory(id, desc) {
     return;
   }
 
-  let html = '<table class="w-full text-left border-collapse">';
+  let html = valueResult.ok ? buildValueHistoryChart(valueResult.data.dates, valueResult.data.values) : "";
+  html += '<table class="w-full text-left border-collapse">';
   html += "<thead>";
   html += '<tr class="border-b-2 border-brand-200">';
   html += '<th class="py-2 px-2 text-sm font-semibold text-brand-700">Date</th>';
   html += '<th class="py-2 px-2 text-sm font-semibold text-brand-700 text-right">Value</th>';
+  html += '<th class="py-2 px-2 text-sm font-semibold text-brand-700">Source</th>';
   html += '<th class="py-2 px-2 text-sm font-semibold text-brand-700">Notes</th>';
   html += '<th class="py-2 px-2 text-sm font-semibold text-brand-700">Exec Ref</th>';
+  html += '<th class="py-2 px-2 text-sm font-semibold text-brand-700">Reason</th>';
   html += "</tr></thead><tbody>";
 
   for (let i = 0; i < history.length; i++) {
@@ -437,8 +721,63 @@ async function showHistory(id, desc) {
     html += '<tr class="' + rowClass + ' border-b border-brand-100">';
     html += '<td class="py-2 px-2 text-sm">' + escapeHtml(formatDisplayDate(h.change_date)) + "</td>";
     html += '<td class="py-2 px-2 text-sm text-right font-mono tabular-nums">' + escapeHtml(formatGBP(h.revised_value)) + "</td>";
+    html += '<td class="py-2 px-2 text-sm">' + escapeHtml(h.revised_value_source ? VALUE_SOURCE_LABELS[h.revised_value_source] : "") + "</td>";
     html += '<td class="py-2 px-2 text-sm">' + escapeHtml(h.revised_notes || "") + "</td>";
     html += '<td class="py-2 px-2 text-sm">' + escapeHtml(h.revised_executor_reference || "") + "</td>";
+    html += '<td class="py-2 px-2 text-sm text-brand-500">' + escapeHtml(h.reason || "") + "</td>";
+    html += "</tr>";
+  }
+
+  html += "</tbody></table>";
+  content.innerHTML = html;
+  document.getElementById("history-modal").classList.remove("hidden");
+}
+
+/**
+ * @description Show a liability's repayment schedule from today in the history modal.
+ * @param {number} id - The liability ID
+ * @param {string} desc - The liability description for the title
+ */
+async function showSchedule(id, desc) {
+  const result = await apiRequest("/api/other-assets/" + id + "/amortisation");
+
+  document.getElementById("history-title").textContent = "Repayment schedule: " + desc;
+  const content = document.getElementById("history-content");
+
+  if (!result.ok) {
+    content.innerHTML = '<p class="text-error">' + escapeHtml(result.detail || result.error) + "</p>";
+    document.getElementById("history-modal").classList.remove("hidden");
+    return;
+  }
+
+  const schedule = result.data;
+
+  if (schedule.never_repaid) {
+    content.innerHTML = '<p class="text-error">A repayment of ' + escapeHtml(formatGBP(schedule.repayment * 10000)) + " a month does not clear this balance within 50 years.</p>";
+    document.getElementById("history-modal").classList.remove("hidden");
+    return;
+  }
+
+  let html = '<p class="text-base text-brand-700 mb-3">Repaid by <strong>' + escapeHtml(formatDisplayDate(schedule.payoff_date)) + "</strong> after " + schedule.months + " payments, ";
+  html += "costing " + escapeHtml(formatGBP(Math.round(schedule.total_interest * 10000))) + " in interest.</p>";
+  html += '<table class="w-full text-left border-collapse">';
+  html += "<thead>";
+  html += '<tr class="border-b-2 border-brand-200">';
+  html += '<th class="py-2 px-2 text-sm font-semibold text-brand-700">Date</th>';
+  html += '<th class="py-2 px-2 text-sm font-semibold text-brand-700 text-right">Payment</th>';
+  html += '<th class="py-2 px-2 text-sm font-semibold text-brand-700 text-right">Interest</th>';
+  html += '<th class="py-2 px-2 text-sm font-semibold text-brand-700 text-right">Principal</th>';
+  html += '<th class="py-2 px-2 text-sm font-semibold text-brand-700 text-right">Balance</th>';
+  html += "</tr></thead><tbody>";
+
+  for (let i = 0; i < schedule.rows.length; i++) {
+    const row = schedule.rows[i];
+    const rowClass = i % 2 === 0 ? "bg-white" : "bg-brand-50";
+    html += '<tr class="' + rowClass + ' border-b border-brand-100">';
+    html += '<td class="py-1 px-2 text-sm">' + escapeHtml(formatDisplayDate(row.date)) + "</td>";
+    for (const amount of [row.payment, row.interest, row.principal, row.balance]) {
+      html += '<td class="py-1 px-2 text-sm text-right font-mono tabular-nums">' + escapeHtml(formatGBP(Math.round(amount * 10000))) + "</td>";
+    }
     html += "</tr>";
   }
 
@@ -454,9 +793,158 @@ function hideHistoryModal() {
   document.getElementById("history-modal").classList.add("hidden");
 }
 
+/**
+ * @description Load and display the index series used for escalation.
+ */
+async function loadIndexSeries() {
+  const container = document.getElementById("index-series-container");
+  const result = await apiRequest("/api/index-series");
+
+  if (!result.ok) {
+    container.innerHTML = '<p class="text-error">Failed to load index series.</p>';
+    return;
+  }
+
+  cachedIndexSeries = result.data;
+
+  if (cachedIndexSeries.length === 0) {
+    container.innerHTML = '<p class="text-brand-500">No index series yet. Click "Add Index" to create one, then import its values.</p>';
+    return;
+  }
+
+  let html = '<div class="overflow-x-auto"><table class="w-full text-left border-collapse">';
+  html += '<thead><tr class="border-b border-brand-200 bg-blue-50">';
+  html += '<th class="py-2 px-3 text-sm font-semibold text-brand-700">Name</th>';
+  html += '<th class="py-2 px-3 text-sm font-semibold text-brand-700">Description</th>';
+  html += '<th class="py-2 px-3 text-sm font-semibold text-brand-700 text-right">Values</th>';
+  html += '<th class="py-2 px-3 text-sm font-semibold text-brand-700">Covers</th>';
+  html += '<th class="py-2 px-3 text-sm font-semibold text-brand-700 text-right">Latest</th>';
+  html += '<th class="py-2 px-3 text-sm font-semibold text-brand-700"></th>';
+  html += "</tr></thead><tbody>";
+
+  for (let i = 0; i < cachedIndexSeries.length; i++) {
+    const series = cachedIndexSeries[i];
+    const rowClass = i % 2 === 0 ? "bg-white" : "bg-brand-50";
+    html += '<tr class="' + rowClass + ' border-b border-brand-100">';
+    html += '<td class="py-2 px-3 text-base">' + escapeHtml(series.name) + "</td>";
+    html += '<td class="py-2 px-3 text-base">' + escapeHtml(series.description || "");
+    if (series.region) {
+      html += '<br><span class="text-xs text-brand-500">Region: ' + escapeHtml(series.region) + "</span>";
+    }
+    html += "</td>";
+    html += '<td class="py-2 px-3 text-base text-right font-mono tabular-nums">' + series.value_count + "</td>";
+    html += '<td class="py-2 px-3 text-base">' + (series.first_date ? escapeHtml(formatDisplayDate(series.first_date) + " to " + formatDisplayDate(series.last_date)) : "") + "</td>";
+    html += '<td class="py-2 px-3 text-base text-right font-mono tabular-nums">' + (series.latest_value !== null ? escapeHtml(String(series.latest_value)) : "") + "</td>";
+    html += '<td class="py-2 px-3 text-base whitespace-nowrap">';
+    html += '<button class="bg-brand-100 hover:bg-brand-200 text-brand-700 text-sm font-medium px-3 py-1 rounded transition-colors" onclick="chooseIndexCsv(' + series.id + ')">Import CSV</button> ';
+    html += '<button class="text-sm text-brand-400 hover:text-red-600 transition-colors ml-2" onclick="deleteIndexSeries(' + series.id + ')">Delete</button>';
+    html += "</td></tr>";
+  }
+
+  html += "</tbody></table></div>";
+  container.innerHTML = html;
+}
+
+/**
+ * @description Show the add index series form modal.
+ */
+function showIndexForm() {
+  document.getElementById("index-form").reset();
+  document.getElementById("index-form-errors").textContent = "";
+  document.getElementById("index-form-container").classList.remove("hidden");
+  setTimeout(function () {
+    document.getElementById("index_name").focus();
+  }, 50);
+}
+
+/**
+ * @description Hide the add index series form modal.
+ */
+function hideIndexForm() {
+  document.getElementById("index-form-container").classList.add("hidden");
+}
+
+/**
+ * @description Handle the add index series form submission.
+ * @param {Event} event - The form submit event
+ */
+async function handleIndexFormSubmit(event) {
+  event.preventDefault();
+
+  const result = await apiRequest("/api/index-series", {
+    method: "POST",
+    body: {
+      name: document.getElementById("index_name").value.trim(),
+      description: document.getElementById("index_description").value.trim() || null,
+      region: document.getElementById("index_region").value.trim() || null,
+    },
+  });
+
+  if (result.ok) {
+    hideIndexForm();
+    await loadIndexSeries();
+    showSuccess("page-messages", "Index added — import its values from a CSV");
+  } else {
+    document.getElementById("index-form-errors").textContent = result.detail || result.error;
+  }
+}
+
+/**
+ * @description Open the file picker to import a CSV into an index series.
+ * @param {number} id - The index series ID
+ */
+function chooseIndexCsv(id) {
+  importIndexId = id;
+  const input = document.getElementById("index-csv-file");
+  input.value = "";
+  input.click();
+}
+
+/**
+ * @description Import the chosen CSV file into the selected index series.
+ */
+async function importIndexCsv() {
+  const file = document.getElementById("index-csv-file").files[0];
+  if (!file || !importIndexId) return;
+
+  const result = await apiRequest("/api/index-series/" + importIndexId + "/import", {
+    method: "POST",
+    body: { csv: await file.text() },
+  });
+
+  if (result.ok) {
+    await loadIndexSeries();
+    let message = result.data.imported + " index values imported (" + result.data.skipped + " rows skipped)";
+    if (result.data.indexed > 0) {
+      message += " — " + result.data.indexed + " property value(s) re-estimated";
+      await loadAssets();
+    }
+    showSuccess("page-messages", message);
+  } else {
+    showError("page-messages", "Failed to import index values", result.detail || result.error);
+  }
+}
+
+/**
+ * @description Delete an index series after confirmation.
+ * @param {number} id - The index series ID
+ */
+async function deleteIndexSeries(id) {
+  if (!confirm("Delete this index and all its values?")) return;
+
+  const result = await apiRequest("/api/index-series/" + id, { method: "DELETE" });
+  if (result.ok) {
+    await loadIndexSeries();
+    showSuccess("page-messages", "Index deleted");
+  } else {
+    showError("page-messages", "Failed to delete index", result.detail || result.error);
+  }
+}
+
 // Initialise the page
 document.addEventListener("DOMContentLoaded", async function () {
   await loadUsers();
+  await loadIndexSeries();
   await loadAssets();
 
   document.getElementById("add-asset-btn").addEventListener("click", showAddForm);
@@ -464,17 +952,30 @@ document.addEventListener("DOMContentLoaded", async function () {
   document.getElementById("asset-form").addEventListener("submit", handleFormSubmit);
   document.getElementById("delete-cancel-btn").addEventListener("click", hideDeleteDialog);
   document.getElementById("delete-confirm-btn").addEventListener("click", executeDelete);
+  document.getElementById("close-cancel-btn").addEventListener("click", hideCloseDialog);
+  document.getElementById("close-confirm-btn").addEventListener("click", executeClose);
   document.getElementById("history-close-btn").addEventListener("click", hideHistoryModal);
-
-  // Toggle frequency visibility based on value_type radio selection
+  document.getElementById("escalation_type").addEventListener("change", updateEscalationFields);
+  document.getElementById("category").addEventListener("change", updateLiabilityFields);
+  document.getElementById("category").addEventListener("change", updatePropertyFields);
+  document.getElementById("valuation_index_id").addEventListener("change", updatePropertyFields);
+  document.getElementById("add-index-btn").addEventListener("click", showIndexForm);
+  document.getElementById("index-cancel-btn").addEventListener("click", hideIndexForm);
+  document.getElementById("index-form").addEventListener("submit", handleIndexFormSubmit);
+  document.getElementById("index-csv-file").addEventListener("change", importIndexCsv);
+
+  // Toggle frequency and escalation visibility based on value_type radio selection
   const radios = document.querySelectorAll('input[name="value_type"]');
   for (const radio of radios) {
     radio.addEventListener("change", function () {
       const freqGroup = document.getElementById("frequency-group");
+      const escalationGroup = document.getElementById("escalation-group");
       if (this.value === "recurring") {
         freqGroup.classList.remove("hidden");
+        escalationGroup.classList.remove("hidden");
       } else {
         freqGroup.classList.add("hidden");
+        escalationGroup.classList.add("hidden");
         document.getElementById("frequency").value = "";
       }
     });
@@ -493,14 +994,21 @@ document.addEventListener("DOMContentLoaded", async function () {
     if (event.target === this) hideHistoryModal();
   });
 
+  document.getElementById("index-form-container").addEventListener("click", function (event) {
+    if (event.target === this) hideIndexForm();
+  });
+
   // Close modals with Escape key
   document.addEventListener("keydown", function (event) {
     if (event.key === "Escape") {
       const historyModal = document.getElementById("history-modal");
       const deleteDialog = document.getElementById("delete-dialog");
       const formContainer = document.getElementById("asset-form-container");
+      const indexFormContainer = document.getElementById("index-form-container");
 
-      if (!historyModal.classList.contains("hidden")) {
+      if (!indexFormContainer.classList.contains("hidden")) {
+        hideIndexForm();
+      } else if (!historyModal.classList.contains("hidden")) {
         hideHistoryModal();
       } else if (!deleteDialog.classList.contains("hidden")) {
         hideDeleteDialog();
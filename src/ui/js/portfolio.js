@@ -6,6 +6,9 @@
 /** @type {Array<Object>} Cached list of users */
 let users = [];
 
+/** @type {Array<{code: string, name: string}>} Cached list of allowed providers (for account dropdown) */
+let providers = [];
+
 /** @type {Array<Object>} Cached list of all investments (for holding dropdown) */
 let allInvestments = [];
 
@@ -210,6 +213,20 @@ function renderUserSummary(summary) {
     html += "onclick=\"showDetail('" + user.id + "', '" + acct.id + "')\">View</button>";
     html += "</td>";
     html += "</tr>";
+
+    // SIPPs show how much has been crystallised and the tax-free cash still available
+    if (acct.crystallisation) {
+      const c = acct.crystallisation;
+      html += '<tr class="' + rowClass + ' border-b border-brand-100">';
+      html += '<td class="pb-2 px-3"></td>';
+      html += '<td class="pb-2 px-3 text-xs text-brand-500" colspan="8">';
+      html += "Uncrystallised " + formatGBPWhole(c.uncrystallised_fund);
+      html += " &middot; Crystallised " + formatGBPWhole(c.crystallised_fund);
+      html += " &middot; Tax-free cash taken " + formatGBPWhole(c.tax_free_cash_taken);
+      html += " &middot; Tax-free cash available " + formatGBPWhole(c.tax_free_cash_available);
+      html += " &middot; Lump sum allowance remaining " + formatGBPWhole(c.lump_sum_allowance.remaining);
+      html += "</td></tr>";
+    }
   }
 
   // Totals row — show N/A for cash and grand total when any account lacks historic cash
@@ -857,6 +874,7 @@ async function loadAccounts() {
   html += "<thead>";
   html += '<tr class="border-b-2 border-brand-200">';
   html += '<th class="py-3 px-3 text-sm font-semibold text-brand-700">Account Type</th>';
+  html += '<th class="py-3 px-3 text-sm font-semibold text-brand-700">Provider</th>';
   html += '<th class="py-3 px-3 text-sm font-semibold text-brand-700">Account Reference</th>';
   html += '<th class="py-3 px-3 text-sm font-semibold text-brand-700 text-center">Holdings</th>';
   html += '<th class="py-3 px-3 text-sm font-semibold text-brand-700 text-right">Cash Balance</th>';
@@ -873,6 +891,7 @@ async function loadAccounts() {
 
     html += '<tr class="' + rowClass + ' border-b border-brand-100 hover:bg-brand-100 transition-colors">';
     html += '<td class="py-3 px-3 text-base font-medium">' + escapeHtml(formatAccountType(acct.account_type)) + "</td>";
+    html += '<td class="py-3 px-3 text-base">' + escapeHtml(getProviderDisplayName(acct.provider)) + "</td>";
     html += '<td class="py-3 px-3 text-base">' + escapeHtml(acct.account_ref) + "</td>";
     html += '<td class="py-3 px-3 text-base text-center">' + (acct.holdings_count || 0) + "</td>";
     html += '<td class="py-3 px-3 text-base text-right' + warnClass + '">' + escapeHtml(formatGBP(acct.cash_balance)) + "</td>";
@@ -889,7 +908,50 @@ async function loadAccounts() {
 }
 
 /**
- * @description Show the add account form. Only shows account types not already used.
+ * @description Load the list of allowed providers from the config API.
+ */
+async function loadProviders() {
+  const result = await apiRequest("/api/config/providers");
+  if (result.ok) {
+    providers = result.data;
+  }
+}
+
+/**
+ * @description Get the display name for a provider code.
+ * @param {string|null} code - The provider code
+ * @returns {string} The provider name, the code if not found, or "" if none
+ */
+function getProviderDisplayName(code) {
+  if (!code) return "";
+  const provider = providers.find(function (p) {
+    return p.code === code;
+  });
+  return provider ? provider.name : code.toUpperCase();
+}
+
+/**
+ * @description Populate the account provider dropdown.
+ * @param {string} [selectedCode=""] - The provider code to pre-select
+ */
+function populateAccountProviderDropdown(selectedCode) {
+  const select = document.getElementById("account-provider");
+  select.innerHTML = '<option value="">Select provider...</option>';
+
+  for (const provider of providers) {
+    const option = document.createElement("option");
+    option.value = provider.code;
+    option.textContent = provider.name + " (" + provider.code + ")";
+    if (selectedCode && provider.code === selectedCode) {
+      option.selected = true;
+    }
+    select.appendChild(option);
+  }
+}
+
+/**
+ * @description Show the add account form. The provider defaults to the
+ * selected user's provider; a user may hold several accounts of each type.
  */
 async function showAddAccountForm() {
   document.getElementById("account-form-title").textContent = "Add Account";
@@ -907,18 +969,25 @@ async function showAddAccountForm() {
   document.getElementById("edit-cash-balance-btn").classList.add("hidden");
   hideCashTxSubForm();
 
-  // Disable account types already in use
-  await filterAvailableAccountTypes();
-
   // Enable the type dropdown for new accounts
   document.getElementById("account-type").disabled = false;
 
-  // Reset ref dropdown
+  // Default the provider to the user's provider
+  const user = users.find(function (u) {
+    return u.id === selectedUserId;
+  });
+  populateAccountProviderDropdown(user && user.provider !== "-" ? user.provider : "");
+
+  // Reset ref suggestions
   populateAccountRefDropdown("");
 
-  // Hide drawdown section when adding a new account
+  // Hide drawdown, crystallisation, cash buffer and import sections when adding a new account
   document.getElementById("drawdown-section").classList.add("hidden");
   hideDrawdownForm();
+  document.getElementById("crystallisation-section").classList.add("hidden");
+  hideCrystallisationForm();
+  document.getElementById("cash-buffer-section").classList.add("hidden");
+  document.getElementById("broker-import-section").classList.add("hidden");
 
   document.getElementById("account-form-container").classList.remove("hidden");
   setTimeout(function () {
@@ -927,20 +996,25 @@ async function showAddAccountForm() {
 }
 
 /**
- * @description Populate the account reference dropdown based on the selected account type.
- * Uses the selected user's trading_ref, isa_ref, or sipp_ref field.
+ * @description Populate the account reference suggestions based on the selected account type.
+ * Suggests the selected user's trading_ref, isa_ref, or sipp_ref field, but any
+ * reference may be typed — e.g. for a second ISA held with another provider.
  * @param {string} accountType - The account type ('trading', 'isa', 'sipp') or empty
- * @param {string} [currentRef=""] - The current ref value to pre-select (for editing)
+ * @param {string} [currentRef=""] - The current ref value (for editing)
  */
 function populateAccountRefDropdown(accountType, currentRef) {
-  const select = document.getElementById("account-ref");
-  select.innerHTML = "";
+  const input = document.getElementById("account-ref");
+  const datalist = document.getElementById("account-ref-suggestions");
+  datalist.innerHTML = "";
 
   if (!accountType) {
-    select.innerHTML = '<option value="">Select account type first...</option>';
+    input.value = "";
+    input.placeholder = "Select account type first...";
     return;
   }
 
+  input.placeholder = "e.g. " + accountType.toUpperCase() + " account number";
+
   const user = users.find(function (u) {
     return u.id === selectedUserId;
   });
@@ -956,41 +1030,14 @@ function populateAccountRefDropdown(accountType, currentRef) {
   if (ref) {
     const option = document.createElement("option");
     option.value = ref;
-    option.textContent = ref;
-    option.selected = true;
-    select.appendChild(option);
-  } else {
-    select.innerHTML = '<option value="">No ' + accountType.toUpperCase() + " reference set for this user</option>";
+    datalist.appendChild(option);
   }
-}
-
-/**
- * @description Filter the account type dropdown to only show types not yet used.
- */
-async function filterAvailableAccountTypes() {
-  const select = document.getElementById("account-type");
-  const options = select.querySelectorAll("option");
-
-  // Re-enable all options first
-  options.forEach(function (opt) {
-    opt.disabled = false;
-    opt.classList.remove("text-brand-300");
-  });
-
-  // Get existing account types for this user
-  const result = await apiRequest("/api/users/" + selectedUserId + "/accounts");
-  if (!result.ok) return;
-
-  const existingTypes = result.data.map(function (a) {
-    return a.account_type;
-  });
 
-  options.forEach(function (opt) {
-    if (opt.value && existingTypes.includes(opt.value)) {
-      opt.disabled = true;
-      opt.classList.add("text-brand-300");
-    }
-  });
+  if (currentRef) {
+    input.value = currentRef;
+  } else if (ref) {
+    input.value = ref;
+  }
 }
 
 /**
@@ -1009,6 +1056,7 @@ async function editAccount(id) {
   document.getElementById("account-id").value = acct.id;
   document.getElementById("account-type").value = acct.account_type;
   document.getElementById("account-type").disabled = true; // Type cannot be changed
+  populateAccountProviderDropdown(acct.provider);
   populateAccountRefDropdown(acct.account_type, acct.account_ref);
   document.getElementById("cash-balance").value = acct.cash_balance;
   document.getElementById("cash-balance").readOnly = true;
@@ -1026,16 +1074,28 @@ async function editAccount(id) {
     confirmDelete("account", acct.id, formatAccountType(acct.account_type) + " account " + acct.account_ref);
   };
 
-  // Show drawdown section only for SIPP accounts
+  // Show drawdown, crystallisation and cash buffer sections only for SIPP accounts
   const drawdownSection = document.getElementById("drawdown-section");
+  const crystallisationSection = document.getElementById("crystallisation-section");
+  const cashBufferSection = document.getElementById("cash-buffer-section");
   if (acct.account_type === "sipp") {
     drawdownSection.classList.remove("hidden");
     hideDrawdownForm();
     loadDrawdownSchedules(acct.id);
+    crystallisationSection.classList.remove("hidden");
+    hideCrystallisationForm();
+    loadCrystallisations(acct.id);
+    cashBufferSection.classList.remove("hidden");
+    resetCashBuffer();
   } else {
     drawdownSection.classList.add("hidden");
+    crystallisationSection.classList.add("hidden");
+    cashBufferSection.classList.add("hidden");
   }
 
+  document.getElementById("broker-import-section").classList.remove("hidden");
+  resetBrokerImport();
+
   document.getElementById("account-form-container").classList.remove("hidden");
   setTimeout(function () {
     document.getElementById("account-ref").focus();
@@ -1066,6 +1126,10 @@ function showCashTxSubForm() {
   document.getElementById("cash-tx-replace-group").classList.add("hidden");
   document.getElementById("cash-tx-replace").checked = false;
   document.getElementById("cash-tx-amount-label").textContent = "Amount (£) *";
+  document.getElementById("cash-tx-investment-group").classList.add("hidden");
+  document.getElementById("cash-tx-pension-group").classList.add("hidden");
+  document.getElementById("cash-tx-contribution-type").value = "personal";
+  document.getElementById("cash-tx-relief-date").value = "";
 
   // Default date to today
   const today = new Date().toISOString().slice(0, 10);
@@ -1103,6 +1167,48 @@ function onCashTxTypeChange() {
     replaceCheckbox.checked = false;
     document.getElementById("cash-tx-amount-label").textContent = "Amount (£) *";
   }
+
+  const investmentGroup = document.getElementById("cash-tx-investment-group");
+  if (txType === "dividend" || txType === "interest") {
+    document.getElementById("cash-tx-investment-label").textContent = txType === "dividend" ? "Paid by *" : "Paid by (leave blank for cash interest)";
+    investmentGroup.classList.remove("hidden");
+    loadCashTxInvestmentOptions();
+  } else {
+    investmentGroup.classList.add("hidden");
+  }
+
+  // Pension contributions are entered gross; the net payment is derived on the server
+  const pensionGroup = document.getElementById("cash-tx-pension-group");
+  if (txType === "pension_contribution") {
+    pensionGroup.classList.remove("hidden");
+    document.getElementById("cash-tx-amount-label").textContent = "Gross Amount (£) *";
+  } else {
+    pensionGroup.classList.add("hidden");
+    if (txType !== "adjustment") {
+      document.getElementById("cash-tx-amount-label").textContent = "Amount (£) *";
+    }
+  }
+}
+
+/**
+ * @description Populate the "Paid by" dropdown with the account's current holdings
+ * so that dividend and interest payments can be linked to an investment.
+ */
+async function loadCashTxInvestmentOptions() {
+  const select = document.getElementById("cash-tx-investment");
+  const accountId = document.getElementById("account-id").value;
+  select.innerHTML = '<option value="">Select...</option>';
+  if (!accountId) return;
+
+  const result = await apiRequest("/api/accounts/" + accountId + "/holdings");
+  if (!result.ok) return;
+
+  for (const holding of result.data) {
+    const option = document.createElement("option");
+    option.value = holding.investment_id;
+    option.textContent = holding.investment_description;
+    select.appendChild(option);
+  }
 }
 
 /**
@@ -1158,6 +1264,32 @@ async function handleCashTxSubFormSave() {
     return;
   }
 
+  if (txType === "pension_contribution" || txType === "tax_relief") {
+    if (document.getElementById("account-type").value !== "sipp") {
+      errorsDiv.textContent = "Pension contributions and tax relief can only be recorded on a SIPP.";
+      return;
+    }
+  }
+
+  // Pension contributions have their own endpoint, which records the gross amount
+  // and, once received, the relief-at-source top-up
+  if (txType === "pension_contribution") {
+    const contributionType = document.getElementById("cash-tx-contribution-type").value;
+    const reliefDate = document.getElementById("cash-tx-relief-date").value;
+    const contributionResult = await apiRequest("/api/accounts/" + accountId + "/pension-contributions", {
+      method: "POST",
+      body: {
+        contribution_type: contributionType,
+        transaction_date: txDate,
+        gross_amount: rawAmount,
+        relief_date: contributionType === "personal" && reliefDate ? reliefDate : null,
+        notes: notes || null,
+      },
+    });
+    await afterCashTxSaved(accountId, contributionResult, errorsDiv);
+    return;
+  }
+
   // Build the request body
   const body = {
     transaction_type: txType,
@@ -1183,11 +1315,47 @@ async function handleCashTxSubFormSave() {
     body.direction = direction;
   }
 
-  const result = await apiRequest("/api/accounts/" + accountId + "/cash-transactions", {
+  // Income must be linked to the investment that paid it (optional for interest)
+  if (txType === "dividend" || txType === "interest") {
+    const investmentId = document.getElementById("cash-tx-investment").value;
+    if (txType === "dividend" && !investmentId) {
+      errorsDiv.textContent = "Please select the investment that paid the dividend.";
+      return;
+    }
+    if (investmentId) {
+      body.investment_id = Number(investmentId);
+    }
+  }
+
+  let result = await apiRequest("/api/accounts/" + accountId + "/cash-transactions", {
     method: "POST",
     body: body,
   });
 
+  // Deposit would take the owner over their ISA allowance (409) — confirm before recording
+  if (result.status === 409 && result.data && result.data.allowance) {
+    if (!confirm(result.detail + "\n\nRecord the deposit anyway?")) {
+      errorsDiv.textContent = result.detail;
+      return;
+    }
+    body.confirm_over_allowance = true;
+    result = await apiRequest("/api/accounts/" + accountId + "/cash-transactions", {
+      method: "POST",
+      body: body,
+    });
+  }
+
+  await afterCashTxSaved(accountId, result, errorsDiv);
+}
+
+/**
+ * @description Refresh the cash balance and close the sub-form after a cash
+ * transaction has been posted, or show the error returned by the server.
+ * @param {string} accountId - The account the transaction was recorded against
+ * @param {Object} result - The apiRequest result
+ * @param {HTMLElement} errorsDiv - Element for error messages
+ */
+async function afterCashTxSaved(accountId, result, errorsDiv) {
   if (result.ok) {
     // Refresh the account to get the updated cash balance
     const acctResult = await apiRequest("/api/accounts/" + accountId);
@@ -1228,6 +1396,7 @@ async function handleAccountSubmit(event) {
   const data = {
     account_type: document.getElementById("account-type").value,
     account_ref: document.getElementById("account-ref").value.trim(),
+    provider: document.getElementById("account-provider").value || null,
     cash_balance: Number(document.getElementById("cash-balance").value) || 0,
     warn_cash: Number(document.getElementById("warn-cash").value) || 0,
   };
@@ -2257,7 +2426,7 @@ async function loadCashTransactions(limit) {
       // Reverse this transaction's effect to get the balance before it
       const txType = displayTx[i].transaction_type;
       const isCreditAdjustment = txType === "adjustment" && displayTx[i].notes && displayTx[i].notes.startsWith("[Credit]");
-      if (txType === "deposit" || txType === "sell" || isCreditAdjustment) {
+      if (txType === "deposit" || txType === "sell" || txType === "dividend" || txType === "interest" || txType === "tax_relief" || isCreditAdjustment) {
         balance -= displayTx[i].amount;
       } else {
         balance += displayTx[i].amount;
@@ -2296,10 +2465,15 @@ async function loadCashTransactions(limit) {
     let typeLabel;
     if (tx.transaction_type === "adjustment") {
       typeLabel = isCreditAdj ? "Adjustment (credit)" : "Adjustment (debit)";
+    } else if (tx.transaction_type === "tax_relief") {
+      typeLabel = "Tax relief";
+    } else if (tx.contribution_type) {
+      typeLabel = tx.contribution_type === "employer" ? "Contribution (employer)" : "Contribution (personal)";
     } else {
       typeLabel = tx.transaction_type.charAt(0).toUpperCase() + tx.transaction_type.slice(1);
     }
-    const typeClass = tx.transaction_type === "deposit" || tx.transaction_type === "sell" || isCreditAdj ? "text-green-700" : tx.transaction_type === "withdrawal" || tx.transaction_type === "buy" ? "text-amber-700" : tx.transaction_type === "adjustment" ? "text-red-700" : "text-brand-600";
+    const isIncome = tx.transaction_type === "dividend" || tx.transaction_type === "interest";
+    const typeClass = tx.transaction_type === "deposit" || tx.transaction_type === "sell" || tx.transaction_type === "tax_relief" || isIncome || isCreditAdj ? "text-green-700" : tx.transaction_type === "withdrawal" || tx.transaction_type === "buy" ? "text-amber-700" : tx.transaction_type === "adjustment" ? "text-red-700" : "text-brand-600";
     const hasMoveData = tx.quantity !== undefined && tx.quantity !== null;
 
     // Total column: for buy/sell show total_consideration from movement; for deposits/withdrawals show the amount
@@ -2311,6 +2485,20 @@ async function loadCashTransactions(limit) {
     } else if (notesText === "[Credit]") {
       notesText = "";
     }
+    // Show which investment paid a dividend or interest payment
+    if (isIncome && tx.investment_description) {
+      notesText = notesText ? tx.investment_description + " — " + notesText : tx.investment_description;
+    }
+    // Show the PAYE deducted from a drawdown and the net paid out
+    if (tx.transaction_type === "drawdown" && tx.tax_withheld) {
+      const taxText = "Tax " + formatGBP(tx.tax_withheld) + ", net " + formatGBP(tx.net_amount) + (tx.tax_code ? " (" + tx.tax_code + ")" : "");
+      notesText = notesText ? taxText + " — " + notesText : taxText;
+    }
+    // Show the gross figure for a personal contribution paid net of relief
+    if (tx.gross_amount && tx.gross_amount !== tx.amount) {
+      const grossText = "Gross " + formatGBP(tx.gross_amount);
+      notesText = notesText ? grossText + " — " + notesText : grossText;
+    }
     const truncatedNotes = notesText.length > 40 ? notesText.substring(0, 40) + "..." : notesText;
 
     html += '<tr class="' + rowClass + ' border-b border-brand-100">';
@@ -2321,7 +2509,14 @@ async function loadCashTransactions(limit) {
     html += '<td class="py-2 px-3 text-sm text-right font-mono">' + (hasMoveData && tx.deductible_costs > 0 ? formatGBP(tx.deductible_costs) : "") + "</td>";
     html += '<td class="py-2 px-3 text-sm text-right font-mono">' + (tx.transaction_type === "buy" && tx.revised_avg_cost ? formatDetailPrice(tx.revised_avg_cost) : "") + "</td>";
     html += '<td class="py-2 px-3 text-sm text-right font-mono">' + (runningBalances.length > 0 ? formatGBP(runningBalances[i]) : "") + "</td>";
-    html += '<td class="py-2 px-3 text-sm text-brand-500 max-w-xs truncate" title="' + escapeHtml(notesText) + '">' + escapeHtml(truncatedNotes) + "</td>";
+    html += '<td class="py-2 px-3 text-sm text-brand-500 max-w-xs truncate" title="' + escapeHtml(notesText) + '">' + escapeHtml(truncatedNotes);
+    // A withdrawal can be recorded in the gifts register
+    if (tx.transaction_type === "withdrawal") {
+      html += tx.gift_id
+        ? ' <a href="/pages/gifts.html" class="text-xs text-brand-400 hover:text-brand-700">Gift recorded</a>'
+        : ' <a href="/pages/gifts.html?transaction=' + tx.id + '" class="text-xs text-brand-600 hover:text-brand-800 underline">Record as gift</a>';
+    }
+    html += "</td>";
     html += "</tr>";
   }
 
@@ -2482,11 +2677,24 @@ async function handleCashTxSubmit(event) {
     data.direction = direction;
   }
 
-  const result = await apiRequest("/api/accounts/" + selectedAccount.id + "/cash-transactions", {
+  let result = await apiRequest("/api/accounts/" + selectedAccount.id + "/cash-transactions", {
     method: "POST",
     body: data,
   });
 
+  // Deposit would take the owner over their ISA allowance (409) — confirm before recording
+  if (result.status === 409 && result.data && result.data.allowance) {
+    if (!confirm(result.detail + "\n\nRecord the deposit anyway?")) {
+      errorsDiv.textContent = result.detail;
+      return;
+    }
+    data.confirm_over_allowance = true;
+    result = await apiRequest("/api/accounts/" + selectedAccount.id + "/cash-transactions", {
+      method: "POST",
+      body: data,
+    });
+  }
+
   if (result.ok) {
     hideCashTxForm();
     await refreshSelectedAccount();
@@ -2857,6 +3065,8 @@ async function loadDrawdownSchedules(accountId) {
   html += '<th class="py-1.5 px-1 text-xs font-semibold text-brand-600">From</th>';
   html += '<th class="py-1.5 px-1 text-xs font-semibold text-brand-600">To</th>';
   html += '<th class="py-1.5 px-1 text-xs font-semibold text-brand-600 text-right">Amount</th>';
+  html += '<th class="py-1.5 px-1 text-xs font-semibold text-brand-600">Tax</th>';
+  html += '<th class="py-1.5 px-1 text-xs font-semibold text-brand-600">Rises</th>';
   html += '<th class="py-1.5 px-1 text-xs font-semibold text-brand-600">Status</th>';
   html += '<th class="py-1.5 px-1 text-xs font-semibold text-brand-600"></th>';
   html += "</tr></thead><tbody>";
@@ -2872,7 +3082,13 @@ async function loadDrawdownSchedules(accountId) {
     html += '<td class="py-1.5 px-1">' + s.trigger_day + "</td>";
     html += '<td class="py-1.5 px-1">' + formatYearMonth(s.from_date) + "</td>";
     html += '<td class="py-1.5 px-1">' + formatYearMonth(s.to_date) + "</td>";
-    html += '<td class="py-1.5 px-1 text-right">&pound;' + formatDetailValue(s.amount) + "</td>";
+    html += '<td class="py-1.5 px-1 text-right">&pound;' + formatDetailValue(s.amount);
+    if (s.current_amount !== undefined && s.current_amount !== s.amount) {
+      html += '<br><span class="text-xs text-brand-500">now &pound;' + formatDetailValue(s.current_amount) + "</span>";
+    }
+    html += "</td>";
+    html += '<td class="py-1.5 px-1">' + escapeHtml(describeDrawdownTax(s)) + "</td>";
+    html += '<td class="py-1.5 px-1">' + escapeHtml(describeDrawdownEscalation(s)) + "</td>";
     html += '<td class="py-1.5 px-1">' + statusLabel + "</td>";
     html += '<td class="py-1.5 px-1 text-right">';
     html += '<button type="button" class="text-brand-500 hover:text-brand-700 text-xs mr-2" onclick="editDrawdownSchedule(' + s.id + ')">Edit</button>';
@@ -2916,6 +3132,15 @@ function showDrawdownForm() {
   document.getElementById("drawdown-to").value = "";
   document.getElementById("drawdown-amount").value = "";
   document.getElementById("drawdown-notes").value = "";
+  document.getElementById("drawdown-tax-treatment").value = "none";
+  document.getElementById("drawdown-tax-code").value = "";
+  document.getElementById("drawdown-flat-rate").value = "";
+  onDrawdownTaxTreatmentChange();
+  populateEscalationIndexDropdown();
+  document.getElementById("drawdown-escalation-type").value = "none";
+  document.getElementById("drawdown-escalation-rate").value = "";
+  document.getElementById("drawdown-escalation-anniversary").value = "";
+  onDrawdownEscalationTypeChange();
   document.getElementById("drawdown-form-errors").textContent = "";
   document.getElementById("drawdown-form-container").classList.remove("hidden");
   document.getElementById("drawdown-trigger-day").focus();
@@ -2941,6 +3166,16 @@ async function editDrawdownSchedule(scheduleId) {
   document.getElementById("drawdown-to").value = s.to_date.substring(0, 7);
   document.getElementById("drawdown-amount").value = s.amount;
   document.getElementById("drawdown-notes").value = s.notes || "";
+  document.getElementById("drawdown-tax-treatment").value = s.tax_treatment || "none";
+  document.getElementById("drawdown-tax-code").value = s.tax_code || "";
+  document.getElementById("drawdown-flat-rate").value = s.flat_tax_rate !== null ? s.flat_tax_rate : "";
+  onDrawdownTaxTreatmentChange();
+  await populateEscalationIndexDropdown();
+  document.getElementById("drawdown-escalation-type").value = s.escalation_type || "none";
+  document.getElementById("drawdown-escalation-rate").value = s.escalation_rate !== null ? s.escalation_rate : "";
+  document.getElementById("drawdown-escalation-index").value = s.escalation_index_id || "";
+  document.getElementById("drawdown-escalation-anniversary").value = s.escalation_anniversary || "";
+  onDrawdownEscalationTypeChange();
   document.getElementById("drawdown-form-errors").textContent = "";
   document.getElementById("drawdown-form-container").classList.remove("hidden");
   document.getElementById("drawdown-frequency").focus();
@@ -2948,6 +3183,69 @@ async function editDrawdownSchedule(scheduleId) {
 // Expose to inline onclick handlers in the schedule table
 window.editDrawdownSchedule = editDrawdownSchedule;
 
+/**
+ * @description Show the tax code or flat rate field to match the selected
+ * PAYE treatment on the drawdown schedule form.
+ */
+function onDrawdownTaxTreatmentChange() {
+  const treatment = document.getElementById("drawdown-tax-treatment").value;
+  document.getElementById("drawdown-tax-code-group").classList.toggle("hidden", treatment !== "tax_code");
+  document.getElementById("drawdown-flat-rate-group").classList.toggle("hidden", treatment !== "flat");
+}
+
+/**
+ * @description Describe a drawdown schedule's PAYE treatment for the schedules table.
+ * @param {Object} schedule - The drawdown schedule
+ * @returns {string} Short description (e.g. "1257L", "Emergency", "Flat 20%")
+ */
+function describeDrawdownTax(schedule) {
+  if (schedule.tax_treatment === "tax_code") return schedule.tax_code || "";
+  if (schedule.tax_treatment === "emergency") return "Emergency";
+  if (schedule.tax_treatment === "flat") return "Flat " + schedule.flat_tax_rate + "%";
+  return "None";
+}
+
+/**
+ * @description Fill the escalation index dropdown on the drawdown schedule form
+ * with the index series set up on the Other Assets page.
+ * @returns {Promise<void>}
+ */
+async function populateEscalationIndexDropdown() {
+  const select = document.getElementById("drawdown-escalation-index");
+  const result = await apiRequest("/api/index-series");
+  select.innerHTML = '<option value="">Select index...</option>';
+  if (!result.ok) return;
+
+  for (const series of result.data) {
+    const option = document.createElement("option");
+    option.value = series.id;
+    option.textContent = series.name;
+    select.appendChild(option);
+  }
+}
+
+/**
+ * @description Show the rate, index and anniversary fields to match the
+ * selected escalation on the drawdown schedule form.
+ */
+function onDrawdownEscalationTypeChange() {
+  const type = document.getElementById("drawdown-escalation-type").value;
+  document.getElementById("drawdown-escalation-rate-group").classList.toggle("hidden", type !== "fixed");
+  document.getElementById("drawdown-escalation-index-group").classList.toggle("hidden", type !== "index");
+  document.getElementById("drawdown-escalation-anniversary-group").classList.toggle("hidden", type === "none");
+}
+
+/**
+ * @description Describe a drawdown schedule's escalation for the schedules table.
+ * @param {Object} schedule - The drawdown schedule
+ * @returns {string} Short description (e.g. "3% 04-06", "Index 04-06"), or empty if it does not escalate
+ */
+function describeDrawdownEscalation(schedule) {
+  if (schedule.escalation_type === "fixed") return schedule.escalation_rate + "% " + schedule.escalation_anniversary;
+  if (schedule.escalation_type === "index") return "Index " + schedule.escalation_anniversary;
+  return "";
+}
+
 /**
  * @description Hide the drawdown schedule form.
  */
@@ -2978,6 +3276,13 @@ async function handleDrawdownSave() {
     to_date: toMonth ? toMonth + "-01" : "",
     amount: Number(document.getElementById("drawdown-amount").value),
     notes: document.getElementById("drawdown-notes").value.trim() || null,
+    tax_treatment: document.getElementById("drawdown-tax-treatment").value,
+    tax_code: document.getElementById("drawdown-tax-code").value.trim().toUpperCase() || null,
+    flat_tax_rate: document.getElementById("drawdown-flat-rate").value !== "" ? Number(document.getElementById("drawdown-flat-rate").value) : null,
+    escalation_type: document.getElementById("drawdown-escalation-type").value,
+    escalation_rate: document.getElementById("drawdown-escalation-rate").value !== "" ? Number(document.getElementById("drawdown-escalation-rate").value) : null,
+    escalation_index_id: document.getElementById("drawdown-escalation-index").value || null,
+    escalation_anniversary: document.getElementById("drawdown-escalation-anniversary").value.trim() || null,
   };
 
   let result;
@@ -3016,6 +3321,13 @@ async function handleDrawdownSave() {
           to_date: overlap.to_date,
           amount: overlap.amount,
           notes: overlap.notes,
+          tax_treatment: overlap.tax_treatment,
+          tax_code: overlap.tax_code,
+          flat_tax_rate: overlap.flat_tax_rate,
+          escalation_type: overlap.escalation_type,
+          escalation_rate: overlap.escalation_rate,
+          escalation_index_id: overlap.escalation_index_id,
+          escalation_anniversary: overlap.escalation_anniversary,
           active: 0,
         },
       });
@@ -3077,9 +3389,535 @@ async function deleteDrawdownSchedule(scheduleId) {
 // Expose to inline onclick handlers in the schedule table
 window.deleteDrawdownSchedule = deleteDrawdownSchedule;
 
+// ─── Crystallisation ────────────────────────────────────────────────
+
+/**
+ * @description Load the crystallisation summary and events for the current SIPP
+ * account: the crystallised and uncrystallised split, tax-free cash taken and
+ * the person's remaining lump sum allowance.
+ * @param {number} accountId - The SIPP account ID
+ */
+async function loadCrystallisations(accountId) {
+  const summaryDiv = document.getElementById("crystallisation-summary");
+  const container = document.getElementById("crystallisation-list");
+  const result = await apiRequest("/api/accounts/" + accountId + "/crystallisations");
+
+  if (!result.ok) {
+    summaryDiv.innerHTML = "";
+    container.innerHTML = '<p class="text-red-600 text-sm">Failed to load crystallisations.</p>';
+    return;
+  }
+
+  const c = result.data;
+  let summaryHtml = "Uncrystallised " + formatGBPWhole(c.uncrystallised_fund);
+  summaryHtml += " &middot; Crystallised " + formatGBPWhole(c.crystallised_fund);
+  summaryHtml += " &middot; Tax-free cash available " + formatGBPWhole(c.tax_free_cash_available);
+  summaryHtml += '<br><span class="text-brand-500">Lump sum allowance remaining ' + formatGBPWhole(c.lump_sum_allowance.remaining);
+  summaryHtml += " of " + formatGBPWhole(c.lump_sum_allowance.lump_sum_allowance) + " (all SIPPs)</span>";
+  summaryDiv.innerHTML = summaryHtml;
+
+  if (c.events.length === 0) {
+    container.innerHTML = '<p class="text-brand-500 text-sm">No crystallisations recorded.</p>';
+    return;
+  }
+
+  let html = '<table class="w-full text-left border-collapse text-sm">';
+  html += '<thead><tr class="border-b border-brand-200">';
+  html += '<th class="py-1.5 px-1 text-xs font-semibold text-brand-600">Date</th>';
+  html += '<th class="py-1.5 px-1 text-xs font-semibold text-brand-600 text-right">Crystallised</th>';
+  html += '<th class="py-1.5 px-1 text-xs font-semibold text-brand-600 text-right">Tax-Free Cash</th>';
+  html += '<th class="py-1.5 px-1 text-xs font-semibold text-brand-600 text-right">To Drawdown</th>';
+  html += '<th class="py-1.5 px-1 text-xs font-semibold text-brand-600">Notes</th>';
+  html += '<th class="py-1.5 px-1 text-xs font-semibold text-brand-600"></th>';
+  html += "</tr></thead><tbody>";
+
+  for (let i = 0; i < c.events.length; i++) {
+    const e = c.events[i];
+    const rowBg = i % 2 === 1 ? "bg-brand-25" : "";
+    html += '<tr class="' + rowBg + ' border-b border-brand-100">';
+    html += '<td class="py-1.5 px-1">' + formatDateUK(e.crystallisation_date) + "</td>";
+    html += '<td class="py-1.5 px-1 text-right">&pound;' + formatDetailValue(e.amount_crystallised) + "</td>";
+    html += '<td class="py-1.5 px-1 text-right">&pound;' + formatDetailValue(e.tax_free_cash) + "</td>";
+    html += '<td class="py-1.5 px-1 text-right">&pound;' + formatDetailValue(e.drawdown_amount) + "</td>";
+    html += '<td class="py-1.5 px-1">' + escapeHtml(e.notes || "") + "</td>";
+    html += '<td class="py-1.5 px-1 text-right">';
+    html += '<button type="button" class="text-brand-400 hover:text-red-600 text-xs" onclick="deleteCrystallisation(' + e.id + ')">Delete</button>';
+    html += "</td></tr>";
+  }
+
+  html += "</tbody></table>";
+  container.innerHTML = html;
+}
+
+/**
+ * @description Show the crystallisation form for recording a new crystallisation.
+ */
+function showCrystallisationForm() {
+  document.getElementById("crystallisation-date").value = getTodayISO();
+  document.getElementById("crystallisation-tax-free-cash").value = "";
+  document.getElementById("crystallisation-drawdown-amount").value = "";
+  document.getElementById("crystallisation-notes").value = "";
+  document.getElementById("crystallisation-pay-from-cash").checked = true;
+  document.getElementById("crystallisation-form-errors").textContent = "";
+  document.getElementById("crystallisation-form-container").classList.remove("hidden");
+  document.getElementById("crystallisation-tax-free-cash").focus();
+}
+
+/**
+ * @description Hide the crystallisation form.
+ */
+function hideCrystallisationForm() {
+  document.getElementById("crystallisation-form-container").classList.add("hidden");
+  document.getElementById("crystallisation-form-errors").textContent = "";
+}
+
+/**
+ * @description Refresh the cash balance shown on the account form after a
+ * crystallisation or cash buffer sale has changed it.
+ * @param {string} accountId - The SIPP account ID
+ */
+async function refreshAccountFormCashBalance(accountId) {
+  const acctResult = await apiRequest("/api/accounts/" + accountId);
+  if (acctResult.ok) {
+    document.getElementById("cash-balance").value = acctResult.data.cash_balance;
+  }
+}
+
+/**
+ * @description Save a new crystallisation against the current SIPP account.
+ */
+async function handleCrystallisationSave() {
+  const errorsDiv = document.getElementById("crystallisation-form-errors");
+  errorsDiv.textContent = "";
+
+  const accountId = document.getElementById("account-id").value;
+  const data = {
+    crystallisation_date: document.getElementById("crystallisation-date").value,
+    tax_free_cash: Number(document.getElementById("crystallisation-tax-free-cash").value || 0),
+    drawdown_amount: Number(document.getElementById("crystallisation-drawdown-amount").value || 0),
+    pay_from_cash: document.getElementById("crystallisation-pay-from-cash").checked,
+    notes: document.getElementById("crystallisation-notes").value.trim() || null,
+  };
+
+  const result = await apiRequest("/api/accounts/" + accountId + "/crystallisations", {
+    method: "POST",
+    body: data,
+  });
+
+  if (result.ok) {
+    hideCrystallisationForm();
+    await refreshAccountFormCashBalance(accountId);
+    await loadCrystallisations(accountId);
+    return;
+  }
+
+  errorsDiv.textContent = result.detail || result.error || "Failed to save crystallisation.";
+}
+
+/**
+ * @description Delete a crystallisation after user confirmation. Any tax-free
+ * cash it paid out of the account is put back.
+ * @param {number} crystallisationId - The crystallisation ID to delete
+ */
+async function deleteCrystallisation(crystallisationId) {
+  if (!confirm("Delete this crystallisation? Any tax-free cash it paid out will be returned to the account's cash.")) return;
+
+  const accountId = document.getElementById("account-id").value;
+  const result = await apiRequest("/api/crystallisations/" + crystallisationId, {
+    method: "DELETE",
+  });
+
+  if (result.ok) {
+    await refreshAccountFormCashBalance(accountId);
+    await loadCrystallisations(accountId);
+  } else {
+    document.getElementById("crystallisation-form-errors").textContent = result.detail || result.error || "Failed to delete crystallisation.";
+  }
+}
+// Expose to inline onclick handlers in the crystallisation table
+window.deleteCrystallisation = deleteCrystallisation;
+
+// ─── Cash Buffer Planner ─────────────────────────────────────────────
+
+/**
+ * @description Holding IDs ticked in the cash buffer planner, in the order they
+ * were ticked (the priority order for sales).
+ * @type {number[]}
+ */
+let cashBufferPriority = [];
+
+/**
+ * @description The most recent cash buffer plan for the account being edited.
+ * @type {Object|null}
+ */
+let cashBufferPlan = null;
+
+/**
+ * @description Reset the cash buffer planner when a SIPP account is opened.
+ */
+function resetCashBuffer() {
+  cashBufferPriority = [];
+  cashBufferPlan = null;
+  document.getElementById("cash-buffer-months").value = "";
+  document.getElementById("cash-buffer-method").value = "";
+  document.getElementById("cash-buffer-minimum").value = "";
+  document.getElementById("cash-buffer-holdings").innerHTML = "";
+  document.getElementById("cash-buffer-result").innerHTML = "Plan to forecast upcoming drawdowns against the cash balance.";
+  document.getElementById("cash-buffer-commit").classList.add("hidden");
+  document.getElementById("cash-buffer-errors").textContent = "";
+}
+
+/**
+ * @description Forecast the current SIPP's drawdowns and fetch proposed sales
+ * to keep its cash above the minimum, using the months, method, minimum cash
+ * and ticked holdings entered.
+ */
+async function handleCashBufferPlan() {
+  const errorsDiv = document.getElementById("cash-buffer-errors");
+  errorsDiv.textContent = "";
+
+  const accountId = document.getElementById("account-id").value;
+  const params = new URLSearchParams();
+  const months = document.getElementById("cash-buffer-months").value;
+  const method = document.getElementById("cash-buffer-method").value;
+  const minimumCash = document.getElementById("cash-buffer-minimum").value;
+  if (months) params.set("months", months);
+  if (method) params.set("method", method);
+  if (minimumCash !== "") params.set("minimumCash", minimumCash);
+  if (cashBufferPriority.length > 0) params.set("holdings", cashBufferPriority.join(","));
+
+  const query = params.toString();
+  const result = await apiRequest("/api/accounts/" + accountId + "/cash-buffer" + (query ? "?" + query : ""));
+  if (!result.ok) {
+    errorsDiv.textContent = result.detail || result.error || "Failed to plan cash buffer.";
+    return;
+  }
+
+  cashBufferPlan = result.data;
+  renderCashBufferHoldings(cashBufferPlan.holdings);
+  renderCashBufferPlan(cashBufferPlan);
+}
+
+/**
+ * @description Render the holdings that may be sold as a tick list. Ticked
+ * holdings are numbered in the order they were ticked; with none ticked, the
+ * planner sells from the largest holding first.
+ * @param {Object[]} holdings - Holdings from the plan (holding_id, description, value_gbp)
+ */
+function renderCashBufferHoldings(holdings) {
+  const container = document.getElementById("cash-buffer-holdings");
+  if (holdings.length === 0) {
+    container.innerHTML = '<p class="text-brand-500">No priced holdings to sell.</p>';
+    return;
+  }
+
+  let html = '<p class="text-xs text-brand-500 mb-1">Sell from (tick in priority order; none ticked sells largest first):</p>';
+  for (const h of holdings) {
+    const position = cashBufferPriority.indexOf(h.holding_id);
+    html += '<label class="flex items-center gap-2 py-0.5">';
+    html += '<input type="checkbox"' + (position >= 0 ? " checked" : "") + ' onchange="toggleCashBufferHolding(' + h.holding_id + ')" />';
+    html += '<span class="w-4 text-xs text-brand-500">' + (position >= 0 ? position + 1 : "") + "</span>";
+    html += "<span>" + escapeHtml(h.description) + "</span>";
+    html += '<span class="text-brand-400">' + formatGBPWhole(h.value_gbp) + "</span>";
+    html += "</label>";
+  }
+  container.innerHTML = html;
+}
+
+/**
+ * @description Tick or untick a holding in the cash buffer planner, adding it
+ * to the end of the priority order or removing it.
+ * @param {number} holdingId - The holding ID
+ */
+function toggleCashBufferHolding(holdingId) {
+  const position = cashBufferPriority.indexOf(holdingId);
+  if (position >= 0) {
+    cashBufferPriority.splice(position, 1);
+  } else {
+    cashBufferPriority.push(holdingId);
+  }
+  if (cashBufferPlan) {
+    renderCashBufferHoldings(cashBufferPlan.holdings);
+  }
+}
+
+/**
+ * @description Render a cash buffer plan: the forecast cash position and a
+ * table of proposed sales whose quantities and proceeds can be adjusted
+ * before they are recorded.
+ * @param {Object} plan - The plan from the cash buffer API
+ */
+function renderCashBufferPlan(plan) {
+  const container = document.getElementById("cash-buffer-result");
+  const commitDiv = document.getElementById("cash-buffer-commit");
+
+  let html = '<p class="text-brand-600 mb-2">';
+  html += plan.drawdowns.length + " drawdown" + (plan.drawdowns.length === 1 ? "" : "s") + " totalling " + formatGBPWhole(plan.total_drawdowns);
+  html += " due by " + formatDateUK(plan.horizon_date) + ". Cash " + formatGBPWhole(plan.cash_balance);
+  html += " falls to " + formatGBPWhole(plan.lowest_balance) + " against a minimum of " + formatGBPWhole(plan.minimum_cash) + ".";
+  html += "</p>";
+
+  if (plan.shortfall === 0) {
+    html += '<p class="text-brand-500">No sales needed.</p>';
+    container.innerHTML = html;
+    commitDiv.classList.add("hidden");
+    return;
+  }
+
+  html += '<p class="text-brand-700 font-medium mb-2">Shortfall of &pound;' + formatDetailValue(plan.shortfall) + " from " + formatDateUK(plan.first_shortfall_date) + ".</p>";
+
+  if (plan.proposals.length > 0) {
+    html += '<table class="w-full text-left border-collapse text-sm mb-2">';
+    html += '<thead><tr class="border-b border-brand-200">';
+    html += '<th class="py-1.5 px-1 text-xs font-semibold text-brand-600">Holding</th>';
+    html += '<th class="py-1.5 px-1 text-xs font-semibold text-brand-600 text-right">Held</th>';
+    html += '<th class="py-1.5 px-1 text-xs font-semibold text-brand-600 text-right">Sell</th>';
+    html += '<th class="py-1.5 px-1 text-xs font-semibold text-brand-600 text-right">Proceeds (&pound;)</th>';
+    html += "</tr></thead><tbody>";
+
+    for (let i = 0; i < plan.proposals.length; i++) {
+      const p = plan.proposals[i];
+      const rowBg = i % 2 === 1 ? "bg-brand-25" : "";
+      html += '<tr class="' + rowBg + ' border-b border-brand-100">';
+      html += '<td class="py-1.5 px-1">' + escapeHtml(p.description) + "</td>";
+      html += '<td class="py-1.5 px-1 text-right">' + p.quantity_held + "</td>";
+      html += '<td class="py-1.5 px-1 text-right"><input type="number" id="cash-buffer-qty-' + i + '" step="any" min="0" value="' + p.quantity + '" class="w-28 px-2 py-1 border border-brand-300 rounded-md text-sm text-right" /></td>';
+      html += '<td class="py-1.5 px-1 text-right"><input type="number" id="cash-buffer-proceeds-' + i + '" step="0.01" min="0" value="' + p.estimated_proceeds.toFixed(2) + '" class="w-28 px-2 py-1 border border-brand-300 rounded-md text-sm text-right" /></td>';
+      html += "</tr>";
+    }
+
+    html += "</tbody></table>";
+  }
+
+  if (plan.unfunded > 0) {
+    html += '<p class="text-red-600">The holdings chosen cannot cover &pound;' + formatDetailValue(plan.unfunded) + " of the shortfall.</p>";
+  }
+
+  container.innerHTML = html;
+
+  if (plan.proposals.length > 0) {
+    document.getElementById("cash-buffer-sale-date").value = getTodayISO();
+    commitDiv.classList.remove("hidden");
+  } else {
+    commitDiv.classList.add("hidden");
+  }
+}
+
+/**
+ * @description Record the proposed sales (as adjusted) as sell movements on
+ * the current SIPP account, crediting the proceeds to its cash.
+ */
+async function handleCashBufferCommit() {
+  const errorsDiv = document.getElementById("cash-buffer-errors");
+  errorsDiv.textContent = "";
+  if (!cashBufferPlan) return;
+
+  const sales = [];
+  for (let i = 0; i < cashBufferPlan.proposals.length; i++) {
+    const quantity = Number(document.getElementById("cash-buffer-qty-" + i).value || 0);
+    if (quantity <= 0) continue;
+    sales.push({
+      holding_id: cashBufferPlan.proposals[i].holding_id,
+      quantity: quantity,
+      total_consideration: Number(document.getElementById("cash-buffer-proceeds-" + i).value || 0),
+    });
+  }
+
+  if (sales.length === 0) {
+    errorsDiv.textContent = "Enter a quantity to sell for at least one holding.";
+    return;
+  }
+  if (!confirm("Record " + sales.length + " sale" + (sales.length === 1 ? "" : "s") + " and credit the proceeds to this account's cash?")) return;
+
+  const accountId = document.getElementById("account-id").value;
+  const result = await apiRequest("/api/accounts/" + accountId + "/cash-buffer/sales", {
+    method: "POST",
+    body: {
+      movement_date: document.getElementById("cash-buffer-sale-date").value,
+      sales: sales,
+    },
+  });
+
+  if (result.ok) {
+    await refreshAccountFormCashBalance(accountId);
+    await handleCashBufferPlan();
+    return;
+  }
+
+  errorsDiv.textContent = result.detail || result.error || "Failed to record sales.";
+}
+
+// Expose to inline onchange handlers in the cash buffer holdings list
+window.toggleCashBufferHolding = toggleCashBufferHolding;
+
+// ─── Broker Import ───────────────────────────────────────────────────
+
+/**
+ * @description The dry-run preview of the broker CSV last chosen for the
+ * account being edited. Importing commits exactly the new rows shown here.
+ * @type {Object|null}
+ */
+let brokerImportPreview = null;
+
+/**
+ * @description Display labels for the status of each previewed import row.
+ * @type {Object<string, string>}
+ */
+const BROKER_IMPORT_STATUS_LABELS = {
+  new: "New",
+  duplicate: "Already recorded",
+  unmatched: "Not matched",
+  skipped: "Skipped",
+};
+
+/**
+ * @description Reset the broker import section when an account is opened.
+ */
+function resetBrokerImport() {
+  brokerImportPreview = null;
+  document.getElementById("broker-import-provider").value = "";
+  document.getElementById("broker-import-preview").innerHTML = "Preview a transaction history export from your provider. Nothing is recorded until you import it.";
+  document.getElementById("broker-import-commit").classList.add("hidden");
+  document.getElementById("broker-import-errors").textContent = "";
+}
+
+/**
+ * @description Open the file picker for a broker CSV export.
+ */
+function chooseBrokerImportFile() {
+  const input = document.getElementById("broker-import-file");
+  input.value = "";
+  input.click();
+}
+
+/**
+ * @description Send the chosen CSV for a dry-run preview and show what would
+ * be imported. Nothing is recorded.
+ */
+async function previewBrokerImportFile() {
+  const errorsDiv = document.getElementById("broker-import-errors");
+  errorsDiv.textContent = "";
+
+  const file = document.getElementById("broker-import-file").files[0];
+  if (!file) return;
+
+  brokerImportPreview = null;
+  document.getElementById("broker-import-commit").classList.add("hidden");
+
+  const accountId = document.getElementById("account-id").value;
+  const result = await apiRequest("/api/accounts/" + accountId + "/import/preview", {
+    method: "POST",
+    body: {
+      provider: document.getElementById("broker-import-provider").value || null,
+      csv: await file.text(),
+    },
+  });
+
+  if (!result.ok) {
+    errorsDiv.textContent = result.detail || result.error || "Failed to preview the file.";
+    return;
+  }
+
+  brokerImportPreview = result.data;
+  renderBrokerImportPreview(brokerImportPreview);
+}
+
+/**
+ * @description Render a broker import preview: a summary line and a table of
+ * every row in the file with its matched investment and status.
+ * @param {Object} preview - The preview from the import API
+ */
+function renderBrokerImportPreview(preview) {
+  const container = document.getElementById("broker-import-preview");
+  const commitDiv = document.getElementById("broker-import-commit");
+  const s = preview.summary;
+
+  let html = '<p class="text-brand-600 mb-2">';
+  html += escapeHtml(preview.provider_name) + " file: " + s.total + " row" + (s.total === 1 ? "" : "s") + " — ";
+  html += s.new + " new, " + s.duplicate + " already recorded, " + s.unmatched + " not matched, " + s.skipped + " skipped.";
+  html += "</p>";
+
+  if (preview.rows.length > 0) {
+    html += '<div class="max-h-64 overflow-y-auto mb-2">';
+    html += '<table class="w-full text-left border-collapse text-sm">';
+    html += '<thead><tr class="border-b border-brand-200">';
+    html += '<th class="py-1.5 px-1 text-xs font-semibold text-brand-600">Date</th>';
+    html += '<th class="py-1.5 px-1 text-xs font-semibold text-brand-600">Type</th>';
+    html += '<th class="py-1.5 px-1 text-xs font-semibold text-brand-600">Description</th>';
+    html += '<th class="py-1.5 px-1 text-xs font-semibold text-brand-600 text-right">Quantity</th>';
+    html += '<th class="py-1.5 px-1 text-xs font-semibold text-brand-600 text-right">Amount (&pound;)</th>';
+    html += '<th class="py-1.5 px-1 text-xs font-semibold text-brand-600">Status</th>';
+    html += "</tr></thead><tbody>";
+
+    for (let i = 0; i < preview.rows.length; i++) {
+      const r = preview.rows[i];
+      const rowBg = i % 2 === 1 ? "bg-brand-25" : "";
+      const statusClass = r.status === "new" ? "text-brand-800 font-medium" : r.status === "unmatched" ? "text-red-600" : "text-brand-400";
+      html += '<tr class="' + rowBg + ' border-b border-brand-100">';
+      html += '<td class="py-1.5 px-1">' + formatDateUK(r.date) + "</td>";
+      html += '<td class="py-1.5 px-1">' + escapeHtml(r.type || "—") + "</td>";
+      html += '<td class="py-1.5 px-1">' + escapeHtml(r.investment_description || r.description || "");
+      if (r.deductible_costs > 0) {
+        html += '<span class="text-xs text-brand-400"> (costs &pound;' + formatDetailValue(r.deductible_costs) + ")</span>";
+      }
+      html += "</td>";
+      html += '<td class="py-1.5 px-1 text-right">' + (r.quantity > 0 ? r.quantity : "") + "</td>";
+      html += '<td class="py-1.5 px-1 text-right">' + formatDetailValue(r.amount) + "</td>";
+      html += '<td class="py-1.5 px-1 ' + statusClass + '"' + (r.reason ? ' title="' + escapeHtml(r.reason) + '"' : "") + ">" + BROKER_IMPORT_STATUS_LABELS[r.status] + "</td>";
+      html += "</tr>";
+    }
+
+    html += "</tbody></table></div>";
+  }
+
+  container.innerHTML = html;
+
+  if (s.new > 0) {
+    document.getElementById("broker-import-commit-btn").textContent = "Import " + s.new + " New Row" + (s.new === 1 ? "" : "s");
+    commitDiv.classList.remove("hidden");
+  } else {
+    commitDiv.classList.add("hidden");
+  }
+}
+
+/**
+ * @description Import the new rows of the current preview into the account.
+ * The server checks the preview is still the account's latest, and records
+ * either every row or none of them.
+ */
+async function handleBrokerImportCommit() {
+  const errorsDiv = document.getElementById("broker-import-errors");
+  errorsDiv.textContent = "";
+  if (!brokerImportPreview) return;
+
+  const count = brokerImportPreview.summary.new;
+  if (!confirm("Import " + count + " new row" + (count === 1 ? "" : "s") + " into this account?")) return;
+
+  const accountId = document.getElementById("account-id").value;
+  const result = await apiRequest("/api/accounts/" + accountId + "/import", {
+    method: "POST",
+    body: { file_hash: brokerImportPreview.file_hash },
+  });
+
+  if (result.ok) {
+    await refreshAccountFormCashBalance(accountId);
+    resetBrokerImport();
+    document.getElementById("broker-import-preview").textContent = result.data.imported + " row" + (result.data.imported === 1 ? "" : "s") + " imported.";
+    return;
+  }
+
+  const failed = result.data && result.data.failed;
+  if (failed) {
+    errorsDiv.textContent = "Nothing was imported — row " + failed.row_number + " (" + failed.description + ") failed: " + failed.error;
+  } else {
+    errorsDiv.textContent = result.detail || result.error || "Failed to import the file.";
+  }
+}
+
 // ─── Initialisation ──────────────────────────────────────────────────
 
 document.addEventListener("DOMContentLoaded", async function () {
+  await loadProviders();
   await loadUsers();
 
   // Determine view from URL query parameter
@@ -3264,6 +4102,22 @@ document.addEventListener("DOMContentLoaded", async function () {
   document.getElementById("drawdown-add-btn").addEventListener("click", showDrawdownForm);
   document.getElementById("drawdown-save-btn").addEventListener("click", handleDrawdownSave);
   document.getElementById("drawdown-cancel-btn").addEventListener("click", hideDrawdownForm);
+  document.getElementById("drawdown-tax-treatment").addEventListener("change", onDrawdownTaxTreatmentChange);
+  document.getElementById("drawdown-escalation-type").addEventListener("change", onDrawdownEscalationTypeChange);
+
+  // Crystallisation form
+  document.getElementById("crystallisation-add-btn").addEventListener("click", showCrystallisationForm);
+  document.getElementById("crystallisation-save-btn").addEventListener("click", handleCrystallisationSave);
+  document.getElementById("crystallisation-cancel-btn").addEventListener("click", hideCrystallisationForm);
+
+  // Cash buffer planner
+  document.getElementById("cash-buffer-plan-btn").addEventListener("click", handleCashBufferPlan);
+  document.getElementById("cash-buffer-commit-btn").addEventListener("click", handleCashBufferCommit);
+
+  // Broker import
+  document.getElementById("broker-import-choose-btn").addEventListener("click", chooseBrokerImportFile);
+  document.getElementById("broker-import-file").addEventListener("change", previewBrokerImportFile);
+  document.getElementById("broker-import-commit-btn").addEventListener("click", handleBrokerImportCommit);
 
   // Delete dialog
   document.getElementById("delete-cancel-btn").addEventListener("click", hideDeleteDialog);
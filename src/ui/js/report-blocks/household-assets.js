@@ -1,8 +1,10 @@
 /**
  * @description Household Assets report block for Portfolio 60.
  * Renders a spreadsheet-style view of non-portfolio assets
- * (pensions, property, savings, alternative assets) grouped by category,
- * with summary totals for recurring income (annualised) and asset values.
+ * (pensions, property, savings, alternative assets) and liabilities grouped
+ * by category, with summary totals for recurring income (annualised), asset
+ * values, liabilities and household net worth, values overdue for revaluation,
+ * and a net worth chart.
  */
 
 /**
@@ -31,6 +33,92 @@ function reportFormatGBP(scaledValue) {
   return Math.round(amount).toLocaleString("en-GB");
 }
 
+/**
+ * @description Format a scaled integer (x 10000) like reportFormatGBP,
+ * with a leading minus sign when negative.
+ * @param {number} scaledValue - The value x 10000
+ * @returns {string} Formatted string like "-1,234"
+ */
+function reportFormatSignedGBP(scaledValue) {
+  return (scaledValue < 0 ? "-" : "") + reportFormatGBP(Math.abs(scaledValue));
+}
+
+/**
+ * @description Series drawn on the net worth chart, with their line colours.
+ * @type {Array<{key: string, label: string, colour: string}>}
+ */
+const NET_WORTH_CHART_SERIES = [
+  { key: "net_worth", label: "Net worth", colour: "#1e3a8a" },
+  { key: "portfolio", label: "Portfolios", colour: "#0891b2" },
+  { key: "other_assets", label: "Other assets", colour: "#16a34a" },
+  { key: "liabilities", label: "Liabilities", colour: "#dc2626" },
+];
+
+/**
+ * @description Build an inline SVG line chart of net worth and its parts at
+ * each month end. Values are in pounds, one per date.
+ * @param {Object} history - Net worth history from /api/other-assets/net-worth-history
+ * @returns {string} HTML string with the chart and its legend
+ */
+function reportNetWorthChart(history) {
+  const width = 640;
+  const height = 220;
+  const left = 56;
+  const right = 8;
+  const top = 8;
+  const bottom = 24;
+  const count = history.dates.length;
+  if (count < 2) return "";
+
+  let min = 0;
+  let max = 0;
+  for (const series of NET_WORTH_CHART_SERIES) {
+    for (const value of history[series.key]) {
+      min = Math.min(min, value);
+      max = Math.max(max, value);
+    }
+  }
+  if (max === min) max = min + 1;
+
+  function xAt(i) {
+    return left + (i * (width - left - right)) / (count - 1);
+  }
+  function yAt(value) {
+    return top + ((max - value) * (height - top - bottom)) / (max - min);
+  }
+
+  let svg = '<svg viewBox="0 0 ' + width + " " + height + '" class="w-full max-w-2xl" role="img" aria-label="Net worth over time">';
+
+  // Grid lines with £ labels at the top, middle and bottom of the range
+  for (const value of [max, (max + min) / 2, min]) {
+    const y = yAt(value).toFixed(1);
+    svg += '<line x1="' + left + '" y1="' + y + '" x2="' + (width - right) + '" y2="' + y + '" stroke="#e2e8f0" stroke-width="1" />';
+    svg += '<text x="' + (left - 4) + '" y="' + y + '" text-anchor="end" dominant-baseline="middle" font-size="10" fill="#475569">' + reportFormatSignedGBP(Math.round(value) * 10000) + "</text>";
+  }
+
+  // Month labels for the first, middle and last dates
+  for (const i of [0, Math.floor((count - 1) / 2), count - 1]) {
+    svg += '<text x="' + xAt(i).toFixed(1) + '" y="' + (height - 6) + '" text-anchor="middle" font-size="10" fill="#475569">' + reportFormatDate(history.dates[i]) + "</text>";
+  }
+
+  for (const series of NET_WORTH_CHART_SERIES) {
+    const points = history[series.key].map(function (value, i) {
+      return xAt(i).toFixed(1) + "," + yAt(value).toFixed(1);
+    });
+    const strokeWidth = series.key === "net_worth" ? 2.5 : 1.5;
+    svg += '<polyline fill="none" stroke="' + series.colour + '" stroke-width="' + strokeWidth + '" points="' + points.join(" ") + '" />';
+  }
+  svg += "</svg>";
+
+  let legend = '<div class="flex gap-4 mt-1 text-xs text-brand-600">';
+  for (const series of NET_WORTH_CHART_SERIES) {
+    legend += '<span><span class="inline-block w-3 h-0.5 align-middle mr-1" style="background:' + series.colour + '"></span>' + escapeHtml(series.label) + "</span>";
+  }
+  legend += "</div>";
+
+  return svg + legend;
+}
+
 /**
  * @description Format an ISO-8601 date string (YYYY-MM-DD) for the report.
  * Returns DD/MM/YYYY format to match the spreadsheet style.
@@ -71,14 +159,18 @@ function reportTodayFormatted() {
 
 /**
  * @description Render the Household Assets report into a container element.
- * Fetches data from /api/other-assets/summary and builds a grouped table.
+ * Fetches data from /api/other-assets/summary and builds a grouped table,
+ * then the net worth chart from /api/other-assets/net-worth-history.
  * @param {string} containerId - The ID of the container element to render into
  * @param {Array} [params] - Reserved for future filtering (currently unused)
  */
 async function renderHouseholdAssets(containerId, params) {
   const container = document.getElementById(containerId);
 
-  const result = await apiRequest("/api/other-assets/summary");
+  const [result, historyResult] = await Promise.all([
+    apiRequest("/api/other-assets/summary"),
+    apiRequest("/api/other-assets/net-worth-history?months=12"),
+  ]);
 
   if (!result.ok) {
     container.innerHTML =
@@ -91,7 +183,7 @@ async function renderHouseholdAssets(containerId, params) {
   }
 
   const data = result.data;
-  const categoryOrder = ["pension", "property", "savings", "alternative"];
+  const categoryOrder = ["pension", "property", "savings", "alternative", "liability"];
 
   // Filter to only categories that have items
   const activeCats = categoryOrder.filter(function (key) {
@@ -174,19 +266,28 @@ async function renderHouseholdAssets(containerId, params) {
         '<td class="py-1 px-2 text-xs font-light ' +
         colEvery +
         '">' +
-        (item.frequency
-          ? REPORT_FREQUENCY_LABELS[item.frequency] || item.frequency
-          : "") +
+        (item.category === "liability"
+          ? item.interest_rate !== null
+            ? item.interest_rate + "%"
+            : ""
+          : item.frequency
+            ? REPORT_FREQUENCY_LABELS[item.frequency] || item.frequency
+            : "") +
         "</td>";
       html +=
         '<td class="py-1 px-2 text-xs font-light ' +
         colEdited +
-        '">' +
+        (item.revalue_overdue
+          ? ' text-error" title="Revaluation was due ' + reportFormatDate(item.revalue_due_date) + '">'
+          : '">') +
         reportFormatDate(item.last_updated) +
         "</td>";
 
       // Notes with executor reference tooltip
       html += '<td class="py-1 px-2 text-xs font-light ' + colNotes + '">';
+      if (item.category === "liability" && item.repayment) {
+        html += "Repays " + reportFormatGBP(item.repayment) + " a month" + (item.notes ? " — " : "");
+      }
       if (item.notes) {
         html += escapeHtml(item.notes);
       }
@@ -224,17 +325,60 @@ async function renderHouseholdAssets(containerId, params) {
   html += '<td class="py-0.5 font-light text-xs text-brand-600">Annually</td>';
   html += "</tr>";
 
-  html += "<tr>";
-  html += '<td class="py-0.5 pr-6 text-sm">Assets </td>';
-  html +=
-    '<td class="py-0.5 pl-12 pr-4 text-sm text-right font-semibold">' +
-    reportFormatGBP(data.totals.value_total) +
-    "</td>";
-  html += '<td class="py-0.5 text-sm"></td>';
-  html += "</tr>";
+  const summaryLines = [
+    { label: "Assets", value: reportFormatGBP(data.totals.value_total) },
+    { label: "Liabilities", value: reportFormatSignedGBP(-data.totals.liabilities_total) },
+    { label: "Portfolios", value: reportFormatGBP(data.totals.portfolio_total) },
+    { label: "Net worth", value: reportFormatSignedGBP(data.totals.net_worth), total: true },
+  ];
+  for (const line of summaryLines) {
+    const border = line.total ? " border-t border-brand-300" : "";
+    html += "<tr>";
+    html += '<td class="py-0.5 pr-6 text-sm' + (line.total ? " font-semibold" : "") + '">' + line.label + "</td>";
+    html +=
+      '<td class="py-0.5 pl-12 pr-4 text-sm text-right font-semibold' + border + '">' +
+      line.value +
+      "</td>";
+    html += '<td class="py-0.5 text-sm"></td>';
+    html += "</tr>";
+  }
 
   html += "</table></div>";
 
+  // Values overdue for revaluation
+  const overdue = [];
+  for (const catKey of activeCats) {
+    for (const item of data.categories[catKey].items) {
+      if (item.revalue_overdue) overdue.push(item);
+    }
+  }
+  if (overdue.length > 0) {
+    html += '<div class="mt-4">';
+    html += '<h3 class="text-sm font-bold text-error mb-1">Revaluation Due</h3>';
+    html += '<ul class="text-xs">';
+    for (const item of overdue) {
+      html +=
+        "<li>" +
+        escapeHtml(item.description) +
+        " — last updated " +
+        reportFormatDate(item.last_updated) +
+        ", due " +
+        reportFormatDate(item.revalue_due_date) +
+        " (every " +
+        item.revalue_months +
+        " months)</li>";
+    }
+    html += "</ul></div>";
+  }
+
+  // Net worth over time (left out if the history could not be built)
+  if (historyResult.ok) {
+    html += '<div class="mt-6">';
+    html += '<h3 class="text-sm font-bold text-brand-800 mb-2">Net Worth — last 12 months</h3>';
+    html += reportNetWorthChart(historyResult.data);
+    html += "</div>";
+  }
+
   // Date footer (suppressed when running inside a composite report)
   if (!window._compositeReport) {
     html += '<div class="mt-6 border-t-2 border-brand-300 pt-1">';
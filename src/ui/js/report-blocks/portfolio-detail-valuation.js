@@ -13,6 +13,10 @@
  * When account type contains "+", a combined totals row is rendered
  * aggregating Value GBP and value-weighted changes across those accounts.
  *
+ * A user may hold more than one account of a type (e.g. ISAs with two
+ * providers). A plain type renders every such account; append "@provider"
+ * or "~account_ref" to address just one, e.g. "BW:isa@hl" or "BW:sipp~3366521".
+ *
  * Examples:
  *   ["BW:ISA:1m,3m,1y,3y", "BW:SIPP:1m,3m,1y,3y", "BW:trading",
  *    "BW:isa+sipp+trading:1m,3m,1y,3y"]
@@ -117,14 +121,28 @@ function parseDetailParam(param) {
 }
 
 /**
- * @description Build the API URL for a portfolio detail request.
+ * @description Build the qualifier that tells apart two accounts of the
+ * same type, e.g. "(HL 3366521)".
+ * @param {Object} account - Account with provider and account_ref
+ * @returns {string} The qualifier in brackets
+ */
+function detailAccountQualifier(account) {
+  const parts = [];
+  if (account.provider) parts.push(account.provider.toUpperCase());
+  if (account.account_ref) parts.push(account.account_ref);
+  return "(" + parts.join(" ") + ")";
+}
+
+/**
+ * @description Build the API URL for a portfolio detail request. The
+ * response is an array with one entry per account matched by the token.
  * @param {string} user - User initials
- * @param {string} accountType - Account type
+ * @param {string} accountType - Account type, optionally narrowed (e.g. "isa@hl")
  * @param {string[]} periods - Period codes
  * @returns {string} The API URL
  */
 function buildDetailApiUrl(user, accountType, periods) {
-  let url = "/api/portfolio/detail?user=" +
+  let url = "/api/portfolio/detail/accounts?user=" +
     encodeURIComponent(user) +
     "&account=" +
     encodeURIComponent(accountType);
@@ -137,12 +155,16 @@ function buildDetailApiUrl(user, accountType, periods) {
 /**
  * @description Render a single account's detail table.
  * @param {Object} data - The portfolio detail data from the API
+ * @param {boolean} [showAccountRef] - Whether to name the provider and reference in the heading
  * @returns {string} HTML string for the account detail section
  */
-function renderDetailAccountSection(data) {
+function renderDetailAccountSection(data, showAccountRef) {
   const user = data.user;
   const account = data.account;
-  const typeLabel = DETAIL_ACCOUNT_TYPE_LABELS[account.account_type] || account.account_type;
+  let typeLabel = DETAIL_ACCOUNT_TYPE_LABELS[account.account_type] || account.account_type;
+  if (showAccountRef) {
+    typeLabel += " " + detailAccountQualifier(account);
+  }
   const periods = data.periods || [];
   const hasPeriods = periods.length > 0;
 
@@ -306,7 +328,13 @@ function renderDetailCombinedTotals(userName, detailResults, periods) {
   for (let d = 0; d < detailResults.length; d++) {
     const data = detailResults[d];
     combinedValueGBP += data.totals.value_gbp;
-    const typeLabel = DETAIL_ACCOUNT_TYPE_LABELS[data.account.account_type] || data.account.account_type;
+    let typeLabel = DETAIL_ACCOUNT_TYPE_LABELS[data.account.account_type] || data.account.account_type;
+    const sameTypeCount = detailResults.filter(function (r) {
+      return r.account.account_type === data.account.account_type;
+    }).length;
+    if (sameTypeCount > 1) {
+      typeLabel += " " + detailAccountQualifier(data.account);
+    }
     accountLabels.push(typeLabel);
 
     // Accumulate weighted changes from individual holdings (not from per-account totals)
@@ -391,7 +419,7 @@ function renderDetailCombinedTotals(userName, detailResults, periods) {
 
 /**
  * @description Render the Portfolio Detail Valuation report into a container element.
- * Fetches data from /api/portfolio/detail for each param entry and builds a
+ * Fetches data from /api/portfolio/detail/accounts for each param entry and builds a
  * per-account holdings detail table with optional price change columns.
  * When account type contains "+", renders a combined totals row instead.
  *
@@ -429,18 +457,27 @@ async function renderPortfolioDetailValuation(containerId, params) {
       const combinedResults = [];
       let combinedPeriods = [];
       let combinedUserName = "";
+      const seenAccountIds = {};
 
       for (let a = 0; a < parsed.accountTypes.length; a++) {
         const url = buildDetailApiUrl(parsed.user, parsed.accountTypes[a], parsed.periods);
         const result = await apiRequest(url);
-
-        if (result.ok && result.data && result.data.holdings && result.data.holdings.length > 0) {
-          combinedResults.push(result.data);
-          if (combinedPeriods.length === 0 && result.data.periods) {
-            combinedPeriods = result.data.periods;
-          }
-          if (!combinedUserName) {
-            combinedUserName = result.data.user.first_name + " " + result.data.user.last_name;
+        if (!result.ok || !result.data) continue;
+
+        for (let d = 0; d < result.data.length; d++) {
+          const data = result.data[d];
+          // "isa+isa@hl" names the HL ISA twice — count each account once
+          if (seenAccountIds[data.account.id]) continue;
+          seenAccountIds[data.account.id] = true;
+
+          if (data.holdings && data.holdings.length > 0) {
+            combinedResults.push(data);
+            if (combinedPeriods.length === 0 && data.periods) {
+              combinedPeriods = data.periods;
+            }
+            if (!combinedUserName) {
+              combinedUserName = data.user.first_name + " " + data.user.last_name;
+            }
           }
         }
       }
@@ -465,19 +502,26 @@ async function renderPortfolioDetailValuation(containerId, params) {
         continue;
       }
 
-      const data = result.data;
+      const showAccountRef = result.data.length > 1;
 
-      if (!data.holdings || data.holdings.length === 0) {
-        html +=
-          '<h3 class="text-sm font-bold text-brand-800 mt-5 mb-1">' +
-          escapeHtml(data.user.first_name + " " + data.user.last_name + " " +
-            (DETAIL_ACCOUNT_TYPE_LABELS[data.account.account_type] || data.account.account_type)) +
-          "</h3>" +
-          '<p class="text-sm text-brand-500">No holdings in this account.</p>';
-        continue;
-      }
+      for (let d = 0; d < result.data.length; d++) {
+        const data = result.data[d];
 
-      html += renderDetailAccountSection(data);
+        if (!data.holdings || data.holdings.length === 0) {
+          let typeLabel = DETAIL_ACCOUNT_TYPE_LABELS[data.account.account_type] || data.account.account_type;
+          if (showAccountRef) {
+            typeLabel += " " + detailAccountQualifier(data.account);
+          }
+          html +=
+            '<h3 class="text-sm font-bold text-brand-800 mt-5 mb-1">' +
+            escapeHtml(data.user.first_name + " " + data.user.last_name + " " + typeLabel) +
+            "</h3>" +
+            '<p class="text-sm text-brand-500">No holdings in this account.</p>';
+          continue;
+        }
+
+        html += renderDetailAccountSection(data, showAccountRef);
+      }
     }
   }
 
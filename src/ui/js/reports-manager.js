@@ -21,6 +21,12 @@ const REPORT_TYPE_LABELS = {
   chart: "Performance Chart",
   chart_group: "Chart Group",
   portfolio_value_chart: "Portfolio Value Chart",
+  isa_allowance: "ISA Allowance",
+  p60_summary: "Pension Income (P60)",
+  retirement_projection: "Retirement Projection",
+  correlation_matrix: "Correlation Matrix",
+  other_assets_chart: "Other Assets Chart",
+  iht_estate: "Inheritance Tax Estate",
   composite: "Composite",
 };
 
@@ -33,6 +39,12 @@ function detectReportType(report) {
   if (report.blocks && Array.isArray(report.blocks)) return "composite";
   if (report.charts && Array.isArray(report.charts)) return "chart_group";
   if (!report.pdfEndpoint) return "household_assets";
+  if (report.pdfEndpoint.indexOf("isa-allowance") !== -1) return "isa_allowance";
+  if (report.pdfEndpoint.indexOf("p60-summary") !== -1) return "p60_summary";
+  if (report.pdfEndpoint.indexOf("retirement-projection") !== -1) return "retirement_projection";
+  if (report.pdfEndpoint.indexOf("correlation-matrix") !== -1) return "correlation_matrix";
+  if (report.pdfEndpoint.indexOf("other-assets-chart") !== -1) return "other_assets_chart";
+  if (report.pdfEndpoint.indexOf("iht-estate") !== -1) return "iht_estate";
   if (report.pdfEndpoint.indexOf("portfolio-value") !== -1) return "portfolio_value_chart";
   if (report.pdfEndpoint.indexOf("chart-group") !== -1) return "chart_group";
   if (report.pdfEndpoint.indexOf("chart") !== -1) return "chart";
@@ -42,6 +54,73 @@ function detectReportType(report) {
   return "household_assets";
 }
 
+/**
+ * @description Read a query parameter from a report's pdfEndpoint.
+ * @param {string} [pdfEndpoint] - The report's PDF endpoint URL
+ * @param {string} name - The query parameter name
+ * @returns {string} The value, or empty string if not set
+ */
+function getEndpointQueryValue(pdfEndpoint, name) {
+  if (!pdfEndpoint || pdfEndpoint.indexOf("?") === -1) return "";
+  const query = new URLSearchParams(pdfEndpoint.slice(pdfEndpoint.indexOf("?") + 1));
+  return query.get(name) || "";
+}
+
+/**
+ * @description Read the taxYear query parameter from a report's pdfEndpoint.
+ * @param {string} [pdfEndpoint] - The report's PDF endpoint URL
+ * @returns {string} The tax year label, or empty string if not set
+ */
+function getEndpointTaxYear(pdfEndpoint) {
+  return getEndpointQueryValue(pdfEndpoint, "taxYear");
+}
+
+/**
+ * @description Check the optional retirement projection fields, as entered.
+ * @param {string} years - Years to project
+ * @param {string} expectedReturn - Expected return in percent a year
+ * @param {string} volatility - Volatility in percent a year
+ * @returns {string|null} An error message, or null if the fields are valid
+ */
+function checkProjectionFields(years, expectedReturn, volatility) {
+  if (years && (!/^\d+$/.test(years) || Number(years) < 1 || Number(years) > 60)) {
+    return "Years must be a whole number from 1 to 60";
+  }
+  if (expectedReturn && (isNaN(Number(expectedReturn)) || Number(expectedReturn) < -10 || Number(expectedReturn) > 20)) {
+    return "Return must be a percentage from -10 to 20";
+  }
+  if (volatility && (isNaN(Number(volatility)) || Number(volatility) < 0 || Number(volatility) > 50)) {
+    return "Volatility must be a percentage from 0 to 50";
+  }
+  return null;
+}
+
+/** @type {Array<{value: string, label: string}>} Period choices for the correlation matrix */
+const CORRELATION_PERIOD_OPTIONS = [
+  { value: "3m", label: "3 months" }, { value: "6m", label: "6 months" },
+  { value: "1y", label: "1 year" }, { value: "2y", label: "2 years" }, { value: "3y", label: "3 years" },
+];
+
+/** @type {Array<{value: string, label: string}>} Holdings choices for the correlation matrix */
+const CORRELATION_HOLDINGS_OPTIONS = [
+  { value: "current", label: "Current holdings" }, { value: "historic", label: "Historic holdings only" },
+  { value: "all", label: "All investments" },
+];
+
+/** @type {Array<{value: string, label: string}>} Months choices for the other assets chart */
+const OTHER_ASSETS_MONTHS_OPTIONS = [
+  { value: "12", label: "12 months" }, { value: "24", label: "24 months" },
+  { value: "36", label: "36 months" }, { value: "60", label: "60 months" },
+];
+
+/** @type {Array<{value: string, label: string}>} Line choices for the other assets chart */
+const OTHER_ASSETS_SERIES_OPTIONS = [
+  { value: "category", label: "A line for each category" }, { value: "asset", label: "A line for each asset" },
+];
+
+/** @type {string} Hint for the other assets chart category params */
+const OTHER_ASSETS_CATEGORY_HINT = "pension, property, savings, alternative or liability. Leave empty for every category.";
+
 /**
  * @description Get a human-readable label for a report type.
  * @param {Object} report - A report definition object
@@ -453,6 +532,28 @@ function showReportModal(type, editIndex) {
     ]);
     html += buildDynamicList("rpt-params", "Portfolios", report.params || [""], "USER1:isa+sipp+trading",
       'Format: USER:ACCOUNT_TYPE (e.g. USER1:isa, USER2:isa+sipp+trading). ' + tokenHint());
+  } else if (type === "isa_allowance") {
+    html += buildDynamicList("rpt-params", "Users", report.params || [""], "e.g. USER1", "Leave empty for everyone holding an ISA. " + tokenHint());
+    html += buildTextField("rpt-taxyear", "Tax Year (optional)", getEndpointTaxYear(report.pdfEndpoint), "e.g. 2025/2026", "Defaults to the current tax year.");
+  } else if (type === "p60_summary") {
+    html += buildDynamicList("rpt-params", "Users", report.params || [""], "e.g. USER1", "Leave empty for everyone drawing from a SIPP. " + tokenHint());
+    html += buildTextField("rpt-taxyear", "Tax Year (optional)", getEndpointTaxYear(report.pdfEndpoint), "e.g. 2025/2026", "Defaults to the current tax year.");
+  } else if (type === "retirement_projection") {
+    html += buildDynamicList("rpt-params", "Users", report.params || [""], "e.g. USER1", "Leave empty for everyone with a SIPP. " + tokenHint());
+    html += buildTextField("rpt-years", "Years to Project (optional)", getEndpointQueryValue(report.pdfEndpoint, "years"), "e.g. 40", "Defaults to the retirementProjection setting.");
+    html += buildTextField("rpt-return", "Return % a year (optional)", getEndpointQueryValue(report.pdfEndpoint, "return"), "e.g. 5", "Leave empty to estimate from price history.");
+    html += buildTextField("rpt-volatility", "Volatility % a year (optional)", getEndpointQueryValue(report.pdfEndpoint, "volatility"), "e.g. 12", "Leave empty to estimate from price history.");
+  } else if (type === "correlation_matrix") {
+    html += buildDynamicList("rpt-params", "Users and Benchmarks", report.params || [""], "e.g. USER1 or bm:FTSE 100", "Leave out users to include everyone. " + tokenHint());
+    html += buildSelect("rpt-period", "Period", getEndpointQueryValue(report.pdfEndpoint, "period") || "1y", CORRELATION_PERIOD_OPTIONS);
+    html += buildSelect("rpt-holdings", "Investments", getEndpointQueryValue(report.pdfEndpoint, "holdings") || "current", CORRELATION_HOLDINGS_OPTIONS);
+  } else if (type === "other_assets_chart") {
+    html += buildTextField("rpt-subtitle", "Subtitle (optional)", report.subTitle || "", "e.g. Property and mortgage");
+    html += buildSelect("rpt-months", "Months to Show", report.monthsToShow || "24", OTHER_ASSETS_MONTHS_OPTIONS);
+    html += buildSelect("rpt-seriesby", "Lines", report.seriesBy || "category", OTHER_ASSETS_SERIES_OPTIONS);
+    html += buildDynamicList("rpt-params", "Categories", report.params || [""], "e.g. property", OTHER_ASSETS_CATEGORY_HINT);
+  } else if (type === "iht_estate") {
+    html += buildDynamicList("rpt-params", "Users", report.params || [""], "e.g. USER1", "Leave empty for every family member. " + tokenHint());
   } else if (type === "composite") {
     html += buildCompositeBlocksEditor(report.blocks || []);
   }
@@ -698,6 +799,12 @@ function buildCompositeBlocksEditor(blocks) {
   html += '<option value="chart">Performance Chart</option>';
   html += '<option value="chart_group">Chart Group</option>';
   html += '<option value="portfolio_value_chart">Portfolio Value Chart</option>';
+  html += '<option value="isa_allowance">ISA Allowance</option>';
+  html += '<option value="p60_summary">Pension Income (P60)</option>';
+  html += '<option value="retirement_projection">Retirement Projection</option>';
+  html += '<option value="correlation_matrix">Correlation Matrix</option>';
+  html += '<option value="other_assets_chart">Other Assets Chart</option>';
+  html += '<option value="iht_estate">Inheritance Tax Estate</option>';
   html += '</select>';
   html += '<button type="button" class="text-sm text-brand-600 hover:text-brand-800" onclick="addCompositeBlock()">+ Add block</button>';
   html += '</div>';
@@ -772,6 +879,29 @@ function buildCompositeBlockCard(index, block) {
       { value: "percent", label: "Percentage change" }, { value: "value", label: "GBP value" },
     ]);
     html += buildDynamicList(prefix + "-params", "Portfolios", block.params || [""], "USER1:isa+sipp+trading", tokenHint());
+  } else if (blockType === "isa_allowance") {
+    html += buildDynamicList(prefix + "-params", "Users", block.params || [""], "e.g. USER1", "Leave empty for everyone holding an ISA. " + tokenHint());
+    html += buildTextField(prefix + "-taxyear", "Tax Year (optional)", block.taxYear || "", "e.g. 2025/2026", "Defaults to the current tax year.");
+  } else if (blockType === "p60_summary") {
+    html += buildDynamicList(prefix + "-params", "Users", block.params || [""], "e.g. USER1", "Leave empty for everyone drawing from a SIPP. " + tokenHint());
+    html += buildTextField(prefix + "-taxyear", "Tax Year (optional)", block.taxYear || "", "e.g. 2025/2026", "Defaults to the current tax year.");
+  } else if (blockType === "retirement_projection") {
+    html += buildDynamicList(prefix + "-params", "Users", block.params || [""], "e.g. USER1", "Leave empty for everyone with a SIPP. " + tokenHint());
+    html += buildTextField(prefix + "-years", "Years to Project (optional)", block.years || "", "e.g. 40", "Defaults to the retirementProjection setting.");
+    html += buildTextField(prefix + "-return", "Return % a year (optional)", block.expectedReturn || "", "e.g. 5", "Leave empty to estimate from price history.");
+    html += buildTextField(prefix + "-volatility", "Volatility % a year (optional)", block.volatility || "", "e.g. 12", "Leave empty to estimate from price history.");
+  } else if (blockType === "correlation_matrix") {
+    html += buildDynamicList(prefix + "-params", "Users and Benchmarks", block.params || [""], "e.g. USER1 or bm:FTSE 100", "Leave out users to include everyone. " + tokenHint());
+    html += buildSelect(prefix + "-period", "Period", block.period || "1y", CORRELATION_PERIOD_OPTIONS);
+    html += buildSelect(prefix + "-holdings", "Investments", block.holdings || "current", CORRELATION_HOLDINGS_OPTIONS);
+  } else if (blockType === "other_assets_chart") {
+    html += buildTextField(prefix + "-title", "Chart Title", block.title || "", "e.g. Other Assets");
+    html += buildTextField(prefix + "-subtitle", "Subtitle (optional)", block.subTitle || "", "e.g. Property and mortgage");
+    html += buildSelect(prefix + "-months", "Months to Show", block.monthsToShow || "24", OTHER_ASSETS_MONTHS_OPTIONS);
+    html += buildSelect(prefix + "-seriesby", "Lines", block.seriesBy || "category", OTHER_ASSETS_SERIES_OPTIONS);
+    html += buildDynamicList(prefix + "-params", "Categories", block.params || [""], "e.g. property", OTHER_ASSETS_CATEGORY_HINT);
+  } else if (blockType === "iht_estate") {
+    html += buildDynamicList(prefix + "-params", "Users", block.params || [""], "e.g. USER1", "Leave empty for every family member. " + tokenHint());
   }
 
   html += '</div></div>';
@@ -935,6 +1065,30 @@ function collectCompositeBlocks() {
       block.showGlobalEvents = getChecked(prefix + "-globalevents");
       block.showPercentOrValue = getVal(prefix + "-pctval");
       block.params = collectDynamicList(prefix + "-params");
+    } else if (blockType === "isa_allowance" || blockType === "p60_summary") {
+      block.params = collectDynamicList(prefix + "-params");
+      const taxYear = getVal(prefix + "-taxyear");
+      if (taxYear) block.taxYear = taxYear;
+    } else if (blockType === "retirement_projection") {
+      block.params = collectDynamicList(prefix + "-params");
+      const years = getVal(prefix + "-years");
+      const expectedReturn = getVal(prefix + "-return");
+      const volatility = getVal(prefix + "-volatility");
+      if (years) block.years = years;
+      if (expectedReturn) block.expectedReturn = expectedReturn;
+      if (volatility) block.volatility = volatility;
+    } else if (blockType === "correlation_matrix") {
+      block.params = collectDynamicList(prefix + "-params");
+      block.period = getVal(prefix + "-period");
+      block.holdings = getVal(prefix + "-holdings");
+    } else if (blockType === "other_assets_chart") {
+      block.title = getVal(prefix + "-title");
+      block.subTitle = getVal(prefix + "-subtitle");
+      block.monthsToShow = getVal(prefix + "-months");
+      block.seriesBy = getVal(prefix + "-seriesby");
+      block.params = collectDynamicList(prefix + "-params");
+    } else if (blockType === "iht_estate") {
+      block.params = collectDynamicList(prefix + "-params");
     }
 
     blocks.push(block);
@@ -1064,6 +1218,52 @@ async function saveReport(type, editIndex) {
     report.showGlobalEvents = getChecked("rpt-globalevents");
     report.showPercentOrValue = getVal("rpt-pctval");
     report.params = collectDynamicList("rpt-params");
+  } else if (type === "isa_allowance") {
+    const taxYear = getVal("rpt-taxyear");
+    if (taxYear && !/^\d{4}\/\d{4}$/.test(taxYear)) {
+      showError("rpt-modal-messages", "Tax year must be in the form YYYY/YYYY (e.g. 2025/2026)");
+      return;
+    }
+    report.pdfEndpoint = "/api/reports/pdf/isa-allowance" + (taxYear ? "?taxYear=" + encodeURIComponent(taxYear) : "");
+    report.params = collectDynamicList("rpt-params");
+  } else if (type === "p60_summary") {
+    const taxYear = getVal("rpt-taxyear");
+    if (taxYear && !/^\d{4}\/\d{4}$/.test(taxYear)) {
+      showError("rpt-modal-messages", "Tax year must be in the form YYYY/YYYY (e.g. 2025/2026)");
+      return;
+    }
+    report.pdfEndpoint = "/api/reports/pdf/p60-summary" + (taxYear ? "?taxYear=" + encodeURIComponent(taxYear) : "");
+    report.params = collectDynamicList("rpt-params");
+  } else if (type === "retirement_projection") {
+    const years = getVal("rpt-years");
+    const expectedReturn = getVal("rpt-return");
+    const volatility = getVal("rpt-volatility");
+    const fieldError = checkProjectionFields(years, expectedReturn, volatility);
+    if (fieldError) {
+      showError("rpt-modal-messages", fieldError);
+      return;
+    }
+    const query = new URLSearchParams();
+    if (years) query.set("years", years);
+    if (expectedReturn) query.set("return", expectedReturn);
+    if (volatility) query.set("volatility", volatility);
+    report.pdfEndpoint = "/api/reports/pdf/retirement-projection" + (query.toString() ? "?" + query.toString() : "");
+    report.params = collectDynamicList("rpt-params");
+  } else if (type === "correlation_matrix") {
+    const query = new URLSearchParams();
+    query.set("period", getVal("rpt-period"));
+    query.set("holdings", getVal("rpt-holdings"));
+    report.pdfEndpoint = "/api/reports/pdf/correlation-matrix?" + query.toString();
+    report.params = collectDynamicList("rpt-params");
+  } else if (type === "other_assets_chart") {
+    report.pdfEndpoint = "/api/reports/pdf/other-assets-chart";
+    report.subTitle = getVal("rpt-subtitle");
+    report.monthsToShow = getVal("rpt-months");
+    report.seriesBy = getVal("rpt-seriesby");
+    report.params = collectDynamicList("rpt-params");
+  } else if (type === "iht_estate") {
+    report.pdfEndpoint = "/api/reports/pdf/iht-estate";
+    report.params = collectDynamicList("rpt-params");
   } else if (type === "composite") {
     report.blocks = collectCompositeBlocks();
     if (report.blocks.length === 0) {
@@ -1091,6 +1291,13 @@ async function saveReport(type, editIndex) {
           blk.charts[bci].params = blkPanelValidation.values;
         }
       }
+      if (blk.type === "retirement_projection") {
+        const blkFieldError = checkProjectionFields(blk.years || "", blk.expectedReturn || "", blk.volatility || "");
+        if (blkFieldError) {
+          showError("rpt-modal-messages", "Block " + (bi + 1) + " — " + blkFieldError);
+          return;
+        }
+      }
     }
   }
 
@@ -12,6 +12,9 @@ let deleteUserName = "";
 /** @type {Array<{code: string, name: string}>} Cached list of allowed providers */
 let providers = [];
 
+/** @type {Object[]} Cached list of users, for the spouse dropdown */
+let users = [];
+
 /**
  * @description Load the list of allowed providers from the config API
  * and populate the provider dropdown.
@@ -45,6 +48,28 @@ function populateProviderDropdown(selectedCode) {
   }
 }
 
+/**
+ * @description Populate the spouse <select> element with every other user,
+ * leaving out the Joint household user.
+ * @param {number|null} [selectedId] - The spouse user ID to pre-select
+ * @param {number|null} [userId] - The user being edited, left out of the list
+ */
+function populateSpouseDropdown(selectedId, userId) {
+  const select = document.getElementById("spouse_user_id");
+  select.innerHTML = '<option value="">None</option>';
+
+  for (const user of users) {
+    if (user.id === userId || user.first_name === "Joint") continue;
+    const option = document.createElement("option");
+    option.value = user.id;
+    option.textContent = user.first_name + " " + user.last_name + " (" + user.initials + ")";
+    if (selectedId && user.id === selectedId) {
+      option.selected = true;
+    }
+    select.appendChild(option);
+  }
+}
+
 /**
  * @description Get the display name for a provider code.
  * @param {string} code - The provider code
@@ -91,7 +116,7 @@ async function loadUsers() {
     return;
   }
 
-  const users = result.data;
+  users = result.data;
 
   if (users.length === 0) {
     container.innerHTML = '<p class="text-brand-500">No users yet. Click "Add User" to create one.</p>';
@@ -146,6 +171,7 @@ function showAddForm() {
   document.getElementById("form-errors").textContent = "";
   document.getElementById("delete-from-form-btn").classList.add("hidden");
   populateProviderDropdown();
+  populateSpouseDropdown();
   document.getElementById("user-form-container").classList.remove("hidden");
   // Focus the first field after a brief delay to ensure modal is visible
   setTimeout(function () {
@@ -173,10 +199,12 @@ async function editUser(id) {
   document.getElementById("last_name").value = user.last_name;
   document.getElementById("ni_number").value = user.ni_number || "";
   document.getElementById("utr").value = user.utr || "";
+  document.getElementById("date_of_birth").value = user.date_of_birth || "";
   populateProviderDropdown(user.provider);
   document.getElementById("trading_ref").value = user.trading_ref || "";
   document.getElementById("isa_ref").value = user.isa_ref || "";
   document.getElementById("sipp_ref").value = user.sipp_ref || "";
+  populateSpouseDropdown(user.spouse_user_id, user.id);
   document.getElementById("form-errors").textContent = "";
 
   // Show the delete link when editing
@@ -219,10 +247,12 @@ async function handleFormSubmit(event) {
     last_name: document.getElementById("last_name").value.trim(),
     ni_number: document.getElementById("ni_number").value.trim() || null,
     utr: document.getElementById("utr").value.trim() || null,
+    date_of_birth: document.getElementById("date_of_birth").value || null,
     provider: document.getElementById("provider").value,
     trading_ref: document.getElementById("trading_ref").value.trim() || null,
     isa_ref: document.getElementById("isa_ref").value.trim() || null,
     sipp_ref: document.getElementById("sipp_ref").value.trim() || null,
+    spouse_user_id: document.getElementById("spouse_user_id").value ? Number(document.getElementById("spouse_user_id").value) : null,
   };
 
   let result;
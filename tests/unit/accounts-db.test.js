@@ -137,12 +137,17 @@ describe("createAccount", () => {
     expect(account.account_type).toBe("isa");
   });
 
-  test("throws on duplicate user_id + account_type", () => {
+  test("defaults the provider to the user's provider", () => {
+    const accounts = getAccountsByUserId(testUser.id);
+    expect(accounts.every((a) => a.provider === "ii")).toBe(true);
+  });
+
+  test("throws on duplicate user_id + account_type + account_ref", () => {
     expect(() => {
       createAccount({
         user_id: testUser.id,
         account_type: "sipp",
-        account_ref: "99999",
+        account_ref: "12345",
         cash_balance: 0,
         warn_cash: 0,
       });
@@ -257,3 +262,39 @@ describe("deleteAccount", () => {
     expect(accounts.length).toBe(2);
   });
 });
+
+describe("multiple accounts of the same type", () => {
+  test("allows a second account of a type with another provider", () => {
+    const first = createAccount({
+      user_id: testUser.id,
+      account_type: "isa",
+      account_ref: "I1001",
+      cash_balance: 0,
+      warn_cash: 0,
+    });
+    const second = createAccount({
+      user_id: testUser.id,
+      account_type: "isa",
+      account_ref: "3366521",
+      provider: "hl",
+      cash_balance: 0,
+      warn_cash: 0,
+    });
+
+    expect(first.provider).toBe("ii");
+    expect(second.provider).toBe("hl");
+
+    const isas = getAccountsByUserId(testUser.id).filter((a) => a.account_type === "isa");
+    expect(isas.map((a) => a.id)).toEqual([first.id, second.id]);
+  });
+
+  test("updates the provider and keeps it when not supplied", () => {
+    const isa = getAccountsByUserId(testUser.id).find((a) => a.account_ref === "I1001");
+
+    const moved = updateAccount(isa.id, { account_ref: "I1001", provider: "aj", cash_balance: 0, warn_cash: 0 });
+    expect(moved.provider).toBe("aj");
+
+    const unchanged = updateAccount(isa.id, { account_ref: "I1001", cash_balance: 0, warn_cash: 0 });
+    expect(unchanged.provider).toBe("aj");
+  });
+});
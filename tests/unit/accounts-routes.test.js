@@ -133,13 +133,36 @@ describe("Accounts Routes - CRUD", () => {
     createdAccountId = account.id;
   });
 
-  test("POST /api/users/:userId/accounts with duplicate type returns 400", async () => {
+  test("POST /api/users/:userId/accounts defaults the provider to the user's provider", async () => {
+    const response = await fetch(`${BASE_URL}/api/accounts/${createdAccountId}`);
+    const account = await response.json();
+    expect(account.provider).toBe("ii");
+  });
+
+  test("POST /api/users/:userId/accounts with invalid provider returns 400", async () => {
+    const response = await fetch(`${BASE_URL}/api/users/${testUserId}/accounts`, {
+      method: "POST",
+      headers: { "Content-Type": "application/json" },
+      body: JSON.stringify({
+        account_type: "isa",
+        account_ref: "55555",
+        provider: "zz",
+        cash_balance: 0,
+        warn_cash: 0,
+      }),
+    });
+    expect(response.status).toBe(400);
+    const data = await response.json();
+    expect(data.detail).toContain("Provider must be one of");
+  });
+
+  test("POST /api/users/:userId/accounts with duplicate type and reference returns 400", async () => {
     const response = await fetch(`${BASE_URL}/api/users/${testUserId}/accounts`, {
       method: "POST",
       headers: { "Content-Type": "application/json" },
       body: JSON.stringify({
         account_type: "sipp",
-        account_ref: "99999",
+        account_ref: "12345",
         cash_balance: 0,
         warn_cash: 0,
       }),
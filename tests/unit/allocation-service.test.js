@@ -0,0 +1,181 @@
+// Set isolated DB path BEFORE importing connection.js
+process.env.DB_PATH = "data/portfolio_60_test/test-allocation-service.db";
+
+import { describe, test, expect, beforeAll, afterAll } from "bun:test";
+import { existsSync, unlinkSync } from "node:fs";
+import { createDatabase, closeDatabase, getDatabasePath, getDatabase } from "../../src/server/db/connection.js";
+import { createUser } from "../../src/server/db/users-db.js";
+import { getAllCurrencies, createCurrency } from "../../src/server/db/currencies-db.js";
+import { createInvestment } from "../../src/server/db/investments-db.js";
+import { getAllInvestmentTypes } from "../../src/server/db/investment-types-db.js";
+import { createAccount } from "../../src/server/db/accounts-db.js";
+import { createHolding, scaleQuantity } from "../../src/server/db/holdings-db.js";
+import { upsertPrice } from "../../src/server/db/prices-db.js";
+import { upsertRate, scaleRate } from "../../src/server/db/currency-rates-db.js";
+import { buildAllocation, buildAllocationHistory, buildMonthEndDates } from "../../src/server/services/allocation-service.js";
+
+const testDbPath = getDatabasePath();
+
+/**
+ * @description Clean up the isolated test database files.
+ */
+function cleanupDatabase() {
+  closeDatabase();
+  for (const suffix of ["", "-wal", "-shm"]) {
+    const filePath = testDbPath + suffix;
+    if (existsSync(filePath)) {
+      unlinkSync(filePath);
+    }
+  }
+}
+
+/**
+ * @description Create a user with no account references.
+ * @param {string} initials - User initials
+ * @param {string} firstName - First name
+ * @returns {number} The new user ID
+ */
+function makeUser(initials, firstName) {
+  return createUser({
+    initials: initials,
+    first_name: firstName,
+    last_name: "Collins",
+    ni_number: "",
+    utr: "",
+    provider: "ii",
+    trading_ref: "",
+    isa_ref: "",
+    sipp_ref: "",
+  }).id;
+}
+
+let robertId;
+let janeId;
+let historyUserId;
+let isaAccountId;
+let shareId;
+let fundId;
+
+beforeAll(() => {
+  cleanupDatabase();
+  createDatabase();
+
+  const gbpId = getAllCurrencies().find((c) => c.code === "GBP").id;
+  const usdId = createCurrency({ code: "USD", description: "US Dollar" }).id;
+  const types = getAllInvestmentTypes();
+  const shareTypeId = types.find((t) => t.short_description === "SHARE").id;
+  const mutualTypeId = types.find((t) => t.short_description === "MUTUAL").id;
+
+  // £10.00 share tagged UK Equity; $200 fund left untagged, at 2 USD to the pound
+  shareId = createInvestment({ currencies_id: gbpId, investment_type_id: shareTypeId, description: "UK Share", allocation_tag: "UK Equity" }).id;
+  fundId = createInvestment({ currencies_id: usdId, investment_type_id: mutualTypeId, description: "US Fund" }).id;
+  upsertPrice(shareId, "2000-01-01", "16:30:00", 1000);
+  upsertPrice(fundId, "2000-01-01", "21:00:00", 20000);
+  upsertRate(usdId, "2000-01-01", "21:00:00", scaleRate(2));
+
+  // Robert: ISA with £1,000 cash, £1,000 of shares and £1,000 of the fund;
+  // trading account with £500 of shares and no cash
+  robertId = makeUser("RC", "Robert");
+  isaAccountId = createAccount({ user_id: robertId, account_type: "isa", account_ref: "I1", cash_balance: 1000, warn_cash: 0 }).id;
+  const tradingAccountId = createAccount({ user_id: robertId, account_type: "trading", account_ref: "T1", cash_balance: 0, warn_cash: 0 }).id;
+  createHolding({ account_id: isaAccountId, investment_id: shareId, quantity: 100, average_cost: 10 });
+  createHolding({ account_id: isaAccountId, investment_id: fundId, quantity: 10, average_cost: 200 });
+  createHolding({ account_id: tradingAccountId, investment_id: shareId, quantity: 50, average_cost: 10 });
+
+  // Jane: trading account holding £500 of cash only
+  janeId = makeUser("JC", "Jane");
+  createAccount({ user_id: janeId, account_type: "trading", account_ref: "T2", cash_balance: 500, warn_cash: 0 });
+});
+
+afterAll(() => {
+  cleanupDatabase();
+  delete process.env.DB_PATH;
+});
+
+describe("Allocation Service - buildAllocation", function () {
+  test("breaks the household down by type, currency and tag with cash as its own group", function () {
+    const result = buildAllocation({ userIds: [robertId, janeId] });
+
+    expect(result.total).toBe(4000);
+    expect(result.investments).toBe(2500);
+    expect(result.cash).toBe(1500);
+    expect(result.by_type).toEqual([
+      { label: "Cash", value: 1500, percent: 37.5 },
+      { label: "Shares", value: 1500, percent: 37.5 },
+      { label: "Mutual Funds", value: 1000, percent: 25 },
+    ]);
+    expect(result.by_currency).toEqual([
+      { label: "GBP", value: 3000, percent: 75 },
+      { label: "USD", value: 1000, percent: 25 },
+    ]);
+    expect(result.by_tag.map((row) => row.label)).toEqual(["Cash", "UK Equity", "Untagged"]);
+  });
+
+  test("limits the breakdown to one person", function () {
+    const result = buildAllocation({ userIds: [janeId] });
+    expect(result.total).toBe(500);
+    expect(result.by_type).toEqual([{ label: "Cash", value: 500, percent: 100 }]);
+  });
+
+  test("limits the breakdown to one account", function () {
+    const result = buildAllocation({ userIds: [robertId, janeId], accountId: isaAccountId });
+    expect(result.total).toBe(3000);
+    expect(result.by_currency[0]).toEqual({ label: "GBP", value: 2000, percent: 66.67 });
+  });
+
+  test("limits the breakdown to account types", function () {
+    const result = buildAllocation({ userIds: [robertId], accountTypes: ["trading"] });
+    expect(result.total).toBe(500);
+    expect(result.by_tag).toEqual([{ label: "UK Equity", value: 500, percent: 100 }]);
+  });
+
+  test("excludes cash balances when asked", function () {
+    const result = buildAllocation({ userIds: [robertId, janeId], includeCash: false });
+    expect(result.total).toBe(2500);
+    expect(result.cash).toBe(0);
+    expect(result.by_type).toEqual([
+      { label: "Shares", value: 1500, percent: 60 },
+      { label: "Mutual Funds", value: 1000, percent: 40 },
+    ]);
+  });
+});
+
+describe("Allocation Service - buildMonthEndDates", function () {
+  test("returns previous month ends then today", function () {
+    expect(buildMonthEndDates(3, "2026-03-15")).toEqual(["2025-12-31", "2026-01-31", "2026-02-28", "2026-03-15"]);
+  });
+
+  test("crosses year ends and leap years", function () {
+    expect(buildMonthEndDates(2, "2024-03-10")).toEqual(["2024-01-31", "2024-02-29", "2024-03-10"]);
+  });
+});
+
+describe("Allocation Service - buildAllocationHistory", function () {
+  test("shows drift from SCD2 holdings switched between month ends", function () {
+    const today = new Date().toISOString().slice(0, 10);
+    const dates = buildMonthEndDates(2, today);
+
+    // The share is sold at the second month end and the fund bought in its place
+    historyUserId = makeUser("HC", "Harriet");
+    const accountId = createAccount({ user_id: historyUserId, account_type: "isa", account_ref: "I3", cash_balance: 0, warn_cash: 0 }).id;
+    const db = getDatabase();
+    db.run("INSERT INTO holdings (account_id, investment_id, quantity, average_cost, effective_from, effective_to) VALUES (?, ?, ?, ?, ?, ?)", [accountId, shareId, scaleQuantity(100), scaleQuantity(10), "2000-01-01", dates[1]]);
+    db.run("INSERT INTO holdings (account_id, investment_id, quantity, average_cost, effective_from) VALUES (?, ?, ?, ?, ?)", [accountId, fundId, scaleQuantity(5), scaleQuantity(200), dates[1]]);
+
+    const result = buildAllocationHistory({ userIds: [historyUserId] }, "type", 2);
+
+    expect(result.dimension).toBe("type");
+    expect(result.dates).toEqual(dates);
+    expect(result.totals).toEqual([1000, 500, 500]);
+    expect(result.series).toEqual([
+      { label: "Mutual Funds", percents: [0, 100, 100] },
+      { label: "Shares", percents: [100, 0, 0] },
+    ]);
+  });
+
+  test("flags month ends with no cash history", function () {
+    const result = buildAllocationHistory({ userIds: [historyUserId] }, "currency", 2);
+    expect(result.cash_available).toEqual([false, false, false]);
+    expect(result.series.map((s) => s.label)).toEqual(["USD", "GBP"]);
+  });
+});
@@ -0,0 +1,228 @@
+// Set isolated DB path BEFORE importing connection.js
+process.env.DB_PATH = "data/portfolio_60_test/test-analysis-service.db";
+
+import { describe, test, expect, beforeAll, afterAll } from "bun:test";
+import { existsSync, unlinkSync } from "node:fs";
+import { createDatabase, closeDatabase, getDatabasePath } from "../../src/server/db/connection.js";
+import { getAllCurrencies } from "../../src/server/db/currencies-db.js";
+import { createInvestment } from "../../src/server/db/investments-db.js";
+import { getAllInvestmentTypes } from "../../src/server/db/investment-types-db.js";
+import { upsertPrice } from "../../src/server/db/prices-db.js";
+import { createBenchmark } from "../../src/server/db/benchmarks-db.js";
+import { upsertBenchmarkData } from "../../src/server/db/benchmark-data-db.js";
+import { formatISODate } from "../../src/server/services/price-utils.js";
+import { calculateMaxDrawdown, calculateRiskRatios, calculateBeta, toWeeklyReturns, resolveRiskFreeRate, buildCorrelationMatrix } from "../../src/server/services/analysis-service.js";
+
+const testDbPath = getDatabasePath();
+
+/**
+ * @description Clean up the isolated test database files.
+ */
+function cleanupDatabase() {
+  closeDatabase();
+  for (const suffix of ["", "-wal", "-shm"]) {
+    const filePath = testDbPath + suffix;
+    if (existsSync(filePath)) {
+      unlinkSync(filePath);
+    }
+  }
+}
+
+/** @description Weekly returns used to build the correlation test prices, oldest first */
+const WEEKLY_RETURNS = [0.02, -0.01, 0.03, -0.02, 0.01, 0.015, -0.005, 0.02, -0.03, 0.01, 0.005, -0.01, 0.02];
+
+/**
+ * @description The date a number of whole weeks before today, matching the
+ * weekly sample dates the analysis service uses.
+ * @param {number} weeksAgo - Weeks before today
+ * @returns {string} ISO-8601 date
+ */
+function weeksBeforeToday(weeksAgo) {
+  const date = new Date();
+  date.setDate(date.getDate() - weeksAgo * 7);
+  return formatISODate(date);
+}
+
+/**
+ * @description Build a price series a week apart ending today from weekly returns.
+ * @param {number} start - The first price
+ * @param {number[]} returns - Weekly returns, oldest first
+ * @returns {Array<{date: string, price: number}>} Prices, oldest first
+ */
+function seriesFromReturns(start, returns) {
+  const points = [{ date: weeksBeforeToday(returns.length), price: start }];
+  let price = start;
+  for (let i = 0; i < returns.length; i++) {
+    price = price * (1 + returns[i]);
+    points.push({ date: weeksBeforeToday(returns.length - i - 1), price: price });
+  }
+  return points;
+}
+
+let trackerId;
+let leveragedId;
+let inverseId;
+let newIssueId;
+let benchmarkId;
+
+beforeAll(() => {
+  cleanupDatabase();
+  createDatabase();
+
+  const gbpId = getAllCurrencies().find((c) => c.code === "GBP").id;
+  const typeId = getAllInvestmentTypes().find((t) => t.short_description === "SHARE").id;
+
+  /**
+   * @description Create a GBP investment with the given prices.
+   * @param {string} description - Investment description
+   * @param {Array<{date: string, price: number}>} points - Prices to store
+   * @returns {number} The investment ID
+   */
+  function addInvestment(description, points) {
+    const id = createInvestment({ currencies_id: gbpId, investment_type_id: typeId, description: description }).id;
+    for (const point of points) {
+      upsertPrice(id, point.date, "16:30:00", point.price);
+    }
+    return id;
+  }
+
+  const inverseReturns = WEEKLY_RETURNS.map(function (r) {
+    return -r;
+  });
+  trackerId = addInvestment("Tracker Fund", seriesFromReturns(100, WEEKLY_RETURNS));
+  leveragedId = addInvestment("Another Tracker", seriesFromReturns(250, WEEKLY_RETURNS));
+  inverseId = addInvestment("Inverse Fund", seriesFromReturns(100, inverseReturns));
+  newIssueId = addInvestment("New Issue", seriesFromReturns(100, WEEKLY_RETURNS.slice(-3)));
+
+  benchmarkId = createBenchmark({ currencies_id: gbpId, benchmark_type: "index", description: "Test Index" }).id;
+  for (const point of seriesFromReturns(7000, WEEKLY_RETURNS)) {
+    upsertBenchmarkData(benchmarkId, point.date, "16:30:00", point.price);
+  }
+});
+
+afterAll(() => {
+  cleanupDatabase();
+  delete process.env.DB_PATH;
+});
+
+/**
+ * @description Build price records a week apart from a list of prices.
+ * @param {number[]} values - Prices, oldest first
+ * @returns {Array<{price_date: string, price: number}>} Price records starting 2 January 2026
+ */
+function weeklyPrices(values) {
+  return values.map(function (price, i) {
+    const date = new Date(Date.UTC(2026, 0, 2 + i * 7));
+    return { price_date: date.toISOString().slice(0, 10), price: price };
+  });
+}
+
+describe("Analysis Service - calculateMaxDrawdown", function () {
+  test("finds the largest fall from a peak and the time back to it", function () {
+    const result = calculateMaxDrawdown(weeklyPrices([100, 120, 90, 100, 125]));
+    expect(result.maxDrawdownPct).toBeCloseTo(-25, 6);
+    expect(result.peakDate).toBe("2026-01-09");
+    expect(result.troughDate).toBe("2026-01-16");
+    expect(result.recoveryDays).toBe(14);
+  });
+
+  test("leaves the recovery time null while the price is still below the peak", function () {
+    const result = calculateMaxDrawdown(weeklyPrices([100, 80, 90]));
+    expect(result.maxDrawdownPct).toBeCloseTo(-20, 6);
+    expect(result.recoveryDays).toBeNull();
+  });
+
+  test("reports no drawdown for a price that only rises", function () {
+    const result = calculateMaxDrawdown(weeklyPrices([100, 101, 105]));
+    expect(result.maxDrawdownPct).toBe(0);
+    expect(result.troughDate).toBeNull();
+  });
+});
+
+describe("Analysis Service - calculateRiskRatios", function () {
+  test("annualises the Sharpe and Sortino ratios of weekly returns", function () {
+    const returns = [null, 0.02, -0.01, 0.02, -0.01, 0.02, -0.01, 0.02, -0.01, 0.02, -0.01];
+    const result = calculateRiskRatios(returns, 0);
+    expect(result.sharpe).toBeCloseTo(2.2804, 3);
+    expect(result.sortino).toBeCloseTo(5.099, 3);
+  });
+
+  test("a higher risk-free rate lowers both ratios", function () {
+    const returns = [null, 0.02, -0.01, 0.02, -0.01, 0.02, -0.01, 0.02, -0.01, 0.02, -0.01];
+    const base = calculateRiskRatios(returns, 0);
+    const withRate = calculateRiskRatios(returns, 5);
+    expect(withRate.sharpe).toBeLessThan(base.sharpe);
+    expect(withRate.sortino).toBeLessThan(base.sortino);
+  });
+
+  test("returns null with fewer than eight weekly returns", function () {
+    expect(calculateRiskRatios([null, 0.01, 0.02, -0.01], 0)).toEqual({ sharpe: null, sortino: null });
+  });
+});
+
+describe("Analysis Service - calculateBeta", function () {
+  test("measures beta and correlation over the weeks both series cover", function () {
+    const benchmark = [null, 0.01, -0.02, 0.015, 0.005, -0.01, 0.02, -0.005, 0.01, 0.03];
+    const investment = benchmark.map(function (r) {
+      return r === null ? null : r * 2;
+    });
+    investment.push(0.5); // a week the benchmark does not cover is ignored
+
+    const result = calculateBeta(investment, benchmark);
+    expect(result.beta).toBeCloseTo(2, 6);
+    expect(result.correlation).toBeCloseTo(1, 6);
+  });
+
+  test("returns null when too few weeks overlap", function () {
+    expect(calculateBeta([null, 0.01, 0.02], [null, 0.01, 0.03])).toEqual({ beta: null, correlation: null });
+  });
+});
+
+describe("Analysis Service - toWeeklyReturns", function () {
+  test("keeps returns aligned with the weeks, null where a value is missing", function () {
+    expect(toWeeklyReturns([null, 100, 110, null, 121])).toEqual([null, null, 0.1, null, null]);
+  });
+});
+
+describe("Analysis Service - resolveRiskFreeRate", function () {
+  test("uses the configured rate when no series is named", function () {
+    const result = resolveRiskFreeRate(["2026-01-02", "2026-01-09"]);
+    expect(result.source).toBe("config");
+    expect(typeof result.rate).toBe("number");
+  });
+});
+
+describe("Analysis Service - buildCorrelationMatrix", function () {
+  test("correlates weekly returns between investments and benchmarks", function () {
+    const data = buildCorrelationMatrix("3m", [trackerId, leveragedId, inverseId, newIssueId], [benchmarkId]);
+
+    expect(data.periodLabel).toBe("3 Months");
+    expect(data.series.map((s) => [s.label, s.kind])).toEqual([
+      ["Another Tracker", "investment"],
+      ["Inverse Fund", "investment"],
+      ["Tracker Fund", "investment"],
+      ["Test Index", "benchmark"],
+    ]);
+    expect(data.matrix).toEqual([
+      [1, -1, 1, 1],
+      [-1, 1, -1, -1],
+      [1, -1, 1, 1],
+      [1, -1, 1, 1],
+    ]);
+  });
+
+  test("lists investment pairs only, most correlated first", function () {
+    const data = buildCorrelationMatrix("3m", [trackerId, leveragedId, inverseId], [benchmarkId]);
+    expect(data.pairs).toEqual([
+      { a: "Another Tracker", b: "Tracker Fund", correlation: 1 },
+      { a: "Another Tracker", b: "Inverse Fund", correlation: -1 },
+      { a: "Inverse Fund", b: "Tracker Fund", correlation: -1 },
+    ]);
+  });
+
+  test("leaves out series with too few weeks in the period", function () {
+    const data = buildCorrelationMatrix("1m", [trackerId, leveragedId], []);
+    expect(data.series).toEqual([]);
+    expect(data.matrix).toEqual([]);
+  });
+});
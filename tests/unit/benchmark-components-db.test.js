@@ -0,0 +1,208 @@
+// Set isolated DB path BEFORE importing connection.js (which reads it at module load)
+process.env.DB_PATH = "data/portfolio_60_test/test-benchmark-components-db.db";
+
+import { describe, test, expect, beforeAll, afterAll } from "bun:test";
+import { existsSync, unlinkSync } from "node:fs";
+import { createDatabase, closeDatabase, getDatabasePath } from "../../src/server/db/connection.js";
+import { getAllCurrencies, createCurrency } from "../../src/server/db/currencies-db.js";
+import { upsertRate, scaleRate } from "../../src/server/db/currency-rates-db.js";
+import { createBenchmark, getAllBenchmarks, deleteBenchmark } from "../../src/server/db/benchmarks-db.js";
+import { getBenchmarkComponents, setBenchmarkComponents, getCompositesUsingBenchmark } from "../../src/server/db/benchmark-components-db.js";
+import { upsertBenchmarkData, getLatestBenchmarkData, getBenchmarkDataInRange, getBenchmarkDataCount, getBenchmarkDataByDate, deriveCompositeValues } from "../../src/server/db/benchmark-data-db.js";
+import { validateBenchmark } from "../../src/server/validation.js";
+
+const testDbPath = getDatabasePath();
+
+/**
+ * @description Clean up the isolated test database files only.
+ */
+function cleanupDatabase() {
+  closeDatabase();
+  for (const suffix of ["", "-wal", "-shm"]) {
+    const filePath = testDbPath + suffix;
+    if (existsSync(filePath)) {
+      unlinkSync(filePath);
+    }
+  }
+}
+
+/**
+ * @description Build a value series from date/value pairs.
+ * @param {Array<[string, number]>} pairs - Dates and values, oldest first
+ * @returns {Array<{benchmark_date: string, value: number}>} The series
+ */
+function series(pairs) {
+  return pairs.map(function (pair) {
+    return { benchmark_date: pair[0], value: pair[1] };
+  });
+}
+
+const HALF_AND_HALF = [
+  { component_benchmark_id: 1, weight_percent: 50 },
+  { component_benchmark_id: 2, weight_percent: 50 },
+];
+
+let gbpId;
+let equityId;
+let giltsId;
+let compositeId;
+
+beforeAll(() => {
+  cleanupDatabase();
+  createDatabase();
+
+  gbpId = getAllCurrencies().find((c) => c.code === "GBP").id;
+  equityId = createBenchmark({ currencies_id: gbpId, benchmark_type: "index", description: "Equity Index" }).id;
+  giltsId = createBenchmark({ currencies_id: gbpId, benchmark_type: "index", description: "Gilts Index" }).id;
+
+  upsertBenchmarkData(equityId, "2026-01-30", "16:30:00", 5000);
+  upsertBenchmarkData(equityId, "2026-02-27", "16:30:00", 10000);
+  upsertBenchmarkData(equityId, "2026-03-27", "16:30:00", 5000);
+  upsertBenchmarkData(giltsId, "2026-02-27", "16:30:00", 200);
+  upsertBenchmarkData(giltsId, "2026-03-27", "16:30:00", 200);
+
+  compositeId = createBenchmark({ currencies_id: gbpId, benchmark_type: "composite", description: "60/40 Blend", rebalance_frequency: "monthly" }).id;
+  setBenchmarkComponents(compositeId, [
+    { component_benchmark_id: equityId, weight_percent: 60 },
+    { component_benchmark_id: giltsId, weight_percent: 40 },
+  ]);
+});
+
+afterAll(() => {
+  cleanupDatabase();
+  delete process.env.DB_PATH;
+});
+
+describe("BenchmarkComponents - deriveCompositeValues", () => {
+  const values = {
+    1: series([
+      ["2026-01-30", 100],
+      ["2026-02-27", 200],
+      ["2026-03-27", 100],
+    ]),
+    2: series([
+      ["2026-01-30", 100],
+      ["2026-02-27", 100],
+      ["2026-03-27", 100],
+    ]),
+  };
+
+  test("restores the weights at the start of each month", () => {
+    const result = deriveCompositeValues(HALF_AND_HALF, "monthly", values);
+    expect(result.map((p) => p.value)).toEqual([1000, 1500, 1125]);
+  });
+
+  test("lets the weights drift within a quarter", () => {
+    const result = deriveCompositeValues(HALF_AND_HALF, "quarterly", values);
+    expect(result.map((p) => p.value)).toEqual([1000, 1500, 1000]);
+  });
+
+  test("starts when every component has a value and carries values forward", () => {
+    const result = deriveCompositeValues(HALF_AND_HALF, "monthly", {
+      1: series([
+        ["2026-01-02", 100],
+        ["2026-01-09", 100],
+        ["2026-01-16", 110],
+      ]),
+      2: series([["2026-01-09", 100]]),
+    });
+    expect(result).toEqual(
+      series([
+        ["2026-01-09", 1000],
+        ["2026-01-16", 1050],
+      ]),
+    );
+  });
+
+  test("returns nothing when a component has no values", () => {
+    expect(deriveCompositeValues(HALF_AND_HALF, "monthly", { 1: series([["2026-01-02", 100]]), 2: [] })).toEqual([]);
+  });
+});
+
+describe("BenchmarkComponents - composite benchmark data", () => {
+  test("stores the components, largest weight first", () => {
+    const components = getBenchmarkComponents(compositeId);
+    expect(components.map((c) => [c.description, c.weight_percent])).toEqual([
+      ["Equity Index", 60],
+      ["Gilts Index", 40],
+    ]);
+    expect(getCompositesUsingBenchmark(giltsId)).toEqual(["60/40 Blend"]);
+  });
+
+  test("derives values from the date every component has one", () => {
+    const rows = getBenchmarkDataInRange(compositeId, "2026-01-01", "2026-12-31");
+    expect(rows.map((r) => [r.benchmark_date, r.value])).toEqual([
+      ["2026-02-27", 1000],
+      ["2026-03-27", 700],
+    ]);
+    expect(getLatestBenchmarkData(compositeId).value).toBe(700);
+    expect(getBenchmarkDataCount(compositeId)).toBe(2);
+  });
+
+  test("converts each component to GBP before weighting", () => {
+    const usd = getAllCurrencies().find((c) => c.code === "USD") || createCurrency({ code: "USD", description: "US Dollar" });
+    upsertRate(usd.id, "2026-02-27", "16:00:00", scaleRate(1.25));
+    upsertRate(usd.id, "2026-03-27", "16:00:00", scaleRate(1));
+    // Flat in dollars, but up 25% in pounds as sterling falls
+    const usIndexId = createBenchmark({ currencies_id: usd.id, benchmark_type: "index", description: "US Index" }).id;
+    upsertBenchmarkData(usIndexId, "2026-02-27", "16:30:00", 100);
+    upsertBenchmarkData(usIndexId, "2026-03-27", "16:30:00", 100);
+
+    const blendId = createBenchmark({ currencies_id: gbpId, benchmark_type: "composite", description: "Gilts/US Blend", rebalance_frequency: "monthly" }).id;
+    setBenchmarkComponents(blendId, [
+      { component_benchmark_id: giltsId, weight_percent: 50 },
+      { component_benchmark_id: usIndexId, weight_percent: 50 },
+    ]);
+
+    expect(getBenchmarkDataByDate(blendId, "2026-03-27").value).toBe(1125);
+    expect(getBenchmarkDataByDate(blendId, "2026-03-01")).toBeNull();
+    expect(getBenchmarkDataCount(blendId)).toBe(2);
+    expect(deleteBenchmark(blendId).deleted).toBe(true);
+  });
+
+  test("reports the first derived date as the oldest value date", () => {
+    const composite = getAllBenchmarks().find((b) => b.id === compositeId);
+    expect(composite.oldest_value_date).toBe("2026-02-27");
+    expect(composite.rebalance_frequency).toBe("monthly");
+  });
+
+  test("a component cannot be deleted while a composite uses it", () => {
+    const result = deleteBenchmark(equityId);
+    expect(result.deleted).toBe(false);
+    expect(result.reason).toBe("Benchmark is part of the composite 60/40 Blend");
+  });
+
+  test("deleting the composite removes its components and frees them", () => {
+    expect(deleteBenchmark(compositeId).deleted).toBe(true);
+    expect(getBenchmarkComponents(compositeId)).toEqual([]);
+    expect(getCompositesUsingBenchmark(equityId)).toEqual([]);
+  });
+});
+
+describe("BenchmarkComponents - validateBenchmark", () => {
+  const composite = {
+    currencies_id: 1,
+    benchmark_type: "composite",
+    description: "Blend",
+    rebalance_frequency: "quarterly",
+    components: [
+      { component_benchmark_id: 1, weight_percent: 60 },
+      { component_benchmark_id: 2, weight_percent: 40 },
+    ],
+  };
+
+  test("accepts a composite with weights totalling 100%", () => {
+    expect(validateBenchmark(composite)).toEqual([]);
+  });
+
+  test("rejects weights that do not total 100%", () => {
+    const errors = validateBenchmark({ ...composite, components: [composite.components[0], { component_benchmark_id: 2, weight_percent: 30 }] });
+    expect(errors).toContain("Component weights must total 100%");
+  });
+
+  test("needs two different components and a rebalance frequency", () => {
+    expect(validateBenchmark({ ...composite, components: [{ component_benchmark_id: 1, weight_percent: 100 }] })).toContain("A composite benchmark needs at least two components");
+    expect(validateBenchmark({ ...composite, components: [composite.components[0], { component_benchmark_id: 1, weight_percent: 40 }] })).toContain("Component 2: appears more than once");
+    expect(validateBenchmark({ ...composite, rebalance_frequency: "yearly" })).toContain("Rebalance frequency must be 'monthly' or 'quarterly'");
+  });
+});
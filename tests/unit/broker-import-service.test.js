@@ -0,0 +1,290 @@
+// Set isolated DB path BEFORE importing connection.js (which reads it at module load)
+process.env.DB_PATH = "data/portfolio_60_test/test-broker-import-service.db";
+
+import { describe, test, expect, beforeAll, afterAll } from "bun:test";
+import { existsSync, unlinkSync } from "node:fs";
+import { createDatabase, closeDatabase, getDatabasePath } from "../../src/server/db/connection.js";
+import { createUser } from "../../src/server/db/users-db.js";
+import { createAccount, getAccountById } from "../../src/server/db/accounts-db.js";
+import { createInvestment } from "../../src/server/db/investments-db.js";
+import { getAllInvestmentTypes } from "../../src/server/db/investment-types-db.js";
+import { getAllCurrencies } from "../../src/server/db/currencies-db.js";
+import { getActiveHoldingRaw } from "../../src/server/db/holdings-db.js";
+import { getMovementsByHoldingId } from "../../src/server/db/holding-movements-db.js";
+import { createCashTransaction, getCashTransactionsByAccountId } from "../../src/server/db/cash-transactions-db.js";
+import { parseCsv, parseBrokerDate, parseBrokerNumber, parseBrokerCsv, previewBrokerImport, commitBrokerImport } from "../../src/server/services/broker-import-service.js";
+
+const testDbPath = getDatabasePath();
+
+/**
+ * @description Clean up the isolated test database files only.
+ */
+function cleanupDatabase() {
+  closeDatabase();
+  for (const suffix of ["", "-wal", "-shm"]) {
+    const filePath = testDbPath + suffix;
+    if (existsSync(filePath)) {
+      unlinkSync(filePath);
+    }
+  }
+}
+
+const II_CSV = [
+  "Date,Settlement Date,Symbol,Sedol,Quantity,Price,Description,Reference,Debit,Credit,Running Balance",
+  '15/01/2025,15/01/2025,n/a,n/a,n/a,n/a,Subscription,SUB001,n/a,"£5,000.00","£5,000.00"',
+  '16/01/2025,20/01/2025,FSEQ,B41YBW7,100,£15.00,Fundsmith Equity I Acc,B0001,"£1,500.00",n/a,"£3,500.00"',
+  "03/02/2025,03/02/2025,DPAY,n/a,n/a,n/a,Dividend Payer plc Dividend,DIV01,n/a,£12.50,\"£3,512.50\"",
+  "28/02/2025,28/02/2025,n/a,n/a,n/a,n/a,Total Monthly Fee,FEE01,£4.99,n/a,\"£3,507.51\"",
+  "01/03/2025,01/03/2025,n/a,n/a,50,n/a,Transfer of stock in,TR001,n/a,n/a,\"£3,507.51\"",
+].join("\n");
+
+const HL_CSV = [
+  "Hargreaves Lansdown",
+  "Client Name:,Mr A Tester",
+  "",
+  "Trade date,Settle date,Reference,Description,Unit cost (p),Quantity,Value (£)",
+  "10/03/2025,12/03/2025,FPC,Card Web deposit,,,1000.00",
+  "11/03/2025,13/03/2025,B123456,Dividend Payer plc Ordinary 10p,250.00,200,-500.00",
+  "12/03/2025,14/03/2025,S654321,Unknown Holdings plc,100.00,10,10.00",
+].join("\r\n");
+
+const AJ_CSV = [
+  "Date,Transaction type,Description,Sedol,ISIN,Quantity,Price,Value (£)",
+  "05-Apr-2025,Receipt,Bank transfer,,,,,2000.00",
+  "06-Apr-2025,Purchase,Fundsmith Equity I Acc,,GB00B41YBW71,10,15.50,-155.00",
+  "07-Apr-2025,Interest,Cash interest,,,,,1.23",
+].join("\n");
+
+/** @type {Object} Account owned by an ii customer */
+let account;
+/** @type {Object} Fund identified by ISIN */
+let fund;
+/** @type {Object} Share identified by description only */
+let share;
+
+beforeAll(() => {
+  cleanupDatabase();
+  createDatabase();
+
+  const user = createUser({
+    initials: "BI",
+    first_name: "Broker",
+    last_name: "Importer",
+    provider: "ii",
+  });
+
+  account = createAccount({
+    user_id: user.id,
+    account_type: "isa",
+    account_ref: "B1001",
+    cash_balance: 0,
+    warn_cash: 0,
+  });
+
+  const types = getAllInvestmentTypes();
+  const gbp = getAllCurrencies().find((c) => c.code === "GBP");
+  fund = createInvestment({
+    currencies_id: gbp.id,
+    investment_type_id: types[0].id,
+    description: "Fundsmith Equity I Acc",
+    public_id: "GB00B41YBW71",
+    unit_type: "accumulation",
+  });
+  share = createInvestment({
+    currencies_id: gbp.id,
+    investment_type_id: types.find((t) => t.short_description === "SHARE").id,
+    description: "Dividend Payer plc",
+    unit_type: "income",
+  });
+});
+
+afterAll(() => {
+  cleanupDatabase();
+  delete process.env.DB_PATH;
+});
+
+describe("Broker Import - parsing helpers", function () {
+  test("parseCsv handles quoted fields, commas and doubled quotes", function () {
+    const rows = parseCsv('a,"b, c","say ""hi"""\r\n\n1,2,3\n\n');
+    expect(rows).toEqual([
+      ["a", "b, c", 'say "hi"'],
+      [""],
+      ["1", "2", "3"],
+    ]);
+  });
+
+  test("parseBrokerDate accepts the common broker formats", function () {
+    expect(parseBrokerDate("16/01/2025")).toBe("2025-01-16");
+    expect(parseBrokerDate("6/4/25")).toBe("2025-04-06");
+    expect(parseBrokerDate("05-Apr-2025")).toBe("2025-04-05");
+    expect(parseBrokerDate("5 April 2025")).toBe("2025-04-05");
+    expect(parseBrokerDate("2025-04-05")).toBe("2025-04-05");
+    expect(parseBrokerDate("31/02/2025")).toBeNull();
+    expect(parseBrokerDate("Total")).toBeNull();
+  });
+
+  test("parseBrokerNumber strips symbols and handles negatives", function () {
+    expect(parseBrokerNumber("£1,500.00")).toBe(1500);
+    expect(parseBrokerNumber("(12.50)")).toBe(-12.5);
+    expect(parseBrokerNumber("-500.00")).toBe(-500);
+    expect(parseBrokerNumber("n/a")).toBe(0);
+    expect(parseBrokerNumber("")).toBe(0);
+  });
+
+  test("throws for an unsupported provider", function () {
+    expect(() => parseBrokerCsv("xx", II_CSV)).toThrow("Unsupported provider");
+  });
+
+  test("throws when the header row is missing", function () {
+    expect(() => parseBrokerCsv("ii", "not,a,broker,file\n1,2,3,4")).toThrow("header row");
+  });
+});
+
+describe("Broker Import - provider formats", function () {
+  test("classifies Interactive Investor rows", function () {
+    const rows = parseBrokerCsv("ii", II_CSV);
+    expect(rows.map((r) => r.type)).toEqual(["deposit", "buy", "dividend", "fee", null]);
+    expect(rows[1].sedol).toBe("B41YBW7");
+    expect(rows[1].quantity).toBe(100);
+    expect(rows[1].amount).toBe(1500);
+  });
+
+  test("skips the Hargreaves Lansdown preamble and uses references for trades", function () {
+    const rows = parseBrokerCsv("hl", HL_CSV);
+    expect(rows.map((r) => r.type)).toEqual(["deposit", "buy", "sell"]);
+    expect(rows[1].amount).toBe(500);
+    expect(rows[1].row_number).toBe(6);
+  });
+
+  test("reads the AJ Bell transaction type and ISIN columns", function () {
+    const rows = parseBrokerCsv("aj", AJ_CSV);
+    expect(rows.map((r) => r.type)).toEqual(["deposit", "buy", "interest"]);
+    expect(rows[1].isin).toBe("GB00B41YBW71");
+    expect(rows[1].date).toBe("2025-04-06");
+  });
+});
+
+describe("Broker Import - preview", function () {
+  test("returns null for a non-existent account", function () {
+    expect(previewBrokerImport(99999, "ii", II_CSV)).toBeNull();
+  });
+
+  test("defaults to the account's provider and matches by SEDOL and description", function () {
+    const preview = previewBrokerImport(account.id, null, II_CSV);
+    expect(preview.provider).toBe("ii");
+    expect(preview.summary).toEqual({ total: 5, new: 4, duplicate: 0, unmatched: 0, skipped: 1 });
+
+    const buy = preview.rows[1];
+    expect(buy.investment_id).toBe(fund.id);
+    expect(buy.matched_by).toBe("sedol");
+
+    const dividend = preview.rows[2];
+    expect(dividend.investment_id).toBe(share.id);
+    expect(dividend.matched_by).toBe("description");
+  });
+
+  test("matches by ISIN and flags unknown instruments as unmatched", function () {
+    const aj = previewBrokerImport(account.id, "aj", AJ_CSV);
+    expect(aj.rows[1].matched_by).toBe("isin");
+
+    const hl = previewBrokerImport(account.id, "hl", HL_CSV);
+    expect(hl.rows[1].investment_id).toBe(share.id);
+    expect(hl.rows[2].status).toBe("unmatched");
+  });
+
+  test("does not write anything", function () {
+    expect(getCashTransactionsByAccountId(account.id).length).toBe(0);
+  });
+});
+
+describe("Broker Import - commit", function () {
+  test("rejects a commit that does not match the last preview", function () {
+    const preview = previewBrokerImport(account.id, "ii", II_CSV);
+    previewBrokerImport(account.id, "aj", AJ_CSV);
+    expect(() => commitBrokerImport(account.id, preview.file_hash)).toThrow("does not match the last preview");
+    expect(getCashTransactionsByAccountId(account.id).length).toBe(0);
+  });
+
+  test("applies the previewed rows in date order and updates holdings and cash", function () {
+    const preview = previewBrokerImport(account.id, "ii", II_CSV);
+    const result = commitBrokerImport(account.id, preview.file_hash);
+    expect(result.imported).toBe(4);
+    expect(result.failed).toBeNull();
+
+    const holding = getActiveHoldingRaw(account.id, fund.id);
+    expect(holding.quantity / 10000).toBe(100);
+    expect(holding.effective_from).toBe("2025-01-16");
+
+    // 5000 deposit - 1500 buy + 12.50 dividend - 4.99 fee
+    expect(getAccountById(account.id).cash_balance).toBe(3507.51);
+
+    const fee = getCashTransactionsByAccountId(account.id).find((tx) => tx.transaction_type === "adjustment");
+    expect(fee.amount).toBe(4.99);
+    expect(fee.notes).toContain("Total Monthly Fee");
+  });
+
+  test("flags every row as a duplicate when the same file is previewed again", function () {
+    const preview = previewBrokerImport(account.id, "ii", II_CSV);
+    expect(preview.summary.new).toBe(0);
+    expect(preview.summary.duplicate).toBe(4);
+
+    const result = commitBrokerImport(account.id, preview.file_hash);
+    expect(result.imported).toBe(0);
+  });
+
+  test("a preview can only be committed once", function () {
+    const preview = previewBrokerImport(account.id, "ii", II_CSV);
+    commitBrokerImport(account.id, preview.file_hash);
+    expect(() => commitBrokerImport(account.id, preview.file_hash)).toThrow("preview it again");
+  });
+
+  test("detects rows already entered by hand", function () {
+    createCashTransaction({ account_id: account.id, transaction_type: "deposit", transaction_date: "2025-04-05", amount: 2000 });
+    const preview = previewBrokerImport(account.id, "aj", AJ_CSV);
+    expect(preview.rows[0].status).toBe("duplicate");
+    expect(preview.rows[1].status).toBe("new");
+  });
+
+  test("imports nothing when a row fails and reports the row", function () {
+    const csv = [
+      "Date,Transaction type,Description,Sedol,ISIN,Quantity,Price,Value (£)",
+      "30-Apr-2025,Interest,Cash interest,,,,,0.75",
+      "01-May-2025,Purchase,Dividend Payer plc,,,100,500.00,-50000.00",
+      "02-May-2025,Interest,Cash interest,,,,,0.50",
+    ].join("\n");
+    const before = getCashTransactionsByAccountId(account.id).length;
+    const cashBefore = getAccountById(account.id).cash_balance;
+
+    const preview = previewBrokerImport(account.id, "aj", csv);
+    const result = commitBrokerImport(account.id, preview.file_hash);
+    expect(result.imported).toBe(0);
+    expect(result.failed.row_number).toBe(3);
+    expect(result.failed.error).toBe("Insufficient cash balance");
+    expect(getActiveHoldingRaw(account.id, share.id)).toBeNull();
+    expect(getCashTransactionsByAccountId(account.id).length).toBe(before);
+    expect(getAccountById(account.id).cash_balance).toBe(cashBefore);
+  });
+
+  test("records dealing charges and stamp duty as deductible costs", function () {
+    const csv = [
+      "Date,Transaction type,Description,Sedol,ISIN,Quantity,Price,Charges,Stamp duty,Value (£)",
+      "10-May-2025,Purchase,Dividend Payer plc,,,100,5.00,9.95,2.50,-512.45",
+      "20-May-2025,Sale,Dividend Payer plc,,,40,6.00,9.95,,230.05",
+    ].join("\n");
+
+    const preview = previewBrokerImport(account.id, "aj", csv);
+    expect(preview.rows.map((r) => r.deductible_costs)).toEqual([12.45, 9.95]);
+
+    const result = commitBrokerImport(account.id, preview.file_hash);
+    expect(result.failed).toBeNull();
+
+    const movements = getMovementsByHoldingId(getActiveHoldingRaw(account.id, share.id).id);
+    const buy = movements.find((m) => m.movement_type === "buy");
+    const sell = movements.find((m) => m.movement_type === "sell");
+    expect(buy.movement_value).toBe(512.45);
+    expect(buy.deductible_costs).toBe(12.45);
+    expect(buy.book_cost).toBe(500);
+    expect(sell.movement_value).toBe(240);
+    expect(sell.deductible_costs).toBe(9.95);
+  });
+});
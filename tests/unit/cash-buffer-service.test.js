@@ -0,0 +1,190 @@
+// Set isolated DB path BEFORE importing connection.js (which reads it at module load)
+process.env.DB_PATH = "data/portfolio_60_test/test-cash-buffer-service.db";
+
+import { describe, test, expect, beforeAll, afterAll } from "bun:test";
+import { existsSync, unlinkSync } from "node:fs";
+import { createDatabase, closeDatabase, getDatabasePath } from "../../src/server/db/connection.js";
+import { createUser } from "../../src/server/db/users-db.js";
+import { getAllCurrencies } from "../../src/server/db/currencies-db.js";
+import { getAllInvestmentTypes } from "../../src/server/db/investment-types-db.js";
+import { createInvestment } from "../../src/server/db/investments-db.js";
+import { createAccount, getAccountById } from "../../src/server/db/accounts-db.js";
+import { createHolding, getHoldingById } from "../../src/server/db/holdings-db.js";
+import { upsertPrice } from "../../src/server/db/prices-db.js";
+import { createDrawdownSchedule } from "../../src/server/db/drawdown-schedules-db.js";
+import { planCashBuffer, commitCashBufferSales } from "../../src/server/services/cash-buffer-service.js";
+import { validateCashBufferSales } from "../../src/server/validation.js";
+
+const testDbPath = getDatabasePath();
+
+/**
+ * @description Clean up the isolated test database files only.
+ */
+function cleanupDatabase() {
+  closeDatabase();
+  for (const suffix of ["", "-wal", "-shm"]) {
+    const filePath = testDbPath + suffix;
+    if (existsSync(filePath)) {
+      unlinkSync(filePath);
+    }
+  }
+}
+
+/** @type {Object} SIPP paying £1,000 a month, keeping £2,000 minimum cash */
+let sipp;
+/** @type {Object} ISA holding the same fund */
+let isa;
+/** @type {Object} SIPP holding of a fund held in fractional units, worth £5,001.25 */
+let fundHolding;
+/** @type {Object} SIPP holding of a share held in whole units, worth £4,000 */
+let shareHolding;
+/** @type {Object} ISA holding of the fund */
+let isaHolding;
+
+/** @description Date the plans are made from */
+const PLAN_DATE = "2026-10-20";
+
+beforeAll(() => {
+  cleanupDatabase();
+  createDatabase();
+
+  const gbpId = getAllCurrencies().find((c) => c.code === "GBP").id;
+  const types = getAllInvestmentTypes();
+  const mutualTypeId = types.find((t) => t.short_description === "MUTUAL").id;
+  const shareTypeId = types.find((t) => t.short_description === "SHARE").id;
+
+  const fund = createInvestment({ currencies_id: gbpId, investment_type_id: mutualTypeId, description: "Global Tracker Fund", public_id: "", investment_url: "", selector: "" });
+  const share = createInvestment({ currencies_id: gbpId, investment_type_id: shareTypeId, description: "Utility plc", public_id: "LSE:UTL", investment_url: "", selector: "" });
+  upsertPrice(fund.id, "2026-10-16", "17:00:00", 250);
+  upsertPrice(share.id, "2026-10-16", "17:00:00", 1000);
+
+  const user = createUser({ initials: "RT", first_name: "Rene", last_name: "Retiree", provider: "ii" });
+  sipp = createAccount({ user_id: user.id, account_type: "sipp", account_ref: "RT-SIPP", cash_balance: 5000, warn_cash: 2000 });
+  isa = createAccount({ user_id: user.id, account_type: "isa", account_ref: "RT-ISA", cash_balance: 0, warn_cash: 0 });
+
+  fundHolding = createHolding({ account_id: sipp.id, investment_id: fund.id, quantity: 2000.5, average_cost: 2 });
+  shareHolding = createHolding({ account_id: sipp.id, investment_id: share.id, quantity: 400, average_cost: 8 });
+  isaHolding = createHolding({ account_id: isa.id, investment_id: fund.id, quantity: 100, average_cost: 2 });
+
+  createDrawdownSchedule({ account_id: sipp.id, frequency: "monthly", trigger_day: 15, from_date: "2026-11-01", to_date: "2027-10-01", amount: 1000 });
+});
+
+afterAll(() => {
+  cleanupDatabase();
+  delete process.env.DB_PATH;
+});
+
+describe("Cash Buffer - forecast", function () {
+  test("runs the cash balance through the drawdowns due in the period", function () {
+    const plan = planCashBuffer(sipp.id, { months: 6, today: PLAN_DATE });
+    expect(plan.horizon_date).toBe("2027-04-20");
+    expect(plan.drawdowns.map((d) => d.date)).toEqual(["2026-11-15", "2026-12-15", "2027-01-15", "2027-02-15", "2027-03-15", "2027-04-15"]);
+    expect(plan.total_drawdowns).toBe(6000);
+    expect(plan.lowest_balance).toBe(-1000);
+    expect(plan.minimum_cash).toBe(2000);
+    expect(plan.first_shortfall_date).toBe("2027-02-15");
+    expect(plan.shortfall).toBe(3000);
+  });
+
+  test("proposes nothing when cash covers the period", function () {
+    const plan = planCashBuffer(sipp.id, { months: 2, today: PLAN_DATE });
+    expect(plan.shortfall).toBe(0);
+    expect(plan.first_shortfall_date).toBeNull();
+    expect(plan.proposals).toEqual([]);
+  });
+
+  test("returns null for an account that is not a SIPP", function () {
+    expect(planCashBuffer(isa.id, { today: PLAN_DATE })).toBeNull();
+  });
+});
+
+describe("Cash Buffer - proposals", function () {
+  test("sells from the largest holding first by default", function () {
+    const plan = planCashBuffer(sipp.id, { months: 6, method: "priority", today: PLAN_DATE });
+    expect(plan.proposals.length).toBe(1);
+    expect(plan.proposals[0].holding_id).toBe(fundHolding.id);
+    expect(plan.proposals[0].quantity).toBe(1200);
+    expect(plan.proposals[0].estimated_proceeds).toBe(3000);
+    expect(plan.unfunded).toBe(0);
+  });
+
+  test("works through the chosen holdings in priority order", function () {
+    const plan = planCashBuffer(sipp.id, { months: 6, method: "priority", holding_ids: [shareHolding.id, fundHolding.id], minimum_cash: 6000, today: PLAN_DATE });
+    expect(plan.shortfall).toBe(7000);
+    expect(plan.proposals.map((p) => [p.holding_id, p.quantity])).toEqual([
+      [shareHolding.id, 400],
+      [fundHolding.id, 1200],
+    ]);
+    expect(plan.proposed_total).toBe(7000);
+  });
+
+  test("spreads sales across holdings in proportion to value, in whole units for shares", function () {
+    const plan = planCashBuffer(sipp.id, { months: 6, method: "pro_rata", today: PLAN_DATE });
+    const fund = plan.proposals.find((p) => p.holding_id === fundHolding.id);
+    const share = plan.proposals.find((p) => p.holding_id === shareHolding.id);
+    // £3,000 split 5001.25 : 4000, rounded up to the next unit
+    expect(fund.quantity).toBe(666.7408);
+    expect(share.quantity).toBe(134);
+    expect(plan.proposed_total).toBeGreaterThanOrEqual(3000);
+  });
+
+  test("reports the shortfall the holdings cannot cover", function () {
+    const plan = planCashBuffer(sipp.id, { months: 6, minimum_cash: 20000, today: PLAN_DATE });
+    expect(plan.proposed_total).toBe(9001.25);
+    expect(plan.unfunded).toBe(11998.75);
+  });
+});
+
+describe("Cash Buffer - committing", function () {
+  test("records each sale as a sell movement and credits the proceeds", function () {
+    const today = new Date().toISOString().slice(0, 10);
+    const movements = commitCashBufferSales(sipp.id, [{ holding_id: shareHolding.id, quantity: 300, total_consideration: 3000 }], today);
+    expect(movements.length).toBe(1);
+    expect(movements[0].movement_type).toBe("sell");
+    expect(movements[0].notes).toBe("Cash buffer for drawdowns");
+    expect(getAccountById(sipp.id).cash_balance).toBe(8000);
+  });
+
+  test("refuses holdings from another account before recording anything", function () {
+    const today = new Date().toISOString().slice(0, 10);
+    expect(() =>
+      commitCashBufferSales(
+        sipp.id,
+        [
+          { holding_id: fundHolding.id, quantity: 10, total_consideration: 25 },
+          { holding_id: isaHolding.id, quantity: 10, total_consideration: 25 },
+        ],
+        today,
+      ),
+    ).toThrow("is not in this account");
+    expect(getHoldingById(fundHolding.id).quantity).toBe(2000.5);
+  });
+
+  test("refuses to sell more than is held", function () {
+    const today = new Date().toISOString().slice(0, 10);
+    expect(() => commitCashBufferSales(sipp.id, [{ holding_id: fundHolding.id, quantity: 5000, total_consideration: 12500 }], today)).toThrow("Insufficient holding quantity");
+  });
+
+  test("adds together several sales from one holding before checking the quantity held", function () {
+    const today = new Date().toISOString().slice(0, 10);
+    const cashBefore = getAccountById(sipp.id).cash_balance;
+    expect(() =>
+      commitCashBufferSales(
+        sipp.id,
+        [
+          { holding_id: fundHolding.id, quantity: 1500, total_consideration: 3750 },
+          { holding_id: fundHolding.id, quantity: 1000, total_consideration: 2500 },
+        ],
+        today,
+      ),
+    ).toThrow("Insufficient holding quantity");
+    expect(getHoldingById(fundHolding.id).quantity).toBe(2000.5);
+    expect(getAccountById(sipp.id).cash_balance).toBe(cashBefore);
+  });
+
+  test("validates the sales requested", function () {
+    expect(validateCashBufferSales({ movement_date: "2026-10-20", sales: [{ holding_id: 1, quantity: 5, total_consideration: 50 }] })).toEqual([]);
+    expect(validateCashBufferSales({ movement_date: "2026-10-20", sales: [] })).toContain("At least one sale is required");
+    expect(validateCashBufferSales({ sales: [{ holding_id: 1, quantity: 0, total_consideration: 50 }] })).toEqual(["Sale date is required", "Sale 1: quantity must be greater than zero"]);
+  });
+});
@@ -6,7 +6,10 @@ import { existsSync, unlinkSync } from "node:fs";
 import { createDatabase, closeDatabase, getDatabasePath } from "../../src/server/db/connection.js";
 import { createUser } from "../../src/server/db/users-db.js";
 import { createAccount, getAccountById } from "../../src/server/db/accounts-db.js";
-import { createCashTransaction, getCashTransactionById, getCashTransactionsByAccountId, deleteCashTransaction, getIsaDepositsForTaxYear, drawdownExistsForDate, getCashBalanceAtDate, scaleCashAmount, unscaleCashAmount } from "../../src/server/db/cash-transactions-db.js";
+import { createCashTransaction, getCashTransactionById, getCashTransactionsByAccountId, deleteCashTransaction, getIsaDepositsForTaxYear, drawdownExistsForDate, getCashBalanceAtDate, getIncomeTransactions, getPensionTransactionsForUser, getDrawdownsBetween, scaleCashAmount, unscaleCashAmount } from "../../src/server/db/cash-transactions-db.js";
+import { createInvestment } from "../../src/server/db/investments-db.js";
+import { getAllInvestmentTypes } from "../../src/server/db/investment-types-db.js";
+import { getAllCurrencies } from "../../src/server/db/currencies-db.js";
 
 const testDbPath = getDatabasePath();
 
@@ -582,3 +585,198 @@ describe("getCashBalanceAtDate", () => {
     expect(midBalance).toBe(10000);
   });
 });
+
+// --- Dividend and interest income ---
+
+describe("dividend and interest income", () => {
+  /** @type {Object} */
+  let incomeAccount;
+  /** @type {Object} */
+  let incomeInvestment;
+
+  beforeAll(() => {
+    const incomeUser = createUser({
+      initials: "IN",
+      first_name: "Income",
+      last_name: "Tester",
+      provider: "ii",
+    });
+    incomeAccount = createAccount({
+      user_id: incomeUser.id,
+      account_type: "isa",
+      account_ref: "INC-001",
+      cash_balance: 1000,
+      warn_cash: 0,
+    });
+
+    const shareType = getAllInvestmentTypes().find((t) => t.short_description === "SHARE");
+    const gbp = getAllCurrencies().find((c) => c.code === "GBP");
+    incomeInvestment = createInvestment({
+      currencies_id: gbp.id,
+      investment_type_id: shareType.id,
+      description: "Income Test plc",
+      unit_type: "income",
+    });
+  });
+
+  test("dividend increases cash balance and stores the investment", () => {
+    const tx = createCashTransaction({
+      account_id: incomeAccount.id,
+      transaction_type: "dividend",
+      transaction_date: "2025-06-15",
+      amount: 42.5,
+      investment_id: incomeInvestment.id,
+    });
+
+    expect(tx.transaction_type).toBe("dividend");
+    expect(tx.investment_id).toBe(incomeInvestment.id);
+    expect(tx.investment_description).toBe("Income Test plc");
+    // The opening balance is a deposit dated today, so it comes after this
+    // backdated dividend in the running balance
+    expect(tx.balance_after).toBe(42.5);
+
+    const account = getAccountById(incomeAccount.id);
+    expect(account.cash_balance).toBe(1042.5);
+  });
+
+  test("interest increases cash balance without an investment", () => {
+    const tx = createCashTransaction({
+      account_id: incomeAccount.id,
+      transaction_type: "interest",
+      transaction_date: "2025-07-01",
+      amount: 7.5,
+    });
+
+    expect(tx.transaction_type).toBe("interest");
+    expect(tx.investment_id).toBeNull();
+
+    expect(tx.balance_after).toBe(50);
+    // The opening balance deposit carries the income in its running balance
+    const opening = getCashTransactionsByAccountId(incomeAccount.id)[0];
+    expect([opening.notes, opening.balance_after]).toEqual(["Opening balance", 1050]);
+
+    const account = getAccountById(incomeAccount.id);
+    expect(account.cash_balance).toBe(1050);
+  });
+
+  test("investment_id is ignored for non-income transaction types", () => {
+    const tx = createCashTransaction({
+      account_id: incomeAccount.id,
+      transaction_type: "deposit",
+      transaction_date: "2025-07-02",
+      amount: 100,
+      investment_id: incomeInvestment.id,
+    });
+    expect(tx.investment_id).toBeNull();
+    deleteCashTransaction(tx.id);
+  });
+
+  test("getIncomeTransactions returns only income, oldest first", () => {
+    const income = getIncomeTransactions({ accountId: incomeAccount.id });
+    expect(income.length).toBe(2);
+    expect(income[0].transaction_type).toBe("dividend");
+    expect(income[0].investment_unit_type).toBe("income");
+    expect(income[1].transaction_type).toBe("interest");
+  });
+
+  test("getIncomeTransactions filters by investment and date range", () => {
+    expect(getIncomeTransactions({ accountId: incomeAccount.id, investmentId: incomeInvestment.id }).length).toBe(1);
+    expect(getIncomeTransactions({ accountId: incomeAccount.id, startDate: "2025-06-20", endDate: "2025-12-31" }).length).toBe(1);
+  });
+
+  test("deleting a dividend reverses the balance increase", () => {
+    const dividend = getIncomeTransactions({ accountId: incomeAccount.id, investmentId: incomeInvestment.id })[0];
+    expect(deleteCashTransaction(dividend.id)).toBe(true);
+
+    const account = getAccountById(incomeAccount.id);
+    expect(account.cash_balance).toBe(1007.5);
+  });
+});
+
+// --- Pension contributions and tax relief ---
+
+describe("pension contributions and tax relief", () => {
+  let pensionAccount;
+
+  beforeAll(() => {
+    pensionAccount = createAccount({
+      user_id: testUser.id,
+      account_type: "sipp",
+      account_ref: "PENSION-SIPP",
+      cash_balance: 0,
+      warn_cash: 0,
+    });
+  });
+
+  test("stores the gross amount against a contribution deposit", () => {
+    const tx = createCashTransaction({
+      account_id: pensionAccount.id,
+      transaction_type: "deposit",
+      transaction_date: "2026-05-01",
+      amount: 800,
+      contribution_type: "personal",
+      gross_amount: 1000,
+    });
+    expect(tx.contribution_type).toBe("personal");
+    expect(tx.gross_amount).toBe(1000);
+    expect(getAccountById(pensionAccount.id).cash_balance).toBe(800);
+  });
+
+  test("tax relief increases cash balance", () => {
+    const tx = createCashTransaction({
+      account_id: pensionAccount.id,
+      transaction_type: "tax_relief",
+      transaction_date: "2026-06-15",
+      amount: 200,
+    });
+    expect(tx.transaction_type).toBe("tax_relief");
+    expect(tx.contribution_type).toBeUndefined();
+    expect(getAccountById(pensionAccount.id).cash_balance).toBe(1000);
+  });
+
+  test("contribution fields are ignored for non-deposit transaction types", () => {
+    const tx = createCashTransaction({
+      account_id: pensionAccount.id,
+      transaction_type: "withdrawal",
+      transaction_date: "2026-07-01",
+      amount: 50,
+      contribution_type: "employer",
+      gross_amount: 50,
+    });
+    expect(tx.contribution_type).toBeUndefined();
+    expect(tx.gross_amount).toBeUndefined();
+  });
+
+  test("getPensionTransactionsForUser returns contributions and relief only, oldest first", () => {
+    const rows = getPensionTransactionsForUser(testUser.id, "2026-04-06", "2027-04-05").filter((t) => t.account_id === pensionAccount.id);
+    expect(rows.map((t) => t.transaction_type)).toEqual(["deposit", "tax_relief"]);
+  });
+
+  test("deleting tax relief reverses the balance increase", () => {
+    const relief = getCashTransactionsByAccountId(pensionAccount.id).find((t) => t.transaction_type === "tax_relief");
+    expect(deleteCashTransaction(relief.id)).toBe(true);
+    expect(getAccountById(pensionAccount.id).cash_balance).toBe(750);
+  });
+
+  test("drawdown records tax withheld and net pay, debiting the gross", () => {
+    const tx = createCashTransaction({
+      account_id: pensionAccount.id,
+      transaction_type: "drawdown",
+      transaction_date: "2026-07-28",
+      amount: 500,
+      tax_withheld: 100,
+      tax_code: "BR",
+    });
+    expect(tx.tax_withheld).toBe(100);
+    expect(tx.net_amount).toBe(400);
+    expect(tx.tax_code).toBe("BR");
+    expect(getAccountById(pensionAccount.id).cash_balance).toBe(250);
+  });
+
+  test("getDrawdownsBetween returns only drawdowns in the date range", () => {
+    const rows = getDrawdownsBetween(pensionAccount.id, "2026-04-06", "2027-04-05");
+    expect(rows.length).toBe(1);
+    expect(rows[0].tax_withheld).toBe(100);
+    expect(getDrawdownsBetween(pensionAccount.id, "2027-04-06", "2028-04-05")).toEqual([]);
+  });
+});
@@ -388,6 +388,37 @@ describe("Cash Transactions Routes", () => {
     expect(data.remaining).toBe(10500);
   });
 
+  test("POST ISA deposit over the allowance returns 409 until confirmed", async () => {
+    const deposit = {
+      transaction_type: "deposit",
+      transaction_date: "2026-07-01",
+      amount: 15000,
+    };
+
+    const warned = await fetch(`${BASE_URL}/api/accounts/${isaAccountId}/cash-transactions`, {
+      method: "POST",
+      headers: { "Content-Type": "application/json" },
+      body: JSON.stringify(deposit),
+    });
+    expect(warned.status).toBe(409);
+    const warning = await warned.json();
+    expect(warning.error).toBe("ISA allowance exceeded");
+    expect(warning.allowance.remaining).toBe(10500);
+
+    const confirmed = await fetch(`${BASE_URL}/api/accounts/${isaAccountId}/cash-transactions`, {
+      method: "POST",
+      headers: { "Content-Type": "application/json" },
+      body: JSON.stringify({ ...deposit, confirm_over_allowance: true }),
+    });
+    expect(confirmed.status).toBe(201);
+
+    const response = await fetch(`${BASE_URL}/api/accounts/${isaAccountId}/isa-allowance`);
+    const data = await response.json();
+    expect(data.used).toBe(24500);
+    expect(data.remaining).toBe(0);
+    expect(data.over_limit).toBe(true);
+  });
+
   test("GET /api/accounts/:id/isa-allowance returns 400 for non-ISA account", async () => {
     const response = await fetch(`${BASE_URL}/api/accounts/${sippAccountId}/isa-allowance`);
     expect(response.status).toBe(400);
@@ -580,4 +611,144 @@ describe("Cash Transactions Routes", () => {
     const deleteData = await deleteResponse.json();
     expect(deleteData.error).toContain("Cannot delete");
   });
+
+  // --- Dividend and interest income ---
+
+  let incomeInvestmentId;
+  let accumulationInvestmentId;
+
+  test("POST dividend credits cash for an income investment held in the account", async () => {
+    const invResponse = await fetch(`${BASE_URL}/api/investments`, {
+      method: "POST",
+      headers: { "Content-Type": "application/json" },
+      body: JSON.stringify({
+        currencies_id: 1,
+        investment_type_id: 1,
+        description: "Income Share",
+        unit_type: "income",
+      }),
+    });
+    incomeInvestmentId = (await invResponse.json()).id;
+
+    await fetch(`${BASE_URL}/api/accounts/${sippAccountId}/holdings`, {
+      method: "POST",
+      headers: { "Content-Type": "application/json" },
+      body: JSON.stringify({ investment_id: incomeInvestmentId, quantity: 100, average_cost: 1 }),
+    });
+
+    const beforeRes = await fetch(`${BASE_URL}/api/accounts/${sippAccountId}`);
+    const balanceBefore = (await beforeRes.json()).cash_balance;
+
+    const response = await fetch(`${BASE_URL}/api/accounts/${sippAccountId}/cash-transactions`, {
+      method: "POST",
+      headers: { "Content-Type": "application/json" },
+      body: JSON.stringify({
+        transaction_type: "dividend",
+        transaction_date: "2025-06-30",
+        amount: 12.34,
+        investment_id: incomeInvestmentId,
+      }),
+    });
+    expect(response.status).toBe(201);
+    const tx = await response.json();
+    expect(tx.transaction_type).toBe("dividend");
+    expect(tx.investment_id).toBe(incomeInvestmentId);
+
+    const afterRes = await fetch(`${BASE_URL}/api/accounts/${sippAccountId}`);
+    expect((await afterRes.json()).cash_balance).toBeCloseTo(balanceBefore + 12.34, 2);
+  });
+
+  test("POST dividend without investment_id returns 400", async () => {
+    const response = await fetch(`${BASE_URL}/api/accounts/${sippAccountId}/cash-transactions`, {
+      method: "POST",
+      headers: { "Content-Type": "application/json" },
+      body: JSON.stringify({ transaction_type: "dividend", transaction_date: "2025-06-30", amount: 5 }),
+    });
+    expect(response.status).toBe(400);
+  });
+
+  test("POST dividend against accumulation units returns 400", async () => {
+    const invResponse = await fetch(`${BASE_URL}/api/investments`, {
+      method: "POST",
+      headers: { "Content-Type": "application/json" },
+      body: JSON.stringify({
+        currencies_id: 1,
+        investment_type_id: 1,
+        description: "Accumulation Fund",
+        unit_type: "accumulation",
+      }),
+    });
+    accumulationInvestmentId = (await invResponse.json()).id;
+
+    await fetch(`${BASE_URL}/api/accounts/${sippAccountId}/holdings`, {
+      method: "POST",
+      headers: { "Content-Type": "application/json" },
+      body: JSON.stringify({ investment_id: accumulationInvestmentId, quantity: 100, average_cost: 1 }),
+    });
+
+    const response = await fetch(`${BASE_URL}/api/accounts/${sippAccountId}/cash-transactions`, {
+      method: "POST",
+      headers: { "Content-Type": "application/json" },
+      body: JSON.stringify({
+        transaction_type: "dividend",
+        transaction_date: "2025-06-30",
+        amount: 5,
+        investment_id: accumulationInvestmentId,
+      }),
+    });
+    expect(response.status).toBe(400);
+    const data = await response.json();
+    expect(data.detail).toContain("accumulation");
+  });
+
+  test("POST dividend for an investment never held in the account returns 400", async () => {
+    const response = await fetch(`${BASE_URL}/api/accounts/${isaAccountId}/cash-transactions`, {
+      method: "POST",
+      headers: { "Content-Type": "application/json" },
+      body: JSON.stringify({
+        transaction_type: "dividend",
+        transaction_date: "2025-06-30",
+        amount: 5,
+        investment_id: incomeInvestmentId,
+      }),
+    });
+    expect(response.status).toBe(400);
+  });
+
+  test("POST interest without an investment credits cash", async () => {
+    const response = await fetch(`${BASE_URL}/api/accounts/${sippAccountId}/cash-transactions`, {
+      method: "POST",
+      headers: { "Content-Type": "application/json" },
+      body: JSON.stringify({ transaction_type: "interest", transaction_date: "2026-01-31", amount: 3.21 }),
+    });
+    expect(response.status).toBe(201);
+  });
+
+  test("GET /api/accounts/:id/income returns totals by tax year", async () => {
+    const response = await fetch(`${BASE_URL}/api/accounts/${sippAccountId}/income`);
+    expect(response.status).toBe(200);
+    const income = await response.json();
+    expect(income.tax_years.length).toBe(1);
+    expect(income.tax_years[0].label).toBe("2025/2026");
+    expect(income.tax_years[0].dividends).toBe(12.34);
+    expect(income.tax_years[0].interest).toBe(3.21);
+    expect(income.totals.total).toBe(15.55);
+  });
+
+  test("GET /api/accounts/:id/income returns 400 for an invalid tax year", async () => {
+    const response = await fetch(`${BASE_URL}/api/accounts/${sippAccountId}/income?taxYear=abc`);
+    expect(response.status).toBe(400);
+  });
+
+  test("GET /api/holdings/:id/income returns income for a holding", async () => {
+    const holdingsRes = await fetch(`${BASE_URL}/api/accounts/${sippAccountId}/holdings`);
+    const holdings = await holdingsRes.json();
+    const holding = holdings.find((h) => h.investment_id === incomeInvestmentId);
+
+    const response = await fetch(`${BASE_URL}/api/holdings/${holding.id}/income`);
+    expect(response.status).toBe(200);
+    const income = await response.json();
+    expect(income.total).toBe(12.34);
+    expect(income.tax_years[0].payments.length).toBe(1);
+  });
 });
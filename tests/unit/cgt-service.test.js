@@ -0,0 +1,253 @@
+// Set isolated DB path BEFORE importing connection.js (which reads it at module load)
+process.env.DB_PATH = "data/portfolio_60_test/test-cgt-service.db";
+
+import { describe, test, expect, beforeAll, afterAll } from "bun:test";
+import { existsSync, unlinkSync } from "node:fs";
+import { createDatabase, closeDatabase, getDatabasePath } from "../../src/server/db/connection.js";
+import { createUser } from "../../src/server/db/users-db.js";
+import { createAccount } from "../../src/server/db/accounts-db.js";
+import { createInvestment } from "../../src/server/db/investments-db.js";
+import { getAllInvestmentTypes } from "../../src/server/db/investment-types-db.js";
+import { getAllCurrencies } from "../../src/server/db/currencies-db.js";
+import { createHolding } from "../../src/server/db/holdings-db.js";
+import { createBuyMovement, createSellMovement } from "../../src/server/db/holding-movements-db.js";
+import { matchDisposals, getCapitalGainsReport, getAnnualExemptAmount } from "../../src/server/services/cgt-service.js";
+
+const testDbPath = getDatabasePath();
+
+/**
+ * @description Clean up the isolated test database files only.
+ */
+function cleanupDatabase() {
+  closeDatabase();
+  for (const suffix of ["", "-wal", "-shm"]) {
+    const filePath = testDbPath + suffix;
+    if (existsSync(filePath)) {
+      unlinkSync(filePath);
+    }
+  }
+}
+
+/** @type {Object} Test user */
+let testUser;
+/** @type {Object} Trading account */
+let tradingAccount;
+/** @type {Object} ISA account */
+let isaAccount;
+
+beforeAll(() => {
+  cleanupDatabase();
+  createDatabase();
+
+  testUser = createUser({
+    initials: "RC",
+    first_name: "Robert",
+    last_name: "Collins",
+    provider: "ii",
+  });
+
+  tradingAccount = createAccount({
+    user_id: testUser.id,
+    account_type: "trading",
+    account_ref: "T1001",
+    cash_balance: 50000,
+    warn_cash: 0,
+  });
+
+  isaAccount = createAccount({
+    user_id: testUser.id,
+    account_type: "isa",
+    account_ref: "I1001",
+    cash_balance: 50000,
+    warn_cash: 0,
+  });
+
+  const types = getAllInvestmentTypes();
+  const currencies = getAllCurrencies();
+  const shareType = types.find((t) => t.short_description === "SHARE");
+  const gbp = currencies.find((c) => c.code === "GBP");
+
+  const investment = createInvestment({
+    currencies_id: gbp.id,
+    investment_type_id: shareType.id,
+    description: "Raspberry Pi Holdings",
+    public_id: "LSE:RPI",
+  });
+
+  // Trading: opening pool of 1000 shares costing £4,000
+  const tradingHolding = createHolding({
+    account_id: tradingAccount.id,
+    investment_id: investment.id,
+    quantity: 1000,
+    average_cost: 4.0,
+  });
+
+  // ISA: same investment — must never appear in the CGT report
+  const isaHolding = createHolding({
+    account_id: isaAccount.id,
+    investment_id: investment.id,
+    quantity: 1000,
+    average_cost: 4.0,
+  });
+
+  createBuyMovement({ holding_id: tradingHolding.id, movement_date: "2024-05-01", quantity: 200, total_consideration: 1000 });
+  createSellMovement({ holding_id: tradingHolding.id, movement_date: "2024-05-01", quantity: 500, total_consideration: 3000 });
+  createBuyMovement({ holding_id: tradingHolding.id, movement_date: "2024-05-11", quantity: 100, total_consideration: 600 });
+
+  createSellMovement({ holding_id: isaHolding.id, movement_date: "2024-05-01", quantity: 500, total_consideration: 9000 });
+});
+
+afterAll(() => {
+  cleanupDatabase();
+  delete process.env.DB_PATH;
+});
+
+describe("CGT Service - matchDisposals", function () {
+  test("matches same-day acquisitions before anything else", function () {
+    const result = matchDisposals([
+      { seq: 0, type: "opening", date: "2020-01-01", account_id: 1, quantity: 100, cost: 100 },
+      { seq: 1, type: "acquisition", date: "2024-06-01", account_id: 1, quantity: 50, cost: 500 },
+      { seq: 2, type: "disposal", date: "2024-06-01", account_id: 1, quantity: 50, proceeds: 600 },
+    ]);
+    expect(result.disposals.length).toBe(1);
+    expect(result.disposals[0].matches[0].rule).toBe("same_day");
+    expect(result.disposals[0].allowable_cost).toBe(500);
+    expect(result.disposals[0].gain).toBe(100);
+    expect(result.pool.quantity).toBe(100);
+  });
+
+  test("matches acquisitions within 30 days under the bed and breakfast rule", function () {
+    const result = matchDisposals([
+      { seq: 0, type: "opening", date: "2020-01-01", account_id: 1, quantity: 100, cost: 100 },
+      { seq: 1, type: "disposal", date: "2024-06-01", account_id: 1, quantity: 100, proceeds: 1000 },
+      { seq: 2, type: "acquisition", date: "2024-07-01", account_id: 1, quantity: 100, cost: 950 },
+    ]);
+    expect(result.disposals[0].matches[0].rule).toBe("bed_and_breakfast");
+    expect(result.disposals[0].allowable_cost).toBe(950);
+    expect(result.disposals[0].gain).toBe(50);
+    // Original shares stay in the pool at their original cost
+    expect(result.pool.quantity).toBe(100);
+    expect(result.pool.cost).toBe(100);
+  });
+
+  test("ignores acquisitions more than 30 days after the disposal", function () {
+    const result = matchDisposals([
+      { seq: 0, type: "opening", date: "2020-01-01", account_id: 1, quantity: 100, cost: 100 },
+      { seq: 1, type: "disposal", date: "2024-06-01", account_id: 1, quantity: 100, proceeds: 1000 },
+      { seq: 2, type: "acquisition", date: "2024-07-02", account_id: 1, quantity: 100, cost: 950 },
+    ]);
+    expect(result.disposals[0].matches[0].rule).toBe("section_104");
+    expect(result.disposals[0].gain).toBe(900);
+  });
+
+  test("uses the Section 104 average cost for the remainder", function () {
+    const result = matchDisposals([
+      { seq: 0, type: "acquisition", date: "2020-01-01", account_id: 1, quantity: 100, cost: 100 },
+      { seq: 1, type: "acquisition", date: "2021-01-01", account_id: 1, quantity: 100, cost: 300 },
+      { seq: 2, type: "disposal", date: "2024-06-01", account_id: 1, quantity: 50, proceeds: 250 },
+    ]);
+    expect(result.disposals[0].allowable_cost).toBe(100);
+    expect(result.disposals[0].gain).toBe(150);
+    expect(result.pool.quantity).toBe(150);
+    expect(result.pool.cost).toBe(300);
+  });
+
+  test("keeps pool cost unchanged across a share split", function () {
+    const result = matchDisposals([
+      { seq: 0, type: "opening", date: "2020-01-01", account_id: 1, quantity: 10, cost: 1000 },
+      { seq: 1, type: "split", date: "2022-01-01", account_id: 1, quantity: 100 },
+      { seq: 2, type: "disposal", date: "2024-06-01", account_id: 1, quantity: 50, proceeds: 800 },
+    ]);
+    expect(result.disposals[0].allowable_cost).toBe(500);
+    expect(result.pool.quantity).toBe(50);
+  });
+
+  test("treats disposals on the same day as one disposal", function () {
+    const result = matchDisposals([
+      { seq: 0, type: "opening", date: "2020-01-01", account_id: 1, quantity: 100, cost: 100 },
+      { seq: 1, type: "disposal", date: "2024-06-01", account_id: 1, quantity: 30, proceeds: 600 },
+      { seq: 2, type: "acquisition", date: "2024-06-01", account_id: 2, quantity: 20, cost: 400 },
+      { seq: 3, type: "disposal", date: "2024-06-01", account_id: 2, quantity: 10, proceeds: 200 },
+    ]);
+    // 40 sold: 20 matched with the day's purchase (£400), 20 from the pool (£20), shared 3:1
+    expect(result.disposals.map((d) => d.allowable_cost)).toEqual([315, 105]);
+    expect(result.disposals[1].matches.map((m) => [m.rule, m.quantity])).toEqual([["same_day", 5], ["section_104", 5]]);
+    expect(result.pool.quantity).toBe(80);
+  });
+
+  test("warns when a disposal exceeds the recorded pool", function () {
+    const result = matchDisposals([
+      { seq: 0, type: "disposal", date: "2024-06-01", account_id: 1, quantity: 10, proceeds: 100 },
+    ]);
+    expect(result.warnings.length).toBe(1);
+    expect(result.disposals[0].gain).toBe(100);
+  });
+});
+
+describe("CGT Service - getCapitalGainsReport", function () {
+  test("returns null for a non-existent user", function () {
+    expect(getCapitalGainsReport(99999)).toBeNull();
+  });
+
+  test("applies the matching rules to trading account movements", function () {
+    const report = getCapitalGainsReport(testUser.id);
+    const year = report.tax_years.find((ty) => ty.label === "2024/2025");
+    expect(year).toBeDefined();
+    expect(year.disposal_count).toBe(1);
+
+    const disposal = year.disposals[0];
+    expect(disposal.account_id).toBe(tradingAccount.id);
+    // 200 same day (£1,000) + 100 B&B (£600) + 200 from pool at £4 (£800).
+    // The opening pool is rebuilt from stored average costs, so allow for rounding.
+    expect(disposal.allowable_cost).toBeCloseTo(2400, 1);
+    expect(disposal.gain).toBeCloseTo(600, 1);
+    expect(disposal.matches.map((m) => m.rule)).toEqual(["same_day", "bed_and_breakfast", "section_104"]);
+  });
+
+  test("excludes ISA account movements", function () {
+    const report = getCapitalGainsReport(testUser.id);
+    for (const ty of report.tax_years) {
+      for (const d of ty.disposals) {
+        expect(d.account_id).not.toBe(isaAccount.id);
+      }
+    }
+  });
+
+  test("reports the remaining annual exempt amount", function () {
+    const report = getCapitalGainsReport(testUser.id);
+    const year = report.tax_years.find((ty) => ty.label === "2024/2025");
+    expect(year.annual_exempt_amount).toBe(3000);
+    expect(year.exempt_amount_used).toBeCloseTo(600, 1);
+    expect(year.exempt_amount_remaining).toBeCloseTo(2400, 1);
+    expect(year.taxable_gain).toBe(0);
+  });
+
+  test("always includes the current tax year", function () {
+    const report = getCapitalGainsReport(testUser.id);
+    const labels = report.tax_years.map((ty) => ty.label);
+    expect(labels).toContain(report.current_tax_year);
+  });
+
+  test("restricts output to a single tax year when requested", function () {
+    const report = getCapitalGainsReport(testUser.id, 2024);
+    expect(report.tax_years.length).toBe(1);
+    expect(report.tax_years[0].label).toBe("2024/2025");
+  });
+});
+
+describe("CGT Service - getAnnualExemptAmount", function () {
+  test("uses published figures for historic years", function () {
+    expect(getAnnualExemptAmount(2022)).toBe(12300);
+    expect(getAnnualExemptAmount(2023)).toBe(6000);
+  });
+
+  test("falls back to the configured amount for later years", function () {
+    expect(getAnnualExemptAmount(2030)).toBe(3000);
+  });
+
+  test("covers every year from 2008/2009 and none before", function () {
+    expect(getAnnualExemptAmount(2008)).toBe(9600);
+    expect(getAnnualExemptAmount(2014)).toBe(11000);
+    expect(getAnnualExemptAmount(2007)).toBeNull();
+  });
+});
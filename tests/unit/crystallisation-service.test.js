@@ -0,0 +1,205 @@
+// Set isolated DB path BEFORE importing connection.js (which reads it at module load)
+process.env.DB_PATH = "data/portfolio_60_test/test-crystallisation-service.db";
+
+import { describe, test, expect, beforeAll, afterAll } from "bun:test";
+import { existsSync, unlinkSync } from "node:fs";
+import { createDatabase, closeDatabase, getDatabasePath } from "../../src/server/db/connection.js";
+import { createUser } from "../../src/server/db/users-db.js";
+import { createAccount, getAccountById, deleteAccount } from "../../src/server/db/accounts-db.js";
+import { createCashTransaction, getCashTransactionById, deleteCashTransaction } from "../../src/server/db/cash-transactions-db.js";
+import { createCrystallisation, getCrystallisationById, getCrystallisationsByAccountId, deleteCrystallisation } from "../../src/server/db/crystallisations-db.js";
+import { getLumpSumAllowanceForUser, getCrystallisationSummary, checkTaxFreeCash, checkAmountCrystallised } from "../../src/server/services/crystallisation-service.js";
+import { getPortfolioSummary } from "../../src/server/services/portfolio-service.js";
+import { validateCrystallisation } from "../../src/server/validation.js";
+
+const testDbPath = getDatabasePath();
+
+/**
+ * @description Clean up the isolated test database files only.
+ */
+function cleanupDatabase() {
+  closeDatabase();
+  for (const suffix of ["", "-wal", "-shm"]) {
+    const filePath = testDbPath + suffix;
+    if (existsSync(filePath)) {
+      unlinkSync(filePath);
+    }
+  }
+}
+
+/** @type {Object} User with two SIPPs */
+let retiree;
+/** @type {Object} Retiree's main SIPP, holding cash only */
+let mainSipp;
+/** @type {Object} Retiree's second SIPP */
+let secondSipp;
+/** @type {Object} Retiree's ISA */
+let isa;
+/** @type {Object} First crystallisation of the main SIPP, paid from cash */
+let first;
+
+beforeAll(() => {
+  cleanupDatabase();
+  createDatabase();
+
+  retiree = createUser({ initials: "RT", first_name: "Rene", last_name: "Retiree", provider: "ii" });
+
+  mainSipp = createAccount({ user_id: retiree.id, account_type: "sipp", account_ref: "RT-SIPP", cash_balance: 0, warn_cash: 0 });
+  secondSipp = createAccount({ user_id: retiree.id, account_type: "sipp", account_ref: "RT-SIPP-AJ", provider: "aj", cash_balance: 0, warn_cash: 0 });
+  isa = createAccount({ user_id: retiree.id, account_type: "isa", account_ref: "RT-ISA", cash_balance: 0, warn_cash: 0 });
+
+  createCashTransaction({ account_id: mainSipp.id, transaction_type: "deposit", transaction_date: "2025-01-10", amount: 400000 });
+  createCashTransaction({ account_id: secondSipp.id, transaction_type: "deposit", transaction_date: "2025-01-10", amount: 50000 });
+});
+
+afterAll(() => {
+  cleanupDatabase();
+  delete process.env.DB_PATH;
+});
+
+describe("Crystallisation - recording", function () {
+  test("pays the tax-free cash out of the SIPP's cash and links the withdrawal", function () {
+    first = createCrystallisation({
+      account_id: mainSipp.id,
+      crystallisation_date: "2025-06-01",
+      tax_free_cash: 50000,
+      drawdown_amount: 150000,
+      pay_from_cash: true,
+      notes: "First phase",
+    });
+
+    expect(first.amount_crystallised).toBe(200000);
+    expect(first.cash_transaction_id).not.toBeNull();
+    expect(getAccountById(mainSipp.id).cash_balance).toBe(350000);
+
+    const withdrawal = getCashTransactionById(first.cash_transaction_id);
+    expect(withdrawal.transaction_type).toBe("withdrawal");
+    expect(withdrawal.amount).toBe(50000);
+    expect(withdrawal.notes).toBe("Tax-free cash: First phase");
+  });
+
+  test("can record tax-free cash already paid without touching the cash balance", function () {
+    const event = createCrystallisation({ account_id: secondSipp.id, crystallisation_date: "2024-03-01", tax_free_cash: 10000, drawdown_amount: 30000 });
+    expect(event.cash_transaction_id).toBeNull();
+    expect(getAccountById(secondSipp.id).cash_balance).toBe(50000);
+  });
+
+  test("throws when paying from cash and the balance is insufficient", function () {
+    expect(() =>
+      createCrystallisation({ account_id: secondSipp.id, crystallisation_date: "2025-06-01", tax_free_cash: 60000, drawdown_amount: 180000, pay_from_cash: true }),
+    ).toThrow("Insufficient cash balance");
+    expect(getCrystallisationsByAccountId(secondSipp.id).length).toBe(1);
+  });
+
+  test("lists an account's crystallisations oldest first", function () {
+    createCrystallisation({ account_id: mainSipp.id, crystallisation_date: "2025-01-15", tax_free_cash: 0, drawdown_amount: 20000 });
+    const dates = getCrystallisationsByAccountId(mainSipp.id).map((e) => e.crystallisation_date);
+    expect(dates).toEqual(["2025-01-15", "2025-06-01"]);
+  });
+});
+
+describe("Crystallisation - validation", function () {
+  test("accepts tax-free cash of up to a quarter of the amount crystallised", function () {
+    expect(validateCrystallisation({ crystallisation_date: "2026-05-01", tax_free_cash: 25000, drawdown_amount: 75000 })).toEqual([]);
+    expect(validateCrystallisation({ crystallisation_date: "2026-05-01", drawdown_amount: 75000 })).toEqual([]);
+  });
+
+  test("rejects more than 25% tax-free cash, empty amounts and bad dates", function () {
+    expect(validateCrystallisation({ crystallisation_date: "2026-05-01", tax_free_cash: 25001, drawdown_amount: 75000 })).toContain("Tax-free cash cannot be more than 25% of the amount crystallised");
+    expect(validateCrystallisation({ crystallisation_date: "2026-05-01", tax_free_cash: 0, drawdown_amount: 0 })).toContain("Enter the tax-free cash taken or the amount moved into drawdown");
+    expect(validateCrystallisation({ crystallisation_date: "01/05/2026", drawdown_amount: 100 })).toContain("Crystallisation date must be a valid date in YYYY-MM-DD format");
+  });
+});
+
+describe("Crystallisation - lump sum allowance", function () {
+  test("counts tax-free cash taken from every SIPP the person holds", function () {
+    const lsa = getLumpSumAllowanceForUser(retiree.id);
+    expect(lsa.lump_sum_allowance).toBe(268275);
+    expect(lsa.tax_free_cash_taken).toBe(60000);
+    expect(lsa.remaining).toBe(208275);
+  });
+
+  test("flags tax-free cash beyond the remaining allowance", function () {
+    expect(checkTaxFreeCash(mainSipp.id, 208275).exceeds).toBe(false);
+    expect(checkTaxFreeCash(mainSipp.id, 208275.01).exceeds).toBe(true);
+    expect(checkTaxFreeCash(isa.id, 100)).toBeNull();
+  });
+
+  test("returns null for a non-existent user", function () {
+    expect(getLumpSumAllowanceForUser(99999)).toBeNull();
+  });
+});
+
+describe("Crystallisation - funds", function () {
+  test("splits the account value into crystallised and uncrystallised funds", function () {
+    // Drawdowns since the first crystallisation come out of the crystallised fund
+    createCashTransaction({ account_id: mainSipp.id, transaction_type: "drawdown", transaction_date: "2025-07-28", amount: 10000 });
+
+    const summary = getCrystallisationSummary(mainSipp.id, 340000);
+    expect(summary.crystallised_total).toBe(220000);
+    expect(summary.tax_free_cash_taken).toBe(50000);
+    expect(summary.moved_to_drawdown).toBe(170000);
+    expect(summary.drawdowns_paid).toBe(10000);
+    expect(summary.crystallised_fund).toBe(160000);
+    expect(summary.uncrystallised_fund).toBe(180000);
+    expect(summary.tax_free_cash_available).toBe(45000);
+    expect(summary.events.length).toBe(2);
+  });
+
+  test("caps the crystallised fund at the account value", function () {
+    const summary = getCrystallisationSummary(mainSipp.id, 100000);
+    expect(summary.crystallised_fund).toBe(100000);
+    expect(summary.uncrystallised_fund).toBe(0);
+    expect(summary.tax_free_cash_available).toBe(0);
+  });
+
+  test("limits the tax-free cash available to the remaining lump sum allowance", function () {
+    expect(getCrystallisationSummary(mainSipp.id, 1160000).tax_free_cash_available).toBe(208275);
+  });
+
+  test("leaves the uncrystallised fund unknown without an account value", function () {
+    const summary = getCrystallisationSummary(mainSipp.id);
+    expect(summary.crystallised_fund).toBe(160000);
+    expect(summary.uncrystallised_fund).toBeNull();
+  });
+
+  test("returns null for an account that is not a SIPP", function () {
+    expect(getCrystallisationSummary(isa.id, 1000)).toBeNull();
+  });
+
+  test("flags a crystallisation larger than the uncrystallised fund", function () {
+    expect(checkAmountCrystallised(mainSipp.id, 180000, 340000)).toEqual({ uncrystallised_fund: 180000, exceeds: false });
+    expect(checkAmountCrystallised(mainSipp.id, 180000.01, 340000).exceeds).toBe(true);
+    expect(checkAmountCrystallised(isa.id, 1000, 1000)).toBeNull();
+  });
+
+  test("appears against SIPP accounts in the portfolio summary", function () {
+    const summary = getPortfolioSummary(retiree.id);
+    const sipp = summary.accounts.find((a) => a.id === mainSipp.id);
+    expect(sipp.crystallisation.uncrystallised_fund).toBe(sipp.account_total - sipp.crystallisation.crystallised_fund);
+    expect(summary.accounts.find((a) => a.id === isa.id).crystallisation).toBeNull();
+  });
+});
+
+describe("Crystallisation - deleting", function () {
+  test("deleting the withdrawal keeps the crystallisation but unlinks it", function () {
+    const event = createCrystallisation({ account_id: secondSipp.id, crystallisation_date: "2025-08-01", tax_free_cash: 1000, drawdown_amount: 3000, pay_from_cash: true });
+    expect(deleteCashTransaction(event.cash_transaction_id)).toBe(true);
+    expect(getCrystallisationById(event.id).cash_transaction_id).toBeNull();
+    expect(getAccountById(secondSipp.id).cash_balance).toBe(50000);
+  });
+
+  test("deleting a crystallisation returns its tax-free cash to the account", function () {
+    expect(deleteCrystallisation(first.id)).toBe(true);
+    expect(getCrystallisationById(first.id)).toBeNull();
+    expect(getCashTransactionById(first.cash_transaction_id)).toBeNull();
+    // 400000 - 10000 drawdown
+    expect(getAccountById(mainSipp.id).cash_balance).toBe(390000);
+    expect(deleteCrystallisation(first.id)).toBe(false);
+  });
+
+  test("deleting the account removes its crystallisations", function () {
+    expect(deleteAccount(secondSipp.id)).toBe(true);
+    expect(getCrystallisationsByAccountId(secondSipp.id)).toEqual([]);
+  });
+});
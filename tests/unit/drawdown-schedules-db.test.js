@@ -129,6 +129,8 @@ describe("createDrawdownSchedule", () => {
     expect(schedule.amount_scaled).toBe(12000000);
     expect(schedule.notes).toBe("Monthly pension");
     expect(schedule.active).toBe(1);
+    expect(schedule.tax_treatment).toBe("none");
+    expect(schedule.tax_code).toBeNull();
   });
 
   test("creates a quarterly schedule", () => {
@@ -276,6 +278,20 @@ describe("updateDrawdownSchedule", () => {
     expect(updated.notes).toBe("Updated pension amount");
   });
 
+  test("stores the tax treatment, normalising the tax code", () => {
+    const monthly = getDrawdownSchedulesByAccountId(sippAccount.id).find((s) => s.frequency === "monthly");
+
+    const coded = updateDrawdownSchedule(monthly.id, { ...monthly, tax_treatment: "tax_code", tax_code: " 1257l ", flat_tax_rate: 20 });
+    expect(coded.tax_treatment).toBe("tax_code");
+    expect(coded.tax_code).toBe("1257L");
+    expect(coded.flat_tax_rate).toBeNull();
+
+    const flat = updateDrawdownSchedule(monthly.id, { ...monthly, tax_treatment: "flat", tax_code: "1257L", flat_tax_rate: 22.5 });
+    expect(flat.tax_treatment).toBe("flat");
+    expect(flat.tax_code).toBeNull();
+    expect(flat.flat_tax_rate).toBe(22.5);
+  });
+
   test("can pause a schedule by setting active to 0", () => {
     const schedules = getDrawdownSchedulesByAccountId(sippAccount.id);
     const quarterly = schedules.find((s) => s.frequency === "quarterly");
@@ -346,7 +362,7 @@ describe("getDueDrawdownDates", () => {
       trigger_day: 15,
     };
 
-    const dates = getDueDrawdownDates(schedule, "2026-12-31");
+    const dates = getDueDrawdownDates(schedule, "2026-12-31").map((due) => due.date);
     expect(dates).toEqual([
       "2026-01-15",
       "2026-02-15",
@@ -365,7 +381,7 @@ describe("getDueDrawdownDates", () => {
       trigger_day: 10,
     };
 
-    const dates = getDueDrawdownDates(schedule, "2026-03-15");
+    const dates = getDueDrawdownDates(schedule, "2026-03-15").map((due) => due.date);
     expect(dates).toEqual([
       "2026-01-10",
       "2026-02-10",
@@ -381,7 +397,7 @@ describe("getDueDrawdownDates", () => {
       trigger_day: 1,
     };
 
-    const dates = getDueDrawdownDates(schedule, "2026-12-31");
+    const dates = getDueDrawdownDates(schedule, "2026-12-31").map((due) => due.date);
     expect(dates).toEqual([
       "2026-01-01",
       "2026-04-01",
@@ -398,7 +414,7 @@ describe("getDueDrawdownDates", () => {
       trigger_day: 5,
     };
 
-    const dates = getDueDrawdownDates(schedule, "2028-12-31");
+    const dates = getDueDrawdownDates(schedule, "2028-12-31").map((due) => due.date);
     expect(dates).toEqual([
       "2026-06-05",
       "2027-06-05",
@@ -414,7 +430,7 @@ describe("getDueDrawdownDates", () => {
       trigger_day: 15,
     };
 
-    const dates = getDueDrawdownDates(schedule, "2026-05-31");
+    const dates = getDueDrawdownDates(schedule, "2026-05-31").map((due) => due.date);
     expect(dates).toEqual([]);
   });
 
@@ -426,7 +442,7 @@ describe("getDueDrawdownDates", () => {
       trigger_day: 28,
     };
 
-    const dates = getDueDrawdownDates(schedule, "2027-12-31");
+    const dates = getDueDrawdownDates(schedule, "2027-12-31").map((due) => due.date);
     expect(dates).toEqual([
       "2026-11-28",
       "2026-12-28",
@@ -443,7 +459,7 @@ describe("getDueDrawdownDates", () => {
       trigger_day: 1,
     };
 
-    const dates = getDueDrawdownDates(schedule, "2027-12-31");
+    const dates = getDueDrawdownDates(schedule, "2027-12-31").map((due) => due.date);
     expect(dates).toEqual([
       "2026-10-01",
       "2027-01-01",
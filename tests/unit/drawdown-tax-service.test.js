@@ -0,0 +1,232 @@
+// Set isolated DB path BEFORE importing connection.js (which reads it at module load)
+process.env.DB_PATH = "data/portfolio_60_test/test-drawdown-tax-service.db";
+
+import { describe, test, expect, beforeAll, afterAll } from "bun:test";
+import { existsSync, unlinkSync } from "node:fs";
+import { createDatabase, closeDatabase, getDatabasePath } from "../../src/server/db/connection.js";
+import { createUser } from "../../src/server/db/users-db.js";
+import { createAccount, getAccountById } from "../../src/server/db/accounts-db.js";
+import { createCashTransaction, getDrawdownsBetween } from "../../src/server/db/cash-transactions-db.js";
+import { createDrawdownSchedule, getDrawdownScheduleById } from "../../src/server/db/drawdown-schedules-db.js";
+import { processDrawdowns, previewDrawdowns } from "../../src/server/services/drawdown-processor.js";
+import { getTaxMonth } from "../../src/server/services/tax-year-utils.js";
+import {
+  parseTaxCode,
+  getEmergencyTaxCode,
+  calculatePayeTax,
+  getP60Summary,
+  getP60SummariesForUser,
+  getP60SummariesForAllUsers,
+} from "../../src/server/services/drawdown-tax-service.js";
+
+const testDbPath = getDatabasePath();
+
+/**
+ * @description Clean up the isolated test database files only.
+ */
+function cleanupDatabase() {
+  closeDatabase();
+  for (const suffix of ["", "-wal", "-shm"]) {
+    const filePath = testDbPath + suffix;
+    if (existsSync(filePath)) {
+      unlinkSync(filePath);
+    }
+  }
+}
+
+/** @type {Object} User drawing from two SIPPs */
+let retiree;
+/** @type {Object} User with a SIPP but no drawdown */
+let saver;
+/** @type {Object} Retiree's SIPP taxed on a cumulative code */
+let codedSipp;
+/** @type {Object} Retiree's SIPP on the emergency code */
+let emergencySipp;
+/** @type {Object} Saver's SIPP with a flat-rate schedule starting next year */
+let flatSipp;
+/** @type {Object} Retiree's ISA */
+let isa;
+/** @type {Object} Monthly schedule on tax code 1257L */
+let codedSchedule;
+/** @type {Object} Annual schedule with 25% flat deduction */
+let flatSchedule;
+
+beforeAll(() => {
+  cleanupDatabase();
+  createDatabase();
+
+  retiree = createUser({ initials: "RT", first_name: "Rene", last_name: "Retiree", provider: "ii" });
+  saver = createUser({ initials: "SV", first_name: "Sam", last_name: "Saver", provider: "ii" });
+
+  codedSipp = createAccount({ user_id: retiree.id, account_type: "sipp", account_ref: "RT-SIPP", cash_balance: 0, warn_cash: 0 });
+  emergencySipp = createAccount({ user_id: retiree.id, account_type: "sipp", account_ref: "RT-SIPP-AJ", provider: "aj", cash_balance: 0, warn_cash: 0 });
+  flatSipp = createAccount({ user_id: saver.id, account_type: "sipp", account_ref: "SV-SIPP", cash_balance: 0, warn_cash: 0 });
+  isa = createAccount({ user_id: retiree.id, account_type: "isa", account_ref: "RT-ISA", cash_balance: 0, warn_cash: 0 });
+
+  for (const account of [codedSipp, emergencySipp, flatSipp]) {
+    createCashTransaction({ account_id: account.id, transaction_type: "deposit", transaction_date: "2026-01-10", amount: 50000 });
+  }
+
+  codedSchedule = createDrawdownSchedule({ account_id: codedSipp.id, frequency: "monthly", trigger_day: 28, from_date: "2026-04-01", to_date: "2027-03-01", amount: 2000, tax_treatment: "tax_code", tax_code: "1257l" });
+  createDrawdownSchedule({ account_id: emergencySipp.id, frequency: "quarterly", trigger_day: 28, from_date: "2026-06-01", to_date: "2027-03-01", amount: 6000, tax_treatment: "emergency" });
+  flatSchedule = createDrawdownSchedule({ account_id: flatSipp.id, frequency: "annually", trigger_day: 1, from_date: "2027-05-01", to_date: "2030-05-01", amount: 4000, tax_treatment: "flat", flat_tax_rate: 25 });
+});
+
+afterAll(() => {
+  cleanupDatabase();
+  delete process.env.DB_PATH;
+});
+
+describe("Drawdown Tax - tax codes", function () {
+  test("parses allowance codes into an annual tax-free amount", function () {
+    const code = parseTaxCode("1257L");
+    expect(code.allowance).toBe(12579);
+    expect(code.cumulative).toBe(true);
+    expect(code.rate).toBeNull();
+    expect(parseTaxCode("0T").allowance).toBe(0);
+  });
+
+  test("treats K codes as a negative allowance", function () {
+    expect(parseTaxCode("K475").allowance).toBe(-4759);
+  });
+
+  test("recognises flat-rate codes", function () {
+    expect(parseTaxCode("BR").rate).toBe("basic");
+    expect(parseTaxCode("D0").rate).toBe("higher");
+    expect(parseTaxCode("D1").rate).toBe("additional");
+    expect(parseTaxCode("NT").rate).toBe("none");
+  });
+
+  test("normalises case, spacing and non-cumulative suffixes", function () {
+    const code = parseTaxCode(" c1257l  m1 ");
+    expect(code.code).toBe("C1257L M1");
+    expect(code.cumulative).toBe(false);
+    expect(parseTaxCode("1257LX").cumulative).toBe(false);
+  });
+
+  test("rejects unrecognised and Scottish codes", function () {
+    expect(parseTaxCode("S1257L")).toBeNull();
+    expect(parseTaxCode("12345678L")).toBeNull();
+    expect(parseTaxCode("")).toBeNull();
+  });
+
+  test("builds the emergency code from the personal allowance", function () {
+    expect(getEmergencyTaxCode()).toBe("1257L M1");
+  });
+
+  test("numbers tax months from 6 April", function () {
+    expect(getTaxMonth("2026-04-06")).toBe(1);
+    expect(getTaxMonth("2026-05-05")).toBe(1);
+    expect(getTaxMonth("2026-05-06")).toBe(2);
+    expect(getTaxMonth("2027-01-05")).toBe(9);
+    expect(getTaxMonth("2027-04-05")).toBe(12);
+  });
+});
+
+describe("Drawdown Tax - PAYE", function () {
+  test("taxes everything at the basic rate on BR", function () {
+    expect(calculatePayeTax({ tax_code: "BR", gross: 1000, payment_date: "2026-04-28" })).toBe(200);
+  });
+
+  test("gives one month's allowance in tax month 1", function () {
+    expect(calculatePayeTax({ tax_code: "1257L", gross: 2000, payment_date: "2026-04-28" })).toBe(190.2);
+  });
+
+  test("works out tax cumulatively against pay and tax to date", function () {
+    const tax = calculatePayeTax({ tax_code: "1257L", gross: 2000, payment_date: "2026-05-28", previous_gross: 2000, previous_tax: 190.2 });
+    expect(tax).toBe(190.4);
+  });
+
+  test("ignores earlier pay on a month 1 basis, moving part of the payment into the higher rate", function () {
+    const monthOne = calculatePayeTax({ tax_code: "1257L M1", gross: 6000, payment_date: "2026-06-28", previous_gross: 4000, previous_tax: 500 });
+    const cumulative = calculatePayeTax({ tax_code: "1257L", gross: 6000, payment_date: "2026-06-28" });
+    expect(monthOne).toBe(1352.07);
+    expect(cumulative).toBe(571);
+  });
+
+  test("refunds over-deducted tax on a cumulative code", function () {
+    expect(calculatePayeTax({ tax_code: "1257L", gross: 100, payment_date: "2026-09-28", previous_gross: 1000, previous_tax: 500 })).toBe(-500);
+  });
+
+  test("limits K code deductions to half of the payment", function () {
+    expect(calculatePayeTax({ tax_code: "K475", gross: 1000, payment_date: "2026-04-28" })).toBe(279.2);
+    expect(calculatePayeTax({ tax_code: "K5000", gross: 1000, payment_date: "2026-04-28" })).toBe(500);
+  });
+
+  test("throws on an unrecognised tax code", function () {
+    expect(() => calculatePayeTax({ tax_code: "XYZ", gross: 1000, payment_date: "2026-04-28" })).toThrow("Unrecognised tax code");
+  });
+});
+
+describe("Drawdown Tax - schedules", function () {
+  test("stores the schedule's tax treatment", function () {
+    const coded = getDrawdownScheduleById(codedSchedule.id);
+    expect(coded.tax_treatment).toBe("tax_code");
+    expect(coded.tax_code).toBe("1257L");
+    expect(coded.flat_tax_rate).toBeNull();
+    expect(getDrawdownScheduleById(flatSchedule.id).flat_tax_rate).toBe(25);
+  });
+
+  test("preview allows for earlier drawdowns in the same run", function () {
+    const preview = previewDrawdowns("2026-06-30");
+    const coded = preview.would_process.filter((item) => item.account_id === codedSipp.id);
+    expect(coded.map((item) => item.tax_withheld)).toEqual([190.2, 190.4, 190.4]);
+    expect(coded[2].net).toBe(1809.6);
+    expect(preview.total_tax).toBe(571 + 1352.07);
+  });
+
+  test("records gross, tax withheld and net on each drawdown and debits the gross", function () {
+    const result = processDrawdowns("2026-06-30");
+    expect(result.processed).toBe(4);
+
+    const coded = getDrawdownsBetween(codedSipp.id, "2026-04-06", "2027-04-05");
+    expect(coded.map((tx) => tx.tax_withheld)).toEqual([190.2, 190.4, 190.4]);
+    expect(coded[0].amount).toBe(2000);
+    expect(coded[0].net_amount).toBe(1809.8);
+    expect(coded[0].tax_code).toBe("1257L");
+    expect(getAccountById(codedSipp.id).cash_balance).toBe(44000);
+
+    const emergency = getDrawdownsBetween(emergencySipp.id, "2026-04-06", "2027-04-05");
+    expect(emergency[0].tax_withheld).toBe(1352.07);
+    expect(emergency[0].tax_code).toBe("1257L M1");
+  });
+
+  test("deducts a flat rate with no tax code", function () {
+    processDrawdowns("2027-05-01");
+    const flat = getDrawdownsBetween(flatSipp.id, "2027-04-06", "2028-04-05");
+    expect(flat[0].tax_withheld).toBe(1000);
+    expect(flat[0].net_amount).toBe(3000);
+    expect(flat[0].tax_code).toBeNull();
+  });
+});
+
+describe("Drawdown Tax - P60 summary", function () {
+  test("totals pay and tax for a SIPP in the tax year", function () {
+    const summary = getP60Summary(codedSipp.id, 2026);
+    // April 2026 to March 2027 were all processed by the 2027-05-01 run
+    expect(summary.tax_year).toBe("2026/2027");
+    expect(summary.payments.length).toBe(12);
+    expect(summary.gross).toBe(24000);
+    expect(summary.tax_code).toBe("1257L");
+    expect(summary.net).toBe(summary.gross - summary.tax_withheld);
+    // Cumulative PAYE over a full year matches tax on annual pay: (24000 - 12579) x 20%
+    expect(summary.tax_withheld).toBe(2284.2);
+  });
+
+  test("returns null for an account that is not a SIPP", function () {
+    expect(getP60Summary(isa.id, 2026)).toBeNull();
+  });
+
+  test("adds up every SIPP a person drew from", function () {
+    const summary = getP60SummariesForUser(retiree.id, 2026);
+    expect(summary.accounts.length).toBe(2);
+    expect(summary.gross).toBe(24000 + 24000);
+    expect(summary.tax_withheld).toBe(summary.accounts[0].tax_withheld + summary.accounts[1].tax_withheld);
+  });
+
+  test("lists only people who drew a pension in the tax year", function () {
+    const ids = getP60SummariesForAllUsers(null, 2026).map((s) => s.user.id);
+    expect(ids).toEqual([retiree.id]);
+    expect(getP60SummariesForAllUsers(null, 2027).map((s) => s.user.id)).toEqual([saver.id]);
+  });
+});
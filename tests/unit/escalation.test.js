@@ -0,0 +1,277 @@
+// Set isolated DB path BEFORE importing connection.js (which reads it at module load)
+process.env.DB_PATH = "data/portfolio_60_test/test-escalation.db";
+
+import { describe, test, expect, beforeAll, afterAll } from "bun:test";
+import { existsSync, unlinkSync } from "node:fs";
+import { createDatabase, closeDatabase, getDatabase, getDatabasePath } from "../../src/server/db/connection.js";
+import { createUser } from "../../src/server/db/users-db.js";
+import { createAccount } from "../../src/server/db/accounts-db.js";
+import { createDrawdownSchedule, getDueDrawdownDates, getDrawdownAmountOn } from "../../src/server/db/drawdown-schedules-db.js";
+import { createOtherAsset, updateOtherAsset, getOtherAssetById, getOtherAssetHistory, applyOtherAssetEscalations } from "../../src/server/db/other-assets-db.js";
+import { createIndexSeries, upsertIndexValues, getIndexValues, countIndexSeriesUsage } from "../../src/server/db/index-series-db.js";
+import { getAnniversaryDates, escalateAmount } from "../../src/server/db/escalation-db.js";
+import { parseIndexCsv, importIndexCsv } from "../../src/server/services/index-series-service.js";
+import { processDrawdowns, previewDrawdowns } from "../../src/server/services/drawdown-processor.js";
+import { getDrawdownsBetween } from "../../src/server/db/cash-transactions-db.js";
+import { validateDrawdownSchedule, validateOtherAsset } from "../../src/server/validation.js";
+
+const testDbPath = getDatabasePath();
+
+/**
+ * @description Clean up the isolated test database files only.
+ */
+function cleanupDatabase() {
+  closeDatabase();
+  for (const suffix of ["", "-wal", "-shm"]) {
+    const filePath = testDbPath + suffix;
+    if (existsSync(filePath)) {
+      unlinkSync(filePath);
+    }
+  }
+}
+
+/** @type {Object} Test user */
+let testUser;
+/** @type {Object} Test SIPP account */
+let sippAccount;
+/** @type {Object} CPI series: 100 in March 2024, 102.5 in March 2025, nothing after */
+let cpi;
+
+/** @description A monthly drawdown of £1,000 rising 3% each 6 April */
+const FIXED_SCHEDULE = {
+  from_date: "2025-01-01",
+  to_date: "2027-12-01",
+  frequency: "monthly",
+  trigger_day: 15,
+  amount: 1000,
+  escalation_type: "fixed",
+  escalation_rate: 3,
+  escalation_anniversary: "04-06",
+};
+
+beforeAll(() => {
+  cleanupDatabase();
+  createDatabase();
+
+  testUser = createUser({ initials: "ES", first_name: "Esme", last_name: "Scale", provider: "ii" });
+  sippAccount = createAccount({ user_id: testUser.id, account_type: "sipp", account_ref: "ES-SIPP", cash_balance: 50000, warn_cash: 0 });
+
+  cpi = createIndexSeries({ name: "CPI", description: "Consumer prices index" });
+  upsertIndexValues(cpi.id, [
+    { value_date: "2024-03-01", value: 100 },
+    { value_date: "2024-09-01", value: 101.2 },
+    { value_date: "2025-03-01", value: 102.5 },
+  ]);
+});
+
+afterAll(() => {
+  cleanupDatabase();
+  delete process.env.DB_PATH;
+});
+
+describe("Escalation - rules", () => {
+  test("lists anniversaries strictly after the start and up to the end date", () => {
+    expect(getAnniversaryDates("04-06", "2024-04-06", "2026-04-06")).toEqual(["2025-04-06", "2026-04-06"]);
+    expect(getAnniversaryDates("04-06", "2024-04-07", "2025-04-05")).toEqual([]);
+  });
+
+  test("moves a 29 February anniversary to 28 February outside leap years", () => {
+    expect(getAnniversaryDates("02-29", "2023-01-01", "2025-12-31")).toEqual(["2023-02-28", "2024-02-29", "2025-02-28"]);
+  });
+
+  test("compounds a fixed rate, rounding to pence at each rise", () => {
+    const rule = { type: "fixed", rate: 3, index_id: null, anniversary: "04-06" };
+    const result = escalateAmount(1000, rule, "2025-01-15", "2026-12-31");
+    expect(result.amount).toBe(1060.9);
+    expect(result.steps.map((s) => [s.date, s.amount, s.reason])).toEqual([
+      ["2025-04-06", 1030, "Escalated 3.00% (fixed)"],
+      ["2026-04-06", 1060.9, "Escalated 3.00% (fixed)"],
+    ]);
+  });
+
+  test("rises with the index over the year to the latest value, and waits for values not yet loaded", () => {
+    const rule = { type: "index", rate: null, index_id: cpi.id, anniversary: "04-06" };
+    const result = escalateAmount(500, rule, "2024-06-01", "2026-12-31");
+    expect(result.steps.map((s) => [s.date, s.amount, s.reason])).toEqual([["2025-04-06", 512.5, "Escalated 2.50% (CPI)"]]);
+    expect(result.pending_date).toBe("2026-04-06");
+  });
+});
+
+describe("Escalation - drawdown schedules", () => {
+  test("getDueDrawdownDates gives the escalated amount on each date", () => {
+    const due = getDueDrawdownDates(FIXED_SCHEDULE, "2026-05-31");
+    expect(due.length).toBe(17);
+    expect(due[2]).toEqual({ date: "2025-03-15", amount: 1000, pending_rise: null });
+    expect(due[3]).toEqual({ date: "2025-04-15", amount: 1030, pending_rise: null });
+    expect(due[14]).toEqual({ date: "2026-03-15", amount: 1030, pending_rise: null });
+    expect(due[15]).toEqual({ date: "2026-04-15", amount: 1060.9, pending_rise: null });
+  });
+
+  test("a payment on the anniversary gets the new amount, and one before the first payment is not applied", () => {
+    const onAnniversary = { ...FIXED_SCHEDULE, from_date: "2025-04-01", trigger_day: 6 };
+    expect(getDueDrawdownDates(onAnniversary, "2026-04-30").map((d) => [d.date, d.amount]).slice(-2)).toEqual([
+      ["2026-03-06", 1000],
+      ["2026-04-06", 1030],
+    ]);
+    expect(getDrawdownAmountOn(onAnniversary, "2025-04-30")).toBe(1000);
+  });
+
+  test("stores the rule and previews escalated drawdowns", () => {
+    const schedule = createDrawdownSchedule({ account_id: sippAccount.id, ...FIXED_SCHEDULE, from_date: "2026-01-01" });
+    expect(schedule.escalation_type).toBe("fixed");
+    expect(schedule.escalation_rate).toBe(3);
+    expect(schedule.escalation_anniversary).toBe("04-06");
+    expect(schedule.escalation_index_id).toBeNull();
+
+    const preview = previewDrawdowns("2027-05-31").would_process.filter((item) => item.account_id === sippAccount.id);
+    expect(preview.length).toBe(17);
+    expect(preview.slice(1, 4).map((item) => [item.date, item.amount])).toEqual([
+      ["2026-02-15", 1000],
+      ["2026-03-15", 1000],
+      ["2026-04-15", 1030],
+    ]);
+    expect(preview[16]).toMatchObject({ date: "2027-05-15", amount: 1060.9 });
+    expect(getDrawdownAmountOn(schedule, "2027-05-31")).toBe(1060.9);
+  });
+});
+
+describe("Escalation - recurring other assets", () => {
+  test("applies each anniversary since the last rise, recording the old value with the reason", () => {
+    const pension = createOtherAsset({
+      user_id: testUser.id,
+      description: "Final Salary Pension",
+      category: "pension",
+      value_type: "recurring",
+      frequency: "monthly",
+      value: 10000000,
+      notes: "Paid monthly",
+      executor_reference: null,
+      escalation_type: "index",
+      escalation_index_id: cpi.id,
+      escalation_anniversary: "04-06",
+    });
+    expect(pension.escalation_type).toBe("index");
+    expect(pension.escalation_last_date).not.toBeNull();
+    expect(countIndexSeriesUsage(cpi.id)).toBe(1);
+
+    getDatabase().run("UPDATE other_assets SET escalation_last_date = '2024-06-01' WHERE id = ?", [pension.id]);
+
+    // 2025 rise applies; 2026 waits for CPI values
+    expect(applyOtherAssetEscalations("2026-10-15")).toEqual({ escalated: 1, pending: 1 });
+    const after = getOtherAssetById(pension.id);
+    expect(after.value).toBe(10250000);
+    expect(after.escalation_last_date).toBe("2025-04-06");
+    const history = getOtherAssetHistory(pension.id);
+    expect(history.map((h) => [h.change_date, h.revised_value, h.revised_notes, h.reason])).toEqual([["2025-04-06", 10000000, "Paid monthly", "Escalated 2.50% (CPI)"]]);
+
+    // Once the index is loaded the pending rise is applied, and only once
+    upsertIndexValues(cpi.id, [{ value_date: "2026-03-01", value: 106.6 }]);
+    expect(applyOtherAssetEscalations("2026-10-15")).toEqual({ escalated: 1, pending: 0 });
+    expect(applyOtherAssetEscalations("2026-10-15")).toEqual({ escalated: 0, pending: 0 });
+    expect(getOtherAssetById(pension.id).value).toBe(10660000);
+    expect(getOtherAssetHistory(pension.id)[0].reason).toBe("Escalated 4.00% (CPI)");
+  });
+
+  test("ignores the rule for value-type assets and clears it when set back to none", () => {
+    const house = createOtherAsset({ user_id: testUser.id, description: "House", category: "property", value_type: "value", frequency: null, value: 3000000000, notes: null, executor_reference: null, escalation_type: "fixed", escalation_rate: 3, escalation_anniversary: "01-01" });
+    expect(house.escalation_type).toBe("none");
+    expect(house.escalation_last_date).toBeNull();
+
+    const annuity = createOtherAsset({ user_id: testUser.id, description: "Annuity", category: "pension", value_type: "recurring", frequency: "annually", value: 20000000, notes: null, executor_reference: null, escalation_type: "fixed", escalation_rate: 2, escalation_anniversary: "01-01" });
+    expect(annuity.escalation_rate).toBe(2);
+    const cleared = updateOtherAsset(annuity.id, { ...annuity, escalation_type: "none" });
+    expect(cleared.escalation_type).toBe("none");
+    expect(cleared.escalation_rate).toBeNull();
+    expect(cleared.escalation_last_date).toBeNull();
+  });
+});
+
+describe("Escalation - index CSV import", () => {
+  test("reads ONS monthly rows and skips metadata, annual and quarterly rows", () => {
+    const csv = ['"Title","CPI INDEX 00: ALL ITEMS 2015=100"', '"CDID","D7BT"', '"2024","131.5"', '"2024 Q1","130.2"', '"2024 JAN","129.8"', '"2024 FEB","130.4"', ""].join("\n");
+    const parsed = parseIndexCsv(csv);
+    expect(parsed.values).toEqual([
+      { value_date: "2024-01-01", value: 129.8 },
+      { value_date: "2024-02-01", value: 130.4 },
+    ]);
+    expect(parsed.skipped).toBe(4);
+  });
+
+  test("accepts ISO dates and months, and replaces values already held", () => {
+    const series = createIndexSeries({ name: "RPI" });
+    const result = importIndexCsv(series.id, "Date,Value\n2024-01,360.3\n2024-02-01,362.9\n");
+    expect(result.imported).toBe(2);
+    expect(result.series.last_date).toBe("2024-02-01");
+
+    importIndexCsv(series.id, "2024-02-01,363.0\n");
+    expect(getIndexValues(series.id)).toEqual([
+      { value_date: "2024-01-01", value: 360.3 },
+      { value_date: "2024-02-01", value: 363 },
+    ]);
+    expect(() => importIndexCsv(series.id, "Date,Value\n")).toThrow("No index values found");
+    expect(importIndexCsv(9999, "2024-01,1")).toBeNull();
+  });
+});
+
+describe("Escalation - pending index rises", () => {
+  test("holds back drawdowns after a rise the index cannot yet give, and records them at the new amount once it can", () => {
+    // CPI is loaded to March 2026, so the April 2027 rise is pending
+    const account = createAccount({ user_id: testUser.id, account_type: "sipp", account_ref: "ES-SIPP-IX", provider: "aj", cash_balance: 50000, warn_cash: 0 });
+    const schedule = createDrawdownSchedule({
+      account_id: account.id,
+      from_date: "2026-05-01",
+      to_date: "2027-12-01",
+      frequency: "monthly",
+      trigger_day: 15,
+      amount: 500,
+      escalation_type: "index",
+      escalation_index_id: cpi.id,
+      escalation_anniversary: "04-06",
+    });
+
+    const due = getDueDrawdownDates(schedule, "2027-05-31");
+    expect(due.slice(-3).map((d) => [d.date, d.pending_rise])).toEqual([
+      ["2027-03-15", null],
+      ["2027-04-15", "2027-04-06"],
+      ["2027-05-15", "2027-04-06"],
+    ]);
+
+    const preview = previewDrawdowns("2027-05-31").would_process.filter((item) => item.account_id === account.id);
+    expect(preview.length).toBe(13);
+    expect(preview[12]).toMatchObject({ date: "2027-05-15", amount: 500, held_back: true });
+    expect(preview[12].warning).toContain("Held back");
+
+    const result = processDrawdowns("2027-05-31");
+    expect(result.held_back).toBe(2);
+    expect(getDrawdownsBetween(account.id, "2026-01-01", "2027-12-31").length).toBe(11);
+
+    // 4% rise over the year to March 2027
+    upsertIndexValues(cpi.id, [{ value_date: "2027-03-01", value: 110.864 }]);
+    expect(processDrawdowns("2027-05-31").held_back).toBe(0);
+    expect(getDrawdownsBetween(account.id, "2027-04-01", "2027-05-31").map((tx) => tx.amount)).toEqual([520, 520]);
+  });
+});
+
+describe("Escalation - validation", () => {
+  const base = { frequency: "monthly", trigger_day: 15, from_date: "2026-01-01", to_date: "2027-01-01", amount: 1000 };
+
+  test("accepts a fixed or index rule with an anniversary", () => {
+    expect(validateDrawdownSchedule({ ...base, escalation_type: "fixed", escalation_rate: 3, escalation_anniversary: "04-06" })).toEqual([]);
+    expect(validateDrawdownSchedule({ ...base, escalation_type: "index", escalation_index_id: 1, escalation_anniversary: "12-31" })).toEqual([]);
+  });
+
+  test("rejects missing rates, indices and invalid anniversaries", () => {
+    expect(validateDrawdownSchedule({ ...base, escalation_type: "fixed", escalation_rate: 30, escalation_anniversary: "02-29" })).toEqual([
+      "Escalation rate must be greater than 0 and no more than 25",
+      "Escalation anniversary must be a day of the year in MM-DD format (not 29 February)",
+    ]);
+    expect(validateDrawdownSchedule({ ...base, escalation_type: "index" })).toEqual(["Escalation index is required", "Escalation anniversary is required"]);
+    expect(validateDrawdownSchedule({ ...base, escalation_type: "yearly" })).toEqual(["Escalation type must be one of: none, fixed, index"]);
+  });
+
+  test("only allows escalation on recurring other assets", () => {
+    const asset = { user_id: 1, description: "House", category: "property", value_type: "value", value: 100, escalation_type: "fixed", escalation_rate: 3, escalation_anniversary: "04-06" };
+    expect(validateOtherAsset(asset)).toEqual(["Escalation can only be set for recurring assets"]);
+    expect(validateOtherAsset({ ...asset, value_type: "recurring", frequency: "monthly" })).toEqual([]);
+  });
+});
@@ -0,0 +1,139 @@
+// Set isolated DB path BEFORE importing connection.js (which reads it at module load)
+process.env.DB_PATH = "data/portfolio_60_test/test-gifts-service.db";
+
+import { describe, test, expect, beforeAll, afterAll } from "bun:test";
+import { existsSync, unlinkSync } from "node:fs";
+import { createDatabase, closeDatabase, getDatabasePath } from "../../src/server/db/connection.js";
+import { getAllUsers, createUser } from "../../src/server/db/users-db.js";
+import { createAccount } from "../../src/server/db/accounts-db.js";
+import { createCashTransaction, deleteCashTransaction } from "../../src/server/db/cash-transactions-db.js";
+import { createGift, getGiftById, getGiftByCashTransactionId, getAllGifts } from "../../src/server/db/gifts-db.js";
+import { analyseGifts, buildGiftsSummary, getTaperRelief } from "../../src/server/services/gifts-service.js";
+import { validateGift } from "../../src/server/validation.js";
+
+const testDbPath = getDatabasePath();
+
+/** @description Bands, rate and gift exemptions as in the default inheritanceTax config */
+const CONFIG = { nilRateBand: 325000, residenceNilRateBand: 175000, taperThreshold: 2000000, rate: 40, annualExemption: 3000, smallGiftLimit: 250 };
+
+/** @description One donor's gifts across several tax years, out of date order */
+const GIFTS = [
+  { id: 8, recipient: "Eve", gift_date: "2026-05-01", amount: 4000, exemption_type: "annual" },
+  { id: 1, recipient: "Amy", gift_date: "2019-05-01", amount: 10000, exemption_type: "annual" },
+  { id: 2, recipient: "Amy", gift_date: "2021-12-01", amount: 200, exemption_type: "small_gift" },
+  { id: 3, recipient: "Ben", gift_date: "2021-12-01", amount: 200, exemption_type: "small_gift" },
+  { id: 4, recipient: "ben", gift_date: "2022-02-01", amount: 100, exemption_type: "small_gift" },
+  { id: 5, recipient: "Cara", gift_date: "2022-09-14", amount: 345700, exemption_type: "annual" },
+  { id: 6, recipient: "Cara", gift_date: "2023-06-01", amount: 500, exemption_type: "normal_expenditure" },
+  { id: 7, recipient: "Dan", gift_date: "2025-08-01", amount: 1000, exemption_type: "annual" },
+];
+
+/**
+ * @description Clean up the isolated test database files only.
+ */
+function cleanupDatabase() {
+  closeDatabase();
+  for (const suffix of ["", "-wal", "-shm"]) {
+    const filePath = testDbPath + suffix;
+    if (existsSync(filePath)) {
+      unlinkSync(filePath);
+    }
+  }
+}
+
+let gina;
+let account;
+
+beforeAll(() => {
+  cleanupDatabase();
+  createDatabase();
+
+  gina = createUser({ initials: "GF", first_name: "Gina", last_name: "Fox", provider: "ii" });
+  account = createAccount({ user_id: gina.id, account_type: "trading", account_ref: "GF-TRD", cash_balance: 50000, warn_cash: 0 });
+});
+
+afterAll(() => {
+  cleanupDatabase();
+  delete process.env.DB_PATH;
+});
+
+describe("Gifts - analyseGifts", () => {
+  const analysis = analyseGifts(GIFTS, CONFIG, "2026-10-15");
+
+  /**
+   * @description Find a gift in the analysis by ID.
+   * @param {number} id - The gift ID
+   * @returns {Object} The analysed gift
+   */
+  function gift(id) {
+    return analysis.gifts.find((g) => g.id === id);
+  }
+
+  test("sets gifts against this year's annual exemption, then last year's unused", () => {
+    expect([gift(1).exempt_amount, gift(1).pet_amount]).toEqual([6000, 4000]);
+    expect([gift(5).exempt_amount, gift(5).pet_amount]).toEqual([5700, 340000]);
+    expect([gift(8).exempt_amount, gift(8).pet_amount]).toEqual([4000, 0]);
+    expect(analysis.tax_years.map((y) => [y.tax_year, y.carried_forward, y.used, y.remaining])).toEqual([
+      ["2019/2020", 3000, 6000, 0],
+      ["2020/2021", 0, 0, 3000],
+      ["2021/2022", 3000, 300, 5700],
+      ["2022/2023", 2700, 5700, 0],
+      ["2023/2024", 0, 0, 3000],
+      ["2024/2025", 3000, 0, 6000],
+      ["2025/2026", 3000, 1000, 5000],
+      ["2026/2027", 2000, 4000, 1000],
+    ]);
+  });
+
+  test("gives the annual exemption left in the current tax year", () => {
+    expect(analysis.annual_exemption).toEqual({ tax_year: "2026/2027", allowance: 3000, carried_forward: 2000, used: 4000, remaining: 1000 });
+  });
+
+  test("exempts small gifts within the limit per recipient and normal expenditure out of income", () => {
+    expect([gift(2).status, gift(2).exempt_amount]).toEqual(["exempt", 200]);
+    expect([gift(6).status, gift(6).exempt_amount, gift(6).exemption_label]).toEqual(["exempt", 500, "Normal expenditure out of income"]);
+    // Ben had £300 of small gifts in 2021/22, so they use the annual exemption instead
+    expect(gift(4).exemption_note).toBe("Gifts to this recipient in the tax year are over the £250 small gift limit");
+    expect([gift(3).pet_amount, gift(4).pet_amount]).toEqual([0, 0]);
+  });
+
+  test("tracks each PET against the seven years with taper relief on the tax", () => {
+    expect([gift(1).status, gift(1).becomes_exempt_on, gift(1).taper_relief]).toEqual(["past_seven_years", "2026-05-01", null]);
+    expect(gift(5)).toMatchObject({ status: "pet", years_elapsed: 4, taper_relief: 40, becomes_exempt_on: "2029-09-14", chargeable: 15000, tax_on_death: 3600 });
+    expect(analysis.totals).toEqual({ gifts: 361700, exempt: 17700, pets: 344000, pets_within_seven_years: 340000, nil_rate_band_used: 325000, tax_on_death: 3600 });
+  });
+
+  test("gives taper relief by whole years since the gift", () => {
+    expect(getTaperRelief("2023-10-16", "2026-10-15")).toBe(0);
+    expect(getTaperRelief("2023-10-15", "2026-10-15")).toBe(20);
+    expect(getTaperRelief("2020-01-01", "2026-10-15")).toBe(80);
+    expect(getTaperRelief("2019-10-15", "2026-10-15")).toBeNull();
+  });
+});
+
+describe("Gifts - register", () => {
+  test("records a gift from a withdrawal and keeps it when the withdrawal is deleted", () => {
+    const withdrawal = createCashTransaction({ account_id: account.id, transaction_type: "withdrawal", transaction_date: "2026-06-01", amount: 2500, notes: "Birthday" });
+    const recorded = createGift({ user_id: gina.id, recipient: " Hugo Fox ", gift_date: withdrawal.transaction_date, amount: withdrawal.amount, exemption_type: "annual", cash_transaction_id: withdrawal.id });
+    expect(recorded).toMatchObject({ recipient: "Hugo Fox", amount: 2500, first_name: "Gina", account_id: account.id });
+    expect(getGiftByCashTransactionId(withdrawal.id).id).toBe(recorded.id);
+
+    deleteCashTransaction(withdrawal.id);
+    expect(getGiftById(recorded.id).cash_transaction_id).toBeNull();
+    expect(getAllGifts(gina.id).length).toBe(1);
+  });
+
+  test("summarises a family member's gifts but not the Joint household user's", () => {
+    const summary = buildGiftsSummary(gina.id, "2026-10-15");
+    expect(summary.user.first_name).toBe("Gina");
+    expect(summary.annual_exemption.remaining).toBe(3500);
+    expect(buildGiftsSummary(getAllUsers().find((u) => u.first_name === "Joint").id)).toBeNull();
+  });
+
+  test("validates the gift fields", () => {
+    const data = { user_id: 2, recipient: "Hugo", gift_date: "2026-06-01", amount: 100, exemption_type: "annual" };
+    expect(validateGift(data)).toEqual([]);
+    expect(validateGift({ ...data, exemption_type: "wedding" })).toEqual(["Exemption type must be one of: annual, small_gift, normal_expenditure"]);
+    expect(validateGift({ ...data, amount: 0, gift_date: "2026-02-30x" })).toEqual(["Gift date must be a valid date in YYYY-MM-DD format", "Amount must be a positive number"]);
+  });
+});
@@ -5,13 +5,13 @@ import { describe, test, expect, beforeAll, afterAll } from "bun:test";
 import { existsSync, unlinkSync } from "node:fs";
 import { createDatabase, closeDatabase, getDatabasePath } from "../../src/server/db/connection.js";
 import { createUser } from "../../src/server/db/users-db.js";
-import { createAccount, getAccountById } from "../../src/server/db/accounts-db.js";
+import { createAccount, getAccountById, updateAccount } from "../../src/server/db/accounts-db.js";
 import { createInvestment } from "../../src/server/db/investments-db.js";
 import { getAllInvestmentTypes } from "../../src/server/db/investment-types-db.js";
 import { getAllCurrencies } from "../../src/server/db/currencies-db.js";
-import { createHolding, getHoldingById, getActiveHoldingRaw, getHoldingsByAccountId } from "../../src/server/db/holdings-db.js";
-import { createBuyMovement, createSellMovement, createSplitMovement, createReplacementMovement, getMovementById, getMovementsByHoldingId, scaleValue, unscaleValue } from "../../src/server/db/holding-movements-db.js";
-import { getCashTransactionsByAccountId } from "../../src/server/db/cash-transactions-db.js";
+import { createHolding, getHoldingById, getActiveHoldingRaw, getHoldingsByAccountId, getHoldingsAtDate } from "../../src/server/db/holdings-db.js";
+import { createBuyMovement, createSellMovement, createSplitMovement, createReplacementMovement, updateMovement, deleteMovement, getMovementById, getMovementsByHoldingId, scaleValue, unscaleValue } from "../../src/server/db/holding-movements-db.js";
+import { createCashTransaction, getCashTransactionsByAccountId } from "../../src/server/db/cash-transactions-db.js";
 import { upsertPrice, getPricesInRange, getLatestPrice } from "../../src/server/db/prices-db.js";
 import { getInvestmentById, getManuallyPricedInvestments } from "../../src/server/db/investments-db.js";
 
@@ -1074,3 +1074,330 @@ describe("Replacement movements", () => {
     expect(accountAfter.cash_balance).toBe(accountBefore.cash_balance);
   });
 });
+
+// --- Backdated buy/sell movements ---
+
+describe("backdated buy/sell movements", () => {
+  /** @type {Object} Fresh account so the SCD2 history is isolated */
+  let backdatedAccount;
+  /** @type {number} ID of the sell movement recorded on 2025-04-01 */
+  let sellMovementId;
+  /** @type {number} ID of the buy movement recorded on 2025-03-01 */
+  let laterBuyMovementId;
+
+  beforeAll(() => {
+    const backdatedUser = createUser({
+      initials: "BD",
+      first_name: "Backdated",
+      last_name: "Tester",
+      provider: "ii",
+    });
+    backdatedAccount = createAccount({
+      user_id: backdatedUser.id,
+      account_type: "trading",
+      account_ref: "BD001",
+      cash_balance: 20000,
+      warn_cash: 0,
+    });
+    createHolding({
+      account_id: backdatedAccount.id,
+      investment_id: investment1.id,
+      quantity: 0,
+      average_cost: 0,
+    });
+  });
+
+  /** @description Get the quantity and average cost held on a date */
+  function positionAt(date) {
+    const holdings = getHoldingsAtDate(backdatedAccount.id, date);
+    return holdings.length > 0 ? holdings[0] : null;
+  }
+
+  /** @description Get the current active holding for this test group */
+  function activeHolding() {
+    return getActiveHoldingRaw(backdatedAccount.id, investment1.id);
+  }
+
+  test("buy dated before the holding was added starts the history on the movement date", () => {
+    createBuyMovement({ holding_id: activeHolding().id, movement_date: "2025-01-10", quantity: 100, total_consideration: 1000 });
+
+    expect(positionAt("2025-01-09")).toBeNull();
+    expect(positionAt("2025-01-10").quantity).toBe(100);
+    expect(positionAt("2025-01-10").average_cost).toBe(10);
+  });
+
+  test("buy and sell create SCD2 rows at their movement dates", () => {
+    const buy = createBuyMovement({ holding_id: activeHolding().id, movement_date: "2025-03-01", quantity: 50, total_consideration: 750 });
+    laterBuyMovementId = buy.id;
+    const sell = createSellMovement({ holding_id: activeHolding().id, movement_date: "2025-04-01", quantity: 30, total_consideration: 400 });
+    sellMovementId = sell.id;
+
+    expect(positionAt("2025-02-28").quantity).toBe(100);
+    expect(positionAt("2025-03-01").quantity).toBe(150);
+    expect(positionAt("2025-04-01").quantity).toBe(120);
+    expect(sell.book_cost).toBeCloseTo(350, 2);
+  });
+
+  test("backdated buy recomputes quantity and average cost of every later row", () => {
+    createBuyMovement({ holding_id: activeHolding().id, movement_date: "2025-02-01", quantity: 50, total_consideration: 1250 });
+
+    expect(positionAt("2025-01-31").quantity).toBe(100);
+    expect(positionAt("2025-01-31").average_cost).toBe(10);
+
+    // 100 @ 10 + 50 @ 25 = 150 @ 15
+    expect(positionAt("2025-02-01").quantity).toBe(150);
+    expect(positionAt("2025-02-01").average_cost).toBe(15);
+
+    // Later buy of 50 @ 15 keeps the average at 15
+    expect(positionAt("2025-03-01").quantity).toBe(200);
+    expect(positionAt("2025-03-01").average_cost).toBeCloseTo(15, 4);
+
+    const active = getHoldingById(activeHolding().id);
+    expect(active.quantity).toBe(170);
+    expect(active.average_cost).toBeCloseTo(15, 4);
+  });
+
+  test("later movements pick up the revised average cost", () => {
+    expect(getMovementById(laterBuyMovementId).revised_avg_cost).toBeCloseTo(15, 4);
+    // Sell book cost is now 30 x 15 rather than 30 x 11.6667
+    expect(getMovementById(sellMovementId).book_cost).toBeCloseTo(450, 2);
+  });
+
+  test("backdated sell that would oversell a later sell is rejected and rolled back", () => {
+    expect(() => {
+      createSellMovement({ holding_id: activeHolding().id, movement_date: "2025-03-15", quantity: 190, total_consideration: 3000 });
+    }).toThrow("Insufficient holding quantity");
+
+    const active = getHoldingById(activeHolding().id);
+    expect(active.quantity).toBe(170);
+    expect(positionAt("2025-03-15").quantity).toBe(200);
+  });
+
+  test("backdated sell reduces every later row", () => {
+    createSellMovement({ holding_id: activeHolding().id, movement_date: "2025-03-15", quantity: 20, total_consideration: 400 });
+
+    expect(positionAt("2025-03-14").quantity).toBe(200);
+    expect(positionAt("2025-03-15").quantity).toBe(180);
+    expect(positionAt("2025-04-01").quantity).toBe(150);
+    expect(positionAt("2025-04-01").average_cost).toBeCloseTo(15, 4);
+  });
+
+  test("backdated split is dated on its movement date and carried into later rows", () => {
+    const split = createSplitMovement({ holding_id: activeHolding().id, movement_date: "2025-03-20", new_quantity: 360 });
+
+    expect(split.movement_date).toBe("2025-03-20");
+    expect(positionAt("2025-03-19").quantity).toBe(180);
+    expect(positionAt("2025-03-20").quantity).toBe(360);
+    expect(positionAt("2025-04-01").quantity).toBe(330);
+    expect(getHoldingById(activeHolding().id).quantity).toBe(330);
+  });
+
+  test("backdated buy must be covered by the cash held on its date and afterwards", () => {
+    const cashUser = createUser({ initials: "BC", first_name: "Backdated", last_name: "Cash", provider: "ii" });
+    const cashAccount = createAccount({ user_id: cashUser.id, account_type: "trading", account_ref: "BD002", cash_balance: 0, warn_cash: 0 });
+    const holding = createHolding({ account_id: cashAccount.id, investment_id: investment1.id, quantity: 0, average_cost: 0 });
+    createCashTransaction({ account_id: cashAccount.id, transaction_type: "deposit", transaction_date: "2025-01-01", amount: 1000 });
+    createCashTransaction({ account_id: cashAccount.id, transaction_type: "deposit", transaction_date: "2025-06-01", amount: 5000 });
+    createBuyMovement({ holding_id: holding.id, movement_date: "2025-01-15", quantity: 80, total_consideration: 800 });
+
+    // £1,000 was held on 10 January, but only £200 was left after the buy on the 15th
+    const active = getActiveHoldingRaw(cashAccount.id, investment1.id);
+    expect(() => {
+      createBuyMovement({ holding_id: active.id, movement_date: "2025-01-10", quantity: 50, total_consideration: 500 });
+    }).toThrow("Insufficient cash balance");
+    expect(getAccountById(cashAccount.id).cash_balance).toBe(5200);
+
+    createBuyMovement({ holding_id: active.id, movement_date: "2025-06-02", quantity: 50, total_consideration: 500 });
+    expect(getAccountById(cashAccount.id).cash_balance).toBe(4700);
+  });
+
+  test("buy from an account whose cash was only set on the account uses its current balance", () => {
+    const setUser = createUser({ initials: "BS", first_name: "Balance", last_name: "Set", provider: "ii" });
+    const setAccount = createAccount({ user_id: setUser.id, account_type: "trading", account_ref: "BD003", cash_balance: 0, warn_cash: 0 });
+    updateAccount(setAccount.id, { account_ref: "BD003", cash_balance: 3000, warn_cash: 0 });
+    const holding = createHolding({ account_id: setAccount.id, investment_id: investment1.id, quantity: 0, average_cost: 0 });
+
+    createBuyMovement({ holding_id: holding.id, movement_date: "2025-05-01", quantity: 100, total_consideration: 1200 });
+    expect(getAccountById(setAccount.id).cash_balance).toBe(1800);
+
+    const active = getActiveHoldingRaw(setAccount.id, investment1.id);
+    expect(() => {
+      createBuyMovement({ holding_id: active.id, movement_date: "2025-04-01", quantity: 200, total_consideration: 2000 });
+    }).toThrow("Insufficient cash balance");
+  });
+});
+
+describe("editing and deleting movements", () => {
+  /** @type {Object} Fresh account so the SCD2 history is isolated */
+  let editAccount;
+  /** @type {number} ID of the buy movement recorded on 2025-01-10 */
+  let firstBuyId;
+  /** @type {number} ID of the buy movement recorded on 2025-02-10 */
+  let secondBuyId;
+  /** @type {number} ID of the sell movement recorded on 2025-03-10 */
+  let sellId;
+
+  beforeAll(() => {
+    const editUser = createUser({
+      initials: "ED",
+      first_name: "Edit",
+      last_name: "Tester",
+      provider: "ii",
+    });
+    editAccount = createAccount({
+      user_id: editUser.id,
+      account_type: "trading",
+      account_ref: "ED001",
+      cash_balance: 0,
+      warn_cash: 0,
+    });
+    createCashTransaction({ account_id: editAccount.id, transaction_type: "deposit", transaction_date: "2025-01-01", amount: 10000 });
+    const holding = createHolding({
+      account_id: editAccount.id,
+      investment_id: investment1.id,
+      quantity: 0,
+      average_cost: 0,
+    });
+
+    firstBuyId = createBuyMovement({ holding_id: holding.id, movement_date: "2025-01-10", quantity: 100, total_consideration: 1000 }).id;
+    secondBuyId = createBuyMovement({ holding_id: activeHolding().id, movement_date: "2025-02-10", quantity: 100, total_consideration: 2000 }).id;
+    sellId = createSellMovement({ holding_id: activeHolding().id, movement_date: "2025-03-10", quantity: 50, total_consideration: 900 }).id;
+  });
+
+  /** @description Get the quantity and average cost held on a date */
+  function positionAt(date) {
+    const holdings = getHoldingsAtDate(editAccount.id, date);
+    return holdings.length > 0 ? holdings[0] : null;
+  }
+
+  /** @description Get the current active holding for this test group */
+  function activeHolding() {
+    return getActiveHoldingRaw(editAccount.id, investment1.id);
+  }
+
+  /** @description Find the cash transaction linked to a movement */
+  function cashTransactionFor(movementId) {
+    return getCashTransactionsByAccountId(editAccount.id).find((tx) => tx.holding_movement_id === movementId);
+  }
+
+  test("returns null or false for a non-existent movement", () => {
+    expect(updateMovement(99999, { quantity: 1 })).toBeNull();
+    expect(deleteMovement(99999)).toBe(false);
+  });
+
+  test("editing a buy's consideration replays average cost and later sell book cost", () => {
+    const updated = updateMovement(firstBuyId, { total_consideration: 2000 });
+    expect(updated.movement_value).toBe(2000);
+    expect(updated.book_cost).toBe(2000);
+    expect(updated.revised_avg_cost).toBe(20);
+
+    expect(positionAt("2025-01-10").average_cost).toBe(20);
+    expect(positionAt("2025-02-10").average_cost).toBe(20);
+    expect(getMovementById(sellId).book_cost).toBeCloseTo(1000, 2);
+
+    // Cash: 10000 - 2000 - 2000 + 900
+    expect(getAccountById(editAccount.id).cash_balance).toBe(6900);
+    expect(cashTransactionFor(firstBuyId).amount).toBe(2000);
+    expect(cashTransactionFor(sellId).balance_after).toBe(6900);
+    expect(cashTransactionFor(firstBuyId).balance_after).toBe(8000);
+  });
+
+  test("editing a buy's date moves its SCD2 row and cash transaction", () => {
+    updateMovement(secondBuyId, { movement_date: "2025-01-20" });
+
+    expect(positionAt("2025-01-19").quantity).toBe(100);
+    expect(positionAt("2025-01-20").quantity).toBe(200);
+    expect(positionAt("2025-03-10").quantity).toBe(150);
+    expect(getMovementById(secondBuyId).movement_date).toBe("2025-01-20");
+    expect(cashTransactionFor(secondBuyId).transaction_date).toBe("2025-01-20");
+  });
+
+  test("edit that would oversell the holding is rejected and rolled back", () => {
+    expect(() => {
+      updateMovement(sellId, { quantity: 300 });
+    }).toThrow("Insufficient holding quantity");
+
+    expect(getHoldingById(activeHolding().id).quantity).toBe(150);
+    expect(getMovementById(sellId).quantity).toBe(50);
+    expect(getAccountById(editAccount.id).cash_balance).toBe(6900);
+  });
+
+  test("deleting a sell restores the quantity and reverses its cash", () => {
+    const txCountBefore = getCashTransactionsByAccountId(editAccount.id).length;
+    expect(deleteMovement(sellId)).toBe(true);
+
+    expect(getMovementById(sellId)).toBeNull();
+    expect(getHoldingById(activeHolding().id).quantity).toBe(200);
+    expect(positionAt("2025-03-10").quantity).toBe(200);
+    expect(getAccountById(editAccount.id).cash_balance).toBe(6000);
+    expect(getCashTransactionsByAccountId(editAccount.id).length).toBe(txCountBefore - 1);
+  });
+
+  test("deleting a buy replays the remaining movements", () => {
+    expect(deleteMovement(firstBuyId)).toBe(true);
+
+    expect(positionAt("2025-01-15").quantity).toBe(0);
+    expect(positionAt("2025-01-20").quantity).toBe(100);
+    expect(positionAt("2025-01-20").average_cost).toBe(20);
+    expect(getMovementById(secondBuyId).revised_avg_cost).toBe(20);
+    expect(getAccountById(editAccount.id).cash_balance).toBe(8000);
+    expect(cashTransactionFor(secondBuyId).balance_after).toBe(8000);
+  });
+
+  test("deleting the only buy that funds a later sell is rejected", () => {
+    createSellMovement({ holding_id: activeHolding().id, movement_date: "2025-04-01", quantity: 60, total_consideration: 1500 });
+
+    expect(() => {
+      deleteMovement(secondBuyId);
+    }).toThrow("Insufficient holding quantity");
+    expect(getMovementById(secondBuyId)).not.toBeNull();
+    expect(getHoldingById(activeHolding().id).quantity).toBe(40);
+  });
+});
+
+describe("editing and deleting movements against later cash balances", () => {
+  /** @type {Object} Fresh account so the cash history is isolated */
+  let cashAccount;
+  /** @type {number} ID of the buy movement recorded on 2025-02-01 */
+  let buyId;
+  /** @type {number} ID of the sell movement recorded on 2025-03-01 */
+  let sellId;
+
+  beforeAll(() => {
+    const cashUser = createUser({ initials: "EC", first_name: "Edit", last_name: "Cash", provider: "ii" });
+    cashAccount = createAccount({ user_id: cashUser.id, account_type: "trading", account_ref: "EC001", cash_balance: 0, warn_cash: 0 });
+    const holding = createHolding({ account_id: cashAccount.id, investment_id: investment1.id, quantity: 0, average_cost: 0 });
+
+    // Balances: 1000, 200 after the buy, 1100 after the sell, 100 after the withdrawal, then 5100
+    createCashTransaction({ account_id: cashAccount.id, transaction_type: "deposit", transaction_date: "2025-01-01", amount: 1000 });
+    buyId = createBuyMovement({ holding_id: holding.id, movement_date: "2025-02-01", quantity: 100, total_consideration: 800 }).id;
+    sellId = createSellMovement({
+      holding_id: getActiveHoldingRaw(cashAccount.id, investment1.id).id,
+      movement_date: "2025-03-01",
+      quantity: 50,
+      total_consideration: 900,
+    }).id;
+    createCashTransaction({ account_id: cashAccount.id, transaction_type: "withdrawal", transaction_date: "2025-04-01", amount: 1000 });
+    createCashTransaction({ account_id: cashAccount.id, transaction_type: "deposit", transaction_date: "2025-06-01", amount: 5000 });
+  });
+
+  test("edit that takes a later running balance below zero is rejected even when today's balance covers it", () => {
+    expect(getAccountById(cashAccount.id).cash_balance).toBe(5100);
+
+    // Raising the buy to £1,000 would leave -£100 after the April withdrawal
+    expect(() => {
+      updateMovement(buyId, { total_consideration: 1000 });
+    }).toThrow("Insufficient cash balance");
+    expect(getMovementById(buyId).movement_value).toBe(800);
+    expect(getAccountById(cashAccount.id).cash_balance).toBe(5100);
+    expect(getCashTransactionsByAccountId(cashAccount.id).find((tx) => tx.holding_movement_id === buyId).amount).toBe(800);
+  });
+
+  test("deleting a sell whose proceeds fund a later withdrawal is rejected", () => {
+    expect(() => {
+      deleteMovement(sellId);
+    }).toThrow("Insufficient cash balance");
+    expect(getMovementById(sellId)).not.toBeNull();
+    expect(getAccountById(cashAccount.id).cash_balance).toBe(5100);
+  });
+});
@@ -511,4 +511,78 @@ describe("Holding Movements Routes", () => {
     });
     expect(response.status).toBe(404);
   });
+
+  // --- Edit and delete ---
+
+  test("PUT /api/holding-movements/:id: edits a buy and replays the holding", async () => {
+    let response = await fetch(`${BASE_URL}/api/holdings/${holding2Id}/movements`, {
+      method: "POST",
+      headers: { "Content-Type": "application/json" },
+      body: JSON.stringify({
+        movement_type: "buy",
+        movement_date: "2026-02-10",
+        quantity: 10,
+        total_consideration: 100,
+      }),
+    });
+    expect(response.status).toBe(201);
+    const created = await response.json();
+
+    response = await fetch(`${BASE_URL}/api/holding-movements/${created.movement.id}`, {
+      method: "PUT",
+      headers: { "Content-Type": "application/json" },
+      body: JSON.stringify({ total_consideration: 150 }),
+    });
+    expect(response.status).toBe(200);
+    const data = await response.json();
+
+    expect(data.movement.movement_value).toBe(150);
+    expect(data.movement.quantity).toBe(10);
+    expect(data.holding.quantity).toBe(10);
+    expect(data.holding.average_cost).toBe(15);
+    expect(data.account.cash_balance).toBe(created.account.cash_balance - 50);
+  });
+
+  test("PUT /api/holding-movements/:id: validation errors return 400", async () => {
+    const listResponse = await fetch(`${BASE_URL}/api/holdings/${holding2Id}/movements`);
+    const movements = await listResponse.json();
+
+    const response = await fetch(`${BASE_URL}/api/holding-movements/${movements[0].id}`, {
+      method: "PUT",
+      headers: { "Content-Type": "application/json" },
+      body: JSON.stringify({ movement_date: "10/02/2026" }),
+    });
+    expect(response.status).toBe(400);
+    const data = await response.json();
+    expect(data.error).toBe("Validation failed");
+  });
+
+  test("PUT /api/holding-movements/:id returns 404 for non-existent movement", async () => {
+    const response = await fetch(`${BASE_URL}/api/holding-movements/99999`, {
+      method: "PUT",
+      headers: { "Content-Type": "application/json" },
+      body: JSON.stringify({ quantity: 5 }),
+    });
+    expect(response.status).toBe(404);
+  });
+
+  test("DELETE /api/holding-movements/:id: reverses the movement and its cash", async () => {
+    const listResponse = await fetch(`${BASE_URL}/api/holdings/${holding2Id}/movements`);
+    const movements = await listResponse.json();
+    const accountResponse = await fetch(`${BASE_URL}/api/accounts/${testAccountId}`);
+    const accountBefore = await accountResponse.json();
+
+    const response = await fetch(`${BASE_URL}/api/holding-movements/${movements[0].id}`, { method: "DELETE" });
+    expect(response.status).toBe(200);
+    const data = await response.json();
+    expect(data.account.cash_balance).toBe(accountBefore.cash_balance + 150);
+
+    const getResponse = await fetch(`${BASE_URL}/api/holding-movements/${movements[0].id}`);
+    expect(getResponse.status).toBe(404);
+  });
+
+  test("DELETE /api/holding-movements/:id returns 404 for non-existent movement", async () => {
+    const response = await fetch(`${BASE_URL}/api/holding-movements/99999`, { method: "DELETE" });
+    expect(response.status).toBe(404);
+  });
 });
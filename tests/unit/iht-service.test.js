@@ -0,0 +1,173 @@
+// Set isolated DB path BEFORE importing connection.js (which reads it at module load)
+process.env.DB_PATH = "data/portfolio_60_test/test-iht-service.db";
+
+import { describe, test, expect, beforeAll, afterAll } from "bun:test";
+import { existsSync, unlinkSync } from "node:fs";
+import { createDatabase, closeDatabase, getDatabasePath } from "../../src/server/db/connection.js";
+import { getAllUsers, createUser } from "../../src/server/db/users-db.js";
+import { createAccount } from "../../src/server/db/accounts-db.js";
+import { createOtherAsset } from "../../src/server/db/other-assets-db.js";
+import { createGift } from "../../src/server/db/gifts-db.js";
+import { buildEstateEstimate, buildEstateEstimates, calculateInheritanceTax } from "../../src/server/services/iht-service.js";
+import { validateOtherAsset, validateUser } from "../../src/server/validation.js";
+
+const testDbPath = getDatabasePath();
+
+/** @description Bands and rate as in the default inheritanceTax config */
+const CONFIG = { nilRateBand: 325000, residenceNilRateBand: 175000, taperThreshold: 2000000, rate: 40, annualExemption: 3000, smallGiftLimit: 250 };
+
+/**
+ * @description Clean up the isolated test database files only.
+ */
+function cleanupDatabase() {
+  closeDatabase();
+  for (const suffix of ["", "-wal", "-shm"]) {
+    const filePath = testDbPath + suffix;
+    if (existsSync(filePath)) {
+      unlinkSync(filePath);
+    }
+  }
+}
+
+let arthur;
+let beatrice;
+let carol;
+
+beforeAll(() => {
+  cleanupDatabase();
+  createDatabase();
+
+  const joint = getAllUsers().find((u) => u.first_name === "Joint");
+  arthur = createUser({ initials: "AH", first_name: "Arthur", last_name: "Hale", provider: "ii" });
+  beatrice = createUser({ initials: "BH", first_name: "Beatrice", last_name: "Hale", provider: "ii", spouse_user_id: arthur.id });
+  carol = createUser({ initials: "CH", first_name: "Carol", last_name: "Hale", provider: "ii" });
+
+  // The couple's home and mortgage are held jointly
+  createOtherAsset({ user_id: joint.id, description: "The Grange", category: "property", value_type: "value", value: 9000000000, main_residence: true });
+  createOtherAsset({ user_id: joint.id, description: "Mortgage", category: "liability", value_type: "value", value: 1000000000 });
+
+  createAccount({ user_id: arthur.id, account_type: "isa", account_ref: "AH-ISA", cash_balance: 300000, warn_cash: 0 });
+  createAccount({ user_id: arthur.id, account_type: "sipp", account_ref: "AH-SIPP", cash_balance: 400000, warn_cash: 0 });
+  createOtherAsset({ user_id: arthur.id, description: "Cash ISA", category: "savings", value_type: "value", value: 500000000 });
+  createOtherAsset({ user_id: arthur.id, description: "State Pension", category: "pension", value_type: "recurring", frequency: "monthly", value: 10000000 });
+
+  createAccount({ user_id: beatrice.id, account_type: "trading", account_ref: "BH-TRD", cash_balance: 200000, warn_cash: 0 });
+  createOtherAsset({ user_id: beatrice.id, description: "Workplace Pension", category: "pension", value_type: "value", value: 800000000 });
+
+  createOtherAsset({ user_id: carol.id, description: "Savings", category: "savings", value_type: "value", value: 4000000000 });
+});
+
+afterAll(() => {
+  cleanupDatabase();
+  delete process.env.DB_PATH;
+});
+
+describe("IHT - calculateInheritanceTax", () => {
+  test("takes the nil-rate band and residence nil-rate band off the estate", () => {
+    expect(calculateInheritanceTax(750000, 450000, 1, CONFIG)).toEqual({
+      estate: 750000,
+      residence: 450000,
+      gifts: 0,
+      nil_rate_band: 325000,
+      residence_nil_rate_band: 175000,
+      taper_reduction: 0,
+      taxable: 250000,
+      tax: 100000,
+    });
+  });
+
+  test("limits the residence band to the home and tapers it over the threshold", () => {
+    expect(calculateInheritanceTax(600000, 100000, 1, CONFIG).residence_nil_rate_band).toBe(100000);
+    const tapered = calculateInheritanceTax(2200000, 500000, 1, CONFIG);
+    expect(tapered.residence_nil_rate_band).toBe(75000);
+    expect(tapered.tax).toBe(720000);
+    expect(calculateInheritanceTax(2500000, 500000, 1, CONFIG).residence_nil_rate_band).toBe(0);
+  });
+
+  test("sets gifts within seven years against the nil-rate band first", () => {
+    const withGifts = calculateInheritanceTax(750000, 450000, 1, CONFIG, 100000);
+    expect(withGifts.nil_rate_band).toBe(225000);
+    expect(withGifts.tax).toBe(140000);
+    expect(calculateInheritanceTax(750000, 450000, 1, CONFIG, 400000).nil_rate_band).toBe(0);
+  });
+
+  test("charges nothing on an estate within the bands or in deficit", () => {
+    expect(calculateInheritanceTax(300000, 0, 1, CONFIG).tax).toBe(0);
+    expect(calculateInheritanceTax(-5000, 0, 1, CONFIG).taxable).toBe(0);
+  });
+});
+
+describe("IHT - buildEstateEstimate", () => {
+  test("counts ISAs, other assets and half the couple's joint items, less liabilities", () => {
+    const estimate = buildEstateEstimate(arthur.id);
+    expect(estimate.assets.map((a) => [a.description, a.value, a.share])).toEqual([
+      ["II ISA AH-ISA", 300000, 1],
+      ["The Grange", 450000, 0.5],
+      ["Cash ISA", 50000, 1],
+    ]);
+    expect(estimate.liabilities.map((l) => [l.description, l.value])).toEqual([["Mortgage", 50000]]);
+    expect(estimate.totals).toEqual({ assets: 800000, liabilities: 50000, net_estate: 750000, residence: 450000, outside_estate: 400000 });
+    expect(estimate.estate_alone.tax).toBe(100000);
+  });
+
+  test("flags SIPPs and pension pots as outside the estate and leaves out recurring income", () => {
+    expect(buildEstateEstimate(arthur.id).outside_estate.map((i) => [i.description, i.note])).toEqual([["II SIPP AH-SIPP", "Pensions are generally outside the estate"]]);
+    expect(buildEstateEstimate(beatrice.id).outside_estate.map((i) => i.description)).toEqual(["Workplace Pension"]);
+  });
+
+  test("passes the estate to the spouse tax free and uses both sets of bands on the second death", () => {
+    const estimate = buildEstateEstimate(beatrice.id);
+    expect(estimate.spouse.id).toBe(arthur.id);
+    expect(estimate.first_death).toEqual({ left_to_spouse: 600000, tax: 0, nil_rate_band_transferred: 325000, residence_nil_rate_band_transferred: 175000 });
+    expect(estimate.second_death).toEqual({
+      estate: 1350000,
+      residence: 900000,
+      gifts: 0,
+      nil_rate_band: 650000,
+      residence_nil_rate_band: 350000,
+      taper_reduction: 0,
+      taxable: 350000,
+      tax: 140000,
+      spouse_estate: 750000,
+    });
+  });
+
+  test("gives a single person no share of the couple's joint items and no transfer", () => {
+    const estimate = buildEstateEstimate(carol.id);
+    expect(estimate.totals.net_estate).toBe(400000);
+    expect(estimate.first_death).toBeNull();
+    expect(estimate.second_death).toBeNull();
+    expect(estimate.estate_alone.tax).toBe(30000);
+  });
+
+  test("reduces the nil-rate band by gifts in the last seven years, and so the band passed to a spouse", () => {
+    const giftDate = new Date(Date.now() - 400 * 24 * 60 * 60 * 1000).toISOString().slice(0, 10);
+    createGift({ user_id: carol.id, recipient: "Nephew", gift_date: giftDate, amount: 53000, exemption_type: "annual" });
+    const carolEstimate = buildEstateEstimate(carol.id);
+    expect(carolEstimate.gifts.pets_within_seven_years).toBe(47000);
+    expect(carolEstimate.estate_alone.nil_rate_band).toBe(278000);
+    expect(carolEstimate.estate_alone.tax).toBe(48800);
+
+    createGift({ user_id: beatrice.id, recipient: "Niece", gift_date: giftDate, amount: 106000, exemption_type: "annual" });
+    const estimate = buildEstateEstimate(beatrice.id);
+    expect(estimate.first_death.nil_rate_band_transferred).toBe(225000);
+    expect(estimate.second_death.nil_rate_band).toBe(550000);
+  });
+
+  test("leaves out the Joint household user", () => {
+    expect(buildEstateEstimates(null).map((e) => e.user.first_name)).toEqual(["Arthur", "Beatrice", "Carol"]);
+    expect(buildEstateEstimate(getAllUsers().find((u) => u.first_name === "Joint").id)).toBeNull();
+  });
+});
+
+describe("IHT - validation", () => {
+  test("only a value-type property can be the main residence", () => {
+    const asset = { user_id: 1, description: "Flat", category: "property", value_type: "value", value: 1, main_residence: true };
+    expect(validateOtherAsset(asset)).toEqual([]);
+    expect(validateOtherAsset({ ...asset, category: "savings" })).toEqual(["Only property can be marked as the main residence"]);
+  });
+
+  test("rejects a spouse that is not a user ID", () => {
+    expect(validateUser({ initials: "X", first_name: "X", last_name: "Y", provider: "ii", spouse_user_id: "abc" })).toContain("Spouse must be a valid selection");
+  });
+});
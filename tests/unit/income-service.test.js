@@ -0,0 +1,146 @@
+// Set isolated DB path BEFORE importing connection.js (which reads it at module load)
+process.env.DB_PATH = "data/portfolio_60_test/test-income-service.db";
+
+import { describe, test, expect, beforeAll, afterAll } from "bun:test";
+import { existsSync, unlinkSync } from "node:fs";
+import { createDatabase, closeDatabase, getDatabasePath } from "../../src/server/db/connection.js";
+import { createUser } from "../../src/server/db/users-db.js";
+import { createAccount } from "../../src/server/db/accounts-db.js";
+import { createInvestment } from "../../src/server/db/investments-db.js";
+import { getAllInvestmentTypes } from "../../src/server/db/investment-types-db.js";
+import { getAllCurrencies } from "../../src/server/db/currencies-db.js";
+import { createHolding } from "../../src/server/db/holdings-db.js";
+import { createCashTransaction } from "../../src/server/db/cash-transactions-db.js";
+import { getAccountIncome, getHoldingIncome } from "../../src/server/services/income-service.js";
+
+const testDbPath = getDatabasePath();
+
+/**
+ * @description Clean up the isolated test database files only.
+ */
+function cleanupDatabase() {
+  closeDatabase();
+  for (const suffix of ["", "-wal", "-shm"]) {
+    const filePath = testDbPath + suffix;
+    if (existsSync(filePath)) {
+      unlinkSync(filePath);
+    }
+  }
+}
+
+/** @type {Object} Test account */
+let account;
+/** @type {Object} Holding in the share investment */
+let shareHolding;
+/** @type {Object} Holding in the bond fund */
+let bondHolding;
+
+beforeAll(() => {
+  cleanupDatabase();
+  createDatabase();
+
+  const user = createUser({
+    initials: "RC",
+    first_name: "Robert",
+    last_name: "Collins",
+    provider: "ii",
+  });
+
+  account = createAccount({
+    user_id: user.id,
+    account_type: "isa",
+    account_ref: "I2001",
+    cash_balance: 0,
+    warn_cash: 0,
+  });
+
+  const types = getAllInvestmentTypes();
+  const gbp = getAllCurrencies().find((c) => c.code === "GBP");
+  const share = createInvestment({
+    currencies_id: gbp.id,
+    investment_type_id: types.find((t) => t.short_description === "SHARE").id,
+    description: "Dividend Payer plc",
+    unit_type: "income",
+  });
+  const bond = createInvestment({
+    currencies_id: gbp.id,
+    investment_type_id: types[0].id,
+    description: "Gilt Income Fund",
+    unit_type: "income",
+  });
+
+  shareHolding = createHolding({ account_id: account.id, investment_id: share.id, quantity: 500, average_cost: 2 });
+  bondHolding = createHolding({ account_id: account.id, investment_id: bond.id, quantity: 100, average_cost: 10 });
+
+  // 2024/2025: one dividend
+  createCashTransaction({ account_id: account.id, transaction_type: "dividend", transaction_date: "2025-04-05", amount: 20, investment_id: share.id });
+  // 2025/2026: two dividends, fund interest and cash interest
+  createCashTransaction({ account_id: account.id, transaction_type: "dividend", transaction_date: "2025-04-06", amount: 25, investment_id: share.id });
+  createCashTransaction({ account_id: account.id, transaction_type: "dividend", transaction_date: "2025-10-06", amount: 30, investment_id: share.id });
+  createCashTransaction({ account_id: account.id, transaction_type: "interest", transaction_date: "2025-09-30", amount: 15.5, investment_id: bond.id });
+  createCashTransaction({ account_id: account.id, transaction_type: "interest", transaction_date: "2025-12-31", amount: 1.25 });
+});
+
+afterAll(() => {
+  cleanupDatabase();
+  delete process.env.DB_PATH;
+});
+
+describe("Income Service - getAccountIncome", function () {
+  test("returns null for a non-existent account", function () {
+    expect(getAccountIncome(99999)).toBeNull();
+  });
+
+  test("groups income by tax year, newest first", function () {
+    const income = getAccountIncome(account.id);
+    expect(income.tax_years.map((ty) => ty.label)).toEqual(["2025/2026", "2024/2025"]);
+
+    const current = income.tax_years[0];
+    expect(current.dividends).toBe(55);
+    expect(current.interest).toBe(16.75);
+    expect(current.total).toBe(71.75);
+
+    expect(income.tax_years[1].dividends).toBe(20);
+    expect(income.totals.total).toBe(91.75);
+  });
+
+  test("breaks each tax year down by investment", function () {
+    const income = getAccountIncome(account.id);
+    const holdings = income.tax_years[0].holdings;
+    expect(holdings.length).toBe(3);
+
+    const share = holdings.find((h) => h.description === "Dividend Payer plc");
+    expect(share.dividends).toBe(55);
+    expect(share.payment_count).toBe(2);
+    expect(share.unit_type).toBe("income");
+
+    const cash = holdings.find((h) => h.investment_id === null);
+    expect(cash.interest).toBe(1.25);
+  });
+
+  test("restricts output to a single tax year when requested", function () {
+    const income = getAccountIncome(account.id, 2024);
+    expect(income.tax_years.length).toBe(1);
+    expect(income.tax_years[0].label).toBe("2024/2025");
+    expect(income.totals.total).toBe(20);
+  });
+});
+
+describe("Income Service - getHoldingIncome", function () {
+  test("returns null for a non-existent holding", function () {
+    expect(getHoldingIncome(99999)).toBeNull();
+  });
+
+  test("returns only income paid by the holding's investment", function () {
+    const income = getHoldingIncome(shareHolding.id);
+    expect(income.total).toBe(75);
+    expect(income.tax_years.length).toBe(2);
+    expect(income.tax_years[0].payments.length).toBe(2);
+  });
+
+  test("reports interest separately from dividends", function () {
+    const income = getHoldingIncome(bondHolding.id);
+    expect(income.tax_years[0].dividends).toBe(0);
+    expect(income.tax_years[0].interest).toBe(15.5);
+  });
+});
@@ -134,6 +134,7 @@ describe("Investments - createInvestment", () => {
     expect(inv).not.toBeNull();
     expect(inv.investment_url).toBeNull();
     expect(inv.selector).toBeNull();
+    expect(inv.allocation_tag).toBeNull();
     expect(inv.type_short).toBe("MUTUAL");
   });
 
@@ -212,9 +213,11 @@ describe("Investments - updateInvestment", () => {
       description: "Fundsmith Equity Fund (Updated)",
       investment_url: "https://www.example.com/updated",
       selector: ".new-selector",
+      allocation_tag: "  Global Equity  ",
     });
 
     expect(updated).not.toBeNull();
+    expect(updated.allocation_tag).toBe("Global Equity");
     expect(updated.description).toBe("Fundsmith Equity Fund (Updated)");
     expect(updated.type_short).toBe("TRUST");
     expect(updated.investment_url).toBe("https://www.example.com/updated");
@@ -0,0 +1,187 @@
+// Set isolated DB path BEFORE importing connection.js (which reads it at module load)
+process.env.DB_PATH = "data/portfolio_60_test/test-isa-allowance-service.db";
+
+import { describe, test, expect, beforeAll, afterAll } from "bun:test";
+import { existsSync, unlinkSync } from "node:fs";
+import { createDatabase, closeDatabase, getDatabasePath } from "../../src/server/db/connection.js";
+import { createUser } from "../../src/server/db/users-db.js";
+import { createAccount, getAccountById } from "../../src/server/db/accounts-db.js";
+import { createCashTransaction, createIsaTransfer } from "../../src/server/db/cash-transactions-db.js";
+import { getTaxYearForDate } from "../../src/server/services/tax-year-utils.js";
+import {
+  getIsaAnnualLimit,
+  getIsaAllowanceForUser,
+  getIsaAllowanceHistory,
+  getIsaAllowanceForAllUsers,
+  checkIsaSubscription,
+} from "../../src/server/services/isa-allowance-service.js";
+
+const testDbPath = getDatabasePath();
+
+/**
+ * @description Clean up the isolated test database files only.
+ */
+function cleanupDatabase() {
+  closeDatabase();
+  for (const suffix of ["", "-wal", "-shm"]) {
+    const filePath = testDbPath + suffix;
+    if (existsSync(filePath)) {
+      unlinkSync(filePath);
+    }
+  }
+}
+
+/** @type {Object} User holding two ISAs */
+let saver;
+/** @type {Object} User holding only a SIPP */
+let pensioner;
+/** @type {Object} User holding one unused ISA */
+let newcomer;
+/** @type {Object} Saver's ii ISA */
+let iiIsa;
+/** @type {Object} Saver's HL ISA */
+let hlIsa;
+/** @type {Object} Pensioner's SIPP */
+let sipp;
+
+beforeAll(() => {
+  cleanupDatabase();
+  createDatabase();
+
+  saver = createUser({ initials: "SV", first_name: "Sam", last_name: "Saver", provider: "ii" });
+  pensioner = createUser({ initials: "PN", first_name: "Pat", last_name: "Pensioner", provider: "ii" });
+  newcomer = createUser({ initials: "NC", first_name: "Nia", last_name: "Newcomer", provider: "hl" });
+
+  iiIsa = createAccount({ user_id: saver.id, account_type: "isa", account_ref: "II-ISA", cash_balance: 0, warn_cash: 0 });
+  hlIsa = createAccount({ user_id: saver.id, account_type: "isa", account_ref: "HL-ISA", provider: "hl", cash_balance: 0, warn_cash: 0 });
+  sipp = createAccount({ user_id: pensioner.id, account_type: "sipp", account_ref: "PN-SIPP", cash_balance: 0, warn_cash: 0 });
+  createAccount({ user_id: newcomer.id, account_type: "isa", account_ref: "NC-ISA", provider: "hl", cash_balance: 0, warn_cash: 0 });
+
+  // 2024/2025 subscription, then nothing in 2025/2026
+  createCashTransaction({ account_id: iiIsa.id, transaction_type: "deposit", transaction_date: "2024-05-01", amount: 20000 });
+
+  // 2026/2027 subscriptions split across both providers
+  createCashTransaction({ account_id: iiIsa.id, transaction_type: "deposit", transaction_date: "2026-05-01", amount: 8000 });
+  createCashTransaction({ account_id: hlIsa.id, transaction_type: "deposit", transaction_date: "2026-06-01", amount: 2000 });
+
+  // Move part of this year's ii subscription to HL — must not count twice
+  createIsaTransfer({ from_account_id: iiIsa.id, to_account_id: hlIsa.id, transaction_date: "2026-07-01", amount: 3000, isa_transfer: "current_year" });
+
+  // Transfers in from an ISA held elsewhere: this year's subscriptions count, earlier years' do not
+  createIsaTransfer({ from_account_id: null, to_account_id: hlIsa.id, transaction_date: "2026-08-01", amount: 1500, isa_transfer: "current_year" });
+  createIsaTransfer({ from_account_id: null, to_account_id: hlIsa.id, transaction_date: "2026-08-15", amount: 5000, isa_transfer: "previous_years" });
+
+  // Transfer out to an ISA held elsewhere does not give allowance back
+  createIsaTransfer({ from_account_id: iiIsa.id, to_account_id: null, transaction_date: "2026-09-01", amount: 1000, isa_transfer: "current_year" });
+});
+
+afterAll(() => {
+  cleanupDatabase();
+  delete process.env.DB_PATH;
+});
+
+describe("ISA Allowance - limits", function () {
+  test("uses published limits for early years and the configured limit otherwise", function () {
+    expect(getIsaAnnualLimit(2014)).toBe(15000);
+    expect(getIsaAnnualLimit(2016)).toBe(15240);
+    expect(getIsaAnnualLimit(2026)).toBe(20000);
+  });
+});
+
+describe("ISA Allowance - transfers", function () {
+  test("moves cash between the person's ISAs", function () {
+    // 20000 + 8000 - 3000 - 1000
+    expect(getAccountById(iiIsa.id).cash_balance).toBe(24000);
+    // 2000 + 3000 + 1500 + 5000
+    expect(getAccountById(hlIsa.id).cash_balance).toBe(11500);
+  });
+
+  test("links both legs of an internal transfer", function () {
+    const result = createIsaTransfer({ from_account_id: hlIsa.id, to_account_id: iiIsa.id, transaction_date: "2026-09-10", amount: 10, isa_transfer: "previous_years" });
+    expect(result.withdrawal.transfer_account_id).toBe(iiIsa.id);
+    expect(result.deposit.transfer_account_id).toBe(hlIsa.id);
+    expect(result.deposit.isa_transfer).toBe("previous_years");
+  });
+
+  test("throws when the source ISA has insufficient cash", function () {
+    expect(() =>
+      createIsaTransfer({ from_account_id: hlIsa.id, to_account_id: iiIsa.id, transaction_date: "2026-09-10", amount: 1000000, isa_transfer: "current_year" }),
+    ).toThrow("Insufficient cash balance");
+  });
+});
+
+describe("ISA Allowance - per person", function () {
+  test("returns null for a non-existent user", function () {
+    expect(getIsaAllowanceForUser(99999, 2026)).toBeNull();
+  });
+
+  test("sums subscriptions across every ISA the person holds", function () {
+    const usage = getIsaAllowanceForUser(saver.id, 2026);
+    expect(usage.tax_year).toBe("2026/2027");
+    expect(usage.annual_limit).toBe(20000);
+    expect(usage.deposits).toBe(10000);
+    expect(usage.transfers_in).toBe(1500);
+    expect(usage.used).toBe(11500);
+    expect(usage.remaining).toBe(8500);
+    expect(usage.over_limit).toBe(false);
+
+    const ii = usage.accounts.find((a) => a.account_id === iiIsa.id);
+    const hl = usage.accounts.find((a) => a.account_id === hlIsa.id);
+    expect(ii.total).toBe(8000);
+    expect(hl.deposits).toBe(2000);
+    expect(hl.transfers_in).toBe(1500);
+    expect(hl.provider).toBe("hl");
+  });
+
+  test("reports an earlier tax year on request", function () {
+    const usage = getIsaAllowanceForUser(saver.id, 2024);
+    expect(usage.used).toBe(20000);
+    expect(usage.remaining).toBe(0);
+    expect(usage.over_limit).toBe(false);
+  });
+
+  test("history runs from the first subscription to the current tax year, including empty years", function () {
+    const currentStart = Number(getTaxYearForDate(new Date().toISOString().slice(0, 10)).start.slice(0, 4));
+    const history = getIsaAllowanceHistory(saver.id);
+    const labels = history.tax_years.map((ty) => ty.tax_year);
+
+    expect(history.tax_years.length).toBe(currentStart - 2024 + 1);
+    expect(labels[labels.length - 1]).toBe("2024/2025");
+    expect(history.tax_years.find((ty) => ty.tax_year === "2025/2026").used).toBe(0);
+    expect(history.tax_years.find((ty) => ty.tax_year === "2026/2027").used).toBe(11500);
+  });
+});
+
+describe("ISA Allowance - household", function () {
+  test("lists only people holding an ISA", function () {
+    const usages = getIsaAllowanceForAllUsers(null, 2026);
+    const ids = usages.map((u) => u.user.id);
+    expect(ids).toContain(saver.id);
+    expect(ids).toContain(newcomer.id);
+    expect(ids).not.toContain(pensioner.id);
+    expect(usages.find((u) => u.user.id === newcomer.id).remaining).toBe(20000);
+  });
+
+  test("can be restricted to selected people", function () {
+    const usages = getIsaAllowanceForAllUsers([newcomer.id], 2026);
+    expect(usages.length).toBe(1);
+    expect(usages[0].user.initials).toBe("NC");
+  });
+});
+
+describe("ISA Allowance - subscription check", function () {
+  test("flags a subscription that would exceed the person's remaining allowance", function () {
+    expect(checkIsaSubscription(hlIsa.id, "2026-10-01", 9000).exceeds).toBe(true);
+    expect(checkIsaSubscription(iiIsa.id, "2026-10-01", 8500).exceeds).toBe(false);
+  });
+
+  test("checks against the tax year of the subscription date", function () {
+    const check = checkIsaSubscription(iiIsa.id, "2025-10-01", 20000);
+    expect(check.tax_year).toBe("2025/2026");
+    expect(check.exceeds).toBe(false);
+  });
+
+  test("returns null for an account that is not an ISA", function () {
+    expect(checkIsaSubscription(sipp.id, "2026-10-01", 100)).toBeNull();
+  });
+});
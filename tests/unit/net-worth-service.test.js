@@ -0,0 +1,207 @@
+// Set isolated DB path BEFORE importing connection.js
+process.env.DB_PATH = "data/portfolio_60_test/test-net-worth-service.db";
+
+import { describe, test, expect, beforeAll, afterAll } from "bun:test";
+import { existsSync, unlinkSync } from "node:fs";
+import { createDatabase, closeDatabase, getDatabasePath, getDatabase } from "../../src/server/db/connection.js";
+import { createUser } from "../../src/server/db/users-db.js";
+import {
+  createOtherAsset,
+  getOtherAssetById,
+  getOtherAssetTotalsAtDate,
+  closeOtherAsset,
+  getHouseholdAssetsSummary,
+  getRevaluationDueDate,
+  getOverdueOtherAssets,
+} from "../../src/server/db/other-assets-db.js";
+import { buildAmortisationSchedule, buildOtherAssetValueHistory } from "../../src/server/services/net-worth-service.js";
+import { validateOtherAsset, validateOtherAssetClose } from "../../src/server/validation.js";
+
+const testDbPath = getDatabasePath();
+
+/**
+ * @description Clean up the isolated test database files.
+ */
+function cleanupDatabase() {
+  closeDatabase();
+  for (const suffix of ["", "-wal", "-shm"]) {
+    const filePath = testDbPath + suffix;
+    if (existsSync(filePath)) {
+      unlinkSync(filePath);
+    }
+  }
+}
+
+let mortgageId;
+let houseId;
+
+beforeAll(() => {
+  cleanupDatabase();
+  createDatabase();
+
+  const user = createUser({
+    initials: "NW",
+    first_name: "Nina",
+    last_name: "Walker",
+    provider: "ii",
+    trading_ref: null,
+    isa_ref: null,
+    sipp_ref: null,
+  });
+
+  houseId = createOtherAsset({ user_id: user.id, description: "House", category: "property", value_type: "value", value: 4000000000, revalue_months: 12, start_date: "2020-05-01" }).id;
+  createOtherAsset({ user_id: user.id, description: "State Pension", category: "pension", value_type: "recurring", frequency: "monthly", value: 10000000 });
+  mortgageId = createOtherAsset({
+    user_id: user.id,
+    description: "Mortgage",
+    category: "liability",
+    value_type: "value",
+    value: 1500000000,
+    interest_rate: 4.5,
+    repayment: 9000000,
+    start_date: "2020-05-01",
+  }).id;
+
+  // The mortgage balance was higher before each of these changes
+  const db = getDatabase();
+  db.run("INSERT INTO other_assets_history (other_asset_id, change_date, revised_value) VALUES (?, ?, ?)", [mortgageId, "2026-03-01", 1700000000]);
+  db.run("INSERT INTO other_assets_history (other_asset_id, change_date, revised_value) VALUES (?, ?, ?)", [mortgageId, "2026-06-01", 1600000000]);
+
+  // The house was last valued more than a year ago, with a lower value before that
+  db.run("UPDATE other_assets SET last_updated = ? WHERE id = ?", ["2024-01-31", houseId]);
+});
+
+afterAll(() => {
+  cleanupDatabase();
+  delete process.env.DB_PATH;
+});
+
+describe("Net Worth - buildAmortisationSchedule", function () {
+  test("charges monthly interest and clears the balance with a smaller last payment", function () {
+    const schedule = buildAmortisationSchedule(1000, 12, 300, "2026-01-31");
+    expect(schedule.rows.map((r) => r.date)).toEqual(["2026-02-28", "2026-03-31", "2026-04-30", "2026-05-31"]);
+    expect(schedule.rows[0]).toEqual({ date: "2026-02-28", payment: 300, interest: 10, principal: 290, balance: 710 });
+    expect(schedule.rows[3].payment).toBe(122.48);
+    expect(schedule.rows[3].balance).toBe(0);
+    expect(schedule.months).toBe(4);
+    expect(schedule.payoff_date).toBe("2026-05-31");
+    expect(schedule.total_interest).toBe(22.48);
+    expect(schedule.total_paid).toBe(1022.48);
+    expect(schedule.never_repaid).toBe(false);
+  });
+
+  test("repays an interest-free balance in equal instalments", function () {
+    const schedule = buildAmortisationSchedule(1200, null, 100, "2026-01-15");
+    expect(schedule.months).toBe(12);
+    expect(schedule.payoff_date).toBe("2027-01-15");
+    expect(schedule.total_interest).toBe(0);
+  });
+
+  test("reports a balance the repayment never clears", function () {
+    const schedule = buildAmortisationSchedule(100000, 6, 500, "2026-01-01");
+    expect(schedule.never_repaid).toBe(true);
+    expect(schedule.rows).toEqual([]);
+    expect(schedule.payoff_date).toBeNull();
+  });
+});
+
+describe("Net Worth - liabilities", function () {
+  test("stores the interest rate as a percentage and the repayment scaled", function () {
+    const mortgage = getOtherAssetById(mortgageId);
+    expect(mortgage.interest_rate).toBe(4.5);
+    expect(mortgage.repayment).toBe(9000000);
+  });
+
+  test("totals liabilities apart from assets and nets them off", function () {
+    const summary = getHouseholdAssetsSummary();
+    expect(summary.categories.liability.items.map((i) => i.description)).toEqual(["Mortgage"]);
+    expect(summary.totals.value_total).toBe(4000000000);
+    expect(summary.totals.liabilities_total).toBe(1500000000);
+    expect(summary.totals.net_assets).toBe(2500000000);
+    expect(summary.totals.recurring_annual).toBe(120000000);
+  });
+
+  test("values assets and liabilities on a past date from the change history", function () {
+    expect(getOtherAssetTotalsAtDate("2026-01-31")).toEqual({ assets: 4000000000, liabilities: 1700000000 });
+    expect(getOtherAssetTotalsAtDate("2026-04-30")).toEqual({ assets: 4000000000, liabilities: 1600000000 });
+    expect(getOtherAssetTotalsAtDate("2026-06-01")).toEqual({ assets: 4000000000, liabilities: 1500000000 });
+  });
+});
+
+describe("Net Worth - validateOtherAsset", function () {
+  const liability = { user_id: 1, description: "Car Loan", category: "liability", value_type: "value", value: 84000000, interest_rate: 6.9, repayment: 3250000 };
+
+  test("accepts a liability with an interest rate and repayment", function () {
+    expect(validateOtherAsset(liability)).toEqual([]);
+  });
+
+  test("rejects a recurring liability and out-of-range loan terms", function () {
+    expect(validateOtherAsset({ ...liability, value_type: "recurring", frequency: "monthly" })).toContain("A liability must use the 'value' type for the balance owed");
+    expect(validateOtherAsset({ ...liability, interest_rate: 120 })).toContain("Interest rate must be between 0% and 100%");
+    expect(validateOtherAsset({ ...liability, repayment: -1 })).toContain("Monthly repayment must be zero or a positive number");
+  });
+
+  test("rejects loan terms on an asset", function () {
+    expect(validateOtherAsset({ ...liability, category: "savings" })).toContain("Interest rate and repayment can only be set for liabilities");
+  });
+});
+
+describe("Net Worth - buildOtherAssetValueHistory", function () {
+  test("gives each asset's value and each category total at every date", function () {
+    const history = buildOtherAssetValueHistory(3);
+    expect(history.assets.map((a) => a.description)).toEqual(["State Pension", "House", "Mortgage"]);
+    expect(history.categories.map((c) => c.label)).toEqual(["Property", "Liabilities"]);
+    for (const asset of history.assets) {
+      expect(asset.values.length).toBe(history.dates.length);
+    }
+    expect(history.categories[0].values[history.dates.length - 1]).toBe(400000);
+    expect(history.categories[1].values[history.dates.length - 1]).toBe(150000);
+  });
+
+  test("filters to categories or a single asset", function () {
+    expect(buildOtherAssetValueHistory(3, { categories: ["liability"] }).assets.map((a) => a.description)).toEqual(["Mortgage"]);
+    const house = buildOtherAssetValueHistory(3, { assetId: houseId });
+    expect(house.assets.map((a) => a.id)).toEqual([houseId]);
+  });
+});
+
+describe("Net Worth - revaluation reminders", function () {
+  test("is due the set number of months after the last update", function () {
+    expect(getRevaluationDueDate({ last_updated: "2024-01-31", revalue_months: 1 })).toBe("2024-02-29");
+    expect(getRevaluationDueDate({ last_updated: "2026-03-01", revalue_months: 12 })).toBe("2027-03-01");
+    expect(getRevaluationDueDate({ last_updated: "2026-03-01", revalue_months: null })).toBeNull();
+  });
+
+  test("lists assets past their revaluation date", function () {
+    const overdue = getOverdueOtherAssets();
+    expect(overdue.map((a) => [a.description, a.revalue_due_date])).toEqual([["House", "2025-01-31"]]);
+    expect(getOtherAssetById(mortgageId).revalue_overdue).toBe(false);
+  });
+
+  test("rejects a revaluation interval that is not 1 to 120 whole months", function () {
+    const asset = { user_id: 1, description: "Flat", category: "property", value_type: "value", value: 1 };
+    expect(validateOtherAsset({ ...asset, revalue_months: 12 })).toEqual([]);
+    expect(validateOtherAsset({ ...asset, revalue_months: 0 })).toContain("Revalue every must be a whole number of months from 1 to 120");
+    expect(validateOtherAsset({ ...asset, revalue_months: 1.5 })).toContain("Revalue every must be a whole number of months from 1 to 120");
+  });
+});
+
+describe("Net Worth - held dates", function () {
+  test("leaves an item out before it was held and keeps a closed item on earlier dates", function () {
+    const car = createOtherAsset({ user_id: getOtherAssetById(houseId).user_id, description: "Car", category: "alternative", value_type: "value", value: 120000000, start_date: "2025-03-10" });
+    expect(getOtherAssetTotalsAtDate("2025-03-09").assets).toBe(4000000000);
+    expect(getOtherAssetTotalsAtDate("2025-03-10").assets).toBe(4120000000);
+
+    expect(closeOtherAsset(car.id, "2026-02-15")).toBe(true);
+    expect(getOtherAssetById(car.id)).toBeNull();
+    expect(getOtherAssetTotalsAtDate("2026-01-31").assets).toBe(4120000000);
+    expect(getOtherAssetTotalsAtDate("2026-02-15").assets).toBe(4000000000);
+    expect(closeOtherAsset(car.id)).toBe(false);
+  });
+
+  test("rejects a closed date in the future or before the item was held", function () {
+    expect(validateOtherAssetClose({ closed_date: "2025-03-09" }, "2025-03-10")).toContain("Closed date cannot be before the date the asset was first held");
+    expect(validateOtherAssetClose({ closed_date: "2999-01-01" }, null)).toContain("Closed date cannot be in the future");
+    expect(validateOtherAssetClose({}, "2025-03-10")).toEqual([]);
+  });
+});
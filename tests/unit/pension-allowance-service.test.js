@@ -0,0 +1,234 @@
+// Set isolated DB path BEFORE importing connection.js (which reads it at module load)
+process.env.DB_PATH = "data/portfolio_60_test/test-pension-allowance-service.db";
+
+import { describe, test, expect, beforeAll, afterAll } from "bun:test";
+import { existsSync, unlinkSync } from "node:fs";
+import { createDatabase, closeDatabase, getDatabasePath } from "../../src/server/db/connection.js";
+import { createUser } from "../../src/server/db/users-db.js";
+import { createAccount, getAccountById } from "../../src/server/db/accounts-db.js";
+import { createCashTransaction, createPensionContribution } from "../../src/server/db/cash-transactions-db.js";
+import { createDrawdownSchedule } from "../../src/server/db/drawdown-schedules-db.js";
+import { getTaxYearForDate } from "../../src/server/services/tax-year-utils.js";
+import { processDrawdowns } from "../../src/server/services/drawdown-processor.js";
+import {
+  getAnnualAllowance,
+  getMoneyPurchaseAnnualAllowance,
+  splitReliefAtSource,
+  getPensionAllowanceForUser,
+  getPensionAllowanceHistory,
+  getPensionAllowanceForAllUsers,
+} from "../../src/server/services/pension-allowance-service.js";
+
+const testDbPath = getDatabasePath();
+
+/**
+ * @description Clean up the isolated test database files only.
+ */
+function cleanupDatabase() {
+  closeDatabase();
+  for (const suffix of ["", "-wal", "-shm"]) {
+    const filePath = testDbPath + suffix;
+    if (existsSync(filePath)) {
+      unlinkSync(filePath);
+    }
+  }
+}
+
+/** @type {Object} User saving into two SIPPs, no drawdown */
+let saver;
+/** @type {Object} User who started drawdown by taking a payment */
+let drawer;
+/** @type {Object} User with a drawdown schedule */
+let scheduled;
+/** @type {Object} User holding only an ISA */
+let isaOnly;
+/** @type {Object} Saver's first SIPP */
+let sippA;
+/** @type {Object} Saver's second SIPP */
+let sippB;
+
+beforeAll(() => {
+  cleanupDatabase();
+  createDatabase();
+
+  saver = createUser({ initials: "SV", first_name: "Sam", last_name: "Saver", provider: "ii" });
+  drawer = createUser({ initials: "DR", first_name: "Dee", last_name: "Drawer", provider: "ii" });
+  scheduled = createUser({ initials: "SC", first_name: "Sol", last_name: "Schedule", provider: "ii" });
+  isaOnly = createUser({ initials: "IO", first_name: "Ida", last_name: "Isa", provider: "hl" });
+
+  sippA = createAccount({ user_id: saver.id, account_type: "sipp", account_ref: "SV-SIPP", cash_balance: 0, warn_cash: 0 });
+  sippB = createAccount({ user_id: saver.id, account_type: "sipp", account_ref: "SV-SIPP-HL", provider: "hl", cash_balance: 0, warn_cash: 0 });
+  const drawerSipp = createAccount({ user_id: drawer.id, account_type: "sipp", account_ref: "DR-SIPP", cash_balance: 0, warn_cash: 0 });
+  const scheduledSipp = createAccount({ user_id: scheduled.id, account_type: "sipp", account_ref: "SC-SIPP", cash_balance: 0, warn_cash: 0 });
+  createAccount({ user_id: isaOnly.id, account_type: "isa", account_ref: "IO-ISA", provider: "hl", cash_balance: 0, warn_cash: 0 });
+
+  // 2022/2023: personal 10000 gross, relief received later — 30000 unused
+  createPensionContribution({ account_id: sippA.id, contribution_type: "personal", transaction_date: "2022-06-01", amount: 8000, gross_amount: 10000, relief_date: "2022-07-15" });
+  // 2023/2024: employer 20000 into the second SIPP — 40000 unused
+  createPensionContribution({ account_id: sippB.id, contribution_type: "employer", transaction_date: "2023-09-01", amount: 20000, gross_amount: 20000 });
+  // 2024/2025: nothing — 60000 unused
+  // 2025/2026: personal 100000 gross, relief not yet received — 40000 above the allowance
+  createPensionContribution({ account_id: sippA.id, contribution_type: "personal", transaction_date: "2025-05-01", amount: 80000, gross_amount: 100000 });
+  // A plain deposit is a transfer of existing savings, not a contribution
+  createCashTransaction({ account_id: sippA.id, transaction_type: "deposit", transaction_date: "2025-06-01", amount: 5000 });
+
+  // Drawer: contributes, starts drawdown mid-year, then contributes again
+  createPensionContribution({ account_id: drawerSipp.id, contribution_type: "personal", transaction_date: "2024-05-01", amount: 24000, gross_amount: 30000 });
+  createPensionContribution({ account_id: drawerSipp.id, contribution_type: "personal", transaction_date: "2025-05-01", amount: 16000, gross_amount: 20000 });
+  createCashTransaction({ account_id: drawerSipp.id, transaction_type: "drawdown", transaction_date: "2025-09-01", amount: 1000 });
+  createPensionContribution({ account_id: drawerSipp.id, contribution_type: "employer", transaction_date: "2025-10-01", amount: 15000, gross_amount: 15000 });
+  createPensionContribution({ account_id: drawerSipp.id, contribution_type: "employer", transaction_date: "2026-05-01", amount: 4000, gross_amount: 4000 });
+
+  // Scheduled: first scheduled payment on 15 Jan 2025 (tax year 2024/2025)
+  createDrawdownSchedule({ account_id: scheduledSipp.id, frequency: "monthly", trigger_day: 15, from_date: "2025-01-01", to_date: "2030-01-01", amount: 500 });
+});
+
+afterAll(() => {
+  cleanupDatabase();
+  delete process.env.DB_PATH;
+});
+
+describe("Pension Allowance - limits", function () {
+  test("uses published annual allowances for earlier years and the configured allowance otherwise", function () {
+    expect(getAnnualAllowance(2005)).toBeNull();
+    expect(getAnnualAllowance(2006)).toBe(215000);
+    expect(getAnnualAllowance(2010)).toBe(255000);
+    expect(getAnnualAllowance(2013)).toBe(50000);
+    expect(getAnnualAllowance(2014)).toBe(40000);
+    expect(getAnnualAllowance(2022)).toBe(40000);
+    expect(getAnnualAllowance(2023)).toBe(60000);
+  });
+
+  test("has no MPAA before 2015/2016 and uses published values until the configured one", function () {
+    expect(getMoneyPurchaseAnnualAllowance(2014)).toBeNull();
+    expect(getMoneyPurchaseAnnualAllowance(2016)).toBe(10000);
+    expect(getMoneyPurchaseAnnualAllowance(2017)).toBe(4000);
+    expect(getMoneyPurchaseAnnualAllowance(2026)).toBe(10000);
+  });
+
+  test("splits a gross personal contribution into net payment and basic rate relief", function () {
+    expect(splitReliefAtSource(10000)).toEqual({ net: 8000, relief: 2000 });
+    expect(splitReliefAtSource(100.01)).toEqual({ net: 80.01, relief: 20 });
+  });
+});
+
+describe("Pension Allowance - contributions", function () {
+  test("credits the net payment and the relief-at-source top-up to cash", function () {
+    const result = createPensionContribution({ account_id: sippB.id, contribution_type: "personal", transaction_date: "2026-04-10", amount: 800, gross_amount: 1000, relief_date: "2026-05-20" });
+    expect(result.contribution.transaction_type).toBe("deposit");
+    expect(result.contribution.contribution_type).toBe("personal");
+    expect(result.contribution.gross_amount).toBe(1000);
+    expect(result.tax_relief.transaction_type).toBe("tax_relief");
+    expect(result.tax_relief.amount).toBe(200);
+    // 20000 employer + 800 net + 200 relief
+    expect(getAccountById(sippB.id).cash_balance).toBe(21000);
+  });
+
+  test("does not record relief until it has been received", function () {
+    const result = createPensionContribution({ account_id: sippB.id, contribution_type: "personal", transaction_date: "2026-06-10", amount: 400, gross_amount: 500 });
+    expect(result.tax_relief).toBeNull();
+  });
+});
+
+describe("Pension Allowance - carry-forward", function () {
+  test("returns null for a non-existent user", function () {
+    expect(getPensionAllowanceForUser(99999, 2026)).toBeNull();
+  });
+
+  test("sums personal and employer contributions across every SIPP the person holds", function () {
+    const usage = getPensionAllowanceForUser(saver.id, 2023);
+    expect(usage.tax_year).toBe("2023/2024");
+    expect(usage.employer).toBe(20000);
+    expect(usage.total).toBe(20000);
+    expect(usage.accounts.find((a) => a.account_id === sippB.id).employer).toBe(20000);
+    expect(usage.accounts.find((a) => a.account_id === sippA.id).total).toBe(0);
+  });
+
+  test("tracks relief expected against relief received", function () {
+    const usage = getPensionAllowanceForUser(saver.id, 2022);
+    expect(usage.personal_gross).toBe(10000);
+    expect(usage.personal_net).toBe(8000);
+    expect(usage.tax_relief_expected).toBe(2000);
+    expect(usage.tax_relief_received).toBe(2000);
+  });
+
+  test("covers contributions above the allowance from the earliest unused year first", function () {
+    const usage = getPensionAllowanceForUser(saver.id, 2025);
+    // Plain deposit not counted
+    expect(usage.total).toBe(100000);
+    expect(usage.annual_allowance).toBe(60000);
+    expect(usage.carry_forward.map((cf) => cf.tax_year)).toEqual(["2022/2023", "2023/2024", "2024/2025"]);
+    expect(usage.carry_forward.map((cf) => cf.unused)).toEqual([30000, 40000, 60000]);
+    expect(usage.carry_forward.map((cf) => cf.used)).toEqual([30000, 10000, 0]);
+    expect(usage.carry_forward_used).toBe(40000);
+    expect(usage.excess).toBe(0);
+    expect(usage.over_limit).toBe(false);
+    expect(usage.tax_relief_expected).toBe(20000);
+    expect(usage.tax_relief_received).toBe(0);
+  });
+
+  test("carries forward only what is left after earlier use", function () {
+    const usage = getPensionAllowanceForUser(saver.id, 2026);
+    expect(usage.carry_forward.map((cf) => cf.unused)).toEqual([30000, 60000, 0]);
+    expect(usage.carry_forward_available).toBe(90000);
+    expect(usage.total).toBe(1500);
+    expect(usage.remaining).toBe(148500);
+  });
+
+  test("history runs from the first contribution to the current tax year, newest first", function () {
+    const currentStart = Number(getTaxYearForDate(new Date().toISOString().slice(0, 10)).start.slice(0, 4));
+    const history = getPensionAllowanceHistory(saver.id);
+    expect(history.user.initials).toBe("SV");
+    expect(history.tax_years.length).toBe(currentStart - 2022 + 1);
+    expect(history.tax_years[history.tax_years.length - 1].tax_year).toBe("2022/2023");
+    expect(history.tax_years.find((ty) => ty.tax_year === "2024/2025").total).toBe(0);
+  });
+});
+
+describe("Pension Allowance - money purchase annual allowance", function () {
+  test("does not apply before drawdown starts", function () {
+    const usage = getPensionAllowanceForUser(drawer.id, 2024);
+    expect(usage.mpaa_applies).toBe(false);
+    expect(usage.remaining).toBe(30000);
+  });
+
+  test("tests contributions after the first drawdown payment against the MPAA in that year", function () {
+    const usage = getPensionAllowanceForUser(drawer.id, 2025);
+    expect(usage.mpaa_applies).toBe(true);
+    expect(usage.mpaa_triggered_on).toBe("2025-09-01");
+    expect(usage.total).toBe(35000);
+    // 15000 after drawdown started against a 10000 MPAA
+    expect(usage.excess).toBe(5000);
+    expect(usage.over_limit).toBe(true);
+    expect(usage.remaining).toBe(0);
+  });
+
+  test("limits later years to the MPAA with no carry-forward", function () {
+    const usage = getPensionAllowanceForUser(drawer.id, 2026);
+    expect(usage.annual_allowance).toBe(10000);
+    expect(usage.carry_forward_available).toBe(0);
+    expect(usage.total).toBe(4000);
+    expect(usage.remaining).toBe(6000);
+  });
+
+  test("is triggered by a scheduled drawdown only once the payment is recorded", function () {
+    expect(getPensionAllowanceForUser(scheduled.id, 2026).mpaa_applies).toBe(false);
+
+    processDrawdowns("2025-01-20");
+    const usage = getPensionAllowanceForUser(scheduled.id, 2026);
+    expect(usage.mpaa_applies).toBe(true);
+    expect(usage.mpaa_triggered_on).toBe("2025-01-15");
+    expect(usage.annual_allowance).toBe(10000);
+  });
+});
+
+describe("Pension Allowance - household", function () {
+  test("lists only people holding a SIPP", function () {
+    const usages = getPensionAllowanceForAllUsers(2026);
+    const ids = usages.map((u) => u.user.id);
+    expect(ids).toContain(saver.id);
+    expect(ids).toContain(drawer.id);
+    expect(ids).toContain(scheduled.id);
+    expect(ids).not.toContain(isaOnly.id);
+  });
+});
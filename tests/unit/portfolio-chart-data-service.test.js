@@ -1,5 +1,5 @@
 import { describe, test, expect } from "bun:test";
-import { parsePortfolioParam, buildSeriesLabel } from "../../src/server/services/portfolio-chart-data-service.js";
+import { parsePortfolioParam, parseAccountToken, accountMatchesSelectors, buildSeriesLabel } from "../../src/server/services/portfolio-chart-data-service.js";
 import { generateFortnightlyDates, formatISODate } from "../../src/server/services/price-utils.js";
 
 // --- parsePortfolioParam ---
@@ -59,6 +59,43 @@ describe("parsePortfolioParam", function () {
   test("returns null for empty account part", function () {
     expect(parsePortfolioParam("BW:")).toBeNull();
   });
+
+  test("parses provider and account reference qualifiers", function () {
+    var result = parsePortfolioParam("BW:isa@HL+sipp~3366521+isa");
+    expect(result.accountTypes).toEqual(["isa", "sipp"]);
+    expect(result.accountSelectors).toEqual([
+      { type: "isa", provider: "hl", ref: null },
+      { type: "sipp", provider: null, ref: "3366521" },
+      { type: "isa", provider: null, ref: null },
+    ]);
+  });
+});
+
+// --- parseAccountToken / accountMatchesSelectors ---
+
+describe("accountMatchesSelectors", function () {
+  var iiIsa = { account_type: "isa", provider: "ii", account_ref: "I1001" };
+  var hlIsa = { account_type: "isa", provider: "hl", account_ref: "3366521" };
+  var sipp = { account_type: "sipp", provider: "ii", account_ref: "S1001" };
+
+  test("a plain type matches every account of that type", function () {
+    var selectors = [parseAccountToken("isa")];
+    expect(accountMatchesSelectors(iiIsa, selectors)).toBe(true);
+    expect(accountMatchesSelectors(hlIsa, selectors)).toBe(true);
+    expect(accountMatchesSelectors(sipp, selectors)).toBe(false);
+  });
+
+  test("a provider or reference narrows to one account", function () {
+    expect(accountMatchesSelectors(hlIsa, [parseAccountToken("isa@hl")])).toBe(true);
+    expect(accountMatchesSelectors(iiIsa, [parseAccountToken("isa@hl")])).toBe(false);
+    expect(accountMatchesSelectors(iiIsa, [parseAccountToken("isa~i1001")])).toBe(true);
+    expect(accountMatchesSelectors(hlIsa, [parseAccountToken("isa~i1001")])).toBe(false);
+  });
+
+  test("returns null for an empty token", function () {
+    expect(parseAccountToken(" ")).toBeNull();
+    expect(parseAccountToken("@hl")).toBeNull();
+  });
 });
 
 // --- buildSeriesLabel ---
@@ -83,6 +120,12 @@ describe("buildSeriesLabel", function () {
   test("multiple users single account type", function () {
     expect(buildSeriesLabel(["Ben Wilson", "Alexis Wilson"], ["sipp"])).toBe("Combined (SIPP)");
   });
+
+  test("names the provider or reference when an account is narrowed", function () {
+    var parsed = parsePortfolioParam("BW:isa@hl+sipp~3366521+trading");
+    expect(buildSeriesLabel(["Ben Wilson"], parsed.accountTypes, parsed.accountSelectors))
+      .toBe("Ben Wilson (ISA HL + SIPP 3366521 + Trading)");
+  });
 });
 
 // --- generateFortnightlyDates ---
@@ -0,0 +1,265 @@
+// Set isolated DB path BEFORE importing connection.js (which reads it at module load)
+process.env.DB_PATH = "data/portfolio_60_test/test-portfolio-valuations-db.db";
+
+import { describe, test, expect, beforeAll, afterAll } from "bun:test";
+import { existsSync, unlinkSync } from "node:fs";
+import { createDatabase, closeDatabase, getDatabasePath, getDatabase } from "../../src/server/db/connection.js";
+import { createUser } from "../../src/server/db/users-db.js";
+import { createAccount, updateAccount } from "../../src/server/db/accounts-db.js";
+import { createInvestment, updateInvestment, getInvestmentById } from "../../src/server/db/investments-db.js";
+import { getAllInvestmentTypes } from "../../src/server/db/investment-types-db.js";
+import { getAllCurrencies, createCurrency } from "../../src/server/db/currencies-db.js";
+import { createHolding } from "../../src/server/db/holdings-db.js";
+import { createBuyMovement } from "../../src/server/db/holding-movements-db.js";
+import { createCashTransaction, getCashTransactionsByAccountId } from "../../src/server/db/cash-transactions-db.js";
+import { upsertPrice } from "../../src/server/db/prices-db.js";
+import {
+  getValuationSnapshot,
+  saveValuationSnapshot,
+  invalidateAccountValuations,
+  invalidateInvestmentValuations,
+  getValuationSnapshotCount,
+} from "../../src/server/db/portfolio-valuations-db.js";
+import { getPortfolioSummaryAtDate, refreshValuationSnapshots } from "../../src/server/services/portfolio-service.js";
+import { upsertIntoDatabase } from "../../src/server/services/fetch-server-sync.js";
+
+const testDbPath = getDatabasePath();
+
+/**
+ * @description Clean up the isolated test database files only.
+ */
+function cleanupDatabase() {
+  closeDatabase();
+  for (const suffix of ["", "-wal", "-shm"]) {
+    const filePath = testDbPath + suffix;
+    if (existsSync(filePath)) {
+      unlinkSync(filePath);
+    }
+  }
+}
+
+/** @type {Object} Test user */
+let user;
+/** @type {Object} Test account */
+let account;
+/** @type {Object} Test investment */
+let fund;
+/** @type {Object} Test holding */
+let holding;
+
+beforeAll(() => {
+  cleanupDatabase();
+  createDatabase();
+
+  user = createUser({
+    initials: "PV",
+    first_name: "Valuation",
+    last_name: "Tester",
+    provider: "ii",
+  });
+
+  account = createAccount({
+    user_id: user.id,
+    account_type: "isa",
+    account_ref: "V1001",
+    cash_balance: 0,
+    warn_cash: 0,
+  });
+
+  const types = getAllInvestmentTypes();
+  const gbp = getAllCurrencies().find((c) => c.code === "GBP");
+  fund = createInvestment({
+    currencies_id: gbp.id,
+    investment_type_id: types[0].id,
+    description: "Snapshot Fund",
+  });
+  holding = createHolding({ account_id: account.id, investment_id: fund.id, quantity: 0, average_cost: 0 });
+
+  upsertPrice(fund.id, "2025-01-02", "16:00:00", 200);
+  createCashTransaction({ account_id: account.id, transaction_type: "deposit", transaction_date: "2025-01-01", amount: 5000 });
+  createBuyMovement({ holding_id: holding.id, movement_date: "2025-01-02", quantity: 1000, total_consideration: 2000 });
+});
+
+afterAll(() => {
+  cleanupDatabase();
+  delete process.env.DB_PATH;
+});
+
+describe("Portfolio Valuations DB - save and get", function () {
+  test("returns null when no snapshot exists", function () {
+    expect(getValuationSnapshot(account.id, "2025-02-01")).toBeNull();
+  });
+
+  test("round-trips a snapshot with unscaled values", function () {
+    saveValuationSnapshot(account.id, "2025-02-01", {
+      cash_balance: null,
+      holdings: [
+        {
+          holding_id: holding.id,
+          investment_id: fund.id,
+          quantity: 12.5,
+          average_cost: 1.8,
+          price: 2.1234,
+          price_date: "2025-01-31",
+          rate: null,
+          rate_date: null,
+          value_local: 26.54,
+          value_gbp: 26.54,
+        },
+      ],
+    });
+
+    const snapshot = getValuationSnapshot(account.id, "2025-02-01");
+    expect(snapshot.cash_balance).toBeNull();
+    expect(snapshot.holdings.length).toBe(1);
+    expect(snapshot.holdings[0].description).toBe("Snapshot Fund");
+    expect(snapshot.holdings[0].currency_code).toBe("GBP");
+    expect(snapshot.holdings[0].quantity).toBe(12.5);
+    expect(snapshot.holdings[0].price).toBe(2.1234);
+    expect(snapshot.holdings[0].value_gbp).toBe(26.54);
+  });
+
+  test("replaces an existing snapshot for the same date", function () {
+    saveValuationSnapshot(account.id, "2025-02-01", { cash_balance: 10, holdings: [] });
+    const snapshot = getValuationSnapshot(account.id, "2025-02-01");
+    expect(snapshot.cash_balance).toBe(10);
+    expect(snapshot.holdings.length).toBe(0);
+  });
+
+  test("can be saved inside an open transaction", function () {
+    const db = getDatabase();
+    db.exec("BEGIN");
+    saveValuationSnapshot(account.id, "2025-02-02", { cash_balance: 20, holdings: [] });
+    expect(getValuationSnapshot(account.id, "2025-02-02").cash_balance).toBe(20);
+    db.exec("ROLLBACK");
+    expect(getValuationSnapshot(account.id, "2025-02-02")).toBeNull();
+  });
+});
+
+describe("Portfolio Valuations DB - invalidation", function () {
+  test("invalidateAccountValuations removes snapshots from the date onwards only", function () {
+    saveValuationSnapshot(account.id, "2025-03-01", { cash_balance: 1, holdings: [] });
+    saveValuationSnapshot(account.id, "2025-04-01", { cash_balance: 2, holdings: [] });
+
+    invalidateAccountValuations(account.id, "2025-03-15");
+    expect(getValuationSnapshot(account.id, "2025-03-01")).not.toBeNull();
+    expect(getValuationSnapshot(account.id, "2025-04-01")).toBeNull();
+  });
+
+  test("invalidateInvestmentValuations removes snapshots for accounts holding the investment", function () {
+    saveValuationSnapshot(account.id, "2025-04-01", { cash_balance: 2, holdings: [] });
+    invalidateInvestmentValuations(fund.id, "2025-02-15");
+    expect(getValuationSnapshot(account.id, "2025-02-01")).not.toBeNull();
+    expect(getValuationSnapshot(account.id, "2025-03-01")).toBeNull();
+    expect(getValuationSnapshot(account.id, "2025-04-01")).toBeNull();
+  });
+
+  test("a new price invalidates snapshots on and after its date", function () {
+    saveValuationSnapshot(account.id, "2025-05-01", { cash_balance: 3, holdings: [] });
+    upsertPrice(fund.id, "2025-05-01", "16:00:00", 210);
+    expect(getValuationSnapshot(account.id, "2025-05-01")).toBeNull();
+    expect(getValuationSnapshot(account.id, "2025-02-01")).not.toBeNull();
+  });
+
+  test("a backdated cash transaction invalidates later snapshots", function () {
+    saveValuationSnapshot(account.id, "2025-06-01", { cash_balance: 4, holdings: [] });
+    createCashTransaction({ account_id: account.id, transaction_type: "withdrawal", transaction_date: "2025-01-20", amount: 100 });
+    expect(getValuationSnapshot(account.id, "2025-02-01")).toBeNull();
+    expect(getValuationSnapshot(account.id, "2025-06-01")).toBeNull();
+  });
+
+  test("prices synced from the fetch server invalidate snapshots from the earliest synced date", function () {
+    getDatabase().run("UPDATE investments SET morningstar_id = ? WHERE id = ?", ["F0SYNCTEST", fund.id]);
+    saveValuationSnapshot(account.id, "2025-07-01", { cash_balance: 5, holdings: [] });
+    saveValuationSnapshot(account.id, "2025-08-01", { cash_balance: 6, holdings: [] });
+    saveValuationSnapshot(account.id, "2025-09-01", { cash_balance: 7, holdings: [] });
+
+    const counts = upsertIntoDatabase({
+      prices: [
+        { morningstarId: "F0SYNCTEST", priceDate: "2025-09-01", price: 2200000 },
+        { morningstarId: "F0SYNCTEST", priceDate: "2025-08-01", price: 2150000 },
+      ],
+    });
+    expect(counts.prices).toBe(2);
+    expect(getValuationSnapshot(account.id, "2025-07-01")).not.toBeNull();
+    expect(getValuationSnapshot(account.id, "2025-08-01")).toBeNull();
+    expect(getValuationSnapshot(account.id, "2025-09-01")).toBeNull();
+  });
+});
+
+describe("Portfolio Valuations - summary service", function () {
+  test("getPortfolioSummaryAtDate materialises and then reuses a snapshot", function () {
+    const first = getPortfolioSummaryAtDate(user.id, "2025-03-01");
+    // 1000 units at 200p plus cash of 5000 - 2000 - 100
+    expect(first.accounts[0].investments_total).toBe(2000);
+    expect(first.accounts[0].cash_balance).toBe(2900);
+
+    const stored = getValuationSnapshot(account.id, "2025-03-01");
+    expect(stored.holdings[0].value_gbp).toBe(2000);
+
+    const second = getPortfolioSummaryAtDate(user.id, "2025-03-01");
+    expect(second.totals.grand_total).toBe(4900);
+  });
+
+  test("a backdated buy invalidates the snapshot and the next read reflects it", function () {
+    createBuyMovement({ holding_id: holding.id, movement_date: "2025-02-10", quantity: 100, total_consideration: 200 });
+    expect(getValuationSnapshot(account.id, "2025-03-01")).toBeNull();
+
+    const summary = getPortfolioSummaryAtDate(user.id, "2025-03-01");
+    expect(summary.accounts[0].investments_total).toBe(2200);
+    expect(summary.accounts[0].cash_balance).toBe(2700);
+  });
+
+  test("does not store snapshots for future dates", function () {
+    const before = getValuationSnapshotCount(account.id);
+    getPortfolioSummaryAtDate(user.id, "2999-01-01");
+    expect(getValuationSnapshotCount(account.id)).toBe(before);
+  });
+
+  test("refreshValuationSnapshots writes a snapshot for every account", function () {
+    expect(refreshValuationSnapshots("2025-07-01")).toBe(1);
+    const snapshot = getValuationSnapshot(account.id, "2025-07-01");
+    expect(snapshot.holdings[0].quantity).toBe(1100);
+    expect(snapshot.holdings[0].price).toBe(2.1);
+  });
+});
+
+describe("Portfolio Valuations - account and investment edits", function () {
+  /** @type {Object} Second user, so the summaries only cover their account */
+  let editUser;
+  /** @type {Object} Account funded by a deposit */
+  let editAccount;
+  /** @type {Object} Investment held in the account */
+  let editFund;
+
+  beforeAll(() => {
+    editUser = createUser({ initials: "PE", first_name: "Valuation", last_name: "Editor", provider: "ii" });
+    editAccount = createAccount({ user_id: editUser.id, account_type: "trading", account_ref: "V2001", cash_balance: 0, warn_cash: 0 });
+    const gbp = getAllCurrencies().find((c) => c.code === "GBP");
+    editFund = createInvestment({ currencies_id: gbp.id, investment_type_id: getAllInvestmentTypes()[0].id, description: "Edited Fund" });
+    createHolding({ account_id: editAccount.id, investment_id: editFund.id, quantity: 10, average_cost: 1 });
+    upsertPrice(editFund.id, "2025-02-03", "16:00:00", 100);
+    createCashTransaction({ account_id: editAccount.id, transaction_type: "deposit", transaction_date: "2025-01-01", amount: 1000 });
+  });
+
+  test("changing an account's cash balance recalculates its running balance and invalidates its snapshots", function () {
+    expect(getPortfolioSummaryAtDate(editUser.id, "2025-03-03").accounts[0].cash_balance).toBe(1000);
+    expect(getValuationSnapshot(editAccount.id, "2025-03-03")).not.toBeNull();
+
+    updateAccount(editAccount.id, { account_ref: "V2001", cash_balance: 1500, warn_cash: 0 });
+    expect(getValuationSnapshot(editAccount.id, "2025-03-03")).toBeNull();
+    expect(getCashTransactionsByAccountId(editAccount.id)[0].balance_after).toBe(1500);
+    expect(getPortfolioSummaryAtDate(editUser.id, "2025-03-03").accounts[0].cash_balance).toBe(1500);
+  });
+
+  test("changing an investment's currency invalidates snapshots from its first price", function () {
+    saveValuationSnapshot(editAccount.id, "2025-01-15", { cash_balance: 1500, holdings: [] });
+    getPortfolioSummaryAtDate(editUser.id, "2025-03-03");
+    expect(getValuationSnapshot(editAccount.id, "2025-03-03")).not.toBeNull();
+
+    const usd = createCurrency({ code: "USD", description: "US Dollar" });
+    updateInvestment(editFund.id, { ...getInvestmentById(editFund.id), currencies_id: usd.id });
+    expect(getValuationSnapshot(editAccount.id, "2025-01-15")).not.toBeNull();
+    expect(getValuationSnapshot(editAccount.id, "2025-03-03")).toBeNull();
+  });
+});
@@ -0,0 +1,142 @@
+// Set isolated DB path BEFORE importing connection.js (which reads it at module load)
+process.env.DB_PATH = "data/portfolio_60_test/test-property-indexation.db";
+
+import { describe, test, expect, beforeAll, afterAll } from "bun:test";
+import { existsSync, unlinkSync } from "node:fs";
+import { createDatabase, closeDatabase, getDatabasePath, getDatabase } from "../../src/server/db/connection.js";
+import { createUser } from "../../src/server/db/users-db.js";
+import { createOtherAsset, updateOtherAsset, getOtherAssetById, getOtherAssetHistory, applyOtherAssetIndexation } from "../../src/server/db/other-assets-db.js";
+import { createIndexSeries, upsertIndexValues, countIndexSeriesUsage } from "../../src/server/db/index-series-db.js";
+import { parseIndexCsv, importIndexCsv } from "../../src/server/services/index-series-service.js";
+import { validateOtherAsset } from "../../src/server/validation.js";
+
+const testDbPath = getDatabasePath();
+
+/**
+ * @description Clean up the isolated test database files only.
+ */
+function cleanupDatabase() {
+  closeDatabase();
+  for (const suffix of ["", "-wal", "-shm"]) {
+    const filePath = testDbPath + suffix;
+    if (existsSync(filePath)) {
+      unlinkSync(filePath);
+    }
+  }
+}
+
+/** @description Rows from the HM Land Registry UK HPI full file, two regions */
+const HPI_CSV = [
+  "Date,RegionName,AreaCode,AveragePrice,Index,IndexSA",
+  "01/01/2024,South West,E12000009,297000,140.0,139.8",
+  "01/01/2024,London,E12000007,521000,150.0,149.5",
+  "01/06/2024,South West,E12000009,301000,142.8,142.0",
+  "01/06/2024,London,E12000007,530000,152.1,151.0",
+  "01/07/2026,South West,E12000009,310000,147.0,146.1",
+  "",
+].join("\n");
+
+/** @type {Object} Test user */
+let testUser;
+/** @type {Object} South West HPI series */
+let southWest;
+/** @type {number} Indexed house */
+let houseId;
+
+beforeAll(() => {
+  cleanupDatabase();
+  createDatabase();
+
+  testUser = createUser({ initials: "HP", first_name: "Hattie", last_name: "Price", provider: "ii" });
+  southWest = createIndexSeries({ name: "SW HPI", description: "UK HPI, South West", region: "South West" });
+});
+
+afterAll(() => {
+  cleanupDatabase();
+  delete process.env.DB_PATH;
+});
+
+describe("Property indexation - UK HPI CSV", () => {
+  test("reads the Index column for the chosen region only", () => {
+    const parsed = parseIndexCsv(HPI_CSV, "south west");
+    expect(parsed.values).toEqual([
+      { value_date: "2024-01-01", value: 140 },
+      { value_date: "2024-06-01", value: 142.8 },
+      { value_date: "2026-07-01", value: 147 },
+    ]);
+    expect(parsed.skipped).toBe(0);
+  });
+
+  test("needs a region when the file covers several, and one that is in the file", () => {
+    expect(() => parseIndexCsv(HPI_CSV)).toThrow("The file covers 2 regions");
+    expect(() => parseIndexCsv(HPI_CSV, "Wales")).toThrow("Region 'Wales' not found in the file");
+  });
+
+  test("reads a single-region download without a region", () => {
+    const csv = '"Name","URI","Period","Average price All property types","House price index All property types"\n"Bristol","x","2024-01","310000","138.4"\n';
+    expect(parseIndexCsv(csv).values).toEqual([{ value_date: "2024-01-01", value: 138.4 }]);
+  });
+});
+
+describe("Property indexation - estimated values", () => {
+  test("estimates the value from the survey and marks the surveyed value in the history", () => {
+    upsertIndexValues(southWest.id, [
+      { value_date: "2024-01-01", value: 140 },
+      { value_date: "2024-06-01", value: 142.8 },
+    ]);
+    const house = createOtherAsset({
+      user_id: testUser.id,
+      description: "The Old Rectory",
+      category: "property",
+      value_type: "value",
+      value: 5000000000,
+      valuation_index_id: southWest.id,
+      surveyed_date: "2024-01-20",
+    });
+    houseId = house.id;
+    expect(countIndexSeriesUsage(southWest.id)).toBe(1);
+
+    // £500,000 surveyed in January 2024, index up 2% by June 2024
+    expect(house.value).toBe(5100000000);
+    expect(house.value_source).toBe("indexed");
+    expect(house.surveyed_value).toBe(5000000000);
+    const history = getOtherAssetHistory(house.id);
+    expect(history.map((h) => [h.revised_value, h.revised_value_source, h.reason])).toEqual([[5000000000, "surveyed", "Indexed +2.00% since survey (SW HPI Jun 2024)"]]);
+  });
+
+  test("re-estimates when the index is imported, keeping the earlier estimate marked indexed", () => {
+    getDatabase().run("UPDATE other_assets SET last_updated = ? WHERE id = ?", ["2025-02-14", houseId]);
+    const result = importIndexCsv(southWest.id, HPI_CSV);
+    expect(result.imported).toBe(3);
+    expect(result.indexed).toBe(1);
+
+    expect(getOtherAssetById(houseId).value).toBe(5250000000);
+    expect(getOtherAssetById(houseId).last_updated).toBe("2025-02-14");
+    expect(getOtherAssetHistory(houseId)[0].revised_value_source).toBe("indexed");
+    expect(applyOtherAssetIndexation()).toEqual({ indexed: 0, pending: 0 });
+  });
+
+  test("keeps the estimate on an edit that leaves the survey alone, and starts again from a new survey", () => {
+    const house = getOtherAssetById(houseId);
+    const data = { ...house, value: house.surveyed_value, notes: "Extension built" };
+    const edited = updateOtherAsset(house.id, data);
+    expect(edited.value).toBe(5250000000);
+    expect(edited.value_source).toBe("indexed");
+
+    const resurveyed = updateOtherAsset(house.id, { ...data, value: 5600000000, surveyed_date: "2026-07-10" });
+    expect(resurveyed.value).toBe(5600000000);
+    expect(resurveyed.value_source).toBe("surveyed");
+    expect(resurveyed.surveyed_date).toBe("2026-07-10");
+  });
+
+  test("waits for an index value covering the survey month", () => {
+    createOtherAsset({ user_id: testUser.id, description: "Old Cottage", category: "property", value_type: "value", value: 1000000000, valuation_index_id: southWest.id, surveyed_date: "2020-05-01" });
+    expect(applyOtherAssetIndexation()).toEqual({ indexed: 0, pending: 1 });
+  });
+
+  test("only links property to a house price index", () => {
+    const asset = { user_id: 1, description: "ISA", category: "savings", value_type: "value", value: 1, valuation_index_id: 1 };
+    expect(validateOtherAsset(asset)).toEqual(["A house price index can only be set for property"]);
+    expect(validateOtherAsset({ ...asset, category: "property", surveyed_date: "2024-13-01" })).toEqual(["Surveyed date must be a valid date in YYYY-MM-DD format"]);
+  });
+});
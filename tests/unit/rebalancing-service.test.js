@@ -0,0 +1,193 @@
+// Set isolated DB path BEFORE importing connection.js
+process.env.DB_PATH = "data/portfolio_60_test/test-rebalancing-service.db";
+
+import { describe, test, expect, beforeAll, afterAll } from "bun:test";
+import { existsSync, unlinkSync } from "node:fs";
+import { createDatabase, closeDatabase, getDatabasePath } from "../../src/server/db/connection.js";
+import { createUser } from "../../src/server/db/users-db.js";
+import { getAllCurrencies } from "../../src/server/db/currencies-db.js";
+import { createInvestment, updateInvestment, getInvestmentById } from "../../src/server/db/investments-db.js";
+import { getAllInvestmentTypes } from "../../src/server/db/investment-types-db.js";
+import { createAccount } from "../../src/server/db/accounts-db.js";
+import { createHolding } from "../../src/server/db/holdings-db.js";
+import { upsertPrice } from "../../src/server/db/prices-db.js";
+import { getAllocationTargets, setAllocationTargets } from "../../src/server/db/allocation-targets-db.js";
+import { buildRebalancePlan } from "../../src/server/services/rebalancing-service.js";
+
+const testDbPath = getDatabasePath();
+
+/**
+ * @description Clean up the isolated test database files.
+ */
+function cleanupDatabase() {
+  closeDatabase();
+  for (const suffix of ["", "-wal", "-shm"]) {
+    const filePath = testDbPath + suffix;
+    if (existsSync(filePath)) {
+      unlinkSync(filePath);
+    }
+  }
+}
+
+/**
+ * @description Set an investment's allocation tag, keeping its other fields.
+ * @param {number} id - The investment ID
+ * @param {string} tag - The allocation tag
+ */
+function tagInvestment(id, tag) {
+  const inv = getInvestmentById(id);
+  updateInvestment(id, { currencies_id: inv.currencies_id, investment_type_id: inv.investment_type_id, description: inv.description, allocation_tag: tag });
+}
+
+const OPTIONS = { tolerance_percent: 5, minimum_trade: 250 };
+
+let userId;
+let isaAccountId;
+let tradingAccountId;
+let shareId;
+let trustId;
+
+beforeAll(() => {
+  cleanupDatabase();
+  createDatabase();
+
+  const gbpId = getAllCurrencies().find((c) => c.code === "GBP").id;
+  const typeId = getAllInvestmentTypes().find((t) => t.short_description === "SHARE").id;
+  shareId = createInvestment({ currencies_id: gbpId, investment_type_id: typeId, description: "UK Share" }).id;
+  trustId = createInvestment({ currencies_id: gbpId, investment_type_id: typeId, description: "Global Trust" }).id;
+  upsertPrice(shareId, "2026-01-02", "16:30:00", 1000);
+  upsertPrice(trustId, "2026-01-02", "16:30:00", 1000);
+
+  userId = createUser({ initials: "RC", first_name: "Robert", last_name: "Collins", ni_number: "", utr: "", provider: "ii", trading_ref: "", isa_ref: "", sipp_ref: "" }).id;
+
+  // ISA: £1,000 cash (keep £500) and £6,000 of the share; trading: £3,000 of the trust bought at £5
+  isaAccountId = createAccount({ user_id: userId, account_type: "isa", account_ref: "I1", cash_balance: 1000, warn_cash: 500 }).id;
+  tradingAccountId = createAccount({ user_id: userId, account_type: "trading", account_ref: "T1", cash_balance: 0, warn_cash: 0 }).id;
+  createHolding({ account_id: isaAccountId, investment_id: shareId, quantity: 600, average_cost: 10 });
+  createHolding({ account_id: tradingAccountId, investment_id: trustId, quantity: 300, average_cost: 5 });
+});
+
+afterAll(() => {
+  cleanupDatabase();
+  delete process.env.DB_PATH;
+});
+
+describe("Allocation targets - setAllocationTargets", function () {
+  test("keeps a person's targets separate from an account's", function () {
+    setAllocationTargets(userId, null, [{ investment_id: shareId, target_percent: 40 }]);
+    setAllocationTargets(userId, isaAccountId, [{ allocation_tag: " UK Equity ", target_percent: 100 }]);
+
+    const personal = getAllocationTargets(userId, null);
+    expect(personal.length).toBe(1);
+    expect(personal[0].label).toBe("UK Share");
+    expect(personal[0].target_percent).toBe(40);
+
+    const account = getAllocationTargets(userId, isaAccountId);
+    expect(account.length).toBe(1);
+    expect(account[0].allocation_tag).toBe("UK Equity");
+  });
+
+  test("replaces the whole set and clears it with an empty list", function () {
+    setAllocationTargets(userId, isaAccountId, []);
+    expect(getAllocationTargets(userId, isaAccountId)).toEqual([]);
+    expect(getAllocationTargets(userId, null).length).toBe(1);
+  });
+});
+
+describe("Rebalancing Service - buildRebalancePlan", function () {
+  test("sells the overweight holding in the ISA and buys the underweight one with the proceeds", function () {
+    setAllocationTargets(userId, null, [
+      { investment_id: shareId, target_percent: 40 },
+      { investment_id: trustId, target_percent: 50 },
+    ]);
+    const plan = buildRebalancePlan(userId, null, OPTIONS);
+
+    expect(plan.basis).toBe("investment");
+    expect(plan.total).toBe(10000);
+    expect(plan.rows.map((r) => [r.label, r.current_percent, r.drift_percent, r.in_band])).toEqual([
+      ["Global Trust", 30, -20, false],
+      ["UK Share", 60, 20, false],
+    ]);
+    expect(plan.trades).toEqual([
+      { action: "sell", account_id: isaAccountId, account_type: "isa", account_ref: "I1", investment_id: shareId, description: "UK Share", amount: 2000, quantity: 200, cgt_exposed: false, estimated_gain: null },
+      { action: "buy", account_id: isaAccountId, account_type: "isa", account_ref: "I1", investment_id: trustId, description: "Global Trust", amount: 2000, quantity: 200, cgt_exposed: false, estimated_gain: null },
+    ]);
+    expect(plan.notes).toEqual([]);
+  });
+
+  test("proposes nothing when every target is inside the tolerance band", function () {
+    setAllocationTargets(userId, null, [
+      { investment_id: shareId, target_percent: 57 },
+      { investment_id: trustId, target_percent: 33 },
+    ]);
+    const plan = buildRebalancePlan(userId, null, OPTIONS);
+    expect(plan.rows.every((r) => r.in_band)).toBe(true);
+    expect(plan.trades).toEqual([]);
+  });
+
+  test("skips trades smaller than the minimum trade size", function () {
+    const plan = buildRebalancePlan(userId, null, { tolerance_percent: 0, minimum_trade: 500 });
+    expect(plan.rows.some((r) => !r.in_band)).toBe(true);
+    expect(plan.trades).toEqual([]);
+  });
+
+  test("flags sells in a trading account with the estimated gain", function () {
+    setAllocationTargets(userId, tradingAccountId, [{ investment_id: trustId, target_percent: 50 }]);
+    const plan = buildRebalancePlan(userId, tradingAccountId, OPTIONS);
+
+    expect(plan.total).toBe(3000);
+    expect(plan.trades.length).toBe(1);
+    expect(plan.trades[0].action).toBe("sell");
+    expect(plan.trades[0].amount).toBe(1500);
+    expect(plan.trades[0].cgt_exposed).toBe(true);
+    expect(plan.trades[0].estimated_gain).toBe(750);
+    expect(plan.notes.length).toBe(1);
+  });
+
+  test("keeps the account's minimum cash and reports what cannot be funded", function () {
+    setAllocationTargets(userId, null, [
+      { investment_id: shareId, target_percent: 60 },
+      { investment_id: trustId, target_percent: 40 },
+    ]);
+    const plan = buildRebalancePlan(userId, null, OPTIONS);
+
+    expect(plan.trades).toEqual([
+      { action: "buy", account_id: isaAccountId, account_type: "isa", account_ref: "I1", investment_id: trustId, description: "Global Trust", amount: 500, quantity: 50, cgt_exposed: false, estimated_gain: null },
+    ]);
+    const trustRow = plan.rows.find((r) => r.investment_id === trustId);
+    expect(trustRow.unfunded).toBe(500);
+    expect(plan.notes[0]).toContain("Not enough cash");
+  });
+
+  test("rebalances by allocation tag through the largest holding with the tag", function () {
+    tagInvestment(shareId, "UK Equity");
+    tagInvestment(trustId, "Global Equity");
+    setAllocationTargets(userId, null, [
+      { allocation_tag: "UK Equity", target_percent: 50 },
+      { allocation_tag: "Global Equity", target_percent: 40 },
+    ]);
+    const plan = buildRebalancePlan(userId, null, OPTIONS);
+
+    expect(plan.basis).toBe("tag");
+    expect(plan.trades.map((t) => [t.action, t.account_type, t.description, t.amount])).toEqual([
+      ["sell", "isa", "UK Share", 1000],
+      ["buy", "isa", "Global Trust", 1000],
+    ]);
+  });
+
+  test("notes a tag target with nothing held to buy", function () {
+    setAllocationTargets(userId, null, [
+      { allocation_tag: "UK Equity", target_percent: 60 },
+      { allocation_tag: "Bonds", target_percent: 10 },
+    ]);
+    const plan = buildRebalancePlan(userId, null, OPTIONS);
+    const bondsRow = plan.rows.find((r) => r.allocation_tag === "Bonds");
+    expect(bondsRow.unfunded).toBe(1000);
+    expect(plan.notes[0]).toContain("No holding is tagged Bonds");
+  });
+
+  test("returns null for an unknown user or account", function () {
+    expect(buildRebalancePlan(99999, null, OPTIONS)).toBeNull();
+    expect(buildRebalancePlan(userId, 99999, OPTIONS)).toBeNull();
+  });
+});
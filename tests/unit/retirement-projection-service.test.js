@@ -0,0 +1,192 @@
+// Set isolated DB path BEFORE importing connection.js (which reads it at module load)
+process.env.DB_PATH = "data/portfolio_60_test/test-retirement-projection-service.db";
+
+import { describe, test, expect, beforeAll, afterAll } from "bun:test";
+import { existsSync, unlinkSync } from "node:fs";
+import { createDatabase, closeDatabase, getDatabasePath } from "../../src/server/db/connection.js";
+import { createUser } from "../../src/server/db/users-db.js";
+import { getAllCurrencies } from "../../src/server/db/currencies-db.js";
+import { getAllInvestmentTypes } from "../../src/server/db/investment-types-db.js";
+import { createInvestment } from "../../src/server/db/investments-db.js";
+import { createAccount } from "../../src/server/db/accounts-db.js";
+import { createHolding } from "../../src/server/db/holdings-db.js";
+import { upsertPrice } from "../../src/server/db/prices-db.js";
+import { createDrawdownSchedule } from "../../src/server/db/drawdown-schedules-db.js";
+import { createOtherAsset } from "../../src/server/db/other-assets-db.js";
+import { projectRetirement, projectRetirementForUsers, estimateReturnAndVolatility } from "../../src/server/services/retirement-projection-service.js";
+import { validateUser } from "../../src/server/validation.js";
+
+const testDbPath = getDatabasePath();
+
+/**
+ * @description Clean up the isolated test database files only.
+ */
+function cleanupDatabase() {
+  closeDatabase();
+  for (const suffix of ["", "-wal", "-shm"]) {
+    const filePath = testDbPath + suffix;
+    if (existsSync(filePath)) {
+      unlinkSync(filePath);
+    }
+  }
+}
+
+/** @type {Object} Retiree with a cash-only SIPP paying £1,000 a month and a DB pension */
+let retiree;
+/** @type {Object} Saver with a SIPP invested in a fund with two years of prices and no drawdowns */
+let saver;
+/** @type {Object} Family member with an ISA but no SIPP */
+let isaOnly;
+
+/** @description Date the projections are made from */
+const PROJECTION_DATE = "2026-10-15";
+
+beforeAll(() => {
+  cleanupDatabase();
+  createDatabase();
+
+  const gbpId = getAllCurrencies().find((c) => c.code === "GBP").id;
+  const mutualTypeId = getAllInvestmentTypes().find((t) => t.short_description === "MUTUAL").id;
+  const fund = createInvestment({ currencies_id: gbpId, investment_type_id: mutualTypeId, description: "Global Growth Fund", public_id: "", investment_url: "", selector: "" });
+
+  // Two years of weekly prices to today, rising with some week-to-week movement
+  const today = new Date();
+  for (let week = 104; week >= 0; week--) {
+    const d = new Date(today);
+    d.setDate(d.getDate() - week * 7);
+    const step = 104 - week;
+    const price = 100 * Math.pow(1.0015, step) * (step % 2 === 0 ? 1.01 : 0.99);
+    upsertPrice(fund.id, d.toISOString().slice(0, 10), "17:00:00", Math.round(price * 10000) / 10000);
+  }
+
+  retiree = createUser({ initials: "PP", first_name: "Pat", last_name: "Pensioner", provider: "ii", date_of_birth: "1960-04-15" });
+  const retireeSipp = createAccount({ user_id: retiree.id, account_type: "sipp", account_ref: "PP-SIPP", cash_balance: 100000, warn_cash: 0 });
+  createDrawdownSchedule({ account_id: retireeSipp.id, frequency: "monthly", trigger_day: 15, from_date: "2026-01-01", to_date: "2030-12-01", amount: 1000 });
+  // A schedule that has ended is not counted
+  createDrawdownSchedule({ account_id: retireeSipp.id, frequency: "annually", trigger_day: 1, from_date: "2024-04-01", to_date: "2025-04-01", amount: 5000 });
+
+  // Defined benefit pension of £500 a month, and a property that is not income
+  createOtherAsset({ user_id: retiree.id, description: "Final Salary Pension", category: "pension", value_type: "recurring", frequency: "monthly", value: 5000000, notes: null, executor_reference: null });
+  createOtherAsset({ user_id: retiree.id, description: "Holiday Cottage", category: "property", value_type: "value", frequency: null, value: 2500000000, notes: null, executor_reference: null });
+
+  saver = createUser({ initials: "SS", first_name: "Sam", last_name: "Saver", provider: "ii" });
+  const saverSipp = createAccount({ user_id: saver.id, account_type: "sipp", account_ref: "SS-SIPP", cash_balance: 0, warn_cash: 0 });
+  createHolding({ account_id: saverSipp.id, investment_id: fund.id, quantity: 1000, average_cost: 1 });
+
+  isaOnly = createUser({ initials: "IO", first_name: "Isa", last_name: "Only", provider: "ii" });
+  createAccount({ user_id: isaOnly.id, account_type: "isa", account_ref: "IO-ISA", cash_balance: 1000, warn_cash: 0 });
+});
+
+afterAll(() => {
+  cleanupDatabase();
+  delete process.env.DB_PATH;
+});
+
+describe("Retirement Projection - starting position", function () {
+  test("counts current drawdown schedules and annualises recurring other assets", function () {
+    const projection = projectRetirement(retiree.id, { expected_return: 0, volatility: 0, today: PROJECTION_DATE });
+    expect(projection.sipp_value).toBe(100000);
+    expect(projection.annual_drawdown).toBe(12000);
+    expect(projection.recurring_income.map((r) => [r.description, r.annual_amount])).toEqual([["Final Salary Pension", 6000]]);
+    expect(projection.annual_recurring_income).toBe(6000);
+    expect(projection.current_age).toBe(66.5);
+    expect(projection.return_source).toBe("override");
+  });
+
+  test("returns null for a user that does not exist", function () {
+    expect(projectRetirement(9999, { today: PROJECTION_DATE })).toBeNull();
+  });
+});
+
+describe("Retirement Projection - depletion", function () {
+  test("runs out after the pot divided by the drawdown when nothing is earned", function () {
+    const projection = projectRetirement(retiree.id, { years: 20, simulations: 100, expected_return: 0, volatility: 0, today: PROJECTION_DATE });
+    expect(projection.deterministic).toEqual({ years_lasted: 8.3, age: 74.8 });
+    expect(projection.success_rate).toBe(0);
+    for (const row of projection.depletion) {
+      expect(row.years_lasted).toBe(8.3);
+      expect(row.age).toBe(74.8);
+    }
+    expect(projection.bands.length).toBe(20);
+    expect(projection.bands[0]).toMatchObject({ year: 1, age: 67.5, p50: 88000, deterministic: 88000, median_drawdown: 12000, recurring_income: 6000 });
+    expect(projection.bands[8].p50).toBe(0);
+    expect(projection.bands[8].median_drawdown).toBe(4000);
+  });
+
+  test("gives ordered bands and depletion ages across simulations", function () {
+    const projection = projectRetirement(retiree.id, { years: 30, simulations: 500, expected_return: 5, volatility: 15, today: PROJECTION_DATE });
+    for (const band of projection.bands) {
+      expect(band.p10).toBeLessThanOrEqual(band.p25);
+      expect(band.p25).toBeLessThanOrEqual(band.p50);
+      expect(band.p50).toBeLessThanOrEqual(band.p75);
+      expect(band.p75).toBeLessThanOrEqual(band.p90);
+    }
+    const p10 = projection.depletion.find((d) => d.percentile === 10);
+    const p50 = projection.depletion.find((d) => d.percentile === 50);
+    expect(p10.years_lasted).toBeLessThanOrEqual(p50.years_lasted);
+    expect(p50.years_lasted).toBeGreaterThan(8.3);
+    expect(Math.abs(p50.years_lasted - projection.deterministic.years_lasted)).toBeLessThan(2);
+  });
+
+  test("repeats exactly with the same seed", function () {
+    const first = projectRetirement(retiree.id, { years: 25, simulations: 200, expected_return: 4, volatility: 12, seed: 7, today: PROJECTION_DATE });
+    const second = projectRetirement(retiree.id, { years: 25, simulations: 200, expected_return: 4, volatility: 12, seed: 7, today: PROJECTION_DATE });
+    expect(second.depletion).toEqual(first.depletion);
+    expect(second.bands).toEqual(first.bands);
+  });
+
+  test("reports years rather than ages without a date of birth, and never runs out without drawdowns", function () {
+    const projection = projectRetirement(saver.id, { years: 10, simulations: 100, expected_return: 5, volatility: 10, today: PROJECTION_DATE });
+    expect(projection.current_age).toBeNull();
+    expect(projection.bands[0].age).toBeNull();
+    expect(projection.success_rate).toBe(100);
+    expect(projection.deterministic).toEqual({ years_lasted: null, age: null });
+    expect(projection.depletion[0]).toEqual({ percentile: 10, years_lasted: null, age: null });
+  });
+});
+
+describe("Retirement Projection - return and volatility", function () {
+  test("estimates return and volatility from the price history of SIPP holdings", function () {
+    const projection = projectRetirement(saver.id, { years: 5, simulations: 100, today: PROJECTION_DATE });
+    expect(projection.return_source).toBe("history");
+    expect(projection.expected_return).toBeGreaterThan(5);
+    expect(projection.expected_return).toBeLessThan(10);
+    expect(projection.volatility).toBeGreaterThan(10);
+  });
+
+  test("uses the configured defaults for holdings without history, and nothing for cash", function () {
+    const config = { historyPeriod: "3y", defaultReturn: 5, defaultVolatility: 12 };
+    const estimate = estimateReturnAndVolatility([{ investment_id: 9999, value_gbp: 1000 }], 1000, config);
+    expect(estimate).toEqual({ expected_return: 2.5, volatility: 6, history_weight: 0 });
+  });
+});
+
+describe("Retirement Projection - household", function () {
+  test("projects the household together, without ages", function () {
+    const projection = projectRetirement(null, { years: 10, simulations: 100, expected_return: 0, volatility: 0, today: PROJECTION_DATE });
+    expect(projection.user).toBeNull();
+    expect(projection.current_age).toBeNull();
+    expect(projection.accounts.length).toBe(2);
+    expect(projection.annual_drawdown).toBe(12000);
+  });
+
+  test("projects each family member with a SIPP", function () {
+    const projections = projectRetirementForUsers(null, { years: 5, simulations: 100, today: PROJECTION_DATE });
+    expect(projections.map((p) => p.user.initials).sort()).toEqual(["PP", "SS"]);
+    expect(projectRetirementForUsers([isaOnly.id], { years: 5, simulations: 100 })).toEqual([]);
+  });
+});
+
+describe("Retirement Projection - date of birth validation", function () {
+  test("accepts a past date of birth or none", function () {
+    expect(validateUser({ initials: "PP", first_name: "Pat", last_name: "Pensioner", provider: "ii", date_of_birth: "1960-04-15" })).toEqual([]);
+    expect(validateUser({ initials: "PP", first_name: "Pat", last_name: "Pensioner", provider: "ii", date_of_birth: "" })).toEqual([]);
+  });
+
+  test("rejects badly formed and future dates of birth", function () {
+    expect(validateUser({ initials: "PP", first_name: "Pat", last_name: "Pensioner", provider: "ii", date_of_birth: "15/04/1960" })).toEqual([
+      "Date of birth must be a valid date in YYYY-MM-DD format",
+    ]);
+    expect(validateUser({ initials: "PP", first_name: "Pat", last_name: "Pensioner", provider: "ii", date_of_birth: "2999-01-01" })).toEqual(["Date of birth cannot be in the future"]);
+  });
+});
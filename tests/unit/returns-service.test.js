@@ -0,0 +1,175 @@
+// Set isolated DB path BEFORE importing connection.js (which reads it at module load)
+process.env.DB_PATH = "data/portfolio_60_test/test-returns-service.db";
+
+import { describe, test, expect, beforeAll, afterAll } from "bun:test";
+import { existsSync, unlinkSync } from "node:fs";
+import { createDatabase, closeDatabase, getDatabasePath } from "../../src/server/db/connection.js";
+import { createUser } from "../../src/server/db/users-db.js";
+import { createAccount } from "../../src/server/db/accounts-db.js";
+import { createInvestment } from "../../src/server/db/investments-db.js";
+import { getAllInvestmentTypes } from "../../src/server/db/investment-types-db.js";
+import { getAllCurrencies } from "../../src/server/db/currencies-db.js";
+import { createHolding } from "../../src/server/db/holdings-db.js";
+import { createBuyMovement } from "../../src/server/db/holding-movements-db.js";
+import { createCashTransaction } from "../../src/server/db/cash-transactions-db.js";
+import { upsertPrice } from "../../src/server/db/prices-db.js";
+import { calculateXirr, calculateTwr, getPortfolioReturns } from "../../src/server/services/returns-service.js";
+
+const testDbPath = getDatabasePath();
+
+/**
+ * @description Clean up the isolated test database files only.
+ */
+function cleanupDatabase() {
+  closeDatabase();
+  for (const suffix of ["", "-wal", "-shm"]) {
+    const filePath = testDbPath + suffix;
+    if (existsSync(filePath)) {
+      unlinkSync(filePath);
+    }
+  }
+}
+
+beforeAll(() => {
+  cleanupDatabase();
+  createDatabase();
+
+  const user = createUser({
+    initials: "RT",
+    first_name: "Returns",
+    last_name: "Tester",
+    provider: "ii",
+  });
+
+  const isa = createAccount({
+    user_id: user.id,
+    account_type: "isa",
+    account_ref: "R1001",
+    cash_balance: 0,
+    warn_cash: 0,
+  });
+
+  const types = getAllInvestmentTypes();
+  const gbp = getAllCurrencies().find((c) => c.code === "GBP");
+  const fund = createInvestment({
+    currencies_id: gbp.id,
+    investment_type_id: types[0].id,
+    description: "Steady Growth Fund",
+  });
+  const holding = createHolding({ account_id: isa.id, investment_id: fund.id, quantity: 0, average_cost: 0 });
+
+  // Prices are in pence: £10.00, then +10% and +10% again
+  upsertPrice(fund.id, "2024-05-02", "16:00:00", 1000);
+  upsertPrice(fund.id, "2024-12-01", "16:00:00", 1100);
+  upsertPrice(fund.id, "2025-06-01", "16:00:00", 1210);
+
+  // £10,000 invested before the 1 year window opens, £5,500 added part way through
+  createCashTransaction({ account_id: isa.id, transaction_type: "deposit", transaction_date: "2024-05-01", amount: 10000 });
+  createBuyMovement({ holding_id: holding.id, movement_date: "2024-05-02", quantity: 1000, total_consideration: 10000 });
+  createCashTransaction({ account_id: isa.id, transaction_type: "deposit", transaction_date: "2024-12-01", amount: 5500 });
+  createBuyMovement({ holding_id: holding.id, movement_date: "2024-12-01", quantity: 500, total_consideration: 5500 });
+});
+
+afterAll(() => {
+  cleanupDatabase();
+  delete process.env.DB_PATH;
+});
+
+describe("Returns Service - calculateXirr", function () {
+  test("returns the annual rate for a single investment held for a year", function () {
+    const rate = calculateXirr([
+      { date: "2023-01-01", amount: -1000 },
+      { date: "2024-01-01", amount: 1100 },
+    ]);
+    expect(rate).toBeCloseTo(0.1, 6);
+  });
+
+  test("weights returns by the timing of each flow", function () {
+    const rate = calculateXirr([
+      { date: "2023-01-01", amount: -1000 },
+      { date: "2023-07-02", amount: -1000 },
+      { date: "2024-01-01", amount: 2100 },
+    ]);
+    // More money was invested for the shorter half, so the rate exceeds 5%
+    expect(rate).toBeGreaterThan(0.06);
+    expect(rate).toBeLessThan(0.07);
+  });
+
+  test("handles losses", function () {
+    const rate = calculateXirr([
+      { date: "2023-01-01", amount: -1000 },
+      { date: "2024-01-01", amount: 800 },
+    ]);
+    expect(rate).toBeCloseTo(-0.2, 6);
+  });
+
+  test("returns null when flows do not change sign", function () {
+    expect(calculateXirr([{ date: "2023-01-01", amount: 1000 }])).toBeNull();
+    expect(calculateXirr([{ date: "2023-01-01", amount: -1000 }, { date: "2024-01-01", amount: -5 }])).toBeNull();
+  });
+});
+
+describe("Returns Service - calculateTwr", function () {
+  test("chains sub-period returns and ignores the size of flows", function () {
+    // +10%, then a £5,500 deposit, then +10% again
+    const twr = calculateTwr(10000, [
+      { value: 16500, flow: 5500 },
+      { value: 18150, flow: 0 },
+    ]);
+    expect(twr).toBeCloseTo(0.21, 10);
+  });
+
+  test("skips sub-periods that start from nothing", function () {
+    const twr = calculateTwr(0, [
+      { value: 1000, flow: 1000 },
+      { value: 1050, flow: 0 },
+    ]);
+    expect(twr).toBeCloseTo(0.05, 10);
+  });
+
+  test("returns null when nothing was ever invested", function () {
+    expect(calculateTwr(0, [{ value: 0, flow: 0 }])).toBeNull();
+  });
+});
+
+describe("Returns Service - getPortfolioReturns", function () {
+  test("returns null for an unknown user or invalid param", function () {
+    expect(getPortfolioReturns("ZZ:isa", ["1y"], "2025-06-01")).toBeNull();
+    expect(getPortfolioReturns("RT", ["1y"], "2025-06-01")).toBeNull();
+  });
+
+  test("calculates XIRR and TWR from external flows and valuations", function () {
+    const returns = getPortfolioReturns("RT:isa", ["1y"], "2025-06-01");
+    expect(returns.label).toBe("Returns Tester (ISA)");
+    expect(returns.accounts.length).toBe(1);
+
+    const year = returns.periods[0];
+    expect(year.start_date).toBe("2024-06-01");
+    expect(year.start_value).toBe(10000);
+    expect(year.end_value).toBe(18150);
+    expect(year.deposits).toBe(5500);
+    expect(year.withdrawals).toBe(0);
+    expect(year.gain).toBe(2650);
+    expect(year.flows.length).toBe(1);
+
+    expect(year.twr).toBe(21);
+    expect(year.twr_annualised).toBe(21);
+    expect(year.xirr).toBeGreaterThan(20);
+    expect(year.xirr).toBeLessThan(22);
+  });
+
+  test("ignores unknown period codes and does not annualise short periods", function () {
+    const returns = getPortfolioReturns("RT:isa", ["6m", "7w"], "2025-06-01");
+    expect(returns.periods.length).toBe(1);
+    expect(returns.periods[0].code).toBe("6m");
+    expect(returns.periods[0].twr).toBe(10);
+    expect(returns.periods[0].twr_annualised).toBeNull();
+  });
+
+  test("excludes account types not in the param", function () {
+    const returns = getPortfolioReturns("RT:sipp", ["1y"], "2025-06-01");
+    expect(returns.accounts.length).toBe(0);
+    expect(returns.periods[0].end_value).toBe(0);
+    expect(returns.periods[0].twr).toBeNull();
+  });
+});
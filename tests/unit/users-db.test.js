@@ -52,6 +52,7 @@ describe("Users DB - createUser", () => {
       trading_ref: "TR001",
       isa_ref: "ISA001",
       sipp_ref: "SIPP001",
+      date_of_birth: "1958-03-21",
     });
 
     expect(user).not.toBeNull();
@@ -64,6 +65,7 @@ describe("Users DB - createUser", () => {
     expect(user.trading_ref).toBe("TR001");
     expect(user.isa_ref).toBe("ISA001");
     expect(user.sipp_ref).toBe("SIPP001");
+    expect(user.date_of_birth).toBe("1958-03-21");
   });
 
   test("creates a user with optional fields as null", () => {
@@ -80,6 +82,7 @@ describe("Users DB - createUser", () => {
     expect(user.trading_ref).toBeNull();
     expect(user.isa_ref).toBeNull();
     expect(user.sipp_ref).toBeNull();
+    expect(user.date_of_birth).toBeNull();
   });
 });
 
@@ -145,6 +148,24 @@ describe("Users DB - updateUser", () => {
   });
 });
 
+describe("Users DB - spouse", () => {
+  test("links both users, moves the link on a change and clears it on delete", () => {
+    const tom = createUser({ initials: "TT", first_name: "Tom", last_name: "Taylor", provider: "ii" });
+    const tina = createUser({ initials: "TU", first_name: "Tina", last_name: "Turner", provider: "ii", spouse_user_id: tom.id });
+    expect(tina.spouse_user_id).toBe(tom.id);
+    expect(getUserById(tom.id).spouse_user_id).toBe(tina.id);
+
+    const smith = getAllUsers().find((u) => u.last_name === "Smith");
+    expect(updateUser(smith.id, { ...smith, spouse_user_id: tom.id }).spouse_user_id).toBe(tom.id);
+    expect(getUserById(tom.id).spouse_user_id).toBe(smith.id);
+    expect(getUserById(tina.id).spouse_user_id).toBeNull();
+
+    deleteUser(tom.id);
+    expect(getUserById(smith.id).spouse_user_id).toBeNull();
+    deleteUser(tina.id);
+  });
+});
+
 describe("Users DB - deleteUser", () => {
   test("deletes a user and returns true", () => {
     const users = getAllUsers();